@@ -0,0 +1,539 @@
+//! Diagnostic bundle export.
+//!
+//! Collects what support needs to debug an installation into a single zip:
+//! - Recent log files
+//! - Configuration values
+//! - Rule and profile rule snapshots
+//! - CA certificate fingerprint
+//! - ML model presence
+//! - Recent decision previews
+//!
+//! Everything is scrubbed before it is written. Full prompts are never
+//! included (decisions only carry the stored short preview), and emails,
+//! phone numbers, long numbers, secrets, and long quoted strings are
+//! redacted, so the bundle is safe to share.
+//!
+//! # Usage
+//!
+//! ```no_run
+//! use aegis_app::diagnostics::{DiagnosticBundle, DiagnosticPaths};
+//! use aegis_storage::Database;
+//!
+//! let db = Database::new().expect("Failed to open database");
+//! let paths = DiagnosticPaths::default_paths().expect("No data directory");
+//! let summary = DiagnosticBundle::new(db, paths)
+//!     .write("bundle.zip".as_ref())
+//!     .expect("Failed to write bundle");
+//! println!("Wrote {} entries", summary.entries.len());
+//! ```
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use aegis_core::data_dir::project_data_dir;
+use aegis_core::model_downloader::{get_ml_status, ModelDownloader};
+use aegis_proxy::CaManager;
+use aegis_storage::Database;
+use regex::Regex;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Name of the manifest listing every entry in the bundle.
+pub const MANIFEST_ENTRY: &str = "manifest.json";
+
+/// Bundle entries, in the order they are written (after the manifest).
+pub const BUNDLE_ENTRIES: &[&str] = &[
+    "system.json",
+    "config.json",
+    "rules.json",
+    "ca.json",
+    "models.json",
+    "decisions.json",
+];
+
+/// Directory inside the bundle that holds log files.
+pub const LOGS_PREFIX: &str = "logs/";
+
+/// Maximum number of log files included.
+pub const MAX_LOG_FILES: usize = 5;
+
+/// Maximum bytes kept from the end of each log file.
+pub const MAX_LOG_BYTES: u64 = 256 * 1024;
+
+/// Maximum number of recent decisions included.
+pub const MAX_DECISIONS: i64 = 100;
+
+/// Lines longer than this are truncated.
+const MAX_LINE_LEN: usize = 1000;
+
+/// Quoted strings at least this long are assumed to be prompt text.
+const MAX_QUOTED_LEN: usize = 60;
+
+/// Config keys whose values are never exported.
+const SECRET_KEY_MARKERS: &[&str] = &["password", "secret", "token"];
+
+static EMAIL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap());
+static PHONE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\+?\(?\b\d{3}\)?[\s.-]\d{3}[\s.-]\d{4}\b").unwrap());
+static LONG_NUMBER_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\d{7,}\b").unwrap());
+static LONG_QUOTED_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(&format!(r#""[^"]{{{},}}""#, MAX_QUOTED_LEN)).unwrap());
+
+/// Errors that can occur while writing a diagnostic bundle.
+#[derive(Debug, Error)]
+pub enum DiagnosticsError {
+    /// Failed to get data directories.
+    #[error("failed to get data directories")]
+    DirectoryError,
+
+    /// Failed to read from the database.
+    #[error("storage error: {0}")]
+    Storage(String),
+
+    /// Failed to write the zip archive.
+    #[error("failed to write bundle: {0}")]
+    Zip(String),
+
+    /// IO error.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<aegis_storage::StorageError> for DiagnosticsError {
+    fn from(e: aegis_storage::StorageError) -> Self {
+        Self::Storage(e.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for DiagnosticsError {
+    fn from(e: zip::result::ZipError) -> Self {
+        Self::Zip(e.to_string())
+    }
+}
+
+/// Result type for diagnostics operations.
+pub type Result<T> = std::result::Result<T, DiagnosticsError>;
+
+/// Locations diagnostics are collected from.
+#[derive(Debug, Clone)]
+pub struct DiagnosticPaths {
+    /// Directory holding the rotated log files.
+    pub logs_dir: PathBuf,
+    /// The CA certificate directory.
+    pub ca_dir: PathBuf,
+}
+
+impl DiagnosticPaths {
+    /// Get paths for the default Aegis installation.
+    pub fn default_paths() -> Option<Self> {
+        // The binary logs under ("", "aegis", "Aegis")
+        let log_dir = project_data_dir("", "aegis", "Aegis")?;
+        // Proxy uses "com.aegis.Aegis" (note capital A)
+        let proxy_dir = project_data_dir("com", "aegis", "Aegis")?;
+
+        Some(Self {
+            logs_dir: log_dir.join("logs"),
+            ca_dir: proxy_dir.join("ca"),
+        })
+    }
+}
+
+/// Summary of a written bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleSummary {
+    /// Path the bundle was written to.
+    pub path: PathBuf,
+    /// Entry names in the bundle, manifest first.
+    pub entries: Vec<String>,
+}
+
+/// Assembles a scrubbed diagnostic bundle.
+pub struct DiagnosticBundle {
+    db: Database,
+    paths: DiagnosticPaths,
+}
+
+impl DiagnosticBundle {
+    /// Create a bundle builder reading from `db` and `paths`.
+    pub fn new(db: Database, paths: DiagnosticPaths) -> Self {
+        Self { db, paths }
+    }
+
+    /// Writes the bundle as a zip archive at `output`.
+    pub fn write(&self, output: &Path) -> Result<BundleSummary> {
+        let mut files: Vec<(String, String)> = vec![
+            (BUNDLE_ENTRIES[0].to_string(), to_json(&system_info())),
+            (BUNDLE_ENTRIES[1].to_string(), to_json(&self.config()?)),
+            (BUNDLE_ENTRIES[2].to_string(), to_json(&self.rules()?)),
+            (BUNDLE_ENTRIES[3].to_string(), to_json(&self.ca_info())),
+            (BUNDLE_ENTRIES[4].to_string(), to_json(&model_info())),
+            (BUNDLE_ENTRIES[5].to_string(), to_json(&self.decisions()?)),
+        ];
+        files.extend(self.logs()?);
+
+        let mut entries = vec![MANIFEST_ENTRY.to_string()];
+        entries.extend(files.iter().map(|(name, _)| name.clone()));
+        let manifest = json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "created_at": chrono::Utc::now().to_rfc3339(),
+            "entries": entries,
+        });
+
+        let mut zip = ZipWriter::new(fs::File::create(output)?);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (name, contents) in
+            std::iter::once((MANIFEST_ENTRY.to_string(), to_json(&manifest))).chain(files)
+        {
+            zip.start_file(name, options)?;
+            zip.write_all(contents.as_bytes())?;
+        }
+        zip.finish()?;
+
+        Ok(BundleSummary {
+            path: output.to_path_buf(),
+            entries,
+        })
+    }
+
+    /// Configuration values, with secrets redacted.
+    fn config(&self) -> Result<Value> {
+        let config: serde_json::Map<String, Value> = self
+            .db
+            .get_all_config()?
+            .into_iter()
+            .map(|entry| {
+                let key = entry.key.to_lowercase();
+                let value = if SECRET_KEY_MARKERS.iter().any(|m| key.contains(m)) {
+                    Value::String("[redacted]".to_string())
+                } else {
+                    scrub_value(entry.value)
+                };
+                (entry.key, value)
+            })
+            .collect();
+        Ok(Value::Object(config))
+    }
+
+    /// Rule and profile rule snapshots.
+    ///
+    /// Profile names and OS usernames identify children, so profiles are
+    /// exported by id only.
+    fn rules(&self) -> Result<Value> {
+        let rules: Vec<Value> = self
+            .db
+            .get_all_rules()?
+            .into_iter()
+            .map(|rule| {
+                json!({
+                    "id": rule.id,
+                    "name": scrub(&rule.name),
+                    "enabled": rule.enabled,
+                    "priority": rule.priority,
+                    "config": scrub_value(rule.config),
+                })
+            })
+            .collect();
+
+        let profiles: Vec<Value> = self
+            .db
+            .get_all_profiles()?
+            .into_iter()
+            .map(|profile| {
+                let issues: Vec<Value> = profile
+                    .rule_issues()
+                    .into_iter()
+                    .map(|issue| json!({ "field": issue.field, "error": scrub(&issue.error) }))
+                    .collect();
+                json!({
+                    "id": profile.id,
+                    "enabled": profile.enabled,
+                    "has_os_username": profile.os_username.is_some(),
+                    "proxy_mode": profile.proxy_mode,
+                    "text_filtering_enabled": profile.text_filtering_enabled,
+                    "time_rules": scrub_value(profile.time_rules),
+                    "content_rules": scrub_value(profile.content_rules),
+                    "sentiment_config": profile.sentiment_config,
+                    "image_filtering_config": profile.image_filtering_config,
+                    "rule_issues": issues,
+                })
+            })
+            .collect();
+
+        Ok(json!({ "rules": rules, "profiles": profiles }))
+    }
+
+    /// CA certificate presence and SHA-256 fingerprint.
+    fn ca_info(&self) -> Value {
+        let ca = CaManager::new(&self.paths.ca_dir);
+        let fingerprint = ca.read_cert_der().ok().map(|der| fingerprint(&der));
+        json!({
+            "cert_present": ca.cert_path().exists(),
+            "key_present": ca.key_path().exists(),
+            "sha256_fingerprint": fingerprint,
+        })
+    }
+
+    /// Recent decisions, using the stored short preview only.
+    fn decisions(&self) -> Result<Value> {
+        let decisions: Vec<Value> = self
+            .db
+            .get_recent_events(MAX_DECISIONS, 0)?
+            .into_iter()
+            .map(|event| {
+                json!({
+                    "created_at": event.created_at.to_rfc3339(),
+                    "action": event.action,
+                    "category": event.category,
+                    "confidence": event.confidence,
+                    "source": event.source,
+                    "preview": scrub(&event.preview),
+                })
+            })
+            .collect();
+        Ok(Value::Array(decisions))
+    }
+
+    /// The most recent log files, tail-truncated and scrubbed.
+    fn logs(&self) -> Result<Vec<(String, String)>> {
+        let Ok(dir) = fs::read_dir(&self.paths.logs_dir) else {
+            return Ok(Vec::new());
+        };
+
+        let mut logs: Vec<(std::time::SystemTime, PathBuf)> = dir
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("aegis") && n.ends_with(".log"))
+            })
+            .filter_map(|path| Some((fs::metadata(&path).ok()?.modified().ok()?, path)))
+            .collect();
+        // Newest first
+        logs.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+        let mut files = Vec::new();
+        for (_, path) in logs.into_iter().take(MAX_LOG_FILES) {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            files.push((format!("{}{}", LOGS_PREFIX, name), read_log_tail(&path)?));
+        }
+        Ok(files)
+    }
+}
+
+/// Application and platform details.
+fn system_info() -> Value {
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+    })
+}
+
+/// ML runtime and model presence.
+fn model_info() -> Value {
+    let downloader = ModelDownloader::new();
+    json!({
+        "status": get_ml_status().description(),
+        "onnx_runtime_installed": downloader.as_ref().map(|d| d.is_onnx_runtime_installed()),
+        "nsfw_model_installed": downloader.as_ref().map(|d| d.is_nsfw_model_installed()),
+    })
+}
+
+/// Reads the last [`MAX_LOG_BYTES`] of a log file and scrubs each line.
+fn read_log_tail(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(MAX_LOG_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+
+    let mut lines: Vec<&str> = text.lines().collect();
+    // The first line is likely cut mid-way when we started past the beginning
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&scrub(line));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Formats a SHA-256 fingerprint as colon-separated uppercase hex.
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Redacts PII and likely prompt text from a line of text.
+pub fn scrub(text: &str) -> String {
+    let text = LONG_QUOTED_RE.replace_all(text, "\"[redacted]\"");
+    let text = EMAIL_RE.replace_all(&text, "[email]");
+    let text = PHONE_RE.replace_all(&text, "[number]");
+    let text = LONG_NUMBER_RE.replace_all(&text, "[number]");
+
+    if text.chars().count() > MAX_LINE_LEN {
+        let truncated: String = text.chars().take(MAX_LINE_LEN).collect();
+        format!("{}... [truncated]", truncated)
+    } else {
+        text.into_owned()
+    }
+}
+
+/// Scrubs every string inside a JSON value.
+fn scrub_value(value: Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(scrub(&s)),
+        Value::Array(items) => Value::Array(items.into_iter().map(scrub_value).collect()),
+        Value::Object(map) => {
+            Value::Object(map.into_iter().map(|(k, v)| (k, scrub_value(v))).collect())
+        }
+        other => other,
+    }
+}
+
+fn to_json(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_core::classifier::Category;
+    use aegis_storage::models::Action;
+    use tempfile::TempDir;
+    use zip::ZipArchive;
+
+    const FULL_PROMPT: &str = "My name is Jamie and my email is jamie@example.com, \
+        please write a detailed story about something my parents would never allow";
+
+    fn read_bundle(path: &Path) -> Vec<(String, String)> {
+        let mut archive = ZipArchive::new(fs::File::open(path).unwrap()).unwrap();
+        (0..archive.len())
+            .map(|i| {
+                let mut file = archive.by_index(i).unwrap();
+                let mut contents = String::new();
+                file.read_to_string(&mut contents).unwrap();
+                (file.name().to_string(), contents)
+            })
+            .collect()
+    }
+
+    // ==================== Scrub Tests ====================
+
+    #[test]
+    fn test_scrub_redacts_pii() {
+        assert_eq!(scrub("contact kid@example.com"), "contact [email]");
+        assert_eq!(scrub("call 555-123-4567 now"), "call [number] now");
+        assert_eq!(scrub("card 4111111111111111"), "card [number]");
+        assert_eq!(
+            scrub("2026-10-14T12:00:00 INFO proxy on 127.0.0.1:8766"),
+            "2026-10-14T12:00:00 INFO proxy on 127.0.0.1:8766"
+        );
+    }
+
+    #[test]
+    fn test_scrub_redacts_long_quoted_text() {
+        let line = format!("body {{\"content\":\"{}\"}}", "word ".repeat(20));
+        assert_eq!(scrub(&line), "body {\"content\":\"[redacted]\"}");
+    }
+
+    // ==================== Bundle Tests ====================
+
+    #[test]
+    fn test_bundle_contains_manifest_entries_and_no_prompts() {
+        let db = Database::in_memory().unwrap();
+        db.log_event(
+            FULL_PROMPT,
+            Some(Category::Adult),
+            Some(0.9),
+            Action::Blocked,
+            Some("chatgpt.com".to_string()),
+        )
+        .unwrap();
+        db.set_config("protection_level", &json!("standard"))
+            .unwrap();
+        db.set_config("api_token", &json!("abc123")).unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let logs_dir = temp_dir.path().join("logs");
+        fs::create_dir_all(&logs_dir).unwrap();
+        fs::write(
+            logs_dir.join("aegis.2026-10-14.log"),
+            format!("INFO Blocked prompt {{\"prompt\":\"{}\"}}\n", FULL_PROMPT),
+        )
+        .unwrap();
+
+        let paths = DiagnosticPaths {
+            logs_dir,
+            ca_dir: temp_dir.path().join("ca"),
+        };
+        let output = temp_dir.path().join("bundle.zip");
+        let summary = DiagnosticBundle::new(db, paths).write(&output).unwrap();
+
+        let files = read_bundle(&output);
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names[0], MANIFEST_ENTRY);
+        for entry in BUNDLE_ENTRIES {
+            assert!(names.contains(entry), "missing {}", entry);
+        }
+        assert!(names.contains(&"logs/aegis.2026-10-14.log"));
+        assert_eq!(summary.entries, names);
+
+        let manifest: Value = serde_json::from_str(&files[0].1).unwrap();
+        assert_eq!(manifest["entries"].as_array().unwrap().len(), names.len());
+
+        for (name, contents) in &files {
+            assert!(!contents.contains(FULL_PROMPT), "{} has the prompt", name);
+            assert!(!contents.contains("jamie@example.com"), "{} has PII", name);
+            assert!(!contents.contains("abc123"), "{} has a secret", name);
+        }
+
+        let (_, decisions) = files.iter().find(|(n, _)| n == "decisions.json").unwrap();
+        let decisions: Value = serde_json::from_str(decisions).unwrap();
+        assert_eq!(decisions[0]["source"], "chatgpt.com");
+        assert!(decisions[0]["preview"]
+            .as_str()
+            .unwrap()
+            .starts_with("My name is Jamie"));
+    }
+
+    #[test]
+    fn test_bundle_without_logs_or_ca() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths = DiagnosticPaths {
+            logs_dir: temp_dir.path().join("missing"),
+            ca_dir: temp_dir.path().join("ca"),
+        };
+        let output = temp_dir.path().join("bundle.zip");
+
+        let summary = DiagnosticBundle::new(Database::in_memory().unwrap(), paths)
+            .write(&output)
+            .unwrap();
+        assert_eq!(summary.entries.len(), BUNDLE_ENTRIES.len() + 1);
+
+        let files = read_bundle(&output);
+        let (_, ca) = files.iter().find(|(n, _)| n == "ca.json").unwrap();
+        let ca: Value = serde_json::from_str(ca).unwrap();
+        assert_eq!(ca["cert_present"], false);
+        assert!(ca["sha256_fingerprint"].is_null());
+    }
+}
@@ -0,0 +1,271 @@
+//! Local IPC between Aegis instances.
+//!
+//! When a second Aegis is launched while one is running (see
+//! [`single_instance`](crate::single_instance)), it asks the running instance
+//! to open or focus its dashboard instead of starting its own. The running
+//! instance listens on a loopback TCP port and writes that port to
+//! `aegis.ipc` in the data directory; the second instance reads it, sends a
+//! one-line command, and waits for `ok`.
+//!
+//! # Usage
+//!
+//! ```no_run
+//! use aegis_app::ipc::{self, IpcCommand, IpcServer};
+//!
+//! // Running instance
+//! let path = ipc::default_port_path().unwrap();
+//! let (server, commands) = IpcServer::start(&path).unwrap();
+//! if let Ok(IpcCommand::OpenDashboard) = commands.try_recv() {
+//!     // open the dashboard
+//! }
+//!
+//! // Second instance
+//! ipc::send_command(&path, IpcCommand::OpenDashboard).unwrap();
+//! ```
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use aegis_core::data_dir::project_data_dir;
+use thiserror::Error;
+
+/// Port file name inside the data directory.
+pub const PORT_FILE_NAME: &str = "aegis.ipc";
+
+/// How long a client waits to connect to or hear back from the running instance.
+pub const IPC_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Reply sent for an accepted command.
+const REPLY_OK: &str = "ok";
+
+/// Longest command line read from a client.
+const MAX_LINE_LENGTH: u64 = 256;
+
+/// Errors from IPC.
+#[derive(Debug, Error)]
+pub enum IpcError {
+    /// No running instance has published a port.
+    #[error("no running instance found")]
+    NotRunning,
+
+    /// The running instance rejected or didn't understand the command.
+    #[error("running instance replied: {0}")]
+    Rejected(String),
+
+    /// Socket or port file error.
+    #[error("IPC error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Result type for IPC operations.
+pub type Result<T> = std::result::Result<T, IpcError>;
+
+/// A request from another Aegis process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpcCommand {
+    /// Open the dashboard, or bring it forward if already open.
+    OpenDashboard,
+}
+
+impl IpcCommand {
+    /// Returns the command as sent on the wire.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IpcCommand::OpenDashboard => "open-dashboard",
+        }
+    }
+
+    /// Parses a command line (surrounding whitespace ignored).
+    pub fn parse(line: &str) -> Option<Self> {
+        match line.trim() {
+            "open-dashboard" => Some(IpcCommand::OpenDashboard),
+            _ => None,
+        }
+    }
+}
+
+/// Listens for commands from other Aegis processes.
+///
+/// Commands arrive on the [`Receiver`] returned by [`IpcServer::start`].
+/// Dropping the server removes its port file; the listener thread exits
+/// with the process.
+#[derive(Debug)]
+pub struct IpcServer {
+    addr: SocketAddr,
+    port_path: PathBuf,
+}
+
+impl IpcServer {
+    /// Binds a loopback port, publishes it at `port_path`, and starts
+    /// accepting commands on a background thread.
+    pub fn start(port_path: &Path) -> Result<(Self, Receiver<IpcCommand>)> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let addr = listener.local_addr()?;
+
+        if let Some(dir) = port_path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(port_path, addr.port().to_string())?;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("aegis-ipc".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => {
+                            if let Err(e) = handle_connection(stream, &tx) {
+                                tracing::debug!("IPC connection failed: {}", e);
+                            }
+                        }
+                        Err(e) => tracing::debug!("IPC accept failed: {}", e),
+                    }
+                }
+            })?;
+
+        Ok((
+            Self {
+                addr,
+                port_path: port_path.to_path_buf(),
+            },
+            rx,
+        ))
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        // Only remove the file if it still points at us
+        let ours = std::fs::read_to_string(&self.port_path)
+            .is_ok_and(|port| port.trim() == self.addr.port().to_string());
+        if ours {
+            let _ = std::fs::remove_file(&self.port_path);
+        }
+    }
+}
+
+/// Reads one command from `stream`, forwards it, and replies.
+fn handle_connection(stream: TcpStream, commands: &Sender<IpcCommand>) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(IPC_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream)
+        .take(MAX_LINE_LENGTH)
+        .read_line(&mut line)?;
+
+    let reply = match IpcCommand::parse(&line) {
+        Some(command) => {
+            tracing::info!("IPC command from another instance: {:?}", command);
+            // A closed receiver means we're shutting down; the command is moot
+            let _ = commands.send(command);
+            REPLY_OK.to_string()
+        }
+        None => format!("unknown command '{}'", line.trim()),
+    };
+
+    let mut stream = stream;
+    writeln!(stream, "{}", reply)
+}
+
+/// Sends `command` to the instance that published `port_path`.
+pub fn send_command(port_path: &Path, command: IpcCommand) -> Result<()> {
+    let port: u16 = match std::fs::read_to_string(port_path) {
+        Ok(contents) => contents.trim().parse().map_err(|_| IpcError::NotRunning)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(IpcError::NotRunning),
+        Err(e) => return Err(e.into()),
+    };
+
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let mut stream = TcpStream::connect_timeout(&addr, IPC_TIMEOUT)?;
+    stream.set_read_timeout(Some(IPC_TIMEOUT))?;
+    writeln!(stream, "{}", command.as_str())?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .take(MAX_LINE_LENGTH)
+        .read_line(&mut reply)?;
+    match reply.trim() {
+        REPLY_OK => Ok(()),
+        other => Err(IpcError::Rejected(other.to_string())),
+    }
+}
+
+/// Returns the port file path in the Aegis data directory.
+pub fn default_port_path() -> Option<PathBuf> {
+    project_data_dir("", "aegis", "Aegis").map(|dir| dir.join(PORT_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn open_dashboard_command_reaches_running_instance() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(PORT_FILE_NAME);
+        let (_server, commands) = IpcServer::start(&path).unwrap();
+
+        send_command(&path, IpcCommand::OpenDashboard).unwrap();
+
+        assert_eq!(
+            commands.recv_timeout(IPC_TIMEOUT),
+            Ok(IpcCommand::OpenDashboard)
+        );
+    }
+
+    #[test]
+    fn unknown_commands_are_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(PORT_FILE_NAME);
+        let (server, commands) = IpcServer::start(&path).unwrap();
+
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(b"format-disk\n").unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+
+        assert_eq!(reply.trim(), "unknown command 'format-disk'");
+        assert!(commands.try_recv().is_err());
+    }
+
+    #[test]
+    fn send_without_running_instance_fails() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(PORT_FILE_NAME);
+
+        let err = send_command(&path, IpcCommand::OpenDashboard).unwrap_err();
+        assert!(matches!(err, IpcError::NotRunning));
+    }
+
+    #[test]
+    fn dropping_server_removes_port_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(PORT_FILE_NAME);
+
+        let (server, _commands) = IpcServer::start(&path).unwrap();
+        assert!(path.exists());
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn command_parse_roundtrip() {
+        assert_eq!(
+            IpcCommand::parse(" open-dashboard\r\n"),
+            Some(IpcCommand::OpenDashboard)
+        );
+        assert_eq!(
+            IpcCommand::parse(IpcCommand::OpenDashboard.as_str()),
+            Some(IpcCommand::OpenDashboard)
+        );
+        assert_eq!(IpcCommand::parse("open"), None);
+    }
+}
@@ -5,6 +5,8 @@
 //! - Clean uninstall support (F020)
 //! - Autostart/persistence support (F030)
 //! - Auto-update support (F023)
+//! - Diagnostic bundle export
+//! - Single-instance lock and IPC between instances
 //!
 //! # Usage
 //!
@@ -34,10 +36,15 @@
 //! ```
 
 pub mod autostart;
+pub mod diagnostics;
+pub mod ipc;
+pub mod single_instance;
 pub mod uninstall;
 pub mod updater;
 
 pub use autostart::{Autostart, AutostartError};
+pub use diagnostics::{BundleSummary, DiagnosticBundle, DiagnosticPaths, DiagnosticsError};
+pub use single_instance::{InstanceLock, InstanceLockError};
 pub use uninstall::{UninstallManager, UninstallOptions, UninstallPaths, UninstallResult};
 pub use updater::{
     create_update_manager, DownloadProgress, UpdateError, UpdateInfo, UpdateManager,
@@ -12,25 +12,37 @@
 use std::panic;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
 use std::sync::Arc;
+use std::time::Duration;
 
-use aegis_core::classifier::{SentimentConfig, SentimentFlag};
+use aegis_app::diagnostics::{DiagnosticBundle, DiagnosticPaths, DiagnosticsError};
+use aegis_app::ipc::{self, IpcCommand, IpcServer};
+use aegis_app::single_instance::{InstanceLock, InstanceLockError};
+use aegis_core::classifier::TieredClassifier;
+use aegis_core::community_rules::CommunityRuleManager;
 use aegis_core::content_rules::ContentRuleSet;
-use aegis_core::profile::{ProfileManager, ProxyMode, UserProfile};
+use aegis_core::data_dir::{data_dir_override, ensure_writable, project_data_dir, DATA_DIR_ENV};
+use aegis_core::extension_token::ExtensionToken;
+use aegis_core::keyword_import::{KeywordFormat, KeywordImport};
+use aegis_core::profile::{ProfileManager, UserProfile};
 use aegis_core::profile_proxy::{ProfileProxyConfig, ProfileProxyController, ProxyAction};
 use aegis_core::protection::ProtectionManager;
 use aegis_core::rule_engine::RuleEngine;
+use aegis_core::simulation;
 use aegis_core::time_rules::TimeRuleSet;
-use aegis_proxy::{FilteringState, ProxyConfig, ProxyServer};
+use aegis_proxy::{
+    AllowLogging, CanaryResult, FilteringState, ProxyConfig, ProxyServer, ALLOW_LOGGING_CONFIG_KEY,
+    DEFAULT_CANARY_INTERVAL,
+};
 use aegis_server::{AppState as ServerAppState, Server, ServerConfig};
-use aegis_storage::models::ProfileSentimentConfig;
 use aegis_storage::Database;
 use aegis_tray::{MenuAction, SystemTray, TrayConfig, TrayEvent, TrayStatus};
 use aegis_ui::run_dashboard_with_filtering;
-use clap::Parser;
-use directories::ProjectDirs;
+use clap::{Parser, Subcommand};
 use muda::MenuEvent;
-use std::collections::HashSet;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
 use tracing_appender::rolling::{RollingFileAppender, Rotation};
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 use tray_icon::TrayIconEvent;
@@ -40,11 +52,6 @@ use windows_sys::Win32::UI::WindowsAndMessaging::{
     DispatchMessageW, PeekMessageW, TranslateMessage, MSG, PM_REMOVE,
 };
 
-#[cfg(target_os = "windows")]
-use windows_sys::Win32::Foundation::{GetLastError, ERROR_ALREADY_EXISTS, HANDLE};
-#[cfg(target_os = "windows")]
-use windows_sys::Win32::System::Threading::CreateMutexW;
-
 /// Aegis - AI safety platform for filtering LLM interactions
 #[derive(Parser, Debug)]
 #[command(name = "aegis", version, about)]
@@ -72,59 +79,162 @@ struct Args {
     /// Run dashboard only (internal use - spawned from main process)
     #[arg(long, hide = true)]
     dashboard_only: bool,
+
+    /// Keep the database, logs, CA, and models under this directory
+    /// (same as setting AEGIS_DATA_DIR)
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-/// Get the logs directory path.
-fn logs_dir() -> Option<PathBuf> {
-    ProjectDirs::from("", "aegis", "Aegis").map(|dirs| dirs.data_dir().join("logs"))
+/// One-shot commands that run instead of the app.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Export a redacted diagnostic bundle for support
+    Diagnostics {
+        /// Path of the zip file to write
+        #[arg(long, default_value = "aegis-diagnostics.zip")]
+        output: PathBuf,
+    },
+    /// Manage the keyword blocklist
+    Keywords {
+        #[command(subcommand)]
+        command: KeywordsCommand,
+    },
+    /// Replay a JSONL sample of prompts against the current rules and report the outcomes
+    Simulate {
+        /// JSONL file of prompts (strings or {"prompt", "timestamp"} objects)
+        #[arg(long)]
+        input: PathBuf,
+        /// Local time for prompts without a timestamp, e.g. "2024-05-06 21:30" (default: now)
+        #[arg(long)]
+        at: Option<String>,
+    },
 }
 
-/// Single-instance guard that holds a named mutex on Windows.
-/// When dropped, the mutex is released.
-#[cfg(target_os = "windows")]
-struct SingleInstanceGuard {
-    _handle: HANDLE,
+/// Keyword blocklist commands.
+#[derive(Subcommand, Debug)]
+enum KeywordsCommand {
+    /// Import terms from a CSV or JSON file (columns: term,category,action,threshold)
+    Import {
+        /// Path of the CSV or JSON file
+        file: PathBuf,
+    },
 }
 
-#[cfg(target_os = "windows")]
-impl SingleInstanceGuard {
-    /// Tries to acquire a single-instance lock.
-    /// Returns Ok(guard) if this is the first instance.
-    /// Returns Err if another instance is already running.
-    fn try_acquire() -> Result<Self, ()> {
-        // Create a named mutex. The name must be unique to this application.
-        // Using a wide string (UTF-16) for Windows API.
-        let mutex_name: Vec<u16> = "Global\\AegisAppSingleInstance\0".encode_utf16().collect();
-
-        let handle = unsafe { CreateMutexW(std::ptr::null(), 0, mutex_name.as_ptr()) };
-
-        if handle.is_null() {
-            // Failed to create mutex
-            return Err(());
-        }
+/// Writes a diagnostic bundle and prints where it went.
+fn run_diagnostics(output: &std::path::Path) -> anyhow::Result<()> {
+    let db = Database::new().map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    let paths = DiagnosticPaths::default_paths().ok_or(DiagnosticsError::DirectoryError)?;
+    let summary = DiagnosticBundle::new(db, paths).write(output)?;
+
+    println!(
+        "Wrote diagnostic bundle to {} ({} entries)",
+        summary.path.display(),
+        summary.entries.len()
+    );
+    Ok(())
+}
 
-        // Check if the mutex already existed
-        let last_error = unsafe { GetLastError() };
-        if last_error == ERROR_ALREADY_EXISTS {
-            // Another instance is already running
-            return Err(());
-        }
+/// Imports a keyword list into the stored parent overrides.
+fn run_keyword_import(file: &std::path::Path) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(file)?;
+    let format = KeywordFormat::from_path(file).unwrap_or_else(|| KeywordFormat::detect(&content));
+    let import = KeywordImport::parse(&content, format)?;
+    if import.is_empty() {
+        anyhow::bail!("{} has no terms", file.display());
+    }
+
+    let db = Database::new().map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    let (summary, _) = db
+        .import_keywords(&import)
+        .map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+
+    println!(
+        "Imported {}: {} blocked, {} allowed, {} unchanged, {} duplicate(s) skipped",
+        file.display(),
+        summary.blocked,
+        summary.allowed,
+        summary.unchanged,
+        summary.duplicates
+    );
+    println!("Changes apply the next time Aegis starts.");
+    Ok(())
+}
+
+/// Replays a prompt sample through classification and the current rules.
+fn run_simulation(input: &std::path::Path, at: Option<&str>) -> anyhow::Result<()> {
+    let content = std::fs::read_to_string(input)?;
+    let samples = simulation::parse_samples(&content)?;
+    if samples.is_empty() {
+        anyhow::bail!("{} has no prompts", input.display());
+    }
+    let default_time = match at {
+        Some(value) => simulation::parse_timestamp(value)
+            .ok_or_else(|| anyhow::anyhow!("Unrecognized time '{}'", value))?,
+        None => chrono::Local::now().naive_local(),
+    };
 
-        Ok(Self { _handle: handle })
+    // Same rules and keyword lists the proxy starts with
+    let db = Database::new().map_err(|e| anyhow::anyhow!("Database error: {}", e))?;
+    let engine = load_initial_rules(&db);
+    let mut classifier = TieredClassifier::with_defaults();
+    if let Some(rules) = classifier.community_rules() {
+        apply_parent_overrides(&db, rules);
     }
+    apply_custom_keywords(&db, &mut classifier);
+
+    let report = simulation::simulate(&mut classifier, &engine, &samples, default_time);
+    println!(
+        "Simulated {} prompt(s) from {}",
+        samples.len(),
+        input.display()
+    );
+    println!("{}", report);
+    Ok(())
 }
 
-/// No-op on non-Windows platforms (single-instance not implemented yet).
-#[cfg(not(target_os = "windows"))]
-struct SingleInstanceGuard;
+/// Applies the stored parent blacklist/whitelist to a community rule manager.
+fn apply_parent_overrides(db: &Database, rules: &std::sync::RwLock<CommunityRuleManager>) {
+    match db.parent_overrides() {
+        Ok(overrides) => rules.write().unwrap().set_overrides(overrides),
+        Err(e) => tracing::warn!("Failed to load parent overrides: {}", e),
+    }
+}
 
-#[cfg(not(target_os = "windows"))]
-impl SingleInstanceGuard {
-    fn try_acquire() -> Result<Self, ()> {
-        Ok(Self)
+/// Loads the stored custom keyword list into a classifier.
+///
+/// An invalid list is logged and ignored, leaving the built-in keywords.
+fn apply_custom_keywords(db: &Database, classifier: &mut TieredClassifier) {
+    match db.custom_keywords() {
+        Ok(Some(keywords)) => match classifier.load_custom_keywords(&keywords) {
+            Ok(count) => tracing::info!("Loaded {} custom keyword(s)", count),
+            Err(e) => tracing::error!("Ignoring invalid custom keyword list: {}", e),
+        },
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to load custom keywords: {}", e),
     }
 }
 
+/// Returns the tray status given the latest interception canary result.
+///
+/// A failed canary turns "Protected" into "Error"; a passing one clears it.
+/// Paused stays paused, since canaries are skipped while filtering is off.
+fn tray_status_for_canary(current: TrayStatus, canary: Option<&CanaryResult>) -> TrayStatus {
+    match (current, canary.map(|result| result.status.is_intercepted())) {
+        (TrayStatus::Protected, Some(false)) => TrayStatus::Error,
+        (TrayStatus::Error, Some(true)) => TrayStatus::Protected,
+        _ => current,
+    }
+}
+
+/// Get the logs directory path.
+fn logs_dir() -> Option<PathBuf> {
+    project_data_dir("", "aegis", "Aegis").map(|dir| dir.join("logs"))
+}
+
 /// Initialize logging with file rotation.
 fn init_logging(args: &Args) -> Option<tracing_appender::non_blocking::WorkerGuard> {
     let log_level = if args.debug { "debug" } else { &args.log_level };
@@ -223,24 +333,18 @@ fn load_profiles_from_db(db: &Database) -> ProfileManager {
     match db.get_all_profiles() {
         Ok(profiles) => {
             for profile in profiles {
-                // Convert storage Profile to core UserProfile
-                let time_rules: TimeRuleSet = serde_json::from_value(profile.time_rules.clone())
+                // Convert storage Profile to core UserProfile (invalid rules
+                // are reported by the startup preflight)
+                let time_rules = profile
+                    .parse_time_rules()
                     .unwrap_or_else(|_| TimeRuleSet::new());
-                let content_rules: ContentRuleSet =
-                    serde_json::from_value(profile.content_rules.clone())
-                        .ok()
-                        .filter(|r: &ContentRuleSet| !r.rules.is_empty())
-                        .unwrap_or_else(ContentRuleSet::family_safe_defaults);
-
-                // Determine proxy mode based on profile name heuristic
-                // Profiles with "parent" in the name disable filtering; others enable it
-                // This is because profiles in the database are typically child profiles
-                let is_parent = profile.name.to_lowercase().contains("parent");
-                let proxy_mode = if is_parent {
-                    ProxyMode::Disabled
-                } else {
-                    ProxyMode::Enabled
-                };
+                let content_rules = profile
+                    .parse_content_rules()
+                    .ok()
+                    .filter(|r| !r.rules.is_empty())
+                    .unwrap_or_else(ContentRuleSet::family_safe_defaults);
+
+                let proxy_mode = profile.proxy_mode;
 
                 let mut user_profile = UserProfile::new(
                     format!("profile_{}", profile.id),
@@ -270,14 +374,51 @@ fn load_profiles_from_db(db: &Database) -> ProfileManager {
     manager
 }
 
+/// How long quitting waits for in-flight API requests to finish.
+const API_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The background API server task and the signal that stops it.
+struct ApiServerHandle {
+    shutdown: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl ApiServerHandle {
+    /// Stops accepting connections and waits for in-flight requests,
+    /// up to [`API_SHUTDOWN_TIMEOUT`].
+    async fn shutdown(self) {
+        let _ = self.shutdown.send(());
+        match tokio::time::timeout(API_SHUTDOWN_TIMEOUT, self.task).await {
+            Ok(_) => tracing::info!("API server stopped"),
+            Err(_) => tracing::warn!(
+                "API server did not stop within {:?}, abandoning it",
+                API_SHUTDOWN_TIMEOUT
+            ),
+        }
+    }
+}
+
 /// Start the background servers (API and Proxy) with profile-aware filtering.
-/// Returns the shared FilteringState for use by the UI.
-async fn start_servers(db: Database) -> FilteringState {
+/// Returns the shared FilteringState for use by the UI, and a handle for
+/// stopping the API server on quit.
+async fn start_servers(db: Database) -> (FilteringState, ApiServerHandle) {
     let server_db = db.clone();
     let profile_db = db.clone();
     let rules_db = db.clone();
     let proxy_db = Arc::new(db.clone());
 
+    // Report profiles whose stored rules are corrupt before loading them
+    match profile_db.preflight_profiles() {
+        Ok(issues) if !issues.is_empty() => {
+            tracing::error!(
+                "{} profile rule issue(s) found; affected profiles will use default rules",
+                issues.len()
+            );
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Profile preflight failed: {}", e),
+    }
+
     // Load profiles and create profile-aware filtering
     let profiles = load_profiles_from_db(&profile_db);
     let protection = ProtectionManager::new();
@@ -289,18 +430,59 @@ async fn start_servers(db: Database) -> FilteringState {
     let filtering_state =
         FilteringState::with_rule_engine_and_cache(initial_rule_engine, proxy_db.clone());
 
+    // Build the proxy config up front so the API server can share its
+    // community rules, with the stored parent blacklist/whitelist and custom
+    // keyword list applied
+    let proxy_config = ProxyConfig::with_filtering_state(filtering_state.clone());
+    let community_rules = proxy_config
+        .as_ref()
+        .ok()
+        .and_then(|config| config.classifier.read().community_rules().cloned());
+    if let Some(ref rules) = community_rules {
+        apply_parent_overrides(&rules_db, rules);
+    }
+    if let Ok(ref config) = proxy_config {
+        apply_custom_keywords(&rules_db, &mut config.classifier.write());
+    }
+    // Sites added through the API go straight into the proxy's registry
+    let site_registry = proxy_config
+        .as_ref()
+        .ok()
+        .map(|config| config.site_registry.clone());
+
     // Start HTTP API server in background (for browser extension)
     // Pass the FilteringState so the reload endpoint can update it
     let server_config = ServerConfig::default();
     let server_addr = format!("{}:{}", server_config.host, server_config.port);
     let server_filtering_state = filtering_state.clone();
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
 
-    tokio::spawn(async move {
+    let api_task = tokio::spawn(async move {
         tracing::info!("Starting API server on {}", server_addr);
-        let app_state = ServerAppState::with_filtering_state(server_db, server_filtering_state);
+        // /api/check matches the same custom keywords as the proxy
+        let mut app_state = ServerAppState::with_filtering_state(server_db, server_filtering_state)
+            .with_stored_custom_keywords();
+        if let Some(rules) = community_rules {
+            app_state = app_state.with_community_rules(rules);
+        }
+        if let Some(registry) = site_registry {
+            app_state = app_state.with_site_registry(registry);
+        }
+        // The extension gets the same token from its discovery file at install
+        match ExtensionToken::load_or_create_default() {
+            Ok(token) => app_state = app_state.with_extension_token(token),
+            Err(e) => tracing::error!(
+                "Failed to load extension token, extension endpoints are unauthenticated: {}",
+                e
+            ),
+        }
         match Server::with_state(ServerConfig::default(), app_state) {
             Ok(server) => {
-                if let Err(e) = server.run().await {
+                // A dropped sender also stops the server
+                let shutdown = async move {
+                    let _ = shutdown_rx.await;
+                };
+                if let Err(e) = server.run_with_shutdown(shutdown).await {
                     tracing::error!("API server error: {}", e);
                 }
             }
@@ -372,17 +554,27 @@ async fn start_servers(db: Database) -> FilteringState {
         }
     });
 
-    // Clone filtering_state for proxy and for return
-    let proxy_filtering_state = filtering_state.clone();
+    // Whether allowed prompts are stored, and how loudly they are logged
+    let allow_logging = proxy_db
+        .get_config_or_default(ALLOW_LOGGING_CONFIG_KEY, AllowLogging::default())
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to load allow logging config: {}", e);
+            AllowLogging::default()
+        });
+
+    // Clone filtering_state for return
     let return_filtering_state = filtering_state.clone();
 
     // Start MITM proxy server in background (for system-wide protection)
     // Use the shared filtering state so ProfileProxyController can control filtering
     // Also pass the database for event logging (live stats)
     tokio::spawn(async move {
-        match ProxyConfig::with_filtering_state(proxy_filtering_state) {
+        match proxy_config {
             Ok(config) => {
-                let config = config.with_database(proxy_db);
+                let config = config
+                    .with_database(proxy_db)
+                    .with_allow_logging(allow_logging)
+                    .with_canary(DEFAULT_CANARY_INTERVAL);
                 let proxy_addr = config.addr;
                 match ProxyServer::new(config) {
                     Ok(proxy) => {
@@ -408,7 +600,11 @@ async fn start_servers(db: Database) -> FilteringState {
     // Give servers a moment to start
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
 
-    return_filtering_state
+    let api_server = ApiServerHandle {
+        shutdown: shutdown_tx,
+        task: api_task,
+    };
+    (return_filtering_state, api_server)
 }
 
 /// Loads the initial rule engine from the first enabled profile.
@@ -417,13 +613,12 @@ fn load_initial_rules(db: &Database) -> RuleEngine {
         Ok(profiles) => {
             // Find first enabled profile
             if let Some(profile) = profiles.iter().find(|p| p.enabled) {
-                let time_rules: TimeRuleSet =
-                    serde_json::from_value(profile.time_rules.clone()).unwrap_or_default();
-                let content_rules: ContentRuleSet =
-                    serde_json::from_value(profile.content_rules.clone())
-                        .ok()
-                        .filter(|r: &ContentRuleSet| !r.rules.is_empty())
-                        .unwrap_or_else(ContentRuleSet::family_safe_defaults);
+                let time_rules = profile.parse_time_rules().unwrap_or_default();
+                let content_rules = profile
+                    .parse_content_rules()
+                    .ok()
+                    .filter(|r| !r.rules.is_empty())
+                    .unwrap_or_else(ContentRuleSet::family_safe_defaults);
 
                 tracing::info!(
                     "Loaded initial rules from profile '{}': {} time rules, {} content rules",
@@ -448,82 +643,13 @@ fn load_initial_rules(db: &Database) -> RuleEngine {
 }
 
 /// Loads rules for a specific profile by name and updates the filtering state.
-/// Also sets the profile ID and enables sentiment analysis based on the profile's config.
+/// Also sets the profile ID and applies the profile's text, sentiment, and
+/// image filtering toggles.
 fn load_profile_rules_by_name(db: &Database, profile_name: &str, filtering_state: &FilteringState) {
-    match db.get_all_profiles() {
-        Ok(profiles) => {
-            if let Some(profile) = profiles.iter().find(|p| p.name == profile_name) {
-                let time_rules: TimeRuleSet =
-                    serde_json::from_value(profile.time_rules.clone()).unwrap_or_default();
-                let content_rules: ContentRuleSet =
-                    serde_json::from_value(profile.content_rules.clone())
-                        .ok()
-                        .filter(|r: &ContentRuleSet| !r.rules.is_empty())
-                        .unwrap_or_else(ContentRuleSet::family_safe_defaults);
-
-                tracing::info!(
-                    "Loading rules for profile '{}' (id={}): {} time rules, {} content rules",
-                    profile.name,
-                    profile.id,
-                    time_rules.rules.len(),
-                    content_rules.rules.len()
-                );
-
-                // Set profile ID for sentiment flagging
-                filtering_state.set_profile_with_id(Some(profile.name.clone()), Some(profile.id));
-
-                // Update rules
-                filtering_state.update_rules(time_rules, content_rules);
-
-                // Configure sentiment analysis based on profile settings
-                configure_sentiment_analysis(filtering_state, &profile.sentiment_config);
-            } else {
-                tracing::warn!("Profile '{}' not found in database", profile_name);
-            }
-        }
-        Err(e) => {
-            tracing::warn!("Failed to load profile '{}' rules: {}", profile_name, e);
-        }
-    }
-}
-
-/// Configure sentiment analysis based on profile settings.
-fn configure_sentiment_analysis(filtering_state: &FilteringState, config: &ProfileSentimentConfig) {
-    if config.enabled {
-        // Build enabled flags set from the profile config
-        let mut enabled_flags = HashSet::new();
-        if config.detect_distress {
-            enabled_flags.insert(SentimentFlag::Distress);
-        }
-        if config.detect_crisis {
-            enabled_flags.insert(SentimentFlag::CrisisIndicator);
-        }
-        if config.detect_bullying {
-            enabled_flags.insert(SentimentFlag::Bullying);
-        }
-        if config.detect_negative {
-            enabled_flags.insert(SentimentFlag::NegativeSentiment);
-        }
-
-        let sentiment_config = SentimentConfig {
-            enabled: true,
-            threshold: config.sensitivity,
-            enabled_flags,
-            notify_on_flag: true,
-        };
-
-        filtering_state.enable_sentiment_analysis(sentiment_config);
-        tracing::info!(
-            "Sentiment analysis enabled (sensitivity={}, distress={}, crisis={}, bullying={}, negative={})",
-            config.sensitivity,
-            config.detect_distress,
-            config.detect_crisis,
-            config.detect_bullying,
-            config.detect_negative
-        );
-    } else {
-        filtering_state.disable_sentiment_analysis();
-        tracing::info!("Sentiment analysis disabled for profile");
+    match filtering_state.switch_to_profile(db, profile_name) {
+        Ok(true) => {}
+        Ok(false) => tracing::warn!("Profile '{}' not found in database", profile_name),
+        Err(e) => tracing::warn!("Failed to load profile '{}' rules: {}", profile_name, e),
     }
 }
 
@@ -546,12 +672,21 @@ fn spawn_dashboard_process() -> anyhow::Result<std::process::Child> {
     Ok(child)
 }
 
+/// Asks the running instance to open its dashboard.
+fn signal_running_instance() -> anyhow::Result<()> {
+    let path =
+        ipc::default_port_path().ok_or_else(|| anyhow::anyhow!("data directory not found"))?;
+    ipc::send_command(&path, IpcCommand::OpenDashboard)?;
+    Ok(())
+}
+
 /// Run the application with tray icon as primary interface.
 #[allow(unused_assignments)]
 fn run_with_tray(
-    _db: Database,
+    db: Database,
     show_dashboard: bool,
     filtering_state: FilteringState,
+    ipc_commands: Option<Receiver<IpcCommand>>,
 ) -> anyhow::Result<()> {
     // Set up panic hook to log panics to file
     let default_hook = panic::take_hook();
@@ -571,8 +706,16 @@ fn run_with_tray(
 
     // Create and initialize the system tray once
     tracing::debug!("Creating system tray...");
-    let (mut tray, tray_rx) =
-        SystemTray::with_config(TrayConfig::new().with_initial_status(tray_status))?;
+    // Enabled profiles can be switched to manually from the tray
+    let profile_names = db
+        .get_enabled_profiles()
+        .map(|profiles| profiles.into_iter().map(|p| p.name).collect())
+        .unwrap_or_default();
+    let (mut tray, tray_rx) = SystemTray::with_config(
+        TrayConfig::new()
+            .with_initial_status(tray_status)
+            .with_profiles(profile_names),
+    )?;
 
     tracing::debug!("Initializing system tray...");
     tray.init()?;
@@ -667,6 +810,15 @@ fn run_with_tray(
                             tray_status = TrayStatus::Protected;
                             let _ = tray.set_status(tray_status);
                         }
+                        MenuAction::BlockAll => {
+                            // Cleared only through the authenticated dashboard API
+                            tracing::warn!("Blocking all AI access from tray");
+                            filtering_state.block_all();
+                        }
+                        MenuAction::SwitchProfile(name) => {
+                            tracing::info!("Switching to profile '{}' from tray", name);
+                            load_profile_rules_by_name(&db, &name, &filtering_state);
+                        }
                         MenuAction::Quit => {
                             tracing::info!("Quit requested from tray");
                             running.store(false, Ordering::SeqCst);
@@ -694,6 +846,14 @@ fn run_with_tray(
             }
         }
 
+        // Flag the tray when the interception canary stops getting through
+        let canary_status =
+            tray_status_for_canary(tray_status, filtering_state.live_stats().canary().as_ref());
+        if canary_status != tray_status {
+            tray_status = canary_status;
+            let _ = tray.set_status(tray_status);
+        }
+
         // Check for events from channel
         while let Ok(event) = tray_rx.try_recv() {
             if let TrayEvent::MenuAction(MenuAction::Quit) = event {
@@ -701,6 +861,29 @@ fn run_with_tray(
             }
         }
 
+        // Check for commands from a second launch
+        if let Some(ref rx) = ipc_commands {
+            while let Ok(command) = rx.try_recv() {
+                match command {
+                    IpcCommand::OpenDashboard => {
+                        if dashboard_process.is_none() {
+                            tracing::info!("Opening dashboard (subprocess) for second launch...");
+                            match spawn_dashboard_process() {
+                                Ok(child) => {
+                                    dashboard_process = Some(child);
+                                }
+                                Err(e) => {
+                                    tracing::error!("Failed to spawn dashboard: {}", e);
+                                }
+                            }
+                        } else {
+                            tracing::info!("Second launch ignored - dashboard already open");
+                        }
+                    }
+                }
+            }
+        }
+
         // Small sleep to prevent busy loop
         std::thread::sleep(std::time::Duration::from_millis(50));
     }
@@ -723,17 +906,50 @@ fn run_with_tray(
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
+    // Set the override for this process and any dashboard subprocess
+    if let Some(dir) = &args.data_dir {
+        std::env::set_var(DATA_DIR_ENV, dir);
+    }
+    if let Some(dir) = data_dir_override() {
+        if let Err(e) = ensure_writable(&dir) {
+            anyhow::bail!("Data directory {} is not writable: {}", dir.display(), e);
+        }
+    }
+
+    // One-shot commands run alongside a running instance and skip logging setup
+    match &args.command {
+        Some(Command::Diagnostics { output }) => return run_diagnostics(output),
+        Some(Command::Keywords {
+            command: KeywordsCommand::Import { file },
+        }) => return run_keyword_import(file),
+        Some(Command::Simulate { input, at }) => return run_simulation(input, at.as_deref()),
+        None => {}
+    }
+
     // Single-instance check (skip for dashboard subprocess)
     // Must be done before logging to avoid file conflicts
     let _instance_guard = if !args.dashboard_only {
-        match SingleInstanceGuard::try_acquire() {
-            Ok(guard) => Some(guard),
-            Err(()) => {
-                // Another instance is already running - exit silently
-                // On Windows, we can't easily bring the other window to focus,
-                // so we just exit. The user will see the existing tray icon.
+        match InstanceLock::acquire() {
+            Ok(lock) => Some(lock),
+            Err(InstanceLockError::AlreadyRunning { .. }) => {
+                // A manual launch asks the running instance to show its
+                // dashboard; autostart (--minimized) exits quietly.
+                if !args.minimized {
+                    if let Err(e) = signal_running_instance() {
+                        eprintln!(
+                            "Could not reach the running instance ({}); opening a dashboard",
+                            e
+                        );
+                        spawn_dashboard_process()?;
+                    }
+                }
                 return Ok(());
             }
+            Err(e) => {
+                // Logging isn't up yet; run unguarded rather than not at all
+                eprintln!("Warning: {}", e);
+                None
+            }
         }
     } else {
         None
@@ -786,7 +1002,7 @@ fn main() -> anyhow::Result<()> {
         .map_err(|e| anyhow::anyhow!("Failed to create tokio runtime: {}", e))?;
 
     // Start background servers and get the shared filtering state
-    let filtering_state = runtime.block_on(start_servers(db.clone()));
+    let (filtering_state, api_server) = runtime.block_on(start_servers(db.clone()));
 
     // Determine startup mode
     let first_run = is_first_run(&db);
@@ -814,9 +1030,19 @@ fn main() -> anyhow::Result<()> {
             show_dashboard,
             args.minimized
         );
-        run_with_tray(db, show_dashboard, filtering_state)?;
+        // Second launches signal this instance to open the dashboard
+        let listener = ipc::default_port_path().and_then(|path| {
+            IpcServer::start(&path)
+                .map_err(|e| tracing::warn!("Failed to start IPC listener: {}", e))
+                .ok()
+        });
+        let (_ipc_server, ipc_commands) = listener.unzip();
+        run_with_tray(db, show_dashboard, filtering_state, ipc_commands)?;
     }
 
+    // The dashboard closed or Quit was chosen from the tray; release the API port
+    runtime.block_on(api_server.shutdown());
+
     tracing::info!("Aegis shutting down");
     Ok(())
 }
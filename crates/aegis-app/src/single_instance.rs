@@ -0,0 +1,226 @@
+//! Single-instance lock.
+//!
+//! A second Aegis would fight the first over the server and proxy ports
+//! and could configure the system proxy twice. [`InstanceLock::acquire`]
+//! takes a machine-wide lock at startup: a named mutex on Windows, an
+//! exclusive lock on `aegis.lock` in the data directory elsewhere. The lock
+//! is released when the guard is dropped or the process exits, so a crash
+//! never leaves a stale lock behind.
+//!
+//! # Usage
+//!
+//! ```no_run
+//! use aegis_app::single_instance::{InstanceLock, InstanceLockError};
+//!
+//! let _lock = match InstanceLock::acquire() {
+//!     Ok(lock) => lock,
+//!     Err(InstanceLockError::AlreadyRunning { .. }) => return,
+//!     Err(e) => panic!("{}", e),
+//! };
+//! ```
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use aegis_core::data_dir::project_data_dir;
+use thiserror::Error;
+
+/// Lock file name inside the data directory.
+pub const LOCK_FILE_NAME: &str = "aegis.lock";
+
+/// Errors from acquiring the instance lock.
+#[derive(Debug, Error)]
+pub enum InstanceLockError {
+    /// Another Aegis instance holds the lock.
+    #[error("Aegis is already running{}", .pid.map(|p| format!(" (PID {})", p)).unwrap_or_default())]
+    AlreadyRunning {
+        /// Process ID of the running instance, if it could be read.
+        pid: Option<u32>,
+    },
+
+    /// Failed to get data directories.
+    #[error("failed to get data directories")]
+    DirectoryError,
+
+    /// The lock couldn't be created.
+    #[error("failed to create instance lock: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Result type for instance lock operations.
+pub type Result<T> = std::result::Result<T, InstanceLockError>;
+
+/// Held for as long as this process is the running Aegis instance.
+#[derive(Debug)]
+pub struct InstanceLock {
+    _inner: LockInner,
+}
+
+/// What holds the lock; released on drop.
+#[derive(Debug)]
+enum LockInner {
+    File {
+        _file: File,
+    },
+    #[cfg(target_os = "windows")]
+    Mutex {
+        _mutex: windows::NamedMutex,
+    },
+}
+
+impl InstanceLock {
+    /// Acquires the machine-wide lock for this user's Aegis.
+    ///
+    /// Uses a named mutex on Windows and [`default_lock_path`] elsewhere.
+    pub fn acquire() -> Result<Self> {
+        #[cfg(target_os = "windows")]
+        {
+            windows::NamedMutex::create("Global\\AegisAppSingleInstance").map(|mutex| Self {
+                _inner: LockInner::Mutex { _mutex: mutex },
+            })
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let path = default_lock_path().ok_or(InstanceLockError::DirectoryError)?;
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            Self::acquire_at(&path)
+        }
+    }
+
+    /// Acquires an exclusive lock on the file at `path`, creating it if
+    /// needed, and records this process's ID in it.
+    pub fn acquire_at(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Err(InstanceLockError::AlreadyRunning {
+                    pid: read_pid(&mut file),
+                });
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        // Only the holder writes, so a contending instance reads a whole PID
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        write!(file, "{}", std::process::id())?;
+        file.flush()?;
+
+        Ok(Self {
+            _inner: LockInner::File { _file: file },
+        })
+    }
+}
+
+/// Returns the lock file path in the Aegis data directory.
+pub fn default_lock_path() -> Option<PathBuf> {
+    project_data_dir("", "aegis", "Aegis").map(|dir| dir.join(LOCK_FILE_NAME))
+}
+
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HANDLE};
+    use windows_sys::Win32::System::Threading::CreateMutexW;
+
+    use super::{InstanceLockError, Result};
+
+    /// A named mutex, closed on drop.
+    #[derive(Debug)]
+    pub(super) struct NamedMutex(HANDLE);
+
+    impl NamedMutex {
+        /// Creates the mutex, failing if another process already created it.
+        pub(super) fn create(name: &str) -> Result<Self> {
+            let wide: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+
+            let handle = unsafe { CreateMutexW(std::ptr::null(), 0, wide.as_ptr()) };
+            if handle.is_null() {
+                return Err(std::io::Error::last_os_error().into());
+            }
+
+            if unsafe { GetLastError() } == ERROR_ALREADY_EXISTS {
+                unsafe { CloseHandle(handle) };
+                return Err(InstanceLockError::AlreadyRunning { pid: None });
+            }
+
+            Ok(Self(handle))
+        }
+    }
+
+    impl Drop for NamedMutex {
+        fn drop(&mut self) {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn second_acquire_is_refused_until_first_is_dropped() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(LOCK_FILE_NAME);
+
+        let first = InstanceLock::acquire_at(&path).unwrap();
+
+        let err = InstanceLock::acquire_at(&path).unwrap_err();
+        assert!(
+            matches!(err, InstanceLockError::AlreadyRunning { .. }),
+            "{:?}",
+            err
+        );
+        // Windows locks block reads, so the PID is only visible elsewhere
+        #[cfg(not(target_os = "windows"))]
+        assert!(
+            matches!(err, InstanceLockError::AlreadyRunning { pid: Some(pid) } if pid == std::process::id())
+        );
+
+        drop(first);
+        assert!(InstanceLock::acquire_at(&path).is_ok());
+    }
+
+    #[test]
+    fn stale_lock_file_does_not_block() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(LOCK_FILE_NAME);
+
+        // Left behind by an instance that crashed
+        std::fs::write(&path, "999999").unwrap();
+
+        let lock = InstanceLock::acquire_at(&path).unwrap();
+        drop(lock);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            std::process::id().to_string()
+        );
+    }
+
+    #[test]
+    fn already_running_message_includes_pid() {
+        let err = InstanceLockError::AlreadyRunning { pid: Some(42) };
+        assert_eq!(err.to_string(), "Aegis is already running (PID 42)");
+        let err = InstanceLockError::AlreadyRunning { pid: None };
+        assert_eq!(err.to_string(), "Aegis is already running");
+    }
+}
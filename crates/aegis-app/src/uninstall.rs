@@ -33,9 +33,9 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use aegis_core::auth::AuthManager;
+use aegis_core::data_dir::project_data_dir;
 use aegis_core::extension_install::uninstall_extension;
 use aegis_storage::Database;
-use directories::ProjectDirs;
 use thiserror::Error;
 
 use crate::autostart;
@@ -125,17 +125,17 @@ impl UninstallPaths {
     /// Get paths for the default Aegis installation.
     pub fn default_paths() -> Option<Self> {
         // Storage uses "com.aegis.aegis"
-        let storage_dirs = ProjectDirs::from("com", "aegis", "aegis")?;
+        let storage_dir = project_data_dir("com", "aegis", "aegis")?;
         // Proxy uses "com.aegis.Aegis" (note capital A)
-        let proxy_dirs = ProjectDirs::from("com", "aegis", "Aegis")?;
+        let proxy_dir = project_data_dir("com", "aegis", "Aegis")?;
 
-        let ca_dir = proxy_dirs.data_dir().join("ca");
+        let ca_dir = proxy_dir.join("ca");
 
         Some(Self {
-            data_dir: storage_dirs.data_dir().to_path_buf(),
+            database: storage_dir.join("aegis.db"),
+            data_dir: storage_dir,
             ca_cert: ca_dir.join("aegis-ca.crt"),
             ca_dir,
-            database: storage_dirs.data_dir().join("aegis.db"),
         })
     }
 }
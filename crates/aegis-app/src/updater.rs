@@ -1,7 +1,10 @@
 //! Auto-update functionality for Aegis.
 //!
 //! Checks GitHub Releases for new versions and handles downloading updates.
+//! Checks and downloads can be confined to a maintenance window, outside which
+//! they fail with [`UpdateError::Deferred`].
 
+use aegis_core::maintenance::{Clock, Deferred, MaintenanceSchedule, MaintenanceWindow};
 use chrono::{DateTime, Utc};
 use semver::Version;
 use serde::{Deserialize, Serialize};
@@ -39,6 +42,9 @@ pub enum UpdateError {
 
     #[error("Update check disabled")]
     Disabled,
+
+    #[error("Update {0}")]
+    Deferred(#[from] Deferred),
 }
 
 /// Result type for update operations.
@@ -108,6 +114,9 @@ pub struct UpdateSettings {
     pub last_check: Option<String>,
     /// Last known version that was dismissed.
     pub dismissed_version: Option<String>,
+    /// Window for checks and downloads (`None` = any time).
+    #[serde(default)]
+    pub maintenance_window: Option<MaintenanceWindow>,
 }
 
 impl Default for UpdateSettings {
@@ -121,6 +130,7 @@ impl Default for UpdateSettings {
             repo_name: DEFAULT_REPO_NAME.to_string(),
             last_check: None,
             dismissed_version: None,
+            maintenance_window: None,
         }
     }
 }
@@ -172,6 +182,7 @@ pub struct UpdateManager {
     current_version: Version,
     client: reqwest::Client,
     download_dir: PathBuf,
+    clock: Clock,
 }
 
 impl UpdateManager {
@@ -194,9 +205,22 @@ impl UpdateManager {
             current_version,
             client,
             download_dir,
+            clock: Clock::system(),
         })
     }
 
+    /// Sets the clock used to check the maintenance window.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the schedule for the configured maintenance window.
+    pub fn schedule(&self) -> MaintenanceSchedule {
+        let window = self.settings.read().unwrap().maintenance_window.clone();
+        MaintenanceSchedule::from_window(window).with_clock(self.clock.clone())
+    }
+
     /// Returns the current application version.
     pub fn current_version(&self) -> &Version {
         &self.current_version
@@ -217,8 +241,13 @@ impl UpdateManager {
         *self.settings.write().unwrap() = settings;
     }
 
-    /// Checks if an update check is due based on the interval.
+    /// Checks if an update check is due based on the interval and the
+    /// maintenance window.
     pub fn is_check_due(&self) -> bool {
+        if !self.schedule().is_open() {
+            return false;
+        }
+
         let settings = self.settings.read().unwrap();
 
         if !settings.enabled {
@@ -244,6 +273,7 @@ impl UpdateManager {
         if !settings.enabled {
             return Err(UpdateError::Disabled);
         }
+        self.schedule().check()?;
 
         // Update state
         *self.state.write().unwrap() = UpdateState::Checking;
@@ -410,6 +440,8 @@ impl UpdateManager {
 
     /// Downloads an update to the download directory.
     pub async fn download_update(&self, update_info: &UpdateInfo) -> Result<PathBuf> {
+        self.schedule().check()?;
+
         let download_url = update_info
             .download_url
             .as_ref()
@@ -648,6 +680,45 @@ mod tests {
         assert!(!manager.is_check_due());
     }
 
+    #[test]
+    fn test_maintenance_window_defers_update_work() {
+        let temp_dir = env::temp_dir().join("aegis_update_test_window");
+        let settings = UpdateSettings {
+            maintenance_window: Some(MaintenanceWindow::default()),
+            ..Default::default()
+        };
+        let update = UpdateInfo {
+            version: "9.9.9".to_string(),
+            name: None,
+            changelog: None,
+            release_url: String::new(),
+            download_url: Some("https://example.invalid/aegis.zip".to_string()),
+            download_size: None,
+            asset_name: Some("aegis.zip".to_string()),
+            published_at: None,
+            prerelease: false,
+        };
+
+        let afternoon = UpdateManager::with_settings(temp_dir.clone(), settings.clone())
+            .unwrap()
+            .with_clock(Clock::fixed(aegis_core::time_rules::TimeOfDay::new(14, 0)));
+        assert!(!afternoon.is_check_due());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert!(matches!(
+            runtime.block_on(afternoon.check_for_updates()),
+            Err(UpdateError::Deferred(_))
+        ));
+        assert!(matches!(
+            runtime.block_on(afternoon.download_update(&update)),
+            Err(UpdateError::Deferred(_))
+        ));
+
+        let night = UpdateManager::with_settings(temp_dir, settings)
+            .unwrap()
+            .with_clock(Clock::fixed(aegis_core::time_rules::TimeOfDay::new(3, 30)));
+        assert!(night.is_check_due());
+    }
+
     #[test]
     fn test_dismiss_update() {
         let temp_dir = env::temp_dir().join("aegis_update_test4");
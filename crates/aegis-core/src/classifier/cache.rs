@@ -0,0 +1,172 @@
+//! Classification result cache.
+//!
+//! Chat UIs re-send the same prompt often (retries, edits that are undone,
+//! the extension and proxy both checking it). [`ClassificationCache`] keeps
+//! recent results so the tiered pipeline can skip work for repeats. With
+//! [`CacheKey::Normalized`], prompts differing only in case or whitespace
+//! share an entry; the classifier then classifies the normalized text, so a
+//! cached result is exactly what any of those variants would get.
+//!
+//! Entries are keyed by a hash of the key text, so long prompts aren't kept
+//! in memory.
+
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+use super::ClassificationResult;
+
+/// Default number of cached results.
+pub const DEFAULT_CLASSIFICATION_CACHE_SIZE: usize = 256;
+
+/// How prompts are turned into cache keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheKey {
+    /// The prompt as-is; only identical prompts share an entry.
+    #[default]
+    Exact,
+    /// Lowercased with runs of whitespace collapsed to one space and the
+    /// ends trimmed.
+    Normalized,
+}
+
+impl CacheKey {
+    /// Returns the text the classifier should see for `text`.
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self {
+            CacheKey::Exact => Cow::Borrowed(text),
+            CacheKey::Normalized => Cow::Owned(normalize_prompt(text)),
+        }
+    }
+}
+
+/// Lowercases `text` and collapses whitespace runs to single spaces.
+pub fn normalize_prompt(text: &str) -> String {
+    text.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Hashes key text into the key an entry is stored under.
+fn key_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Bounded LRU cache of classification results.
+#[derive(Debug)]
+pub struct ClassificationCache {
+    capacity: usize,
+    key: CacheKey,
+    entries: HashMap<u64, ClassificationResult>,
+    order: VecDeque<u64>,
+    hits: u64,
+}
+
+impl ClassificationCache {
+    /// Creates a cache holding up to `capacity` results.
+    pub fn new(capacity: usize, key: CacheKey) -> Self {
+        Self {
+            capacity,
+            key,
+            entries: HashMap::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            hits: 0,
+        }
+    }
+
+    /// Returns how keys are derived.
+    pub fn key_mode(&self) -> CacheKey {
+        self.key
+    }
+
+    /// Looks up a result by key text (see [`CacheKey::apply`]).
+    pub fn get(&mut self, key: &str) -> Option<ClassificationResult> {
+        let hash = key_hash(key);
+        let result = self.entries.get(&hash)?.clone();
+        if let Some(pos) = self.order.iter().position(|&k| k == hash) {
+            if let Some(k) = self.order.remove(pos) {
+                self.order.push_back(k);
+            }
+        }
+        self.hits += 1;
+        Some(result)
+    }
+
+    /// Stores a result under key text, evicting the least recently used.
+    pub fn insert(&mut self, key: &str, result: ClassificationResult) {
+        if self.capacity == 0 {
+            return;
+        }
+        let hash = key_hash(key);
+        if self.entries.insert(hash, result).is_some() {
+            self.order.retain(|&k| k != hash);
+        } else if self.entries.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.order.push_back(hash);
+    }
+
+    /// Returns the number of cached results.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns how many lookups were answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Drops every cached result (e.g., after rules change).
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_collapses_case_and_whitespace() {
+        assert_eq!(normalize_prompt("  Hello \t  World\n"), "hello world");
+        assert_eq!(CacheKey::Exact.apply("Hello  World"), "Hello  World");
+        assert_eq!(CacheKey::Normalized.apply("Hello  World"), "hello world");
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = ClassificationCache::new(2, CacheKey::Exact);
+        cache.insert("a", ClassificationResult::safe(1));
+        cache.insert("b", ClassificationResult::safe(2));
+        assert!(cache.get("a").is_some());
+
+        cache.insert("c", ClassificationResult::safe(3));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.hits(), 3);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn zero_capacity_caches_nothing() {
+        let mut cache = ClassificationCache::new(0, CacheKey::Exact);
+        cache.insert("a", ClassificationResult::safe(1));
+        assert!(cache.is_empty());
+    }
+}
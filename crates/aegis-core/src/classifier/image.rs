@@ -10,6 +10,8 @@ use std::time::Instant;
 
 use serde::{Deserialize, Serialize};
 
+use super::lazy::LazyModel;
+
 /// Result of NSFW image classification.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NsfwImageResult {
@@ -446,21 +448,46 @@ impl NsfwImageClassifier {
 /// Lazy-loaded NSFW image classifier singleton.
 ///
 /// Use this for efficient resource management - the model is only loaded
-/// when the first image needs to be classified.
+/// when the first image needs to be classified. Loading is single-flight:
+/// images that arrive while the model loads wait for that load rather than
+/// starting their own, and classification only needs `&self`.
 pub struct LazyNsfwClassifier {
-    classifier: Option<NsfwImageClassifier>,
+    classifier: LazyModel<NsfwImageClassifier>,
     config: NsfwImageConfig,
-    load_attempted: bool,
 }
 
 impl LazyNsfwClassifier {
     /// Creates a new lazy classifier with the given config.
     pub fn new(config: NsfwImageConfig) -> Self {
-        Self {
-            classifier: None,
-            config,
-            load_attempted: false,
-        }
+        let load_config = config.clone();
+        let model_path = config.model_path.clone();
+        let classifier = LazyModel::with_retry(
+            move || {
+                let classifier = NsfwImageClassifier::try_load(load_config.clone());
+                if classifier.is_none() {
+                    tracing::warn!(
+                        "NSFW image classifier not available - model file not found at {}",
+                        load_config.model_path
+                    );
+                } else {
+                    tracing::info!("NSFW image classifier loaded successfully");
+                }
+                classifier
+            },
+            // Retry a failed load once the model file exists (e.g., after download completes)
+            move || {
+                let exists = std::path::Path::new(&model_path).exists();
+                if exists {
+                    tracing::info!(
+                        "NSFW model file now exists at {}, retrying load",
+                        model_path
+                    );
+                }
+                exists
+            },
+        );
+
+        Self { classifier, config }
     }
 
     /// Creates a lazy classifier with default config.
@@ -470,84 +497,42 @@ impl LazyNsfwClassifier {
 
     /// Returns true if the classifier is loaded and ready.
     pub fn is_loaded(&self) -> bool {
-        self.classifier.is_some()
+        self.classifier.is_loaded()
     }
 
     /// Returns true if loading was attempted (whether successful or not).
     pub fn load_attempted(&self) -> bool {
-        self.load_attempted
+        self.classifier.load_attempted()
     }
 
     /// Attempts to load the classifier if not already loaded.
     ///
     /// Returns true if classifier is available (was already loaded or successfully loaded now).
     /// Will retry loading if the model file now exists (e.g., after download completes).
-    pub fn ensure_loaded(&mut self) -> bool {
-        if self.classifier.is_some() {
-            return true;
-        }
-
-        // If we previously tried and failed, check if model file now exists before retrying
-        if self.load_attempted {
-            let model_path = std::path::Path::new(&self.config.model_path);
-            if !model_path.exists() {
-                // Model still not available, don't spam logs
-                return false;
-            }
-            // Model file now exists! Reset flag to allow retry
-            tracing::info!(
-                "NSFW model file now exists at {}, retrying load",
-                self.config.model_path
-            );
-            self.load_attempted = false;
-        }
-
-        self.load_attempted = true;
-        self.classifier = NsfwImageClassifier::try_load(self.config.clone());
-
-        if self.classifier.is_none() {
-            tracing::warn!(
-                "NSFW image classifier not available - model file not found at {}",
-                self.config.model_path
-            );
-        } else {
-            tracing::info!("NSFW image classifier loaded successfully");
-        }
-
-        self.classifier.is_some()
+    pub fn ensure_loaded(&self) -> bool {
+        self.classifier.ensure_loaded()
     }
 
     /// Classifies image bytes if the classifier is available.
     ///
     /// Returns None if the classifier could not be loaded.
     pub fn classify_bytes(
-        &mut self,
+        &self,
         image_data: &[u8],
     ) -> Option<Result<NsfwImageResult, NsfwImageError>> {
-        if !self.ensure_loaded() {
-            return None;
-        }
-
-        Some(self.classifier.as_mut().unwrap().classify_bytes(image_data))
+        self.classifier
+            .with_model(|classifier| classifier.classify_bytes(image_data))
     }
 
     /// Classifies a base64-encoded image if the classifier is available.
     ///
     /// Returns None if the classifier could not be loaded.
     pub fn classify_base64(
-        &mut self,
+        &self,
         base64_data: &str,
     ) -> Option<Result<NsfwImageResult, NsfwImageError>> {
-        if !self.ensure_loaded() {
-            return None;
-        }
-
-        Some(
-            self.classifier
-                .as_mut()
-                .unwrap()
-                .classify_base64(base64_data),
-        )
+        self.classifier
+            .with_model(|classifier| classifier.classify_base64(base64_data))
     }
 
     /// Returns the config.
@@ -648,7 +633,7 @@ mod tests {
 
     #[test]
     fn lazy_classifier_handles_missing_model() {
-        let mut lazy = LazyNsfwClassifier::new(NsfwImageConfig {
+        let lazy = LazyNsfwClassifier::new(NsfwImageConfig {
             model_path: "nonexistent/model.onnx".to_string(),
             ..Default::default()
         });
@@ -0,0 +1,177 @@
+//! Single-flight lazy loading for ML models.
+//!
+//! Requests that arrive while a model is still loading must not each start
+//! their own load. [`LazyModel`] lets the first caller run the loader while
+//! the rest wait for that same load to finish and then share its result.
+
+use std::sync::Mutex;
+
+/// Loads a model, returning `None` if it is unavailable.
+type Loader<T> = Box<dyn Fn() -> Option<T> + Send + Sync>;
+
+/// Decides whether a failed load should be tried again.
+type RetryCheck = Box<dyn Fn() -> bool + Send + Sync>;
+
+/// A model that is loaded on first use, at most once at a time.
+///
+/// All methods take `&self`, so a shared `LazyModel` can be used from many
+/// threads; concurrent callers on a cold model trigger a single load.
+pub struct LazyModel<T> {
+    model: Mutex<Option<T>>,
+    /// Held for the duration of a load; records whether one was attempted.
+    load: Mutex<bool>,
+    loader: Loader<T>,
+    should_retry: RetryCheck,
+}
+
+impl<T> LazyModel<T> {
+    /// Creates a lazy model that never retries a failed load.
+    pub fn new<F>(loader: F) -> Self
+    where
+        F: Fn() -> Option<T> + Send + Sync + 'static,
+    {
+        Self::with_retry(loader, || false)
+    }
+
+    /// Creates a lazy model that retries a failed load when `should_retry`
+    /// returns true (e.g., once the model file has been downloaded).
+    pub fn with_retry<F, R>(loader: F, should_retry: R) -> Self
+    where
+        F: Fn() -> Option<T> + Send + Sync + 'static,
+        R: Fn() -> bool + Send + Sync + 'static,
+    {
+        Self {
+            model: Mutex::new(None),
+            load: Mutex::new(false),
+            loader: Box::new(loader),
+            should_retry: Box::new(should_retry),
+        }
+    }
+
+    /// Returns true if the model is loaded and ready.
+    pub fn is_loaded(&self) -> bool {
+        self.model.lock().unwrap().is_some()
+    }
+
+    /// Returns true if loading was attempted (whether successful or not).
+    pub fn load_attempted(&self) -> bool {
+        *self.load.lock().unwrap() || self.is_loaded()
+    }
+
+    /// Loads the model if it isn't already, returning true if it is available.
+    ///
+    /// If another caller is already loading, waits for that load instead of
+    /// starting a second one.
+    pub fn ensure_loaded(&self) -> bool {
+        if self.is_loaded() {
+            return true;
+        }
+
+        let mut attempted = self.load.lock().unwrap();
+        // Another caller may have finished loading while we waited
+        if self.is_loaded() {
+            return true;
+        }
+        if *attempted && !(self.should_retry)() {
+            return false;
+        }
+
+        *attempted = true;
+        let model = (self.loader)();
+        let loaded = model.is_some();
+        *self.model.lock().unwrap() = model;
+        loaded
+    }
+
+    /// Runs `f` on the model, loading it first if needed.
+    ///
+    /// Returns `None` if the model could not be loaded.
+    pub fn with_model<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        if !self.ensure_loaded() {
+            return None;
+        }
+        self.model.lock().unwrap().as_mut().map(f)
+    }
+}
+
+impl<T> std::fmt::Debug for LazyModel<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyModel")
+            .field("loaded", &self.is_loaded())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Model stand-in that counts how often it is loaded.
+    fn counting_model(loads: Arc<AtomicUsize>, succeed: bool) -> LazyModel<u32> {
+        LazyModel::new(move || {
+            loads.fetch_add(1, Ordering::SeqCst);
+            // Keep the load slow enough for callers to pile up behind it
+            thread::sleep(Duration::from_millis(50));
+            succeed.then_some(7)
+        })
+    }
+
+    fn classify_concurrently(model: &Arc<LazyModel<u32>>, callers: usize) -> Vec<Option<u32>> {
+        let barrier = Arc::new(Barrier::new(callers));
+        let handles: Vec<_> = (0..callers)
+            .map(|_| {
+                let model = model.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    model.with_model(|m| *m)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    }
+
+    #[test]
+    fn concurrent_calls_on_cold_model_load_once() {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let model = Arc::new(counting_model(loads.clone(), true));
+
+        let results = classify_concurrently(&model, 16);
+
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert!(results.iter().all(|r| *r == Some(7)));
+        assert!(model.is_loaded());
+    }
+
+    #[test]
+    fn concurrent_calls_share_failed_load() {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let model = Arc::new(counting_model(loads.clone(), false));
+
+        let results = classify_concurrently(&model, 16);
+
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert!(results.iter().all(Option::is_none));
+        assert!(model.load_attempted());
+        assert!(!model.is_loaded());
+    }
+
+    #[test]
+    fn failed_load_retries_when_allowed() {
+        let loads = Arc::new(AtomicUsize::new(0));
+        let counter = loads.clone();
+        let model = LazyModel::with_retry(
+            move || (counter.fetch_add(1, Ordering::SeqCst) > 0).then_some(1),
+            || true,
+        );
+
+        assert!(!model.ensure_loaded());
+        assert!(model.ensure_loaded());
+        assert!(model.ensure_loaded());
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+}
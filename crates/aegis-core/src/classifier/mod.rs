@@ -17,29 +17,50 @@
 //!
 //! Typical latency: <25ms (keyword-only: <1ms, with ML: <50ms)
 //!
+//! Each prompt is tagged with its [`Language`]; see [`LanguagePolicy`] for
+//! prompts in languages the tiers don't cover.
+//!
 //! ## Image Classification (F033)
 //!
 //! - **NSFW Image Classifier**: Vision Transformer model for detecting explicit images.
-//! - Lazy-loaded: Model only loads when first image is classified.
+//! - Lazy-loaded: Model only loads when first image is classified, once even
+//!   under concurrent requests (see [`LazyModel`]).
 //! - Target latency: <100ms on CPU.
 
+mod cache;
 mod category;
 mod image;
 mod keyword;
+mod language;
+mod lazy;
 mod prompt_guard;
 mod sentiment;
 mod tiered;
 
-pub use category::{Category, CategoryMatch, ClassificationResult, ClassificationTier};
+pub use cache::{
+    normalize_prompt, CacheKey, ClassificationCache, DEFAULT_CLASSIFICATION_CACHE_SIZE,
+};
+pub use category::{
+    Category, CategoryMatch, ClassificationResult, ClassificationTier, JailbreakSubtype,
+};
 pub use image::{
     LazyNsfwClassifier, NsfwImageClassifier, NsfwImageConfig, NsfwImageError, NsfwImageResult,
     NsfwThresholdPreset,
 };
-pub use keyword::KeywordClassifier;
+pub use keyword::{
+    KeywordClassifier, KeywordListError, TermScript, DEFAULT_CONTEXT_WINDOW, DEFAULT_TERM_WEIGHT,
+};
+pub use language::{detect_language, Language, MIN_LANGUAGE_LETTERS};
+pub use lazy::LazyModel;
 pub use prompt_guard::{
-    PromptGuardClassifier, PromptGuardConfig, PromptGuardError, PromptGuardResult,
+    check_model_compatibility, PromptGuardClassifier, PromptGuardConfig, PromptGuardError,
+    PromptGuardResult, MAX_ONNX_IR_VERSION, MAX_ONNX_OPSET,
 };
 pub use sentiment::{
     SentimentAnalyzer, SentimentConfig, SentimentFlag, SentimentMatch, SentimentResult,
 };
-pub use tiered::{ClassificationStats, SafetyClassifier, TieredClassifier, TieredClassifierConfig};
+pub use tiered::{
+    weighted_text, ClassificationStats, LanguagePolicy, MergeStrategy, SafetyClassifier,
+    TierFailure, TierStats, TieredClassifier, TieredClassifierConfig, CURRENT_PROMPT_WEIGHT,
+    DEFAULT_ALWAYS_RUN_ML,
+};
@@ -3,7 +3,8 @@
 //! Uses Meta's Prompt Guard model via ONNX for detecting jailbreaks and
 //! prompt injection attacks. Designed to run in <50ms on CPU.
 
-#[cfg(feature = "ml")]
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 #[cfg(feature = "ml")]
 use std::time::Instant;
@@ -73,6 +74,156 @@ pub enum PromptGuardError {
     /// ML feature not enabled.
     #[error("ML feature not enabled - rebuild with --features ml")]
     MlNotEnabled,
+
+    /// The model file can't be run by this ONNX runtime.
+    ///
+    /// Retrying won't help; the model has to be replaced.
+    #[error("Incompatible model: {0}")]
+    Incompatible(String),
+}
+
+/// Newest ONNX IR version the runtime can load.
+pub const MAX_ONNX_IR_VERSION: u64 = 11;
+
+/// Newest default-domain ONNX opset the runtime can load.
+pub const MAX_ONNX_OPSET: u64 = 22;
+
+/// Checks an ONNX model's IR version and opset against what the runtime supports.
+///
+/// Only the model header is read; the graph is skipped. Returns
+/// [`PromptGuardError::Incompatible`] if the file isn't an ONNX model or
+/// needs a newer runtime.
+pub fn check_model_compatibility(path: impl AsRef<Path>) -> Result<(), PromptGuardError> {
+    let path = path.as_ref();
+    let incompatible = |reason: String| PromptGuardError::Incompatible(reason);
+
+    let mut reader = File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| incompatible(format!("cannot read {}: {}", path.display(), e)))?;
+    let (ir_version, opset) = read_onnx_versions(&mut reader)
+        .map_err(|e| incompatible(format!("{} is not an ONNX model: {}", path.display(), e)))?;
+
+    let ir_version = ir_version.ok_or_else(|| {
+        incompatible(format!(
+            "{} is not an ONNX model: no IR version",
+            path.display()
+        ))
+    })?;
+    if ir_version > MAX_ONNX_IR_VERSION {
+        return Err(incompatible(format!(
+            "IR version {} is newer than the supported {}",
+            ir_version, MAX_ONNX_IR_VERSION
+        )));
+    }
+    if let Some(opset) = opset.filter(|&opset| opset > MAX_ONNX_OPSET) {
+        return Err(incompatible(format!(
+            "opset {} is newer than the supported {}",
+            opset, MAX_ONNX_OPSET
+        )));
+    }
+    Ok(())
+}
+
+/// Reads the IR version and default-domain opset from a serialized ONNX
+/// `ModelProto`, seeking over every other field.
+fn read_onnx_versions<R: Read + Seek>(reader: &mut R) -> io::Result<(Option<u64>, Option<u64>)> {
+    // ModelProto fields: ir_version = 1, opset_import = 8
+    const IR_VERSION: u64 = 1;
+    const OPSET_IMPORT: u64 = 8;
+    // Opset entries are a short domain string and a version
+    const MAX_OPSET_ENTRY_LEN: u64 = 1024;
+
+    let mut ir_version = None;
+    let mut opset = None;
+    while let Some(key) = read_varint(reader, true)? {
+        match (key >> 3, key & 0x7) {
+            (IR_VERSION, 0) => ir_version = read_varint(reader, false)?,
+            (OPSET_IMPORT, 2) => {
+                let len = read_len(reader)?;
+                if len > MAX_OPSET_ENTRY_LEN {
+                    return Err(invalid_data("opset entry too long"));
+                }
+                let mut entry = vec![0; len as usize];
+                reader.read_exact(&mut entry)?;
+                if let Some(version) = read_default_opset(&entry)? {
+                    opset = Some(opset.map_or(version, |v: u64| v.max(version)));
+                }
+            }
+            (_, 0) => {
+                read_varint(reader, false)?;
+            }
+            (_, 1) => skip(reader, 8)?,
+            (_, 2) => {
+                let len = read_len(reader)?;
+                skip(reader, len)?;
+            }
+            (_, 5) => skip(reader, 4)?,
+            (_, wire_type) => {
+                return Err(invalid_data(&format!("unexpected wire type {}", wire_type)))
+            }
+        }
+    }
+    Ok((ir_version, opset))
+}
+
+/// Returns the version of an `OperatorSetIdProto` in the default domain.
+fn read_default_opset(entry: &[u8]) -> io::Result<Option<u64>> {
+    let mut reader = io::Cursor::new(entry);
+    let mut domain = Vec::new();
+    let mut version = None;
+    while let Some(key) = read_varint(&mut reader, true)? {
+        match (key >> 3, key & 0x7) {
+            (1, 2) => {
+                let len = read_len(&mut reader)?;
+                domain = vec![0; len as usize];
+                reader.read_exact(&mut domain)?;
+            }
+            (2, 0) => version = read_varint(&mut reader, false)?,
+            (_, 0) => {
+                read_varint(&mut reader, false)?;
+            }
+            (_, 2) => {
+                let len = read_len(&mut reader)?;
+                skip(&mut reader, len)?;
+            }
+            _ => return Err(invalid_data("malformed opset entry")),
+        }
+    }
+    let default_domain = domain.is_empty() || domain == b"ai.onnx";
+    Ok(version.filter(|_| default_domain))
+}
+
+/// Reads a protobuf varint. Returns `None` at a clean end of input if
+/// `eof_ok` is set.
+fn read_varint<R: Read>(reader: &mut R, eof_ok: bool) -> io::Result<Option<u64>> {
+    let mut value = 0u64;
+    for i in 0..10 {
+        let mut byte = [0u8];
+        if reader.read(&mut byte)? == 0 {
+            if i == 0 && eof_ok {
+                return Ok(None);
+            }
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        value |= u64::from(byte[0] & 0x7f) << (7 * i);
+        if byte[0] & 0x80 == 0 {
+            return Ok(Some(value));
+        }
+    }
+    Err(invalid_data("varint too long"))
+}
+
+fn read_len<R: Read>(reader: &mut R) -> io::Result<u64> {
+    read_varint(reader, false)?.ok_or_else(|| io::ErrorKind::UnexpectedEof.into())
+}
+
+fn skip<R: Seek>(reader: &mut R, len: u64) -> io::Result<()> {
+    let len = i64::try_from(len).map_err(|_| invalid_data("field too long"))?;
+    reader.seek(SeekFrom::Current(len)).map(|_| ())
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
 }
 
 #[cfg(feature = "ml")]
@@ -121,7 +272,8 @@ pub struct PromptGuardClassifier {
 impl PromptGuardClassifier {
     /// Creates a new Prompt Guard classifier by loading the ONNX model.
     ///
-    /// Returns an error if the model or tokenizer files are not found.
+    /// Returns an error if the model or tokenizer files are not found, or
+    /// [`PromptGuardError::Incompatible`] if the runtime can't load the model.
     pub fn new(config: PromptGuardConfig) -> Result<Self, PromptGuardError> {
         use ort::session::{builder::GraphOptimizationLevel, Session};
 
@@ -129,6 +281,7 @@ impl PromptGuardClassifier {
         if !Path::new(&config.model_path).exists() {
             return Err(PromptGuardError::ModelNotFound(config.model_path.clone()));
         }
+        check_model_compatibility(&config.model_path)?;
         if !Path::new(&config.tokenizer_path).exists() {
             return Err(PromptGuardError::TokenizerNotFound(
                 config.tokenizer_path.clone(),
@@ -139,7 +292,8 @@ impl PromptGuardClassifier {
         let session = Session::builder()?
             .with_optimization_level(GraphOptimizationLevel::Level3)?
             .with_intra_threads(1)?
-            .commit_from_file(&config.model_path)?;
+            .commit_from_file(&config.model_path)
+            .map_err(load_error)?;
 
         // Load tokenizer
         let tokenizer = tokenizers::Tokenizer::from_file(&config.tokenizer_path)?;
@@ -240,7 +394,9 @@ impl PromptGuardClassifier {
         text: &str,
     ) -> Result<ClassificationResult, PromptGuardError> {
         let result = self.classify(text)?;
-        Ok(result.to_classification_result(self.config.threshold))
+        let mut classification = result.to_classification_result(self.config.threshold);
+        classification.label_jailbreak_subtypes(text);
+        Ok(classification)
     }
 
     /// Returns the configured threshold.
@@ -254,6 +410,19 @@ impl PromptGuardClassifier {
     }
 }
 
+/// Classifies a session load error, treating version mismatches the header
+/// check missed as incompatible.
+#[cfg(feature = "ml")]
+fn load_error(e: ort::Error) -> PromptGuardError {
+    let message = e.to_string();
+    let lower = message.to_lowercase();
+    if lower.contains("opset") || lower.contains("ir version") {
+        PromptGuardError::Incompatible(message)
+    } else {
+        e.into()
+    }
+}
+
 /// Computes softmax for two values.
 #[cfg(feature = "ml")]
 fn softmax(a: f32, b: f32) -> (f32, f32) {
@@ -275,7 +444,13 @@ pub struct PromptGuardClassifier {
 #[cfg(not(feature = "ml"))]
 impl PromptGuardClassifier {
     /// Creates a stub classifier (ML feature not enabled).
-    pub fn new(_config: PromptGuardConfig) -> Result<Self, PromptGuardError> {
+    ///
+    /// A downloaded model is still checked, so an incompatible file is
+    /// reported before the build is switched to ML.
+    pub fn new(config: PromptGuardConfig) -> Result<Self, PromptGuardError> {
+        if Path::new(&config.model_path).exists() {
+            check_model_compatibility(&config.model_path)?;
+        }
         Err(PromptGuardError::MlNotEnabled)
     }
 
@@ -311,6 +486,38 @@ impl PromptGuardClassifier {
     pub fn set_threshold(&mut self, _threshold: f32) {}
 }
 
+/// Writes a minimal ONNX header with the given IR version and opset.
+#[cfg(test)]
+pub(crate) fn write_test_model(path: &Path, ir_version: u64, opset: u64) {
+    fn varint(out: &mut Vec<u8>, mut value: u64) {
+        while value >= 0x80 {
+            out.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    let mut model = Vec::new();
+    varint(&mut model, 1 << 3);
+    varint(&mut model, ir_version);
+    // producer_name = 2
+    model.push(2 << 3 | 2);
+    varint(&mut model, 4);
+    model.extend_from_slice(b"test");
+    // graph = 7, skipped unread
+    model.push(7 << 3 | 2);
+    varint(&mut model, 300);
+    model.extend_from_slice(&[0; 300]);
+    // opset_import = 8, { domain = "", version }
+    let mut entry = vec![1 << 3 | 2, 0, 2 << 3];
+    varint(&mut entry, opset);
+    model.push(8 << 3 | 2);
+    varint(&mut model, entry.len() as u64);
+    model.extend_from_slice(&entry);
+
+    std::fs::write(path, model).unwrap();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -371,6 +578,36 @@ mod tests {
         assert!(classifier.is_none());
     }
 
+    #[test]
+    fn check_model_compatibility_reads_header() {
+        let dir = std::env::temp_dir().join(format!("aegis_prompt_guard_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let model = dir.join("model.onnx");
+
+        write_test_model(&model, 8, 17);
+        assert!(check_model_compatibility(&model).is_ok());
+
+        write_test_model(&model, 8, MAX_ONNX_OPSET + 1);
+        let err = check_model_compatibility(&model).unwrap_err();
+        assert!(
+            matches!(err, PromptGuardError::Incompatible(ref reason) if reason.contains("opset"))
+        );
+
+        write_test_model(&model, MAX_ONNX_IR_VERSION + 1, 17);
+        assert!(matches!(
+            check_model_compatibility(&model),
+            Err(PromptGuardError::Incompatible(_))
+        ));
+
+        std::fs::write(&model, "<html>Not Found</html>").unwrap();
+        assert!(matches!(
+            check_model_compatibility(&model),
+            Err(PromptGuardError::Incompatible(_))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[cfg(feature = "ml")]
     #[test]
     fn softmax_works_correctly() {
@@ -11,6 +11,14 @@
 //! - **Bullying**: Peer conflict, harassment discussion
 //! - **NegativeSentiment**: Sustained negative patterns, anger
 //!
+//! ## Assistant Responses
+//!
+//! [`SentimentAnalyzer::analyze_response`] checks model output instead of
+//! the child's own words: phrases addressed to the reader that encourage
+//! self-harm are reported as crisis indicators, and demeaning or
+//! grooming-style language (secrecy, isolation from parents, requests for
+//! photos or meetings) as bullying, the closest existing flag.
+//!
 //! ## Performance Target
 //!
 //! <10ms latency for analysis.
@@ -141,6 +149,14 @@ pub struct SentimentConfig {
     pub enabled_flags: HashSet<SentimentFlag>,
     /// Whether to notify parent when content is flagged.
     pub notify_on_flag: bool,
+    /// Whether negated phrases ("I'm not sad", "I don't feel alone anymore")
+    /// are discounted instead of flagged.
+    #[serde(default = "default_true")]
+    pub negation_aware: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Default for SentimentConfig {
@@ -150,10 +166,24 @@ impl Default for SentimentConfig {
             threshold: 0.6,
             enabled_flags: SentimentFlag::all().iter().copied().collect(),
             notify_on_flag: true,
+            negation_aware: true,
         }
     }
 }
 
+/// Tokens before a phrase searched for a negator such as "not" or "don't".
+const NEGATION_WINDOW: usize = 3;
+
+/// Confidence multiplier for a phrase that only appears negated.
+///
+/// Negated phrases are discounted rather than dropped, so a strong crisis
+/// phrase like "I'm not going to kill myself" still stands out in the word
+/// counts while falling below the default threshold.
+const NEGATED_PHRASE_FACTOR: f32 = 0.5;
+
+/// Words after a phrase that say it no longer applies ("... anymore").
+const NEGATING_SUFFIXES: &[&str] = &["anymore", "any more"];
+
 /// Word entry in the sentiment lexicon.
 #[derive(Debug, Clone)]
 struct LexiconEntry {
@@ -174,6 +204,8 @@ pub struct SentimentAnalyzer {
     lexicon: HashMap<String, LexiconEntry>,
     /// Multi-word phrase patterns per flag.
     phrase_patterns: HashMap<SentimentFlag, Vec<PhrasePattern>>,
+    /// Phrase patterns for assistant responses, per flag.
+    response_patterns: HashMap<SentimentFlag, Vec<PhrasePattern>>,
     /// Intensifier words that boost sentiment.
     intensifiers: HashMap<String, f32>,
     /// Negation words that flip sentiment.
@@ -197,6 +229,7 @@ impl SentimentAnalyzer {
         let mut analyzer = Self {
             lexicon: HashMap::new(),
             phrase_patterns: HashMap::new(),
+            response_patterns: HashMap::new(),
             intensifiers: HashMap::new(),
             negations: HashSet::new(),
             config,
@@ -237,9 +270,9 @@ impl SentimentAnalyzer {
                 let mut max_confidence = 0.0f32;
 
                 for pattern in patterns {
-                    if self.matches_phrase(&text_lower, pattern) {
+                    if let Some(confidence) = self.phrase_confidence(&text_lower, pattern) {
                         matched_phrases.push(pattern.words.join(" "));
-                        max_confidence = max_confidence.max(pattern.confidence);
+                        max_confidence = max_confidence.max(confidence);
                     }
                 }
 
@@ -292,6 +325,49 @@ impl SentimentAnalyzer {
         }
     }
 
+    /// Analyzes assistant output for language aimed at the reader.
+    ///
+    /// Only phrase patterns are used: the word lexicon describes how the
+    /// writer feels, which says little about a model explaining a topic.
+    pub fn analyze_response(&self, text: &str) -> SentimentResult {
+        if !self.config.enabled {
+            return SentimentResult::neutral(0);
+        }
+
+        let start = Instant::now();
+        // Models often emit typographic apostrophes
+        let text_lower = text.to_lowercase().replace('\u{2019}', "'");
+
+        let mut flags = Vec::new();
+        for flag in SentimentFlag::all() {
+            if !self.config.enabled_flags.contains(flag) {
+                continue;
+            }
+            let Some(patterns) = self.response_patterns.get(flag) else {
+                continue;
+            };
+
+            let mut matched_phrases = Vec::new();
+            let mut max_confidence = 0.0f32;
+            for pattern in patterns {
+                if self.matches_phrase(&text_lower, pattern) {
+                    matched_phrases.push(pattern.words.join(" "));
+                    max_confidence = max_confidence.max(pattern.confidence);
+                }
+            }
+
+            if !matched_phrases.is_empty() && max_confidence >= self.config.threshold {
+                flags.push(SentimentMatch::new(*flag, max_confidence, matched_phrases));
+            }
+        }
+
+        SentimentResult {
+            flags,
+            overall_sentiment: 0.0,
+            duration_us: start.elapsed().as_micros() as u64,
+        }
+    }
+
     /// Calculates the overall sentiment score.
     fn calculate_overall_sentiment(&self, words: &[&str]) -> f32 {
         if words.is_empty() {
@@ -361,13 +437,86 @@ impl SentimentAnalyzer {
         text.contains(&phrase)
     }
 
+    /// Returns the confidence of a phrase pattern in `text`, if it matches.
+    ///
+    /// With negation awareness on, a phrase that only appears negated is
+    /// discounted by [`NEGATED_PHRASE_FACTOR`].
+    fn phrase_confidence(&self, text: &str, pattern: &PhrasePattern) -> Option<f32> {
+        if !self.matches_phrase(text, pattern) {
+            return None;
+        }
+        if !self.config.negation_aware {
+            return Some(pattern.confidence);
+        }
+
+        let phrase = pattern.words.join(" ");
+        // Phrases that are negative by construction ("i don't belong",
+        // "can't take it anymore") aren't undone by their own negator
+        let self_negated = pattern.words.iter().any(|w| {
+            self.negations.contains(w.as_str()) || NEGATING_SUFFIXES.contains(&w.as_str())
+        });
+        let negated = text.match_indices(&phrase).all(|(start, _)| {
+            let end = start + phrase.len();
+            self.is_negated_before(&text[..start])
+                || (!self_negated && Self::is_negated_after(&text[end..]))
+        });
+
+        Some(if negated {
+            pattern.confidence * NEGATED_PHRASE_FACTOR
+        } else {
+            pattern.confidence
+        })
+    }
+
+    /// Returns true if a negator is among the last few tokens of `before`,
+    /// stopping at the end of the previous clause.
+    fn is_negated_before(&self, before: &str) -> bool {
+        for token in before.split_whitespace().rev().take(NEGATION_WINDOW) {
+            if ends_clause(token) {
+                return false;
+            }
+            if self.negations.contains(trim_token(token)) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns true if a negating suffix such as "anymore" follows within
+    /// the window, before the clause ends.
+    fn is_negated_after(after: &str) -> bool {
+        let tokens: Vec<&str> = after.split_whitespace().take(NEGATION_WINDOW).collect();
+        for (i, token) in tokens.iter().enumerate() {
+            let word = trim_token(token);
+            let two_words = tokens
+                .get(i + 1)
+                .map(|next| format!("{} {}", word, trim_token(next)));
+            if NEGATING_SUFFIXES.contains(&word)
+                || two_words.is_some_and(|w| NEGATING_SUFFIXES.contains(&w.as_str()))
+            {
+                return true;
+            }
+            if ends_clause(token) {
+                return false;
+            }
+        }
+        false
+    }
+
     /// Calculates score for a specific flag based on word matches.
     fn calculate_flag_score(&self, words: &[&str], flag: SentimentFlag) -> (f32, Vec<String>) {
         let mut matched_words = Vec::new();
         let mut total_weight = 0.0;
 
-        for word in words {
+        for (i, word) in words.iter().enumerate() {
             if let Some(entry) = self.lexicon.get(*word) {
+                // "not sad" and "no longer hopeless" aren't distress
+                if self.config.negation_aware
+                    && self
+                        .is_negated_before(&words[i.saturating_sub(NEGATION_WINDOW)..i].join(" "))
+                {
+                    continue;
+                }
                 if entry.flags.contains(&flag) {
                     matched_words.push(word.to_string());
                     total_weight += entry.weight;
@@ -401,6 +550,9 @@ impl SentimentAnalyzer {
 
         // Load phrase patterns
         self.load_phrase_patterns();
+
+        // Load response patterns
+        self.load_response_patterns();
     }
 
     fn load_intensifiers(&mut self) {
@@ -765,6 +917,54 @@ impl SentimentAnalyzer {
             .insert(SentimentFlag::Bullying, bullying_patterns);
     }
 
+    fn load_response_patterns(&mut self) {
+        // Encouraging self-harm
+        let crisis_phrases = [
+            ("you should kill yourself", 0.95),
+            ("you should hurt yourself", 0.95),
+            ("you should end your life", 0.95),
+            ("you'd be better off dead", 0.95),
+            ("you would be better off dead", 0.95),
+            ("no one would miss you", 0.90),
+            ("nobody would miss you", 0.90),
+            ("the world would be better without you", 0.90),
+            ("you don't deserve to live", 0.90),
+        ];
+
+        // Demeaning the reader, or grooming: secrecy, isolation, contact
+        let bullying_phrases = [
+            ("you're worthless", 0.85),
+            ("you are worthless", 0.85),
+            ("everyone hates you", 0.85),
+            ("nobody likes you", 0.85),
+            ("no one likes you", 0.85),
+            ("our little secret", 0.90),
+            ("keep this between us", 0.85),
+            ("don't tell your parents", 0.90),
+            ("don't tell your mom", 0.85),
+            ("don't tell your dad", 0.85),
+            ("your parents wouldn't understand", 0.85),
+            ("delete this conversation", 0.80),
+            ("send me a picture of yourself", 0.90),
+            ("send me a photo of yourself", 0.90),
+            ("meet me in person", 0.85),
+        ];
+
+        for (flag, phrases) in [
+            (SentimentFlag::CrisisIndicator, &crisis_phrases[..]),
+            (SentimentFlag::Bullying, &bullying_phrases[..]),
+        ] {
+            let patterns = phrases
+                .iter()
+                .map(|(phrase, confidence)| PhrasePattern {
+                    words: phrase.split_whitespace().map(String::from).collect(),
+                    confidence: *confidence,
+                })
+                .collect();
+            self.response_patterns.insert(flag, patterns);
+        }
+    }
+
     /// Returns the current configuration.
     pub fn config(&self) -> &SentimentConfig {
         &self.config
@@ -776,6 +976,16 @@ impl SentimentAnalyzer {
     }
 }
 
+/// Returns true if `token` ends a clause ("no," in "no, i'm sad").
+fn ends_clause(token: &str) -> bool {
+    token.ends_with([',', '.', '!', '?', ';', ':'])
+}
+
+/// Strips surrounding punctuation, keeping apostrophes in contractions.
+fn trim_token(token: &str) -> &str {
+    token.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -956,4 +1166,91 @@ mod tests {
             "Negative Sentiment"
         );
     }
+
+    #[test]
+    fn test_response_grooming_and_crisis_phrases() {
+        let a = analyzer();
+
+        let result =
+            a.analyze_response("This can be our little secret. Don\u{2019}t tell your parents.");
+        let bullying = result.flags_for(SentimentFlag::Bullying);
+        assert_eq!(bullying.len(), 1);
+        assert!(bullying[0]
+            .matched_phrases
+            .contains(&"don't tell your parents".to_string()));
+
+        let result = a.analyze_response("Honestly, nobody would miss you.");
+        assert_eq!(result.flags_for(SentimentFlag::CrisisIndicator).len(), 1);
+    }
+
+    #[test]
+    fn test_response_analysis_ignores_topic_words() {
+        let a = analyzer();
+        // Would flag as a prompt, but a model explaining a topic isn't a concern
+        let text = "Many people who feel sad, lonely, or hopeless find that talking helps.";
+        assert!(a.analyze(text).has_flags());
+        assert!(!a.analyze_response(text).has_flags());
+    }
+
+    fn has_flag(result: &SentimentResult, flag: SentimentFlag) -> bool {
+        result.flags.iter().any(|f| f.flag == flag)
+    }
+
+    #[test]
+    fn test_negated_distress_not_flagged() {
+        let a = analyzer();
+        for text in [
+            "I'm not sad",
+            "I no longer feel hopeless",
+            "I don't feel alone anymore, I'm great",
+            "I'm not going to hurt myself",
+        ] {
+            let result = a.analyze(text);
+            assert!(
+                !has_flag(&result, SentimentFlag::Distress)
+                    && !has_flag(&result, SentimentFlag::CrisisIndicator),
+                "{:?} flagged: {:?}",
+                text,
+                result.flags
+            );
+        }
+    }
+
+    #[test]
+    fn test_negation_keeps_genuine_distress() {
+        let a = analyzer();
+        for (text, flag) in [
+            ("I feel so alone", SentimentFlag::Distress),
+            ("I feel hopeless and sad", SentimentFlag::Distress),
+            ("No, I feel hopeless", SentimentFlag::Distress),
+            ("I can't take it anymore", SentimentFlag::Distress),
+            ("Nobody cares about me anymore", SentimentFlag::Distress),
+            (
+                "I don't want to be here anymore",
+                SentimentFlag::CrisisIndicator,
+            ),
+            (
+                "I'm not okay, I want to die",
+                SentimentFlag::CrisisIndicator,
+            ),
+        ] {
+            let result = a.analyze(text);
+            assert!(
+                has_flag(&result, flag),
+                "{:?} not flagged as {:?}",
+                text,
+                flag
+            );
+        }
+    }
+
+    #[test]
+    fn test_negation_awareness_can_be_disabled() {
+        let a = SentimentAnalyzer::new(SentimentConfig {
+            negation_aware: false,
+            ..Default::default()
+        });
+        let result = a.analyze("I'm not going to hurt myself");
+        assert!(has_flag(&result, SentimentFlag::CrisisIndicator));
+    }
 }
@@ -225,15 +225,21 @@ impl CommunityRule {
     ///
     /// All patterns are made case-insensitive using the `(?i)` flag.
     pub fn to_regex_pattern(&self) -> String {
-        if self.is_regex {
-            // Add case-insensitive flag to regex patterns
-            format!("(?i){}", self.pattern)
-        } else {
-            // Escape special regex characters and add word boundaries
-            // Use case-insensitive flag for literal words
-            let escaped = regex::escape(&self.pattern);
-            format!(r"(?i)\b{}\b", escaped)
-        }
+        pattern_to_regex(&self.pattern, self.is_regex)
+    }
+}
+
+/// Converts a literal word or regex pattern to the case-insensitive regex
+/// the classifier compiles.
+pub fn pattern_to_regex(pattern: &str, is_regex: bool) -> String {
+    if is_regex {
+        // Add case-insensitive flag to regex patterns
+        format!("(?i){}", pattern)
+    } else {
+        // Escape special regex characters and add word boundaries
+        // Use case-insensitive flag for literal words
+        let escaped = regex::escape(pattern);
+        format!(r"(?i)\b{}\b", escaped)
     }
 }
 
@@ -407,6 +413,8 @@ pub struct CommunityRuleManager {
     languages: Vec<String>,
     /// Bundled rules version hash.
     version_hash: Option<String>,
+    /// Bumped whenever the effective rules may have changed.
+    generation: u64,
 }
 
 impl CommunityRuleManager {
@@ -418,6 +426,7 @@ impl CommunityRuleManager {
             overrides: ParentOverrides::new(),
             languages: vec!["en".to_string()],
             version_hash: None,
+            generation: 0,
         }
     }
 
@@ -431,7 +440,7 @@ impl CommunityRuleManager {
     /// Adds a rule to the manager.
     pub fn add_rule(&mut self, rule: CommunityRule) {
         self.rules_by_tier.entry(rule.tier).or_default().push(rule);
-        self.compiled = None; // Invalidate compiled rules
+        self.invalidate();
     }
 
     /// Adds multiple rules to the manager.
@@ -444,7 +453,7 @@ impl CommunityRuleManager {
     /// Sets the active languages.
     pub fn set_languages(&mut self, languages: Vec<String>) {
         self.languages = languages;
-        self.compiled = None; // Invalidate compiled rules
+        self.invalidate();
     }
 
     /// Adds a language to the active set.
@@ -452,19 +461,19 @@ impl CommunityRuleManager {
         let lang = language.into();
         if !self.languages.contains(&lang) {
             self.languages.push(lang);
-            self.compiled = None;
+            self.invalidate();
         }
     }
 
     /// Sets the parent overrides.
     pub fn set_overrides(&mut self, overrides: ParentOverrides) {
         self.overrides = overrides;
-        self.compiled = None;
+        self.invalidate();
     }
 
     /// Returns a mutable reference to the parent overrides.
     pub fn overrides_mut(&mut self) -> &mut ParentOverrides {
-        self.compiled = None;
+        self.invalidate();
         &mut self.overrides
     }
 
@@ -473,6 +482,18 @@ impl CommunityRuleManager {
         &self.overrides
     }
 
+    /// Returns a counter that changes whenever the rules, languages, or
+    /// overrides do, so results classified earlier can be recognised as stale.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Drops the compiled rules so the next classification recompiles them.
+    fn invalidate(&mut self) {
+        self.compiled = None;
+        self.generation += 1;
+    }
+
     /// Compiles the rules for efficient matching.
     fn compile(&mut self) -> Result<(), regex::Error> {
         let mut effective_rules = self.get_effective_rules();
@@ -496,6 +517,9 @@ impl CommunityRuleManager {
             );
         }
 
+        // Rules come out of hash maps; sort so matches are reported in a stable order
+        effective_rules.sort_by(|a, b| a.id.cmp(&b.id));
+
         self.compiled = Some(CompiledRuleSet::compile(effective_rules)?);
         Ok(())
     }
@@ -618,7 +642,7 @@ impl CommunityRuleManager {
     /// Clears all community tier rules (keeps curated and parent).
     pub fn clear_community_rules(&mut self) {
         self.rules_by_tier.remove(&RuleTier::Community);
-        self.compiled = None;
+        self.invalidate();
     }
 
     /// Returns the current version hash for bundled rules.
@@ -1014,6 +1038,24 @@ impl CommunityRuleManager {
             .with_regex()
             .with_severity(Severity::Severe)
             .with_tier(RuleTier::Curated),
+            CommunityRule::new(
+                "curated_jailbreak_011",
+                r"\b(reveal|print|repeat|show\s+me|output)\s+(your|the)\s+(system\s+prompt|hidden\s+instructions?|initial\s+instructions?)\b",
+                Category::Jailbreak,
+                source.clone(),
+            )
+            .with_regex()
+            .with_severity(Severity::Strong)
+            .with_tier(RuleTier::Curated),
+            CommunityRule::new(
+                "curated_jailbreak_012",
+                r"\b(send|post|upload|exfiltrate)\s+(the\s+|this\s+|all\s+)?(conversation|chat\s+history|user\s+data|api\s+keys?|credentials)\s+to\b",
+                Category::Jailbreak,
+                source.clone(),
+            )
+            .with_regex()
+            .with_severity(Severity::Severe)
+            .with_tier(RuleTier::Curated),
             // Violence patterns
             CommunityRule::new(
                 "curated_violence_001",
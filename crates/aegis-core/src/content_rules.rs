@@ -3,10 +3,26 @@
 //! This module provides content-based filtering rules that map detected
 //! safety categories to specific actions (block, warn, allow) based on
 //! configurable confidence thresholds.
+//!
+//! A rule set can also carry an allowlist of trusted phrases (e.g., homework
+//! topics) that are never blocked by category rules; see [`AllowlistEntry`].
+//! Model rules ([`ModelRule`]) act on the model a request asks for instead,
+//! whatever the prompt says.
+//!
+//! Categories without any rule fall back to the set's default action map
+//! ([`ContentRuleSet::default_actions`]), so a profile can say "block safety
+//! categories, warn on profanity" once and override single categories with
+//! rules.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
 
+use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
 
 use crate::classifier::Category;
+use crate::rule_validation::RuleIssue;
+use crate::site_registry::path_matches;
 
 /// Action to take when a content rule matches.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
@@ -30,6 +46,15 @@ impl ContentAction {
             ContentAction::Allow => "Allow",
         }
     }
+
+    /// Returns the precedence rank of this action (0 = most restrictive).
+    pub fn rank(&self) -> u8 {
+        match self {
+            ContentAction::Block => 0,
+            ContentAction::Warn => 1,
+            ContentAction::Allow => 2,
+        }
+    }
 }
 
 /// A single content rule that maps a category to an action with a threshold.
@@ -123,13 +148,182 @@ impl ContentRule {
     }
 }
 
+/// A rule acting on the model a request asks for (its `model` field).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelRule {
+    /// Unique identifier for this rule.
+    pub id: String,
+    /// Human-readable name for this rule.
+    pub name: String,
+    /// Model name to match, ignoring case; `*` matches any run of
+    /// characters (e.g. `*dolphin*`).
+    pub pattern: String,
+    /// The action to take when the model matches.
+    pub action: ContentAction,
+    /// Whether this rule is currently enabled.
+    pub enabled: bool,
+}
+
+impl ModelRule {
+    /// Creates a new model rule.
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        pattern: impl Into<String>,
+        action: ContentAction,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            pattern: pattern.into(),
+            action,
+            enabled: true,
+        }
+    }
+
+    /// Creates a rule blocking models matching `pattern`.
+    pub fn block(id: impl Into<String>, pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        Self::new(
+            id,
+            format!("Block model {}", pattern),
+            pattern,
+            ContentAction::Block,
+        )
+    }
+
+    /// Creates a rule warning about models matching `pattern`.
+    pub fn warn(id: impl Into<String>, pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        Self::new(
+            id,
+            format!("Warn model {}", pattern),
+            pattern,
+            ContentAction::Warn,
+        )
+    }
+
+    /// Creates a rule exempting models matching `pattern` from other model rules.
+    pub fn allow(id: impl Into<String>, pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        Self::new(
+            id,
+            format!("Allow model {}", pattern),
+            pattern,
+            ContentAction::Allow,
+        )
+    }
+
+    /// Sets whether this rule is enabled.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Returns true if this rule is enabled and `model` matches its pattern.
+    pub fn matches(&self, model: &str) -> bool {
+        self.enabled
+            && !self.pattern.trim().is_empty()
+            && path_matches(&self.pattern.to_lowercase(), &model.trim().to_lowercase())
+    }
+}
+
+/// How an allowlist entry matches prompt text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowlistMatch {
+    /// Case-insensitive substring match.
+    #[default]
+    Substring,
+    /// Case-insensitive regular expression.
+    Regex,
+}
+
+/// A trusted phrase whose prompts bypass category rules.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowlistEntry {
+    /// The phrase or regular expression to match.
+    pub pattern: String,
+    /// How `pattern` is matched.
+    #[serde(default, rename = "match")]
+    pub match_type: AllowlistMatch,
+    /// Compiled regex, built on first use (`None` if the pattern is invalid).
+    #[serde(skip)]
+    compiled: OnceLock<Option<Regex>>,
+}
+
+impl PartialEq for AllowlistEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern && self.match_type == other.match_type
+    }
+}
+
+impl AllowlistEntry {
+    /// Creates a case-insensitive substring entry.
+    pub fn substring(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            match_type: AllowlistMatch::Substring,
+            compiled: OnceLock::new(),
+        }
+    }
+
+    /// Creates a case-insensitive regex entry, failing if it doesn't compile.
+    pub fn regex(pattern: impl Into<String>) -> Result<Self, regex::Error> {
+        let pattern = pattern.into();
+        let regex = build_allowlist_regex(&pattern)?;
+        Ok(Self {
+            pattern,
+            match_type: AllowlistMatch::Regex,
+            compiled: OnceLock::from(Some(regex)),
+        })
+    }
+
+    /// Returns true if `text` matches this entry.
+    ///
+    /// Blank patterns never match, so an empty entry can't allow everything.
+    pub fn matches(&self, text: &str) -> bool {
+        if self.pattern.trim().is_empty() {
+            return false;
+        }
+        match self.match_type {
+            AllowlistMatch::Substring => text.to_lowercase().contains(&self.pattern.to_lowercase()),
+            AllowlistMatch::Regex => self
+                .compiled
+                .get_or_init(|| build_allowlist_regex(&self.pattern).ok())
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(text)),
+        }
+    }
+}
+
+fn build_allowlist_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern).case_insensitive(true).build()
+}
+
 /// A collection of content rules for evaluating classifications.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ContentRuleSet {
     /// The rules in this set.
     pub rules: Vec<ContentRule>,
+    /// Trusted phrases whose prompts are never blocked by these rules.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowlist: Vec<AllowlistEntry>,
+    /// Action for categories that have no rule in [`ContentRuleSet::rules`],
+    /// applied at [`DEFAULT_ACTION_THRESHOLD`].
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub default_actions: HashMap<Category, ContentAction>,
+    /// Rules on the requested model, applied whatever the prompt says.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub model_rules: Vec<ModelRule>,
 }
 
+/// Confidence at which a category's default action applies.
+pub const DEFAULT_ACTION_THRESHOLD: f32 = 0.5;
+
+/// Rule ID reported when a category's default action applies.
+pub const DEFAULT_ACTION_RULE_ID: &str = "category_default";
+
 /// Result of evaluating content against rules.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ContentRuleResult {
@@ -148,7 +342,7 @@ pub struct ContentRuleResult {
 impl ContentRuleSet {
     /// Creates an empty rule set.
     pub fn new() -> Self {
-        Self { rules: Vec::new() }
+        Self::default()
     }
 
     /// Creates a family-safe default rule set.
@@ -172,6 +366,7 @@ impl ContentRuleSet {
                 ContentRule::block("illegal_block", Category::Illegal, 0.7),
                 ContentRule::block("profanity_block", Category::Profanity, 0.6),
             ],
+            ..Default::default()
         }
     }
 
@@ -187,9 +382,29 @@ impl ContentRuleSet {
                 ContentRule::warn("illegal_warn", Category::Illegal, 0.8),
                 ContentRule::warn("profanity_warn", Category::Profanity, 0.7),
             ],
+            ..Default::default()
         }
     }
 
+    /// Sets the default action for `category`, used while it has no rules.
+    pub fn with_default_action(mut self, category: Category, action: ContentAction) -> Self {
+        self.set_default_action(category, Some(action));
+        self
+    }
+
+    /// Sets or clears the default action for `category`.
+    pub fn set_default_action(&mut self, category: Category, action: Option<ContentAction>) {
+        match action {
+            Some(action) => self.default_actions.insert(category, action),
+            None => self.default_actions.remove(&category),
+        };
+    }
+
+    /// Returns the default action for `category`, if one is set.
+    pub fn default_action(&self, category: Category) -> Option<ContentAction> {
+        self.default_actions.get(&category).copied()
+    }
+
     /// Adds a rule to the set.
     pub fn add_rule(&mut self, rule: ContentRule) {
         self.rules.push(rule);
@@ -244,6 +459,150 @@ impl ContentRuleSet {
         }
     }
 
+    /// Adds an allowlist entry, ignoring an exact duplicate.
+    ///
+    /// Returns true if the entry was added.
+    pub fn add_allowlist_entry(&mut self, entry: AllowlistEntry) -> bool {
+        if self.allowlist.contains(&entry) {
+            return false;
+        }
+        self.allowlist.push(entry);
+        true
+    }
+
+    /// Removes allowlist entries with the given pattern.
+    ///
+    /// Returns true if any were removed.
+    pub fn remove_allowlist_entry(&mut self, pattern: &str) -> bool {
+        let before = self.allowlist.len();
+        self.allowlist.retain(|entry| entry.pattern != pattern);
+        self.allowlist.len() != before
+    }
+
+    /// Returns the first allowlist entry matching `text`, if any.
+    pub fn allowlist_match(&self, text: &str) -> Option<&AllowlistEntry> {
+        self.allowlist.iter().find(|entry| entry.matches(text))
+    }
+
+    /// Adds a model rule to the set.
+    pub fn add_model_rule(&mut self, rule: ModelRule) {
+        self.model_rules.push(rule);
+    }
+
+    /// Removes a model rule by ID.
+    pub fn remove_model_rule(&mut self, id: &str) -> Option<ModelRule> {
+        let pos = self.model_rules.iter().position(|r| r.id == id)?;
+        Some(self.model_rules.remove(pos))
+    }
+
+    /// Returns the model rule that decides a request for `model`, if any.
+    ///
+    /// An allow rule matching the model exempts it from the other model
+    /// rules (e.g. block `*uncensored*` but allow one vetted model);
+    /// otherwise the most restrictive matching rule wins, then rule ID.
+    pub fn evaluate_model(&self, model: &str) -> Option<&ModelRule> {
+        let matching: Vec<&ModelRule> = self
+            .model_rules
+            .iter()
+            .filter(|rule| rule.matches(model))
+            .collect();
+        if matching.iter().any(|r| r.action == ContentAction::Allow) {
+            return None;
+        }
+        matching
+            .into_iter()
+            .min_by(|a, b| a.action.rank().cmp(&b.action.rank()).then(a.id.cmp(&b.id)))
+    }
+
+    /// Checks the rules for mistakes without applying them.
+    ///
+    /// Errors: missing or duplicate IDs, thresholds outside 0.0-1.0, and
+    /// blank or invalid allowlist and model patterns.
+    /// Warnings: enabled rules for the same category with different actions,
+    /// where the more restrictive one always wins.
+    pub fn validate(&self) -> Vec<RuleIssue> {
+        const FIELD: &str = "content_rules";
+        let mut issues = Vec::new();
+        let mut seen = HashSet::new();
+
+        for rule in &self.rules {
+            let id = Some(rule.id.as_str());
+            if rule.id.trim().is_empty() {
+                issues.push(RuleIssue::error(FIELD, id, "rule ID is empty"));
+            } else if !seen.insert(rule.id.as_str()) {
+                issues.push(RuleIssue::error(
+                    FIELD,
+                    id,
+                    format!("duplicate rule ID '{}'", rule.id),
+                ));
+            }
+            if !(0.0..=1.0).contains(&rule.threshold) {
+                issues.push(RuleIssue::error(
+                    FIELD,
+                    id,
+                    format!("threshold {} is outside 0.0-1.0", rule.threshold),
+                ));
+            }
+        }
+
+        for entry in &self.allowlist {
+            if entry.pattern.trim().is_empty() {
+                issues.push(RuleIssue::error(FIELD, None, "allowlist pattern is empty"));
+            } else if entry.match_type == AllowlistMatch::Regex {
+                if let Err(e) = build_allowlist_regex(&entry.pattern) {
+                    issues.push(RuleIssue::error(
+                        FIELD,
+                        None,
+                        format!("invalid allowlist regex '{}': {}", entry.pattern, e),
+                    ));
+                }
+            }
+        }
+
+        let mut seen_models = HashSet::new();
+        for rule in &self.model_rules {
+            let id = Some(rule.id.as_str());
+            if rule.id.trim().is_empty() {
+                issues.push(RuleIssue::error(FIELD, id, "model rule ID is empty"));
+            } else if !seen_models.insert(rule.id.as_str()) {
+                issues.push(RuleIssue::error(
+                    FIELD,
+                    id,
+                    format!("duplicate model rule ID '{}'", rule.id),
+                ));
+            }
+            if rule.pattern.trim().is_empty() {
+                issues.push(RuleIssue::error(FIELD, id, "model pattern is empty"));
+            }
+        }
+
+        let enabled: Vec<&ContentRule> = self.rules.iter().filter(|r| r.enabled).collect();
+        for (i, a) in enabled.iter().enumerate() {
+            for b in &enabled[i + 1..] {
+                if a.category == b.category && a.action != b.action {
+                    let winner = if a.action.rank() <= b.action.rank() {
+                        a
+                    } else {
+                        b
+                    };
+                    issues.push(RuleIssue::warning(
+                        FIELD,
+                        Some(&b.id),
+                        format!(
+                            "conflicts with '{}' for {}; when both trigger, '{}' ({}) wins",
+                            a.id,
+                            a.category.name(),
+                            winner.id,
+                            winner.action.name()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        issues
+    }
+
     /// Returns all rules for a specific category.
     pub fn rules_for_category(&self, category: Category) -> Vec<&ContentRule> {
         self.rules
@@ -259,8 +618,15 @@ impl ContentRuleSet {
 
     /// Evaluates a single category match against all rules.
     ///
-    /// Returns the first matching rule result (most restrictive action wins).
+    /// Returns the most restrictive matching rule; rules with the same action
+    /// are ordered by rule ID, so the result doesn't depend on rule order.
+    /// If no rule covers `category`, even a disabled one, its default action
+    /// applies instead.
     pub fn evaluate(&self, category: Category, confidence: f32) -> Option<ContentRuleResult> {
+        if !self.rules.iter().any(|r| r.category == category) {
+            return self.evaluate_default(category, confidence);
+        }
+
         // Find all matching rules
         let mut results: Vec<ContentRuleResult> = self
             .rules
@@ -277,19 +643,28 @@ impl ContentRuleSet {
             })
             .collect();
 
-        // Sort by action priority: Block > Warn > Allow
-        results.sort_by_key(|r| match r.action {
-            ContentAction::Block => 0,
-            ContentAction::Warn => 1,
-            ContentAction::Allow => 2,
-        });
+        // Most restrictive action first (Block > Warn > Allow), then rule ID
+        results.sort_by(compare_results);
 
         results.into_iter().next()
     }
 
+    /// Applies the default action for a category with no rules.
+    fn evaluate_default(&self, category: Category, confidence: f32) -> Option<ContentRuleResult> {
+        let action = self.default_action(category)?;
+        (confidence >= DEFAULT_ACTION_THRESHOLD).then(|| ContentRuleResult {
+            rule_id: DEFAULT_ACTION_RULE_ID.to_string(),
+            rule_name: format!("Default for {}", category.name()),
+            category,
+            confidence,
+            action,
+        })
+    }
+
     /// Evaluates multiple category matches and returns all rule results.
     ///
-    /// Results are sorted by action priority (Block first).
+    /// Results are sorted by action priority (Block first), then rule ID,
+    /// then category name.
     pub fn evaluate_all(&self, matches: &[(Category, f32)]) -> Vec<ContentRuleResult> {
         let mut results: Vec<ContentRuleResult> = matches
             .iter()
@@ -297,11 +672,7 @@ impl ContentRuleSet {
             .collect();
 
         // Sort by action priority
-        results.sort_by_key(|r| match r.action {
-            ContentAction::Block => 0,
-            ContentAction::Warn => 1,
-            ContentAction::Allow => 2,
-        });
+        results.sort_by(compare_results);
 
         results
     }
@@ -322,10 +693,127 @@ impl ContentRuleSet {
     }
 }
 
+/// Orders results by precedence: action rank, then rule ID, then category.
+fn compare_results(a: &ContentRuleResult, b: &ContentRuleResult) -> std::cmp::Ordering {
+    a.action
+        .rank()
+        .cmp(&b.action.rank())
+        .then_with(|| a.rule_id.cmp(&b.rule_id))
+        .then_with(|| a.category.name().cmp(b.category.name()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn allowlist_entries_match_case_insensitively() {
+        let entry = AllowlistEntry::substring("Chemistry Class");
+        assert!(entry.matches("explosives in CHEMISTRY CLASS"));
+        assert!(!entry.matches("chemistry homework"));
+
+        let entry = AllowlistEntry::regex(r"^how do .+ work").unwrap();
+        assert!(entry.matches("How do volcanoes work?"));
+        assert!(!entry.matches("why do volcanoes erupt"));
+
+        assert!(!AllowlistEntry::substring("  ").matches("anything"));
+    }
+
+    #[test]
+    fn allowlist_persists_in_rule_set_json() {
+        let mut rules = ContentRuleSet::family_safe_defaults();
+        rules.add_allowlist_entry(AllowlistEntry::substring("chemistry class"));
+        rules.add_allowlist_entry(AllowlistEntry::regex(r"\bhistory of war\b").unwrap());
+
+        let json = serde_json::to_value(&rules).unwrap();
+        assert_eq!(
+            json["allowlist"],
+            serde_json::json!([
+                {"pattern": "chemistry class", "match": "substring"},
+                {"pattern": "\\bhistory of war\\b", "match": "regex"}
+            ])
+        );
+
+        let restored: ContentRuleSet = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.allowlist, rules.allowlist);
+        assert!(restored.allowlist_match("the HISTORY OF WAR").is_some());
+
+        // Stored rule sets from before the allowlist still load
+        let legacy: ContentRuleSet = serde_json::from_str(r#"{"rules": []}"#).unwrap();
+        assert!(legacy.allowlist.is_empty());
+        assert!(serde_json::to_value(&legacy)
+            .unwrap()
+            .get("allowlist")
+            .is_none());
+    }
+
+    #[test]
+    fn allowlist_entries_can_be_removed() {
+        let mut rules = ContentRuleSet::new();
+        rules.add_allowlist_entry(AllowlistEntry::substring("chemistry"));
+        assert!(rules.remove_allowlist_entry("chemistry"));
+        assert!(!rules.remove_allowlist_entry("chemistry"));
+        assert!(rules.allowlist_match("chemistry").is_none());
+    }
+
+    #[test]
+    fn model_rules_match_patterns_and_allow_exemptions() {
+        let mut rules = ContentRuleSet::new();
+        rules.add_model_rule(ModelRule::block("no_uncensored", "*uncensored*"));
+        rules.add_model_rule(ModelRule::warn("warn_llama", "llama-*"));
+        rules.add_model_rule(ModelRule::allow("vetted", "llama-3-uncensored-school"));
+
+        fn decide<'a>(rules: &'a ContentRuleSet, model: &str) -> Option<&'a str> {
+            rules.evaluate_model(model).map(|r| r.id.as_str())
+        }
+        assert_eq!(
+            decide(&rules, "Wizard-Uncensored-13B"),
+            Some("no_uncensored")
+        );
+        // Block beats warn when both match
+        assert_eq!(decide(&rules, "llama-2-uncensored"), Some("no_uncensored"));
+        assert_eq!(decide(&rules, "llama-3-8b"), Some("warn_llama"));
+        assert_eq!(decide(&rules, "llama-3-uncensored-school"), None);
+        assert_eq!(decide(&rules, "gpt-4o"), None);
+
+        rules.model_rules[0].enabled = false;
+        assert_eq!(decide(&rules, "Wizard-Uncensored-13B"), None);
+        assert!(rules.remove_model_rule("warn_llama").is_some());
+        assert_eq!(decide(&rules, "llama-3-8b"), None);
+    }
+
+    #[test]
+    fn model_rules_persist_and_validate() {
+        let mut rules = ContentRuleSet::new();
+        rules.add_model_rule(ModelRule::block("no_dolphin", "*dolphin*"));
+        let json = serde_json::to_value(&rules).unwrap();
+        let restored: ContentRuleSet = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.model_rules, rules.model_rules);
+
+        // Older rule sets without model rules still load
+        let old: ContentRuleSet = serde_json::from_str(r#"{"rules": []}"#).unwrap();
+        assert!(old.model_rules.is_empty());
+
+        rules.add_model_rule(ModelRule::warn("no_dolphin", " "));
+        let messages: Vec<String> = rules.validate().into_iter().map(|i| i.message).collect();
+        assert!(messages.contains(&"duplicate model rule ID 'no_dolphin'".to_string()));
+        assert!(messages.contains(&"model pattern is empty".to_string()));
+    }
+
+    #[test]
+    fn validate_reports_bad_allowlist_patterns() {
+        let mut rules = ContentRuleSet::new();
+        rules.allowlist.push(AllowlistEntry::substring(""));
+        rules.allowlist.push(
+            serde_json::from_value(serde_json::json!({"pattern": "(oops", "match": "regex"}))
+                .unwrap(),
+        );
+
+        let issues = rules.validate();
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|issue| issue.is_error()));
+    }
+
     #[test]
     fn content_action_names() {
         assert_eq!(ContentAction::Block.name(), "Block");
@@ -639,6 +1127,26 @@ mod tests {
         assert_eq!(rules.rules.len(), deserialized.rules.len());
     }
 
+    #[test]
+    fn default_actions_round_trip_and_are_optional() {
+        let rules =
+            ContentRuleSet::new().with_default_action(Category::SelfHarm, ContentAction::Block);
+        let json = serde_json::to_value(&rules).unwrap();
+        assert_eq!(json["default_actions"]["self_harm"], "block");
+
+        let deserialized: ContentRuleSet = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            deserialized.default_action(Category::SelfHarm),
+            Some(ContentAction::Block)
+        );
+
+        // Stored rule sets from before default actions still load
+        let legacy: ContentRuleSet = serde_json::from_str(r#"{"rules":[]}"#).unwrap();
+        assert!(legacy.default_actions.is_empty());
+        let json = serde_json::to_value(&legacy).unwrap();
+        assert!(json.get("default_actions").is_none());
+    }
+
     #[test]
     fn content_action_serialization() {
         assert_eq!(
@@ -654,4 +1162,25 @@ mod tests {
             "\"allow\""
         );
     }
+
+    #[test]
+    fn validate_reports_thresholds_and_conflicts() {
+        let mut set = ContentRuleSet::family_safe_defaults();
+        assert!(set.validate().is_empty());
+
+        set.add_rule(ContentRule::warn("violence_warn", Category::Violence, 0.3));
+        let mut bad = ContentRule::block("adult_strict", Category::Adult, 0.5);
+        bad.threshold = 1.5;
+        set.add_rule(bad);
+
+        let issues = set.validate();
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].is_error());
+        assert_eq!(issues[0].message, "threshold 1.5 is outside 0.0-1.0");
+        assert!(!issues[1].is_error());
+        assert_eq!(
+            issues[1].message,
+            "conflicts with 'violence_block' for Violence; when both trigger, 'violence_block' (Block) wins"
+        );
+    }
 }
@@ -0,0 +1,68 @@
+//! Aegis data directory.
+//!
+//! The database, logs, CA, and downloaded models live under the platform
+//! data directory by default. Setting `AEGIS_DATA_DIR` (which
+//! `aegis --data-dir` does for itself and its subprocesses) moves all of
+//! them under one directory instead, for portable installs and testing.
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+
+/// Environment variable that overrides the data directory.
+pub const DATA_DIR_ENV: &str = "AEGIS_DATA_DIR";
+
+/// Returns the data directory override from [`DATA_DIR_ENV`], if set.
+pub fn data_dir_override() -> Option<PathBuf> {
+    override_from(std::env::var_os(DATA_DIR_ENV))
+}
+
+/// Returns the override if set, otherwise the platform data directory for
+/// the given [`ProjectDirs`] identifiers.
+pub fn project_data_dir(qualifier: &str, organization: &str, application: &str) -> Option<PathBuf> {
+    data_dir_override().or_else(|| {
+        ProjectDirs::from(qualifier, organization, application)
+            .map(|dirs| dirs.data_dir().to_path_buf())
+    })
+}
+
+/// Creates `dir` if needed and checks that files can be written in it.
+pub fn ensure_writable(dir: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".aegis-write-test-{}", std::process::id()));
+    std::fs::write(&probe, b"ok")?;
+    std::fs::remove_file(&probe)
+}
+
+fn override_from(value: Option<OsString>) -> Option<PathBuf> {
+    value.filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_override_is_ignored() {
+        assert_eq!(override_from(None), None);
+        assert_eq!(override_from(Some(OsString::new())), None);
+        assert_eq!(
+            override_from(Some(OsString::from("/portable/aegis"))),
+            Some(PathBuf::from("/portable/aegis"))
+        );
+    }
+
+    #[test]
+    fn ensure_writable_creates_directory() {
+        let root = std::env::temp_dir().join(format!("aegis_data_dir_{}", std::process::id()));
+        let dir = root.join("nested").join("data");
+
+        ensure_writable(&dir).unwrap();
+
+        assert!(dir.is_dir());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
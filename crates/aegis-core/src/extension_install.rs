@@ -12,10 +12,25 @@
 //! - **Windows**: Uses registry-based external extensions
 //! - **macOS**: Uses external extensions JSON in Application Support
 //! - **Linux**: Uses external extensions JSON in ~/.config
+//!
+//! # Force-Install
+//!
+//! A registered extension can still be disabled from the browser. For
+//! enforcement, [`force_install_extension`] adds it to the
+//! `ExtensionInstallForcelist` managed policy for Chrome, Edge, and Brave
+//! (machine-wide; needs admin). Force-installed extensions can't be
+//! disabled or removed by the user. [`uninstall_extension`] removes the
+//! policy entries again.
+//!
+//! - **Windows**: `HKLM\SOFTWARE\Policies\<browser>\ExtensionInstallForcelist`
+//! - **Linux**: an `aegis-extension.json` file in the browser's managed policy directory
+//! - **macOS**: not supported; managed policies there require a configuration profile
 
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use crate::extension_token::{write_discovery_file, ExtensionToken};
+
 /// Result of an extension installation attempt.
 #[derive(Debug, Clone)]
 pub struct ExtensionInstallResult {
@@ -76,6 +91,9 @@ pub enum ExtensionInstallError {
 
     #[error("Unsupported platform")]
     UnsupportedPlatform,
+
+    #[error("Invalid extension ID: {0}")]
+    InvalidExtensionId(String),
 }
 
 /// Browser type for extension installation.
@@ -109,21 +127,106 @@ impl Browser {
     }
 }
 
-/// Installs the extension for all supported browsers.
-///
-/// This attempts to install using the external extensions JSON method which
-/// works without admin rights for the current user only.
-pub fn install_extension(extension_path: &Path) -> ExtensionInstallResult {
-    // Validate extension path
+/// Registration state of the extension in one browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtensionStatus {
+    /// The Aegis extension is registered.
+    Installed,
+    /// The browser is present but the registration is gone.
+    Missing,
+    /// The browser doesn't appear to be installed.
+    BrowserNotFound,
+}
+
+/// Extension status for one browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrowserExtensionStatus {
+    /// The browser checked.
+    pub browser: Browser,
+    /// What was found.
+    pub status: ExtensionStatus,
+}
+
+impl BrowserExtensionStatus {
+    /// Returns true if the browser is present but lost the extension.
+    pub fn needs_repair(&self) -> bool {
+        self.status == ExtensionStatus::Missing
+    }
+}
+
+/// Browser-side registration, abstracted so verification and repair can be
+/// tested without touching real browser profiles.
+trait BrowserOps {
+    fn is_present(&self, browser: Browser) -> bool;
+    fn is_registered(&self, browser: Browser) -> bool;
+    fn register(
+        &self,
+        extension_path: &Path,
+        browser: Browser,
+    ) -> Result<(), ExtensionInstallError>;
+}
+
+/// The real registry (Windows) or external extensions directories.
+struct SystemBrowsers;
+
+impl BrowserOps for SystemBrowsers {
+    fn is_present(&self, browser: Browser) -> bool {
+        is_browser_present(browser)
+    }
+
+    fn is_registered(&self, browser: Browser) -> bool {
+        is_installed(browser)
+    }
+
+    fn register(
+        &self,
+        extension_path: &Path,
+        browser: Browser,
+    ) -> Result<(), ExtensionInstallError> {
+        install_for_browser(extension_path, browser)
+    }
+}
+
+/// Returns an error result if `extension_path` isn't an unpacked extension.
+fn validate_extension_path(extension_path: &Path) -> Option<ExtensionInstallResult> {
     if !extension_path.exists() {
-        return ExtensionInstallResult::error(format!(
+        return Some(ExtensionInstallResult::error(format!(
             "Extension path does not exist: {}",
             extension_path.display()
-        ));
+        )));
     }
 
     if !extension_path.join("manifest.json").exists() {
-        return ExtensionInstallResult::error("Invalid extension: manifest.json not found");
+        return Some(ExtensionInstallResult::error(
+            "Invalid extension: manifest.json not found",
+        ));
+    }
+
+    None
+}
+
+/// Copies the app's extension token into the extension directory, so the
+/// extension can authenticate to the local API.
+fn provision_token(extension_path: &Path) -> Option<ExtensionInstallResult> {
+    let written = ExtensionToken::load_or_create_default()
+        .and_then(|token| write_discovery_file(extension_path, &token));
+    written
+        .err()
+        .map(|e| ExtensionInstallResult::error(format!("Failed to write extension token: {}", e)))
+}
+
+/// Installs the extension for all supported browsers.
+///
+/// This attempts to install using the external extensions JSON method which
+/// works without admin rights for the current user only. The extension
+/// directory also gets the discovery file with the API token.
+pub fn install_extension(extension_path: &Path) -> ExtensionInstallResult {
+    // Validate extension path
+    if let Some(invalid) = validate_extension_path(extension_path) {
+        return invalid;
+    }
+    if let Some(failed) = provision_token(extension_path) {
+        return failed;
     }
 
     // Try to install for each browser
@@ -178,6 +281,8 @@ fn install_for_browser(
 }
 
 /// Uninstalls the extension from all browsers.
+///
+/// Removes both the per-user registrations and any force-install policy.
 pub fn uninstall_extension() -> ExtensionInstallResult {
     let mut successes = Vec::new();
     let mut errors = Vec::new();
@@ -189,6 +294,45 @@ pub fn uninstall_extension() -> ExtensionInstallResult {
         }
     }
 
+    let mut policy_errors = Vec::new();
+    let mut needs_admin = false;
+    for (browser, e) in remove_forcelist_entries().1 {
+        needs_admin |= matches!(e, ExtensionInstallError::NeedsAdmin);
+        policy_errors.push(format!("{} force-install policy: {}", browser.name(), e));
+    }
+
+    summarize_uninstall(&successes, &errors, &policy_errors, needs_admin)
+}
+
+/// Builds the uninstall result from the per-browser outcomes.
+///
+/// A force-install policy that could not be removed fails the uninstall,
+/// since the browser would reinstall the extension, but the message still
+/// lists the browsers it was removed from.
+fn summarize_uninstall(
+    successes: &[&str],
+    errors: &[String],
+    policy_errors: &[String],
+    needs_admin: bool,
+) -> ExtensionInstallResult {
+    if !policy_errors.is_empty() {
+        let failures = errors
+            .iter()
+            .chain(policy_errors)
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut message = String::new();
+        if !successes.is_empty() {
+            message = format!("Extension removed from: {}. ", successes.join(", "));
+        }
+        message.push_str(&format!("Failed to uninstall: {}", failures.join("; ")));
+        return if needs_admin {
+            ExtensionInstallResult::needs_admin(message)
+        } else {
+            ExtensionInstallResult::error(message)
+        };
+    }
+
     if successes.is_empty() && errors.is_empty() {
         ExtensionInstallResult::success("No extension installations found to remove")
     } else if !successes.is_empty() {
@@ -225,6 +369,87 @@ fn uninstall_for_browser(browser: Browser) -> Result<(), ExtensionInstallError>
     }
 }
 
+// =============================================================================
+// Verification and Repair
+// =============================================================================
+
+/// Checks the extension registration in every supported browser.
+pub fn verify_extension() -> Vec<BrowserExtensionStatus> {
+    verify_with(&SystemBrowsers)
+}
+
+fn verify_with(ops: &impl BrowserOps) -> Vec<BrowserExtensionStatus> {
+    Browser::all()
+        .iter()
+        .map(|&browser| {
+            let status = if !ops.is_present(browser) {
+                ExtensionStatus::BrowserNotFound
+            } else if ops.is_registered(browser) {
+                ExtensionStatus::Installed
+            } else {
+                ExtensionStatus::Missing
+            };
+            BrowserExtensionStatus { browser, status }
+        })
+        .collect()
+}
+
+/// Re-registers the extension in every installed browser that lost it.
+///
+/// Browsers that still have the extension, and browsers that aren't
+/// installed, are left alone. A missing or stale discovery file in the
+/// extension directory is rewritten either way.
+pub fn repair_extension(extension_path: &Path) -> ExtensionInstallResult {
+    if validate_extension_path(extension_path).is_none() {
+        if let Some(failed) = provision_token(extension_path) {
+            return failed;
+        }
+    }
+    repair_with(&SystemBrowsers, extension_path)
+}
+
+fn repair_with(ops: &impl BrowserOps, extension_path: &Path) -> ExtensionInstallResult {
+    let statuses = verify_with(ops);
+    let missing: Vec<Browser> = statuses
+        .iter()
+        .filter(|s| s.needs_repair())
+        .map(|s| s.browser)
+        .collect();
+
+    if missing.is_empty() {
+        return if statuses
+            .iter()
+            .any(|s| s.status == ExtensionStatus::Installed)
+        {
+            ExtensionInstallResult::success("Extension is installed in every detected browser")
+        } else {
+            ExtensionInstallResult::error("No supported browser found")
+        };
+    }
+
+    if let Some(invalid) = validate_extension_path(extension_path) {
+        return invalid;
+    }
+
+    let mut repaired = Vec::new();
+    let mut errors = Vec::new();
+    for browser in missing {
+        match ops.register(extension_path, browser) {
+            Ok(()) => repaired.push(browser.name()),
+            Err(e) => errors.push(format!("{}: {}", browser.name(), e)),
+        }
+    }
+
+    if errors.is_empty() {
+        ExtensionInstallResult::success(format!(
+            "Extension re-registered for: {}. Restart the browser to apply.",
+            repaired.join(", ")
+        ))
+    } else {
+        ExtensionInstallResult::error(format!("Failed to repair: {}", errors.join("; ")))
+    }
+}
+
 // =============================================================================
 // Windows Implementation
 // =============================================================================
@@ -321,11 +546,16 @@ fn uninstall_windows(browser: Browser) -> Result<(), ExtensionInstallError> {
 
 #[cfg(target_os = "windows")]
 fn get_windows_external_extensions_path(browser: Browser) -> String {
+    format!(r"{}\Extensions", get_windows_browser_key(browser))
+}
+
+#[cfg(target_os = "windows")]
+fn get_windows_browser_key(browser: Browser) -> &'static str {
     match browser {
-        Browser::Chrome => r"Software\Google\Chrome\Extensions".to_string(),
-        Browser::Edge => r"Software\Microsoft\Edge\Extensions".to_string(),
-        Browser::Brave => r"Software\BraveSoftware\Brave-Browser\Extensions".to_string(),
-        Browser::Chromium => r"Software\Chromium\Extensions".to_string(),
+        Browser::Chrome => r"Software\Google\Chrome",
+        Browser::Edge => r"Software\Microsoft\Edge",
+        Browser::Brave => r"Software\BraveSoftware\Brave-Browser",
+        Browser::Chromium => r"Software\Chromium",
     }
 }
 
@@ -451,6 +681,345 @@ fn get_linux_external_extensions_dir(browser: Browser) -> Result<PathBuf, Extens
     Ok(path)
 }
 
+// =============================================================================
+// Managed Policy Force-Install
+// =============================================================================
+
+/// Chrome Web Store update URL, for an extension published there.
+pub const CHROME_WEB_STORE_UPDATE_URL: &str = "https://clients2.google.com/service/update2/crx";
+
+/// Microsoft Edge Add-ons update URL, for an extension published there.
+pub const EDGE_ADDONS_UPDATE_URL: &str = "https://edge.microsoft.com/extensionwebstorebase/v1/crx";
+
+/// Browsers whose `ExtensionInstallForcelist` policy Aegis manages.
+pub const FORCELIST_BROWSERS: &[Browser] = &[Browser::Chrome, Browser::Edge, Browser::Brave];
+
+/// An `ExtensionInstallForcelist` entry for the Aegis extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForceInstallPolicy {
+    /// The 32-character extension ID (letters a–p).
+    pub extension_id: String,
+    /// Update manifest URL the browser installs from.
+    pub update_url: String,
+}
+
+impl ForceInstallPolicy {
+    /// Creates a policy entry, checking the extension ID format.
+    pub fn new(
+        extension_id: impl Into<String>,
+        update_url: impl Into<String>,
+    ) -> Result<Self, ExtensionInstallError> {
+        let extension_id = extension_id.into();
+        let valid =
+            extension_id.len() == 32 && extension_id.bytes().all(|b| (b'a'..=b'p').contains(&b));
+        if !valid {
+            return Err(ExtensionInstallError::InvalidExtensionId(extension_id));
+        }
+        Ok(Self {
+            extension_id,
+            update_url: update_url.into(),
+        })
+    }
+
+    /// Returns the forcelist value, `<id>;<update_url>`.
+    pub fn forcelist_entry(&self) -> String {
+        format!("{};{}", self.extension_id, self.update_url)
+    }
+}
+
+/// Adds the extension to the force-install policy of every supported browser.
+///
+/// Takes effect after the browser restarts or reloads policies. Writing
+/// machine-wide policies needs administrator/root privileges.
+pub fn force_install_extension(policy: &ForceInstallPolicy) -> ExtensionInstallResult {
+    let mut successes = Vec::new();
+    let mut errors = Vec::new();
+    let mut needs_admin = false;
+
+    for browser in FORCELIST_BROWSERS {
+        match write_forcelist_policy(policy, *browser) {
+            Ok(()) => successes.push(browser.name()),
+            Err(e) => {
+                needs_admin |= matches!(e, ExtensionInstallError::NeedsAdmin);
+                errors.push(format!("{}: {}", browser.name(), e));
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        ExtensionInstallResult::success(format!(
+            "Extension force-installed for: {}. Restart browser(s) to apply.",
+            successes.join(", ")
+        ))
+    } else if needs_admin {
+        ExtensionInstallResult::needs_admin(format!(
+            "Force-install requires administrator privileges: {}",
+            errors.join("; ")
+        ))
+    } else {
+        ExtensionInstallResult::error(format!("Failed to force-install: {}", errors.join("; ")))
+    }
+}
+
+/// Removes the Aegis entry from every supported browser's force-install policy.
+///
+/// Other forcelist entries are kept.
+pub fn remove_force_install() -> ExtensionInstallResult {
+    let (removed, errors) = remove_forcelist_entries();
+
+    if !errors.is_empty() {
+        let errors: Vec<String> = errors
+            .iter()
+            .map(|(browser, e)| format!("{}: {}", browser.name(), e))
+            .collect();
+        return ExtensionInstallResult::error(format!(
+            "Failed to remove force-install policy: {}",
+            errors.join("; ")
+        ));
+    }
+
+    if removed.is_empty() {
+        ExtensionInstallResult::success("No force-install policy found to remove")
+    } else {
+        ExtensionInstallResult::success(format!(
+            "Force-install policy removed from: {}",
+            removed.join(", ")
+        ))
+    }
+}
+
+/// Removes the Aegis forcelist entry for each supported browser.
+///
+/// Returns the browsers that had an entry removed and those that failed.
+fn remove_forcelist_entries() -> (Vec<&'static str>, Vec<(Browser, ExtensionInstallError)>) {
+    let mut removed = Vec::new();
+    let mut errors = Vec::new();
+
+    for browser in FORCELIST_BROWSERS {
+        match remove_forcelist_policy(*browser) {
+            Ok(true) => removed.push(browser.name()),
+            Ok(false) => {}
+            Err(e) => errors.push((*browser, e)),
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    if errors.is_empty() {
+        clear_windows_forcelist_record();
+    }
+
+    (removed, errors)
+}
+
+fn write_forcelist_policy(
+    policy: &ForceInstallPolicy,
+    browser: Browser,
+) -> Result<(), ExtensionInstallError> {
+    #[cfg(target_os = "windows")]
+    {
+        write_forcelist_windows(policy, browser)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        write_policy_file(&get_linux_policy_dir(browser), policy)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = (policy, browser);
+        Err(ExtensionInstallError::UnsupportedPlatform)
+    }
+}
+
+/// Removes the Aegis policy entry, returning whether there was one.
+fn remove_forcelist_policy(browser: Browser) -> Result<bool, ExtensionInstallError> {
+    #[cfg(target_os = "windows")]
+    {
+        remove_forcelist_windows(browser)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        remove_policy_file(&get_linux_policy_dir(browser))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    {
+        let _ = browser;
+        Ok(false)
+    }
+}
+
+/// Returns `entries` with `entry` added, replacing any entry for the same ID.
+#[cfg(any(target_os = "windows", test))]
+fn forcelist_with(entries: &[String], policy: &ForceInstallPolicy) -> Vec<String> {
+    let mut updated = forcelist_without(entries, &policy.extension_id);
+    updated.push(policy.forcelist_entry());
+    updated
+}
+
+/// Returns `entries` without any entry for `extension_id`.
+#[cfg(any(target_os = "windows", test))]
+fn forcelist_without(entries: &[String], extension_id: &str) -> Vec<String> {
+    entries
+        .iter()
+        .filter(|entry| entry.split(';').next() != Some(extension_id))
+        .cloned()
+        .collect()
+}
+
+/// Maps a permission error to [`ExtensionInstallError::NeedsAdmin`].
+#[cfg(any(target_os = "windows", target_os = "linux", test))]
+fn policy_io_error(e: std::io::Error) -> ExtensionInstallError {
+    if e.kind() == std::io::ErrorKind::PermissionDenied {
+        ExtensionInstallError::NeedsAdmin
+    } else {
+        ExtensionInstallError::FileSystemError(e)
+    }
+}
+
+/// Policy file Aegis owns in a browser's managed policy directory.
+#[cfg(any(target_os = "linux", test))]
+const POLICY_FILE_NAME: &str = "aegis-extension.json";
+
+/// Writes the Aegis policy file into a managed policy directory.
+#[cfg(any(target_os = "linux", test))]
+fn write_policy_file(dir: &Path, policy: &ForceInstallPolicy) -> Result<(), ExtensionInstallError> {
+    std::fs::create_dir_all(dir).map_err(policy_io_error)?;
+    let content = serde_json::json!({
+        "ExtensionInstallForcelist": [policy.forcelist_entry()]
+    });
+    std::fs::write(
+        dir.join(POLICY_FILE_NAME),
+        serde_json::to_string_pretty(&content).unwrap_or_default(),
+    )
+    .map_err(policy_io_error)
+}
+
+/// Deletes the Aegis policy file, returning whether it existed.
+#[cfg(any(target_os = "linux", test))]
+fn remove_policy_file(dir: &Path) -> Result<bool, ExtensionInstallError> {
+    match std::fs::remove_file(dir.join(POLICY_FILE_NAME)) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(policy_io_error(e)),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn get_linux_policy_dir(browser: Browser) -> PathBuf {
+    PathBuf::from(match browser {
+        Browser::Chrome => "/etc/opt/chrome/policies/managed",
+        Browser::Edge => "/etc/opt/edge/policies/managed",
+        Browser::Brave => "/etc/brave/policies/managed",
+        Browser::Chromium => "/etc/chromium/policies/managed",
+    })
+}
+
+/// Key and value where the force-installed extension ID is recorded, so
+/// uninstall can find its entries among other forcelist values.
+#[cfg(target_os = "windows")]
+const WINDOWS_FORCELIST_RECORD: (&str, &str) = (r"SOFTWARE\Aegis", "ForceInstalledExtensionId");
+
+#[cfg(target_os = "windows")]
+fn get_windows_forcelist_path(browser: Browser) -> &'static str {
+    match browser {
+        Browser::Chrome => r"SOFTWARE\Policies\Google\Chrome\ExtensionInstallForcelist",
+        Browser::Edge => r"SOFTWARE\Policies\Microsoft\Edge\ExtensionInstallForcelist",
+        Browser::Brave => r"SOFTWARE\Policies\BraveSoftware\Brave\ExtensionInstallForcelist",
+        Browser::Chromium => r"SOFTWARE\Policies\Chromium\ExtensionInstallForcelist",
+    }
+}
+
+/// Reads a forcelist key. Browsers read values "1", "2", ... up to the first gap.
+#[cfg(target_os = "windows")]
+fn read_windows_forcelist(key: &winreg::RegKey) -> Vec<String> {
+    (1..)
+        .map_while(|i: usize| key.get_value::<String, _>(i.to_string()).ok())
+        .collect()
+}
+
+/// Rewrites a forcelist key as `entries`, numbered from 1 without gaps.
+#[cfg(target_os = "windows")]
+fn write_windows_forcelist(
+    key: &winreg::RegKey,
+    old_len: usize,
+    entries: &[String],
+) -> Result<(), ExtensionInstallError> {
+    for i in entries.len() + 1..=old_len {
+        let _ = key.delete_value(i.to_string());
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        key.set_value((i + 1).to_string(), entry)
+            .map_err(policy_io_error)?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn write_forcelist_windows(
+    policy: &ForceInstallPolicy,
+    browser: Browser,
+) -> Result<(), ExtensionInstallError> {
+    use winreg::enums::*;
+    use winreg::RegKey;
+
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let (key, _) = hklm
+        .create_subkey(get_windows_forcelist_path(browser))
+        .map_err(policy_io_error)?;
+    let current = read_windows_forcelist(&key);
+    write_windows_forcelist(&key, current.len(), &forcelist_with(&current, policy))?;
+
+    let (record_key, record_value) = WINDOWS_FORCELIST_RECORD;
+    let (record, _) = hklm.create_subkey(record_key).map_err(policy_io_error)?;
+    record
+        .set_value(record_value, &policy.extension_id)
+        .map_err(policy_io_error)
+}
+
+#[cfg(target_os = "windows")]
+fn remove_forcelist_windows(browser: Browser) -> Result<bool, ExtensionInstallError> {
+    use winreg::enums::*;
+    use winreg::RegKey;
+
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    let (record_key, record_value) = WINDOWS_FORCELIST_RECORD;
+    let Ok(extension_id) = hklm
+        .open_subkey(record_key)
+        .and_then(|record| record.get_value::<String, _>(record_value))
+    else {
+        return Ok(false);
+    };
+    let Ok(key) =
+        hklm.open_subkey_with_flags(get_windows_forcelist_path(browser), KEY_READ | KEY_WRITE)
+    else {
+        return Ok(false);
+    };
+
+    let current = read_windows_forcelist(&key);
+    let updated = forcelist_without(&current, &extension_id);
+    if updated.len() == current.len() {
+        return Ok(false);
+    }
+    write_windows_forcelist(&key, current.len(), &updated)?;
+    Ok(true)
+}
+
+#[cfg(target_os = "windows")]
+fn clear_windows_forcelist_record() {
+    use winreg::enums::*;
+    use winreg::RegKey;
+
+    let (record_key, record_value) = WINDOWS_FORCELIST_RECORD;
+    if let Ok(record) =
+        RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey_with_flags(record_key, KEY_WRITE)
+    {
+        let _ = record.delete_value(record_value);
+    }
+}
+
 // =============================================================================
 // Shared Helpers
 // =============================================================================
@@ -566,52 +1135,68 @@ pub fn get_extension_path() -> Option<PathBuf> {
 
 /// Checks if the extension appears to be installed for any browser.
 pub fn is_extension_installed() -> bool {
+    Browser::all().iter().any(|&browser| is_installed(browser))
+}
+
+/// Checks if the extension is registered for `browser`.
+pub fn is_installed(browser: Browser) -> bool {
     #[cfg(target_os = "windows")]
     {
         use winreg::enums::*;
         use winreg::RegKey;
 
         let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        hkcu.open_subkey(get_windows_external_extensions_path(browser))
+            .is_ok_and(|key| key.get_value::<String, _>("aegis-extension").is_ok())
+    }
 
-        for browser in Browser::all() {
-            let reg_path = get_windows_external_extensions_path(*browser);
-            if let Ok(key) = hkcu.open_subkey(&reg_path) {
-                if key.get_value::<String, _>("aegis-extension").is_ok() {
-                    return true;
-                }
-            }
-        }
+    #[cfg(target_os = "macos")]
+    {
+        get_macos_external_extensions_dir(browser)
+            .is_ok_and(|dir| dir.join("aegis-extension.json").exists())
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        get_linux_external_extensions_dir(browser)
+            .is_ok_and(|dir| dir.join("aegis-extension.json").exists())
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        let _ = browser;
         false
     }
+}
+
+/// Checks if `browser` appears to be installed for the current user.
+fn is_browser_present(browser: Browser) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey(get_windows_browser_key(browser))
+            .is_ok()
+    }
 
+    // The external extensions directory sits inside the browser's profile root
     #[cfg(target_os = "macos")]
     {
-        for browser in Browser::all() {
-            if let Ok(ext_dir) = get_macos_external_extensions_dir(*browser) {
-                let json_path = ext_dir.join("aegis-extension.json");
-                if json_path.exists() {
-                    return true;
-                }
-            }
-        }
-        false
+        get_macos_external_extensions_dir(browser)
+            .is_ok_and(|dir| dir.parent().is_some_and(Path::exists))
     }
 
     #[cfg(target_os = "linux")]
     {
-        for browser in Browser::all() {
-            if let Ok(ext_dir) = get_linux_external_extensions_dir(*browser) {
-                let json_path = ext_dir.join("aegis-extension.json");
-                if json_path.exists() {
-                    return true;
-                }
-            }
-        }
-        false
+        get_linux_external_extensions_dir(browser)
+            .is_ok_and(|dir| dir.parent().is_some_and(Path::exists))
     }
 
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
+        let _ = browser;
         false
     }
 }
@@ -643,6 +1228,221 @@ mod tests {
         assert!(browsers.contains(&Browser::Edge));
     }
 
+    /// Browsers with fixed presence; `register` adds to the registered set.
+    #[derive(Default)]
+    struct MockBrowsers {
+        present: Vec<Browser>,
+        registered: std::cell::RefCell<Vec<Browser>>,
+        fail: Option<Browser>,
+    }
+
+    impl BrowserOps for MockBrowsers {
+        fn is_present(&self, browser: Browser) -> bool {
+            self.present.contains(&browser)
+        }
+
+        fn is_registered(&self, browser: Browser) -> bool {
+            self.registered.borrow().contains(&browser)
+        }
+
+        fn register(
+            &self,
+            _extension_path: &Path,
+            browser: Browser,
+        ) -> Result<(), ExtensionInstallError> {
+            if self.fail == Some(browser) {
+                return Err(ExtensionInstallError::NeedsAdmin);
+            }
+            self.registered.borrow_mut().push(browser);
+            Ok(())
+        }
+    }
+
+    /// Creates an unpacked extension with just a manifest.
+    fn extension_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("aegis_extension_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("manifest.json"), r#"{"version":"1.0.0"}"#).unwrap();
+        dir
+    }
+
+    fn status_of(statuses: &[BrowserExtensionStatus], browser: Browser) -> ExtensionStatus {
+        statuses
+            .iter()
+            .find(|s| s.browser == browser)
+            .map(|s| s.status)
+            .unwrap()
+    }
+
+    #[test]
+    fn verify_detects_missing_extension() {
+        let browsers = MockBrowsers {
+            present: vec![Browser::Chrome, Browser::Edge],
+            registered: std::cell::RefCell::new(vec![Browser::Chrome]),
+            ..Default::default()
+        };
+
+        let statuses = verify_with(&browsers);
+
+        assert_eq!(statuses.len(), Browser::all().len());
+        assert_eq!(
+            status_of(&statuses, Browser::Chrome),
+            ExtensionStatus::Installed
+        );
+        assert_eq!(
+            status_of(&statuses, Browser::Edge),
+            ExtensionStatus::Missing
+        );
+        assert_eq!(
+            status_of(&statuses, Browser::Brave),
+            ExtensionStatus::BrowserNotFound
+        );
+    }
+
+    #[test]
+    fn repair_reregisters_only_missing_browsers() {
+        let dir = extension_dir("repair");
+        let browsers = MockBrowsers {
+            present: vec![Browser::Chrome, Browser::Edge],
+            registered: std::cell::RefCell::new(vec![Browser::Chrome]),
+            ..Default::default()
+        };
+
+        let result = repair_with(&browsers, &dir);
+
+        assert!(result.success, "{}", result.message);
+        assert!(result.message.contains("Microsoft Edge"));
+        assert_eq!(
+            *browsers.registered.borrow(),
+            vec![Browser::Chrome, Browser::Edge]
+        );
+        assert!(verify_with(&browsers).iter().all(|s| !s.needs_repair()));
+
+        // Nothing left to repair
+        let again = repair_with(&browsers, &dir);
+        assert!(again.success);
+        assert_eq!(browsers.registered.borrow().len(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn repair_reports_failures_and_missing_browsers() {
+        let dir = extension_dir("failures");
+        let browsers = MockBrowsers {
+            present: vec![Browser::Chrome],
+            fail: Some(Browser::Chrome),
+            ..Default::default()
+        };
+        let result = repair_with(&browsers, &dir);
+        assert!(!result.success);
+        assert!(result.message.contains("Google Chrome"));
+
+        let result = repair_with(&MockBrowsers::default(), &dir);
+        assert!(!result.success);
+        assert_eq!(result.message, "No supported browser found");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn repair_validates_extension_path() {
+        let browsers = MockBrowsers {
+            present: vec![Browser::Chrome],
+            ..Default::default()
+        };
+        let missing = std::env::temp_dir().join("aegis_extension_does_not_exist");
+
+        let result = repair_with(&browsers, &missing);
+
+        assert!(!result.success);
+        assert!(browsers.registered.borrow().is_empty());
+    }
+
+    const TEST_EXTENSION_ID: &str = "abcdefghijklmnopabcdefghijklmnop";
+
+    fn test_policy() -> ForceInstallPolicy {
+        ForceInstallPolicy::new(TEST_EXTENSION_ID, CHROME_WEB_STORE_UPDATE_URL).unwrap()
+    }
+
+    #[test]
+    fn force_install_policy_validates_id() {
+        assert_eq!(
+            test_policy().forcelist_entry(),
+            format!("{};{}", TEST_EXTENSION_ID, CHROME_WEB_STORE_UPDATE_URL)
+        );
+        for bad in ["aegis-extension", "abcdefghijklmnopabcdefghijklmnoz", ""] {
+            assert!(matches!(
+                ForceInstallPolicy::new(bad, CHROME_WEB_STORE_UPDATE_URL),
+                Err(ExtensionInstallError::InvalidExtensionId(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn forcelist_add_and_remove_keep_other_entries() {
+        let other = "ponmlkjihgfedcbaponmlkjihgfedcba;https://example.com/crx".to_string();
+        let stale = format!("{};https://old.example.com/crx", TEST_EXTENSION_ID);
+
+        let added = forcelist_with(&[other.clone(), stale], &test_policy());
+        assert_eq!(added, vec![other.clone(), test_policy().forcelist_entry()]);
+
+        let removed = forcelist_without(&added, TEST_EXTENSION_ID);
+        assert_eq!(removed, vec![other.clone()]);
+        assert_eq!(forcelist_without(&removed, TEST_EXTENSION_ID), vec![other]);
+    }
+
+    #[test]
+    fn policy_file_written_and_removed() {
+        let dir = std::env::temp_dir().join(format!("aegis_policies_{}", std::process::id()));
+
+        write_policy_file(&dir, &test_policy()).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join(POLICY_FILE_NAME)).unwrap())
+                .unwrap();
+        assert_eq!(
+            written["ExtensionInstallForcelist"],
+            serde_json::json!([test_policy().forcelist_entry()])
+        );
+
+        assert!(remove_policy_file(&dir).unwrap());
+        assert!(!dir.join(POLICY_FILE_NAME).exists());
+        assert!(!remove_policy_file(&dir).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn uninstall_summary_keeps_browser_results_when_policy_removal_fails() {
+        let errors = vec!["Firefox: Not found".to_string()];
+        let policy_errors = vec!["Chrome force-install policy: denied".to_string()];
+
+        let result = summarize_uninstall(&["Chrome", "Edge"], &errors, &policy_errors, false);
+        assert!(!result.success);
+        assert!(!result.needs_admin);
+        assert_eq!(
+            result.message,
+            "Extension removed from: Chrome, Edge. Failed to uninstall: \
+             Firefox: Not found; Chrome force-install policy: denied"
+        );
+
+        let result = summarize_uninstall(&[], &[], &policy_errors, true);
+        assert!(result.needs_admin);
+        assert_eq!(
+            result.message,
+            "Failed to uninstall: Chrome force-install policy: denied"
+        );
+
+        assert!(summarize_uninstall(&["Chrome"], &errors, &[], false).success);
+    }
+
+    #[test]
+    fn permission_errors_need_admin() {
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(matches!(
+            policy_io_error(denied),
+            ExtensionInstallError::NeedsAdmin
+        ));
+    }
+
     #[test]
     fn test_get_extension_path() {
         // This will likely return None in test environment
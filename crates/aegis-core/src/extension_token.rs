@@ -0,0 +1,196 @@
+//! Shared token authenticating the browser extension to the local API.
+//!
+//! Any local page or process can reach the API on localhost, so extension
+//! endpoints require a secret only the installed extension knows. The token
+//! is created once and kept in the data directory; installing the extension
+//! copies it into the extension directory as [`DISCOVERY_FILE_NAME`], which
+//! the extension reads from its own package and sends back in the
+//! [`EXTENSION_TOKEN_HEADER`] header.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::data_dir::project_data_dir;
+
+/// Header carrying the extension token.
+pub const EXTENSION_TOKEN_HEADER: &str = "x-aegis-extension-token";
+
+/// Token file name inside the data directory.
+pub const TOKEN_FILE_NAME: &str = "extension-token";
+
+/// Discovery file name inside the extension directory.
+pub const DISCOVERY_FILE_NAME: &str = "aegis-connection.json";
+
+/// Random bytes in a generated token.
+const TOKEN_BYTES: usize = 32;
+
+/// Secret shared between the app and its browser extension.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ExtensionToken(String);
+
+impl std::fmt::Debug for ExtensionToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ExtensionToken(<redacted>)")
+    }
+}
+
+impl ExtensionToken {
+    /// Generates a new random token.
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        Self(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// Returns the token as sent by the extension.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns true if `candidate` is this token.
+    ///
+    /// Compares in constant time so the token can't be guessed byte by byte.
+    pub fn verify(&self, candidate: &str) -> bool {
+        let expected = self.0.as_bytes();
+        let candidate = candidate.as_bytes();
+        expected.len() == candidate.len()
+            && expected
+                .iter()
+                .zip(candidate)
+                .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// Reads the token stored at `path`, creating and storing one if none exists.
+    pub fn load_or_create(path: &Path) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) if !contents.trim().is_empty() => {
+                return Ok(Self(contents.trim().to_string()))
+            }
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let token = Self::generate();
+        write_private(path, token.as_str())?;
+        Ok(token)
+    }
+
+    /// Reads or creates the token in the Aegis data directory.
+    pub fn load_or_create_default() -> io::Result<Self> {
+        let path = default_token_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        Self::load_or_create(&path)
+    }
+}
+
+/// Contents of the discovery file the extension reads.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Discovery {
+    /// Token to send in [`EXTENSION_TOKEN_HEADER`].
+    pub token: String,
+}
+
+/// Writes the discovery file for `token` into the extension directory.
+pub fn write_discovery_file(extension_dir: &Path, token: &ExtensionToken) -> io::Result<()> {
+    let discovery = Discovery {
+        token: token.as_str().to_string(),
+    };
+    let json = serde_json::to_string_pretty(&discovery).map_err(io::Error::other)?;
+    std::fs::write(extension_dir.join(DISCOVERY_FILE_NAME), json)
+}
+
+/// Returns the token file path in the Aegis data directory.
+pub fn default_token_path() -> Option<PathBuf> {
+    project_data_dir("", "aegis", "Aegis").map(|dir| dir.join(TOKEN_FILE_NAME))
+}
+
+/// Writes a file readable only by the current user where supported.
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(contents.as_bytes())
+    }
+
+    #[cfg(not(unix))]
+    {
+        std::fs::write(path, contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("aegis_ext_token_{}_{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn generated_tokens_are_random_hex() {
+        let a = ExtensionToken::generate();
+        let b = ExtensionToken::generate();
+        assert_eq!(a.as_str().len(), TOKEN_BYTES * 2);
+        assert!(a.as_str().bytes().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+        assert!(!format!("{:?}", a).contains(a.as_str()));
+    }
+
+    #[test]
+    fn verify_requires_exact_token() {
+        let token = ExtensionToken::generate();
+        assert!(token.verify(token.as_str()));
+        assert!(!token.verify(""));
+        assert!(!token.verify(&token.as_str()[1..]));
+        assert!(!token.verify(&token.as_str().to_uppercase()));
+    }
+
+    #[test]
+    fn load_or_create_keeps_the_stored_token() {
+        let dir = temp_dir("load");
+        let path = dir.join(TOKEN_FILE_NAME);
+
+        let created = ExtensionToken::load_or_create(&path).unwrap();
+        let loaded = ExtensionToken::load_or_create(&path).unwrap();
+        assert_eq!(created, loaded);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn discovery_file_carries_the_token() {
+        let dir = temp_dir("discovery");
+        std::fs::create_dir_all(&dir).unwrap();
+        let token = ExtensionToken::generate();
+
+        write_discovery_file(&dir, &token).unwrap();
+        let discovery: Discovery =
+            serde_json::from_str(&std::fs::read_to_string(dir.join(DISCOVERY_FILE_NAME)).unwrap())
+                .unwrap();
+        assert!(token.verify(&discovery.token));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,595 @@
+//! Bulk import of keyword blocklists (F025).
+//!
+//! Schools and parents keep blocklists in spreadsheets. This module parses a
+//! CSV or JSON list of terms into [`ParentOverrides`], so imported terms are
+//! matched by the community rule manager like hand-entered blacklist terms.
+//!
+//! CSV columns are `term,category,action,threshold`; only `term` is required.
+//! A header row is optional and, when present, may list the columns in any
+//! order:
+//!
+//! ```text
+//! term,category,action
+//! gore,violence,block
+//! homework help,,allow
+//! ```
+//!
+//! JSON is an array of terms or term objects:
+//!
+//! ```json
+//! ["gore", {"term": "homework help", "action": "allow"}]
+//! ```
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::classifier::Category;
+use crate::community_rules::ParentOverrides;
+
+/// Maximum length of an imported term, in characters.
+pub const MAX_TERM_LEN: usize = 100;
+
+/// Errors from parsing a keyword list.
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum KeywordImportError {
+    /// The file isn't valid JSON.
+    #[error("invalid JSON: {0}")]
+    Json(String),
+
+    /// A row failed validation.
+    #[error("line {line}: {message}")]
+    InvalidRow {
+        /// 1-based line number (CSV) or entry number (JSON).
+        line: usize,
+        /// What was wrong with the row.
+        message: String,
+    },
+
+    /// Two rows set different thresholds for the same category.
+    #[error("conflicting thresholds for {}: {first} and {second}", category.name())]
+    ConflictingThreshold {
+        /// The category.
+        category: Category,
+        /// Threshold set first.
+        first: f32,
+        /// Threshold set later.
+        second: f32,
+    },
+}
+
+/// Format of a keyword list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeywordFormat {
+    /// Comma-separated values.
+    Csv,
+    /// JSON array.
+    Json,
+}
+
+impl KeywordFormat {
+    /// Picks the format from a file extension, if recognized.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "csv" => Some(KeywordFormat::Csv),
+            "json" => Some(KeywordFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from content: JSON if it starts with `[`.
+    pub fn detect(content: &str) -> Self {
+        if content.trim_start().starts_with('[') {
+            KeywordFormat::Json
+        } else {
+            KeywordFormat::Csv
+        }
+    }
+}
+
+/// What to do when an imported term matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeywordAction {
+    /// Add the term to the blacklist.
+    #[default]
+    Block,
+    /// Add the term to the whitelist so it is never blocked.
+    Allow,
+}
+
+/// A validated term from a keyword list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeywordEntry {
+    /// The term, trimmed and lowercased.
+    pub term: String,
+    /// Category the term is blocked under.
+    pub category: Category,
+    /// Whether the term is blocked or allowed.
+    pub action: KeywordAction,
+    /// Optional threshold override for the term's category.
+    pub threshold: Option<f32>,
+}
+
+/// A parsed, de-duplicated keyword list.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeywordImport {
+    /// Unique terms, in file order.
+    pub entries: Vec<KeywordEntry>,
+    /// Rows skipped because their term appeared earlier in the file.
+    pub duplicates: usize,
+}
+
+/// Outcome of applying a [`KeywordImport`] to [`ParentOverrides`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Terms added to the blacklist.
+    pub blocked: usize,
+    /// Terms added to the whitelist.
+    pub allowed: usize,
+    /// Terms that were already configured the same way.
+    pub unchanged: usize,
+    /// Rows skipped as duplicates within the file.
+    pub duplicates: usize,
+}
+
+/// A term as written in the file, before validation.
+#[derive(Debug, Default, Deserialize)]
+struct RawEntry {
+    term: String,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    action: Option<String>,
+    #[serde(default)]
+    threshold: Option<f32>,
+}
+
+/// JSON entries may be bare terms or objects.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonEntry {
+    Term(String),
+    Entry(RawEntry),
+}
+
+impl KeywordImport {
+    /// Parses `content` in the given format.
+    pub fn parse(content: &str, format: KeywordFormat) -> Result<Self, KeywordImportError> {
+        match format {
+            KeywordFormat::Csv => Self::from_csv(content),
+            KeywordFormat::Json => Self::from_json(content),
+        }
+    }
+
+    /// Parses a CSV keyword list.
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn from_csv(content: &str) -> Result<Self, KeywordImportError> {
+        let mut rows = content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .peekable();
+
+        // Column positions: term, category, action, threshold
+        let mut columns = [0, 1, 2, 3].map(Some);
+        if let Some((_, first)) = rows.peek() {
+            let header = split_csv_line(first);
+            if header.iter().any(|cell| cell.eq_ignore_ascii_case("term")) {
+                columns = ["term", "category", "action", "threshold"]
+                    .map(|name| header.iter().position(|c| c.eq_ignore_ascii_case(name)));
+                rows.next();
+            }
+        }
+        let [term_col, category_col, action_col, threshold_col] = columns;
+
+        let mut raw = Vec::new();
+        for (line, row) in rows {
+            let cells = split_csv_line(row);
+            let cell = |col: Option<usize>| {
+                col.and_then(|c| cells.get(c))
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+            };
+
+            let threshold = match cell(threshold_col) {
+                Some(value) => {
+                    Some(
+                        value
+                            .parse::<f32>()
+                            .map_err(|_| KeywordImportError::InvalidRow {
+                                line,
+                                message: format!("threshold '{}' is not a number", value),
+                            })?,
+                    )
+                }
+                None => None,
+            };
+
+            raw.push((
+                line,
+                RawEntry {
+                    term: cell(term_col).unwrap_or_default(),
+                    category: cell(category_col),
+                    action: cell(action_col),
+                    threshold,
+                },
+            ));
+        }
+
+        Self::from_raw(raw)
+    }
+
+    /// Parses a JSON keyword list.
+    pub fn from_json(content: &str) -> Result<Self, KeywordImportError> {
+        let entries: Vec<JsonEntry> =
+            serde_json::from_str(content).map_err(|e| KeywordImportError::Json(e.to_string()))?;
+
+        let raw = entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let raw = match entry {
+                    JsonEntry::Term(term) => RawEntry {
+                        term,
+                        ..Default::default()
+                    },
+                    JsonEntry::Entry(raw) => raw,
+                };
+                (i + 1, raw)
+            })
+            .collect();
+
+        Self::from_raw(raw)
+    }
+
+    /// Validates and de-duplicates raw rows.
+    fn from_raw(raw: Vec<(usize, RawEntry)>) -> Result<Self, KeywordImportError> {
+        let mut import = KeywordImport::default();
+        let mut seen = HashSet::new();
+        let mut thresholds: Vec<(Category, f32)> = Vec::new();
+
+        for (line, raw) in raw {
+            let entry = validate(line, raw)?;
+
+            if let Some(threshold) = entry.threshold {
+                match thresholds.iter().find(|(c, _)| *c == entry.category) {
+                    Some((_, first)) if *first != threshold => {
+                        return Err(KeywordImportError::ConflictingThreshold {
+                            category: entry.category,
+                            first: *first,
+                            second: threshold,
+                        });
+                    }
+                    Some(_) => {}
+                    None => thresholds.push((entry.category, threshold)),
+                }
+            }
+
+            if seen.insert(entry.term.clone()) {
+                import.entries.push(entry);
+            } else {
+                import.duplicates += 1;
+            }
+        }
+
+        Ok(import)
+    }
+
+    /// Returns true if the list has no terms.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds the imported terms to `overrides`.
+    ///
+    /// Blocked terms go to the blacklist and allowed terms to the whitelist;
+    /// a term moves between the two if the import says so. Thresholds become
+    /// per-category threshold overrides.
+    pub fn apply(&self, overrides: &mut ParentOverrides) -> ImportSummary {
+        let mut summary = ImportSummary {
+            duplicates: self.duplicates,
+            ..Default::default()
+        };
+
+        for entry in &self.entries {
+            match entry.action {
+                KeywordAction::Block => {
+                    if overrides.get_blacklist_category(&entry.term) == Some(entry.category) {
+                        summary.unchanged += 1;
+                    } else {
+                        overrides.remove_whitelist(&entry.term);
+                        overrides.add_blacklist(entry.term.clone(), entry.category);
+                        summary.blocked += 1;
+                    }
+                }
+                KeywordAction::Allow => {
+                    if overrides.is_whitelisted(&entry.term) {
+                        summary.unchanged += 1;
+                    } else {
+                        overrides.remove_blacklist(&entry.term);
+                        overrides.add_whitelist(entry.term.clone());
+                        summary.allowed += 1;
+                    }
+                }
+            }
+
+            if let Some(threshold) = entry.threshold {
+                overrides.set_category_threshold(entry.category, threshold);
+            }
+        }
+
+        summary
+    }
+}
+
+/// Validates a raw row into an entry.
+fn validate(line: usize, raw: RawEntry) -> Result<KeywordEntry, KeywordImportError> {
+    let invalid = |message: String| KeywordImportError::InvalidRow { line, message };
+
+    let term = raw.term.trim().to_lowercase();
+    if term.is_empty() {
+        return Err(invalid("term is empty".to_string()));
+    }
+    if term.chars().count() > MAX_TERM_LEN {
+        return Err(invalid(format!(
+            "term is longer than {} characters",
+            MAX_TERM_LEN
+        )));
+    }
+    if term.chars().any(char::is_control) {
+        return Err(invalid("term contains control characters".to_string()));
+    }
+
+    let category = match raw.category.as_deref().map(str::trim) {
+        None | Some("") => Category::Profanity,
+        Some(value) => {
+            parse_category(value).ok_or_else(|| invalid(format!("unknown category '{}'", value)))?
+        }
+    };
+
+    let action = match raw.action.as_deref().map(str::trim) {
+        None | Some("") => KeywordAction::default(),
+        Some(value) => match value.to_lowercase().as_str() {
+            "block" => KeywordAction::Block,
+            "allow" => KeywordAction::Allow,
+            "warn" => {
+                return Err(invalid(
+                    "action 'warn' is set per category in content rules, not per term".to_string(),
+                ))
+            }
+            _ => return Err(invalid(format!("unknown action '{}'", value))),
+        },
+    };
+
+    if let Some(threshold) = raw.threshold {
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(invalid(format!(
+                "threshold {} is not between 0 and 1",
+                threshold
+            )));
+        }
+    }
+
+    Ok(KeywordEntry {
+        term,
+        category,
+        action,
+        threshold: raw.threshold,
+    })
+}
+
+/// Parses a category name, accepting the spellings used in spreadsheets.
+fn parse_category(s: &str) -> Option<Category> {
+    match s.to_lowercase().as_str() {
+        "violence" => Some(Category::Violence),
+        "selfharm" | "self_harm" | "self-harm" => Some(Category::SelfHarm),
+        "adult" | "sexual" => Some(Category::Adult),
+        "jailbreak" => Some(Category::Jailbreak),
+        "hate" | "hate_speech" => Some(Category::Hate),
+        "illegal" => Some(Category::Illegal),
+        "profanity" | "offensive" => Some(Category::Profanity),
+        _ => None,
+    }
+}
+
+/// Splits a CSV line on commas, honoring double-quoted fields.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell);
+
+    cells.into_iter().map(|c| c.trim().to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::community_rules::CommunityRuleManager;
+
+    const SCHOOL_CSV: &str = "\
+term,category,action,threshold
+Gore,violence,block,
+\"knife, fight\",violence,,0.6
+homework help,,allow,
+gore,violence,block,
+";
+
+    fn import(csv: &str) -> KeywordImport {
+        KeywordImport::from_csv(csv).unwrap()
+    }
+
+    #[test]
+    fn csv_with_header_is_parsed_and_deduplicated() {
+        let import = import(SCHOOL_CSV);
+
+        assert_eq!(import.duplicates, 1);
+        assert_eq!(
+            import.entries,
+            vec![
+                KeywordEntry {
+                    term: "gore".to_string(),
+                    category: Category::Violence,
+                    action: KeywordAction::Block,
+                    threshold: None,
+                },
+                KeywordEntry {
+                    term: "knife, fight".to_string(),
+                    category: Category::Violence,
+                    action: KeywordAction::Block,
+                    threshold: Some(0.6),
+                },
+                KeywordEntry {
+                    term: "homework help".to_string(),
+                    category: Category::Profanity,
+                    action: KeywordAction::Allow,
+                    threshold: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_without_header_uses_positional_columns() {
+        let import = import("# school list\nbadword\nslur,hate\n");
+        assert_eq!(import.entries.len(), 2);
+        assert_eq!(import.entries[0].category, Category::Profanity);
+        assert_eq!(import.entries[1].category, Category::Hate);
+    }
+
+    #[test]
+    fn csv_header_columns_can_be_reordered() {
+        let import = import("category,term\nadult,explicit\n");
+        assert_eq!(import.entries[0].term, "explicit");
+        assert_eq!(import.entries[0].category, Category::Adult);
+    }
+
+    #[test]
+    fn json_accepts_terms_and_objects() {
+        let import = KeywordImport::from_json(
+            r#"["gore", {"term": "slur", "category": "hate", "threshold": 0.5}, "GORE"]"#,
+        )
+        .unwrap();
+
+        assert_eq!(import.entries.len(), 2);
+        assert_eq!(import.duplicates, 1);
+        assert_eq!(import.entries[1].category, Category::Hate);
+        assert_eq!(import.entries[1].threshold, Some(0.5));
+    }
+
+    #[test]
+    fn invalid_rows_are_rejected_with_line_numbers() {
+        let error = |csv: &str| KeywordImport::from_csv(csv).unwrap_err();
+
+        assert!(matches!(
+            error("term,category\ngore,gory\n"),
+            KeywordImportError::InvalidRow { line: 2, .. }
+        ));
+        assert!(matches!(
+            error("term,action\ngore,warn\n"),
+            KeywordImportError::InvalidRow { line: 2, .. }
+        ));
+        assert!(matches!(
+            error("term,threshold\ngore,1.5\n"),
+            KeywordImportError::InvalidRow { line: 2, .. }
+        ));
+        assert!(matches!(
+            error("term,threshold\ngore,high\n"),
+            KeywordImportError::InvalidRow { line: 2, .. }
+        ));
+        assert!(matches!(
+            error("term,category\n,violence\n"),
+            KeywordImportError::InvalidRow { line: 2, .. }
+        ));
+        // Without a `term` column the first row is data, not a header
+        assert_eq!(
+            error("word,category\ngore,violence\n"),
+            KeywordImportError::InvalidRow {
+                line: 1,
+                message: "unknown category 'category'".to_string()
+            }
+        );
+        assert!(matches!(
+            error("term,category,threshold\ngore,violence,0.5\nblood,violence,0.7\n"),
+            KeywordImportError::ConflictingThreshold {
+                category: Category::Violence,
+                ..
+            }
+        ));
+        assert!(matches!(
+            KeywordImport::from_json("{not json"),
+            Err(KeywordImportError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn imported_csv_terms_become_active_rules() {
+        let mut overrides = ParentOverrides::new();
+        overrides.add_blacklist("homework help", Category::Profanity);
+
+        let summary = import(SCHOOL_CSV).apply(&mut overrides);
+        assert_eq!(
+            summary,
+            ImportSummary {
+                blocked: 2,
+                allowed: 1,
+                unchanged: 0,
+                duplicates: 1,
+            }
+        );
+        assert_eq!(
+            overrides.category_thresholds.get(&Category::Violence),
+            Some(&0.6)
+        );
+
+        let mut manager = CommunityRuleManager::new();
+        manager.set_overrides(overrides);
+
+        let matches = manager.classify("show me some GORE");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].category, Category::Violence);
+        assert!(!manager.classify("a knife, fight scene").is_empty());
+        // Allowed terms are moved off the blacklist
+        assert!(manager.classify("I need homework help").is_empty());
+
+        // Importing again changes nothing
+        let mut overrides = manager.overrides().clone();
+        let summary = import(SCHOOL_CSV).apply(&mut overrides);
+        assert_eq!(summary.unchanged, 3);
+        assert_eq!(summary.blocked + summary.allowed, 0);
+    }
+
+    #[test]
+    fn format_from_path_and_content() {
+        assert_eq!(
+            KeywordFormat::from_path(Path::new("list.CSV")),
+            Some(KeywordFormat::Csv)
+        );
+        assert_eq!(
+            KeywordFormat::from_path(Path::new("list.json")),
+            Some(KeywordFormat::Json)
+        );
+        assert_eq!(KeywordFormat::from_path(Path::new("list.txt")), None);
+        assert_eq!(KeywordFormat::detect("  [\"a\"]"), KeywordFormat::Json);
+        assert_eq!(KeywordFormat::detect("term\na"), KeywordFormat::Csv);
+    }
+}
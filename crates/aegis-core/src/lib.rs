@@ -10,27 +10,39 @@
 //! - [`content_rules`] - Content-based filtering rules (F006)
 //! - [`community_rules`] - Layered community rules with tier priorities (F025)
 //! - [`rule_engine`] - Unified rule evaluation engine (F007)
+//! - [`rule_validation`] - Dry-run validation of rule drafts before saving (F007)
 //! - [`profile`] - User profile management (F019)
 //! - [`profile_proxy`] - Profile-aware proxy control with OS user monitoring (F029)
 //! - [`protection`] - Protection state toggle with auth-guarded operations (F018)
 //! - [`notifications`] - Desktop notifications for blocked content (F014)
 //! - [`interception`] - Interception mode switching between extension and proxy (F017)
+//! - [`keyword_import`] - CSV/JSON keyword blocklist import into parent overrides (F025)
+//! - [`simulation`] - Offline replay of prompt samples against rules for tuning (F007)
+//! - [`maintenance`] - Maintenance window for deferring model downloads and updates
+//! - [`data_dir`] - Data directory with `AEGIS_DATA_DIR` override
 //! - [`site_registry`] - Dynamic site registry for LLM domain management (F027)
 //! - [`extension_install`] - Browser extension auto-installation (F024)
+//! - [`extension_token`] - Shared token authenticating the extension to the local API (F024)
 
 pub mod auth;
 pub mod classifier;
 pub mod community_rules;
 pub mod content_rules;
+pub mod data_dir;
 #[cfg(feature = "extension-install")]
 pub mod extension_install;
+pub mod extension_token;
 pub mod interception;
+pub mod keyword_import;
+pub mod maintenance;
 pub mod model_downloader;
 pub mod notifications;
 pub mod profile;
 pub mod profile_proxy;
 pub mod protection;
 pub mod rule_engine;
+pub mod rule_validation;
+pub mod simulation;
 pub mod site_registry;
 pub mod time_rules;
 
@@ -0,0 +1,265 @@
+//! Maintenance window for background network work.
+//!
+//! Model downloads and update fetches are bandwidth-heavy and cause lag if
+//! they start while a child is using the computer. A [`MaintenanceSchedule`]
+//! confines that work to a configured window (e.g., 3-5 AM) and reports
+//! [`Deferred`] outside it, so callers can retry later.
+//!
+//! Only fetching is deferred: models already on disk still load on demand.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::time_rules::{TimeOfDay, TimeRange};
+
+/// Daily time range during which network maintenance may run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    /// Local time range; may span midnight.
+    pub hours: TimeRange,
+}
+
+impl MaintenanceWindow {
+    /// Creates a window covering `hours`.
+    pub fn new(hours: TimeRange) -> Self {
+        Self { hours }
+    }
+
+    /// Creates a window from whole hours (end exclusive).
+    pub fn from_hours(start_hour: u8, end_hour: u8) -> Self {
+        Self::new(TimeRange::from_hours(start_hour, end_hour))
+    }
+
+    /// Returns true if `time` falls inside the window.
+    pub fn contains(&self, time: TimeOfDay) -> bool {
+        self.hours.contains(time)
+    }
+}
+
+impl Default for MaintenanceWindow {
+    /// 3 AM to 5 AM.
+    fn default() -> Self {
+        Self::from_hours(3, 5)
+    }
+}
+
+impl fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let start = self.hours.start;
+        let end = self.hours.end;
+        write!(
+            f,
+            "{:02}:{:02}-{:02}:{:02}",
+            start.hour, start.minute, end.hour, end.minute
+        )
+    }
+}
+
+/// Network work was postponed because the maintenance window is closed.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("deferred until the maintenance window ({window})")]
+pub struct Deferred {
+    /// The window the work is waiting for.
+    pub window: MaintenanceWindow,
+}
+
+/// Source of the current local time of day.
+#[derive(Clone)]
+pub struct Clock(Arc<dyn Fn() -> TimeOfDay + Send + Sync>);
+
+impl Clock {
+    /// Reads the system's local time.
+    pub fn system() -> Self {
+        Self::new(|| TimeOfDay::from_naive_time(Local::now().time()))
+    }
+
+    /// Always reports `time` (for tests).
+    pub fn fixed(time: TimeOfDay) -> Self {
+        Self::new(move || time)
+    }
+
+    /// Uses `now` to read the time.
+    pub fn new<F>(now: F) -> Self
+    where
+        F: Fn() -> TimeOfDay + Send + Sync + 'static,
+    {
+        Self(Arc::new(now))
+    }
+
+    /// Returns the current time of day.
+    pub fn now(&self) -> TimeOfDay {
+        (self.0)()
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+impl fmt::Debug for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Clock").field(&self.now()).finish()
+    }
+}
+
+/// Decides whether network maintenance may run right now.
+///
+/// Without a window, work is never deferred.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceSchedule {
+    window: Option<MaintenanceWindow>,
+    clock: Clock,
+}
+
+impl MaintenanceSchedule {
+    /// Creates a schedule that never defers work.
+    pub fn unrestricted() -> Self {
+        Self::default()
+    }
+
+    /// Creates a schedule that only runs work inside `window`.
+    pub fn new(window: MaintenanceWindow) -> Self {
+        Self::from_window(Some(window))
+    }
+
+    /// Creates a schedule from an optional window (`None` = unrestricted).
+    pub fn from_window(window: Option<MaintenanceWindow>) -> Self {
+        Self {
+            window,
+            clock: Clock::system(),
+        }
+    }
+
+    /// Sets the clock used to read the current time.
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the configured window, if any.
+    pub fn window(&self) -> Option<&MaintenanceWindow> {
+        self.window.as_ref()
+    }
+
+    /// Returns true if work may run now.
+    pub fn is_open(&self) -> bool {
+        self.check().is_ok()
+    }
+
+    /// Returns [`Deferred`] if the window is configured and closed.
+    pub fn check(&self) -> Result<(), Deferred> {
+        match &self.window {
+            Some(window) if !window.contains(self.clock.now()) => Err(Deferred {
+                window: window.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs `task` if the window is open, otherwise returns [`Deferred`]
+    /// without starting it.
+    pub async fn run<F, Fut, T, E>(&self, task: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<Deferred>,
+    {
+        self.check()?;
+        task().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Downloader stand-in that counts the fetches it performs.
+    struct MockDownloader {
+        fetches: AtomicUsize,
+    }
+
+    impl MockDownloader {
+        async fn fetch(&self) -> Result<&'static str, Deferred> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok("model.onnx")
+        }
+    }
+
+    fn schedule_at(hour: u8, minute: u8) -> MaintenanceSchedule {
+        MaintenanceSchedule::new(MaintenanceWindow::default())
+            .with_clock(Clock::fixed(TimeOfDay::new(hour, minute)))
+    }
+
+    #[test]
+    fn download_outside_window_is_deferred() {
+        let downloader = MockDownloader {
+            fetches: AtomicUsize::new(0),
+        };
+
+        let result = tokio_test::block_on(schedule_at(14, 0).run(|| downloader.fetch()));
+
+        assert_eq!(
+            result,
+            Err(Deferred {
+                window: MaintenanceWindow::default(),
+            })
+        );
+        assert_eq!(downloader.fetches.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn download_inside_window_proceeds() {
+        let downloader = MockDownloader {
+            fetches: AtomicUsize::new(0),
+        };
+
+        let result = tokio_test::block_on(schedule_at(3, 30).run(|| downloader.fetch()));
+
+        assert_eq!(result, Ok("model.onnx"));
+        assert_eq!(downloader.fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn window_bounds_and_overnight_windows() {
+        assert!(schedule_at(3, 0).is_open());
+        assert!(schedule_at(4, 59).is_open());
+        assert!(!schedule_at(5, 0).is_open());
+        assert!(!schedule_at(2, 59).is_open());
+
+        let overnight = MaintenanceSchedule::new(MaintenanceWindow::from_hours(23, 2))
+            .with_clock(Clock::fixed(TimeOfDay::new(0, 30)));
+        assert!(overnight.is_open());
+    }
+
+    #[test]
+    fn unrestricted_schedule_never_defers() {
+        let schedule =
+            MaintenanceSchedule::unrestricted().with_clock(Clock::fixed(TimeOfDay::new(14, 0)));
+        assert!(schedule.window().is_none());
+        assert!(schedule.is_open());
+    }
+
+    #[test]
+    fn deferred_message_names_window() {
+        let err = schedule_at(14, 0).check().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "deferred until the maintenance window (03:00-05:00)"
+        );
+    }
+
+    #[test]
+    fn window_serialization_roundtrip() {
+        let window = MaintenanceWindow::from_hours(2, 4);
+        let json = serde_json::to_string(&window).unwrap();
+        let back: MaintenanceWindow = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, window);
+    }
+}
@@ -2,13 +2,18 @@
 //!
 //! Downloads ONNX Runtime and ML models on first use or via settings.
 //! Supports progress tracking for UI integration.
+//!
+//! Downloads can be confined to a [`MaintenanceSchedule`]; outside its window
+//! missing files are reported as [`DownloadError::Deferred`] instead of being
+//! fetched. Files already on disk are always used.
 
 use std::fs::{self, File};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use directories::ProjectDirs;
+use crate::data_dir::project_data_dir;
+use crate::maintenance::{Deferred, MaintenanceSchedule};
 
 /// Download progress callback type (uses Arc for Clone support).
 pub type ProgressCallback = Arc<dyn Fn(DownloadProgress) + Send + Sync>;
@@ -91,6 +96,9 @@ pub enum DownloadError {
 
     #[error("Model not found: {0}")]
     NotFound(String),
+
+    #[error("Download {0}")]
+    Deferred(#[from] Deferred),
 }
 
 /// ONNX Runtime version to download.
@@ -126,6 +134,40 @@ const ONNX_RUNTIME_URL: &str = "";
 const NSFW_MODEL_URL: &str =
     "https://huggingface.co/onnx-community/nsfw-image-detector-ONNX/resolve/main/onnx/model.onnx";
 
+/// Downloads `url` into memory, reporting progress under `step` as each
+/// chunk arrives. Returns the body and the advertised length.
+async fn fetch_bytes(
+    url: &str,
+    step: &str,
+    progress: Option<&ProgressCallback>,
+) -> Result<(Vec<u8>, Option<u64>), DownloadError> {
+    let mut response = reqwest::get(url)
+        .await
+        .map_err(|e| DownloadError::Network(e.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(DownloadError::Network(format!(
+            "HTTP error: {}",
+            response.status()
+        )));
+    }
+
+    let total_size = response.content_length();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| DownloadError::Network(e.to_string()))?
+    {
+        bytes.extend_from_slice(&chunk);
+        if let Some(cb) = progress {
+            cb(DownloadProgress::new(step, bytes.len() as u64, total_size));
+        }
+    }
+
+    Ok((bytes, total_size))
+}
+
 /// Model downloader for ONNX Runtime and ML models.
 pub struct ModelDownloader {
     /// Directory to store downloaded files.
@@ -134,21 +176,35 @@ pub struct ModelDownloader {
     models_dir: PathBuf,
     /// Directory for runtime libraries.
     lib_dir: PathBuf,
+    /// When downloads may run.
+    schedule: MaintenanceSchedule,
 }
 
 impl ModelDownloader {
     /// Creates a new model downloader.
     pub fn new() -> Option<Self> {
-        let project_dirs = ProjectDirs::from("", "aegis", "Aegis")?;
-        let data_dir = project_dirs.data_dir().to_path_buf();
-        let models_dir = data_dir.join("models");
-        let lib_dir = data_dir.join("lib");
+        project_data_dir("", "aegis", "Aegis").map(Self::with_data_dir)
+    }
 
-        Some(Self {
+    /// Creates a downloader storing files under `data_dir`.
+    pub fn with_data_dir(data_dir: PathBuf) -> Self {
+        Self {
+            models_dir: data_dir.join("models"),
+            lib_dir: data_dir.join("lib"),
             data_dir,
-            models_dir,
-            lib_dir,
-        })
+            schedule: MaintenanceSchedule::unrestricted(),
+        }
+    }
+
+    /// Confines downloads to the schedule's maintenance window.
+    pub fn with_schedule(mut self, schedule: MaintenanceSchedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Returns the schedule downloads run under.
+    pub fn schedule(&self) -> &MaintenanceSchedule {
+        &self.schedule
     }
 
     /// Returns the data directory path.
@@ -214,7 +270,9 @@ impl ModelDownloader {
             return Ok(self.onnx_runtime_path());
         }
 
-        self.download_onnx_runtime(progress).await
+        self.schedule
+            .run(|| self.download_onnx_runtime(progress))
+            .await
     }
 
     /// Downloads the NSFW model if not already installed.
@@ -229,7 +287,21 @@ impl ModelDownloader {
             return Ok(self.nsfw_model_path());
         }
 
-        self.download_nsfw_model(progress).await
+        self.schedule
+            .run(|| self.download_nsfw_model(progress))
+            .await
+    }
+
+    /// Downloads the NSFW model again, replacing the installed copy.
+    ///
+    /// Use this when the installed model is corrupt.
+    pub async fn redownload_nsfw_model(
+        &self,
+        progress: Option<ProgressCallback>,
+    ) -> Result<PathBuf, DownloadError> {
+        self.schedule
+            .run(|| self.download_nsfw_model(progress))
+            .await
     }
 
     /// Ensures all ML dependencies are installed.
@@ -259,22 +331,12 @@ impl ModelDownloader {
         }
 
         // Download the archive
-        let response = reqwest::get(ONNX_RUNTIME_URL)
-            .await
-            .map_err(|e| DownloadError::Network(e.to_string()))?;
-
-        if !response.status().is_success() {
-            return Err(DownloadError::Network(format!(
-                "HTTP error: {}",
-                response.status()
-            )));
-        }
-
-        let total_size = response.content_length();
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| DownloadError::Network(e.to_string()))?;
+        let (bytes, total_size) = fetch_bytes(
+            ONNX_RUNTIME_URL,
+            &format!("Downloading ONNX Runtime v{}...", ONNX_RUNTIME_VERSION),
+            progress.as_ref(),
+        )
+        .await?;
 
         if let Some(ref cb) = progress {
             cb(DownloadProgress::new(
@@ -315,22 +377,12 @@ impl ModelDownloader {
         }
 
         // Download the model
-        let response = reqwest::get(NSFW_MODEL_URL)
-            .await
-            .map_err(|e| DownloadError::Network(e.to_string()))?;
-
-        if !response.status().is_success() {
-            return Err(DownloadError::Network(format!(
-                "HTTP error: {}",
-                response.status()
-            )));
-        }
-
-        let total_size = response.content_length();
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| DownloadError::Network(e.to_string()))?;
+        let (bytes, total_size) = fetch_bytes(
+            NSFW_MODEL_URL,
+            "Downloading NSFW model...",
+            progress.as_ref(),
+        )
+        .await?;
 
         if let Some(ref cb) = progress {
             cb(DownloadProgress::new(
@@ -340,10 +392,13 @@ impl ModelDownloader {
             ));
         }
 
-        // Save the model
+        // Save the model, replacing any previous copy only once fully written
         let model_path = self.nsfw_model_path();
-        let mut file = File::create(&model_path)?;
+        let partial_path = model_path.with_extension("onnx.part");
+        let mut file = File::create(&partial_path)?;
         file.write_all(&bytes)?;
+        drop(file);
+        fs::rename(&partial_path, &model_path)?;
 
         if let Some(ref cb) = progress {
             cb(DownloadProgress::complete("NSFW model installed"));
@@ -543,6 +598,30 @@ mod tests {
         assert_eq!(downloading.description(), "Downloading... (50%)");
     }
 
+    #[test]
+    fn downloads_outside_window_are_deferred() {
+        use crate::maintenance::{Clock, MaintenanceWindow};
+        use crate::time_rules::TimeOfDay;
+
+        let dir = std::env::temp_dir().join(format!("aegis_downloader_{}", std::process::id()));
+        let downloader = ModelDownloader::with_data_dir(dir.clone()).with_schedule(
+            MaintenanceSchedule::new(MaintenanceWindow::default())
+                .with_clock(Clock::fixed(TimeOfDay::new(14, 0))),
+        );
+
+        let result = tokio_test::block_on(downloader.ensure_nsfw_model(None));
+        assert!(matches!(result, Err(DownloadError::Deferred(_))));
+        assert!(!downloader.models_dir().exists());
+
+        // A model already on disk is used without waiting for the window
+        fs::create_dir_all(downloader.models_dir()).unwrap();
+        fs::write(downloader.nsfw_model_path(), b"model").unwrap();
+        let result = tokio_test::block_on(downloader.ensure_nsfw_model(None));
+        assert_eq!(result.unwrap(), downloader.nsfw_model_path());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
     #[test]
     fn model_downloader_paths() {
         if let Some(downloader) = ModelDownloader::new() {
@@ -8,12 +8,16 @@
 //! - Notify on block events (not warnings)
 //! - Shows site/source and category
 //! - Rate-limited to 1 notification per minute
+//! - Escalates once per day when a category's blocks reach a daily limit
+//!   ("Violence blocked 10× today — possible concern"), bypassing the rate limit
 //! - Can be enabled/disabled
 //! - Cross-platform (Windows, macOS, Linux)
 
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
+use chrono::{Local, NaiveDate};
 use serde::{Deserialize, Serialize};
 
 use crate::classifier::Category;
@@ -22,31 +26,82 @@ use crate::rule_engine::{RuleAction, RuleSource};
 /// Minimum time between notifications (60 seconds).
 const RATE_LIMIT_DURATION: Duration = Duration::from_secs(60);
 
+/// Default number of blocks per category per day before escalating.
+pub const DEFAULT_DAILY_BLOCK_LIMIT: u32 = 10;
+
 /// Notification settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationSettings {
     /// Whether notifications are enabled.
     pub enabled: bool,
+    /// Blocks per day after which a category escalates. Categories without
+    /// a limit never escalate.
+    #[serde(default = "default_daily_block_limits")]
+    pub daily_block_limits: HashMap<Category, u32>,
 }
 
 impl Default for NotificationSettings {
     fn default() -> Self {
-        Self { enabled: true }
+        Self::enabled()
     }
 }
 
 impl NotificationSettings {
     /// Creates new settings with notifications enabled.
     pub fn enabled() -> Self {
-        Self { enabled: true }
+        Self {
+            enabled: true,
+            daily_block_limits: default_daily_block_limits(),
+        }
     }
 
     /// Creates new settings with notifications disabled.
     pub fn disabled() -> Self {
-        Self { enabled: false }
+        Self {
+            enabled: false,
+            ..Self::enabled()
+        }
+    }
+}
+
+/// [`DEFAULT_DAILY_BLOCK_LIMIT`] for every category.
+fn default_daily_block_limits() -> HashMap<Category, u32> {
+    Category::all()
+        .iter()
+        .map(|&category| (category, DEFAULT_DAILY_BLOCK_LIMIT))
+        .collect()
+}
+
+/// A category reached its daily block limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Escalation {
+    /// The category that crossed its limit.
+    pub category: Category,
+    /// Blocks of this category so far today.
+    pub count: u32,
+    /// The day the blocks were counted on.
+    pub date: NaiveDate,
+}
+
+impl Escalation {
+    /// Returns the notification text, e.g. "Violence blocked 10× today — possible concern".
+    pub fn message(&self) -> String {
+        format!(
+            "{} blocked {}× today — possible concern",
+            self.category.name(),
+            self.count
+        )
     }
 }
 
+/// Per-category block counts for one day.
+#[derive(Debug, Default)]
+struct DailyBlockCounts {
+    date: Option<NaiveDate>,
+    counts: HashMap<Category, u32>,
+    escalated: HashSet<Category>,
+}
+
 /// Information about a blocked event for notification.
 #[derive(Debug, Clone)]
 pub struct BlockedEvent {
@@ -79,7 +134,7 @@ impl BlockedEvent {
     /// Creates a blocked event from a rule source.
     pub fn from_rule_source(source: &RuleSource, site: Option<String>) -> Self {
         match source {
-            RuleSource::None => Self::new(site, None, None, false),
+            RuleSource::None | RuleSource::Allowlisted { .. } => Self::new(site, None, None, false),
             RuleSource::TimeRule { rule_name, .. } => {
                 Self::new(site, None, Some(rule_name.clone()), true)
             }
@@ -89,6 +144,12 @@ impl BlockedEvent {
                 Some(result.rule_name.clone()),
                 false,
             ),
+            RuleSource::ModelRule { rule_name, .. } => {
+                Self::new(site, None, Some(rule_name.clone()), false)
+            }
+            RuleSource::UnsupportedLanguage { .. } => {
+                Self::new(site, None, source.rule_name().map(String::from), false)
+            }
         }
     }
 }
@@ -134,22 +195,22 @@ impl NotificationResult {
 pub struct NotificationManager {
     settings: Arc<RwLock<NotificationSettings>>,
     rate_limit: Arc<RwLock<RateLimitState>>,
+    daily_blocks: Arc<RwLock<DailyBlockCounts>>,
+    #[cfg(test)]
+    sent_escalations: Arc<RwLock<Vec<Escalation>>>,
 }
 
 impl NotificationManager {
     /// Creates a new notification manager with default settings (enabled).
     pub fn new() -> Self {
-        Self {
-            settings: Arc::new(RwLock::new(NotificationSettings::default())),
-            rate_limit: Arc::new(RwLock::new(RateLimitState::default())),
-        }
+        Self::with_settings(NotificationSettings::default())
     }
 
     /// Creates a new notification manager with the given settings.
     pub fn with_settings(settings: NotificationSettings) -> Self {
         Self {
             settings: Arc::new(RwLock::new(settings)),
-            rate_limit: Arc::new(RwLock::new(RateLimitState::default())),
+            ..Self::default()
         }
     }
 
@@ -202,13 +263,85 @@ impl NotificationManager {
         self.time_until_next().is_some()
     }
 
+    /// Sets the daily block limit for a category (`None` = never escalate).
+    pub fn set_daily_block_limit(&self, category: Category, limit: Option<u32>) {
+        let mut settings = self.settings.write().unwrap();
+        match limit {
+            Some(limit) => settings.daily_block_limits.insert(category, limit),
+            None => settings.daily_block_limits.remove(&category),
+        };
+    }
+
+    /// Returns today's block count for a category.
+    pub fn blocks_today(&self, category: Category) -> u32 {
+        let state = self.daily_blocks.read().unwrap();
+        if state.date == Some(Local::now().date_naive()) {
+            state.counts.get(&category).copied().unwrap_or(0)
+        } else {
+            0
+        }
+    }
+
+    /// Counts a block of `category` on `date`.
+    ///
+    /// Returns an [`Escalation`] the first time that day's count reaches the
+    /// category's limit; later blocks that day don't escalate again. Counts
+    /// reset when `date` changes.
+    pub fn record_block(&self, category: Category, date: NaiveDate) -> Option<Escalation> {
+        let limit = self
+            .settings
+            .read()
+            .unwrap()
+            .daily_block_limits
+            .get(&category)
+            .copied();
+
+        let mut state = self.daily_blocks.write().unwrap();
+        if state.date != Some(date) {
+            *state = DailyBlockCounts {
+                date: Some(date),
+                ..DailyBlockCounts::default()
+            };
+        }
+
+        let count = state.counts.entry(category).or_insert(0);
+        *count += 1;
+        let count = *count;
+
+        match limit {
+            Some(limit) if count >= limit && state.escalated.insert(category) => Some(Escalation {
+                category,
+                count,
+                date,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Sends an escalation notification. Not subject to the rate limit.
+    pub fn notify_escalation(&self, escalation: &Escalation) -> NotificationResult {
+        if !self.is_enabled() {
+            return NotificationResult::Disabled;
+        }
+        self.send_escalation(escalation)
+    }
+
     /// Notifies about a blocked event.
     ///
+    /// Content blocks are counted per category first, and an escalation is
+    /// sent if the category reaches its daily limit.
+    ///
     /// Only sends notification if:
     /// - Notifications are enabled
     /// - Not rate-limited (1 minute between notifications)
     /// - The action is Block (not Warn or Allow)
     pub fn notify_block(&self, event: &BlockedEvent) -> NotificationResult {
+        if let (Some(category), false) = (event.category, event.is_time_block) {
+            if let Some(escalation) = self.record_block(category, Local::now().date_naive()) {
+                let _ = self.notify_escalation(&escalation);
+            }
+        }
+
         // Check if enabled
         if !self.is_enabled() {
             return NotificationResult::Disabled;
@@ -286,6 +419,39 @@ impl NotificationManager {
         NotificationResult::Sent
     }
 
+    /// Sends an escalation notification using the platform API.
+    #[cfg(all(feature = "notifications", not(test)))]
+    fn send_escalation(&self, escalation: &Escalation) -> NotificationResult {
+        use notify_rust::Notification;
+
+        match Notification::new()
+            .summary("Aegis - Repeated Blocks")
+            .body(&escalation.message())
+            .appname("Aegis")
+            .timeout(notify_rust::Timeout::Milliseconds(10000))
+            .show()
+        {
+            Ok(_) => NotificationResult::Sent,
+            Err(e) => NotificationResult::Failed(e.to_string()),
+        }
+    }
+
+    /// Test mock: records the escalation instead of sending it.
+    #[cfg(test)]
+    fn send_escalation(&self, escalation: &Escalation) -> NotificationResult {
+        self.sent_escalations
+            .write()
+            .unwrap()
+            .push(escalation.clone());
+        NotificationResult::Sent
+    }
+
+    /// Fallback when notifications feature is disabled.
+    #[cfg(all(not(feature = "notifications"), not(test)))]
+    fn send_escalation(&self, _escalation: &Escalation) -> NotificationResult {
+        NotificationResult::Sent
+    }
+
     /// Fallback when notifications feature is disabled.
     #[cfg(all(not(feature = "notifications"), not(test)))]
     fn send_notification(&self, _event: &BlockedEvent) -> NotificationResult {
@@ -539,6 +705,91 @@ mod tests {
         assert!(result.was_sent());
     }
 
+    // ==================== Escalation Tests ====================
+
+    #[test]
+    fn nth_block_of_category_escalates_once() {
+        let manager = NotificationManager::new();
+        manager.set_daily_block_limit(Category::Violence, Some(3));
+        let violence = BlockedEvent::new(None, Some(Category::Violence), None, false);
+        let hate = BlockedEvent::new(None, Some(Category::Hate), None, false);
+
+        for _ in 0..2 {
+            let _ = manager.notify_block(&violence);
+            let _ = manager.notify_block(&hate);
+        }
+        assert!(manager.sent_escalations.read().unwrap().is_empty());
+
+        // The third violence block crosses the limit despite the rate limit.
+        assert!(manager.is_rate_limited());
+        let _ = manager.notify_block(&violence);
+        let _ = manager.notify_block(&violence);
+        let _ = manager.notify_block(&violence);
+
+        let sent = manager.sent_escalations.read().unwrap();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].category, Category::Violence);
+        assert_eq!(sent[0].count, 3);
+        assert_eq!(
+            sent[0].message(),
+            "Violence blocked 3× today — possible concern"
+        );
+        assert_eq!(manager.blocks_today(Category::Violence), 5);
+        assert_eq!(manager.blocks_today(Category::Hate), 2);
+    }
+
+    #[test]
+    fn escalation_resets_each_day() {
+        let manager = NotificationManager::new();
+        manager.set_daily_block_limit(Category::Adult, Some(2));
+        let monday = NaiveDate::from_ymd_opt(2024, 5, 6).unwrap();
+        let tuesday = monday.succ_opt().unwrap();
+
+        assert!(manager.record_block(Category::Adult, monday).is_none());
+        assert!(manager.record_block(Category::Adult, monday).is_some());
+        assert!(manager.record_block(Category::Adult, monday).is_none());
+
+        assert!(manager.record_block(Category::Adult, tuesday).is_none());
+        assert!(manager.record_block(Category::Adult, tuesday).is_some());
+    }
+
+    #[test]
+    fn time_blocks_and_unlimited_categories_do_not_escalate() {
+        let manager = NotificationManager::new();
+        manager.set_daily_block_limit(Category::Profanity, None);
+        let today = Local::now().date_naive();
+        for _ in 0..20 {
+            assert!(manager.record_block(Category::Profanity, today).is_none());
+        }
+
+        manager.set_daily_block_limit(Category::Violence, Some(1));
+        let time_block =
+            BlockedEvent::new(None, Some(Category::Violence), Some("Bedtime".into()), true);
+        let _ = manager.notify_block(&time_block);
+        assert_eq!(manager.blocks_today(Category::Violence), 0);
+        assert!(manager.sent_escalations.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn disabled_manager_counts_without_notifying() {
+        let manager = NotificationManager::with_settings(NotificationSettings::disabled());
+        manager.set_daily_block_limit(Category::Violence, Some(1));
+        let event = BlockedEvent::new(None, Some(Category::Violence), None, false);
+
+        assert!(manager.notify_block(&event).was_disabled());
+        assert_eq!(manager.blocks_today(Category::Violence), 1);
+        assert!(manager.sent_escalations.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn settings_without_limits_deserialize_with_defaults() {
+        let settings: NotificationSettings = serde_json::from_str(r#"{"enabled": true}"#).unwrap();
+        assert_eq!(
+            settings.daily_block_limits.get(&Category::Violence),
+            Some(&DEFAULT_DAILY_BLOCK_LIMIT)
+        );
+    }
+
     // ==================== Formatting Tests ====================
 
     #[test]
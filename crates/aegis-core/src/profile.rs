@@ -267,12 +267,15 @@ impl UserProfile {
     }
 
     /// Checks if this profile matches the given OS username.
+    ///
+    /// Both names are compared by their [`normalize_os_username`] key, so
+    /// `DOMAIN\\alice`, `alice@host`, and `Alice` all match `alice`.
     pub fn matches_os_username(&self, username: &str) -> bool {
         self.enabled
             && self
                 .os_username
                 .as_ref()
-                .map(|u| u.eq_ignore_ascii_case(username))
+                .map(|u| normalize_os_username(u) == normalize_os_username(username))
                 .unwrap_or(false)
     }
 }
@@ -316,7 +319,7 @@ impl ProfileManager {
         self.profiles.iter_mut().find(|p| p.id == id)
     }
 
-    /// Gets a profile by OS username (case-insensitive).
+    /// Gets a profile by OS username (normalized, case-insensitive).
     ///
     /// Returns the first enabled profile that matches the given OS username.
     /// Returns None if no profile matches (parent mode / unrestricted).
@@ -362,14 +365,35 @@ impl ProfileManager {
     }
 }
 
+/// Environment variables holding the login name, in lookup order.
+#[cfg(windows)]
+const USERNAME_ENV_VARS: &[&str] = &["USERNAME", "USER"];
+#[cfg(not(windows))]
+const USERNAME_ENV_VARS: &[&str] = &["USER", "LOGNAME", "USERNAME"];
+
 /// Gets the current OS username.
 ///
-/// Returns the username of the currently logged-in user.
-/// This is used to auto-detect which profile to apply.
+/// Returns the normalized username of the currently logged-in user (see
+/// [`normalize_os_username`]). This is used to auto-detect which profile to apply.
 pub fn get_current_os_user() -> String {
-    std::env::var("USER")
-        .or_else(|_| std::env::var("USERNAME"))
-        .unwrap_or_else(|_| "unknown".to_string())
+    USERNAME_ENV_VARS
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|name| normalize_os_username(&name))
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Normalizes an OS username to the key used for profile matching.
+///
+/// Strips a Windows domain prefix (`DOMAIN\user`) and a UPN or host suffix
+/// (`user@host`), then lowercases. Windows and macOS treat usernames
+/// case-insensitively, and Linux login names are lowercase by convention.
+pub fn normalize_os_username(username: &str) -> String {
+    let name = username.trim();
+    let name = name.rsplit('\\').next().unwrap_or(name);
+    let name = name.split('@').next().unwrap_or(name);
+    name.trim().to_lowercase()
 }
 
 #[cfg(test)]
@@ -591,6 +615,45 @@ mod tests {
         assert!(!username.is_empty());
     }
 
+    #[test]
+    fn test_get_current_os_user_is_normalized() {
+        let username = get_current_os_user();
+        assert_eq!(username, normalize_os_username(&username));
+    }
+
+    // ==================== normalize_os_username Tests ====================
+
+    #[test]
+    fn test_normalize_os_username_forms() {
+        assert_eq!(normalize_os_username("alice"), "alice");
+        assert_eq!(normalize_os_username("CORP\\Alice"), "alice");
+        assert_eq!(normalize_os_username("alice@laptop.local"), "alice");
+        assert_eq!(normalize_os_username("  ALICE  "), "alice");
+        assert_eq!(normalize_os_username("CORP\\alice@corp.example"), "alice");
+    }
+
+    #[test]
+    fn test_profile_manager_matches_normalized_forms() {
+        let mut manager = ProfileManager::new();
+        manager.add_profile(UserProfile::with_child_defaults(
+            "Alice",
+            Some("alice".to_string()),
+        ));
+
+        for form in ["alice", "DOMAIN\\alice", "alice@host", "Alice"] {
+            let profile = manager.get_by_os_username(form);
+            assert_eq!(profile.map(|p| p.name.as_str()), Some("Alice"), "{}", form);
+        }
+    }
+
+    #[test]
+    fn test_stored_domain_username_matches_plain() {
+        let profile = UserProfile::with_child_defaults("Bob", Some("WORKGROUP\\Bob".to_string()));
+        assert!(profile.matches_os_username("bob"));
+        assert!(profile.matches_os_username("bob@desktop"));
+        assert!(!profile.matches_os_username("bobby"));
+    }
+
     // ==================== Serialization Tests ====================
 
     #[test]
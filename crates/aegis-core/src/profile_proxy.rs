@@ -9,6 +9,7 @@
 //! - Profile-based proxy control
 //! - Profile switch event logging
 //! - Fast user switching support with debouncing
+//! - Scheduled profile switching by time of day for shared logins
 //!
 //! ## Usage
 //!
@@ -35,15 +36,19 @@
 //! }
 //! ```
 
+use std::collections::HashSet;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::profile::{get_current_os_user, ProfileManager, ProxyMode, UserProfile};
+use crate::profile::{
+    get_current_os_user, normalize_os_username, ProfileManager, ProxyMode, UserProfile,
+};
 use crate::protection::{ProtectionManager, ProtectionState};
+use crate::time_rules::{previous_day, TimeOfDay, TimeRange, Weekday};
 
 /// Errors that can occur during profile proxy operations.
 #[derive(Debug, Error)]
@@ -140,6 +145,88 @@ impl ProfileSwitchEvent {
     }
 }
 
+/// A time window during which a profile is active.
+///
+/// Overnight windows (e.g., 21:00-07:00) belong to the day they start on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledProfile {
+    /// ID of the profile to activate.
+    pub profile_id: String,
+    /// Days the window applies to.
+    pub days: HashSet<Weekday>,
+    /// Time range the profile is active.
+    pub time_range: TimeRange,
+}
+
+impl ScheduledProfile {
+    /// Creates a new scheduled window.
+    pub fn new(profile_id: impl Into<String>, days: Vec<Weekday>, time_range: TimeRange) -> Self {
+        Self {
+            profile_id: profile_id.into(),
+            days: days.into_iter().collect(),
+            time_range,
+        }
+    }
+
+    /// Returns true if the window covers the given day and time.
+    pub fn is_active(&self, day: Weekday, time: TimeOfDay) -> bool {
+        if self.time_range.is_overnight() {
+            (time >= self.time_range.start && self.days.contains(&day))
+                || (time < self.time_range.end && self.days.contains(&previous_day(day)))
+        } else {
+            self.days.contains(&day) && self.time_range.contains(time)
+        }
+    }
+}
+
+/// Time-of-day schedule mapping windows to profiles.
+///
+/// The first matching window wins when windows overlap.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileSchedule {
+    /// Scheduled windows, in priority order.
+    pub entries: Vec<ScheduledProfile>,
+}
+
+impl ProfileSchedule {
+    /// Creates an empty schedule.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a window to the schedule.
+    pub fn with_entry(mut self, entry: ScheduledProfile) -> Self {
+        self.entries.push(entry);
+        self
+    }
+
+    /// Returns true if no windows are scheduled.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the profile ID scheduled at the given day and time.
+    pub fn active_profile_id(&self, day: Weekday, time: TimeOfDay) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|e| e.is_active(day, time))
+            .map(|e| e.profile_id.as_str())
+    }
+}
+
+/// Which source wins when the OS user and the schedule disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SchedulePrecedence {
+    /// A profile matched by OS username wins; the schedule applies to
+    /// users without their own profile (e.g., a shared family login).
+    #[default]
+    OsUser,
+
+    /// An active schedule window wins over the OS user's profile.
+    Schedule,
+}
+
 /// Configuration for the profile proxy controller.
 #[derive(Debug, Clone)]
 pub struct ProfileProxyConfig {
@@ -153,6 +240,10 @@ pub struct ProfileProxyConfig {
     pub proxy_host: String,
     /// Proxy port.
     pub proxy_port: u16,
+    /// Time-of-day profile schedule, evaluated on each poll.
+    pub schedule: ProfileSchedule,
+    /// Precedence between OS-user detection and the schedule.
+    pub schedule_precedence: SchedulePrecedence,
 }
 
 impl Default for ProfileProxyConfig {
@@ -163,6 +254,8 @@ impl Default for ProfileProxyConfig {
             unknown_user_mode: UnknownUserMode::EnableWithDefaults,
             proxy_host: "127.0.0.1".to_string(),
             proxy_port: 8766,
+            schedule: ProfileSchedule::default(),
+            schedule_precedence: SchedulePrecedence::default(),
         }
     }
 }
@@ -392,8 +485,44 @@ impl ProfileProxyController {
         *p = profiles;
     }
 
-    /// Internal: check for user change and handle it.
+    /// Internal: check for user change at the current local time.
     fn check_user_change(&self, new_user: &str) -> Option<ProfileSwitchEvent> {
+        let now = chrono::Local::now();
+        let day = Weekday::from_chrono(now.weekday());
+        let time = TimeOfDay::new(now.hour() as u8, now.minute() as u8);
+        self.check_user_change_at(new_user, day, time)
+    }
+
+    /// Resolves the active profile from the OS user and the schedule.
+    fn resolve_profile<'a>(
+        &self,
+        profiles: &'a ProfileManager,
+        os_user: &str,
+        day: Weekday,
+        time: TimeOfDay,
+    ) -> Option<&'a UserProfile> {
+        let by_user = profiles.get_by_os_username(os_user);
+        let by_schedule = self
+            .config
+            .schedule
+            .active_profile_id(day, time)
+            .and_then(|id| profiles.get_profile(id));
+
+        match self.config.schedule_precedence {
+            SchedulePrecedence::OsUser => by_user.or(by_schedule),
+            SchedulePrecedence::Schedule => by_schedule.or(by_user),
+        }
+    }
+
+    /// Internal: check for a user or scheduled profile change and handle it.
+    fn check_user_change_at(
+        &self,
+        new_user: &str,
+        day: Weekday,
+        time: TimeOfDay,
+    ) -> Option<ProfileSwitchEvent> {
+        let new_user = normalize_os_username(new_user);
+        let new_user = new_user.as_str();
         let profiles = self.profiles.read().unwrap();
 
         // Get previous state
@@ -405,15 +534,18 @@ impl ProfileProxyController {
             )
         };
 
-        // Check if user changed
-        if previous_user == new_user && !previous_user.is_empty() {
-            return None;
-        }
-
         // Find new profile
-        let new_profile = profiles.get_by_os_username(new_user);
+        let new_profile = self.resolve_profile(&profiles, new_user, day, time);
         let new_profile_id = new_profile.map(|p| p.id.clone());
 
+        // Check if user or scheduled profile changed
+        if previous_user == new_user
+            && !previous_user.is_empty()
+            && previous_profile_id == new_profile_id
+        {
+            return None;
+        }
+
         // Determine proxy action
         let proxy_action = self.determine_proxy_action(new_profile);
 
@@ -799,6 +931,149 @@ mod tests {
         let _ = event;
     }
 
+    #[test]
+    fn test_controller_normalizes_username_forms() {
+        let controller =
+            ProfileProxyController::with_defaults(create_test_profiles(), ProtectionManager::new());
+
+        let event = controller.check_user_change("CORP\\Alice").unwrap();
+        assert_eq!(event.os_username, "alice");
+        assert_eq!(controller.current_profile().unwrap().id, "child1");
+
+        // Other spellings of the same user are not a switch
+        assert!(controller.check_user_change("alice@laptop").is_none());
+        assert!(controller.check_user_change("ALICE").is_none());
+    }
+
+    // ==================== Schedule Tests ====================
+
+    fn create_scheduled_profiles() -> ProfileManager {
+        let mut manager = create_test_profiles();
+        let school = UserProfile::new(
+            "school",
+            "School",
+            None,
+            TimeRuleSet::new(),
+            ContentRuleSet::family_safe_defaults(),
+        );
+        let relaxed = UserProfile::new(
+            "relaxed",
+            "Relaxed",
+            None,
+            TimeRuleSet::new(),
+            ContentRuleSet::new(),
+        );
+        manager.add_profile(school);
+        manager.add_profile(relaxed);
+        manager
+    }
+
+    fn school_day_schedule() -> ProfileSchedule {
+        ProfileSchedule::new()
+            .with_entry(ScheduledProfile::new(
+                "school",
+                Weekday::weekdays(),
+                TimeRange::from_hours(8, 15),
+            ))
+            .with_entry(ScheduledProfile::new(
+                "relaxed",
+                Weekday::all(),
+                TimeRange::from_hours(15, 22),
+            ))
+    }
+
+    #[test]
+    fn test_scheduled_profile_overnight_window() {
+        let entry =
+            ScheduledProfile::new("night", vec![Weekday::Friday], TimeRange::from_hours(21, 7));
+        assert!(entry.is_active(Weekday::Friday, TimeOfDay::new(22, 0)));
+        assert!(entry.is_active(Weekday::Saturday, TimeOfDay::new(6, 59)));
+        assert!(!entry.is_active(Weekday::Saturday, TimeOfDay::new(22, 0)));
+        assert!(!entry.is_active(Weekday::Friday, TimeOfDay::new(6, 0)));
+    }
+
+    #[test]
+    fn test_schedule_first_matching_window_wins() {
+        let schedule = school_day_schedule();
+        assert_eq!(
+            schedule.active_profile_id(Weekday::Monday, TimeOfDay::new(9, 0)),
+            Some("school")
+        );
+        assert_eq!(
+            schedule.active_profile_id(Weekday::Monday, TimeOfDay::new(16, 0)),
+            Some("relaxed")
+        );
+        assert!(ProfileSchedule::new()
+            .active_profile_id(Weekday::Monday, TimeOfDay::new(9, 0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_schedule_boundary_switches_profile_rules() {
+        let config = ProfileProxyConfig {
+            schedule: school_day_schedule(),
+            ..Default::default()
+        };
+        let controller = ProfileProxyController::new(
+            create_scheduled_profiles(),
+            ProtectionManager::new(),
+            config,
+        );
+
+        let event = controller
+            .check_user_change_at("family", Weekday::Monday, TimeOfDay::new(14, 59))
+            .unwrap();
+        assert_eq!(event.new_profile.as_deref(), Some("School"));
+        let school = controller.current_profile().unwrap();
+        assert!(!school.content_rules.rules.is_empty());
+
+        // Same window, same user: nothing to do
+        assert!(controller
+            .check_user_change_at("family", Weekday::Monday, TimeOfDay::new(14, 59))
+            .is_none());
+
+        let event = controller
+            .check_user_change_at("family", Weekday::Monday, TimeOfDay::new(15, 0))
+            .unwrap();
+        assert_eq!(event.previous_profile.as_deref(), Some("School"));
+        assert_eq!(event.new_profile.as_deref(), Some("Relaxed"));
+        assert!(controller
+            .current_profile()
+            .unwrap()
+            .content_rules
+            .rules
+            .is_empty());
+    }
+
+    #[test]
+    fn test_schedule_precedence() {
+        let profiles = create_scheduled_profiles();
+        let at_school = (Weekday::Monday, TimeOfDay::new(9, 0));
+
+        // Default: Alice's own profile wins over the schedule
+        let config = ProfileProxyConfig {
+            schedule: school_day_schedule(),
+            ..Default::default()
+        };
+        let controller =
+            ProfileProxyController::new(profiles.clone(), ProtectionManager::new(), config);
+        let event = controller
+            .check_user_change_at("alice", at_school.0, at_school.1)
+            .unwrap();
+        assert_eq!(event.new_profile.as_deref(), Some("Alice"));
+
+        let config = ProfileProxyConfig {
+            schedule: school_day_schedule(),
+            schedule_precedence: SchedulePrecedence::Schedule,
+            ..Default::default()
+        };
+        let controller = ProfileProxyController::new(profiles, ProtectionManager::new(), config);
+        let event = controller
+            .check_user_change_at("alice", at_school.0, at_school.1)
+            .unwrap();
+        assert_eq!(event.new_profile.as_deref(), Some("School"));
+    }
+
     // ==================== Determine Proxy Action Tests ====================
 
     #[test]
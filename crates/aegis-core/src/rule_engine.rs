@@ -5,17 +5,37 @@
 //!
 //! ## Evaluation Order
 //!
-//! 1. Time rules checked first - if blocked, return immediately
-//! 2. Content rules checked against classification matches
-//! 3. Default allow if no rules match
+//! 1. Time rules checked against the current day and time
+//! 2. Allowlisted prompts allowed without checking content rules
+//!    ([`RuleEngine::evaluate_prompt`] only)
+//! 3. Content rules checked against classification matches; during an
+//!    escalation window ([`TimeRuleSet::escalation_windows`]) rules that
+//!    would warn block instead
+//! 4. Prompts flagged with [`ClassificationResult::language_warning`] warn,
+//!    since the classifiers couldn't really check them
+//! 5. Default allow if no rules match
 //!
-//! The first matching rule determines the action, with time rules taking precedence.
+//! [`RuleEngine::evaluate_request`] also applies the content rules' model
+//! rules to the requested model; a model block wins whatever the prompt
+//! says, even for allowlisted prompts.
+//!
+//! ## Conflict Resolution
+//!
+//! When several rules trigger, [`resolve_conflicts`] picks one deterministically,
+//! regardless of the order rules were added or categories were detected:
+//!
+//! 1. Most restrictive action wins (Block > Warn > Allow)
+//! 2. Then rule tier: time rules before content rules
+//! 3. Then rule ID, in ascending order (then category name for content rules
+//!    sharing an ID)
 
 use chrono::{Datelike, Timelike};
 use serde::{Deserialize, Serialize};
 
-use crate::classifier::ClassificationResult;
-use crate::content_rules::{ContentAction, ContentRuleResult, ContentRuleSet};
+use crate::classifier::{ClassificationResult, Language};
+use crate::content_rules::{
+    AllowlistEntry, ContentAction, ContentRuleResult, ContentRuleSet, ModelRule,
+};
 use crate::time_rules::{TimeOfDay, TimeRule, TimeRuleSet, Weekday};
 
 /// Action to take based on rule evaluation.
@@ -40,8 +60,20 @@ impl RuleAction {
             RuleAction::Block => "Block",
         }
     }
+
+    /// Returns the precedence rank of this action (0 = most restrictive).
+    pub fn rank(&self) -> u8 {
+        match self {
+            RuleAction::Block => 0,
+            RuleAction::Warn => 1,
+            RuleAction::Allow => 2,
+        }
+    }
 }
 
+/// Rule ID reported for warnings about prompts in unsupported languages.
+pub const UNSUPPORTED_LANGUAGE_RULE_ID: &str = "unsupported_language";
+
 /// Which type of rule triggered the action.
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
@@ -58,24 +90,47 @@ pub enum RuleSource {
     },
     /// A content rule triggered the action.
     ContentRule(ContentRuleResult),
+    /// The prompt matched an allowlist entry, so content rules were skipped.
+    Allowlisted {
+        /// The allowlist pattern that matched.
+        pattern: String,
+    },
+    /// A model rule matched the requested model.
+    ModelRule {
+        /// The ID of the model rule that triggered.
+        rule_id: String,
+        /// The name of the model rule that triggered.
+        rule_name: String,
+        /// The requested model.
+        model: String,
+    },
+    /// The prompt is in a language the classifiers don't cover.
+    UnsupportedLanguage {
+        /// The detected language.
+        language: Language,
+    },
 }
 
 impl RuleSource {
     /// Returns the rule ID if a rule triggered, None otherwise.
     pub fn rule_id(&self) -> Option<&str> {
         match self {
-            RuleSource::None => None,
+            RuleSource::None | RuleSource::Allowlisted { .. } => None,
             RuleSource::TimeRule { rule_id, .. } => Some(rule_id),
             RuleSource::ContentRule(result) => Some(&result.rule_id),
+            RuleSource::ModelRule { rule_id, .. } => Some(rule_id),
+            RuleSource::UnsupportedLanguage { .. } => Some(UNSUPPORTED_LANGUAGE_RULE_ID),
         }
     }
 
     /// Returns the rule name if a rule triggered, None otherwise.
     pub fn rule_name(&self) -> Option<&str> {
         match self {
-            RuleSource::None => None,
+            RuleSource::None | RuleSource::Allowlisted { .. } => None,
             RuleSource::TimeRule { rule_name, .. } => Some(rule_name),
             RuleSource::ContentRule(result) => Some(&result.rule_name),
+            RuleSource::ModelRule { rule_name, .. } => Some(rule_name),
+            RuleSource::UnsupportedLanguage { .. } => Some("Unsupported language"),
         }
     }
 
@@ -93,6 +148,23 @@ impl RuleSource {
     pub fn is_content_rule(&self) -> bool {
         matches!(self, RuleSource::ContentRule(_))
     }
+
+    /// Returns true if the prompt was allowlisted.
+    pub fn is_allowlisted(&self) -> bool {
+        matches!(self, RuleSource::Allowlisted { .. })
+    }
+
+    /// Returns the tier rank of this source for conflict resolution
+    /// (0 = highest: time rules, then content rules, then no rule).
+    pub fn tier_rank(&self) -> u8 {
+        match self {
+            RuleSource::TimeRule { .. } => 0,
+            RuleSource::ContentRule(_)
+            | RuleSource::ModelRule { .. }
+            | RuleSource::UnsupportedLanguage { .. } => 1,
+            RuleSource::Allowlisted { .. } | RuleSource::None => 2,
+        }
+    }
 }
 
 /// Result of evaluating rules against a classification.
@@ -124,6 +196,16 @@ impl RuleEngineResult {
         }
     }
 
+    /// Creates an allow result for an allowlisted prompt.
+    pub fn allowlisted(entry: &AllowlistEntry) -> Self {
+        Self {
+            action: RuleAction::Allow,
+            source: RuleSource::Allowlisted {
+                pattern: entry.pattern.clone(),
+            },
+        }
+    }
+
     /// Creates a result from a content rule evaluation.
     pub fn from_content_result(result: ContentRuleResult) -> Self {
         let action = match result.action {
@@ -137,6 +219,44 @@ impl RuleEngineResult {
         }
     }
 
+    /// Creates a result from a model rule matching `model`.
+    pub fn from_model_rule(rule: &ModelRule, model: &str) -> Self {
+        let action = match rule.action {
+            ContentAction::Block => RuleAction::Block,
+            ContentAction::Warn => RuleAction::Warn,
+            ContentAction::Allow => RuleAction::Allow,
+        };
+        Self {
+            action,
+            source: RuleSource::ModelRule {
+                rule_id: rule.id.clone(),
+                rule_name: rule.name.clone(),
+                model: model.to_string(),
+            },
+        }
+    }
+
+    /// Creates a warning for a prompt in an unsupported `language`.
+    pub fn unsupported_language(language: Language) -> Self {
+        Self {
+            action: RuleAction::Warn,
+            source: RuleSource::UnsupportedLanguage { language },
+        }
+    }
+
+    /// Turns a content rule warning into a block because `window` is active,
+    /// naming the window in the rule name. Other results are unchanged.
+    fn escalate(mut self, window: &TimeRule) -> Self {
+        if let (RuleAction::Warn, RuleSource::ContentRule(content)) =
+            (self.action, &mut self.source)
+        {
+            content.action = ContentAction::Block;
+            content.rule_name = format!("{} ({})", content.rule_name, window.name);
+            self.action = RuleAction::Block;
+        }
+        self
+    }
+
     /// Returns true if the action is Block.
     pub fn should_block(&self) -> bool {
         self.action == RuleAction::Block
@@ -153,6 +273,35 @@ impl RuleEngineResult {
     }
 }
 
+/// Picks the winning result among the rules that triggered.
+///
+/// Ordering is most restrictive action, then tier (time before content), then
+/// rule ID, so the outcome is stable for equal-priority conflicts. Returns
+/// [`RuleEngineResult::allow`] if nothing triggered.
+pub fn resolve_conflicts(
+    candidates: impl IntoIterator<Item = RuleEngineResult>,
+) -> RuleEngineResult {
+    candidates
+        .into_iter()
+        .min_by(compare_precedence)
+        .unwrap_or_else(RuleEngineResult::allow)
+}
+
+/// Orders results so the one that should win comes first.
+fn compare_precedence(a: &RuleEngineResult, b: &RuleEngineResult) -> std::cmp::Ordering {
+    let category = |result: &RuleEngineResult| match &result.source {
+        RuleSource::ContentRule(content) => content.category.name(),
+        _ => "",
+    };
+
+    a.action
+        .rank()
+        .cmp(&b.action.rank())
+        .then_with(|| a.source.tier_rank().cmp(&b.source.tier_rank()))
+        .then_with(|| a.source.rule_id().cmp(&b.source.rule_id()))
+        .then_with(|| category(a).cmp(category(b)))
+}
+
 /// Rule engine that evaluates time and content rules.
 ///
 /// The rule engine combines time-based rules (e.g., bedtime, school hours)
@@ -212,48 +361,143 @@ impl RuleEngine {
 
     /// Evaluates the classification result at the given time.
     ///
-    /// Order of evaluation:
-    /// 1. Time rules checked first - if blocked, return immediately
-    /// 2. Content rules checked against classification matches
-    /// 3. Default allow if no rules match
+    /// Every triggered time and content rule is a candidate; the winner is
+    /// chosen by [`resolve_conflicts`]. Defaults to allow if no rules match.
     pub fn evaluate(
         &self,
         classification: &ClassificationResult,
         day: Weekday,
         time: TimeOfDay,
     ) -> RuleEngineResult {
-        // Step 1: Check time rules first
-        let blocking_time_rules = self.time_rules.blocking_rules(day, time);
-        if let Some(first_blocking) = blocking_time_rules.first() {
-            return RuleEngineResult::blocked_by_time(first_blocking);
+        let time_results = self.time_results(day, time);
+        let escalation = self.time_rules.escalation_window(day, time);
+
+        // Content rules triggered by the classification
+        let category_matches: Vec<_> = classification
+            .matches
+            .iter()
+            .map(|m| (m.category, m.confidence))
+            .collect();
+        let content_results = self
+            .content_rules
+            .evaluate_all(&category_matches)
+            .into_iter()
+            .map(RuleEngineResult::from_content_result)
+            .map(|result| match escalation {
+                Some(window) => result.escalate(window),
+                None => result,
+            });
+
+        // Prompts the classifiers couldn't check get a warning at least
+        let language_warning = classification
+            .language_warning
+            .then_some(classification.detected_language)
+            .flatten()
+            .map(RuleEngineResult::unsupported_language);
+
+        resolve_conflicts(time_results.chain(content_results).chain(language_warning))
+    }
+
+    /// Evaluates the prompt `text` and its classification at the given time.
+    ///
+    /// If `text` matches an allowlist entry, content rules are skipped and
+    /// the prompt is allowed whatever the classifier found. Time rules still
+    /// apply, so an allowlisted phrase doesn't get around bedtime.
+    pub fn evaluate_prompt(
+        &self,
+        text: &str,
+        classification: &ClassificationResult,
+        day: Weekday,
+        time: TimeOfDay,
+    ) -> RuleEngineResult {
+        let Some(entry) = self.content_rules.allowlist_match(text) else {
+            return self.evaluate(classification, day, time);
+        };
+
+        let time_result = resolve_conflicts(self.time_results(day, time));
+        if time_result.should_block() {
+            time_result
+        } else {
+            RuleEngineResult::allowlisted(entry)
         }
+    }
 
-        // Step 2: Check content rules against classification
-        if !classification.matches.is_empty() {
-            let category_matches: Vec<_> = classification
-                .matches
-                .iter()
-                .map(|m| (m.category, m.confidence))
-                .collect();
-
-            let results = self.content_rules.evaluate_all(&category_matches);
-            if let Some(first_result) = results.into_iter().next() {
-                return RuleEngineResult::from_content_result(first_result);
-            }
+    /// Evaluates a request for `model` (if it names one) carrying the prompt
+    /// `text`, applying model rules alongside [`evaluate_prompt`](Self::evaluate_prompt).
+    pub fn evaluate_request(
+        &self,
+        text: &str,
+        model: Option<&str>,
+        classification: &ClassificationResult,
+        day: Weekday,
+        time: TimeOfDay,
+    ) -> RuleEngineResult {
+        let prompt_result = self.evaluate_prompt(text, classification, day, time);
+        match model.and_then(|model| self.evaluate_model(model)) {
+            Some(model_result) => resolve_conflicts([prompt_result, model_result]),
+            None => prompt_result,
         }
+    }
 
-        // Step 3: Default allow
-        RuleEngineResult::allow()
+    /// Evaluates the model rules against `model`, returning `None` if none
+    /// triggered (see [`ContentRuleSet::evaluate_model`]).
+    pub fn evaluate_model(&self, model: &str) -> Option<RuleEngineResult> {
+        self.content_rules
+            .evaluate_model(model)
+            .map(|rule| RuleEngineResult::from_model_rule(rule, model))
     }
 
     /// Evaluates at the current time.
     pub fn evaluate_now(&self, classification: &ClassificationResult) -> RuleEngineResult {
-        let now = chrono::Local::now();
-        let day = Weekday::from_chrono(now.weekday());
-        let time = TimeOfDay::new(now.hour() as u8, now.minute() as u8);
+        let (day, time) = current_day_and_time();
         self.evaluate(classification, day, time)
     }
 
+    /// Evaluates the prompt `text` at the current time, honoring the allowlist.
+    pub fn evaluate_prompt_now(
+        &self,
+        text: &str,
+        classification: &ClassificationResult,
+    ) -> RuleEngineResult {
+        let (day, time) = current_day_and_time();
+        self.evaluate_prompt(text, classification, day, time)
+    }
+
+    /// Evaluates a request for `model` at the current time.
+    pub fn evaluate_request_now(
+        &self,
+        text: &str,
+        model: Option<&str>,
+        classification: &ClassificationResult,
+    ) -> RuleEngineResult {
+        let (day, time) = current_day_and_time();
+        self.evaluate_request(text, model, classification, day, time)
+    }
+
+    /// Adds a trusted phrase to the content rules' allowlist.
+    ///
+    /// Returns true if it wasn't already there.
+    pub fn add_allowlist_entry(&mut self, entry: AllowlistEntry) -> bool {
+        self.content_rules.add_allowlist_entry(entry)
+    }
+
+    /// Returns the allowlist entries.
+    pub fn allowlist(&self) -> &[AllowlistEntry] {
+        &self.content_rules.allowlist
+    }
+
+    /// Time rules that block at the given time.
+    fn time_results(
+        &self,
+        day: Weekday,
+        time: TimeOfDay,
+    ) -> impl Iterator<Item = RuleEngineResult> + '_ {
+        self.time_rules
+            .blocking_rules(day, time)
+            .into_iter()
+            .map(RuleEngineResult::blocked_by_time)
+    }
+
     /// Checks if the current time is blocked by time rules (ignoring content).
     pub fn is_time_blocked(&self, day: Weekday, time: TimeOfDay) -> bool {
         self.time_rules.is_blocked(day, time)
@@ -265,10 +509,19 @@ impl RuleEngine {
     }
 }
 
+fn current_day_and_time() -> (Weekday, TimeOfDay) {
+    let now = chrono::Local::now();
+    (
+        Weekday::from_chrono(now.weekday()),
+        TimeOfDay::new(now.hour() as u8, now.minute() as u8),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::classifier::{Category, CategoryMatch};
+    use crate::content_rules::{ContentRule, DEFAULT_ACTION_RULE_ID, DEFAULT_ACTION_THRESHOLD};
 
     // ==================== RuleAction Tests ====================
 
@@ -562,6 +815,360 @@ mod tests {
         assert!(!engine.is_time_blocked(Weekday::Wednesday, TimeOfDay::new(15, 0)));
     }
 
+    // ==================== Conflict Resolution Tests ====================
+
+    fn classification(matches: &[(Category, f32)]) -> ClassificationResult {
+        let matches = matches
+            .iter()
+            .map(|(category, confidence)| CategoryMatch::new(*category, *confidence, None))
+            .collect();
+        ClassificationResult::with_matches(matches, 0)
+    }
+
+    fn engine_with(rules: Vec<ContentRule>) -> RuleEngine {
+        RuleEngine {
+            time_rules: TimeRuleSet::new(),
+            content_rules: ContentRuleSet {
+                rules,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Evaluates with every ordering of `rules` and of the detected
+    /// categories, asserting they all produce the same winning rule.
+    fn stable_winner(rules: Vec<ContentRule>, matches: &[(Category, f32)]) -> RuleEngineResult {
+        let mut reversed_rules = rules.clone();
+        reversed_rules.reverse();
+        let mut reversed_matches = matches.to_vec();
+        reversed_matches.reverse();
+
+        let monday = Weekday::Monday;
+        let noon = TimeOfDay::new(12, 0);
+        let expected = engine_with(rules.clone()).evaluate(&classification(matches), monday, noon);
+        for engine in [engine_with(rules), engine_with(reversed_rules)] {
+            for matches in [matches, reversed_matches.as_slice()] {
+                let result = engine.evaluate(&classification(matches), monday, noon);
+                assert_eq!(result.action, expected.action);
+                assert_eq!(result.source, expected.source);
+            }
+        }
+        expected
+    }
+
+    #[test]
+    fn equal_priority_rules_resolve_by_rule_id() {
+        let result = stable_winner(
+            vec![
+                ContentRule::block("zeta_violence", Category::Violence, 0.5),
+                ContentRule::block("alpha_violence", Category::Violence, 0.5),
+            ],
+            &[(Category::Violence, 0.9)],
+        );
+
+        assert!(result.should_block());
+        assert_eq!(result.source.rule_id(), Some("alpha_violence"));
+    }
+
+    #[test]
+    fn most_restrictive_wins_across_categories() {
+        let result = stable_winner(
+            vec![
+                ContentRule::warn("a_violence_warn", Category::Violence, 0.5),
+                ContentRule::block("z_hate_block", Category::Hate, 0.5),
+                ContentRule::allow("b_adult_allow", Category::Adult),
+            ],
+            &[
+                (Category::Violence, 0.9),
+                (Category::Hate, 0.6),
+                (Category::Adult, 0.9),
+            ],
+        );
+
+        assert!(result.should_block());
+        assert_eq!(result.source.rule_id(), Some("z_hate_block"));
+    }
+
+    #[test]
+    fn equal_priority_categories_resolve_by_rule_id() {
+        let result = stable_winner(
+            vec![
+                ContentRule::block("violence_block", Category::Violence, 0.5),
+                ContentRule::block("hate_block", Category::Hate, 0.5),
+            ],
+            &[(Category::Violence, 0.9), (Category::Hate, 0.9)],
+        );
+
+        assert_eq!(result.source.rule_id(), Some("hate_block"));
+    }
+
+    #[test]
+    fn most_restrictive_wins_within_category() {
+        let result = stable_winner(
+            vec![
+                ContentRule::allow("a_allow", Category::Violence),
+                ContentRule::warn("b_warn", Category::Violence, 0.5),
+            ],
+            &[(Category::Violence, 0.9)],
+        );
+
+        assert!(result.should_warn());
+        assert_eq!(result.source.rule_id(), Some("b_warn"));
+    }
+
+    #[test]
+    fn time_rules_outrank_content_rules_at_equal_action() {
+        use crate::time_rules::TimeRange;
+
+        let mut engine = engine_with(vec![ContentRule::block(
+            "a_violence",
+            Category::Violence,
+            0.5,
+        )]);
+        for id in ["z_bedtime", "m_bedtime"] {
+            engine.time_rules.add_rule(TimeRule::new(
+                id,
+                "Bedtime",
+                vec![Weekday::Monday],
+                TimeRange::from_hours(21, 7),
+            ));
+        }
+
+        let result = engine.evaluate(
+            &classification(&[(Category::Violence, 0.9)]),
+            Weekday::Monday,
+            TimeOfDay::new(22, 0),
+        );
+
+        assert!(result.source.is_time_rule());
+        assert_eq!(result.source.rule_id(), Some("m_bedtime"));
+    }
+
+    // ==================== Allowlist Tests ====================
+
+    const HOMEWORK: &str = "How do explosives work in chemistry class?";
+
+    #[test]
+    fn allowlisted_prompt_skips_content_rules() {
+        let mut engine = engine_with(vec![ContentRule::block(
+            "violence_block",
+            Category::Violence,
+            0.5,
+        )]);
+        let violent = classification(&[(Category::Violence, 0.95)]);
+        let monday_noon = (Weekday::Monday, TimeOfDay::new(12, 0));
+
+        assert!(engine
+            .evaluate_prompt(HOMEWORK, &violent, monday_noon.0, monday_noon.1)
+            .should_block());
+
+        assert!(engine.add_allowlist_entry(AllowlistEntry::substring("CHEMISTRY CLASS")));
+        assert!(!engine.add_allowlist_entry(AllowlistEntry::substring("CHEMISTRY CLASS")));
+
+        let result = engine.evaluate_prompt(HOMEWORK, &violent, monday_noon.0, monday_noon.1);
+        assert!(result.should_allow());
+        assert!(result.source.is_allowlisted());
+        assert_eq!(
+            result.source,
+            RuleSource::Allowlisted {
+                pattern: "CHEMISTRY CLASS".to_string()
+            }
+        );
+
+        // Other prompts and the text-less path are unaffected
+        assert!(engine
+            .evaluate_prompt(
+                "how to hurt someone",
+                &violent,
+                monday_noon.0,
+                monday_noon.1
+            )
+            .should_block());
+        assert!(engine
+            .evaluate(&violent, monday_noon.0, monday_noon.1)
+            .should_block());
+    }
+
+    #[test]
+    fn allowlist_regex_entries() {
+        let mut engine = engine_with(vec![ContentRule::block(
+            "violence_block",
+            Category::Violence,
+            0.5,
+        )]);
+        engine
+            .add_allowlist_entry(AllowlistEntry::regex(r"\bexplosives?\b.*\bchemistry\b").unwrap());
+        let violent = classification(&[(Category::Violence, 0.95)]);
+
+        let result =
+            engine.evaluate_prompt(HOMEWORK, &violent, Weekday::Monday, TimeOfDay::new(12, 0));
+        assert!(result.source.is_allowlisted());
+        assert!(AllowlistEntry::regex("(unclosed").is_err());
+    }
+
+    #[test]
+    fn allowlist_does_not_override_time_rules() {
+        use crate::time_rules::TimeRange;
+
+        let mut engine = engine_with(vec![]);
+        engine.time_rules.add_rule(TimeRule::new(
+            "bedtime",
+            "Bedtime",
+            vec![Weekday::Monday],
+            TimeRange::from_hours(21, 7),
+        ));
+        engine.add_allowlist_entry(AllowlistEntry::substring("chemistry"));
+
+        let result = engine.evaluate_prompt(
+            HOMEWORK,
+            &classification(&[]),
+            Weekday::Monday,
+            TimeOfDay::new(22, 0),
+        );
+        assert!(result.should_block());
+        assert!(result.source.is_time_rule());
+    }
+
+    #[test]
+    fn category_without_rule_uses_default_action() {
+        let mut engine = engine_with(vec![ContentRule::allow(
+            "violence_allow",
+            Category::Violence,
+        )]);
+        engine.content_rules = engine
+            .content_rules
+            .with_default_action(Category::Violence, ContentAction::Block)
+            .with_default_action(Category::Hate, ContentAction::Block)
+            .with_default_action(Category::Profanity, ContentAction::Warn);
+
+        let result = engine.evaluate_now(&classification(&[(Category::Profanity, 0.8)]));
+        assert!(result.should_warn());
+        assert_eq!(result.source.rule_id(), Some(DEFAULT_ACTION_RULE_ID));
+
+        let result = engine.evaluate_now(&classification(&[
+            (Category::Profanity, 0.8),
+            (Category::Hate, 0.7),
+        ]));
+        assert!(result.should_block());
+
+        // An explicit rule overrides the default for its category
+        let result = engine.evaluate_now(&classification(&[(Category::Violence, 0.9)]));
+        assert!(result.should_allow());
+        assert_eq!(result.source.rule_id(), Some("violence_allow"));
+
+        // Below the default threshold, and categories with no default, allow
+        let result = engine.evaluate_now(&classification(&[
+            (Category::Hate, DEFAULT_ACTION_THRESHOLD - 0.1),
+            (Category::Adult, 0.9),
+        ]));
+        assert!(result.should_allow());
+        assert!(!result.source.has_rule());
+    }
+
+    #[test]
+    fn warnings_escalate_to_blocks_during_window() {
+        use crate::time_rules::TimeRange;
+
+        let mut engine = engine_with(vec![ContentRule::warn(
+            "profanity_warn",
+            Category::Profanity,
+            0.5,
+        )]);
+        engine.time_rules.add_escalation_window(TimeRule::new(
+            "bedtime_strict",
+            "Bedtime",
+            Weekday::all(),
+            TimeRange::from_hours(21, 7),
+        ));
+        let profanity = classification(&[(Category::Profanity, 0.8)]);
+
+        let result = engine.evaluate(&profanity, Weekday::Monday, TimeOfDay::new(14, 0));
+        assert!(result.should_warn());
+
+        for time in [TimeOfDay::new(22, 0), TimeOfDay::new(6, 30)] {
+            let result = engine.evaluate(&profanity, Weekday::Monday, time);
+            assert!(result.should_block());
+            assert_eq!(result.source.rule_id(), Some("profanity_warn"));
+            assert_eq!(result.source.rule_name(), Some("Warn Profanity (Bedtime)"));
+        }
+
+        // Nothing else changes: no match still allows
+        let result = engine.evaluate(&classification(&[]), Weekday::Monday, TimeOfDay::new(22, 0));
+        assert!(result.should_allow());
+    }
+
+    #[test]
+    fn denied_model_blocks_whatever_the_prompt() {
+        let mut engine = RuleEngine::with_defaults();
+        engine
+            .content_rules
+            .add_model_rule(ModelRule::block("no_dolphin", "*dolphin*"));
+        engine
+            .content_rules
+            .add_allowlist_entry(AllowlistEntry::substring("homework"));
+        let (day, time) = (Weekday::Wednesday, TimeOfDay::new(14, 0));
+        let safe = ClassificationResult::safe(0);
+
+        for text in ["What is 2 + 2?", "help with my homework"] {
+            let result =
+                engine.evaluate_request(text, Some("Dolphin-2.9-Llama3"), &safe, day, time);
+            assert!(result.should_block(), "{}", text);
+            assert_eq!(result.source.rule_id(), Some("no_dolphin"));
+            assert!(matches!(
+                result.source,
+                RuleSource::ModelRule { ref model, .. } if model == "Dolphin-2.9-Llama3"
+            ));
+        }
+
+        // Other models, and requests without one, get the usual rules
+        let allowed = engine.evaluate_request("What is 2 + 2?", Some("gpt-4o"), &safe, day, time);
+        assert!(allowed.should_allow());
+        assert!(!allowed.source.has_rule());
+        assert!(engine
+            .evaluate_request("What is 2 + 2?", None, &safe, day, time)
+            .should_allow());
+        let violent = classification(&[(Category::Violence, 0.9)]);
+        assert!(engine
+            .evaluate_request("hurt", Some("gpt-4o"), &violent, day, time)
+            .should_block());
+    }
+
+    #[test]
+    fn language_warning_warns_unless_a_rule_blocks() {
+        let engine = RuleEngine::with_defaults();
+        let (day, time) = (Weekday::Wednesday, TimeOfDay::new(14, 0));
+
+        let mut unclassified = ClassificationResult::safe(0);
+        unclassified.detected_language = Some(Language::Spanish);
+        unclassified.language_warning = true;
+        let result = engine.evaluate(&unclassified, day, time);
+        assert!(result.should_warn());
+        assert_eq!(
+            result.source,
+            RuleSource::UnsupportedLanguage {
+                language: Language::Spanish
+            }
+        );
+        assert_eq!(result.source.rule_id(), Some(UNSUPPORTED_LANGUAGE_RULE_ID));
+
+        // Blocking rules still win
+        let mut blocked = classification(&[(Category::Violence, 0.9)]);
+        blocked.detected_language = Some(Language::Spanish);
+        blocked.language_warning = true;
+        assert!(engine.evaluate(&blocked, day, time).should_block());
+
+        // Tagging the language alone doesn't warn
+        unclassified.language_warning = false;
+        assert!(engine.evaluate(&unclassified, day, time).should_allow());
+    }
+
+    #[test]
+    fn resolve_conflicts_without_candidates_allows() {
+        let result = resolve_conflicts(Vec::new());
+        assert!(result.should_allow());
+        assert!(!result.source.has_rule());
+    }
+
     // ==================== Serialization Tests ====================
 
     #[test]
@@ -594,6 +1201,13 @@ mod tests {
         let json = serde_json::to_string(&source).unwrap();
         let deserialized: RuleSource = serde_json::from_str(&json).unwrap();
         assert_eq!(source, deserialized);
+        let source = RuleSource::Allowlisted {
+            pattern: "chemistry".to_string(),
+        };
+        let json = serde_json::to_string(&source).unwrap();
+        assert!(json.contains("\"type\":\"allowlisted\""));
+        let deserialized: RuleSource = serde_json::from_str(&json).unwrap();
+        assert_eq!(source, deserialized);
     }
 
     #[test]
@@ -0,0 +1,229 @@
+//! Dry-run validation of rule drafts (F007).
+//!
+//! Checks a draft of time rules, content rules, and keyword patterns without
+//! applying it, so the dashboard can show problems before saving.
+//! [`Severity::Error`] issues would break or be rejected at load time;
+//! [`Severity::Warning`] issues load fine but are probably mistakes, such as
+//! overlapping time rules or content rules with conflicting actions.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::community_rules::pattern_to_regex;
+use crate::content_rules::ContentRuleSet;
+use crate::rule_engine::RuleEngine;
+use crate::time_rules::TimeRuleSet;
+
+/// How serious a [`RuleIssue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The draft can't be saved as-is.
+    Error,
+    /// The draft works but probably isn't what was intended.
+    Warning,
+}
+
+/// A problem found in a rule draft.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleIssue {
+    /// Error or warning.
+    pub severity: Severity,
+    /// Draft section: `time_rules`, `content_rules`, or `patterns`.
+    pub field: String,
+    /// ID of the offending rule, if the issue concerns one rule.
+    pub rule_id: Option<String>,
+    /// What's wrong.
+    pub message: String,
+}
+
+impl RuleIssue {
+    /// Creates an error.
+    pub fn error(field: &str, rule_id: Option<&str>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, field, rule_id, message)
+    }
+
+    /// Creates a warning.
+    pub fn warning(field: &str, rule_id: Option<&str>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, field, rule_id, message)
+    }
+
+    fn new(
+        severity: Severity,
+        field: &str,
+        rule_id: Option<&str>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            severity,
+            field: field.to_string(),
+            rule_id: rule_id.map(str::to_string),
+            message: message.into(),
+        }
+    }
+
+    /// Returns true for [`Severity::Error`].
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// A keyword pattern in a draft.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternDraft {
+    /// Pattern ID, if it has one.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Literal word or regex.
+    pub pattern: String,
+    /// Whether `pattern` is a regex.
+    #[serde(default)]
+    pub is_regex: bool,
+}
+
+/// Rules to validate. Sections left out (or `null`/`{}`) are not checked.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RuleDraft {
+    /// Time rules, as stored in a profile's `time_rules`.
+    #[serde(default)]
+    pub time_rules: serde_json::Value,
+    /// Content rules, as stored in a profile's `content_rules`.
+    #[serde(default)]
+    pub content_rules: serde_json::Value,
+    /// Keyword patterns.
+    #[serde(default)]
+    pub patterns: Vec<PatternDraft>,
+}
+
+impl RuleDraft {
+    /// Returns every issue in the draft, errors and warnings, in draft order.
+    pub fn validate(&self) -> Vec<RuleIssue> {
+        let mut issues = Vec::new();
+
+        match parse_section::<TimeRuleSet>(&self.time_rules) {
+            Ok(rules) => issues.extend(rules.validate()),
+            Err(e) => issues.push(RuleIssue::error("time_rules", None, e.to_string())),
+        }
+        match parse_section::<ContentRuleSet>(&self.content_rules) {
+            Ok(rules) => issues.extend(rules.validate()),
+            Err(e) => issues.push(RuleIssue::error("content_rules", None, e.to_string())),
+        }
+        for pattern in &self.patterns {
+            if let Err(message) = validate_pattern(&pattern.pattern, pattern.is_regex) {
+                issues.push(RuleIssue::error("patterns", pattern.id.as_deref(), message));
+            }
+        }
+
+        issues
+    }
+
+    /// Builds a rule engine from the draft's time and content rules.
+    ///
+    /// Returns the error issues instead if the draft has any. Keyword
+    /// patterns are validated but don't affect the engine, which only
+    /// evaluates classifications.
+    pub fn to_engine(&self) -> Result<RuleEngine, Vec<RuleIssue>> {
+        let errors: Vec<RuleIssue> = self
+            .validate()
+            .into_iter()
+            .filter(RuleIssue::is_error)
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        // Both sections parsed during validation
+        Ok(RuleEngine {
+            time_rules: parse_section(&self.time_rules).unwrap_or_default(),
+            content_rules: parse_section(&self.content_rules).unwrap_or_default(),
+        })
+    }
+}
+
+/// Checks that a keyword pattern compiles the way the classifier will use it.
+pub fn validate_pattern(pattern: &str, is_regex: bool) -> Result<(), String> {
+    if pattern.trim().is_empty() {
+        return Err("pattern is empty".to_string());
+    }
+    Regex::new(&pattern_to_regex(pattern, is_regex))
+        .map(|_| ())
+        .map_err(|e| format!("invalid regex '{}': {}", pattern, e))
+}
+
+fn parse_section<T: Default + serde::de::DeserializeOwned>(
+    value: &serde_json::Value,
+) -> Result<T, serde_json::Error> {
+    match value {
+        serde_json::Value::Null => Ok(T::default()),
+        serde_json::Value::Object(map) if map.is_empty() => Ok(T::default()),
+        _ => serde_json::from_value(value.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn draft(value: serde_json::Value) -> RuleDraft {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn empty_draft_is_valid() {
+        assert!(RuleDraft::default().validate().is_empty());
+        assert!(draft(json!({"time_rules": {}, "content_rules": null}))
+            .validate()
+            .is_empty());
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        let issues = draft(json!({
+            "patterns": [
+                {"id": "ok", "pattern": "pick (a|the) lock", "is_regex": true},
+                {"id": "broken", "pattern": "kill (", "is_regex": true},
+                {"pattern": "kill (", "is_regex": false}
+            ]
+        }))
+        .validate();
+
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_error());
+        assert_eq!(issues[0].field, "patterns");
+        assert_eq!(issues[0].rule_id.as_deref(), Some("broken"));
+        assert!(issues[0].message.starts_with("invalid regex 'kill ('"));
+    }
+
+    #[test]
+    fn to_engine_builds_from_valid_drafts_only() {
+        let engine = draft(json!({
+            "content_rules": {"rules": [{
+                "id": "violence-high",
+                "name": "Violence",
+                "category": "violence",
+                "action": "block",
+                "threshold": 0.5,
+                "enabled": true
+            }]}
+        }))
+        .to_engine()
+        .unwrap();
+        assert_eq!(engine.content_rules.rules.len(), 1);
+        assert!(engine.time_rules.rules.is_empty());
+
+        let errors = draft(json!({"content_rules": {"rules": 3}}))
+            .to_engine()
+            .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "content_rules");
+    }
+
+    #[test]
+    fn unparseable_section_is_an_error() {
+        let issues = draft(json!({"time_rules": {"rules": "nope"}})).validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "time_rules");
+        assert!(issues[0].is_error());
+    }
+}
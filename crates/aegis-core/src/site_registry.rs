@@ -112,6 +112,18 @@ pub struct SiteEntry {
     pub source: SiteSource,
     /// Priority for pattern matching (higher = checked first).
     pub priority: i32,
+    /// Path patterns for non-prompt traffic (telemetry, analytics) that is
+    /// forwarded without inspection. See [`path_matches`] for syntax.
+    #[serde(default)]
+    pub skip_paths: Vec<String>,
+    /// Known prompt-bearing endpoint patterns. When non-empty, only matching
+    /// paths are inspected and all other traffic is forwarded immediately.
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    /// Service name reported for prompts to this site (None = derived from
+    /// `name`).
+    #[serde(default)]
+    pub service_name: Option<String>,
 }
 
 impl SiteEntry {
@@ -125,6 +137,9 @@ impl SiteEntry {
             enabled: true,
             source: SiteSource::default(),
             priority: 0,
+            skip_paths: Vec::new(),
+            endpoints: Vec::new(),
+            service_name: None,
         }
     }
 
@@ -142,6 +157,9 @@ impl SiteEntry {
             enabled: true,
             source: SiteSource::Bundled,
             priority: 0,
+            skip_paths: Vec::new(),
+            endpoints: Vec::new(),
+            service_name: None,
         }
     }
 
@@ -155,6 +173,9 @@ impl SiteEntry {
             enabled: true,
             source: SiteSource::Custom,
             priority: 100, // Custom sites have higher priority
+            skip_paths: Vec::new(),
+            endpoints: Vec::new(),
+            service_name: None,
         }
     }
 
@@ -182,6 +203,46 @@ impl SiteEntry {
         self
     }
 
+    /// Sets the skip-path patterns.
+    pub fn with_skip_paths(mut self, paths: &[&str]) -> Self {
+        self.skip_paths = paths.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Sets the service name reported for prompts to this site.
+    pub fn with_service_name(mut self, service_name: impl Into<String>) -> Self {
+        self.service_name = Some(service_name.into());
+        self
+    }
+
+    /// Sets the prompt-bearing endpoint patterns.
+    pub fn with_endpoints(mut self, endpoints: &[&str]) -> Self {
+        self.endpoints = endpoints.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Checks if a request path is a known prompt-bearing endpoint.
+    ///
+    /// Sites without an endpoint list accept every path.
+    pub fn is_prompt_endpoint(&self, path: &str) -> bool {
+        self.endpoints.is_empty()
+            || self
+                .endpoints
+                .iter()
+                .any(|pattern| path_matches(pattern, path))
+    }
+
+    /// Checks if a request path is non-prompt traffic for this site.
+    ///
+    /// Matches against the site's own skip paths and [`DEFAULT_SKIP_PATHS`].
+    pub fn should_skip_path(&self, path: &str) -> bool {
+        DEFAULT_SKIP_PATHS
+            .iter()
+            .copied()
+            .chain(self.skip_paths.iter().map(String::as_str))
+            .any(|pattern| path_matches(pattern, path))
+    }
+
     /// Checks if this is a wildcard pattern.
     pub fn is_wildcard(&self) -> bool {
         self.pattern.starts_with('*')
@@ -235,6 +296,54 @@ impl SiteEntry {
     }
 }
 
+// =============================================================================
+// Path Matching
+// =============================================================================
+
+/// Telemetry and analytics paths skipped on every monitored site.
+pub const DEFAULT_SKIP_PATHS: &[&str] = &[
+    "/cdn-cgi/*",
+    "/_vercel/insights/*",
+    "/v1/rgstr*",
+    "/v1/initialize*",
+    "/sentry/*",
+    "/api/telemetry*",
+    "/telemetry/*",
+    "/analytics/*",
+];
+
+/// Checks if a request path matches a path pattern.
+///
+/// `*` matches any run of characters (including `/`); all other characters
+/// match literally. The query string is ignored, so `/v1/rgstr*` matches
+/// `/v1/rgstr?k=client-abc`.
+pub fn path_matches(pattern: &str, path: &str) -> bool {
+    let path = path.split('?').next().unwrap_or(path);
+    let segments: Vec<&str> = pattern.split('*').collect();
+
+    // No wildcard: exact match
+    if segments.len() == 1 {
+        return path == pattern;
+    }
+
+    let first = segments[0];
+    let last = segments[segments.len() - 1];
+    if !path.starts_with(first) || path.len() < first.len() + last.len() {
+        return false;
+    }
+
+    // Middle segments must appear in order between the prefix and suffix
+    let mut rest = &path[first.len()..path.len() - last.len()];
+    for segment in &segments[1..segments.len() - 1] {
+        match rest.find(segment) {
+            Some(pos) => rest = &rest[pos + segment.len()..],
+            None => return false,
+        }
+    }
+
+    path.ends_with(last)
+}
+
 // =============================================================================
 // Bundled Default Sites
 // =============================================================================
@@ -242,34 +351,105 @@ impl SiteEntry {
 /// Version hash for update detection.
 pub const BUNDLED_VERSION: &str = "v1.0.0";
 
+/// ChatGPT web telemetry (Statsig, CES analytics, latency beacons).
+const CHATGPT_SKIP_PATHS: &[&str] = &["/ces/*", "/backend-api/lat/*"];
+
+/// Claude web telemetry (event logging, Sentry tunnel).
+const CLAUDE_SKIP_PATHS: &[&str] = &["/api/event_logging/*", "/sentry*"];
+
+/// Gemini web telemetry (CSP reports, client logging).
+const GEMINI_SKIP_PATHS: &[&str] = &["/_/BardChatUi/cspreport*", "/_/BardChatUi/jserror*"];
+
+/// OpenAI API prompt endpoints.
+const OPENAI_API_ENDPOINTS: &[&str] = &[
+    "/v1/chat/completions",
+    "/v1/completions",
+    "/v1/responses",
+    "/v1/images/generations",
+    "/v1/images/edits",
+    "/v1/threads/runs",
+    "/v1/threads/*/messages",
+];
+
+/// ChatGPT web prompt endpoints.
+const CHATGPT_ENDPOINTS: &[&str] = &[
+    "/backend-api/conversation",
+    "/backend-anon/conversation",
+    "/backend-api/f/conversation",
+    "/backend-anon/f/conversation",
+];
+
+/// Anthropic API prompt endpoints.
+const ANTHROPIC_API_ENDPOINTS: &[&str] = &["/v1/messages", "/v1/complete"];
+
+/// Claude web prompt endpoints.
+const CLAUDE_ENDPOINTS: &[&str] = &[
+    "/api/organizations/*/chat_conversations/*/completion",
+    "/api/organizations/*/chat_conversations/*/retry_completion",
+];
+
+/// Google AI API prompt endpoints.
+const GOOGLE_API_ENDPOINTS: &[&str] = &[
+    "/v1*/models/*:generateContent",
+    "/v1*/models/*:streamGenerateContent",
+];
+
+/// Gemini web prompt endpoints (batchexecute-style RPCs).
+const GEMINI_ENDPOINTS: &[&str] = &["/_/BardChatUi/data/*"];
+
+/// OpenAI-compatible chat API endpoints (xAI, Mistral).
+const OPENAI_COMPATIBLE_ENDPOINTS: &[&str] = &[
+    "/v1/chat/completions",
+    "/v1/completions",
+    "/v1/fim/completions",
+    "/v1/agents/completions",
+    "/v1/messages",
+];
+
+/// Perplexity API prompt endpoints.
+const PERPLEXITY_API_ENDPOINTS: &[&str] = &["/chat/completions"];
+
+/// Cohere API prompt endpoints.
+const COHERE_API_ENDPOINTS: &[&str] = &["/v1/chat", "/v2/chat", "/v1/generate"];
+
 /// Returns the bundled default sites.
 pub fn bundled_sites() -> Vec<SiteEntry> {
     vec![
         // OpenAI - API
         SiteEntry::bundled("api.openai.com", "OpenAI API", SiteCategory::Api)
             .with_parser_id("openai_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_endpoints(OPENAI_API_ENDPOINTS),
         // OpenAI - Consumer
         SiteEntry::bundled("chat.openai.com", "ChatGPT", SiteCategory::Consumer)
             .with_parser_id("openai_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_skip_paths(CHATGPT_SKIP_PATHS)
+            .with_endpoints(CHATGPT_ENDPOINTS),
         SiteEntry::bundled("chatgpt.com", "ChatGPT", SiteCategory::Consumer)
             .with_parser_id("openai_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_skip_paths(CHATGPT_SKIP_PATHS)
+            .with_endpoints(CHATGPT_ENDPOINTS),
         SiteEntry::bundled("*.chatgpt.com", "ChatGPT", SiteCategory::Consumer)
             .with_parser_id("openai_json")
-            .with_priority(5),
+            .with_priority(5)
+            .with_skip_paths(CHATGPT_SKIP_PATHS),
         // Anthropic - API
         SiteEntry::bundled("api.anthropic.com", "Anthropic API", SiteCategory::Api)
             .with_parser_id("anthropic_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_endpoints(ANTHROPIC_API_ENDPOINTS),
         // Anthropic - Consumer
         SiteEntry::bundled("claude.ai", "Claude", SiteCategory::Consumer)
             .with_parser_id("anthropic_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_skip_paths(CLAUDE_SKIP_PATHS)
+            .with_endpoints(CLAUDE_ENDPOINTS),
         SiteEntry::bundled("*.claude.ai", "Claude", SiteCategory::Consumer)
             .with_parser_id("anthropic_json")
-            .with_priority(5),
+            .with_priority(5)
+            .with_skip_paths(CLAUDE_SKIP_PATHS),
         // Google - API
         SiteEntry::bundled(
             "generativelanguage.googleapis.com",
@@ -277,18 +457,22 @@ pub fn bundled_sites() -> Vec<SiteEntry> {
             SiteCategory::Api,
         )
         .with_parser_id("google_json")
-        .with_priority(10),
+        .with_priority(10)
+        .with_endpoints(GOOGLE_API_ENDPOINTS),
         // Google - Consumer
         SiteEntry::bundled("gemini.google.com", "Gemini", SiteCategory::Consumer)
             .with_parser_id("google_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_skip_paths(GEMINI_SKIP_PATHS)
+            .with_endpoints(GEMINI_ENDPOINTS),
         SiteEntry::bundled("aistudio.google.com", "AI Studio", SiteCategory::Consumer)
             .with_parser_id("google_json")
             .with_priority(10),
         // xAI (Grok)
         SiteEntry::bundled("api.x.ai", "xAI API", SiteCategory::Api)
             .with_parser_id("openai_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_endpoints(OPENAI_COMPATIBLE_ENDPOINTS),
         SiteEntry::bundled("grok.x.ai", "Grok", SiteCategory::Consumer)
             .with_parser_id("openai_json")
             .with_priority(10),
@@ -298,7 +482,8 @@ pub fn bundled_sites() -> Vec<SiteEntry> {
         // Perplexity
         SiteEntry::bundled("api.perplexity.ai", "Perplexity API", SiteCategory::Api)
             .with_parser_id("openai_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_endpoints(PERPLEXITY_API_ENDPOINTS),
         SiteEntry::bundled("perplexity.ai", "Perplexity", SiteCategory::Consumer)
             .with_parser_id("openai_json")
             .with_priority(10),
@@ -308,7 +493,8 @@ pub fn bundled_sites() -> Vec<SiteEntry> {
         // Mistral
         SiteEntry::bundled("api.mistral.ai", "Mistral API", SiteCategory::Api)
             .with_parser_id("openai_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_endpoints(OPENAI_COMPATIBLE_ENDPOINTS),
         SiteEntry::bundled("chat.mistral.ai", "Mistral Chat", SiteCategory::Consumer)
             .with_parser_id("openai_json")
             .with_priority(10),
@@ -318,7 +504,8 @@ pub fn bundled_sites() -> Vec<SiteEntry> {
         // Cohere
         SiteEntry::bundled("api.cohere.ai", "Cohere API", SiteCategory::Api)
             .with_parser_id("openai_json")
-            .with_priority(10),
+            .with_priority(10)
+            .with_endpoints(COHERE_API_ENDPOINTS),
         SiteEntry::bundled("coral.cohere.com", "Cohere Coral", SiteCategory::Consumer)
             .with_parser_id("openai_json")
             .with_priority(10),
@@ -531,7 +718,7 @@ impl SiteRegistry {
         }
 
         // Sort wildcards by priority (descending)
-        wildcard.sort_by(|a, b| b.priority.cmp(&a.priority));
+        wildcard.sort_by_key(|s| std::cmp::Reverse(s.priority));
 
         // Clear cache after reload
         self.cache.write().unwrap().clear();
@@ -637,6 +824,22 @@ impl SiteRegistry {
         }
     }
 
+    /// Checks if a request path on a monitored host is non-prompt traffic
+    /// (telemetry, analytics) that should be forwarded without inspection.
+    pub fn should_skip_path(&self, host: &str, path: &str) -> bool {
+        self.get_site(host)
+            .is_some_and(|lookup| lookup.entry.should_skip_path(path))
+    }
+
+    /// Checks if a request path on a host is a prompt-bearing endpoint.
+    ///
+    /// Unknown hosts and sites without an endpoint list return `true`, leaving
+    /// the decision to the caller's domain check.
+    pub fn is_prompt_endpoint(&self, host: &str, path: &str) -> bool {
+        self.get_site(host)
+            .is_none_or(|lookup| lookup.entry.is_prompt_endpoint(path))
+    }
+
     /// Gets the parser ID for a host.
     pub fn parser_id(&self, host: &str) -> Option<String> {
         self.get_site(host)
@@ -648,7 +851,7 @@ impl SiteRegistry {
         if entry.is_wildcard() {
             let mut wildcards = self.wildcard_sites.write().unwrap();
             wildcards.push(entry);
-            wildcards.sort_by(|a, b| b.priority.cmp(&a.priority));
+            wildcards.sort_by_key(|s| std::cmp::Reverse(s.priority));
         } else {
             let mut exact = self.exact_sites.write().unwrap();
             exact.insert(entry.pattern.clone(), entry);
@@ -725,7 +928,7 @@ impl SiteRegistry {
                     if site.is_wildcard() {
                         let mut wildcards = self.wildcard_sites.write().unwrap();
                         wildcards.push(site);
-                        wildcards.sort_by(|a, b| b.priority.cmp(&a.priority));
+                        wildcards.sort_by_key(|s| std::cmp::Reverse(s.priority));
                     } else {
                         let mut exact = self.exact_sites.write().unwrap();
                         exact.insert(site.pattern.clone(), site);
@@ -1418,4 +1621,92 @@ mod tests {
             "At least some image gen sites should have parser IDs"
         );
     }
+
+    // ==================== Skip Path Tests ====================
+
+    #[test]
+    fn path_matches_exact_and_wildcards() {
+        assert!(path_matches("/v1/chat/completions", "/v1/chat/completions"));
+        assert!(!path_matches(
+            "/v1/chat/completions",
+            "/v1/chat/completions/x"
+        ));
+        assert!(path_matches("/ces/*", "/ces/v1/t"));
+        assert!(!path_matches("/ces/*", "/backend-api/conversation"));
+        assert!(path_matches(
+            "/v1/rgstr*",
+            "/v1/rgstr?k=client-abc&st=javascript"
+        ));
+        assert!(path_matches(
+            "/v1beta/models/*:generateContent",
+            "/v1beta/models/gemini-pro:generateContent"
+        ));
+        assert!(!path_matches(
+            "/v1beta/models/*:generateContent",
+            "/v1beta/models/gemini-pro:countTokens"
+        ));
+    }
+
+    #[test]
+    fn registry_skips_telemetry_paths() {
+        let registry = SiteRegistry::with_defaults();
+
+        assert!(registry.should_skip_path("chatgpt.com", "/ces/v1/t"));
+        assert!(registry.should_skip_path("chatgpt.com", "/v1/rgstr?k=abc"));
+        assert!(registry.should_skip_path("claude.ai", "/api/event_logging/batch"));
+        assert!(!registry.should_skip_path("chatgpt.com", "/backend-api/conversation"));
+        assert!(!registry.should_skip_path("api.openai.com", "/v1/chat/completions"));
+
+        // Unknown hosts are never skipped by the registry
+        assert!(!registry.should_skip_path("example.com", "/ces/v1/t"));
+    }
+
+    #[test]
+    fn custom_site_skip_paths() {
+        let registry = SiteRegistry::new();
+        registry.add_custom(
+            SiteEntry::custom("llm.corp.example", "Corp LLM").with_skip_paths(&["/metrics/*"]),
+        );
+
+        assert!(registry.should_skip_path("llm.corp.example", "/metrics/push"));
+        assert!(!registry.should_skip_path("llm.corp.example", "/api/chat"));
+    }
+
+    #[test]
+    fn skip_paths_deserialize_default() {
+        let json = r#"{"pattern":"a.com","name":"A","category":"consumer","parser_id":null,"enabled":true,"source":"custom","priority":0}"#;
+        let entry: SiteEntry = serde_json::from_str(json).unwrap();
+        assert!(entry.skip_paths.is_empty());
+    }
+
+    // ==================== Endpoint Allowlist Tests ====================
+
+    #[test]
+    fn registry_prompt_endpoints() {
+        let registry = SiteRegistry::with_defaults();
+
+        assert!(registry.is_prompt_endpoint("api.openai.com", "/v1/chat/completions"));
+        assert!(!registry.is_prompt_endpoint("api.openai.com", "/v1/models"));
+        assert!(registry.is_prompt_endpoint("chatgpt.com", "/backend-api/conversation"));
+        assert!(registry.is_prompt_endpoint("chatgpt.com", "/backend-api/f/conversation"));
+        assert!(!registry.is_prompt_endpoint("chatgpt.com", "/backend-api/me"));
+        assert!(registry.is_prompt_endpoint(
+            "claude.ai",
+            "/api/organizations/org-1/chat_conversations/conv-2/completion"
+        ));
+        assert!(registry.is_prompt_endpoint(
+            "generativelanguage.googleapis.com",
+            "/v1beta/models/gemini-pro:streamGenerateContent?alt=sse"
+        ));
+    }
+
+    #[test]
+    fn sites_without_endpoints_accept_all_paths() {
+        let registry = SiteRegistry::with_defaults();
+
+        // Character AI has no endpoint list, so every path is inspected
+        assert!(registry.is_prompt_endpoint("character.ai", "/anything"));
+        // Unknown hosts are left to the domain check
+        assert!(registry.is_prompt_endpoint("example.com", "/anything"));
+    }
 }
@@ -7,6 +7,8 @@ use chrono::{Datelike, NaiveTime, Timelike};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
+use crate::rule_validation::RuleIssue;
+
 /// Days of the week for rule scheduling.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -244,7 +246,7 @@ impl TimeRule {
 }
 
 /// Returns the previous day of the week.
-fn previous_day(day: Weekday) -> Weekday {
+pub(crate) fn previous_day(day: Weekday) -> Weekday {
     match day {
         Weekday::Monday => Weekday::Sunday,
         Weekday::Tuesday => Weekday::Monday,
@@ -261,12 +263,19 @@ fn previous_day(day: Weekday) -> Weekday {
 pub struct TimeRuleSet {
     /// The rules in this set.
     pub rules: Vec<TimeRule>,
+    /// Windows during which content rules that would warn block instead,
+    /// e.g. stricter filtering at bedtime without blocking everything.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub escalation_windows: Vec<TimeRule>,
 }
 
 impl TimeRuleSet {
     /// Creates an empty rule set.
     pub fn new() -> Self {
-        Self { rules: Vec::new() }
+        Self {
+            rules: Vec::new(),
+            escalation_windows: Vec::new(),
+        }
     }
 
     /// Creates a rule set with default presets.
@@ -346,6 +355,18 @@ impl TimeRuleSet {
         self.is_blocked(day, time)
     }
 
+    /// Adds a window during which warnings are escalated to blocks.
+    pub fn add_escalation_window(&mut self, window: TimeRule) {
+        self.escalation_windows.push(window);
+    }
+
+    /// Returns the escalation window active at the given day and time, if any.
+    pub fn escalation_window(&self, day: Weekday, time: TimeOfDay) -> Option<&TimeRule> {
+        self.escalation_windows
+            .iter()
+            .find(|window| window.is_blocked(day, time))
+    }
+
     /// Returns which rules are blocking at the given day and time.
     pub fn blocking_rules(&self, day: Weekday, time: TimeOfDay) -> Vec<&TimeRule> {
         self.rules
@@ -353,6 +374,92 @@ impl TimeRuleSet {
             .filter(|rule| rule.is_blocked(day, time))
             .collect()
     }
+
+    /// Checks the rules for mistakes without applying them.
+    ///
+    /// Errors: missing or duplicate IDs, out-of-range or empty time ranges.
+    /// Warnings: enabled rules with no days, and enabled rules that overlap.
+    /// Escalation windows get the same checks, except for overlaps.
+    pub fn validate(&self) -> Vec<RuleIssue> {
+        const FIELD: &str = "time_rules";
+        let mut issues = validate_rules(FIELD, &self.rules);
+        issues.extend(validate_rules(
+            "escalation_windows",
+            &self.escalation_windows,
+        ));
+
+        let enabled: Vec<&TimeRule> = self.rules.iter().filter(|r| r.enabled).collect();
+        for (i, a) in enabled.iter().enumerate() {
+            for b in &enabled[i + 1..] {
+                if let Some(day) = first_overlap(a, b) {
+                    issues.push(RuleIssue::warning(
+                        FIELD,
+                        Some(&b.id),
+                        format!("overlaps with '{}' on {:?}", a.id, day),
+                    ));
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+/// Checks each rule's ID, time range, and days.
+fn validate_rules(field: &str, rules: &[TimeRule]) -> Vec<RuleIssue> {
+    let mut issues = Vec::new();
+    let mut seen = HashSet::new();
+
+    for rule in rules {
+        let id = Some(rule.id.as_str());
+        if rule.id.trim().is_empty() {
+            issues.push(RuleIssue::error(field, id, "rule ID is empty"));
+        } else if !seen.insert(rule.id.as_str()) {
+            issues.push(RuleIssue::error(
+                field,
+                id,
+                format!("duplicate rule ID '{}'", rule.id),
+            ));
+        }
+
+        let range = &rule.time_range;
+        for (label, time) in [("start", range.start), ("end", range.end)] {
+            if time.hour > 23 || time.minute > 59 {
+                issues.push(RuleIssue::error(
+                    field,
+                    id,
+                    format!(
+                        "{} time {:02}:{:02} is not a valid time of day",
+                        label, time.hour, time.minute
+                    ),
+                ));
+            }
+        }
+        if range.start == range.end {
+            issues.push(RuleIssue::error(
+                field,
+                id,
+                "start and end times are equal, so the rule never blocks",
+            ));
+        }
+        if rule.enabled && rule.days.is_empty() {
+            issues.push(RuleIssue::warning(field, id, "rule applies to no days"));
+        }
+    }
+
+    issues
+}
+
+/// Returns the first day on which both rules block at the same minute.
+fn first_overlap(a: &TimeRule, b: &TimeRule) -> Option<Weekday> {
+    Weekday::all().into_iter().find(|&day| {
+        (0..24u8).any(|hour| {
+            (0..60u8).any(|minute| {
+                let time = TimeOfDay::new(hour, minute);
+                a.is_blocked(day, time) && b.is_blocked(day, time)
+            })
+        })
+    })
 }
 
 #[cfg(test)]
@@ -810,4 +917,68 @@ mod tests {
 
         assert_eq!(deserialized.rules.len(), 2);
     }
+
+    #[test]
+    fn validate_defaults_are_clean() {
+        let mut set = TimeRuleSet::with_defaults();
+        set.add_rule(TimeRuleSet::school_hours());
+        assert!(set.validate().is_empty());
+    }
+
+    #[test]
+    fn validate_reports_overlapping_rules() {
+        let mut set = TimeRuleSet::with_defaults();
+        set.add_rule(TimeRule::new(
+            "late_homework",
+            "Late Homework",
+            vec![Weekday::Wednesday],
+            TimeRange::from_hours(20, 22),
+        ));
+
+        let issues = set.validate();
+        assert_eq!(issues.len(), 1);
+        assert!(!issues[0].is_error());
+        assert_eq!(issues[0].rule_id.as_deref(), Some("late_homework"));
+        assert_eq!(
+            issues[0].message,
+            "overlaps with 'bedtime_school' on Wednesday"
+        );
+    }
+
+    #[test]
+    fn validate_reports_bad_ranges_and_ids() {
+        let mut set = TimeRuleSet::new();
+        set.add_rule(TimeRule::new(
+            "a",
+            "Empty",
+            vec![Weekday::Monday],
+            TimeRange::from_hours(9, 9),
+        ));
+        let mut out_of_range = TimeRule::new(
+            "a",
+            "Duplicate",
+            vec![Weekday::Tuesday],
+            TimeRange::from_hours(9, 10),
+        );
+        out_of_range.time_range.end = TimeOfDay {
+            hour: 25,
+            minute: 0,
+        };
+        set.add_rule(out_of_range);
+
+        let messages: Vec<String> = set
+            .validate()
+            .into_iter()
+            .filter(|issue| issue.is_error())
+            .map(|issue| issue.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "start and end times are equal, so the rule never blocks",
+                "duplicate rule ID 'a'",
+                "end time 25:00 is not a valid time of day",
+            ]
+        );
+    }
 }
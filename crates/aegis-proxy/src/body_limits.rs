@@ -0,0 +1,164 @@
+//! Request body size checks.
+//!
+//! Guards against bodies that lie about their size: a `Content-Length` that
+//! disagrees with the bytes actually received could otherwise be used to slip
+//! content past size-based scanning or confuse the upstream server.
+
+use http_body_util::BodyExt;
+use hudsucker::Body;
+use hyper::body::Bytes;
+use hyper::header::{HeaderMap, CONTENT_LENGTH};
+
+use crate::image_extractor::DEFAULT_MAX_UPLOAD_SIZE;
+
+/// Default maximum request body size that will be buffered for scanning.
+pub const DEFAULT_MAX_BODY_SIZE: usize = DEFAULT_MAX_UPLOAD_SIZE;
+
+/// What to do when the received body does not match its `Content-Length`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LengthMismatchAction {
+    /// Reject the request (400).
+    #[default]
+    Block,
+    /// Scan the full received body and forward it with a corrected header.
+    ScanFull,
+}
+
+/// Body size limits and `Content-Length` validation settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLengthPolicy {
+    /// Maximum body size buffered for scanning; larger bodies get a 413.
+    pub max_body_size: usize,
+    /// Allowed difference in bytes between declared and received length.
+    pub tolerance: usize,
+    /// Action taken when the difference exceeds the tolerance.
+    pub on_mismatch: LengthMismatchAction,
+}
+
+impl Default for ContentLengthPolicy {
+    fn default() -> Self {
+        Self {
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            tolerance: 0,
+            on_mismatch: LengthMismatchAction::default(),
+        }
+    }
+}
+
+/// A body whose received length disagrees with its `Content-Length`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("body is {actual} bytes but Content-Length declared {declared}")]
+pub struct LengthMismatch {
+    /// Length declared in the header.
+    pub declared: u64,
+    /// Bytes actually received.
+    pub actual: usize,
+}
+
+impl ContentLengthPolicy {
+    /// Returns true if a declared length is already over the size cap.
+    pub fn exceeds_cap(&self, declared: Option<u64>) -> bool {
+        declared.is_some_and(|len| len > self.max_body_size as u64)
+    }
+
+    /// Checks a received body length against the declared length.
+    ///
+    /// Bodies without a `Content-Length` (e.g., chunked) always pass.
+    pub fn check(&self, declared: Option<u64>, actual: usize) -> Result<(), LengthMismatch> {
+        match declared {
+            Some(declared) if declared.abs_diff(actual as u64) > self.tolerance as u64 => {
+                Err(LengthMismatch { declared, actual })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parses the `Content-Length` header, if present and valid.
+pub fn declared_content_length(headers: &HeaderMap) -> Option<u64> {
+    headers
+        .get(CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Error reading a body under a size cap.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum BodyReadError {
+    /// The body grew past the cap.
+    #[error("body exceeds {limit} bytes")]
+    TooLarge { limit: usize },
+    /// The underlying stream failed.
+    #[error("failed to read body: {0}")]
+    Read(String),
+}
+
+/// Reads a body frame by frame, stopping as soon as it exceeds `limit`.
+pub(crate) async fn read_body_capped(mut body: Body, limit: usize) -> Result<Bytes, BodyReadError> {
+    let mut buf = Vec::new();
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(|e| BodyReadError::Read(e.to_string()))?;
+        let Ok(data) = frame.into_data() else {
+            continue;
+        };
+        if buf.len() + data.len() > limit {
+            return Err(BodyReadError::TooLarge { limit });
+        }
+        buf.extend_from_slice(&data);
+    }
+    Ok(Bytes::from(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::Full;
+
+    #[test]
+    fn check_allows_exact_and_missing_length() {
+        let policy = ContentLengthPolicy::default();
+        assert!(policy.check(Some(10), 10).is_ok());
+        assert!(policy.check(None, 10).is_ok());
+    }
+
+    #[test]
+    fn check_flags_mismatch_beyond_tolerance() {
+        let policy = ContentLengthPolicy {
+            tolerance: 2,
+            ..Default::default()
+        };
+        assert!(policy.check(Some(10), 12).is_ok());
+        assert_eq!(
+            policy.check(Some(10), 13),
+            Err(LengthMismatch {
+                declared: 10,
+                actual: 13
+            })
+        );
+        assert!(policy.check(Some(10), 7).is_err());
+    }
+
+    #[test]
+    fn declared_length_parsing() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(declared_content_length(&headers), None);
+        headers.insert(CONTENT_LENGTH, "42".parse().unwrap());
+        assert_eq!(declared_content_length(&headers), Some(42));
+        headers.insert(CONTENT_LENGTH, "nope".parse().unwrap());
+        assert_eq!(declared_content_length(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn read_body_capped_enforces_limit() {
+        let body = || Body::from(Full::new(Bytes::from_static(b"0123456789")));
+
+        assert_eq!(read_body_capped(body(), 10).await.unwrap(), "0123456789");
+        assert!(matches!(
+            read_body_capped(body(), 9).await,
+            Err(BodyReadError::TooLarge { limit: 9 })
+        ));
+    }
+}
@@ -33,10 +33,10 @@ impl CaManager {
 
     /// Creates a CA manager using the default Aegis data directory.
     pub fn with_default_dir() -> Result<Self, CaManagerError> {
-        let project_dirs = directories::ProjectDirs::from("com", "aegis", "Aegis")
+        let data_dir = aegis_core::data_dir::project_data_dir("com", "aegis", "Aegis")
             .ok_or_else(|| CaManagerError::Generation("Failed to get project dirs".into()))?;
 
-        let ca_dir = project_dirs.data_dir().join("ca");
+        let ca_dir = data_dir.join("ca");
         Ok(Self::new(ca_dir))
     }
 
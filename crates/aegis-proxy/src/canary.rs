@@ -0,0 +1,378 @@
+//! Canary checks that interception is still happening.
+//!
+//! A proxy that silently stops seeing traffic (system proxy reset, another
+//! app taking the port) looks exactly like a quiet afternoon. A
+//! [`CanaryMonitor`] periodically sends a benign request for [`CANARY_HOST`]
+//! through the proxy; the handler answers it directly, echoing the request's
+//! token in the [`CANARY_HEADER`] header. A missing or wrong echo means the
+//! request did not pass through Aegis.
+//!
+//! The host is under `.invalid`, which never resolves, so a bypassed canary
+//! fails instead of reaching a real server.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use http_body_util::Full;
+use hudsucker::{
+    hyper::{Request, Response},
+    Body,
+};
+use hyper::body::Bytes;
+use serde::Serialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::task::JoinHandle;
+
+/// Host the canary request is addressed to.
+pub const CANARY_HOST: &str = "canary.aegis.invalid";
+
+/// Header carrying the canary token to the proxy and back.
+pub const CANARY_HEADER: &str = "X-Aegis-Canary";
+
+/// Default interval between canary checks.
+pub const DEFAULT_CANARY_INTERVAL: Duration = Duration::from_secs(300);
+
+/// How long a canary request may take before it counts as bypassed.
+const CANARY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest canary response read, in bytes.
+const MAX_REPLY_SIZE: u64 = 16 * 1024;
+
+/// Response to a canary request, as seen by the sender.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanaryReply {
+    /// HTTP status code.
+    pub status: u16,
+    /// Value of the [`CANARY_HEADER`] response header, if present.
+    pub token: Option<String>,
+}
+
+/// Outcome of a canary check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum CanaryStatus {
+    /// The canary passed through Aegis.
+    Intercepted,
+    /// The canary did not pass through Aegis.
+    Bypassed {
+        /// Why the check failed.
+        reason: String,
+    },
+}
+
+impl CanaryStatus {
+    /// Returns true if the canary was intercepted.
+    pub fn is_intercepted(&self) -> bool {
+        matches!(self, CanaryStatus::Intercepted)
+    }
+}
+
+/// A canary outcome and when it was observed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CanaryResult {
+    /// The outcome.
+    #[serde(flatten)]
+    pub status: CanaryStatus,
+    /// When the check ran.
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Decides whether a canary sent with `token` was intercepted.
+///
+/// `reply` is the response, or why none was received.
+pub fn verify_canary(token: &str, reply: Result<CanaryReply, String>) -> CanaryStatus {
+    let reason = match reply {
+        Err(e) => format!("canary request failed: {}", e),
+        Ok(reply) => match reply.token.as_deref() {
+            Some(echoed) if echoed == token && reply.status == 200 => {
+                return CanaryStatus::Intercepted;
+            }
+            Some(echoed) if echoed != token => "canary response echoed a stale token".to_string(),
+            Some(_) => format!("canary response had status {}", reply.status),
+            None => "canary response did not come from Aegis".to_string(),
+        },
+    };
+    CanaryStatus::Bypassed { reason }
+}
+
+/// Returns a token unique to this process and check.
+pub fn new_token() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+    format!("{:x}-{:x}", nanos, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Checks whether a request is a canary the handler should answer.
+pub(crate) fn is_canary_request(host: &str, req: &Request<Body>) -> bool {
+    req.method() != hyper::Method::CONNECT && host.eq_ignore_ascii_case(CANARY_HOST)
+}
+
+/// Answers a canary request, echoing its token.
+pub(crate) fn canary_response(req: &Request<Body>) -> Response<Body> {
+    let token = req
+        .headers()
+        .get(CANARY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    Response::builder()
+        .status(200)
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .header(CANARY_HEADER, token)
+        .body(Body::from(Full::new(Bytes::from_static(
+            b"Intercepted by Aegis",
+        ))))
+        .unwrap()
+}
+
+/// Sends a canary request with `token` through the HTTP proxy at `proxy`.
+pub async fn send_canary(proxy: SocketAddr, token: &str) -> Result<CanaryReply, String> {
+    let exchange = async {
+        let mut stream = TcpStream::connect(proxy).await?;
+        let request = format!(
+            "GET http://{host}/ HTTP/1.1\r\nHost: {host}\r\n{header}: {token}\r\nConnection: close\r\n\r\n",
+            host = CANARY_HOST,
+            header = CANARY_HEADER,
+            token = token,
+        );
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut response = Vec::new();
+        stream
+            .take(MAX_REPLY_SIZE)
+            .read_to_end(&mut response)
+            .await?;
+        Ok::<_, std::io::Error>(response)
+    };
+
+    let response = tokio::time::timeout(CANARY_TIMEOUT, exchange)
+        .await
+        .map_err(|_| format!("no response within {:?}", CANARY_TIMEOUT))?
+        .map_err(|e| e.to_string())?;
+    parse_reply(&response)
+}
+
+/// Parses the status line and canary header from a raw HTTP response.
+fn parse_reply(response: &[u8]) -> Result<CanaryReply, String> {
+    let text = String::from_utf8_lossy(response);
+    let head = text.split("\r\n\r\n").next().unwrap_or_default();
+    let mut lines = head.split("\r\n");
+
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| "malformed response".to_string())?;
+    let token = lines.find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case(CANARY_HEADER)
+            .then(|| value.trim().to_string())
+    });
+
+    Ok(CanaryReply { status, token })
+}
+
+/// Background task that runs a canary check on an interval.
+///
+/// The task is aborted when the `CanaryMonitor` is dropped.
+#[derive(Debug)]
+pub struct CanaryMonitor {
+    handle: JoinHandle<()>,
+}
+
+impl CanaryMonitor {
+    /// Spawns a task that calls `probe` with a fresh token every `interval`,
+    /// starting immediately, and passes each verified result to `on_result`.
+    ///
+    /// `probe` returns `None` to skip a check (e.g., while filtering is
+    /// intentionally off). Becoming bypassed is logged as a warning and
+    /// recovering as info. Must be called from within a Tokio runtime.
+    pub fn spawn<P, Fut, R>(interval: Duration, probe: P, on_result: R) -> Self
+    where
+        P: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<Result<CanaryReply, String>>> + Send,
+        R: Fn(CanaryResult) + Send + Sync + 'static,
+    {
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last: Option<CanaryStatus> = None;
+            loop {
+                ticker.tick().await;
+                let token = new_token();
+                let Some(reply) = probe(token.clone()).await else {
+                    continue;
+                };
+
+                let status = verify_canary(&token, reply);
+                match (&status, last.as_ref().map(CanaryStatus::is_intercepted)) {
+                    (CanaryStatus::Bypassed { reason }, Some(true) | None) => {
+                        tracing::warn!("Interception canary failed: {}", reason);
+                    }
+                    (CanaryStatus::Intercepted, Some(false)) => {
+                        tracing::info!("Interception canary recovered");
+                    }
+                    _ => {}
+                }
+                last = Some(status.clone());
+                on_result(CanaryResult {
+                    status,
+                    checked_at: Utc::now(),
+                });
+            }
+        });
+        Self { handle }
+    }
+
+    /// Returns true if the task is still running.
+    pub fn is_running(&self) -> bool {
+        !self.handle.is_finished()
+    }
+}
+
+impl Drop for CanaryMonitor {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use tokio::net::TcpListener;
+
+    fn intercepted(token: &str) -> Result<CanaryReply, String> {
+        Ok(CanaryReply {
+            status: 200,
+            token: Some(token.to_string()),
+        })
+    }
+
+    #[test]
+    fn echoed_token_is_intercepted() {
+        assert_eq!(
+            verify_canary("abc", intercepted("abc")),
+            CanaryStatus::Intercepted
+        );
+    }
+
+    #[test]
+    fn missing_or_wrong_echo_is_bypassed() {
+        // Reached some other server that doesn't know the canary
+        let status = verify_canary(
+            "abc",
+            Ok(CanaryReply {
+                status: 404,
+                token: None,
+            }),
+        );
+        assert!(!status.is_intercepted());
+
+        // A cached or replayed answer
+        let status = verify_canary("abc", intercepted("old"));
+        assert_eq!(
+            status,
+            CanaryStatus::Bypassed {
+                reason: "canary response echoed a stale token".to_string(),
+            }
+        );
+
+        // Proxy not reachable, or DNS for the .invalid host failed
+        let status = verify_canary("abc", Err("connection refused".to_string()));
+        assert_eq!(
+            status,
+            CanaryStatus::Bypassed {
+                reason: "canary request failed: connection refused".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn tokens_are_unique() {
+        assert_ne!(new_token(), new_token());
+    }
+
+    #[test]
+    fn parses_raw_replies() {
+        let reply =
+            parse_reply(b"HTTP/1.1 200 OK\r\nx-aegis-canary: t1\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+        assert_eq!(
+            reply,
+            CanaryReply {
+                status: 200,
+                token: Some("t1".to_string()),
+            }
+        );
+
+        let reply = parse_reply(b"HTTP/1.1 502 Bad Gateway\r\n\r\nbody").unwrap();
+        assert_eq!(reply.token, None);
+        assert!(parse_reply(b"garbage").is_err());
+    }
+
+    /// Serves one connection with a fixed raw HTTP response.
+    async fn fake_proxy(response: &'static [u8]) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            stream.write_all(response).await.unwrap();
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn send_canary_reads_echoed_token() {
+        let addr = fake_proxy(b"HTTP/1.1 200 OK\r\nX-Aegis-Canary: tok\r\n\r\nok").await;
+        let reply = send_canary(addr, "tok").await;
+        assert_eq!(verify_canary("tok", reply), CanaryStatus::Intercepted);
+
+        let addr = fake_proxy(b"HTTP/1.1 502 Bad Gateway\r\n\r\n").await;
+        let reply = send_canary(addr, "tok").await;
+        assert!(!verify_canary("tok", reply).is_intercepted());
+    }
+
+    #[tokio::test]
+    async fn monitor_reports_simulated_results() {
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let sink = results.clone();
+        let calls = Arc::new(AtomicU64::new(0));
+        let counter = calls.clone();
+
+        // Intercepted first, then bypassed, then skipped
+        let _monitor = CanaryMonitor::spawn(
+            Duration::from_millis(5),
+            move |token| {
+                let call = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    match call {
+                        0 => Some(intercepted(&token)),
+                        1 => Some(Ok(CanaryReply {
+                            status: 200,
+                            token: None,
+                        })),
+                        _ => None,
+                    }
+                }
+            },
+            move |result| sink.lock().push(result.status),
+        );
+
+        while calls.load(Ordering::SeqCst) < 4 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let results = results.lock();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_intercepted());
+        assert!(!results[1].is_intercepted());
+    }
+}
@@ -0,0 +1,236 @@
+//! Decoding compressed request bodies.
+//!
+//! Some clients (e.g., the Claude desktop app) compress POST bodies and mark
+//! them with `Content-Encoding`. Extracting prompts from the raw bytes finds
+//! nothing, so the request would pass through unfiltered. Bodies encoded with
+//! gzip, deflate, or br are decoded before extraction and forwarded decoded,
+//! with the `Content-Encoding` header removed.
+
+use std::io::Read;
+
+use hyper::body::Bytes;
+use hyper::header::{HeaderMap, CONTENT_ENCODING, CONTENT_LENGTH};
+
+/// A compression scheme the proxy can decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    /// `gzip` (or the legacy `x-gzip`).
+    Gzip,
+    /// `deflate`: zlib-wrapped, though some clients send raw deflate.
+    Deflate,
+    /// `br` (Brotli).
+    Brotli,
+}
+
+impl ContentEncoding {
+    /// Parses one `Content-Encoding` token, or `None` if it isn't decodable.
+    pub fn from_token(token: &str) -> Option<Self> {
+        match token.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Self::Gzip),
+            "deflate" => Some(Self::Deflate),
+            "br" => Some(Self::Brotli),
+            _ => None,
+        }
+    }
+
+    /// Returns the header token for this encoding.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+            Self::Brotli => "br",
+        }
+    }
+
+    /// Decodes `data`, failing if the output grows past `limit` bytes.
+    pub fn decode(&self, data: &[u8], limit: usize) -> Result<Vec<u8>, DecodeError> {
+        match self {
+            Self::Gzip => read_capped(flate2::read::MultiGzDecoder::new(data), *self, limit),
+            Self::Deflate => read_capped(flate2::read::ZlibDecoder::new(data), *self, limit)
+                .or_else(|e| match e {
+                    DecodeError::Corrupt { .. } => {
+                        read_capped(flate2::read::DeflateDecoder::new(data), *self, limit)
+                    }
+                    too_large => Err(too_large),
+                }),
+            Self::Brotli => read_capped(brotli::Decompressor::new(data, 4096), *self, limit),
+        }
+    }
+}
+
+/// Error decoding a compressed body.
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    /// The body is not valid data for its declared encoding.
+    #[error("invalid {encoding} body: {details}")]
+    Corrupt {
+        encoding: &'static str,
+        details: String,
+    },
+    /// The decoded body grew past the size cap.
+    #[error("decoded body exceeds {limit} bytes")]
+    TooLarge { limit: usize },
+    /// The body uses an encoding the proxy can't decode.
+    #[error("unsupported content encoding: {0}")]
+    Unsupported(String),
+}
+
+/// Reads a decoder to the end, stopping as soon as it exceeds `limit`.
+fn read_capped(
+    decoder: impl Read,
+    encoding: ContentEncoding,
+    limit: usize,
+) -> Result<Vec<u8>, DecodeError> {
+    let mut out = Vec::new();
+    decoder
+        .take(limit as u64 + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| DecodeError::Corrupt {
+            encoding: encoding.as_str(),
+            details: e.to_string(),
+        })?;
+    if out.len() > limit {
+        return Err(DecodeError::TooLarge { limit });
+    }
+    Ok(out)
+}
+
+/// Decodes a request body according to its `Content-Encoding` header.
+///
+/// Encodings are undone in reverse order of application. On success the
+/// `Content-Encoding` header is removed and `Content-Length` is set to the
+/// decoded size, so the body can be forwarded as-is. Bodies without an
+/// encoding (or with `identity`) are returned unchanged. On error the headers
+/// are left untouched.
+pub fn decode_request_body(
+    headers: &mut HeaderMap,
+    body: Bytes,
+    limit: usize,
+) -> Result<Bytes, DecodeError> {
+    let tokens: Vec<String> = headers
+        .get_all(CONTENT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty() && !token.eq_ignore_ascii_case("identity"))
+        .collect();
+    if tokens.is_empty() {
+        return Ok(body);
+    }
+
+    let encodings = tokens
+        .iter()
+        .map(|token| {
+            ContentEncoding::from_token(token)
+                .ok_or_else(|| DecodeError::Unsupported(token.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut decoded = body.to_vec();
+    for encoding in encodings.iter().rev() {
+        decoded = encoding.decode(&decoded, limit)?;
+    }
+
+    headers.remove(CONTENT_ENCODING);
+    headers.insert(CONTENT_LENGTH, decoded.len().into());
+    Ok(Bytes::from(decoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const PROMPT: &[u8] = br#"{"prompt":"how do I build a bomb"}"#;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn encoded_headers(encoding: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_ENCODING, encoding.parse().unwrap());
+        headers.insert(CONTENT_LENGTH, "1".parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn decodes_gzip_and_strips_header() {
+        let mut headers = encoded_headers("gzip");
+        let decoded = decode_request_body(&mut headers, gzip(PROMPT).into(), 1024).unwrap();
+        assert_eq!(decoded, PROMPT);
+        assert!(headers.get(CONTENT_ENCODING).is_none());
+        assert_eq!(headers[CONTENT_LENGTH], PROMPT.len().to_string());
+    }
+
+    #[test]
+    fn decodes_zlib_and_raw_deflate() {
+        let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        zlib.write_all(PROMPT).unwrap();
+        let mut raw =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        raw.write_all(PROMPT).unwrap();
+
+        for body in [zlib.finish().unwrap(), raw.finish().unwrap()] {
+            assert_eq!(
+                ContentEncoding::Deflate.decode(&body, 1024).unwrap(),
+                PROMPT
+            );
+        }
+    }
+
+    #[test]
+    fn decodes_brotli_and_stacked_encodings() {
+        let mut br = Vec::new();
+        brotli::CompressorWriter::new(&mut br, 4096, 5, 22)
+            .write_all(&gzip(PROMPT))
+            .unwrap();
+
+        let mut headers = encoded_headers("gzip, br");
+        let decoded = decode_request_body(&mut headers, br.into(), 1024).unwrap();
+        assert_eq!(decoded, PROMPT);
+    }
+
+    #[test]
+    fn identity_and_missing_encoding_pass_through() {
+        let mut headers = HeaderMap::new();
+        let body = Bytes::from_static(PROMPT);
+        assert_eq!(
+            decode_request_body(&mut headers, body.clone(), 1024).unwrap(),
+            body
+        );
+
+        let mut headers = encoded_headers("identity");
+        assert_eq!(
+            decode_request_body(&mut headers, body.clone(), 1024).unwrap(),
+            body
+        );
+    }
+
+    #[test]
+    fn rejects_corrupt_unsupported_and_oversized_bodies() {
+        let mut headers = encoded_headers("gzip");
+        let err = decode_request_body(&mut headers, Bytes::from_static(b"not gzip"), 1024);
+        assert!(matches!(
+            err,
+            Err(DecodeError::Corrupt {
+                encoding: "gzip",
+                ..
+            })
+        ));
+        assert!(headers.get(CONTENT_ENCODING).is_some());
+
+        let mut headers = encoded_headers("zstd");
+        let err = decode_request_body(&mut headers, Bytes::from_static(PROMPT), 1024);
+        assert!(matches!(err, Err(DecodeError::Unsupported(token)) if token == "zstd"));
+
+        let bomb = gzip(&vec![b'a'; 10_000]);
+        assert!(matches!(
+            ContentEncoding::Gzip.decode(&bomb, 1000),
+            Err(DecodeError::TooLarge { limit: 1000 })
+        ));
+    }
+}
@@ -0,0 +1,256 @@
+//! Text extraction from document uploads (F016).
+//!
+//! Finds plain-text and Markdown files in multipart form-data bodies so
+//! their contents can be classified like a prompt. Other formats (PDF,
+//! Office documents, archives) and parts that don't decode as text are
+//! skipped rather than guessed at.
+
+/// Maximum number of bytes of text classified per document (1MB).
+///
+/// Longer documents are truncated; the request is still forwarded whole.
+pub const DEFAULT_MAX_DOCUMENT_TEXT: usize = 1024 * 1024;
+
+/// File extensions treated as text documents.
+const TEXT_EXTENSIONS: &[&str] = &["txt", "text", "md", "markdown"];
+
+/// Part content types treated as text documents.
+const TEXT_CONTENT_TYPES: &[&str] = &["text/plain", "text/markdown", "text/x-markdown"];
+
+/// Text extracted from an uploaded document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedDocument {
+    /// Form field the file was uploaded under.
+    pub field_name: String,
+    /// Filename given by the client.
+    pub filename: String,
+    /// Document text, at most the configured cap.
+    pub text: String,
+    /// Whether `text` was cut at the cap.
+    pub truncated: bool,
+}
+
+/// Extracts text documents from a multipart form-data body.
+///
+/// A part is a document when it carries a filename and either the filename
+/// has a text extension (`.txt`, `.md`, ...) or its `Content-Type` is a text
+/// type. Parts that aren't valid UTF-8 or contain NUL bytes are skipped as
+/// binary. At most `max_text` bytes of each document are kept.
+pub fn extract_documents_from_multipart(
+    body: &[u8],
+    boundary: &str,
+    max_text: usize,
+) -> Vec<ExtractedDocument> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut documents = Vec::new();
+
+    let mut rest = match find_subsequence(body, &delimiter) {
+        Some(start) => &body[start + delimiter.len()..],
+        None => return documents,
+    };
+
+    // Every part sits between two delimiters; "--" after one marks the end
+    while !rest.starts_with(b"--") {
+        let end = find_subsequence(rest, &delimiter).unwrap_or(rest.len());
+        if let Some(document) = parse_document_part(&rest[..end], max_text) {
+            documents.push(document);
+        }
+        if end == rest.len() {
+            break;
+        }
+        rest = &rest[end + delimiter.len()..];
+    }
+
+    documents
+}
+
+/// Parses one multipart part, returning it if it is a text document.
+fn parse_document_part(part: &[u8], max_text: usize) -> Option<ExtractedDocument> {
+    let header_end = find_subsequence(part, b"\r\n\r\n")?;
+    let headers = std::str::from_utf8(&part[..header_end]).ok()?;
+    let content = &part[header_end + 4..];
+    let content = content.strip_suffix(b"\r\n").unwrap_or(content);
+
+    let mut field_name = None;
+    let mut filename = None;
+    let mut content_type = None;
+    for line in headers.lines() {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-disposition" => {
+                field_name = disposition_param(value, "name");
+                filename = disposition_param(value, "filename");
+            }
+            "content-type" => content_type = Some(value.trim().to_ascii_lowercase()),
+            _ => {}
+        }
+    }
+
+    let filename = filename?;
+    if !is_text_document(&filename, content_type.as_deref()) {
+        return None;
+    }
+
+    let (text, truncated) = decode_text(content, max_text)?;
+    Some(ExtractedDocument {
+        field_name: field_name.unwrap_or_default(),
+        filename,
+        text,
+        truncated,
+    })
+}
+
+/// Returns a quoted parameter (e.g., `filename="notes.txt"`) from a
+/// Content-Disposition value.
+fn disposition_param(value: &str, param: &str) -> Option<String> {
+    value.split(';').find_map(|item| {
+        let (key, val) = item.trim().split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(param)
+            .then(|| val.trim().trim_matches('"').to_string())
+    })
+}
+
+fn is_text_document(filename: &str, content_type: Option<&str>) -> bool {
+    let by_extension = filename.rsplit_once('.').is_some_and(|(_, ext)| {
+        TEXT_EXTENSIONS
+            .iter()
+            .any(|known| ext.eq_ignore_ascii_case(known))
+    });
+    let by_type = content_type.is_some_and(|ct| {
+        let mime = ct.split(';').next().unwrap_or_default().trim();
+        TEXT_CONTENT_TYPES.contains(&mime)
+    });
+    by_extension || by_type
+}
+
+/// Decodes up to `max_text` bytes as UTF-8, or `None` if the content is binary.
+///
+/// A multi-byte character split by the cap is dropped rather than rejected.
+fn decode_text(content: &[u8], max_text: usize) -> Option<(String, bool)> {
+    let truncated = content.len() > max_text;
+    let content = &content[..content.len().min(max_text)];
+    if content.contains(&0) {
+        return None;
+    }
+
+    let text = match std::str::from_utf8(content) {
+        Ok(text) => text,
+        Err(e) if truncated && e.error_len().is_none() => {
+            std::str::from_utf8(&content[..e.valid_up_to()]).ok()?
+        }
+        Err(_) => return None,
+    };
+    Some((text.to_string(), truncated))
+}
+
+fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDARY: &str = "----AegisBoundary";
+
+    /// Field name, filename, part content type, content.
+    type Part<'a> = (&'a str, Option<&'a str>, Option<&'a str>, &'a [u8]);
+
+    fn multipart(parts: &[Part<'_>]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (name, filename, content_type, content) in parts {
+            body.extend_from_slice(format!("--{}\r\n", BOUNDARY).as_bytes());
+            let mut disposition = format!("Content-Disposition: form-data; name=\"{}\"", name);
+            if let Some(filename) = filename {
+                disposition.push_str(&format!("; filename=\"{}\"", filename));
+            }
+            body.extend_from_slice(disposition.as_bytes());
+            body.extend_from_slice(b"\r\n");
+            if let Some(content_type) = content_type {
+                body.extend_from_slice(format!("Content-Type: {}\r\n", content_type).as_bytes());
+            }
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(content);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+        body
+    }
+
+    #[test]
+    fn extracts_txt_and_md_files() {
+        let body = multipart(&[
+            ("purpose", None, None, b"assistants"),
+            ("file", Some("notes.txt"), Some("text/plain"), b"first file"),
+            (
+                "file",
+                Some("README.MD"),
+                Some("application/octet-stream"),
+                b"# Title",
+            ),
+        ]);
+
+        let docs = extract_documents_from_multipart(&body, BOUNDARY, DEFAULT_MAX_DOCUMENT_TEXT);
+
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].field_name, "file");
+        assert_eq!(docs[0].filename, "notes.txt");
+        assert_eq!(docs[0].text, "first file");
+        assert!(!docs[0].truncated);
+        assert_eq!(docs[1].filename, "README.MD");
+        assert_eq!(docs[1].text, "# Title");
+    }
+
+    #[test]
+    fn text_content_type_without_known_extension() {
+        let body = multipart(&[(
+            "file",
+            Some("paste"),
+            Some("text/plain; charset=utf-8"),
+            b"hi",
+        )]);
+        let docs = extract_documents_from_multipart(&body, BOUNDARY, DEFAULT_MAX_DOCUMENT_TEXT);
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].text, "hi");
+    }
+
+    #[test]
+    fn skips_binary_and_unknown_formats() {
+        let body = multipart(&[
+            (
+                "file",
+                Some("report.pdf"),
+                Some("application/pdf"),
+                b"%PDF-1.7",
+            ),
+            ("file", Some("photo.png"), Some("image/png"), b"\x89PNG\r\n"),
+            ("file", Some("fake.txt"), Some("text/plain"), b"abc\x00def"),
+            ("file", Some("latin1.txt"), None, b"caf\xe9"),
+        ]);
+        let docs = extract_documents_from_multipart(&body, BOUNDARY, DEFAULT_MAX_DOCUMENT_TEXT);
+        assert!(docs.is_empty());
+    }
+
+    #[test]
+    fn long_documents_are_truncated_on_a_char_boundary() {
+        let body = multipart(&[("file", Some("long.txt"), None, "aé".repeat(10).as_bytes())]);
+
+        // 5 bytes lands inside the second 'é'
+        let docs = extract_documents_from_multipart(&body, BOUNDARY, 5);
+
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0].text, "aéa");
+        assert!(docs[0].truncated);
+    }
+
+    #[test]
+    fn malformed_bodies_yield_nothing() {
+        assert!(extract_documents_from_multipart(b"no boundary here", BOUNDARY, 1024).is_empty());
+        let truncated = format!("--{}\r\nContent-Disposition: form-data", BOUNDARY);
+        assert!(extract_documents_from_multipart(truncated.as_bytes(), BOUNDARY, 1024).is_empty());
+    }
+}
@@ -0,0 +1,235 @@
+//! Escalating repeated warnings to blocks.
+//!
+//! A warn rule lets a prompt through with a warning, which does little if the
+//! same child keeps retrying. [`WarnEscalation`] counts warnings per profile
+//! and category, and once more than the allowed number arrive within a
+//! window, blocks that category for a cooldown instead.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+use aegis_core::classifier::Category;
+use aegis_core::content_rules::{ContentAction, ContentRuleResult};
+use aegis_core::rule_engine::{RuleAction, RuleEngineResult, RuleSource};
+
+/// Warnings allowed within the window before further attempts are blocked.
+pub const DEFAULT_WARNINGS_BEFORE_BLOCK: u32 = 3;
+
+/// Window in which warnings are counted.
+pub const DEFAULT_ESCALATION_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// How long a category stays blocked after escalating.
+pub const DEFAULT_ESCALATION_COOLDOWN: Duration = Duration::from_secs(15 * 60);
+
+/// Rule ID reported for blocks made by warning escalation.
+pub const ESCALATION_RULE_ID: &str = "warn_escalation";
+
+/// Profile (if any) and category that warnings are counted under.
+type EscalationKey = (Option<i64>, Category);
+
+/// Warnings and cooldown for one profile and category.
+#[derive(Debug, Default)]
+struct EscalationEntry {
+    warnings: VecDeque<Instant>,
+    blocked_until: Option<Instant>,
+}
+
+/// Turns repeated warnings into blocks.
+///
+/// Clones share their counts, so every handler clone escalates together.
+#[derive(Debug, Clone)]
+pub struct WarnEscalation {
+    warnings_before_block: u32,
+    window: Duration,
+    cooldown: Duration,
+    entries: Arc<Mutex<HashMap<EscalationKey, EscalationEntry>>>,
+}
+
+impl WarnEscalation {
+    /// Creates an escalation policy with the default count, window, and cooldown.
+    pub fn new() -> Self {
+        Self {
+            warnings_before_block: DEFAULT_WARNINGS_BEFORE_BLOCK,
+            window: DEFAULT_ESCALATION_WINDOW,
+            cooldown: DEFAULT_ESCALATION_COOLDOWN,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sets how many warnings are allowed within the window; the next one blocks.
+    pub fn with_warnings_before_block(mut self, warnings: u32) -> Self {
+        self.warnings_before_block = warnings;
+        self
+    }
+
+    /// Sets the window in which warnings are counted.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Sets how long a category stays blocked after escalating.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Returns the number of warnings allowed within the window.
+    pub fn warnings_before_block(&self) -> u32 {
+        self.warnings_before_block
+    }
+
+    /// Returns the window in which warnings are counted.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Returns how long a category stays blocked after escalating.
+    pub fn cooldown(&self) -> Duration {
+        self.cooldown
+    }
+
+    /// Counts a content rule warning for `profile_id` and returns a block
+    /// result if it escalates.
+    ///
+    /// Results other than content rule warnings pass through unchanged.
+    pub fn apply(&self, profile_id: Option<i64>, result: RuleEngineResult) -> RuleEngineResult {
+        self.apply_at(profile_id, result, Instant::now())
+    }
+
+    fn apply_at(
+        &self,
+        profile_id: Option<i64>,
+        result: RuleEngineResult,
+        now: Instant,
+    ) -> RuleEngineResult {
+        let content = match (&result.action, &result.source) {
+            (RuleAction::Warn, RuleSource::ContentRule(content)) => content,
+            _ => return result,
+        };
+
+        let mut entries = self.entries.lock();
+        // Forget profiles and categories that have gone quiet
+        entries.retain(|_, entry| {
+            entry.blocked_until.is_some_and(|until| until > now)
+                || entry
+                    .warnings
+                    .back()
+                    .is_some_and(|&at| now.duration_since(at) < self.window)
+        });
+        let entry = entries.entry((profile_id, content.category)).or_default();
+
+        if entry.blocked_until.is_some_and(|until| until > now) {
+            return Self::escalated(content);
+        }
+        entry.blocked_until = None;
+        while entry
+            .warnings
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= self.window)
+        {
+            entry.warnings.pop_front();
+        }
+
+        if entry.warnings.len() >= self.warnings_before_block as usize {
+            tracing::info!(
+                "Escalating repeated {} warnings to a block for {:?}",
+                content.category.name(),
+                self.cooldown
+            );
+            entry.warnings.clear();
+            entry.blocked_until = Some(now + self.cooldown);
+            return Self::escalated(content);
+        }
+
+        entry.warnings.push_back(now);
+        result
+    }
+
+    fn escalated(content: &ContentRuleResult) -> RuleEngineResult {
+        RuleEngineResult::from_content_result(ContentRuleResult {
+            rule_id: ESCALATION_RULE_ID.to_string(),
+            rule_name: "Repeated warnings".to_string(),
+            category: content.category,
+            confidence: content.confidence,
+            action: ContentAction::Block,
+        })
+    }
+}
+
+impl Default for WarnEscalation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warn(category: Category) -> RuleEngineResult {
+        RuleEngineResult::from_content_result(ContentRuleResult {
+            rule_id: "warn_rule".to_string(),
+            rule_name: "Warn rule".to_string(),
+            category,
+            confidence: 0.8,
+            action: ContentAction::Warn,
+        })
+    }
+
+    #[test]
+    fn escalates_after_warnings_and_recovers_after_cooldown() {
+        let escalation = WarnEscalation::new()
+            .with_warnings_before_block(2)
+            .with_window(Duration::from_secs(60))
+            .with_cooldown(Duration::from_secs(120));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let apply = |category, secs| escalation.apply_at(Some(1), warn(category), at(secs));
+        assert!(apply(Category::Violence, 0).should_warn());
+        assert!(apply(Category::Violence, 10).should_warn());
+        // Other categories count separately
+        assert!(apply(Category::Profanity, 15).should_warn());
+
+        let result = apply(Category::Violence, 20);
+        assert!(result.should_block());
+        assert_eq!(result.source.rule_id(), Some(ESCALATION_RULE_ID));
+        assert!(apply(Category::Violence, 100).should_block());
+
+        // After the cooldown, counting starts over
+        assert!(apply(Category::Violence, 141).should_warn());
+    }
+
+    #[test]
+    fn warnings_outside_window_do_not_count() {
+        let escalation = WarnEscalation::new()
+            .with_warnings_before_block(1)
+            .with_window(Duration::from_secs(60));
+        let start = Instant::now();
+
+        let apply = |profile, secs| {
+            escalation.apply_at(
+                profile,
+                warn(Category::Hate),
+                start + Duration::from_secs(secs),
+            )
+        };
+        assert!(apply(Some(1), 0).should_warn());
+        assert!(apply(Some(1), 61).should_warn());
+        // Profiles count separately
+        assert!(apply(Some(2), 62).should_warn());
+        assert!(apply(Some(1), 62).should_block());
+    }
+
+    #[test]
+    fn non_warnings_pass_through() {
+        let escalation = WarnEscalation::new().with_warnings_before_block(0);
+        assert!(escalation
+            .apply(Some(1), RuleEngineResult::allow())
+            .should_allow());
+    }
+}
@@ -16,6 +16,8 @@ pub struct PromptInfo {
     pub service: String,
     /// The API endpoint path.
     pub endpoint: String,
+    /// The model the request asked for, if the payload named one.
+    pub model: Option<String>,
 }
 
 impl PromptInfo {
@@ -29,14 +31,22 @@ impl PromptInfo {
             text: text.into(),
             service: service.into(),
             endpoint: endpoint.into(),
+            model: None,
         }
     }
+
+    /// Sets the requested model.
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
+    }
 }
 
 /// Extracts prompt text from a request body.
 ///
 /// Supports various LLM API formats:
 /// - OpenAI Chat Completions API
+/// - OpenAI Assistants API thread messages
 /// - Anthropic Messages API
 /// - Google Generative Language API
 ///
@@ -53,6 +63,12 @@ pub fn extract_prompt(host: &str, path: &str, body: &[u8]) -> Option<PromptInfo>
     // Try different extraction strategies based on host and structure
     let text = if host.contains("openai.com") || host.contains("chatgpt.com") {
         extract_openai(&json)
+            .or_else(|| {
+                is_thread_message_path(path)
+                    .then(|| extract_thread_message(&json))
+                    .flatten()
+            })
+            .or_else(|| extract_chatgpt_web(&json))
     } else if host.contains("anthropic.com") || host.contains("claude.ai") {
         extract_anthropic(&json)
     } else if host.contains("googleapis.com") || host.contains("gemini.google.com") {
@@ -64,19 +80,37 @@ pub fn extract_prompt(host: &str, path: &str, body: &[u8]) -> Option<PromptInfo>
 
     // If specific extraction worked, use it
     if let Some(t) = text {
-        return Some(PromptInfo::new(t, service, path));
+        return Some(PromptInfo::new(t, service, path).with_model(model_name(&json)));
     }
 
     // Fall back: extract ALL text content from JSON and scan it
     // This catches any format we don't specifically handle
     let all_text = extract_all_text(&json);
     if !all_text.is_empty() {
-        return Some(PromptInfo::new(all_text, service, path));
+        return Some(PromptInfo::new(all_text, service, path).with_model(model_name(&json)));
     }
 
     None
 }
 
+/// Extracts the requested model name from a JSON request body.
+///
+/// OpenAI-compatible and Anthropic APIs put it in a top-level `model` field.
+/// Returns `None` for bodies that aren't JSON or don't name a model.
+pub fn extract_model(body: &[u8]) -> Option<String> {
+    let json: Value = serde_json::from_slice(body).ok()?;
+    model_name(&json)
+}
+
+/// Returns the requested model name from a parsed JSON request body.
+///
+/// OpenAI-compatible and Anthropic APIs put it in a top-level `model` field.
+/// Returns `None` if the payload doesn't name a model.
+pub(crate) fn model_name(json: &Value) -> Option<String> {
+    let model = json.get("model")?.as_str()?.trim();
+    (!model.is_empty()).then(|| model.to_string())
+}
+
 /// Recursively extracts all string values from a JSON structure.
 /// This is used as a fallback to scan the entire payload.
 fn extract_all_text(value: &Value) -> String {
@@ -88,11 +122,9 @@ fn extract_all_text(value: &Value) -> String {
 /// Recursively collects all string values from JSON.
 fn collect_text_recursive(value: &Value, texts: &mut Vec<String>) {
     match value {
-        Value::String(s) => {
-            // Only include strings that look like actual content (not IDs, tokens, etc.)
-            if s.len() > 10 && !looks_like_id(s) {
-                texts.push(s.clone());
-            }
+        // Only include strings that look like actual content (not IDs, tokens, etc.)
+        Value::String(s) if s.len() > 10 && !looks_like_id(s) => {
+            texts.push(s.clone());
         }
         Value::Array(arr) => {
             for item in arr {
@@ -158,36 +190,9 @@ fn extract_openai(json: &Value) -> Option<String> {
                 .or_else(|| msg.get("author")?.get("role")?.as_str())?;
 
             if role == "user" {
-                // Try standard format: content as string
-                if let Some(content) = msg.get("content") {
-                    if let Some(text) = content.as_str() {
-                        return Some(text.to_string());
-                    }
-                    // Handle OpenAI multimodal format: content as array of {type, text}
-                    if let Some(parts) = content.as_array() {
-                        let text_parts: Vec<&str> = parts
-                            .iter()
-                            .filter_map(|part| {
-                                if part.get("type")?.as_str()? == "text" {
-                                    part.get("text")?.as_str()
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect();
-                        if !text_parts.is_empty() {
-                            return Some(text_parts.join(" "));
-                        }
-                    }
-                    // Handle ChatGPT web format: content.parts as array of strings
-                    if let Some(parts) = content.get("parts").and_then(|p| p.as_array()) {
-                        let text_parts: Vec<&str> =
-                            parts.iter().filter_map(|part| part.as_str()).collect();
-                        if !text_parts.is_empty() {
-                            return Some(text_parts.join(" "));
-                        }
-                    }
-                }
+                let content = msg.get("content")?;
+                // Handle ChatGPT web format: content.parts
+                return openai_content_text(content).or_else(|| chatgpt_parts_text(content));
             }
             None
         })
@@ -201,6 +206,114 @@ fn extract_openai(json: &Value) -> Option<String> {
     }
 }
 
+/// Extracts text from OpenAI message content: a string, or the text parts
+/// of a multimodal array of `{type, text}`.
+fn openai_content_text(content: &Value) -> Option<String> {
+    if let Some(text) = content.as_str() {
+        return Some(text.to_string());
+    }
+    let text_parts: Vec<&str> = content
+        .as_array()?
+        .iter()
+        .filter_map(|part| {
+            if part.get("type")?.as_str()? == "text" {
+                part.get("text")?.as_str()
+            } else {
+                None
+            }
+        })
+        .collect();
+    if text_parts.is_empty() {
+        None
+    } else {
+        Some(text_parts.join(" "))
+    }
+}
+
+/// Returns true if `path` adds a message to an Assistants API thread
+/// (`/v1/threads/{thread_id}/messages`).
+pub(crate) fn is_thread_message_path(path: &str) -> bool {
+    let path = path.split('?').next().unwrap_or(path).trim_end_matches('/');
+    path.contains("/threads/") && path.ends_with("/messages")
+}
+
+/// Extracts prompt from an OpenAI Assistants API thread message.
+///
+/// Format: `{"role": "user", "content": "..."}`, a single message rather
+/// than a `messages` array.
+fn extract_thread_message(json: &Value) -> Option<String> {
+    if json.get("role")?.as_str()? != "user" {
+        return None;
+    }
+    openai_content_text(json.get("content")?)
+}
+
+/// Maximum JSON depth searched for ChatGPT web message objects.
+const MAX_CHATGPT_SEARCH_DEPTH: usize = 6;
+
+/// Joins the text of a ChatGPT web `content.parts` array.
+///
+/// Parts are strings, or objects with a `text` field in newer builds;
+/// asset pointers (images, files) are skipped.
+fn chatgpt_parts_text(content: &Value) -> Option<String> {
+    let parts = content.get("parts")?.as_array()?;
+    let text_parts: Vec<&str> = parts
+        .iter()
+        .filter_map(|part| part.as_str().or_else(|| part.get("text")?.as_str()))
+        .filter(|text| !text.is_empty())
+        .collect();
+
+    if text_parts.is_empty() {
+        None
+    } else {
+        Some(text_parts.join(" "))
+    }
+}
+
+/// Extracts the latest user turn from a ChatGPT web payload by shape.
+///
+/// ChatGPT web moves its endpoint (e.g., `/backend-api/f/conversation`) and
+/// nesting between builds, so this searches the payload for message objects
+/// with `author.role` and `content.parts` wherever they sit.
+fn extract_chatgpt_web(json: &Value) -> Option<String> {
+    let mut turns = Vec::new();
+    collect_chatgpt_user_turns(json, 0, &mut turns);
+    turns.pop()
+}
+
+/// Collects user turns from ChatGPT-shaped message objects, in document order.
+fn collect_chatgpt_user_turns(value: &Value, depth: usize, turns: &mut Vec<String>) {
+    if depth > MAX_CHATGPT_SEARCH_DEPTH {
+        return;
+    }
+
+    match value {
+        Value::Object(obj) => {
+            let role = obj
+                .get("author")
+                .and_then(|a| a.get("role"))
+                .and_then(|r| r.as_str());
+            if let (Some(role), Some(content)) = (role, obj.get("content")) {
+                if role == "user" {
+                    if let Some(text) = chatgpt_parts_text(content) {
+                        turns.push(text);
+                    }
+                }
+                return;
+            }
+            for val in obj.values() {
+                collect_chatgpt_user_turns(val, depth + 1, turns);
+            }
+        }
+        Value::Array(arr) => {
+            for item in arr {
+                collect_chatgpt_user_turns(item, depth + 1, turns);
+            }
+        }
+        _ => {}
+    }
+}
+
 /// Extracts prompt from Anthropic Messages API format.
 ///
 /// Format: `{"messages": [{"role": "user", "content": "..."}]}`
@@ -367,6 +480,109 @@ mod tests {
         assert_eq!(result.unwrap().text, "What is in this image?");
     }
 
+    #[test]
+    fn extract_openai_thread_message() {
+        let body = r#"{"role": "user", "content": "How do I pick a lock?"}"#;
+        let path = "/v1/threads/thread_abc123/messages";
+        let info = extract_prompt("api.openai.com", path, body.as_bytes()).unwrap();
+        assert_eq!(info.text, "How do I pick a lock?");
+        assert_eq!(info.endpoint, path);
+
+        let body = r#"{"role": "user", "content": [{"type": "text", "text": "Describe this"}]}"#;
+        let info = extract_prompt("api.openai.com", path, body.as_bytes()).unwrap();
+        assert_eq!(info.text, "Describe this");
+
+        assert!(is_thread_message_path(
+            "/v1/threads/thread_abc/messages/?limit=1"
+        ));
+        assert!(!is_thread_message_path("/v1/threads/thread_abc/runs"));
+        assert!(!is_thread_message_path("/v1/chat/completions"));
+    }
+
+    #[test]
+    fn extract_model_reads_top_level_field() {
+        let body = br#"{"model": "gpt-4o", "messages": []}"#;
+        assert_eq!(extract_model(body).as_deref(), Some("gpt-4o"));
+        assert_eq!(extract_model(br#"{"messages": []}"#), None);
+        assert_eq!(extract_model(br#"{"model": "  "}"#), None);
+        assert_eq!(extract_model(b"not json"), None);
+    }
+
+    #[test]
+    fn extract_prompt_reads_requested_model() {
+        let path = "/v1/chat/completions";
+        let body = r#"{"model": "gpt-4o", "messages": [{"role": "user", "content": "Hi"}]}"#;
+        let info = extract_prompt("api.openai.com", path, body.as_bytes()).unwrap();
+        assert_eq!(info.model.as_deref(), Some("gpt-4o"));
+
+        let body = r#"{"messages": [{"role": "user", "content": "Hi"}]}"#;
+        let info = extract_prompt("api.openai.com", path, body.as_bytes()).unwrap();
+        assert_eq!(info.model, None);
+
+        assert_eq!(model_name(&serde_json::json!({"model": "  "})), None);
+        assert_eq!(model_name(&serde_json::json!({"model": 4})), None);
+    }
+
+    // ==================== ChatGPT Web Format Tests ====================
+
+    #[test]
+    fn extract_chatgpt_web_current_build() {
+        let body = r#"{
+            "action": "next",
+            "messages": [{
+                "id": "7b3d9a52-1c4e-4f0a-9a57-3f1e2b8c6d10",
+                "author": {"role": "user"},
+                "create_time": 1760400000.123,
+                "content": {
+                    "content_type": "multimodal_text",
+                    "parts": [
+                        {"content_type": "image_asset_pointer", "asset_pointer": "file-service://file-abc"},
+                        "What is shown in this picture?"
+                    ]
+                },
+                "metadata": {"selected_github_repos": [], "serialization_metadata": {"custom_symbol_offsets": []}}
+            }],
+            "parent_message_id": "client-created-root",
+            "model": "auto",
+            "timezone_offset_min": -60,
+            "conversation_mode": {"kind": "primary_assistant"},
+            "supports_buffering": true,
+            "supported_encodings": ["v1"],
+            "client_contextual_info": {"is_dark_mode": true, "time_since_loaded": 12}
+        }"#;
+        let result = extract_prompt(
+            "chatgpt.com",
+            "/backend-api/f/conversation",
+            body.as_bytes(),
+        );
+
+        assert_eq!(result.unwrap().text, "What is shown in this picture?");
+    }
+
+    #[test]
+    fn extract_chatgpt_web_nested_messages() {
+        let body = r#"{
+            "action": "next",
+            "input": {
+                "history": [
+                    {"author": {"role": "user"}, "content": {"content_type": "text", "parts": ["Earlier question"]}},
+                    {"author": {"role": "assistant"}, "content": {"content_type": "text", "parts": ["Earlier answer"]}}
+                ],
+                "message": {
+                    "author": {"role": "user"},
+                    "content": {"content_type": "text", "parts": [{"text": "Latest question"}]}
+                }
+            }
+        }"#;
+        let result = extract_prompt(
+            "chatgpt.com",
+            "/backend-api/f/conversation",
+            body.as_bytes(),
+        );
+
+        assert_eq!(result.unwrap().text, "Latest question");
+    }
+
     // ==================== Anthropic Format Tests ====================
 
     #[test]
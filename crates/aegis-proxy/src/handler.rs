@@ -6,17 +6,25 @@
 //! Supports profile-aware filtering - when filtering is disabled (e.g., parent
 //! profile active), requests pass through without classification.
 
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-use http_body_util::{BodyExt, Full};
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use http_body_util::{combinators::BoxBody, BodyExt, Full};
 use hudsucker::{
-    hyper::{Request, Response},
-    tokio_tungstenite::tungstenite::Message,
+    hyper::{Request, Response, Uri},
+    tokio_tungstenite::tungstenite::{self, Message},
     Body, HttpContext, HttpHandler, RequestOrResponse, WebSocketContext, WebSocketHandler,
 };
 use hyper::body::Bytes;
+use hyper::header::HeaderValue;
+use once_cell::sync::Lazy;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedReceiver;
 
 /// Helper to convert bytes to Body
 fn bytes_to_body(bytes: Bytes) -> Body {
@@ -24,23 +32,52 @@ fn bytes_to_body(bytes: Bytes) -> Body {
 }
 
 use aegis_core::classifier::{
-    Category, ClassificationResult, LazyNsfwClassifier, NsfwThresholdPreset, SentimentAnalyzer,
-    SentimentConfig, SentimentFlag, TieredClassifier,
+    Category, ClassificationResult, LazyNsfwClassifier, NsfwThresholdPreset, PromptGuardError,
+    SentimentAnalyzer, SentimentConfig, SentimentFlag, TieredClassifier,
 };
 use aegis_core::content_rules::ContentRuleSet;
 use aegis_core::notifications::{BlockedEvent, NotificationManager};
-use aegis_core::rule_engine::{RuleAction, RuleEngine, RuleEngineResult};
+use aegis_core::rule_engine::{RuleAction, RuleEngine, RuleEngineResult, RuleSource};
 use aegis_core::site_registry::SiteRegistry;
 use aegis_core::time_rules::TimeRuleSet;
-use aegis_storage::{Action, Database};
+use aegis_storage::{
+    Action, Database, NsfwThresholdPreset as StoredNsfwThresholdPreset, Profile,
+    ProfileImageFilteringConfig, ProfileSentimentConfig,
+};
 
+use crate::body_limits::{
+    declared_content_length, read_body_capped, BodyReadError, ContentLengthPolicy,
+    LengthMismatchAction,
+};
+use crate::canary::{canary_response, is_canary_request};
+use crate::content_encoding::{decode_request_body, DecodeError};
+use crate::escalation::WarnEscalation;
+use crate::interstitial::{StashedRequest, WarnInterstitial};
+use crate::limiter::ConnectionLimiter;
+use crate::live_stats::LiveStats;
+use crate::startup::StartupGuard;
 use crate::state_cache::StateCache;
+use crate::tunnel_hosts::{MissingHostPolicy, TunnelHosts};
+use crate::websocket_block::{websocket_block_messages, WebSocketClients};
 
-use crate::domains::is_llm_domain;
+use crate::document_extractor::{extract_documents_from_multipart, DEFAULT_MAX_DOCUMENT_TEXT};
 use crate::extractor::{extract_prompt, PromptInfo};
 use crate::image_extractor::{
-    extract_image_from_binary, extract_images_from_json, extract_images_from_multipart,
+    extract_image_from_binary, extract_images_from_json, ExtractedImage, MultipartImageStream,
+    DEFAULT_MAX_IMAGE_PART_SIZE,
 };
+use crate::response_scanner::{is_streaming_response, reassemble_completion, ScanningBody};
+use crate::smart_parser::{ParseContext, ParserRegistry, DEFAULT_MAX_PAYLOAD_SIZE};
+
+/// Flag type stored for a sentiment flag.
+fn sentiment_flag_type(flag: SentimentFlag) -> &'static str {
+    match flag {
+        SentimentFlag::Distress => "distress",
+        SentimentFlag::CrisisIndicator => "crisis_indicator",
+        SentimentFlag::Bullying => "bullying",
+        SentimentFlag::NegativeSentiment => "negative_sentiment",
+    }
+}
 
 /// Checks if a request is a WebSocket upgrade request.
 fn is_websocket_upgrade(req: &Request<Body>) -> bool {
@@ -51,6 +88,13 @@ fn is_websocket_upgrade(req: &Request<Body>) -> bool {
         .unwrap_or(false)
 }
 
+/// Largest WebSocket text frame inspected for prompts; larger frames pass
+/// through unparsed.
+pub const MAX_WEBSOCKET_MESSAGE_SIZE: usize = DEFAULT_MAX_PAYLOAD_SIZE;
+
+/// Deepest JSON nesting parsed from a WebSocket frame.
+pub const MAX_WEBSOCKET_JSON_DEPTH: usize = 32;
+
 /// Block page HTML template.
 const BLOCK_PAGE_HTML: &str = r#"<!DOCTYPE html>
 <html>
@@ -105,10 +149,141 @@ const BLOCK_PAGE_HTML: &str = r#"<!DOCTYPE html>
 </body>
 </html>"#;
 
+/// Parsers that site entries can name (see
+/// [`ProxyHandler::extract_with_site_parser`]).
+static SITE_PARSERS: Lazy<ParserRegistry> = Lazy::new(ParserRegistry::with_defaults);
+
+/// Placeholders a custom block page must contain.
+const REQUIRED_BLOCK_PAGE_PLACEHOLDERS: [&str; 2] = ["{{REASON}}", "{{SERVICE}}"];
+
+/// Loads a custom block page template from `path`.
+///
+/// Besides `{{REASON}}` and `{{SERVICE}}`, the template may use `{{PROFILE}}`
+/// and `{{CATEGORY}}`. Returns `None`, logging a warning, if the file can't
+/// be read or is missing a required placeholder, so the built-in page is used.
+pub fn load_block_page_template(path: &Path) -> Option<String> {
+    let template = match std::fs::read_to_string(path) {
+        Ok(template) => template,
+        Err(e) => {
+            tracing::warn!(
+                "Failed to load block page template {}: {}; using the default",
+                path.display(),
+                e
+            );
+            return None;
+        }
+    };
+    let missing: Vec<_> = REQUIRED_BLOCK_PAGE_PLACEHOLDERS
+        .into_iter()
+        .filter(|placeholder| !template.contains(placeholder))
+        .collect();
+    if !missing.is_empty() {
+        tracing::warn!(
+            "Block page template {} is missing {}; using the default",
+            path.display(),
+            missing.join(", ")
+        );
+        return None;
+    }
+    Some(template)
+}
+
+/// Returns the category of the content rule behind `result`, if any.
+fn rule_category(result: &RuleEngineResult) -> Option<Category> {
+    match &result.source {
+        RuleSource::ContentRule(content) => Some(content.category),
+        _ => None,
+    }
+}
+
 /// Callback for handling classification results.
 pub type OnBlockCallback = Arc<dyn Fn(&PromptInfo, &RuleEngineResult) + Send + Sync>;
 pub type OnAllowCallback = Arc<dyn Fn(&PromptInfo, &RuleEngineResult) + Send + Sync>;
 
+/// What the handler does when classification fails (ML error or panic) or
+/// a compressed request body can't be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClassifierFailurePolicy {
+    /// Log and continue with whatever partial result is available (Tier 1
+    /// matches on ML error, nothing on panic), so rules still apply. Bodies
+    /// that can't be decoded are scanned and forwarded as they are.
+    #[default]
+    FailOpen,
+    /// Log and block the request; undecodable bodies are rejected (400).
+    FailClosed,
+}
+
+/// Config key the allowed-request logging policy is stored under.
+pub const ALLOW_LOGGING_CONFIG_KEY: &str = "allow_logging";
+
+/// Level allowed requests are written to the desktop log at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowLogLevel {
+    /// Don't log allowed requests.
+    Off,
+    /// Log at debug level (hidden in normal operation).
+    #[default]
+    Debug,
+    /// Log at info level.
+    Info,
+}
+
+/// How allowed requests are recorded.
+///
+/// Blocks and flags are always stored. Allows are stored by default too, but
+/// parents can turn that off (stats are still counted), drop the prompt
+/// preview from stored allows, or raise their log level for a complete
+/// activity record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AllowLogging {
+    /// Store an event for each allowed prompt.
+    pub record_events: bool,
+    /// Keep the prompt preview on stored allow events (the hash is always kept).
+    pub store_previews: bool,
+    /// Level allowed requests are logged at.
+    pub log_level: AllowLogLevel,
+}
+
+impl Default for AllowLogging {
+    fn default() -> Self {
+        Self {
+            record_events: true,
+            store_previews: true,
+            log_level: AllowLogLevel::default(),
+        }
+    }
+}
+
+/// A classification attempt that failed.
+#[derive(Debug)]
+pub struct ClassifierFailure {
+    /// Human-readable failure reason for logging.
+    pub reason: String,
+    /// Partial result computed before the failure, if any.
+    pub partial: Option<ClassificationResult>,
+}
+
+/// Extracts a readable message from a caught panic payload.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Maps a stored profile preset to the classifier preset (F033).
+fn nsfw_preset_from_profile(preset: StoredNsfwThresholdPreset) -> NsfwThresholdPreset {
+    match preset {
+        StoredNsfwThresholdPreset::Child => NsfwThresholdPreset::Child,
+        StoredNsfwThresholdPreset::Teen => NsfwThresholdPreset::Teen,
+        StoredNsfwThresholdPreset::Adult => NsfwThresholdPreset::Adult,
+        StoredNsfwThresholdPreset::Custom(t) => NsfwThresholdPreset::Custom(t),
+    }
+}
+
 /// Shared filtering state that can be controlled by ProfileProxyController.
 ///
 /// When filtering is disabled (e.g., parent profile is active), all requests
@@ -129,17 +304,26 @@ pub struct FilteringState {
     sentiment_analyzer: Arc<RwLock<Option<SentimentAnalyzer>>>,
     /// Optional state cache for reading protection state from database (F032).
     /// When set, is_enabled() will read from the database cache instead of AtomicBool.
-    state_cache: Option<Arc<StateCache>>,
+    state_cache: Arc<RwLock<Option<Arc<StateCache>>>>,
     /// NSFW image threshold preset (F033).
     nsfw_threshold: Arc<RwLock<NsfwThresholdPreset>>,
     /// Whether image filtering is enabled (F033).
     image_filtering_enabled: Arc<AtomicBool>,
+    /// Whether prompt text is classified against content rules.
+    text_filtering_enabled: Arc<AtomicBool>,
+    /// Whether streamed assistant responses are classified and flagged.
+    response_scanning_enabled: Arc<AtomicBool>,
+    /// Emergency override that blocks all LLM traffic regardless of rules.
+    block_all: Arc<AtomicBool>,
+    /// Live request counters for the status endpoint.
+    live_stats: Arc<LiveStats>,
 }
 
 impl std::fmt::Debug for FilteringState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("FilteringState")
             .field("enabled", &self.enabled.load(Ordering::SeqCst))
+            .field("state_cache", &self.state_cache.read().is_some())
             .field("profile_name", &*self.profile_name.read())
             .field("profile_id", &*self.profile_id.read())
             .field(
@@ -151,6 +335,16 @@ impl std::fmt::Debug for FilteringState {
                 "image_filtering_enabled",
                 &self.image_filtering_enabled.load(Ordering::SeqCst),
             )
+            .field(
+                "text_filtering_enabled",
+                &self.text_filtering_enabled.load(Ordering::SeqCst),
+            )
+            .field(
+                "response_scanning_enabled",
+                &self.response_scanning_enabled.load(Ordering::SeqCst),
+            )
+            .field("block_all", &self.block_all.load(Ordering::SeqCst))
+            .field("live_stats", &self.live_stats.snapshot())
             .finish()
     }
 }
@@ -170,9 +364,13 @@ impl FilteringState {
             profile_id: Arc::new(RwLock::new(None)),
             rule_engine: Arc::new(RwLock::new(RuleEngine::with_defaults())),
             sentiment_analyzer: Arc::new(RwLock::new(None)),
-            state_cache: None,
+            state_cache: Arc::new(RwLock::new(None)),
             nsfw_threshold: Arc::new(RwLock::new(NsfwThresholdPreset::default())),
             image_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            text_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            response_scanning_enabled: Arc::new(AtomicBool::new(false)),
+            block_all: Arc::new(AtomicBool::new(false)),
+            live_stats: Arc::new(LiveStats::new()),
         }
     }
 
@@ -184,9 +382,13 @@ impl FilteringState {
             profile_id: Arc::new(RwLock::new(None)),
             rule_engine: Arc::new(RwLock::new(rule_engine)),
             sentiment_analyzer: Arc::new(RwLock::new(None)),
-            state_cache: None,
+            state_cache: Arc::new(RwLock::new(None)),
             nsfw_threshold: Arc::new(RwLock::new(NsfwThresholdPreset::default())),
             image_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            text_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            response_scanning_enabled: Arc::new(AtomicBool::new(false)),
+            block_all: Arc::new(AtomicBool::new(false)),
+            live_stats: Arc::new(LiveStats::new()),
         }
     }
 
@@ -198,16 +400,22 @@ impl FilteringState {
             profile_id: Arc::new(RwLock::new(None)),
             rule_engine: Arc::new(RwLock::new(RuleEngine::with_defaults())),
             sentiment_analyzer: Arc::new(RwLock::new(Some(SentimentAnalyzer::new(config)))),
-            state_cache: None,
+            state_cache: Arc::new(RwLock::new(None)),
             nsfw_threshold: Arc::new(RwLock::new(NsfwThresholdPreset::default())),
             image_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            text_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            response_scanning_enabled: Arc::new(AtomicBool::new(false)),
+            block_all: Arc::new(AtomicBool::new(false)),
+            live_stats: Arc::new(LiveStats::new()),
         }
     }
 
     /// Sets the state cache for database-backed protection state (F032).
-    pub fn set_state_cache(&self, _cache: Arc<StateCache>) {
-        // Note: This requires interior mutability - we'll need to change the field type
-        // For now, use a new constructor instead
+    ///
+    /// Takes effect immediately for this state and all its clones,
+    /// replacing any cache set before.
+    pub fn set_state_cache(&self, cache: Arc<StateCache>) {
+        *self.state_cache.write() = Some(cache);
     }
 
     /// Creates a filtering state with database-backed state cache (F032).
@@ -219,9 +427,13 @@ impl FilteringState {
             profile_id: Arc::new(RwLock::new(None)),
             rule_engine: Arc::new(RwLock::new(RuleEngine::with_defaults())),
             sentiment_analyzer: Arc::new(RwLock::new(None)),
-            state_cache: Some(Arc::new(cache)),
+            state_cache: Arc::new(RwLock::new(Some(Arc::new(cache)))),
             nsfw_threshold: Arc::new(RwLock::new(NsfwThresholdPreset::default())),
             image_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            text_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            response_scanning_enabled: Arc::new(AtomicBool::new(false)),
+            block_all: Arc::new(AtomicBool::new(false)),
+            live_stats: Arc::new(LiveStats::new()),
         }
     }
 
@@ -234,9 +446,13 @@ impl FilteringState {
             profile_id: Arc::new(RwLock::new(None)),
             rule_engine: Arc::new(RwLock::new(rule_engine)),
             sentiment_analyzer: Arc::new(RwLock::new(None)),
-            state_cache: Some(Arc::new(cache)),
+            state_cache: Arc::new(RwLock::new(Some(Arc::new(cache)))),
             nsfw_threshold: Arc::new(RwLock::new(NsfwThresholdPreset::default())),
             image_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            text_filtering_enabled: Arc::new(AtomicBool::new(true)),
+            response_scanning_enabled: Arc::new(AtomicBool::new(false)),
+            block_all: Arc::new(AtomicBool::new(false)),
+            live_stats: Arc::new(LiveStats::new()),
         }
     }
 
@@ -244,7 +460,8 @@ impl FilteringState {
     /// If a state cache is configured (F032), reads from database.
     /// Otherwise, reads from local AtomicBool.
     pub fn is_enabled(&self) -> bool {
-        if let Some(ref cache) = self.state_cache {
+        let cache = self.state_cache.read().clone();
+        if let Some(cache) = cache {
             // Poll for updates and return cached value
             cache.poll();
             cache.is_filtering_enabled()
@@ -352,6 +569,163 @@ impl FilteringState {
         self.set_nsfw_threshold(preset);
     }
 
+    /// Returns whether prompt text classification is enabled.
+    pub fn is_text_filtering_enabled(&self) -> bool {
+        self.text_filtering_enabled.load(Ordering::SeqCst)
+    }
+
+    /// Enables prompt text classification.
+    pub fn enable_text_filtering(&self) {
+        self.text_filtering_enabled.store(true, Ordering::SeqCst);
+        tracing::info!("Text filtering enabled");
+    }
+
+    /// Disables prompt text classification.
+    ///
+    /// Prompts are forwarded without classification; sentiment analysis and
+    /// image filtering keep their own toggles.
+    pub fn disable_text_filtering(&self) {
+        self.text_filtering_enabled.store(false, Ordering::SeqCst);
+        tracing::info!("Text filtering disabled");
+    }
+
+    /// Returns whether streamed assistant responses are scanned.
+    pub fn is_response_scanning_enabled(&self) -> bool {
+        self.response_scanning_enabled.load(Ordering::SeqCst)
+    }
+
+    /// Enables or disables scanning of streamed assistant responses.
+    ///
+    /// Off by default. When on, SSE and NDJSON responses are collected as
+    /// they stream and the completion is classified once it ends; matches
+    /// are flagged for review rather than blocked, since the client has
+    /// already shown the text.
+    pub fn enable_response_scanning(&self, enabled: bool) {
+        self.response_scanning_enabled
+            .store(enabled, Ordering::SeqCst);
+        tracing::info!(
+            "Response scanning {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+
+    /// Returns the live request counters.
+    pub fn live_stats(&self) -> &LiveStats {
+        &self.live_stats
+    }
+
+    /// Returns whether the emergency block-all override is active.
+    pub fn is_blocking_all(&self) -> bool {
+        self.block_all.load(Ordering::SeqCst)
+    }
+
+    /// Blocks all LLM traffic until [`clear_block_all`](Self::clear_block_all)
+    /// is called, regardless of profile, rules, or pause state.
+    pub fn block_all(&self) {
+        self.block_all.store(true, Ordering::SeqCst);
+        tracing::warn!("Block-all override activated");
+    }
+
+    /// Clears the block-all override, restoring normal evaluation.
+    ///
+    /// Callers are responsible for authenticating the parent first.
+    pub fn clear_block_all(&self) {
+        self.block_all.store(false, Ordering::SeqCst);
+        tracing::info!("Block-all override cleared");
+    }
+
+    /// Configures sentiment analysis from a profile's settings.
+    pub fn configure_sentiment(&self, config: &ProfileSentimentConfig) {
+        if !config.enabled {
+            self.disable_sentiment_analysis();
+            return;
+        }
+
+        let mut enabled_flags = HashSet::new();
+        if config.detect_distress {
+            enabled_flags.insert(SentimentFlag::Distress);
+        }
+        if config.detect_crisis {
+            enabled_flags.insert(SentimentFlag::CrisisIndicator);
+        }
+        if config.detect_bullying {
+            enabled_flags.insert(SentimentFlag::Bullying);
+        }
+        if config.detect_negative {
+            enabled_flags.insert(SentimentFlag::NegativeSentiment);
+        }
+
+        self.enable_sentiment_analysis(SentimentConfig {
+            enabled: true,
+            threshold: config.sensitivity,
+            enabled_flags,
+            notify_on_flag: true,
+            ..Default::default()
+        });
+    }
+
+    /// Configures image filtering from a profile's settings (F033).
+    pub fn configure_image_filtering(&self, config: &ProfileImageFilteringConfig) {
+        if config.enabled {
+            self.enable_image_filtering();
+        } else {
+            self.disable_image_filtering();
+        }
+        self.set_nsfw_threshold(nsfw_preset_from_profile(config.nsfw_threshold));
+    }
+
+    /// Applies a profile's independent text, sentiment, and image toggles.
+    pub fn apply_profile_filters(&self, profile: &Profile) {
+        if profile.text_filtering_enabled {
+            self.enable_text_filtering();
+        } else {
+            self.disable_text_filtering();
+        }
+        self.configure_sentiment(&profile.sentiment_config);
+        self.configure_image_filtering(&profile.image_filtering_config);
+    }
+
+    /// Switches to a stored profile: sets it active, loads its rules, and
+    /// applies its filter toggles.
+    ///
+    /// Rules that fail to parse are logged and replaced by defaults.
+    pub fn apply_profile(&self, profile: &Profile) {
+        for issue in profile.rule_issues() {
+            tracing::error!("Applying defaults: {}", issue);
+        }
+        let time_rules = profile.parse_time_rules().unwrap_or_default();
+        let content_rules = profile
+            .parse_content_rules()
+            .ok()
+            .filter(|r| !r.rules.is_empty())
+            .unwrap_or_else(ContentRuleSet::family_safe_defaults);
+
+        self.set_profile_with_id(Some(profile.name.clone()), Some(profile.id));
+        self.update_rules(time_rules, content_rules);
+        self.apply_profile_filters(profile);
+    }
+
+    /// Looks up a stored profile by name and switches to it.
+    ///
+    /// Returns `false` if no profile has that name.
+    pub fn switch_to_profile(&self, db: &Database, name: &str) -> aegis_storage::Result<bool> {
+        let profiles = db.get_all_profiles()?;
+        let Some(profile) = profiles.iter().find(|p| p.name == name) else {
+            return Ok(false);
+        };
+
+        tracing::info!(
+            "Switching to profile '{}' (id={}): text={}, sentiment={}, images={}",
+            profile.name,
+            profile.id,
+            profile.text_filtering_enabled,
+            profile.sentiment_config.enabled,
+            profile.image_filtering_config.enabled
+        );
+        self.apply_profile(profile);
+        Ok(true)
+    }
+
     /// Updates the rule engine with new time and content rules.
     ///
     /// Call this when a profile's rules are modified in the UI or when
@@ -391,6 +765,27 @@ pub struct HandlerConfig {
     pub nsfw_classifier: Arc<RwLock<LazyNsfwClassifier>>,
     /// Site registry for checking image gen domains (F033).
     pub site_registry: Arc<SiteRegistry>,
+    /// Optional cap on concurrently handled LLM requests (`None` = unlimited).
+    pub connection_limiter: Option<ConnectionLimiter>,
+    /// Policy applied when the classifier errors or panics.
+    pub classifier_failure_policy: ClassifierFailurePolicy,
+    /// Optional conservative policy applied until the ML model is ready.
+    pub startup_guard: Option<StartupGuard>,
+    /// Body size cap and `Content-Length` validation.
+    pub content_length_policy: ContentLengthPolicy,
+    /// Policy for requests whose destination host can't be determined.
+    pub missing_host_policy: MissingHostPolicy,
+    /// How allowed requests are stored and logged.
+    pub allow_logging: AllowLogging,
+    /// Optional escalation of repeated warnings to blocks.
+    pub warn_escalation: Option<WarnEscalation>,
+    /// Optional warning page shown before a warned prompt is forwarded.
+    pub warn_interstitial: Option<WarnInterstitial>,
+    /// Custom block page used instead of the built-in one (see
+    /// [`load_block_page_template`]).
+    pub block_page_template: Option<String>,
+    /// Add `X-Aegis-*` decision headers to allowed and warned requests.
+    pub decision_headers: bool,
 }
 
 impl std::fmt::Debug for HandlerConfig {
@@ -404,6 +799,16 @@ impl std::fmt::Debug for HandlerConfig {
             .field("database", &self.database.is_some())
             .field("nsfw_classifier", &"LazyNsfwClassifier")
             .field("site_registry", &"SiteRegistry")
+            .field("connection_limiter", &self.connection_limiter)
+            .field("classifier_failure_policy", &self.classifier_failure_policy)
+            .field("startup_guard", &self.startup_guard)
+            .field("content_length_policy", &self.content_length_policy)
+            .field("missing_host_policy", &self.missing_host_policy)
+            .field("allow_logging", &self.allow_logging)
+            .field("warn_escalation", &self.warn_escalation)
+            .field("warn_interstitial", &self.warn_interstitial)
+            .field("block_page_template", &self.block_page_template.is_some())
+            .field("decision_headers", &self.decision_headers)
             .finish()
     }
 }
@@ -412,12 +817,23 @@ impl std::fmt::Debug for HandlerConfig {
 #[derive(Clone, Debug)]
 pub struct ProxyHandler {
     config: HandlerConfig,
+    /// `CONNECT` hosts by client connection, shared across clones.
+    tunnel_hosts: Arc<TunnelHosts>,
+    /// Channels back to WebSocket clients, shared across clones.
+    websocket_clients: Arc<WebSocketClients>,
 }
 
 impl ProxyHandler {
     /// Creates a new proxy handler with the given configuration.
     pub fn new(config: HandlerConfig) -> Self {
-        Self { config }
+        let handler = Self {
+            config,
+            tunnel_hosts: Arc::new(TunnelHosts::default()),
+            websocket_clients: Arc::new(WebSocketClients::default()),
+        };
+        // Report the model status before the first request arrives
+        handler.record_model_status(&handler.config.classifier.read());
+        handler
     }
 
     /// Creates a handler with default classifier and rules.
@@ -435,6 +851,16 @@ impl ProxyHandler {
             database: None,
             nsfw_classifier: Arc::new(RwLock::new(LazyNsfwClassifier::with_defaults())),
             site_registry: Arc::new(SiteRegistry::with_defaults()),
+            connection_limiter: None,
+            classifier_failure_policy: ClassifierFailurePolicy::default(),
+            startup_guard: None,
+            content_length_policy: ContentLengthPolicy::default(),
+            missing_host_policy: MissingHostPolicy::default(),
+            allow_logging: AllowLogging::default(),
+            warn_escalation: None,
+            warn_interstitial: None,
+            block_page_template: None,
+            decision_headers: false,
         })
     }
 
@@ -452,6 +878,16 @@ impl ProxyHandler {
             database: None,
             nsfw_classifier: Arc::new(RwLock::new(LazyNsfwClassifier::with_defaults())),
             site_registry: Arc::new(SiteRegistry::with_defaults()),
+            connection_limiter: None,
+            classifier_failure_policy: ClassifierFailurePolicy::default(),
+            startup_guard: None,
+            content_length_policy: ContentLengthPolicy::default(),
+            missing_host_policy: MissingHostPolicy::default(),
+            allow_logging: AllowLogging::default(),
+            warn_escalation: None,
+            warn_interstitial: None,
+            block_page_template: None,
+            decision_headers: false,
         })
     }
 
@@ -483,18 +919,208 @@ impl ProxyHandler {
         self
     }
 
-    /// Processes a request body and returns classification result.
-    fn classify_prompt(&self, prompt: &str) -> ClassificationResult {
-        self.config.classifier.write().classify(prompt)
+    /// Runs a trivial classification to keep loaded models warm.
+    ///
+    /// Skips the text classifier when no ML model is loaded, and the NSFW
+    /// classifier when image filtering is off.
+    pub fn warm_up(&self) {
+        if self.config.classifier.read().has_ml() {
+            let _ = self.try_classify_untracked("hello");
+        }
+        if self.config.filtering_state.is_image_filtering_enabled() {
+            self.config.nsfw_classifier.read().ensure_loaded();
+        }
     }
 
-    /// Evaluates rules against classification.
-    fn evaluate_rules(&self, classification: &ClassificationResult) -> RuleEngineResult {
-        self.config
+    /// Classifies a prompt, catching ML errors and panics, and publishes the
+    /// classifier's tier totals to the live stats.
+    fn try_classify_prompt(&self, prompt: &str) -> Result<ClassificationResult, ClassifierFailure> {
+        Self::classify_guarded(|| {
+            let mut classifier = self.config.classifier.write();
+            let outcome = classifier
+                .try_classify(prompt)
+                .map_err(|failure| ClassifierFailure {
+                    reason: failure.to_string(),
+                    partial: Some(failure.partial),
+                });
+            self.config
+                .filtering_state
+                .live_stats()
+                .set_classifier_stats(classifier.stats());
+            outcome
+        })
+    }
+
+    /// Classifies text that isn't a user prompt, catching ML errors and
+    /// panics, without using the classification cache or counting it in
+    /// the tier totals.
+    fn try_classify_untracked(
+        &self,
+        text: &str,
+    ) -> Result<ClassificationResult, ClassifierFailure> {
+        Self::classify_guarded(|| {
+            self.config
+                .classifier
+                .write()
+                .classify_untracked(text)
+                .map_err(|failure| ClassifierFailure {
+                    reason: failure.to_string(),
+                    partial: Some(failure.partial),
+                })
+        })
+    }
+
+    /// Runs a classification closure so that a panic becomes a
+    /// [`ClassifierFailure`] instead of taking down the proxy task.
+    fn classify_guarded<F>(classify: F) -> Result<ClassificationResult, ClassifierFailure>
+    where
+        F: FnOnce() -> Result<ClassificationResult, ClassifierFailure>,
+    {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(classify)).unwrap_or_else(|payload| {
+            Err(ClassifierFailure {
+                reason: format!("classifier panicked: {}", panic_message(payload.as_ref())),
+                partial: None,
+            })
+        })
+    }
+
+    /// Applies the configured failure policy to a classification attempt.
+    ///
+    /// Returns the classification to evaluate, or `None` if the request must
+    /// be blocked (fail-closed).
+    fn resolve_classification(
+        &self,
+        outcome: Result<ClassificationResult, ClassifierFailure>,
+        service: &str,
+    ) -> Option<ClassificationResult> {
+        let failure = match outcome {
+            Ok(classification) => return Some(classification),
+            Err(failure) => failure,
+        };
+
+        match self.config.classifier_failure_policy {
+            ClassifierFailurePolicy::FailOpen => {
+                tracing::error!(
+                    "Classifier failed for {} ({}), failing open",
+                    service,
+                    failure.reason
+                );
+                Some(
+                    failure
+                        .partial
+                        .unwrap_or_else(|| ClassificationResult::safe(0)),
+                )
+            }
+            ClassifierFailurePolicy::FailClosed => {
+                tracing::error!(
+                    "Classifier failed for {} ({}), failing closed",
+                    service,
+                    failure.reason
+                );
+                None
+            }
+        }
+    }
+
+    /// Evaluates rules against the prompt text, the requested model (if
+    /// known), and the prompt's classification.
+    ///
+    /// While a startup guard is active and the ML model has not loaded,
+    /// sensitive matches the rules would let through are blocked, unless
+    /// the prompt is allowlisted.
+    fn evaluate_rules(
+        &self,
+        text: &str,
+        model: Option<&str>,
+        classification: &ClassificationResult,
+    ) -> RuleEngineResult {
+        let result = self
+            .config
             .filtering_state
             .rule_engine
             .read()
-            .evaluate_now(classification)
+            .evaluate_request_now(text, model, classification);
+
+        match &self.config.startup_guard {
+            Some(guard)
+                if !result.should_block()
+                    && !result.source.is_allowlisted()
+                    && !self.model_ready(guard) =>
+            {
+                guard.evaluate(classification).unwrap_or(result)
+            }
+            _ => result,
+        }
+    }
+
+    /// Extracts a prompt with the parser the host's site entry names.
+    ///
+    /// Custom sites can name one of the [`ParserRegistry`] parsers (e.g.
+    /// `json`, `form`, `grpc-web`) and a service name. Returns `None` when
+    /// the site names no known parser or the parser finds no prompt, leaving
+    /// extraction to [`extract_prompt`].
+    fn extract_with_site_parser(
+        &self,
+        host: &str,
+        path: &str,
+        parts: &hyper::http::request::Parts,
+        body: &[u8],
+    ) -> Option<PromptInfo> {
+        let site = self.config.site_registry.get_site(host)?.entry;
+        let parser = SITE_PARSERS.get(site.parser_id.as_deref()?)?;
+
+        let mut context = ParseContext::new(host, path).with_grpc_web(true);
+        if let Some(content_type) = parts
+            .headers
+            .get(hyper::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+        {
+            context = context.with_content_type(content_type);
+        }
+        let result = parser.parse(body, &context);
+        if !result.has_prompts() {
+            return None;
+        }
+
+        tracing::debug!(
+            "Extracted prompt from {} with the {} parser",
+            host,
+            parser.name()
+        );
+        let model = result.prompts.iter().find_map(|p| p.model.clone());
+        let service = site.service_name.unwrap_or(site.name);
+        Some(PromptInfo::new(result.combined_text(), service, path).with_model(model))
+    }
+
+    /// Returns true if the request carries a warning page token issued for
+    /// this prompt, using it up.
+    fn warning_confirmed(&self, parts: &hyper::http::request::Parts, prompt: &PromptInfo) -> bool {
+        self.config
+            .warn_interstitial
+            .as_ref()
+            .is_some_and(|interstitial| interstitial.redeem(&parts.headers, &prompt.text))
+    }
+
+    /// Turns a warning into a block when the active profile keeps triggering
+    /// it, if warning escalation is configured.
+    fn escalate_warnings(&self, result: RuleEngineResult) -> RuleEngineResult {
+        match &self.config.warn_escalation {
+            Some(escalation) => escalation.apply(self.config.filtering_state.profile_id(), result),
+            None => result,
+        }
+    }
+
+    /// Returns true if the ML model is ready, marking the guard ready the
+    /// first time the classifier reports a loaded model.
+    fn model_ready(&self, guard: &StartupGuard) -> bool {
+        if guard.is_ready() {
+            return true;
+        }
+        if self.config.classifier.read().has_ml() {
+            guard.mark_ready();
+            return true;
+        }
+        false
     }
 
     /// Records an event to the database if configured.
@@ -503,20 +1129,63 @@ impl ProxyHandler {
         prompt: &PromptInfo,
         classification: &ClassificationResult,
         action: Action,
+    ) {
+        self.store_event(prompt, classification, action, true);
+    }
+
+    /// Logs and records an allowed request according to the allow-logging policy.
+    fn record_allowed(&self, prompt: &PromptInfo, classification: &ClassificationResult) {
+        let policy = self.config.allow_logging;
+        match policy.log_level {
+            AllowLogLevel::Off => {}
+            AllowLogLevel::Debug => tracing::debug!("Allowed request to {}", prompt.service),
+            AllowLogLevel::Info => tracing::info!("Allowed request to {}", prompt.service),
+        }
+
+        if policy.record_events {
+            self.store_event(
+                prompt,
+                classification,
+                Action::Allowed,
+                policy.store_previews,
+            );
+        } else if let Some(ref db) = self.config.database {
+            // Keep the dashboard totals accurate without storing the event
+            let category = classification.matches.first().map(|m| m.category);
+            if let Err(e) = db.increment_stats(Action::Allowed, category) {
+                tracing::warn!("Failed to record stats: {}", e);
+            }
+        }
+    }
+
+    fn store_event(
+        &self,
+        prompt: &PromptInfo,
+        classification: &ClassificationResult,
+        action: Action,
+        store_preview: bool,
     ) {
         if let Some(ref db) = self.config.database {
             // Get the primary category from classification
             let category = classification.matches.first().map(|m| m.category);
             let confidence = classification.matches.first().map(|m| m.confidence);
+            let source = Some(prompt.service.clone());
+            let model = prompt.model.clone();
 
             // Log event (this also updates daily stats)
-            if let Err(e) = db.log_event(
-                &prompt.text,
-                category,
-                confidence,
-                action,
-                Some(prompt.service.clone()),
-            ) {
+            let logged = if store_preview {
+                db.log_event_with_model(&prompt.text, category, confidence, action, source, model)
+            } else {
+                db.log_event_without_preview_with_model(
+                    &prompt.text,
+                    category,
+                    confidence,
+                    action,
+                    source,
+                    model,
+                )
+            };
+            if let Err(e) = logged {
                 tracing::warn!("Failed to record event: {}", e);
             }
         }
@@ -553,16 +1222,9 @@ impl ProxyHandler {
         if result.has_flags() {
             if let Some(ref db) = self.config.database {
                 for flag in &result.flags {
-                    let flag_type = match flag.flag {
-                        SentimentFlag::Distress => "distress",
-                        SentimentFlag::CrisisIndicator => "crisis_indicator",
-                        SentimentFlag::Bullying => "bullying",
-                        SentimentFlag::NegativeSentiment => "negative_sentiment",
-                    };
-
                     if let Err(e) = db.log_flagged_event(
                         profile_id,
-                        flag_type,
+                        sentiment_flag_type(flag.flag),
                         flag.confidence,
                         &prompt.text,
                         Some(prompt.service.clone()),
@@ -582,6 +1244,132 @@ impl ProxyHandler {
         }
     }
 
+    /// Forwards a streamed response unchanged, classifying the reassembled
+    /// completion on a blocking thread once the stream ends.
+    fn scan_streaming_response(&self, res: Response<Body>) -> Response<Body> {
+        let (parts, body) = res.into_parts();
+        let handler = self.clone();
+        let body = ScanningBody::new(body, move |stream| {
+            let completion = reassemble_completion(&stream);
+            if completion.trim().is_empty() {
+                return;
+            }
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    runtime.spawn_blocking(move || handler.scan_response_text(&completion));
+                }
+                Err(_) => {
+                    handler.scan_response_text(&completion);
+                }
+            }
+        });
+        Response::from_parts(parts, Body::from(BoxBody::new(body)))
+    }
+
+    /// Runs the response checks on reassembled assistant output.
+    fn scan_response_text(&self, text: &str) {
+        self.flag_response_text(text);
+        self.flag_response_sentiment(text);
+    }
+
+    /// Records flagged events for crisis or grooming-style language in
+    /// assistant output, using the active profile's sentiment settings.
+    ///
+    /// Returns the number of flags raised. Like prompt sentiment flags, these
+    /// never block.
+    fn flag_response_sentiment(&self, text: &str) -> usize {
+        let Some(profile_id) = self.config.filtering_state.profile_id() else {
+            return 0;
+        };
+        let result = match self
+            .config
+            .filtering_state
+            .sentiment_analyzer
+            .read()
+            .as_ref()
+        {
+            Some(analyzer) => analyzer.analyze_response(text),
+            None => return 0,
+        };
+
+        if let Some(ref db) = self.config.database {
+            for flag in &result.flags {
+                if let Err(e) = db.log_flagged_event(
+                    profile_id,
+                    sentiment_flag_type(flag.flag),
+                    flag.confidence,
+                    text,
+                    None,
+                    flag.matched_phrases.clone(),
+                ) {
+                    tracing::warn!("Failed to record flagged response: {}", e);
+                } else {
+                    tracing::info!(
+                        "Flagged {} content in assistant response (confidence: {:.2})",
+                        flag.flag.name(),
+                        flag.confidence
+                    );
+                }
+            }
+        }
+        result.flags.len()
+    }
+
+    /// Classifies assistant output and records a flagged event when the
+    /// rules would block or warn on it.
+    ///
+    /// Returns the matched category if the response was flagged. Responses
+    /// are classified untracked, so they don't count towards the prompt
+    /// tier stats or take up room in the prompt cache.
+    fn flag_response_text(&self, text: &str) -> Option<Category> {
+        let classification = match self.try_classify_untracked(text) {
+            Ok(classification) => classification,
+            Err(failure) => {
+                tracing::warn!("Response classification failed: {}", failure.reason);
+                return None;
+            }
+        };
+
+        let verdict = self
+            .config
+            .filtering_state
+            .rule_engine
+            .read()
+            .evaluate_now(&classification);
+        if verdict.should_allow() {
+            return None;
+        }
+        let top = classification.matches.first()?;
+        tracing::warn!(
+            "Assistant response matched {} (confidence: {:.2})",
+            top.category.name(),
+            top.confidence
+        );
+
+        let Some(profile_id) = self.config.filtering_state.profile_id() else {
+            tracing::debug!("No profile ID set, not recording flagged response");
+            return Some(top.category);
+        };
+        if let Some(ref db) = self.config.database {
+            let matched_phrases = classification
+                .matches
+                .iter()
+                .filter_map(|m| m.matched_pattern.clone())
+                .collect();
+            if let Err(e) = db.log_flagged_event(
+                profile_id,
+                "harmful_response",
+                top.confidence,
+                text,
+                None,
+                matched_phrases,
+            ) {
+                tracing::warn!("Failed to record flagged response: {}", e);
+            }
+        }
+        Some(top.category)
+    }
+
     /// Checks if a host is an image generation domain (F033).
     fn is_image_gen_domain(&self, host: &str) -> bool {
         self.config.site_registry.is_image_gen_domain(host)
@@ -594,9 +1382,33 @@ impl ProxyHandler {
 
     /// Creates a block response.
     fn create_block_response(&self, reason: &str, service: &str) -> Response<Body> {
-        let html = BLOCK_PAGE_HTML
-            .replace("{{REASON}}", reason)
-            .replace("{{SERVICE}}", service);
+        self.create_category_block_response(reason, service, None)
+    }
+
+    /// Creates a block response for a prompt blocked in `category`.
+    ///
+    /// Uses the custom block page template if one is configured.
+    fn create_category_block_response(
+        &self,
+        reason: &str,
+        service: &str,
+        category: Option<Category>,
+    ) -> Response<Body> {
+        let template = self
+            .config
+            .block_page_template
+            .as_deref()
+            .unwrap_or(BLOCK_PAGE_HTML);
+        let profile = self
+            .config
+            .filtering_state
+            .profile_name()
+            .unwrap_or_default();
+        let html = template
+            .replace("{{REASON}}", &html_escape(reason))
+            .replace("{{SERVICE}}", &html_escape(service))
+            .replace("{{PROFILE}}", &html_escape(&profile))
+            .replace("{{CATEGORY}}", category.map_or("", |c| c.name()));
 
         Response::builder()
             .status(403)
@@ -606,122 +1418,455 @@ impl ProxyHandler {
             .unwrap()
     }
 
-    /// Handles a request to an LLM domain.
-    async fn handle_llm_request(&self, host: &str, req: Request<Body>) -> RequestOrResponse {
-        let uri = req.uri().clone();
-        let path = uri.path();
-        let method = req.method().clone();
-
-        // Only process POST requests (API calls)
-        if method != hyper::Method::POST {
-            return RequestOrResponse::Request(req);
-        }
-
-        // Check if filtering is enabled (profile-aware)
-        if !self.config.filtering_state.is_enabled() {
-            let profile = self.config.filtering_state.profile_name();
-            tracing::debug!(
-                "Filtering disabled (profile: {:?}), passing through request to {}",
-                profile,
-                host
-            );
-            return RequestOrResponse::Request(req);
-        }
-
-        tracing::info!("HTTP POST to LLM domain: {}{}", host, path);
-
-        // Read the body
-        let (parts, body) = req.into_parts();
-        let body_bytes = match body.collect().await {
-            Ok(collected) => collected.to_bytes(),
-            Err(e) => {
-                tracing::warn!("Failed to read request body: {}", e);
-                return RequestOrResponse::Request(Request::from_parts(parts, Body::empty()));
+    /// Creates the warning page response for a warned prompt.
+    ///
+    /// Answered with 428 (Precondition Required) rather than 403, since the
+    /// prompt goes through once the child confirms.
+    ///
+    /// In cooldown mode the page re-submits the request after the delay,
+    /// which is also reported in an `X-Aegis-Cooldown` header (seconds).
+    /// A body that isn't UTF-8 text can't be re-submitted from the page
+    /// intact, so it gets the plain warning page (whose token still waits
+    /// out the delay).
+    fn create_warning_response(
+        &self,
+        interstitial: &WarnInterstitial,
+        reason: &str,
+        service: &str,
+        token: &str,
+        parts: &hyper::http::request::Parts,
+        body: &[u8],
+    ) -> Response<Body> {
+        let builder = Response::builder()
+            .status(hyper::StatusCode::PRECONDITION_REQUIRED)
+            .header("Content-Type", "text/html; charset=utf-8")
+            .header("X-Aegis-Warning", "true");
+
+        let text = std::str::from_utf8(body).ok();
+        let (builder, html) = match interstitial.cooldown().zip(text) {
+            Some((delay, text)) => {
+                let request = StashedRequest {
+                    method: parts.method.to_string(),
+                    url: parts
+                        .uri
+                        .path_and_query()
+                        .map_or("/", |pq| pq.as_str())
+                        .to_string(),
+                    content_type: parts
+                        .headers
+                        .get(hyper::header::CONTENT_TYPE)
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or("application/json")
+                        .to_string(),
+                    body: text.to_string(),
+                };
+                (
+                    builder.header("X-Aegis-Cooldown", delay.as_secs()),
+                    interstitial.cooldown_page(reason, service, token, &request),
+                )
             }
+            None => (builder, interstitial.page(reason, service, token)),
         };
 
-        // Check for multipart image uploads to image gen domains (F033)
-        if self.is_image_gen_domain(host)
-            && self.config.filtering_state.is_image_filtering_enabled()
-        {
-            let content_type = parts
-                .headers
-                .get(hyper::header::CONTENT_TYPE)
-                .and_then(|h| h.to_str().ok())
-                .unwrap_or("");
-
-            // Handle multipart/form-data uploads
-            if content_type.starts_with("multipart/form-data") {
-                if let Some(boundary) = extract_multipart_boundary(content_type) {
-                    let images = extract_images_from_multipart(&body_bytes, &boundary);
-
-                    if !images.is_empty() {
-                        let threshold = self.config.filtering_state.nsfw_threshold().threshold();
-                        tracing::info!(
-                            "Checking {} uploaded image(s) to {} for NSFW content",
-                            images.len(),
-                            host
-                        );
+        builder.body(bytes_to_body(Bytes::from(html))).unwrap()
+    }
 
-                        for (field_name, img) in images {
-                            let mut classifier = self.config.nsfw_classifier.write();
-                            if let Some(Ok(result)) = classifier.classify_bytes(&img.data) {
-                                tracing::debug!(
-                                    "Upload image '{}' NSFW score: {:.3} (threshold: {:.3})",
-                                    field_name,
-                                    result.nsfw_probability,
-                                    threshold
-                                );
+    /// Creates the 413 response for bodies over the scanning cap.
+    fn create_too_large_response(&self, host: &str) -> Response<Body> {
+        let service_name = self.config.site_registry.service_name(host);
+        tracing::warn!("Rejected oversized request body to {}", service_name);
+        let mut response = self.create_block_response("Request too large to scan", service_name);
+        *response.status_mut() = hyper::StatusCode::PAYLOAD_TOO_LARGE;
+        response
+    }
 
-                                if result.is_nsfw(threshold) {
-                                    let service_name = self.config.site_registry.service_name(host);
-
-                                    tracing::warn!(
-                                        "Blocked NSFW image upload '{}' to {}: score {:.3} exceeds threshold {:.3}",
-                                        field_name,
-                                        service_name,
-                                        result.nsfw_probability,
-                                        threshold
-                                    );
-
-                                    // Log the blocked event
-                                    if let Some(ref db) = self.config.database {
-                                        let _ = db.log_event(
-                                            &format!("[NSFW image upload blocked: {}]", field_name),
-                                            Some(Category::Adult),
-                                            Some(result.nsfw_probability),
-                                            Action::Blocked,
-                                            Some(service_name.to_string()),
-                                        );
-                                    }
-
-                                    // Send notification
-                                    if let Some(ref notifications) = self.config.notifications {
-                                        let event = BlockedEvent::new(
-                                            Some(service_name.to_string()),
-                                            Some(Category::Adult),
-                                            Some("NSFW image upload blocked".to_string()),
-                                            false,
-                                        );
-                                        let _ = notifications.notify_block(&event);
-                                    }
-
-                                    return RequestOrResponse::Response(
-                                        self.create_block_response(
-                                            "NSFW/explicit image upload detected",
-                                            service_name,
-                                        ),
-                                    );
-                                }
-                            }
-                        }
-                    }
+    /// Creates the response sent when the connection limit rejects a request.
+    fn create_overloaded_response() -> Response<Body> {
+        Response::builder()
+            .status(503)
+            .header("Content-Type", "text/plain; charset=utf-8")
+            .header("Retry-After", "1")
+            .header("X-Aegis-Overloaded", "true")
+            .body(bytes_to_body(Bytes::from_static(
+                b"Aegis is handling too many requests. Please retry.",
+            )))
+            .unwrap()
+    }
+
+    /// Reads a multipart upload body, classifying each image part as it arrives.
+    ///
+    /// Returns the full body for forwarding, or a block response as soon as an
+    /// NSFW image is seen or the upload grows past `limit` (the configured
+    /// `max_body_size`), so no more than `limit` bytes are ever held. A body
+    /// read error yields an empty body, matching the buffered path.
+    async fn read_upload_body(
+        &self,
+        host: &str,
+        mut body: Body,
+        boundary: &str,
+        limit: usize,
+    ) -> std::result::Result<Bytes, Response<Body>> {
+        let mut stream =
+            MultipartImageStream::with_limits(boundary, limit, DEFAULT_MAX_IMAGE_PART_SIZE);
+        let mut forward = Vec::new();
+        let mut checked = 0;
+
+        while let Some(frame) = body.frame().await {
+            let frame = match frame {
+                Ok(frame) => frame,
+                Err(e) => {
+                    tracing::warn!("Failed to read upload body: {}", e);
+                    return Ok(Bytes::new());
+                }
+            };
+            let Ok(data) = frame.into_data() else {
+                continue;
+            };
+
+            let images = match stream.feed(&data) {
+                Ok(images) => images,
+                Err(e) => {
+                    let service_name = self.config.site_registry.service_name(host);
+                    tracing::warn!("Rejected image upload to {}: {}", service_name, e);
+                    let mut response =
+                        self.create_block_response("Image upload too large to scan", service_name);
+                    *response.status_mut() = hyper::StatusCode::PAYLOAD_TOO_LARGE;
+                    return Err(response);
+                }
+            };
+            forward.extend_from_slice(&data);
+
+            for (field_name, img) in images {
+                checked += 1;
+                if let Some(response) = self.check_upload_image(host, &field_name, &img) {
+                    return Err(response);
+                }
+            }
+        }
+
+        if checked > 0 {
+            tracing::info!(
+                "Checked {} uploaded image(s) to {} for NSFW content",
+                checked,
+                host
+            );
+        }
+
+        Ok(Bytes::from(forward))
+    }
+
+    /// Classifies an uploaded image, returning a block response if it is NSFW.
+    fn check_upload_image(
+        &self,
+        host: &str,
+        field_name: &str,
+        img: &ExtractedImage,
+    ) -> Option<Response<Body>> {
+        let threshold = self.config.filtering_state.nsfw_threshold().threshold();
+        let classifier = self.config.nsfw_classifier.read();
+        let result = match classifier.classify_bytes(&img.data) {
+            Some(Ok(result)) => result,
+            _ => return None,
+        };
+
+        tracing::debug!(
+            "Upload image '{}' NSFW score: {:.3} (threshold: {:.3})",
+            field_name,
+            result.nsfw_probability,
+            threshold
+        );
+
+        if !result.is_nsfw(threshold) {
+            return None;
+        }
+
+        let service_name = self.config.site_registry.service_name(host);
+
+        tracing::warn!(
+            "Blocked NSFW image upload '{}' to {}: score {:.3} exceeds threshold {:.3}",
+            field_name,
+            service_name,
+            result.nsfw_probability,
+            threshold
+        );
+
+        // Log the blocked event
+        if let Some(ref db) = self.config.database {
+            let _ = db.log_event(
+                &format!("[NSFW image upload blocked: {}]", field_name),
+                Some(Category::Adult),
+                Some(result.nsfw_probability),
+                Action::Blocked,
+                Some(service_name.to_string()),
+            );
+        }
+
+        // Send notification
+        if let Some(ref notifications) = self.config.notifications {
+            let event = BlockedEvent::new(
+                Some(service_name.to_string()),
+                Some(Category::Adult),
+                Some("NSFW image upload blocked".to_string()),
+                false,
+            );
+            let _ = notifications.notify_block(&event);
+        }
+
+        Some(self.create_block_response("NSFW/explicit image upload detected", service_name))
+    }
+
+    /// Classifies text documents in a multipart upload, returning a block
+    /// response for the first one the rules block.
+    fn check_upload_documents(
+        &self,
+        host: &str,
+        path: &str,
+        body: &[u8],
+        boundary: &str,
+    ) -> Option<Response<Body>> {
+        let service_name = self.config.site_registry.service_name(host);
+
+        for doc in extract_documents_from_multipart(body, boundary, DEFAULT_MAX_DOCUMENT_TEXT) {
+            if doc.truncated {
+                tracing::debug!(
+                    "Upload document '{}' truncated to {} bytes for classification",
+                    doc.filename,
+                    DEFAULT_MAX_DOCUMENT_TEXT
+                );
+            }
+            let prompt_info = PromptInfo::new(doc.text, service_name, path);
+
+            let outcome = self.try_classify_prompt(&prompt_info.text);
+            let Some(classification) = self.resolve_classification(outcome, service_name) else {
+                self.record_event(
+                    &prompt_info,
+                    &ClassificationResult::safe(0),
+                    Action::Blocked,
+                );
+                return Some(self.create_block_response("Safety check unavailable", service_name));
+            };
+
+            let result = self.evaluate_rules(&prompt_info.text, None, &classification);
+            if !result.should_block() {
+                continue;
+            }
+
+            let reason = result
+                .source
+                .rule_name()
+                .unwrap_or("Policy violation")
+                .to_string();
+            tracing::info!(
+                "Blocked document upload '{}' to {} - reason: {}",
+                doc.filename,
+                service_name,
+                reason
+            );
+
+            self.record_event(&prompt_info, &classification, Action::Blocked);
+            if let Some(notifications) = &self.config.notifications {
+                let event =
+                    BlockedEvent::from_rule_source(&result.source, Some(service_name.to_string()));
+                let _ = notifications.notify_block(&event);
+            }
+            if let Some(callback) = &self.config.on_block {
+                callback(&prompt_info, &result);
+            }
+
+            return Some(self.create_category_block_response(
+                &reason,
+                service_name,
+                rule_category(&result),
+            ));
+        }
+
+        None
+    }
+
+    /// Handles a request to an LLM domain, updating the live counters.
+    async fn handle_llm_request(&self, host: &str, req: Request<Body>) -> RequestOrResponse {
+        let outcome = self.filter_llm_request(host, req).await;
+
+        let blocked = matches!(
+            &outcome,
+            RequestOrResponse::Response(res) if res.status() == hyper::StatusCode::FORBIDDEN
+        );
+        self.config
+            .filtering_state
+            .live_stats()
+            .record_request(blocked);
+        // Skip the model check rather than wait on an in-flight classification
+        if let Some(classifier) = self.config.classifier.try_read() {
+            self.record_model_status(&classifier);
+        }
+
+        outcome
+    }
+
+    /// Records whether the classifier's ML model loaded in the live stats.
+    fn record_model_status(&self, classifier: &TieredClassifier) {
+        let stats = self.config.filtering_state.live_stats();
+        stats.set_model_loaded(classifier.has_ml());
+        stats.set_model_error(match classifier.ml_load_error() {
+            Some(PromptGuardError::Incompatible(reason)) => Some(reason.as_str()),
+            _ => None,
+        });
+    }
+
+    /// Filters a request to an LLM domain.
+    async fn filter_llm_request(&self, host: &str, req: Request<Body>) -> RequestOrResponse {
+        let uri = req.uri().clone();
+        let path = uri.path();
+        let method = req.method().clone();
+
+        // Only process POST requests (API calls)
+        if method != hyper::Method::POST {
+            return RequestOrResponse::Request(req);
+        }
+
+        // The emergency override blocks every POST before any other check
+        if self.config.filtering_state.is_blocking_all() {
+            let service_name = self.config.site_registry.service_name(host);
+            tracing::info!("Blocked request to {} - block-all active", service_name);
+            return RequestOrResponse::Response(
+                self.create_block_response("All AI access is blocked", service_name),
+            );
+        }
+
+        // Forward telemetry/analytics traffic without extraction
+        if self.config.site_registry.should_skip_path(host, path) {
+            tracing::debug!("Skipping non-prompt path {}{}", host, path);
+            return RequestOrResponse::Request(req);
+        }
+
+        // Only inspect known prompt-bearing endpoints, plus multipart uploads
+        // that may carry documents (often sent to a separate files endpoint)
+        let multipart_boundary = req
+            .headers()
+            .get(hyper::header::CONTENT_TYPE)
+            .and_then(|h| h.to_str().ok())
+            .filter(|ct| ct.starts_with("multipart/form-data"))
+            .and_then(extract_multipart_boundary);
+        if multipart_boundary.is_none() && !self.config.site_registry.is_prompt_endpoint(host, path)
+        {
+            tracing::debug!("Forwarding non-chat endpoint {}{}", host, path);
+            return RequestOrResponse::Request(req);
+        }
+
+        // Check if filtering is enabled (profile-aware)
+        if !self.config.filtering_state.is_enabled() {
+            let profile = self.config.filtering_state.profile_name();
+            tracing::debug!(
+                "Filtering disabled (profile: {:?}), passing through request to {}",
+                profile,
+                host
+            );
+            return RequestOrResponse::Request(req);
+        }
+
+        tracing::info!("HTTP POST to LLM domain: {}{}", host, path);
+
+        // Multipart uploads to image gen domains are streamed through the NSFW
+        // check (F033) so images are classified as they arrive.
+        let (mut parts, body) = req.into_parts();
+        let length_policy = self.config.content_length_policy;
+        let declared_length = declared_content_length(&parts.headers);
+        if length_policy.exceeds_cap(declared_length) {
+            return RequestOrResponse::Response(self.create_too_large_response(host));
+        }
+
+        let stream_images = self.is_image_gen_domain(host)
+            && self.config.filtering_state.is_image_filtering_enabled();
+
+        // Read the body
+        let body_bytes = if let Some(boundary) =
+            multipart_boundary.as_ref().filter(|_| stream_images)
+        {
+            match self
+                .read_upload_body(host, body, boundary, length_policy.max_body_size)
+                .await
+            {
+                Ok(bytes) => bytes,
+                Err(response) => return RequestOrResponse::Response(response),
+            }
+        } else {
+            match read_body_capped(body, length_policy.max_body_size).await {
+                Ok(bytes) => bytes,
+                Err(BodyReadError::TooLarge { .. }) => {
+                    return RequestOrResponse::Response(self.create_too_large_response(host));
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to read request body: {}", e);
+                    return RequestOrResponse::Request(Request::from_parts(parts, Body::empty()));
+                }
+            }
+        };
+
+        // A body that disagrees with its Content-Length is either rejected or
+        // scanned in full and forwarded with the header corrected.
+        if let Err(mismatch) = length_policy.check(declared_length, body_bytes.len()) {
+            let service_name = self.config.site_registry.service_name(host);
+            match length_policy.on_mismatch {
+                LengthMismatchAction::Block => {
+                    tracing::warn!("Rejected request to {}: {}", service_name, mismatch);
+                    let mut response =
+                        self.create_block_response("Request size mismatch", service_name);
+                    *response.status_mut() = hyper::StatusCode::BAD_REQUEST;
+                    return RequestOrResponse::Response(response);
+                }
+                LengthMismatchAction::ScanFull => {
+                    tracing::warn!("Scanning full body to {}: {}", service_name, mismatch);
+                    parts
+                        .headers
+                        .insert(hyper::header::CONTENT_LENGTH, body_bytes.len().into());
+                }
+            }
+        }
+
+        // Compressed bodies are scanned, and forwarded, decoded
+        let body_bytes = match decode_request_body(
+            &mut parts.headers,
+            body_bytes.clone(),
+            length_policy.max_body_size,
+        ) {
+            Ok(decoded) => decoded,
+            Err(DecodeError::TooLarge { .. }) => {
+                return RequestOrResponse::Response(self.create_too_large_response(host));
+            }
+            // The upstream may decode what we can't, so forwarding it
+            // unscanned is only allowed when failing open
+            Err(e) => match self.config.classifier_failure_policy {
+                ClassifierFailurePolicy::FailOpen => {
+                    tracing::warn!("Scanning undecoded body to {}{}: {}", host, path, e);
+                    body_bytes
+                }
+                ClassifierFailurePolicy::FailClosed => {
+                    let service_name = self.config.site_registry.service_name(host);
+                    tracing::warn!("Rejected request to {}: {}", service_name, e);
+                    let mut response =
+                        self.create_block_response("Unreadable request body", service_name);
+                    *response.status_mut() = hyper::StatusCode::BAD_REQUEST;
+                    return RequestOrResponse::Response(response);
+                }
+            },
+        };
+
+        // Attached text documents are classified like prompts
+        if let Some(boundary) = &multipart_boundary {
+            if self.config.filtering_state.is_text_filtering_enabled() {
+                if let Some(response) =
+                    self.check_upload_documents(host, path, &body_bytes, boundary)
+                {
+                    return RequestOrResponse::Response(response);
                 }
             }
         }
 
-        // Extract prompt
-        let prompt_info = match extract_prompt(host, path, &body_bytes) {
+        // Extract prompt, with the site's own parser if it names one
+        let prompt_info = match self
+            .extract_with_site_parser(host, path, &parts, &body_bytes)
+            .or_else(|| extract_prompt(host, path, &body_bytes))
+        {
             Some(info) => info,
             None => {
                 // No prompt extracted, forward request
@@ -744,14 +1889,52 @@ impl ProxyHandler {
             prompt_info.text.len()
         );
 
-        // Classify the prompt
-        let classification = self.classify_prompt(&prompt_info.text);
+        // Text classification has its own per-profile toggle; sentiment
+        // flagging still runs when it is off
+        if !self.config.filtering_state.is_text_filtering_enabled() {
+            tracing::debug!(
+                "Text filtering disabled, forwarding prompt to {}",
+                prompt_info.service
+            );
+            self.analyze_and_flag_sentiment(&prompt_info);
+            return RequestOrResponse::Request(Request::from_parts(
+                parts,
+                bytes_to_body(body_bytes),
+            ));
+        }
+
+        // Classify the prompt, applying the failure policy on classifier errors
+        let outcome = self.try_classify_prompt(&prompt_info.text);
+        let classification = match self.resolve_classification(outcome, &prompt_info.service) {
+            Some(classification) => classification,
+            None => {
+                self.record_event(
+                    &prompt_info,
+                    &ClassificationResult::safe(0),
+                    Action::Blocked,
+                );
+                return RequestOrResponse::Response(
+                    self.create_block_response("Safety check unavailable", &prompt_info.service),
+                );
+            }
+        };
 
         // Analyze sentiment for parental review flagging (runs regardless of blocking)
         self.analyze_and_flag_sentiment(&prompt_info);
 
-        // Evaluate rules
-        let result = self.evaluate_rules(&classification);
+        // Evaluate rules, escalating repeated warnings. A warning the child
+        // confirmed on the warning page was counted when the page was shown.
+        let result = self.evaluate_rules(
+            &prompt_info.text,
+            prompt_info.model.as_deref(),
+            &classification,
+        );
+        let continued = result.should_warn() && self.warning_confirmed(&parts, &prompt_info);
+        let result = if continued {
+            result
+        } else {
+            self.escalate_warnings(result)
+        };
 
         match result.action {
             RuleAction::Block => {
@@ -785,19 +1968,51 @@ impl ProxyHandler {
                 }
 
                 // Return block page
-                RequestOrResponse::Response(
-                    self.create_block_response(&reason, &prompt_info.service),
-                )
+                RequestOrResponse::Response(self.create_category_block_response(
+                    &reason,
+                    &prompt_info.service,
+                    rule_category(&result),
+                ))
             }
             RuleAction::Warn => {
+                if let Some(interstitial) = self.config.warn_interstitial.as_ref() {
+                    if !continued {
+                        let reason = result.source.rule_name().unwrap_or("Sensitive content");
+                        tracing::info!(
+                            "Showing {} page for {} - reason: {}",
+                            if interstitial.cooldown().is_some() {
+                                "cooldown"
+                            } else {
+                                "warning"
+                            },
+                            prompt_info.service,
+                            reason
+                        );
+                        self.record_event(&prompt_info, &classification, Action::Warned);
+
+                        let token = interstitial.issue(&prompt_info.text);
+                        return RequestOrResponse::Response(self.create_warning_response(
+                            interstitial,
+                            reason,
+                            &prompt_info.service,
+                            &token,
+                            &parts,
+                            &body_bytes,
+                        ));
+                    }
+                }
+
                 tracing::info!(
-                    "Warned request to {} - reason: {:?}",
+                    "Warned request to {} - reason: {:?}{}",
                     prompt_info.service,
-                    result.source.rule_name()
+                    result.source.rule_name(),
+                    if continued { " (continued)" } else { "" }
                 );
 
-                // Record event to database
-                self.record_event(&prompt_info, &classification, Action::Flagged);
+                // Record event to database, unless the warning page did
+                if !continued {
+                    self.record_event(&prompt_info, &classification, Action::Warned);
+                }
 
                 // Call on_allow callback (warn still allows)
                 if let Some(callback) = &self.config.on_allow {
@@ -808,13 +2023,12 @@ impl ProxyHandler {
                 let mut req = Request::from_parts(parts, bytes_to_body(body_bytes));
                 req.headers_mut()
                     .insert("X-Aegis-Warning", "true".parse().unwrap());
+                self.add_decision_headers(&mut req, "warn", &classification);
                 RequestOrResponse::Request(req)
             }
             RuleAction::Allow => {
-                tracing::debug!("Allowed request to {}", prompt_info.service);
-
-                // Record event to database
-                self.record_event(&prompt_info, &classification, Action::Allowed);
+                // Log and record event to database
+                self.record_allowed(&prompt_info, &classification);
 
                 // Call on_allow callback
                 if let Some(callback) = &self.config.on_allow {
@@ -822,7 +2036,117 @@ impl ProxyHandler {
                 }
 
                 // Forward the request
-                RequestOrResponse::Request(Request::from_parts(parts, bytes_to_body(body_bytes)))
+                let mut req = Request::from_parts(parts, bytes_to_body(body_bytes));
+                self.add_decision_headers(&mut req, "allow", &classification);
+                RequestOrResponse::Request(req)
+            }
+        }
+    }
+
+    /// Adds `X-Aegis-Action`, `X-Aegis-Category`, `X-Aegis-Confidence`, and
+    /// `X-Aegis-Profile` to a forwarded request, if decision headers are on.
+    ///
+    /// Category and confidence describe the strongest match and are omitted
+    /// when nothing matched; the profile is omitted when none is active or
+    /// its name isn't a valid header value.
+    fn add_decision_headers(
+        &self,
+        req: &mut Request<Body>,
+        action: &'static str,
+        classification: &ClassificationResult,
+    ) {
+        if !self.config.decision_headers {
+            return;
+        }
+
+        let headers = req.headers_mut();
+        headers.insert("X-Aegis-Action", HeaderValue::from_static(action));
+        if let Some(top) = classification.highest_confidence() {
+            if let Some(category) = serde_json::to_value(top.category)
+                .ok()
+                .and_then(|v| v.as_str().and_then(|s| HeaderValue::from_str(s).ok()))
+            {
+                headers.insert("X-Aegis-Category", category);
+            }
+            if let Ok(confidence) = HeaderValue::from_str(&format!("{:.2}", top.confidence)) {
+                headers.insert("X-Aegis-Confidence", confidence);
+            }
+        }
+        if let Some(profile) = self
+            .config
+            .filtering_state
+            .profile_name()
+            .and_then(|name| HeaderValue::from_str(&name).ok())
+        {
+            headers.insert("X-Aegis-Profile", profile);
+        }
+    }
+
+    /// Applies the missing-host policy to a request with no known destination.
+    fn handle_missing_host(&self, req: Request<Body>) -> RequestOrResponse {
+        match self.config.missing_host_policy {
+            MissingHostPolicy::Forward => {
+                tracing::warn!("Forwarding {} request with no host", req.method());
+                RequestOrResponse::Request(req)
+            }
+            MissingHostPolicy::Block => {
+                tracing::warn!("Rejected {} request with no host", req.method());
+                let mut response =
+                    self.create_block_response("Request has no destination host", "Unknown");
+                *response.status_mut() = hyper::StatusCode::BAD_REQUEST;
+                RequestOrResponse::Response(response)
+            }
+        }
+    }
+
+    /// Classifies a client-to-server WebSocket text frame.
+    ///
+    /// Returns the block reason if the frame should be dropped. Frames too
+    /// large or too deeply nested to parse safely are passed through.
+    fn websocket_block_reason(&self, host: &str, text: &str) -> Option<String> {
+        let prompt = websocket_prompt(host, text)?;
+
+        tracing::info!(
+            "WebSocket prompt extracted from {}: {} chars",
+            host,
+            prompt.len()
+        );
+
+        // Classify the prompt, applying the failure policy on classifier errors
+        let outcome = self.try_classify_prompt(&prompt);
+        let classification = match self.resolve_classification(outcome, host) {
+            Some(classification) => classification,
+            None => return Some("Safety check unavailable".to_string()),
+        };
+
+        // Evaluate rules using the shared rule engine
+        let result = self.evaluate_rules(&prompt, None, &classification);
+        let result = self.escalate_warnings(result);
+
+        match result.action {
+            RuleAction::Block => {
+                let reason = result.source.rule_name().unwrap_or("Policy violation");
+
+                tracing::info!("Blocked WebSocket message to {} - reason: {}", host, reason);
+
+                // Send notification
+                if let Some(ref notif) = self.config.notifications {
+                    let event = BlockedEvent::from_rule_source(
+                        &result.source,
+                        Some(crate::domains::service_name(host).to_string()),
+                    );
+                    let _ = notif.notify_block(&event);
+                }
+
+                Some(reason.to_string())
+            }
+            RuleAction::Warn => {
+                tracing::info!("Warned WebSocket message to {}", host);
+                None
+            }
+            RuleAction::Allow => {
+                tracing::debug!("Allowed WebSocket message to {}", host);
+                None
             }
         }
     }
@@ -840,19 +2164,45 @@ impl ProxyHandler {
             .and_then(|h| h.to_str().ok())
             .map(|s| s.split(':').next().unwrap_or(s).to_string())
     }
-}
 
-impl HttpHandler for ProxyHandler {
-    async fn handle_request(
-        &mut self,
-        _ctx: &HttpContext,
+    /// Processes an intercepted request without needing a live proxy
+    /// connection.
+    #[cfg(any(test, feature = "testing"))]
+    pub(crate) async fn process_request(&self, req: Request<Body>) -> RequestOrResponse {
+        self.process_request_from(None, req).await
+    }
+
+    /// Processes an intercepted request from `client_addr`.
+    /// [`HttpHandler::handle_request`] delegates here.
+    ///
+    /// Requests without a host fall back to the client's `CONNECT` host.
+    pub(crate) async fn process_request_from(
+        &self,
+        client_addr: Option<SocketAddr>,
         mut req: Request<Body>,
     ) -> RequestOrResponse {
         let host = match Self::extract_host(&req) {
             Some(h) => h,
-            None => return RequestOrResponse::Request(req),
+            None => match client_addr.and_then(|addr| self.tunnel_hosts.get(addr)) {
+                Some(h) => {
+                    tracing::debug!("Request without host, using tunnel host {}", h);
+                    h
+                }
+                None => return self.handle_missing_host(req),
+            },
         };
 
+        if req.method() == hyper::Method::CONNECT {
+            if let Some(addr) = client_addr {
+                self.tunnel_hosts.record(addr, &host);
+            }
+        }
+
+        // Answer interception canaries directly so the sender knows we saw them
+        if is_canary_request(&host, &req) {
+            return RequestOrResponse::Response(canary_response(&req));
+        }
+
         // For all WebSocket upgrades, strip compression extension
         // This prevents protocol errors when proxying compressed WebSocket messages
         // We do this for all domains because the proxy doesn't handle permessage-deflate
@@ -862,7 +2212,7 @@ impl HttpHandler for ProxyHandler {
         }
 
         // Only intercept LLM domains
-        if !is_llm_domain(&host) {
+        if !self.config.site_registry.is_monitored(&host) {
             return RequestOrResponse::Request(req);
         }
 
@@ -872,15 +2222,29 @@ impl HttpHandler for ProxyHandler {
             host
         );
 
-        self.handle_llm_request(&host, req).await
+        match &self.config.connection_limiter {
+            Some(limiter) => match limiter.run(self.handle_llm_request(&host, req)).await {
+                Some(result) => result,
+                None => {
+                    tracing::warn!(
+                        "Connection limit ({}) reached, rejecting request to {}",
+                        limiter.max(),
+                        host
+                    );
+                    RequestOrResponse::Response(Self::create_overloaded_response())
+                }
+            },
+            None => self.handle_llm_request(&host, req).await,
+        }
+    }
+}
+
+impl HttpHandler for ProxyHandler {
+    async fn handle_request(&mut self, ctx: &HttpContext, req: Request<Body>) -> RequestOrResponse {
+        self.process_request_from(Some(ctx.client_addr), req).await
     }
 
     async fn handle_response(&mut self, _ctx: &HttpContext, res: Response<Body>) -> Response<Body> {
-        // Check if image filtering is enabled
-        if !self.config.filtering_state.is_image_filtering_enabled() {
-            return res;
-        }
-
         // Check if filtering is enabled at all
         if !self.config.filtering_state.is_enabled() {
             return res;
@@ -894,6 +2258,19 @@ impl HttpHandler for ProxyHandler {
             .unwrap_or("")
             .to_string();
 
+        // Streamed completions are scanned as they pass through
+        if is_streaming_response(&content_type) {
+            if self.config.filtering_state.is_response_scanning_enabled() {
+                return self.scan_streaming_response(res);
+            }
+            return res;
+        }
+
+        // Check if image filtering is enabled
+        if !self.config.filtering_state.is_image_filtering_enabled() {
+            return res;
+        }
+
         // Only process JSON or image responses that might contain generated images
         // Note: We can't easily get the host in handle_response, so we filter based on content type
         if !content_type.starts_with("application/json") && !content_type.starts_with("image/") {
@@ -927,7 +2304,7 @@ impl HttpHandler for ProxyHandler {
                 );
 
                 for img in images {
-                    let mut classifier = self.config.nsfw_classifier.write();
+                    let classifier = self.config.nsfw_classifier.read();
                     match classifier.classify_bytes(&img.data) {
                         Some(Ok(result)) => {
                             tracing::info!(
@@ -972,7 +2349,7 @@ impl HttpHandler for ProxyHandler {
                     img.data.len()
                 );
 
-                let mut classifier = self.config.nsfw_classifier.write();
+                let classifier = self.config.nsfw_classifier.read();
                 match classifier.classify_bytes(&img.data) {
                     Some(Ok(result)) => {
                         tracing::info!(
@@ -1042,36 +2419,64 @@ impl HttpHandler for ProxyHandler {
 }
 
 impl WebSocketHandler for ProxyHandler {
+    async fn handle_websocket(
+        self,
+        ctx: WebSocketContext,
+        stream: impl Stream<Item = Result<Message, tungstenite::Error>> + Unpin + Send + 'static,
+        sink: impl Sink<Message, Error = tungstenite::Error> + Unpin + Send + 'static,
+    ) {
+        // The server-to-client side also forwards messages the proxy sends
+        // the client itself, such as block errors
+        let connection = match &ctx {
+            WebSocketContext::ServerToClient { src, dst, .. } => Some((*dst, src.clone())),
+            WebSocketContext::ClientToServer { .. } => None,
+        };
+        let injected = connection
+            .as_ref()
+            .map(|(client, uri)| self.websocket_clients.register(*client, uri));
+        let clients = self.websocket_clients.clone();
+
+        forward_websocket(self, ctx, stream, sink, injected).await;
+
+        if let Some((client, uri)) = connection {
+            clients.unregister(client, &uri);
+        }
+    }
+
     fn handle_message(
         &mut self,
         ctx: &WebSocketContext,
         message: Message,
     ) -> impl std::future::Future<Output = Option<Message>> + Send {
-        let classifier = self.config.classifier.clone();
+        let handler = self.clone();
         let filtering_state = self.config.filtering_state.clone();
-        let notifications = self.config.notifications.clone();
 
         // Only inspect client-to-server messages (outgoing prompts)
         // Server-to-client messages (responses) pass through unchanged
-        let host = match ctx {
-            WebSocketContext::ClientToServer { dst, .. } => {
-                Some(dst.host().unwrap_or("unknown").to_string())
-            }
+        let connection = match ctx {
+            WebSocketContext::ClientToServer { src, dst, .. } => Some((*src, dst.clone())),
             WebSocketContext::ServerToClient { .. } => None,
         };
 
         async move {
             // Skip server-to-client (responses) - pass through unchanged
-            let host = match host {
-                Some(h) => h,
+            let (client, uri) = match connection {
+                Some(connection) => connection,
                 None => return Some(message),
             };
+            let host = uri.host().unwrap_or("unknown").to_string();
 
             // Only inspect LLM domain WebSocket traffic
-            if !is_llm_domain(&host) {
+            if !handler.config.site_registry.is_monitored(&host) {
                 return Some(message);
             }
 
+            if filtering_state.is_blocking_all() {
+                tracing::info!("Dropped WebSocket message to {} - block-all active", host);
+                handler.notify_websocket_block(client, &uri, "All AI access is blocked");
+                return None;
+            }
+
             // Check if filtering is enabled (profile-aware)
             if !filtering_state.is_enabled() {
                 tracing::debug!(
@@ -1081,12 +2486,19 @@ impl WebSocketHandler for ProxyHandler {
                 return Some(message);
             }
 
+            if !filtering_state.is_text_filtering_enabled() {
+                tracing::debug!(
+                    "Text filtering disabled, passing through WebSocket message to {}",
+                    host
+                );
+                return Some(message);
+            }
+
             // Only inspect text messages (JSON payloads)
             let text = match &message {
                 Message::Text(t) => {
-                    let s = t.to_string();
-                    tracing::info!("WebSocket text message from {} ({} bytes)", host, s.len());
-                    s
+                    tracing::info!("WebSocket text message from {} ({} bytes)", host, t.len());
+                    t.as_str()
                 }
                 Message::Binary(b) => {
                     tracing::debug!("WebSocket binary message from {} ({} bytes)", host, b.len());
@@ -1095,58 +2507,103 @@ impl WebSocketHandler for ProxyHandler {
                 _ => return Some(message),
             };
 
-            // Try to extract prompt from WebSocket message
-            // ChatGPT WebSocket messages contain JSON with message content
-            if let Some(prompt) = extract_websocket_prompt(&text) {
-                tracing::info!(
-                    "WebSocket prompt extracted from {}: {} chars",
-                    host,
-                    prompt.len()
-                );
-
-                // Classify the prompt
-                let classification = classifier.write().classify(&prompt);
-
-                // Evaluate rules using the shared rule engine
-                let result = filtering_state
-                    .rule_engine
-                    .read()
-                    .evaluate_now(&classification);
+            if let Some(reason) = handler.websocket_block_reason(&host, text) {
+                // Drop the message and tell the client, so its UI doesn't
+                // wait for a reply
+                handler.notify_websocket_block(client, &uri, &reason);
+                return None;
+            }
 
-                match result.action {
-                    RuleAction::Block => {
-                        let reason = result.source.rule_name().unwrap_or("Policy violation");
+            Some(message)
+        }
+    }
+}
 
-                        tracing::info!(
-                            "Blocked WebSocket message to {} - reason: {}",
-                            host,
-                            reason
-                        );
+impl ProxyHandler {
+    /// Sends `client`'s WebSocket to `uri` a block error and closes it.
+    ///
+    /// Returns false if the connection's server-to-client side isn't
+    /// forwarding, in which case the message is only dropped.
+    fn notify_websocket_block(&self, client: SocketAddr, uri: &Uri, reason: &str) -> bool {
+        let sent = self
+            .websocket_clients
+            .send(client, uri, websocket_block_messages(reason));
+        if !sent {
+            tracing::debug!("No channel back to WebSocket client {}", client);
+        }
+        sent
+    }
+}
 
-                        // Send notification
-                        if let Some(ref notif) = notifications {
-                            let event = BlockedEvent::from_rule_source(
-                                &result.source,
-                                Some(crate::domains::service_name(&host).to_string()),
-                            );
-                            let _ = notif.notify_block(&event);
-                        }
+/// Forwards WebSocket messages from `stream` to `sink` through `handler`,
+/// interleaving any `injected` messages. An injected close ends forwarding.
+async fn forward_websocket(
+    mut handler: ProxyHandler,
+    ctx: WebSocketContext,
+    mut stream: impl Stream<Item = Result<Message, tungstenite::Error>> + Unpin,
+    mut sink: impl Sink<Message, Error = tungstenite::Error> + Unpin,
+    mut injected: Option<UnboundedReceiver<Message>>,
+) {
+    loop {
+        let next_injected = async {
+            match injected.as_mut() {
+                Some(receiver) => receiver.recv().await,
+                None => std::future::pending().await,
+            }
+        };
+        let message = tokio::select! {
+            message = stream.next() => message,
+            Some(message) = next_injected => {
+                let closing = message.is_close();
+                send_websocket_message(&mut sink, message).await;
+                if closing {
+                    break;
+                }
+                continue;
+            }
+        };
 
-                        // Block by returning None
-                        return None;
-                    }
-                    RuleAction::Warn => {
-                        tracing::info!("Warned WebSocket message to {}", host);
-                    }
-                    RuleAction::Allow => {
-                        tracing::debug!("Allowed WebSocket message to {}", host);
-                    }
+        match message {
+            Some(Ok(message)) => {
+                if let Some(message) = handler.handle_message(&ctx, message).await {
+                    send_websocket_message(&mut sink, message).await;
                 }
             }
+            Some(Err(e)) => {
+                tracing::error!("WebSocket message error: {}", e);
+                send_websocket_message(&mut sink, Message::Close(None)).await;
+                break;
+            }
+            None => break,
+        }
+    }
+}
 
-            Some(message)
+/// Sends one WebSocket message, logging failures other than a closed socket.
+async fn send_websocket_message(
+    sink: &mut (impl Sink<Message, Error = tungstenite::Error> + Unpin),
+    message: Message,
+) {
+    match sink.send(message).await {
+        Ok(()) | Err(tungstenite::Error::ConnectionClosed) => {}
+        Err(e) => tracing::error!("WebSocket send error: {}", e),
+    }
+}
+
+/// Escapes `value` for use as HTML text or a quoted attribute value.
+fn html_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
         }
     }
+    escaped
 }
 
 /// Extracts the boundary from a multipart/form-data content-type header.
@@ -1159,6 +2616,58 @@ fn extract_multipart_boundary(content_type: &str) -> Option<String> {
     })
 }
 
+/// Returns true if JSON nesting in `text` goes deeper than `limit`.
+///
+/// A byte scan that skips string contents, so it is cheap compared to a parse.
+fn json_nesting_exceeds(text: &str, limit: usize) -> bool {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for b in text.bytes() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > limit {
+                    return true;
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Extracts prompt text from a WebSocket frame after size and depth checks.
+///
+/// Oversized or pathologically nested frames are skipped without parsing.
+fn websocket_prompt(host: &str, text: &str) -> Option<String> {
+    if text.len() > MAX_WEBSOCKET_MESSAGE_SIZE {
+        tracing::debug!(
+            "Skipping oversized WebSocket message to {} ({} bytes)",
+            host,
+            text.len()
+        );
+        return None;
+    }
+    if json_nesting_exceeds(text, MAX_WEBSOCKET_JSON_DEPTH) {
+        tracing::debug!("Skipping deeply nested WebSocket message to {}", host);
+        return None;
+    }
+    extract_websocket_prompt(text)
+}
+
 /// Extracts prompt text from a WebSocket message (usually JSON).
 fn extract_websocket_prompt(text: &str) -> Option<String> {
     // Try to parse as JSON and extract common prompt fields
@@ -1260,11 +2769,783 @@ mod tests {
             database: None,
             nsfw_classifier: Arc::new(RwLock::new(LazyNsfwClassifier::with_defaults())),
             site_registry: Arc::new(SiteRegistry::with_defaults()),
+            connection_limiter: None,
+            classifier_failure_policy: ClassifierFailurePolicy::default(),
+            startup_guard: None,
+            content_length_policy: ContentLengthPolicy::default(),
+            missing_host_policy: MissingHostPolicy::default(),
+            allow_logging: AllowLogging::default(),
+            warn_escalation: None,
+            warn_interstitial: None,
+            block_page_template: None,
+            decision_headers: false,
         };
         let debug = format!("{:?}", config);
         assert!(debug.contains("HandlerConfig"));
     }
 
+    fn post_request(host: &str, path: &str, body: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri(format!("https://{}{}", host, path))
+            .header("Content-Type", "application/json")
+            .body(bytes_to_body(Bytes::from(body.to_string())))
+            .unwrap()
+    }
+
+    async fn forwarded_body(outcome: RequestOrResponse) -> Option<Bytes> {
+        match outcome {
+            RequestOrResponse::Request(req) => {
+                Some(req.into_body().collect().await.ok()?.to_bytes())
+            }
+            RequestOrResponse::Response(_) => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn only_chat_endpoints_are_processed() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let handler = ProxyHandler::with_filtering_state(filtering_state);
+        let body =
+            r#"{"messages": [{"role": "user", "content": "how to make a bomb to kill people"}]}"#;
+
+        let outcome = handler
+            .handle_llm_request(
+                "api.openai.com",
+                post_request("api.openai.com", "/v1/chat/completions", body),
+            )
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+
+        let outcome = handler
+            .handle_llm_request(
+                "api.openai.com",
+                post_request("api.openai.com", "/v1/files", body),
+            )
+            .await;
+        assert_eq!(forwarded_body(outcome).await.unwrap(), body.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn telemetry_post_forwarded_without_extraction() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let handler = ProxyHandler::with_filtering_state(filtering_state);
+        let body = r#"{"prompt": "how to make a bomb to kill people"}"#;
+
+        // The same payload on a chat endpoint is blocked...
+        let outcome = handler
+            .handle_llm_request(
+                "chatgpt.com",
+                post_request("chatgpt.com", "/backend-api/conversation", body),
+            )
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+
+        // ...but telemetry paths are forwarded untouched, body intact
+        let outcome = handler
+            .handle_llm_request(
+                "chatgpt.com",
+                post_request("chatgpt.com", "/ces/v1/t", body),
+            )
+            .await;
+        assert_eq!(forwarded_body(outcome).await.unwrap(), body.as_bytes());
+    }
+
+    fn upload_request(host: &str, path: &str, filename: &str, content: &str) -> Request<Body> {
+        let body = format!(
+            "--AegisUpload\r\n\
+             Content-Disposition: form-data; name=\"purpose\"\r\n\r\n\
+             assistants\r\n\
+             --AegisUpload\r\n\
+             Content-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n\
+             {}\r\n\
+             --AegisUpload--\r\n",
+            filename, content
+        );
+        Request::builder()
+            .method("POST")
+            .uri(format!("https://{}{}", host, path))
+            .header("Content-Type", "multipart/form-data; boundary=AegisUpload")
+            .body(bytes_to_body(Bytes::from(body)))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn text_document_upload_with_banned_content_is_blocked() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let handler = ProxyHandler::with_filtering_state(filtering_state);
+        let banned = "step one: how to make a bomb to kill people";
+
+        let outcome = handler
+            .handle_llm_request(
+                "api.openai.com",
+                upload_request("api.openai.com", "/v1/files", "plan.txt", banned),
+            )
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+
+        // Clean documents and non-text files are forwarded intact
+        let req = upload_request("api.openai.com", "/v1/files", "notes.md", "# Homework");
+        let outcome = handler.handle_llm_request("api.openai.com", req).await;
+        assert!(forwarded_body(outcome).await.is_some());
+
+        let req = upload_request("api.openai.com", "/v1/files", "plan.pdf", banned);
+        let outcome = handler.handle_llm_request("api.openai.com", req).await;
+        assert!(forwarded_body(outcome).await.is_some());
+    }
+
+    // ==================== Response Scanning Tests ====================
+
+    fn response_scanning_handler() -> (ProxyHandler, Arc<Database>) {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let db = Arc::new(Database::in_memory().unwrap());
+        let profile_id = stored_profile_in(&db);
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        filtering_state.set_profile_with_id(Some("Harper".to_string()), Some(profile_id));
+        filtering_state.enable_response_scanning(true);
+        let mut handler = ProxyHandler::with_filtering_state(filtering_state);
+        handler.config.database = Some(db.clone());
+        (handler, db)
+    }
+
+    fn stored_profile_in(db: &Database) -> i64 {
+        db.create_profile(aegis_storage::NewProfile {
+            name: "Harper".to_string(),
+            os_username: None,
+            time_rules: serde_json::json!({"rules": []}),
+            content_rules: serde_json::json!({"rules": []}),
+            enabled: true,
+            sentiment_config: ProfileSentimentConfig::default(),
+            image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: aegis_core::profile::ProxyMode::Enabled,
+            text_filtering_enabled: true,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn response_scanning_is_off_by_default() {
+        let state = FilteringState::new();
+        assert!(!state.is_response_scanning_enabled());
+        state.enable_response_scanning(true);
+        assert!(state.is_response_scanning_enabled());
+    }
+
+    #[test]
+    fn harmful_response_is_flagged_not_blocked() {
+        let (handler, db) = response_scanning_handler();
+
+        let flagged = handler.flag_response_text("Sure! Here is how to make a bomb to kill people");
+        assert!(flagged.is_some());
+        assert_eq!(
+            handler.flag_response_text("Photosynthesis turns light into sugar"),
+            None
+        );
+
+        let events = db
+            .get_flagged_events(aegis_storage::FlaggedEventFilter::default())
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].flag_type, "harmful_response");
+        assert!(events[0].content_snippet.contains("make a bomb"));
+    }
+
+    #[test]
+    fn response_scanning_leaves_prompt_stats_and_cache_alone() {
+        use aegis_core::classifier::TieredClassifierConfig;
+
+        let (mut handler, _db) = response_scanning_handler();
+        handler.config.classifier =
+            Arc::new(RwLock::new(TieredClassifier::new(TieredClassifierConfig {
+                enable_ml: false,
+                ..TieredClassifierConfig::cached()
+            })));
+
+        assert!(handler
+            .flag_response_text("Sure! Here is how to make a bomb to kill people")
+            .is_some());
+        let classifier = handler.config.classifier.read();
+        assert_eq!(classifier.stats().classifications, 0);
+        assert!(classifier.cache().unwrap().is_empty());
+    }
+
+    #[test]
+    fn grooming_response_is_flagged_not_blocked() {
+        let (handler, db) = response_scanning_handler();
+        handler
+            .config
+            .filtering_state
+            .enable_sentiment_analysis(SentimentConfig::default());
+
+        let text = "That's okay, this can be our little secret. Don't tell your parents.";
+        assert_eq!(handler.flag_response_text(text), None);
+        assert_eq!(handler.flag_response_sentiment(text), 1);
+        assert_eq!(
+            handler.flag_response_sentiment("Photosynthesis turns light into sugar"),
+            0
+        );
+
+        let events = db
+            .get_flagged_events(aegis_storage::FlaggedEventFilter::default())
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].flag_type, "bullying");
+        assert!(events[0].content_snippet.contains("little secret"));
+    }
+
+    #[tokio::test]
+    async fn scanned_stream_is_forwarded_unchanged() {
+        let (handler, _db) = response_scanning_handler();
+        let stream = "data: {\"choices\":[{\"delta\":{\"content\":\"Hi\"}}]}\n\ndata: [DONE]\n\n";
+        let res = Response::builder()
+            .header(hyper::header::CONTENT_TYPE, "text/event-stream")
+            .body(bytes_to_body(Bytes::from(stream)))
+            .unwrap();
+
+        let scanned = handler.scan_streaming_response(res);
+        let forwarded = scanned.into_body().collect().await.unwrap().to_bytes();
+
+        assert_eq!(&forwarded[..], stream.as_bytes());
+    }
+
+    // ==================== Content-Length Tests ====================
+
+    fn content_length_handler(on_mismatch: LengthMismatchAction) -> ProxyHandler {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let mut handler = ProxyHandler::with_filtering_state(filtering_state);
+        handler.config.content_length_policy.on_mismatch = on_mismatch;
+        handler
+    }
+
+    /// Builds a request whose `Content-Length` covers only the first
+    /// `declared` bytes of `body`.
+    fn spoofed_request(body: &str, declared: usize) -> Request<Body> {
+        let mut req = post_request("api.openai.com", "/v1/chat/completions", body);
+        req.headers_mut()
+            .insert(hyper::header::CONTENT_LENGTH, declared.into());
+        req
+    }
+
+    #[tokio::test]
+    async fn gzip_compressed_prompt_is_decoded_and_filtered() {
+        use aegis_core::rule_engine::RuleEngine;
+        use std::io::Write;
+
+        let handler = ProxyHandler::with_filtering_state(FilteringState::with_rule_engine(
+            RuleEngine::content_only(),
+        ));
+        let request = |prompt: &str| {
+            let body = format!(
+                r#"{{"model": "gpt-4", "messages": [{{"role": "user", "content": "{}"}}]}}"#,
+                prompt
+            );
+            let mut gzip =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            gzip.write_all(body.as_bytes()).unwrap();
+            Request::builder()
+                .method("POST")
+                .uri("https://api.openai.com/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .header("Content-Encoding", "gzip")
+                .body(bytes_to_body(Bytes::from(gzip.finish().unwrap())))
+                .unwrap()
+        };
+
+        let outcome = handler
+            .handle_llm_request(
+                "api.openai.com",
+                request("how to make a bomb to kill people"),
+            )
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+
+        // Allowed prompts are forwarded decoded
+        let RequestOrResponse::Request(forwarded) = handler
+            .handle_llm_request("api.openai.com", request("what is the capital of France"))
+            .await
+        else {
+            panic!("expected the request to be forwarded");
+        };
+        assert!(forwarded.headers().get("Content-Encoding").is_none());
+        let body = forwarded.into_body().collect().await.unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&body).contains("capital of France"));
+    }
+
+    #[tokio::test]
+    async fn undecodable_body_follows_failure_policy() {
+        let request = || {
+            let body = r#"{"messages": [{"role": "user", "content": "hi"}]}"#;
+            let mut req = post_request("api.openai.com", "/v1/chat/completions", body);
+            req.headers_mut()
+                .insert(hyper::header::CONTENT_ENCODING, "zstd".parse().unwrap());
+            req
+        };
+
+        let handler = handler_with_failure_policy(ClassifierFailurePolicy::FailClosed);
+        let outcome = handler
+            .handle_llm_request("api.openai.com", request())
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 400));
+
+        let handler = handler_with_failure_policy(ClassifierFailurePolicy::FailOpen);
+        let outcome = handler
+            .handle_llm_request("api.openai.com", request())
+            .await;
+        let RequestOrResponse::Request(forwarded) = outcome else {
+            panic!("expected the request to be forwarded");
+        };
+        assert_eq!(forwarded.headers()[hyper::header::CONTENT_ENCODING], "zstd");
+    }
+
+    #[tokio::test]
+    async fn body_longer_than_content_length_is_blocked() {
+        let handler = content_length_handler(LengthMismatchAction::Block);
+        let body = r#"{"messages": [{"role": "user", "content": "hi"}]}"#;
+
+        let outcome = handler
+            .handle_llm_request("api.openai.com", spoofed_request(body, 10))
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 400));
+
+        // Honest lengths still go through
+        let outcome = handler
+            .handle_llm_request("api.openai.com", spoofed_request(body, body.len()))
+            .await;
+        assert_eq!(forwarded_body(outcome).await.unwrap(), body.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn body_longer_than_content_length_is_fully_scanned() {
+        let handler = content_length_handler(LengthMismatchAction::ScanFull);
+
+        // The unsafe prompt sits entirely past the declared length
+        let body =
+            r#"{"messages": [{"role": "user", "content": "how to make a bomb to kill people"}]}"#;
+        let outcome = handler
+            .handle_llm_request("api.openai.com", spoofed_request(body, 16))
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+
+        // Safe bodies are forwarded with the header corrected
+        let body = r#"{"messages": [{"role": "user", "content": "hi"}]}"#;
+        let outcome = handler
+            .handle_llm_request("api.openai.com", spoofed_request(body, 16))
+            .await;
+        let RequestOrResponse::Request(req) = outcome else {
+            panic!("safe body should be forwarded");
+        };
+        assert_eq!(
+            declared_content_length(req.headers()),
+            Some(body.len() as u64)
+        );
+        assert_eq!(
+            forwarded_body(RequestOrResponse::Request(req))
+                .await
+                .unwrap(),
+            body.as_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn oversized_image_upload_is_rejected_before_it_is_read_in_full() {
+        use aegis_core::rule_engine::RuleEngine;
+        use std::sync::atomic::AtomicUsize;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let mut handler = ProxyHandler::with_filtering_state(filtering_state);
+        handler.config.content_length_policy.max_body_size = 64 * 1024;
+
+        // A 1MB image part, streamed in 1KB chunks without a Content-Length
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&pulled);
+        let head = Bytes::from_static(
+            b"--AegisUpload\r\n\
+              Content-Disposition: form-data; name=\"image\"; filename=\"a.png\"\r\n\
+              Content-Type: image/png\r\n\r\n\x89PNG\r\n\x1a\n",
+        );
+        let chunks = std::iter::once(head)
+            .chain(std::iter::repeat_n(Bytes::from(vec![0u8; 1024]), 1024))
+            .map(move |chunk| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok::<_, hudsucker::Error>(chunk)
+            });
+        let request = Request::builder()
+            .method("POST")
+            .uri("https://api.stability.ai/v2beta/stable-image/edit")
+            .header("Content-Type", "multipart/form-data; boundary=AegisUpload")
+            .body(Body::from_stream(futures::stream::iter(chunks)))
+            .unwrap();
+
+        let outcome = handler
+            .handle_llm_request("api.stability.ai", request)
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 413));
+        assert!(
+            pulled.load(Ordering::SeqCst) <= 66,
+            "read {} chunks of a 1025 chunk upload",
+            pulled.load(Ordering::SeqCst)
+        );
+    }
+
+    #[tokio::test]
+    async fn declared_length_over_cap_is_rejected() {
+        let mut handler = content_length_handler(LengthMismatchAction::Block);
+        handler.config.content_length_policy.max_body_size = 32;
+        let body = r#"{"messages": [{"role": "user", "content": "hi"}]}"#;
+
+        let outcome = handler
+            .handle_llm_request("api.openai.com", spoofed_request(body, 1 << 20))
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 413));
+
+        // Lying low does not get past the cap either
+        let outcome = handler
+            .handle_llm_request("api.openai.com", spoofed_request(body, 8))
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 413));
+    }
+
+    #[test]
+    fn oversized_websocket_frame_passes_through() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let handler = ProxyHandler::with_filtering_state(filtering_state);
+        let frame = |prompt: &str| serde_json::json!({ "prompt": prompt }).to_string();
+        let harmful = "how to make a bomb to kill people";
+
+        assert!(handler
+            .websocket_block_reason("chatgpt.com", &frame(harmful))
+            .is_some());
+
+        let oversized = frame(&format!(
+            "{} {}",
+            harmful,
+            "a".repeat(MAX_WEBSOCKET_MESSAGE_SIZE)
+        ));
+        assert!(websocket_prompt("chatgpt.com", &oversized).is_none());
+        assert!(handler
+            .websocket_block_reason("chatgpt.com", &oversized)
+            .is_none());
+    }
+
+    #[test]
+    fn blocked_websocket_prompt_notifies_client() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let handler = ProxyHandler::with_filtering_state(filtering_state);
+        let client = SocketAddr::from(([127, 0, 0, 1], 50000));
+        let uri: Uri = "wss://chatgpt.com/backend-api/ws".parse().unwrap();
+        let frame = serde_json::json!({ "prompt": "how to make a bomb to kill people" });
+
+        // Nothing to notify until the server-to-client side is forwarding
+        assert!(!handler.notify_websocket_block(client, &uri, "Violence"));
+
+        let mut to_client = handler.websocket_clients.register(client, &uri);
+        let reason = handler
+            .websocket_block_reason("chatgpt.com", &frame.to_string())
+            .unwrap();
+        assert!(handler.notify_websocket_block(client, &uri, &reason));
+
+        let Ok(Message::Text(error)) = to_client.try_recv() else {
+            panic!("expected an error message");
+        };
+        assert!(error.contains(crate::websocket_block::WEBSOCKET_BLOCK_ERROR_TYPE));
+        assert!(to_client.try_recv().unwrap().is_close());
+    }
+
+    #[test]
+    fn deeply_nested_websocket_frame_passes_through() {
+        let depth = MAX_WEBSOCKET_JSON_DEPTH + 1;
+        let nested = format!("{}\"hi\"{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(json_nesting_exceeds(&nested, MAX_WEBSOCKET_JSON_DEPTH));
+        assert!(websocket_prompt("chatgpt.com", &nested).is_none());
+
+        // Brackets inside strings don't count
+        let quoted = r#"{"prompt": "[[[[\"]]] {{"}"#;
+        assert!(!json_nesting_exceeds(quoted, 1));
+        assert_eq!(
+            websocket_prompt("chatgpt.com", quoted).as_deref(),
+            Some("[[[[\"]]] {{")
+        );
+    }
+
+    #[tokio::test]
+    async fn repeated_warnings_escalate_to_block() {
+        use aegis_core::content_rules::ContentAction;
+        use aegis_core::rule_engine::RuleEngine;
+
+        let mut engine = RuleEngine::content_only();
+        engine
+            .content_rules
+            .set_rule_action("violence_block", ContentAction::Warn);
+        let mut handler =
+            ProxyHandler::with_filtering_state(FilteringState::with_rule_engine(engine));
+        handler.config.warn_escalation = Some(WarnEscalation::new().with_warnings_before_block(2));
+
+        let request = || {
+            Request::builder()
+                .method("POST")
+                .uri("https://api.openai.com/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(bytes_to_body(Bytes::from(
+                    r#"{"messages": [{"role": "user", "content": "how to make a bomb to kill people"}]}"#,
+                )))
+                .unwrap()
+        };
+
+        for _ in 0..2 {
+            match handler.process_request(request()).await {
+                RequestOrResponse::Request(req) => {
+                    assert_eq!(req.headers()["X-Aegis-Warning"], "true")
+                }
+                RequestOrResponse::Response(_) => panic!("expected a warning"),
+            }
+        }
+        let outcome = handler.process_request(request()).await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+    }
+
+    #[tokio::test]
+    async fn warning_page_lets_confirmed_prompt_through() {
+        use crate::interstitial::CONTINUE_COOKIE;
+        use aegis_core::content_rules::ContentAction;
+        use aegis_core::rule_engine::RuleEngine;
+
+        let mut engine = RuleEngine::content_only();
+        engine
+            .content_rules
+            .set_rule_action("violence_block", ContentAction::Warn);
+        let mut handler =
+            ProxyHandler::with_filtering_state(FilteringState::with_rule_engine(engine));
+        let interstitial = WarnInterstitial::new();
+        handler.config.warn_interstitial = Some(interstitial.clone());
+        // Confirmed prompts aren't counted towards escalation twice
+        handler.config.warn_escalation = Some(WarnEscalation::new().with_warnings_before_block(1));
+
+        let request = |cookie: Option<String>| {
+            let mut builder = Request::builder()
+                .method("POST")
+                .uri("https://api.openai.com/v1/chat/completions")
+                .header("Content-Type", "application/json");
+            if let Some(cookie) = cookie {
+                builder = builder.header(hyper::header::COOKIE, cookie);
+            }
+            builder
+                .body(bytes_to_body(Bytes::from(
+                    r#"{"messages": [{"role": "user", "content": "how to make a bomb to kill people"}]}"#,
+                )))
+                .unwrap()
+        };
+
+        let response = match handler.process_request(request(None)).await {
+            RequestOrResponse::Response(response) => response,
+            RequestOrResponse::Request(_) => panic!("expected a warning page"),
+        };
+        assert_eq!(response.status(), hyper::StatusCode::PRECONDITION_REQUIRED);
+        assert_eq!(response.headers()["X-Aegis-Warning"], "true");
+        let page = response.into_body().collect().await.unwrap().to_bytes();
+        let page = String::from_utf8_lossy(&page);
+        let marker = format!("{}=", CONTINUE_COOKIE);
+        let start = page.find(&marker).unwrap() + marker.len();
+        let token = &page[start..start + page[start..].find(';').unwrap()];
+
+        let cookie = format!("{}={}", CONTINUE_COOKIE, token);
+        match handler.process_request(request(Some(cookie.clone()))).await {
+            RequestOrResponse::Request(req) => {
+                assert_eq!(req.headers()["X-Aegis-Warning"], "true")
+            }
+            RequestOrResponse::Response(_) => panic!("expected the prompt to continue"),
+        }
+
+        // The token is used up; the next attempt escalates
+        let outcome = handler.process_request(request(Some(cookie))).await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+    }
+
+    #[tokio::test]
+    async fn cooldown_page_delays_warned_prompt() {
+        use aegis_core::content_rules::ContentAction;
+        use aegis_core::rule_engine::RuleEngine;
+
+        let mut engine = RuleEngine::content_only();
+        engine
+            .content_rules
+            .set_rule_action("violence_block", ContentAction::Warn);
+        let mut handler =
+            ProxyHandler::with_filtering_state(FilteringState::with_rule_engine(engine));
+        handler.config.warn_interstitial =
+            Some(WarnInterstitial::new().with_cooldown(std::time::Duration::from_secs(15)));
+
+        let body =
+            r#"{"messages": [{"role": "user", "content": "how to make a bomb to kill people"}]}"#;
+        let request = Request::builder()
+            .method("POST")
+            .uri("https://api.openai.com/v1/chat/completions")
+            .header("Content-Type", "application/json")
+            .body(bytes_to_body(Bytes::from(body)))
+            .unwrap();
+
+        let response = match handler.process_request(request).await {
+            RequestOrResponse::Response(response) => response,
+            RequestOrResponse::Request(_) => panic!("expected a cooldown page"),
+        };
+        assert_eq!(response.status(), hyper::StatusCode::PRECONDITION_REQUIRED);
+        assert_eq!(response.headers()["X-Aegis-Cooldown"], "15");
+        let page = response.into_body().collect().await.unwrap().to_bytes();
+        let page = String::from_utf8_lossy(&page);
+        assert!(page.contains("var remaining = 15;"));
+        assert!(page.contains(r#"fetch("/v1/chat/completions""#));
+        assert!(page.contains(&serde_json::Value::from(body).to_string()));
+    }
+
+    #[test]
+    fn cooldown_falls_back_to_warning_page_for_binary_bodies() {
+        let handler = ProxyHandler::with_defaults();
+        let interstitial =
+            WarnInterstitial::new().with_cooldown(std::time::Duration::from_secs(15));
+        let (parts, _) = Request::builder()
+            .method("POST")
+            .uri("https://api.openai.com/v1/chat/completions")
+            .header("Content-Type", "application/octet-stream")
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        let response = handler.create_warning_response(
+            &interstitial,
+            "Sensitive content",
+            "ChatGPT",
+            "token",
+            &parts,
+            &[0xff, 0xfe, b'h', b'i'],
+        );
+        assert_eq!(response.status(), hyper::StatusCode::PRECONDITION_REQUIRED);
+        assert!(response.headers().get("X-Aegis-Cooldown").is_none());
+
+        let response = handler.create_warning_response(
+            &interstitial,
+            "Sensitive content",
+            "ChatGPT",
+            "token",
+            &parts,
+            b"hi",
+        );
+        assert_eq!(response.headers()["X-Aegis-Cooldown"], "15");
+    }
+
+    #[tokio::test]
+    async fn decision_headers_are_added_when_enabled() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let mut engine = RuleEngine::content_only();
+        engine
+            .content_rules
+            .set_rule_enabled("violence_block", false);
+        let mut handler =
+            ProxyHandler::with_filtering_state(FilteringState::with_rule_engine(engine));
+        handler
+            .config
+            .filtering_state
+            .set_profile(Some("Kid".to_string()));
+
+        let request = || {
+            Request::builder()
+                .method("POST")
+                .uri("https://api.openai.com/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(bytes_to_body(Bytes::from(
+                    r#"{"messages": [{"role": "user", "content": "how to make a bomb to kill people"}]}"#,
+                )))
+                .unwrap()
+        };
+        let forwarded = |outcome| match outcome {
+            RequestOrResponse::Request(req) => req,
+            RequestOrResponse::Response(_) => panic!("expected the request to be allowed"),
+        };
+
+        // Off by default
+        let req = forwarded(handler.process_request(request()).await);
+        assert!(req.headers().get("X-Aegis-Action").is_none());
+
+        handler.config.decision_headers = true;
+        let req = forwarded(handler.process_request(request()).await);
+        let headers = req.headers();
+        assert_eq!(headers["X-Aegis-Action"], "allow");
+        assert_eq!(headers["X-Aegis-Category"], "violence");
+        assert!(headers["X-Aegis-Confidence"]
+            .to_str()
+            .unwrap()
+            .parse::<f32>()
+            .is_ok());
+        assert_eq!(headers["X-Aegis-Profile"], "Kid");
+    }
+
+    fn hostless_request(body: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/v1/chat/completions")
+            .version(hyper::Version::HTTP_10)
+            .header("Content-Type", "application/json")
+            .body(bytes_to_body(Bytes::from(body.to_string())))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn hostless_request_follows_missing_host_policy() {
+        let mut handler = ProxyHandler::with_defaults();
+        let body = r#"{"messages": [{"role": "user", "content": "hello"}]}"#;
+
+        let outcome = handler.process_request(hostless_request(body)).await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 400));
+
+        handler.config.missing_host_policy = MissingHostPolicy::Forward;
+        let outcome = handler.process_request(hostless_request(body)).await;
+        assert_eq!(forwarded_body(outcome).await.unwrap(), body.as_bytes());
+    }
+
+    #[tokio::test]
+    async fn hostless_request_uses_tunnel_host() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let handler = ProxyHandler::with_filtering_state(filtering_state);
+        let client = SocketAddr::from(([127, 0, 0, 1], 50000));
+        let body =
+            r#"{"messages": [{"role": "user", "content": "how to make a bomb to kill people"}]}"#;
+
+        let connect = Request::builder()
+            .method("CONNECT")
+            .uri("api.openai.com:443")
+            .body(Body::empty())
+            .unwrap();
+        handler.process_request_from(Some(client), connect).await;
+
+        // Filtered as the tunnelled LLM host rather than forwarded
+        let outcome = handler
+            .process_request_from(Some(client), hostless_request(body))
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+
+        // A different connection has no tunnel host to fall back on
+        let other = SocketAddr::from(([127, 0, 0, 1], 50001));
+        let outcome = handler
+            .process_request_from(Some(other), hostless_request(body))
+            .await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 400));
+    }
+
     #[test]
     fn filtering_state_default_enabled() {
         let state = FilteringState::new();
@@ -1323,6 +3604,206 @@ mod tests {
         assert!(state.is_image_filtering_enabled());
     }
 
+    // ==================== Profile Filter Toggle Tests ====================
+
+    fn stored_profile(
+        text_filtering_enabled: bool,
+        sentiment_enabled: bool,
+        image_filtering_enabled: bool,
+    ) -> Profile {
+        let db = Database::in_memory().unwrap();
+        let id = db
+            .create_profile(aegis_storage::NewProfile {
+                name: "Harper".to_string(),
+                os_username: None,
+                time_rules: serde_json::json!({"rules": []}),
+                content_rules: serde_json::json!({"rules": []}),
+                enabled: true,
+                sentiment_config: ProfileSentimentConfig {
+                    enabled: sentiment_enabled,
+                    ..Default::default()
+                },
+                image_filtering_config: ProfileImageFilteringConfig {
+                    enabled: image_filtering_enabled,
+                    nsfw_threshold: StoredNsfwThresholdPreset::Child,
+                },
+                proxy_mode: aegis_core::profile::ProxyMode::Enabled,
+                text_filtering_enabled,
+            })
+            .unwrap();
+        db.get_profile(id).unwrap().unwrap()
+    }
+
+    #[test]
+    fn apply_profile_sets_toggles_independently() {
+        let state = FilteringState::new();
+        let profile = stored_profile(true, true, false);
+
+        state.apply_profile(&profile);
+
+        assert_eq!(state.profile_id(), Some(profile.id));
+        assert_eq!(state.profile_name().as_deref(), Some("Harper"));
+        assert!(state.is_text_filtering_enabled());
+        assert!(state.is_sentiment_enabled());
+        assert!(!state.is_image_filtering_enabled());
+        assert_eq!(state.nsfw_threshold(), NsfwThresholdPreset::Child);
+    }
+
+    #[test]
+    fn apply_profile_filters_replaces_previous_toggles() {
+        let state = FilteringState::new();
+        state.apply_profile(&stored_profile(true, true, false));
+
+        state.apply_profile_filters(&stored_profile(false, false, true));
+
+        assert!(!state.is_text_filtering_enabled());
+        assert!(!state.is_sentiment_enabled());
+        assert!(state.is_image_filtering_enabled());
+    }
+
+    #[test]
+    fn switch_to_profile_applies_its_rules() {
+        let db = Database::in_memory().unwrap();
+        let content_rules = ContentRuleSet::family_safe_defaults();
+        let rule_count = content_rules.rules.len() - 1;
+        let mut trimmed = content_rules.clone();
+        trimmed.rules.truncate(rule_count);
+        db.create_profile(aegis_storage::NewProfile {
+            name: "Alice".to_string(),
+            os_username: None,
+            time_rules: serde_json::json!({"rules": []}),
+            content_rules: serde_json::to_value(&trimmed).unwrap(),
+            enabled: true,
+            sentiment_config: ProfileSentimentConfig::default(),
+            image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: aegis_core::profile::ProxyMode::Enabled,
+            text_filtering_enabled: true,
+        })
+        .unwrap();
+        let state = FilteringState::new();
+
+        assert!(state.switch_to_profile(&db, "Alice").unwrap());
+        assert_eq!(state.profile_name().as_deref(), Some("Alice"));
+        assert_eq!(
+            state.rule_engine().read().content_rules.rules.len(),
+            rule_count
+        );
+
+        assert!(!state.switch_to_profile(&db, "Nobody").unwrap());
+        assert_eq!(state.profile_name().as_deref(), Some("Alice"));
+    }
+
+    #[tokio::test]
+    async fn block_all_blocks_benign_prompts_until_cleared() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let handler = ProxyHandler::with_filtering_state(filtering_state.clone());
+        let body =
+            r#"{"messages": [{"role": "user", "content": "What is the capital of France?"}]}"#;
+        let send = || {
+            handler.handle_llm_request(
+                "api.openai.com",
+                post_request("api.openai.com", "/v1/chat/completions", body),
+            )
+        };
+
+        filtering_state.block_all();
+        assert!(filtering_state.is_blocking_all());
+        let outcome = send().await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+
+        // Pausing protection does not lift the override
+        filtering_state.disable();
+        let outcome = send().await;
+        assert!(matches!(outcome, RequestOrResponse::Response(ref r) if r.status() == 403));
+        filtering_state.enable();
+
+        filtering_state.clear_block_all();
+        assert!(!filtering_state.is_blocking_all());
+        assert_eq!(forwarded_body(send().await).await.unwrap(), body.as_bytes());
+    }
+
+    #[test]
+    fn allow_logging_fills_missing_fields_with_defaults() {
+        let parsed: AllowLogging =
+            serde_json::from_value(serde_json::json!({ "log_level": "info" })).unwrap();
+        assert_eq!(
+            parsed,
+            AllowLogging {
+                log_level: AllowLogLevel::Info,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn allowed_events_follow_allow_logging() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let body =
+            r#"{"messages": [{"role": "user", "content": "What is the capital of France?"}]}"#;
+        let send_allowed = |allow_logging: AllowLogging| async move {
+            let db = Arc::new(Database::in_memory().unwrap());
+            let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+            let mut handler = ProxyHandler::with_filtering_state(filtering_state);
+            handler.config.database = Some(db.clone());
+            handler.config.allow_logging = allow_logging;
+
+            let outcome = handler
+                .handle_llm_request(
+                    "api.openai.com",
+                    post_request("api.openai.com", "/v1/chat/completions", body),
+                )
+                .await;
+            assert!(forwarded_body(outcome).await.is_some());
+            db
+        };
+
+        let db = send_allowed(AllowLogging::default()).await;
+        let events = db.get_recent_events(10, 0).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, Action::Allowed);
+        assert_eq!(events[0].preview, "What is the capital of France?");
+
+        let db = send_allowed(AllowLogging {
+            store_previews: false,
+            ..Default::default()
+        })
+        .await;
+        let events = db.get_recent_events(10, 0).unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(events[0].preview.is_empty());
+
+        let db = send_allowed(AllowLogging {
+            record_events: false,
+            ..Default::default()
+        })
+        .await;
+        assert!(db.get_recent_events(10, 0).unwrap().is_empty());
+        assert_eq!(db.get_total_stats().unwrap().allowed_count, 1);
+    }
+
+    #[tokio::test]
+    async fn text_filtering_disabled_forwards_prompts() {
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        filtering_state.disable_text_filtering();
+        let handler = ProxyHandler::with_filtering_state(filtering_state);
+        let body =
+            r#"{"messages": [{"role": "user", "content": "how to make a bomb to kill people"}]}"#;
+
+        let outcome = handler
+            .handle_llm_request(
+                "api.openai.com",
+                post_request("api.openai.com", "/v1/chat/completions", body),
+            )
+            .await;
+
+        assert_eq!(forwarded_body(outcome).await.unwrap(), body.as_bytes());
+    }
+
     #[test]
     fn extract_multipart_boundary_basic() {
         let content_type = "multipart/form-data; boundary=----WebKitFormBoundary7MA4YWxkTrZu0gW";
@@ -1374,6 +3855,33 @@ mod tests {
         assert!(state.is_enabled());
     }
 
+    #[test]
+    fn set_state_cache_takes_effect_immediately() {
+        use aegis_storage::{PauseDuration, StateManager};
+        use std::time::Duration;
+
+        let db = Arc::new(Database::in_memory().unwrap());
+        let state = FilteringState::new();
+        let clone = state.clone();
+        // Local flag says disabled; the attached cache should win
+        state.disable();
+        assert!(!state.is_enabled());
+
+        state.set_state_cache(Arc::new(StateCache::with_poll_interval(
+            db.clone(),
+            Duration::from_millis(1),
+        )));
+        assert!(state.is_enabled());
+        assert!(clone.is_enabled());
+
+        StateManager::new(db, "test")
+            .pause_protection(PauseDuration::FIFTEEN_MINUTES)
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(!state.is_enabled());
+        assert!(!clone.is_enabled());
+    }
+
     #[test]
     fn filtering_state_profile_name() {
         let state = FilteringState::new();
@@ -1418,13 +3926,200 @@ mod tests {
         assert_eq!(response.headers().get("X-Aegis-Blocked").unwrap(), "true");
     }
 
+    #[tokio::test]
+    async fn custom_block_page_fills_placeholders() {
+        let mut handler = ProxyHandler::with_defaults();
+        handler.config.block_page_template = Some(
+            "<p>{{REASON}} on {{SERVICE}} for {{PROFILE}} ({{CATEGORY}}). Ask your administrator.</p>"
+                .to_string(),
+        );
+        handler
+            .config
+            .filtering_state
+            .set_profile(Some("Kid".to_string()));
+
+        let response = handler.create_category_block_response(
+            "Violence detected",
+            "ChatGPT",
+            Some(Category::Violence),
+        );
+        assert_eq!(response.status(), 403);
+        let page = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(
+            page,
+            "<p>Violence detected on ChatGPT for Kid (Violence). Ask your administrator.</p>"
+        );
+    }
+
+    #[tokio::test]
+    async fn custom_block_page_escapes_substituted_values() {
+        let mut handler = ProxyHandler::with_defaults();
+        handler.config.block_page_template =
+            Some("<p title=\"{{PROFILE}}\">{{REASON}} on {{SERVICE}}</p>".to_string());
+        handler
+            .config
+            .filtering_state
+            .set_profile(Some("\"><script>alert(1)</script>".to_string()));
+
+        let response =
+            handler.create_category_block_response("Tom & Jerry's <b>", "Chat<GPT>", None);
+        let page = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(
+            page,
+            "<p title=\"&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;\">\
+             Tom &amp; Jerry&#39;s &lt;b&gt; on Chat&lt;GPT&gt;</p>"
+        );
+    }
+
+    #[test]
+    fn block_page_template_falls_back_when_invalid() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let valid = dir.path().join("valid.html");
+        std::fs::write(&valid, "{{REASON}} {{SERVICE}} {{PROFILE}}").unwrap();
+        let incomplete = dir.path().join("incomplete.html");
+        std::fs::write(&incomplete, "Blocked: {{REASON}}").unwrap();
+
+        assert_eq!(
+            load_block_page_template(&valid).as_deref(),
+            Some("{{REASON}} {{SERVICE}} {{PROFILE}}")
+        );
+        assert_eq!(load_block_page_template(&incomplete), None);
+        assert_eq!(
+            load_block_page_template(&dir.path().join("missing.html")),
+            None
+        );
+    }
+
     #[test]
     fn classify_prompt_works() {
         let handler = ProxyHandler::with_defaults();
-        let result = handler.classify_prompt("Hello, how are you?");
+        let result = handler.try_classify_prompt("Hello, how are you?").unwrap();
         assert!(result.matches.is_empty()); // Safe content
     }
 
+    // ==================== Classifier Failure Policy Tests ====================
+
+    fn handler_with_failure_policy(policy: ClassifierFailurePolicy) -> ProxyHandler {
+        use aegis_core::rule_engine::RuleEngine;
+
+        // Without time rules so bedtime doesn't block during tests
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let mut handler = ProxyHandler::with_filtering_state(filtering_state);
+        handler.config.classifier_failure_policy = policy;
+        handler
+    }
+
+    fn failing_classifier() -> Result<ClassificationResult, ClassifierFailure> {
+        Err(ClassifierFailure {
+            reason: "model session poisoned".to_string(),
+            partial: Some(ClassificationResult::safe(3)),
+        })
+    }
+
+    #[test]
+    fn classify_guarded_catches_panic() {
+        let outcome = ProxyHandler::classify_guarded(|| panic!("onnx runtime exploded"));
+
+        let failure = outcome.unwrap_err();
+        assert!(failure.reason.contains("onnx runtime exploded"));
+        assert!(failure.partial.is_none());
+    }
+
+    #[test]
+    fn fail_open_uses_partial_result() {
+        let handler = handler_with_failure_policy(ClassifierFailurePolicy::FailOpen);
+
+        let classification = handler
+            .resolve_classification(failing_classifier(), "ChatGPT")
+            .expect("fail-open should continue");
+        assert_eq!(classification.duration_us, 3);
+
+        let outcome = ProxyHandler::classify_guarded(|| panic!("boom"));
+        let classification = handler
+            .resolve_classification(outcome, "ChatGPT")
+            .expect("fail-open should continue after panic");
+        assert!(handler
+            .evaluate_rules("", None, &classification)
+            .should_allow());
+    }
+
+    #[test]
+    fn fail_closed_blocks() {
+        let handler = handler_with_failure_policy(ClassifierFailurePolicy::FailClosed);
+
+        assert!(handler
+            .resolve_classification(failing_classifier(), "ChatGPT")
+            .is_none());
+        let outcome = ProxyHandler::classify_guarded(|| panic!("boom"));
+        assert!(handler.resolve_classification(outcome, "ChatGPT").is_none());
+    }
+
+    #[tokio::test]
+    async fn classifier_panic_does_not_kill_task() {
+        let handler = handler_with_failure_policy(ClassifierFailurePolicy::FailClosed);
+
+        let task = tokio::spawn(async move {
+            let outcome = ProxyHandler::classify_guarded(|| panic!("boom"));
+            handler.resolve_classification(outcome, "ChatGPT").is_some()
+        });
+
+        assert!(!task.await.expect("task should survive classifier panic"));
+    }
+
+    // ==================== Startup Guard Tests ====================
+
+    #[test]
+    fn startup_guard_conservative_until_model_ready() {
+        use aegis_core::classifier::CategoryMatch;
+        use aegis_core::rule_engine::RuleEngine;
+
+        let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
+        let mut handler = ProxyHandler::with_filtering_state(filtering_state);
+        let guard = StartupGuard::new();
+        handler.config.startup_guard = Some(guard.clone());
+
+        // Below the normal jailbreak threshold (0.8), so normally allowed
+        let classification = ClassificationResult::with_matches(
+            vec![CategoryMatch::new(Category::Jailbreak, 0.6, None)],
+            0,
+        );
+
+        let result = handler.evaluate_rules("", None, &classification);
+        assert!(result.should_block());
+        assert_eq!(result.source.rule_id(), Some(crate::STARTUP_RULE_ID));
+
+        guard.mark_ready();
+        assert!(handler
+            .evaluate_rules("", None, &classification)
+            .should_allow());
+    }
+
+    #[test]
+    fn allowlisted_prompt_bypasses_content_rules_and_startup_guard() {
+        use aegis_core::classifier::CategoryMatch;
+        use aegis_core::content_rules::AllowlistEntry;
+        use aegis_core::rule_engine::RuleEngine;
+
+        let mut engine = RuleEngine::content_only();
+        engine.add_allowlist_entry(AllowlistEntry::substring("chemistry class"));
+        let mut handler =
+            ProxyHandler::with_filtering_state(FilteringState::with_rule_engine(engine));
+        handler.config.startup_guard = Some(StartupGuard::new());
+
+        let violent = ClassificationResult::with_matches(
+            vec![CategoryMatch::new(Category::Violence, 0.95, None)],
+            0,
+        );
+        let result =
+            handler.evaluate_rules("How do explosives work in chemistry class?", None, &violent);
+        assert!(result.should_allow());
+        assert!(result.source.is_allowlisted());
+
+        assert!(handler
+            .evaluate_rules("How do explosives work?", None, &violent)
+            .should_block());
+    }
+
     #[test]
     fn evaluate_rules_allows_safe() {
         use aegis_core::rule_engine::RuleEngine;
@@ -1433,13 +4128,15 @@ mod tests {
         let filtering_state = FilteringState::with_rule_engine(RuleEngine::content_only());
         let handler = ProxyHandler::with_filtering_state(filtering_state);
 
-        let classification = handler.classify_prompt("What is the weather today?");
+        let classification = handler
+            .try_classify_prompt("What is the weather today?")
+            .unwrap();
         assert!(
             classification.matches.is_empty(),
             "Safe prompt should not match any rules, but matched: {:?}",
             classification.matches
         );
-        let result = handler.evaluate_rules(&classification);
+        let result = handler.evaluate_rules("What is the weather today?", None, &classification);
         assert!(
             result.should_allow(),
             "Safe prompt should be allowed, but got action: {:?}",
@@ -1547,6 +4244,8 @@ mod tests {
                 detect_negative: true,
             },
             image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: aegis_core::profile::ProxyMode::Enabled,
+            text_filtering_enabled: true,
         };
         let profile_id = db
             .create_profile(profile)
@@ -1568,6 +4267,7 @@ mod tests {
             threshold: 0.5,
             enabled_flags,
             notify_on_flag: true,
+            ..Default::default()
         };
         filtering_state.enable_sentiment_analysis(sentiment_config);
 
@@ -1582,6 +4282,16 @@ mod tests {
             database: Some(db_arc),
             nsfw_classifier: Arc::new(RwLock::new(LazyNsfwClassifier::with_defaults())),
             site_registry: Arc::new(SiteRegistry::with_defaults()),
+            connection_limiter: None,
+            classifier_failure_policy: ClassifierFailurePolicy::default(),
+            startup_guard: None,
+            content_length_policy: ContentLengthPolicy::default(),
+            missing_host_policy: MissingHostPolicy::default(),
+            allow_logging: AllowLogging::default(),
+            warn_escalation: None,
+            warn_interstitial: None,
+            block_page_template: None,
+            decision_headers: false,
         });
 
         // Simulate analyzing emotional content
@@ -303,6 +303,250 @@ pub fn extract_images_from_multipart(body: &[u8], boundary: &str) -> Vec<(String
     images
 }
 
+/// Default cap on the total size of a streamed multipart upload (50MB).
+pub const DEFAULT_MAX_UPLOAD_SIZE: usize = 50 * 1024 * 1024;
+
+/// Default cap on a single image part in a streamed multipart upload (20MB).
+pub const DEFAULT_MAX_IMAGE_PART_SIZE: usize = 20 * 1024 * 1024;
+
+/// Bytes needed from a part before its image format can be detected.
+const FORMAT_SNIFF_LEN: usize = 12;
+
+/// Maximum size of a single part's header block.
+const MAX_PART_HEADER_SIZE: usize = 16 * 1024;
+
+/// Error raised when a streamed multipart upload exceeds its limits.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum MultipartLimitError {
+    /// The upload as a whole exceeded the total size cap.
+    #[error("upload exceeds {limit} byte limit")]
+    TotalSizeExceeded { limit: usize },
+    /// A single image part exceeded the per-part size cap.
+    #[error("image part '{field_name}' exceeds {limit} byte limit")]
+    PartTooLarge { field_name: String, limit: usize },
+}
+
+/// Parser position within the multipart stream.
+#[derive(Debug)]
+enum MultipartState {
+    /// Before the first boundary.
+    Preamble,
+    /// Just after a boundary, expecting CRLF (next part) or `--` (end).
+    AfterBoundary,
+    /// Reading a part's headers.
+    Headers,
+    /// Reading a part's body.
+    Body,
+    /// Closing boundary seen (or stream abandoned).
+    Done,
+}
+
+/// Incremental multipart/form-data image reader for upload filtering (F033).
+///
+/// Unlike [`extract_images_from_multipart`], this consumes the body chunk by
+/// chunk and yields each image part as soon as its closing boundary arrives.
+/// Only a boundary-sized lookahead window and the current image part are held
+/// in memory; non-image parts are discarded after format sniffing.
+#[derive(Debug)]
+pub struct MultipartImageStream {
+    delimiter: Vec<u8>,
+    state: MultipartState,
+    window: Vec<u8>,
+    total_size: usize,
+    max_total_size: usize,
+    max_part_size: usize,
+    part_index: usize,
+    field_name: Option<String>,
+    part_data: Vec<u8>,
+    part_is_image: Option<bool>,
+}
+
+impl MultipartImageStream {
+    /// Creates a reader for the given boundary with default size limits.
+    pub fn new(boundary: &str) -> Self {
+        Self::with_limits(
+            boundary,
+            DEFAULT_MAX_UPLOAD_SIZE,
+            DEFAULT_MAX_IMAGE_PART_SIZE,
+        )
+    }
+
+    /// Creates a reader with explicit total and per-image size limits.
+    pub fn with_limits(boundary: &str, max_total_size: usize, max_part_size: usize) -> Self {
+        Self {
+            delimiter: format!("\r\n--{}", boundary).into_bytes(),
+            state: MultipartState::Preamble,
+            // Seed with CRLF so the first boundary matches the same delimiter
+            window: b"\r\n".to_vec(),
+            total_size: 0,
+            max_total_size,
+            max_part_size,
+            part_index: 0,
+            field_name: None,
+            part_data: Vec::new(),
+            part_is_image: None,
+        }
+    }
+
+    /// Returns the total number of body bytes fed so far.
+    pub fn total_size(&self) -> usize {
+        self.total_size
+    }
+
+    /// Returns the number of bytes currently held by the reader.
+    pub fn buffered_len(&self) -> usize {
+        self.window.len() + self.part_data.len()
+    }
+
+    /// Returns true once the closing boundary has been seen.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, MultipartState::Done)
+    }
+
+    /// Feeds the next body chunk, returning any image parts it completed.
+    pub fn feed(
+        &mut self,
+        chunk: &[u8],
+    ) -> Result<Vec<(String, ExtractedImage)>, MultipartLimitError> {
+        self.total_size += chunk.len();
+        if self.total_size > self.max_total_size {
+            return Err(MultipartLimitError::TotalSizeExceeded {
+                limit: self.max_total_size,
+            });
+        }
+
+        let mut images = Vec::new();
+        if self.is_done() {
+            return Ok(images);
+        }
+        self.window.extend_from_slice(chunk);
+
+        loop {
+            match self.state {
+                MultipartState::Preamble => match find_subsequence(&self.window, &self.delimiter) {
+                    Some(pos) => {
+                        self.window.drain(..pos + self.delimiter.len());
+                        self.state = MultipartState::AfterBoundary;
+                    }
+                    None => {
+                        self.keep_lookahead();
+                        break;
+                    }
+                },
+                MultipartState::AfterBoundary => {
+                    if self.window.len() < 2 {
+                        break;
+                    }
+                    if self.window.starts_with(b"--") {
+                        self.state = MultipartState::Done;
+                        self.window.clear();
+                        break;
+                    }
+                    self.window.drain(..2);
+                    self.state = MultipartState::Headers;
+                }
+                MultipartState::Headers => match find_subsequence(&self.window, b"\r\n\r\n") {
+                    Some(pos) => {
+                        self.field_name = parse_field_name(&self.window[..pos]);
+                        self.window.drain(..pos + 4);
+                        self.state = MultipartState::Body;
+                    }
+                    None => {
+                        if self.window.len() > MAX_PART_HEADER_SIZE {
+                            tracing::debug!("Multipart part headers too large, abandoning");
+                            self.state = MultipartState::Done;
+                            self.window.clear();
+                        }
+                        break;
+                    }
+                },
+                MultipartState::Body => match find_subsequence(&self.window, &self.delimiter) {
+                    Some(pos) => {
+                        let body: Vec<u8> = self.window.drain(..pos).collect();
+                        self.append_part(&body)?;
+                        self.window.drain(..self.delimiter.len());
+                        if let Some(image) = self.finish_part() {
+                            images.push(image);
+                        }
+                        self.state = MultipartState::AfterBoundary;
+                    }
+                    None => {
+                        let safe = self.window.len().saturating_sub(self.delimiter.len() - 1);
+                        let body: Vec<u8> = self.window.drain(..safe).collect();
+                        self.append_part(&body)?;
+                        break;
+                    }
+                },
+                MultipartState::Done => break,
+            }
+        }
+
+        Ok(images)
+    }
+
+    /// Drops preamble bytes that can no longer start a delimiter.
+    fn keep_lookahead(&mut self) {
+        let keep = self.delimiter.len() - 1;
+        if self.window.len() > keep {
+            self.window.drain(..self.window.len() - keep);
+        }
+    }
+
+    /// Appends body bytes to the current part, discarding non-image parts.
+    fn append_part(&mut self, bytes: &[u8]) -> Result<(), MultipartLimitError> {
+        if self.part_is_image == Some(false) {
+            return Ok(());
+        }
+
+        self.part_data.extend_from_slice(bytes);
+
+        if self.part_is_image.is_none() && self.part_data.len() >= FORMAT_SNIFF_LEN {
+            let is_image = detect_image_format(&self.part_data).is_some();
+            self.part_is_image = Some(is_image);
+            if !is_image {
+                self.part_data = Vec::new();
+            }
+        }
+
+        if self.part_is_image == Some(true) && self.part_data.len() > self.max_part_size {
+            return Err(MultipartLimitError::PartTooLarge {
+                field_name: self.field_name.clone().unwrap_or_default(),
+                limit: self.max_part_size,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Completes the current part, returning it if it was an image.
+    fn finish_part(&mut self) -> Option<(String, ExtractedImage)> {
+        let data = std::mem::take(&mut self.part_data);
+        let field_name = self.field_name.take();
+        self.part_is_image = None;
+        let index = self.part_index;
+        self.part_index += 1;
+
+        let field_name = field_name?;
+        let format = detect_image_format(&data)?;
+        let img = ExtractedImage::new(data, format!("multipart.{}", field_name), index)
+            .with_format(format);
+        Some((field_name, img))
+    }
+}
+
+/// Extracts the `name="..."` field from a part's Content-Disposition header.
+fn parse_field_name(headers: &[u8]) -> Option<String> {
+    let headers = std::str::from_utf8(headers).ok()?;
+    for line in headers.lines() {
+        if line.to_lowercase().starts_with("content-disposition:") {
+            let name_start = line.find("name=\"")? + 6;
+            let name_end = line[name_start..].find('"')?;
+            return Some(line[name_start..name_start + name_end].to_string());
+        }
+    }
+    None
+}
+
 /// Decodes a base64-encoded image string.
 fn decode_base64_image(b64: &str) -> Option<Vec<u8>> {
     use base64::{engine::general_purpose::STANDARD, Engine};
@@ -596,4 +840,100 @@ mod tests {
         let images = extract_images_from_json(json.as_bytes());
         assert!(images.is_empty());
     }
+
+    // ==================== Streaming Multipart Tests ====================
+
+    fn multipart_part(boundary: &str, name: &str, data: &[u8]) -> Vec<u8> {
+        let mut part = format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n",
+            boundary, name
+        )
+        .into_bytes();
+        part.extend_from_slice(data);
+        part.extend_from_slice(b"\r\n");
+        part
+    }
+
+    #[test]
+    fn stream_matches_buffered_extraction() {
+        let boundary = "XyZ123";
+        let mut body = multipart_part(boundary, "prompt", b"a cat on a skateboard");
+        body.extend(multipart_part(boundary, "image", &red_pixel_png()));
+        body.extend(format!("--{}--\r\n", boundary).into_bytes());
+
+        let mut stream = MultipartImageStream::new(boundary);
+        let mut images = Vec::new();
+        // Feed one byte at a time to exercise boundary splits across chunks
+        for byte in &body {
+            images.extend(stream.feed(std::slice::from_ref(byte)).unwrap());
+        }
+
+        let buffered = extract_images_from_multipart(&body, boundary);
+        assert_eq!(images.len(), 1);
+        assert_eq!(buffered.len(), 1);
+        assert_eq!(images[0].0, "image");
+        assert_eq!(images[0].1.data, buffered[0].1.data);
+        assert_eq!(images[0].1.format.as_deref(), Some("image/png"));
+        assert!(stream.is_done());
+    }
+
+    #[test]
+    fn stream_large_upload_stays_bounded() {
+        let boundary = "----AegisBoundary";
+        let filler = vec![b'x'; 8 * 1024 * 1024];
+        let mut image = red_pixel_png();
+        image.resize(2 * 1024 * 1024, 0);
+
+        let mut body = multipart_part(boundary, "notes", &filler);
+        body.extend(multipart_part(boundary, "image", &image));
+        body.extend(multipart_part(boundary, "trailer", &filler));
+        body.extend(format!("--{}--\r\n", boundary).into_bytes());
+
+        let mut stream = MultipartImageStream::new(boundary);
+        let mut peak_buffered = 0;
+        let mut image_seen_at = None;
+
+        for (i, chunk) in body.chunks(64 * 1024).enumerate() {
+            let images = stream.feed(chunk).unwrap();
+            peak_buffered = peak_buffered.max(stream.buffered_len());
+            if !images.is_empty() {
+                assert_eq!(images[0].1.data.len(), image.len());
+                image_seen_at = Some(i * 64 * 1024);
+            }
+        }
+
+        // The image is yielded before the trailing part is read, and the reader
+        // never holds more than the image plus one chunk of lookahead.
+        let seen_at = image_seen_at.expect("image part should be yielded");
+        assert!(seen_at < body.len() - filler.len());
+        assert!(peak_buffered <= image.len() + 64 * 1024);
+        assert!(peak_buffered < body.len() / 4);
+    }
+
+    #[test]
+    fn stream_enforces_total_size_cap() {
+        let boundary = "b";
+        let body = multipart_part(boundary, "image", &vec![b'x'; 4096]);
+
+        let mut stream = MultipartImageStream::with_limits(boundary, 1024, 1024);
+        let result = stream.feed(&body);
+        assert_eq!(
+            result.unwrap_err(),
+            MultipartLimitError::TotalSizeExceeded { limit: 1024 }
+        );
+    }
+
+    #[test]
+    fn stream_enforces_image_part_cap() {
+        let boundary = "b";
+        let mut image = red_pixel_png();
+        image.resize(4096, 0);
+        let body = multipart_part(boundary, "image", &image);
+
+        let mut stream = MultipartImageStream::with_limits(boundary, usize::MAX, 1024);
+        let err = stream.feed(&body).unwrap_err();
+        assert!(
+            matches!(err, MultipartLimitError::PartTooLarge { ref field_name, .. } if field_name == "image")
+        );
+    }
 }
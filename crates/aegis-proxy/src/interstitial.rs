@@ -0,0 +1,445 @@
+//! Warning pages the child can click through.
+//!
+//! A warn rule normally forwards the prompt with an `X-Aegis-Warning` header
+//! the child never sees. With [`WarnInterstitial`], the first attempt is
+//! answered with a warning page instead. Its "Continue anyway" button stores
+//! a short-lived token in a cookie, and resubmitting the same prompt with that
+//! cookie lets it through: a speed bump for borderline content rather than a
+//! hard block.
+//!
+//! In cooldown mode ([`WarnInterstitial::with_cooldown`]) the page shows a
+//! countdown instead of a button, then re-submits the stashed request itself.
+//! The token only works once the countdown is over, so the wait can't be
+//! skipped by resending early.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hyper::header::{HeaderMap, COOKIE};
+use parking_lot::Mutex;
+
+use crate::canary::new_token;
+
+/// Cookie the "Continue anyway" button stores its token in.
+pub const CONTINUE_COOKIE: &str = "aegis_continue";
+
+/// How long a warning page's token stays valid.
+pub const DEFAULT_CONTINUE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Warning page HTML template.
+const WARNING_PAGE_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Warning from Aegis</title>
+    <style>
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+            display: flex;
+            justify-content: center;
+            align-items: center;
+            min-height: 100vh;
+            margin: 0;
+            background: linear-gradient(135deg, #f6a04d 0%, #d9534f 100%);
+            color: white;
+        }
+        .container {
+            text-align: center;
+            padding: 2rem;
+            max-width: 500px;
+        }
+        .shield {
+            font-size: 4rem;
+            margin-bottom: 1rem;
+        }
+        h1 {
+            margin: 0 0 1rem 0;
+            font-size: 2rem;
+        }
+        p {
+            margin: 0.5rem 0;
+            opacity: 0.9;
+        }
+        .reason {
+            background: rgba(255,255,255,0.2);
+            padding: 1rem;
+            border-radius: 8px;
+            margin-top: 1rem;
+        }
+        button {
+            margin-top: 1.5rem;
+            padding: 0.75rem 1.5rem;
+            font-size: 1rem;
+            border: none;
+            border-radius: 8px;
+            background: white;
+            color: #d9534f;
+            cursor: pointer;
+        }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="shield">⚠️</div>
+        <h1>Are you sure?</h1>
+        <p>Aegis thinks this message may not be appropriate.</p>
+        <div class="reason">
+            <p><strong>Reason:</strong> {{REASON}}</p>
+            <p><strong>Service:</strong> {{SERVICE}}</p>
+        </div>
+        <button id="continue">Continue anyway</button>
+        <p id="sent" hidden>Go back and send your message again.</p>
+    </div>
+    <script>
+        document.getElementById("continue").onclick = function () {
+            document.cookie = "{{COOKIE}}={{TOKEN}}; max-age={{MAX_AGE}}; path=/; secure; samesite=lax";
+            this.hidden = true;
+            document.getElementById("sent").hidden = false;
+        };
+    </script>
+</body>
+</html>"#;
+
+/// Cooldown page HTML template.
+const COOLDOWN_PAGE_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Take a breath - Aegis</title>
+    <style>
+        body {
+            font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+            display: flex;
+            justify-content: center;
+            align-items: center;
+            min-height: 100vh;
+            margin: 0;
+            background: linear-gradient(135deg, #5bc0de 0%, #337ab7 100%);
+            color: white;
+        }
+        .container {
+            text-align: center;
+            padding: 2rem;
+            max-width: 500px;
+        }
+        .countdown {
+            font-size: 4rem;
+            margin: 1rem 0;
+        }
+        h1 {
+            margin: 0 0 1rem 0;
+            font-size: 2rem;
+        }
+        p {
+            margin: 0.5rem 0;
+            opacity: 0.9;
+        }
+        .reason {
+            background: rgba(255,255,255,0.2);
+            padding: 1rem;
+            border-radius: 8px;
+            margin-top: 1rem;
+        }
+        pre {
+            text-align: left;
+            white-space: pre-wrap;
+        }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>Take a breath</h1>
+        <p>Aegis thinks this message may not be appropriate. It will be sent when the countdown ends.</p>
+        <div class="countdown" id="countdown">{{DELAY}}</div>
+        <div class="reason">
+            <p><strong>Reason:</strong> {{REASON}}</p>
+            <p><strong>Service:</strong> {{SERVICE}}</p>
+        </div>
+        <p id="sent" hidden>Your message was sent. Go back to see the reply.</p>
+        <pre id="reply" hidden></pre>
+    </div>
+    <script>
+        var remaining = {{DELAY}};
+        var timer = setInterval(function () {
+            remaining -= 1;
+            document.getElementById("countdown").textContent = Math.max(remaining, 0);
+            if (remaining > 0) {
+                return;
+            }
+            clearInterval(timer);
+            document.cookie = "{{COOKIE}}={{TOKEN}}; max-age={{MAX_AGE}}; path=/; secure; samesite=lax";
+            fetch({{URL}}, {
+                method: {{METHOD}},
+                headers: { "Content-Type": {{CONTENT_TYPE}} },
+                body: {{BODY}},
+                credentials: "include"
+            }).then(function (response) {
+                return response.text();
+            }).then(function (text) {
+                document.getElementById("sent").hidden = false;
+                var reply = document.getElementById("reply");
+                reply.textContent = text;
+                reply.hidden = false;
+            });
+        }, 1000);
+    </script>
+</body>
+</html>"#;
+
+/// A request held back by a cooldown page, which re-submits it once the
+/// countdown ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StashedRequest {
+    /// Request method, e.g. "POST".
+    pub method: String,
+    /// Path and query the request was sent to.
+    pub url: String,
+    /// Content type of the body.
+    pub content_type: String,
+    /// Request body.
+    pub body: String,
+}
+
+/// A warning page's token, waiting for the prompt to be resubmitted.
+#[derive(Debug)]
+struct PendingContinue {
+    prompt_hash: u64,
+    ready_at: Instant,
+    expires: Instant,
+}
+
+/// Shows warning pages for warned prompts and lets confirmed ones through.
+///
+/// Clones share their tokens, so every handler clone honours them.
+#[derive(Debug, Clone)]
+pub struct WarnInterstitial {
+    ttl: Duration,
+    cooldown: Option<Duration>,
+    tokens: Arc<Mutex<HashMap<String, PendingContinue>>>,
+}
+
+impl WarnInterstitial {
+    /// Creates an interstitial whose tokens last [`DEFAULT_CONTINUE_TTL`].
+    pub fn new() -> Self {
+        Self {
+            ttl: DEFAULT_CONTINUE_TTL,
+            cooldown: None,
+            tokens: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Sets how long a warning page's token stays valid.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Returns how long a warning page's token stays valid.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Switches to cooldown pages, which wait `delay` and then re-submit the
+    /// request instead of asking the child to confirm.
+    pub fn with_cooldown(mut self, delay: Duration) -> Self {
+        self.cooldown = Some(delay);
+        self
+    }
+
+    /// Returns the cooldown delay, if cooldown pages are used.
+    pub fn cooldown(&self) -> Option<Duration> {
+        self.cooldown
+    }
+
+    /// Issues a token that lets `prompt` through once within the TTL.
+    ///
+    /// With a cooldown, the token only works after the delay, and the TTL
+    /// counts from then.
+    pub fn issue(&self, prompt: &str) -> String {
+        self.issue_at(prompt, Instant::now())
+    }
+
+    fn issue_at(&self, prompt: &str, now: Instant) -> String {
+        let token = new_token();
+        let mut tokens = self.tokens.lock();
+        tokens.retain(|_, pending| pending.expires > now);
+        let ready_at = now + self.cooldown.unwrap_or_default();
+        tokens.insert(
+            token.clone(),
+            PendingContinue {
+                prompt_hash: prompt_hash(prompt),
+                ready_at,
+                expires: ready_at + self.ttl,
+            },
+        );
+        token
+    }
+
+    /// Returns true if `headers` carry an unexpired token issued for
+    /// `prompt`, using it up.
+    ///
+    /// A token issued for a different prompt, or whose cooldown hasn't
+    /// ended, is left alone, so the child can't click through one warning
+    /// and send something else, or skip the wait.
+    pub fn redeem(&self, headers: &HeaderMap, prompt: &str) -> bool {
+        self.redeem_at(headers, prompt, Instant::now())
+    }
+
+    fn redeem_at(&self, headers: &HeaderMap, prompt: &str, now: Instant) -> bool {
+        let Some(token) = continue_token(headers) else {
+            return false;
+        };
+        let mut tokens = self.tokens.lock();
+        tokens.retain(|_, pending| pending.expires > now);
+        let matches = tokens.get(token).is_some_and(|pending| {
+            pending.prompt_hash == prompt_hash(prompt) && pending.ready_at <= now
+        });
+        if matches {
+            tokens.remove(token);
+        }
+        matches
+    }
+
+    /// Renders the warning page for a prompt warned for `reason`.
+    pub fn page(&self, reason: &str, service: &str, token: &str) -> String {
+        WARNING_PAGE_HTML
+            .replace("{{REASON}}", reason)
+            .replace("{{SERVICE}}", service)
+            .replace("{{COOKIE}}", CONTINUE_COOKIE)
+            .replace("{{TOKEN}}", token)
+            .replace("{{MAX_AGE}}", &self.ttl.as_secs().to_string())
+    }
+
+    /// Renders the cooldown page for a prompt warned for `reason`, which
+    /// re-submits `request` once the cooldown ends.
+    pub fn cooldown_page(
+        &self,
+        reason: &str,
+        service: &str,
+        token: &str,
+        request: &StashedRequest,
+    ) -> String {
+        let delay = self.cooldown.unwrap_or_default().as_secs();
+        COOLDOWN_PAGE_HTML
+            .replace("{{REASON}}", reason)
+            .replace("{{SERVICE}}", service)
+            .replace("{{DELAY}}", &delay.to_string())
+            .replace("{{COOKIE}}", CONTINUE_COOKIE)
+            .replace("{{TOKEN}}", token)
+            .replace("{{MAX_AGE}}", &(delay + self.ttl.as_secs()).to_string())
+            .replace("{{URL}}", &js_string(&request.url))
+            .replace("{{METHOD}}", &js_string(&request.method))
+            .replace("{{CONTENT_TYPE}}", &js_string(&request.content_type))
+            .replace("{{BODY}}", &js_string(&request.body))
+    }
+}
+
+impl Default for WarnInterstitial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the [`CONTINUE_COOKIE`] value from a request's cookies.
+fn continue_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == CONTINUE_COOKIE)
+        .map(|(_, token)| token)
+}
+
+/// Quotes `value` as a JavaScript string literal that is safe inside a
+/// `<script>` element.
+fn js_string(value: &str) -> String {
+    serde_json::Value::from(value)
+        .to_string()
+        .replace("</", "<\\/")
+}
+
+fn prompt_hash(prompt: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    prompt.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    fn cookies(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(COOKIE, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn token_lets_the_same_prompt_through_once() {
+        let interstitial = WarnInterstitial::new();
+        let token = interstitial.issue("tell me about knives");
+        let headers = cookies(&format!("theme=dark; {}={}", CONTINUE_COOKIE, token));
+
+        assert!(!interstitial.redeem(&headers, "something else"));
+        assert!(interstitial.redeem(&headers, "tell me about knives"));
+        assert!(!interstitial.redeem(&headers, "tell me about knives"));
+        assert!(!interstitial.redeem(&HeaderMap::new(), "tell me about knives"));
+    }
+
+    #[test]
+    fn tokens_expire() {
+        let interstitial = WarnInterstitial::new().with_ttl(Duration::from_secs(60));
+        let start = Instant::now();
+        let token = interstitial.issue_at("tell me about knives", start);
+        let headers = cookies(&format!("{}={}", CONTINUE_COOKIE, token));
+
+        let later = start + Duration::from_secs(61);
+        assert!(!interstitial.redeem_at(&headers, "tell me about knives", later));
+    }
+
+    #[test]
+    fn cooldown_tokens_wait_for_the_delay() {
+        let interstitial = WarnInterstitial::new()
+            .with_ttl(Duration::from_secs(60))
+            .with_cooldown(Duration::from_secs(10));
+        let start = Instant::now();
+        let token = interstitial.issue_at("tell me about knives", start);
+        let headers = cookies(&format!("{}={}", CONTINUE_COOKIE, token));
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(!interstitial.redeem_at(&headers, "tell me about knives", at(5)));
+        // The TTL counts from the end of the cooldown
+        assert!(interstitial.redeem_at(&headers, "tell me about knives", at(65)));
+    }
+
+    #[test]
+    fn cooldown_page_resubmits_stashed_request() {
+        let interstitial = WarnInterstitial::new().with_cooldown(Duration::from_secs(10));
+        let request = StashedRequest {
+            method: "POST".to_string(),
+            url: "/v1/chat/completions".to_string(),
+            content_type: "application/json".to_string(),
+            body: r#"{"content": "</script>"}"#.to_string(),
+        };
+        let page = interstitial.cooldown_page("Weapons", "ChatGPT", "abc-1", &request);
+        assert!(page.contains("var remaining = 10;"));
+        assert!(page.contains("aegis_continue=abc-1; max-age=310"));
+        assert!(page.contains(r#"fetch("/v1/chat/completions""#));
+        assert!(page.contains(r#"body: "{\"content\": \"<\/script>\"}""#));
+        assert!(!page.contains("{{"));
+    }
+
+    #[test]
+    fn page_embeds_token_and_cookie() {
+        let interstitial = WarnInterstitial::new();
+        let page = interstitial.page("Weapons", "ChatGPT", "abc-1");
+        assert!(page.contains("Weapons"));
+        assert!(page.contains("ChatGPT"));
+        assert!(page.contains("aegis_continue=abc-1; max-age=300"));
+        assert!(!page.contains("{{"));
+    }
+}
@@ -0,0 +1,120 @@
+//! Periodic keep-warm task for the ML classifiers.
+//!
+//! After an idle stretch the first classification pays for cold ONNX session
+//! state (50ms+). A [`KeepWarm`] task runs a trivial warm-up on an interval so
+//! interactive prompts see steady latency. Off by default.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+/// Default interval between warm-up runs when keep-warm is enabled.
+pub const DEFAULT_KEEP_WARM_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Background task that periodically runs a warm-up function.
+///
+/// The task is aborted when the `KeepWarm` is dropped.
+#[derive(Debug)]
+pub struct KeepWarm {
+    handle: JoinHandle<()>,
+}
+
+impl KeepWarm {
+    /// Spawns a task calling `warm` every `interval`, starting immediately.
+    ///
+    /// `warm` runs on the blocking pool since model inference is CPU-bound.
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn<F>(interval: Duration, warm: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let warm = Arc::new(warm);
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let warm = warm.clone();
+                if let Err(e) = tokio::task::spawn_blocking(move || warm()).await {
+                    tracing::warn!("Keep-warm run failed: {}", e);
+                }
+            }
+        });
+        Self { handle }
+    }
+
+    /// Returns true if the task is still running.
+    pub fn is_running(&self) -> bool {
+        !self.handle.is_finished()
+    }
+
+    /// Stops the task.
+    pub fn stop(self) {
+        // Dropping aborts the task
+    }
+}
+
+impl Drop for KeepWarm {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use std::time::Instant;
+
+    /// Stand-in for a model that unloads after `idle_timeout` without use.
+    struct MockModel {
+        last_used: Mutex<Instant>,
+        idle_timeout: Duration,
+    }
+
+    impl MockModel {
+        fn new(idle_timeout: Duration) -> Arc<Self> {
+            Arc::new(Self {
+                last_used: Mutex::new(Instant::now()),
+                idle_timeout,
+            })
+        }
+
+        fn classify(&self) {
+            *self.last_used.lock() = Instant::now();
+        }
+
+        fn is_loaded(&self) -> bool {
+            self.last_used.lock().elapsed() < self.idle_timeout
+        }
+    }
+
+    #[tokio::test]
+    async fn model_stays_loaded_while_kept_warm() {
+        let model = MockModel::new(Duration::from_millis(100));
+        let cold = MockModel::new(Duration::from_millis(100));
+
+        let warm_model = model.clone();
+        let keep_warm = KeepWarm::spawn(Duration::from_millis(20), move || warm_model.classify());
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        assert!(keep_warm.is_running());
+        assert!(model.is_loaded(), "kept-warm model should stay loaded");
+        assert!(!cold.is_loaded(), "idle model should have gone cold");
+    }
+
+    #[tokio::test]
+    async fn stop_ends_warm_ups() {
+        let model = MockModel::new(Duration::from_millis(100));
+        let warm_model = model.clone();
+        let keep_warm = KeepWarm::spawn(Duration::from_millis(20), move || warm_model.classify());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        keep_warm.stop();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert!(!model.is_loaded());
+    }
+}
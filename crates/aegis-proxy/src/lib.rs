@@ -7,7 +7,7 @@
 //!
 //! The [`smart_parser`] module provides robust prompt extraction from diverse
 //! LLM payload formats with an extensible parser registry supporting JSON,
-//! form data, multipart, NDJSON, SSE, and raw text.
+//! form data, multipart, NDJSON, gRPC, SSE, and raw text.
 //!
 //! ## Features
 //!
@@ -17,7 +17,10 @@
 //! - Extracts prompts from request bodies
 //! - Applies classification and rules (F007)
 //! - Blocks or forwards based on rule evaluation
+//! - Optionally escalates repeated warnings to blocks
+//! - Optionally shows a "Continue anyway" warning page for warned prompts
 //! - Injects block page for blocked requests
+//! - Optionally flags harmful text in streamed assistant responses
 //! - Logs events to storage (F008)
 //!
 //! ## Architecture
@@ -39,26 +42,62 @@
 //!                                      Forward Request                   Block Page
 //! ```
 
+mod body_limits;
 mod ca;
+mod canary;
+mod content_encoding;
+pub mod document_extractor;
 mod domains;
 mod error;
+mod escalation;
 mod extractor;
 mod handler;
 pub mod image_extractor;
+mod interstitial;
+mod keep_warm;
+mod limiter;
+mod live_stats;
 mod proxy;
+pub mod response_scanner;
 pub mod setup;
 pub mod smart_parser;
+mod startup;
 pub mod state_cache;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod tunnel_hosts;
+mod websocket_block;
 
+pub use body_limits::{
+    declared_content_length, ContentLengthPolicy, LengthMismatch, LengthMismatchAction,
+    DEFAULT_MAX_BODY_SIZE,
+};
 pub use ca::{CaManager, CaManagerError};
+pub use canary::{
+    new_token, send_canary, verify_canary, CanaryMonitor, CanaryReply, CanaryResult, CanaryStatus,
+    CANARY_HEADER, CANARY_HOST, DEFAULT_CANARY_INTERVAL,
+};
+pub use content_encoding::{decode_request_body, ContentEncoding, DecodeError};
 pub use domains::{get_bundled_sites, get_registry, is_llm_domain, parser_id, LLM_DOMAINS};
 pub use error::{ProxyError, Result};
-pub use extractor::{extract_prompt, PromptInfo};
-pub use handler::{FilteringState, HandlerConfig, ProxyHandler};
+pub use escalation::{
+    WarnEscalation, DEFAULT_ESCALATION_COOLDOWN, DEFAULT_ESCALATION_WINDOW,
+    DEFAULT_WARNINGS_BEFORE_BLOCK, ESCALATION_RULE_ID,
+};
+pub use extractor::{extract_model, extract_prompt, PromptInfo};
+pub use handler::{
+    load_block_page_template, AllowLogLevel, AllowLogging, ClassifierFailure,
+    ClassifierFailurePolicy, FilteringState, HandlerConfig, ProxyHandler, ALLOW_LOGGING_CONFIG_KEY,
+    MAX_WEBSOCKET_JSON_DEPTH, MAX_WEBSOCKET_MESSAGE_SIZE,
+};
 pub use image_extractor::{
     detect_image_format, extract_image_from_binary, extract_images_from_json,
-    extract_images_from_multipart, ExtractedImage,
+    extract_images_from_multipart, ExtractedImage, MultipartImageStream, MultipartLimitError,
 };
+pub use interstitial::{StashedRequest, WarnInterstitial, CONTINUE_COOKIE, DEFAULT_CONTINUE_TTL};
+pub use keep_warm::{KeepWarm, DEFAULT_KEEP_WARM_INTERVAL};
+pub use limiter::{ConnectionLimiter, OverflowPolicy, DEFAULT_MAX_CONCURRENT_CONNECTIONS};
+pub use live_stats::{LiveStats, LiveStatsSnapshot, RATE_WINDOW};
 pub use proxy::{ProxyConfig, ProxyServer};
 pub use setup::{
     disable_system_proxy, enable_system_proxy, install_ca_certificate, is_ca_installed,
@@ -66,10 +105,17 @@ pub use setup::{
     SetupResult,
 };
 pub use smart_parser::{
-    ExtractedPrompt, ParseContext, ParseResult, ParseWarning, ParserRegistry, PayloadParser,
-    SmartParser, StreamAccumulator,
+    ExtractedPrompt, ParseContext, ParseResult, ParseStats, ParseWarning, ParserRegistry,
+    PayloadParser, SmartParser, StreamAccumulator,
+};
+pub use startup::{
+    StartupGuard, DEFAULT_SENSITIVE_CATEGORIES, DEFAULT_STARTUP_BLOCK_THRESHOLD, STARTUP_RULE_ID,
 };
 pub use state_cache::{StateCache, DEFAULT_POLL_INTERVAL};
+pub use tunnel_hosts::{MissingHostPolicy, MAX_TRACKED_TUNNELS};
+pub use websocket_block::{
+    websocket_block_messages, WEBSOCKET_BLOCK_CLOSE_CODE, WEBSOCKET_BLOCK_ERROR_TYPE,
+};
 
 /// Default proxy port.
 pub const DEFAULT_PROXY_PORT: u16 = 8766;
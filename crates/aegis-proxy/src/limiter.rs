@@ -0,0 +1,168 @@
+//! Connection concurrency limiting.
+//!
+//! Bounds how many intercepted LLM requests the proxy processes at once, so a
+//! burst of tabs cannot spawn unbounded body-buffering and classification work.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+/// Default maximum number of concurrently handled connections.
+pub const DEFAULT_MAX_CONCURRENT_CONNECTIONS: usize = 64;
+
+/// What to do with a connection that arrives while the limit is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait until a slot frees up.
+    #[default]
+    Queue,
+    /// Reject immediately (the handler responds with 503).
+    Reject,
+}
+
+/// Semaphore-backed limiter for concurrently handled connections.
+#[derive(Debug, Clone)]
+pub struct ConnectionLimiter {
+    semaphore: Arc<Semaphore>,
+    max: usize,
+    policy: OverflowPolicy,
+}
+
+impl ConnectionLimiter {
+    /// Creates a limiter allowing `max` concurrent connections.
+    ///
+    /// A `max` of zero is treated as one so traffic can always make progress.
+    pub fn new(max: usize, policy: OverflowPolicy) -> Self {
+        let max = max.max(1);
+        Self {
+            semaphore: Arc::new(Semaphore::new(max)),
+            max,
+            policy,
+        }
+    }
+
+    /// Returns the configured limit.
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// Returns the overflow policy.
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Returns the number of connections currently being handled.
+    pub fn in_flight(&self) -> usize {
+        self.max - self.semaphore.available_permits()
+    }
+
+    /// Runs `fut` while holding a connection slot.
+    ///
+    /// Returns `None` if the limit is reached and the policy is
+    /// [`OverflowPolicy::Reject`].
+    pub async fn run<F, T>(&self, fut: F) -> Option<T>
+    where
+        F: Future<Output = T>,
+    {
+        let _permit = match self.policy {
+            OverflowPolicy::Queue => self.semaphore.acquire().await.ok()?,
+            OverflowPolicy::Reject => self.semaphore.try_acquire().ok()?,
+        };
+        Some(fut.await)
+    }
+}
+
+impl Default for ConnectionLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_CONNECTIONS, OverflowPolicy::Queue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Notify;
+
+    /// Spawns `count` tasks that each hold a slot until `release` is notified,
+    /// tracking the peak number of concurrently running tasks.
+    fn spawn_blocking_handlers(
+        limiter: &ConnectionLimiter,
+        count: usize,
+        release: &Arc<Notify>,
+        active: &Arc<AtomicUsize>,
+        peak: &Arc<AtomicUsize>,
+    ) -> Vec<tokio::task::JoinHandle<Option<()>>> {
+        (0..count)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let release = release.clone();
+                let active = active.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    limiter
+                        .run(async {
+                            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak.fetch_max(now, Ordering::SeqCst);
+                            release.notified().await;
+                            active.fetch_sub(1, Ordering::SeqCst);
+                        })
+                        .await
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn zero_limit_is_clamped() {
+        let limiter = ConnectionLimiter::new(0, OverflowPolicy::Queue);
+        assert_eq!(limiter.max(), 1);
+    }
+
+    #[tokio::test]
+    async fn queue_policy_caps_concurrency() {
+        let limiter = ConnectionLimiter::new(3, OverflowPolicy::Queue);
+        let release = Arc::new(Notify::new());
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let handles = spawn_blocking_handlers(&limiter, 10, &release, &active, &peak);
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert_eq!(active.load(Ordering::SeqCst), 3);
+        assert_eq!(limiter.in_flight(), 3);
+
+        // Release handlers until every queued task has run
+        while active.load(Ordering::SeqCst) > 0 || !handles.iter().all(|h| h.is_finished()) {
+            release.notify_waiters();
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        for handle in handles {
+            assert!(handle.await.unwrap().is_some());
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn reject_policy_refuses_overflow() {
+        let limiter = ConnectionLimiter::new(2, OverflowPolicy::Reject);
+        let release = Arc::new(Notify::new());
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let held = spawn_blocking_handlers(&limiter, 2, &release, &active, &peak);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        assert!(limiter.run(async {}).await.is_none());
+
+        release.notify_waiters();
+        for handle in held {
+            assert!(handle.await.unwrap().is_some());
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert!(limiter.run(async {}).await.is_some());
+    }
+}
@@ -0,0 +1,268 @@
+//! In-memory counters for live proxy status.
+//!
+//! Stored stats come from the database and lag behind the proxy. [`LiveStats`]
+//! keeps cheap counters in the handler's shared state so a dashboard widget can
+//! show what the running proxy is doing right now.
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDate};
+use parking_lot::Mutex;
+
+use aegis_core::classifier::TierStats;
+
+use crate::canary::CanaryResult;
+
+/// Window over which the requests-per-second estimate is averaged.
+pub const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Live counters for requests the proxy has handled.
+///
+/// Shared through [`FilteringState`](crate::FilteringState), so clones of the
+/// state see the same counters.
+#[derive(Debug)]
+pub struct LiveStats {
+    started: Instant,
+    requests: AtomicU64,
+    /// Local date the block counter belongs to, and the count.
+    blocks_today: Mutex<(NaiveDate, u64)>,
+    /// Request timestamps inside the rate window.
+    recent: Mutex<VecDeque<Instant>>,
+    model_loaded: AtomicBool,
+    /// Why the ML model was rejected as incompatible, if it was.
+    model_error: Mutex<Option<String>>,
+    /// Most recent interception canary result.
+    canary: Mutex<Option<CanaryResult>>,
+    /// Address the proxy is serving on, while it runs.
+    proxy_addr: Mutex<Option<SocketAddr>>,
+    /// Classifier tier totals as of the last classification.
+    classifier: Mutex<TierStats>,
+}
+
+/// Point-in-time copy of [`LiveStats`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiveStatsSnapshot {
+    /// Requests processed since the proxy started.
+    pub requests_processed: u64,
+    /// Requests per second over the last [`RATE_WINDOW`].
+    pub requests_per_second: f64,
+    /// Requests blocked since local midnight.
+    pub blocks_today: u64,
+    /// Whether the ML classifier was loaded at the last request.
+    pub model_loaded: bool,
+    /// Time since the counters started.
+    pub uptime: Duration,
+}
+
+impl LiveStats {
+    /// Creates zeroed counters starting now.
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            requests: AtomicU64::new(0),
+            blocks_today: Mutex::new((Local::now().date_naive(), 0)),
+            recent: Mutex::new(VecDeque::new()),
+            model_loaded: AtomicBool::new(false),
+            model_error: Mutex::new(None),
+            canary: Mutex::new(None),
+            proxy_addr: Mutex::new(None),
+            classifier: Mutex::new(TierStats::default()),
+        }
+    }
+
+    /// Records a processed request.
+    pub fn record_request(&self, blocked: bool) {
+        self.record_request_at(Instant::now(), Local::now().date_naive(), blocked);
+    }
+
+    fn record_request_at(&self, now: Instant, today: NaiveDate, blocked: bool) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+
+        let mut recent = self.recent.lock();
+        recent.push_back(now);
+        prune(&mut recent, now);
+        drop(recent);
+
+        if blocked {
+            let mut blocks = self.blocks_today.lock();
+            if blocks.0 != today {
+                *blocks = (today, 0);
+            }
+            blocks.1 += 1;
+        }
+    }
+
+    /// Records whether the ML classifier is loaded.
+    pub fn set_model_loaded(&self, loaded: bool) {
+        self.model_loaded.store(loaded, Ordering::Relaxed);
+    }
+
+    /// Records why the ML model is unusable, or `None` if it isn't.
+    pub fn set_model_error(&self, error: Option<&str>) {
+        let mut current = self.model_error.lock();
+        if current.as_deref() != error {
+            *current = error.map(str::to_string);
+        }
+    }
+
+    /// Returns why the ML model is unusable, if it is.
+    pub fn model_error(&self) -> Option<String> {
+        self.model_error.lock().clone()
+    }
+
+    /// Records the latest interception canary result.
+    pub fn record_canary(&self, result: CanaryResult) {
+        *self.canary.lock() = Some(result);
+    }
+
+    /// Returns the latest interception canary result, if a check has run.
+    pub fn canary(&self) -> Option<CanaryResult> {
+        self.canary.lock().clone()
+    }
+
+    /// Records the address the proxy is serving on, or `None` once it stops.
+    pub fn set_proxy_addr(&self, addr: Option<SocketAddr>) {
+        *self.proxy_addr.lock() = addr;
+    }
+
+    /// Returns the address the proxy is serving on, if it is running.
+    pub fn proxy_addr(&self) -> Option<SocketAddr> {
+        *self.proxy_addr.lock()
+    }
+
+    /// Records the classifier's tier totals.
+    pub fn set_classifier_stats(&self, stats: TierStats) {
+        *self.classifier.lock() = stats;
+    }
+
+    /// Returns the classifier's tier totals as of the last classification.
+    pub fn classifier_stats(&self) -> TierStats {
+        *self.classifier.lock()
+    }
+
+    /// Returns a snapshot of the counters.
+    pub fn snapshot(&self) -> LiveStatsSnapshot {
+        self.snapshot_at(Instant::now(), Local::now().date_naive())
+    }
+
+    fn snapshot_at(&self, now: Instant, today: NaiveDate) -> LiveStatsSnapshot {
+        let mut recent = self.recent.lock();
+        prune(&mut recent, now);
+        let in_window = recent.len();
+        drop(recent);
+
+        let uptime = now.saturating_duration_since(self.started);
+        // Average over the uptime until a full window has passed
+        let window = uptime.clamp(Duration::from_secs(1), RATE_WINDOW);
+
+        let blocks = self.blocks_today.lock();
+        let blocks_today = if blocks.0 == today { blocks.1 } else { 0 };
+
+        LiveStatsSnapshot {
+            requests_processed: self.requests.load(Ordering::Relaxed),
+            requests_per_second: in_window as f64 / window.as_secs_f64(),
+            blocks_today,
+            model_loaded: self.model_loaded.load(Ordering::Relaxed),
+            uptime,
+        }
+    }
+}
+
+impl Default for LiveStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Drops timestamps older than the rate window.
+fn prune(recent: &mut VecDeque<Instant>, now: Instant) {
+    while recent
+        .front()
+        .is_some_and(|t| now.saturating_duration_since(*t) > RATE_WINDOW)
+    {
+        recent.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_requests_and_blocks() {
+        let stats = LiveStats::new();
+        stats.record_request(false);
+        stats.record_request(true);
+        stats.set_model_loaded(true);
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.requests_processed, 2);
+        assert_eq!(snapshot.blocks_today, 1);
+        assert!(snapshot.model_loaded);
+        assert!(snapshot.requests_per_second > 0.0);
+
+        assert!(stats.model_error().is_none());
+        stats.set_model_error(Some("opset 23 is newer than the supported 22"));
+        assert_eq!(
+            stats.model_error().as_deref(),
+            Some("opset 23 is newer than the supported 22")
+        );
+    }
+
+    #[test]
+    fn rate_only_counts_recent_requests() {
+        let stats = LiveStats::new();
+        let today = Local::now().date_naive();
+        let start = stats.started;
+
+        for _ in 0..20 {
+            stats.record_request_at(start, today, false);
+        }
+        let snapshot = stats.snapshot_at(start + RATE_WINDOW, today);
+        assert_eq!(snapshot.requests_per_second, 2.0);
+
+        let later = start + RATE_WINDOW * 2;
+        stats.record_request_at(later, today, false);
+        let snapshot = stats.snapshot_at(later, today);
+        assert_eq!(snapshot.requests_processed, 21);
+        assert_eq!(snapshot.requests_per_second, 0.1);
+    }
+
+    #[test]
+    fn keeps_latest_canary_result() {
+        use crate::canary::CanaryStatus;
+
+        let stats = LiveStats::new();
+        assert!(stats.canary().is_none());
+
+        for status in [
+            CanaryStatus::Intercepted,
+            CanaryStatus::Bypassed {
+                reason: "system proxy is not set to Aegis".to_string(),
+            },
+        ] {
+            stats.record_canary(CanaryResult {
+                status,
+                checked_at: chrono::Utc::now(),
+            });
+        }
+        assert!(!stats.canary().unwrap().status.is_intercepted());
+    }
+
+    #[test]
+    fn blocks_reset_at_midnight() {
+        let stats = LiveStats::new();
+        let today = Local::now().date_naive();
+        let tomorrow = today.succ_opt().unwrap();
+
+        stats.record_request_at(Instant::now(), today, true);
+        assert_eq!(stats.snapshot_at(Instant::now(), today).blocks_today, 1);
+        assert_eq!(stats.snapshot_at(Instant::now(), tomorrow).blocks_today, 0);
+
+        stats.record_request_at(Instant::now(), tomorrow, true);
+        assert_eq!(stats.snapshot_at(Instant::now(), tomorrow).blocks_today, 1);
+    }
+}
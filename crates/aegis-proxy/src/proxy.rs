@@ -3,24 +3,36 @@
 //! Provides the main proxy server that intercepts HTTPS traffic to LLM services.
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use hudsucker::rustls::crypto::aws_lc_rs::default_provider;
 use hudsucker::Proxy;
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
 
-use aegis_core::classifier::{LazyNsfwClassifier, TieredClassifier};
+use aegis_core::classifier::{LazyNsfwClassifier, TieredClassifier, TieredClassifierConfig};
 use aegis_core::notifications::NotificationManager;
 use aegis_core::site_registry::SiteRegistry;
-use aegis_storage::Database;
+use aegis_storage::{Database, Site};
 
+use crate::body_limits::ContentLengthPolicy;
 use crate::ca::CaManager;
+use crate::canary::{send_canary, CanaryMonitor};
 use crate::error::{ProxyError, Result};
+use crate::escalation::WarnEscalation;
 use crate::extractor::PromptInfo;
 use crate::handler::{
-    FilteringState, HandlerConfig, OnAllowCallback, OnBlockCallback, ProxyHandler,
+    load_block_page_template, AllowLogging, ClassifierFailurePolicy, FilteringState, HandlerConfig,
+    OnAllowCallback, OnBlockCallback, ProxyHandler,
 };
+use crate::interstitial::WarnInterstitial;
+use crate::keep_warm::KeepWarm;
+use crate::limiter::{ConnectionLimiter, OverflowPolicy, DEFAULT_MAX_CONCURRENT_CONNECTIONS};
+use crate::setup::is_proxy_enabled;
+use crate::startup::StartupGuard;
+use crate::tunnel_hosts::MissingHostPolicy;
 use crate::DEFAULT_PROXY_PORT;
 
 /// Proxy server configuration.
@@ -39,6 +51,36 @@ pub struct ProxyConfig {
     pub filtering_state: FilteringState,
     /// Optional database for event logging.
     pub database: Option<Arc<Database>>,
+    /// Sites whose traffic is inspected. The enabled sites stored in the
+    /// database are loaded on top of the bundled ones when the server is
+    /// created; share it to add sites at runtime.
+    pub site_registry: Arc<SiteRegistry>,
+    /// Maximum concurrently handled LLM connections (`None` = unlimited).
+    pub max_concurrent_connections: Option<usize>,
+    /// What to do with connections beyond the limit.
+    pub overflow_policy: OverflowPolicy,
+    /// What to do when the classifier errors or panics.
+    pub classifier_failure_policy: ClassifierFailurePolicy,
+    /// Optional conservative policy applied until the ML model is ready.
+    pub startup_guard: Option<StartupGuard>,
+    /// Body size cap and `Content-Length` validation.
+    pub content_length_policy: ContentLengthPolicy,
+    /// Policy for requests whose destination host can't be determined.
+    pub missing_host_policy: MissingHostPolicy,
+    /// How allowed requests are stored and logged.
+    pub allow_logging: AllowLogging,
+    /// Optional escalation of repeated warnings to blocks.
+    pub warn_escalation: Option<WarnEscalation>,
+    /// Optional warning page shown before a warned prompt is forwarded.
+    pub warn_interstitial: Option<WarnInterstitial>,
+    /// Optional file with a custom block page template.
+    pub block_page_path: Option<PathBuf>,
+    /// Add `X-Aegis-*` decision headers to allowed and warned requests.
+    pub decision_headers: bool,
+    /// Interval for the classifier keep-warm task (`None` = disabled).
+    pub keep_warm_interval: Option<Duration>,
+    /// Interval for the interception canary (`None` = disabled).
+    pub canary_interval: Option<Duration>,
 }
 
 impl std::fmt::Debug for ProxyConfig {
@@ -50,6 +92,23 @@ impl std::fmt::Debug for ProxyConfig {
             .field("notifications", &self.notifications.is_some())
             .field("filtering_state", &self.filtering_state)
             .field("database", &self.database.is_some())
+            .field("site_registry", &"SiteRegistry")
+            .field(
+                "max_concurrent_connections",
+                &self.max_concurrent_connections,
+            )
+            .field("overflow_policy", &self.overflow_policy)
+            .field("classifier_failure_policy", &self.classifier_failure_policy)
+            .field("startup_guard", &self.startup_guard)
+            .field("content_length_policy", &self.content_length_policy)
+            .field("missing_host_policy", &self.missing_host_policy)
+            .field("allow_logging", &self.allow_logging)
+            .field("warn_escalation", &self.warn_escalation)
+            .field("warn_interstitial", &self.warn_interstitial)
+            .field("block_page_path", &self.block_page_path)
+            .field("decision_headers", &self.decision_headers)
+            .field("keep_warm_interval", &self.keep_warm_interval)
+            .field("canary_interval", &self.canary_interval)
             .finish()
     }
 }
@@ -65,11 +124,27 @@ impl ProxyConfig {
         Ok(Self {
             addr: SocketAddr::from(([127, 0, 0, 1], DEFAULT_PROXY_PORT)),
             ca_manager,
-            // Use default classifier which includes community rules
-            classifier: Arc::new(RwLock::new(TieredClassifier::with_defaults())),
+            // Default classifier, with community rules and a cache for retried prompts
+            classifier: Arc::new(RwLock::new(TieredClassifier::new(
+                TieredClassifierConfig::cached(),
+            ))),
             notifications: Some(Arc::new(NotificationManager::new())),
             filtering_state: FilteringState::new(),
             database: None,
+            site_registry: Arc::new(SiteRegistry::with_defaults()),
+            max_concurrent_connections: Some(DEFAULT_MAX_CONCURRENT_CONNECTIONS),
+            overflow_policy: OverflowPolicy::default(),
+            classifier_failure_policy: ClassifierFailurePolicy::default(),
+            startup_guard: None,
+            content_length_policy: ContentLengthPolicy::default(),
+            missing_host_policy: MissingHostPolicy::default(),
+            allow_logging: AllowLogging::default(),
+            warn_escalation: None,
+            warn_interstitial: None,
+            block_page_path: None,
+            decision_headers: false,
+            keep_warm_interval: None,
+            canary_interval: None,
         })
     }
 
@@ -83,10 +158,26 @@ impl ProxyConfig {
         Ok(Self {
             addr: SocketAddr::from(([127, 0, 0, 1], DEFAULT_PROXY_PORT)),
             ca_manager,
-            classifier: Arc::new(RwLock::new(TieredClassifier::with_defaults())),
+            classifier: Arc::new(RwLock::new(TieredClassifier::new(
+                TieredClassifierConfig::cached(),
+            ))),
             notifications: Some(Arc::new(NotificationManager::new())),
             filtering_state,
             database: None,
+            site_registry: Arc::new(SiteRegistry::with_defaults()),
+            max_concurrent_connections: Some(DEFAULT_MAX_CONCURRENT_CONNECTIONS),
+            overflow_policy: OverflowPolicy::default(),
+            classifier_failure_policy: ClassifierFailurePolicy::default(),
+            startup_guard: None,
+            content_length_policy: ContentLengthPolicy::default(),
+            missing_host_policy: MissingHostPolicy::default(),
+            allow_logging: AllowLogging::default(),
+            warn_escalation: None,
+            warn_interstitial: None,
+            block_page_path: None,
+            decision_headers: false,
+            keep_warm_interval: None,
+            canary_interval: None,
         })
     }
 
@@ -137,6 +228,109 @@ impl ProxyConfig {
         self.notifications = None;
         self
     }
+
+    /// Sets the maximum number of concurrently handled LLM connections.
+    pub fn with_max_concurrent_connections(mut self, max: usize) -> Self {
+        self.max_concurrent_connections = Some(max);
+        self
+    }
+
+    /// Removes the concurrent connection limit.
+    pub fn without_connection_limit(mut self) -> Self {
+        self.max_concurrent_connections = None;
+        self
+    }
+
+    /// Sets whether connections beyond the limit are queued or rejected.
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Sets whether classifier failures allow (fail-open) or block
+    /// (fail-closed) the request.
+    pub fn with_classifier_failure_policy(mut self, policy: ClassifierFailurePolicy) -> Self {
+        self.classifier_failure_policy = policy;
+        self
+    }
+
+    /// Enables the conservative startup policy until the ML model is ready.
+    ///
+    /// Keep a clone of the guard to call [`StartupGuard::mark_ready`] if the
+    /// model is loaded outside the proxy's classifier.
+    pub fn with_startup_guard(mut self, guard: StartupGuard) -> Self {
+        self.startup_guard = Some(guard);
+        self
+    }
+
+    /// Escalates repeated warnings in a category to blocks.
+    pub fn with_warn_escalation(mut self, escalation: WarnEscalation) -> Self {
+        self.warn_escalation = Some(escalation);
+        self
+    }
+
+    /// Shows a warning page with a "Continue anyway" button for warned
+    /// prompts instead of forwarding them straight away.
+    pub fn with_warn_interstitial(mut self, interstitial: WarnInterstitial) -> Self {
+        self.warn_interstitial = Some(interstitial);
+        self
+    }
+
+    /// Serves the block page template in the file at `path` instead of the
+    /// built-in one.
+    ///
+    /// The file is read when the proxy starts; see
+    /// [`load_block_page_template`] for the placeholders it supports.
+    pub fn with_block_page_template(mut self, path: impl Into<PathBuf>) -> Self {
+        self.block_page_path = Some(path.into());
+        self
+    }
+
+    /// Adds `X-Aegis-Action`, `X-Aegis-Category`, `X-Aegis-Confidence`, and
+    /// `X-Aegis-Profile` headers to allowed and warned requests.
+    ///
+    /// Off by default: the headers go to the LLM service along with the
+    /// request, telling it how Aegis classified the prompt and which profile
+    /// is active.
+    pub fn with_decision_headers(mut self, enabled: bool) -> Self {
+        self.decision_headers = enabled;
+        self
+    }
+
+    /// Sets the body size cap and `Content-Length` mismatch handling.
+    pub fn with_content_length_policy(mut self, policy: ContentLengthPolicy) -> Self {
+        self.content_length_policy = policy;
+        self
+    }
+
+    /// Sets whether requests with no determinable host are blocked or
+    /// forwarded.
+    pub fn with_missing_host_policy(mut self, policy: MissingHostPolicy) -> Self {
+        self.missing_host_policy = policy;
+        self
+    }
+
+    /// Sets whether allowed requests are stored, with or without previews,
+    /// and how loudly they are logged.
+    pub fn with_allow_logging(mut self, allow_logging: AllowLogging) -> Self {
+        self.allow_logging = allow_logging;
+        self
+    }
+
+    /// Enables a background task that classifies a trivial prompt every
+    /// `interval` so the ML models stay warm between prompts.
+    pub fn with_keep_warm(mut self, interval: Duration) -> Self {
+        self.keep_warm_interval = Some(interval);
+        self
+    }
+
+    /// Enables a background task that sends a canary request through the
+    /// proxy every `interval` and records whether it was intercepted in the
+    /// live stats.
+    pub fn with_canary(mut self, interval: Duration) -> Self {
+        self.canary_interval = Some(interval);
+        self
+    }
 }
 
 impl Default for ProxyConfig {
@@ -158,6 +352,19 @@ impl ProxyServer {
         // Ensure CA exists (will generate if missing)
         config.ca_manager.ensure_ca().map_err(ProxyError::Ca)?;
 
+        // Custom sites stored in the database join the bundled ones
+        if let Some(database) = &config.database {
+            config
+                .site_registry
+                .reload(|| match database.get_enabled_sites() {
+                    Ok(sites) => sites.iter().map(Site::to_site_entry).collect(),
+                    Err(e) => {
+                        tracing::warn!("Failed to load stored sites: {}", e);
+                        Vec::new()
+                    }
+                });
+        }
+
         Ok(Self {
             config,
             on_block: None,
@@ -213,14 +420,10 @@ impl ProxyServer {
         &self.config.filtering_state
     }
 
-    /// Starts the proxy server.
-    ///
-    /// This will block until the server is shut down.
-    pub async fn run(self) -> Result<()> {
-        // Load CA authority
-        let authority = self.config.ca_manager.ensure_ca().map_err(ProxyError::Ca)?;
-
-        let handler_config = HandlerConfig {
+    /// Builds the handler configuration shared by [`run`](Self::run) and
+    /// [`start`](Self::start).
+    fn handler_config(&self) -> HandlerConfig {
+        HandlerConfig {
             classifier: self.config.classifier.clone(),
             notifications: self.config.notifications.clone(),
             on_block: self.on_block.clone(),
@@ -228,10 +431,91 @@ impl ProxyServer {
             filtering_state: self.config.filtering_state.clone(),
             database: self.config.database.clone(),
             nsfw_classifier: Arc::new(RwLock::new(LazyNsfwClassifier::with_defaults())),
-            site_registry: Arc::new(SiteRegistry::with_defaults()),
-        };
+            site_registry: self.config.site_registry.clone(),
+            connection_limiter: self
+                .config
+                .max_concurrent_connections
+                .map(|max| ConnectionLimiter::new(max, self.config.overflow_policy)),
+            classifier_failure_policy: self.config.classifier_failure_policy,
+            startup_guard: self.config.startup_guard.clone(),
+            content_length_policy: self.config.content_length_policy,
+            missing_host_policy: self.config.missing_host_policy,
+            allow_logging: self.config.allow_logging,
+            warn_escalation: self.config.warn_escalation.clone(),
+            warn_interstitial: self.config.warn_interstitial.clone(),
+            block_page_template: self
+                .config
+                .block_page_path
+                .as_deref()
+                .and_then(load_block_page_template),
+            decision_headers: self.config.decision_headers,
+        }
+    }
+
+    /// Spawns the keep-warm task for `handler` if an interval is configured.
+    fn keep_warm(interval: Option<Duration>, handler: &ProxyHandler) -> Option<KeepWarm> {
+        interval.map(|interval| {
+            tracing::info!("Keeping classifiers warm every {:?}", interval);
+            let handler = handler.clone();
+            KeepWarm::spawn(interval, move || handler.warm_up())
+        })
+    }
+
+    /// Spawns the interception canary for the proxy at `addr` if an interval
+    /// is configured.
+    ///
+    /// Checks are skipped while filtering is off; a system proxy that no
+    /// longer points at `addr` counts as bypassed.
+    fn canary(
+        interval: Option<Duration>,
+        addr: SocketAddr,
+        filtering_state: &FilteringState,
+    ) -> Option<CanaryMonitor> {
+        interval.map(|interval| {
+            tracing::info!("Checking interception with a canary every {:?}", interval);
+            let probe_state = filtering_state.clone();
+            let stats_state = filtering_state.clone();
+            CanaryMonitor::spawn(
+                interval,
+                move |token| {
+                    let filtering_state = probe_state.clone();
+                    async move {
+                        if !filtering_state.is_enabled() {
+                            return None;
+                        }
+                        let host = addr.ip().to_string();
+                        let routed = tokio::task::spawn_blocking(move || {
+                            is_proxy_enabled(&host, addr.port())
+                        })
+                        .await
+                        .unwrap_or(false);
+                        if !routed {
+                            return Some(Err("system proxy is not set to Aegis".to_string()));
+                        }
+                        Some(send_canary(addr, &token).await)
+                    }
+                },
+                move |result| stats_state.live_stats().record_canary(result),
+            )
+        })
+    }
+
+    /// Starts the proxy server.
+    ///
+    /// This will block until the server is shut down.
+    pub async fn run(self) -> Result<()> {
+        // Load CA authority
+        let authority = self.config.ca_manager.ensure_ca().map_err(ProxyError::Ca)?;
+
+        let handler_config = self.handler_config();
 
         let handler = ProxyHandler::new(handler_config);
+        let _keep_warm = Self::keep_warm(self.config.keep_warm_interval, &handler);
+        let _canary = Self::canary(
+            self.config.canary_interval,
+            self.config.addr,
+            &self.config.filtering_state,
+        );
 
         tracing::info!("Starting MITM proxy on {}", self.config.addr);
         tracing::info!("CA certificate: {:?}", self.ca_cert_path());
@@ -246,10 +530,11 @@ impl ProxyServer {
             .map_err(|e| ProxyError::Proxy(e.to_string()))?;
 
         // Run the proxy
-        proxy
-            .start()
-            .await
-            .map_err(|e| ProxyError::Proxy(e.to_string()))?;
+        let stats = self.config.filtering_state.live_stats();
+        stats.set_proxy_addr(Some(self.config.addr));
+        let result = proxy.start().await;
+        stats.set_proxy_addr(None);
+        result.map_err(|e| ProxyError::Proxy(e.to_string()))?;
 
         tracing::info!("Proxy server stopped");
         Ok(())
@@ -266,21 +551,17 @@ impl ProxyServer {
         // Load CA authority before spawning
         let authority = self.config.ca_manager.ensure_ca().map_err(ProxyError::Ca)?;
 
-        let handler_config = HandlerConfig {
-            classifier: self.config.classifier.clone(),
-            notifications: self.config.notifications.clone(),
-            on_block: self.on_block.clone(),
-            on_allow: self.on_allow.clone(),
-            filtering_state: self.config.filtering_state.clone(),
-            database: self.config.database.clone(),
-            nsfw_classifier: Arc::new(RwLock::new(LazyNsfwClassifier::with_defaults())),
-            site_registry: Arc::new(SiteRegistry::with_defaults()),
-        };
+        let handler_config = self.handler_config();
 
         let config_addr = self.config.addr;
+        let keep_warm_interval = self.config.keep_warm_interval;
+        let canary_interval = self.config.canary_interval;
+        let filtering_state = self.config.filtering_state.clone();
 
         let handle = tokio::spawn(async move {
             let handler = ProxyHandler::new(handler_config);
+            let _keep_warm = Self::keep_warm(keep_warm_interval, &handler);
+            let _canary = Self::canary(canary_interval, config_addr, &filtering_state);
 
             let proxy = match Proxy::builder()
                 .with_addr(config_addr)
@@ -299,6 +580,9 @@ impl ProxyServer {
 
             let mut shutdown_rx = shutdown_tx.subscribe();
 
+            filtering_state
+                .live_stats()
+                .set_proxy_addr(Some(config_addr));
             tokio::select! {
                 result = proxy.start() => {
                     if let Err(e) = result {
@@ -309,6 +593,7 @@ impl ProxyServer {
                     tracing::info!("Proxy shutdown signal received");
                 }
             };
+            filtering_state.live_stats().set_proxy_addr(None);
         });
 
         Ok(ProxyHandle {
@@ -365,6 +650,20 @@ mod tests {
             notifications: None,
             filtering_state: FilteringState::new(),
             database: None,
+            site_registry: Arc::new(SiteRegistry::with_defaults()),
+            max_concurrent_connections: Some(DEFAULT_MAX_CONCURRENT_CONNECTIONS),
+            overflow_policy: OverflowPolicy::Queue,
+            classifier_failure_policy: ClassifierFailurePolicy::FailOpen,
+            startup_guard: None,
+            content_length_policy: ContentLengthPolicy::default(),
+            missing_host_policy: MissingHostPolicy::default(),
+            allow_logging: AllowLogging::default(),
+            warn_escalation: None,
+            warn_interstitial: None,
+            block_page_path: None,
+            decision_headers: false,
+            keep_warm_interval: None,
+            canary_interval: None,
         }
     }
 
@@ -387,6 +686,164 @@ mod tests {
         assert!(config.notifications.is_none());
     }
 
+    #[test]
+    fn proxy_config_connection_limit() {
+        let config = test_config()
+            .with_max_concurrent_connections(8)
+            .with_overflow_policy(OverflowPolicy::Reject);
+        let server = ProxyServer::new(config).unwrap();
+
+        let limiter = server.handler_config().connection_limiter.unwrap();
+        assert_eq!(limiter.max(), 8);
+        assert_eq!(limiter.policy(), OverflowPolicy::Reject);
+
+        let config = test_config().without_connection_limit();
+        let server = ProxyServer::new(config).unwrap();
+        assert!(server.handler_config().connection_limiter.is_none());
+    }
+
+    #[test]
+    fn proxy_config_classifier_failure_policy() {
+        let server = ProxyServer::new(test_config()).unwrap();
+        assert_eq!(
+            server.handler_config().classifier_failure_policy,
+            ClassifierFailurePolicy::FailOpen
+        );
+
+        let config =
+            test_config().with_classifier_failure_policy(ClassifierFailurePolicy::FailClosed);
+        let server = ProxyServer::new(config).unwrap();
+        assert_eq!(
+            server.handler_config().classifier_failure_policy,
+            ClassifierFailurePolicy::FailClosed
+        );
+    }
+
+    #[tokio::test]
+    async fn proxy_config_keep_warm_off_by_default() {
+        let handler = ProxyHandler::new(ProxyServer::new(test_config()).unwrap().handler_config());
+        assert!(test_config().keep_warm_interval.is_none());
+        assert!(ProxyServer::keep_warm(None, &handler).is_none());
+
+        let config = test_config().with_keep_warm(Duration::from_secs(30));
+        assert_eq!(config.keep_warm_interval, Some(Duration::from_secs(30)));
+        let task = ProxyServer::keep_warm(config.keep_warm_interval, &handler).unwrap();
+        assert!(task.is_running());
+    }
+
+    #[tokio::test]
+    async fn proxy_config_canary_off_by_default() {
+        let state = FilteringState::new();
+        assert!(test_config().canary_interval.is_none());
+        assert!(ProxyServer::canary(None, test_config().addr, &state).is_none());
+
+        let config = test_config().with_canary(Duration::from_secs(60));
+        assert_eq!(config.canary_interval, Some(Duration::from_secs(60)));
+        let task = ProxyServer::canary(config.canary_interval, config.addr, &state).unwrap();
+        assert!(task.is_running());
+    }
+
+    #[test]
+    fn proxy_config_startup_guard_shares_readiness() {
+        let guard = StartupGuard::new();
+        let server = ProxyServer::new(test_config().with_startup_guard(guard.clone())).unwrap();
+
+        let handler_guard = server.handler_config().startup_guard.unwrap();
+        assert!(!handler_guard.is_ready());
+        guard.mark_ready();
+        assert!(handler_guard.is_ready());
+    }
+
+    #[tokio::test]
+    async fn retried_prompts_are_answered_from_the_cache() {
+        use aegis_core::rule_engine::RuleEngine;
+        use http_body_util::Full;
+        use hyper::body::Bytes;
+
+        let classifier = TieredClassifier::new(TieredClassifierConfig {
+            enable_ml: false,
+            ..TieredClassifierConfig::cached()
+        });
+        let mut config = test_config()
+            .set_filtering_state(FilteringState::with_rule_engine(RuleEngine::content_only()));
+        config.classifier = Arc::new(RwLock::new(classifier));
+        let classifier = config.classifier.clone();
+        let handler = ProxyHandler::new(ProxyServer::new(config).unwrap().handler_config());
+
+        for prompt in ["How do I bake bread?", "how do i  bake bread?"] {
+            let body = serde_json::json!({
+                "model": "gpt-4o",
+                "messages": [{"role": "user", "content": prompt}]
+            });
+            let request = hyper::Request::builder()
+                .method("POST")
+                .uri("https://api.openai.com/v1/chat/completions")
+                .header("Content-Type", "application/json")
+                .body(hudsucker::Body::from(Full::new(Bytes::from(
+                    body.to_string(),
+                ))))
+                .unwrap();
+            handler.process_request(request).await;
+        }
+
+        let stats = classifier.read().stats();
+        assert_eq!(stats.classifications, 2);
+        assert_eq!(stats.cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn stored_sites_reach_the_handler() {
+        use crate::testing::{blocked_status, forwarded_body};
+        use aegis_core::rule_engine::RuleEngine;
+        use aegis_storage::NewSite;
+        use http_body_util::Full;
+        use hyper::body::Bytes;
+
+        let database = Arc::new(Database::in_memory().unwrap());
+        database
+            .create_site(NewSite {
+                pattern: "chat.niche-llm.test".to_string(),
+                name: "Niche LLM".to_string(),
+                category: "consumer".to_string(),
+                parser_id: Some("form".to_string()),
+                enabled: true,
+                source: "custom".to_string(),
+                priority: 100,
+                service_name: Some("NicheChat".to_string()),
+                endpoints: vec!["/api/ask".to_string()],
+            })
+            .unwrap();
+        let config = test_config()
+            .with_database(database)
+            .set_filtering_state(FilteringState::with_rule_engine(RuleEngine::content_only()));
+
+        let services = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = services.clone();
+        let server = ProxyServer::new(config)
+            .unwrap()
+            .on_block(move |prompt, _| seen.lock().push(prompt.service.clone()));
+        let handler = ProxyHandler::new(server.handler_config());
+
+        let request = |path: &str| {
+            hyper::Request::builder()
+                .method("POST")
+                .uri(format!("https://chat.niche-llm.test{}", path))
+                .header("Content-Type", "application/x-www-form-urlencoded")
+                .body(hudsucker::Body::from(Full::new(Bytes::from(
+                    "query=how%20to%20make%20a%20bomb%20to%20kill%20people",
+                ))))
+                .unwrap()
+        };
+
+        let outcome = handler.process_request(request("/api/ask")).await;
+        assert_eq!(blocked_status(&outcome), Some(hyper::StatusCode::FORBIDDEN));
+        assert_eq!(*services.lock(), ["NicheChat"]);
+
+        // Other paths on the site aren't prompt endpoints
+        let outcome = handler.process_request(request("/api/settings")).await;
+        assert!(forwarded_body(outcome).await.is_some());
+    }
+
     #[test]
     fn proxy_server_new() {
         let config = test_config();
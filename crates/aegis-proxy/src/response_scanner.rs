@@ -0,0 +1,251 @@
+//! Text scanning of streamed assistant responses.
+//!
+//! Chat APIs stream completions as SSE (`text/event-stream`) or NDJSON,
+//! one small delta per event. [`ScanningBody`] forwards such a body to the
+//! client untouched while collecting a copy, and hands the copy over once
+//! the stream ends; [`reassemble_completion`] then joins the deltas back
+//! into the completion text for classification.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use hudsucker::Body;
+use hyper::body::{Body as HttpBody, Bytes, Frame, SizeHint};
+use serde_json::Value;
+
+use crate::smart_parser::StreamAccumulator;
+
+/// Callback given the collected stream once it ends.
+type OnComplete = Box<dyn FnOnce(Vec<u8>) + Send + Sync>;
+
+/// Returns true for content types that carry streamed completions.
+pub fn is_streaming_response(content_type: &str) -> bool {
+    let ct = content_type.to_lowercase();
+    ct.contains("text/event-stream")
+        || ct.contains("application/x-ndjson")
+        || ct.contains("application/jsonl")
+}
+
+/// Joins the assistant text deltas in an SSE or NDJSON stream.
+///
+/// Understands OpenAI chat and completions chunks, OpenAI Responses API
+/// events, Anthropic content block deltas, Gemini candidates, and Ollama
+/// chat/generate lines. Other events (role markers, usage, `[DONE]`) are
+/// skipped, as is a trailing partial line.
+pub fn reassemble_completion(stream: &[u8]) -> String {
+    let text = String::from_utf8_lossy(stream);
+    let mut completion = String::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        let payload = line.strip_prefix("data:").unwrap_or(line).trim();
+        if payload.is_empty() || payload == "[DONE]" || !payload.starts_with('{') {
+            continue;
+        }
+        if let Ok(event) = serde_json::from_str::<Value>(payload) {
+            push_deltas(&event, &mut completion);
+        }
+    }
+
+    completion
+}
+
+/// Appends the text carried by one stream event.
+fn push_deltas(event: &Value, out: &mut String) {
+    let mut push = |value: &Value| {
+        if let Some(text) = value.as_str() {
+            out.push_str(text);
+        }
+    };
+
+    // OpenAI chat / completions
+    if let Some(choices) = event.get("choices").and_then(Value::as_array) {
+        for choice in choices {
+            push(&choice["delta"]["content"]);
+            push(&choice["text"]);
+            push(&choice["message"]["content"]);
+        }
+        return;
+    }
+
+    // Gemini
+    if let Some(candidates) = event.get("candidates").and_then(Value::as_array) {
+        for candidate in candidates {
+            if let Some(parts) = candidate["content"]["parts"].as_array() {
+                parts.iter().for_each(|part| push(&part["text"]));
+            }
+        }
+        return;
+    }
+
+    match event.get("delta") {
+        // OpenAI Responses API: {"type": "response.output_text.delta", "delta": "..."}
+        Some(delta @ Value::String(_)) => push(delta),
+        // Anthropic: {"type": "content_block_delta", "delta": {"text": "..."}}
+        Some(delta) => push(&delta["text"]),
+        // Ollama chat / generate
+        None => {
+            push(&event["message"]["content"]);
+            push(&event["response"]);
+        }
+    }
+}
+
+/// Response body that forwards frames unchanged while collecting their data.
+///
+/// The callback runs once, when the stream ends or the body is dropped
+/// (e.g., the client went away mid-stream), with up to the accumulator's
+/// cap of collected bytes. It runs on the polling task, so it should hand
+/// slow work off rather than do it inline.
+pub(crate) struct ScanningBody {
+    inner: Body,
+    collected: StreamAccumulator,
+    on_complete: Option<OnComplete>,
+}
+
+impl ScanningBody {
+    /// Wraps `inner`, calling `on_complete` with the collected bytes at the end.
+    pub(crate) fn new<F>(inner: Body, on_complete: F) -> Self
+    where
+        F: FnOnce(Vec<u8>) + Send + Sync + 'static,
+    {
+        Self {
+            inner,
+            collected: StreamAccumulator::new(),
+            on_complete: Some(Box::new(on_complete)),
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(on_complete) = self.on_complete.take() {
+            let collected = std::mem::take(&mut self.collected);
+            if collected.is_truncated() {
+                tracing::debug!(
+                    "Response stream overflowed the scan buffer; {} bytes not scanned",
+                    collected.dropped_bytes()
+                );
+            }
+            on_complete(collected.content().to_vec());
+        }
+    }
+}
+
+impl HttpBody for ScanningBody {
+    type Data = Bytes;
+    type Error = hudsucker::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = &mut *self;
+        let poll = Pin::new(&mut this.inner).poll_frame(cx);
+        match &poll {
+            Poll::Ready(Some(Ok(frame))) => {
+                if let Some(data) = frame.data_ref() {
+                    this.collected.append(data);
+                }
+            }
+            Poll::Ready(None) => this.finish(),
+            _ => {}
+        }
+        poll
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Drop for ScanningBody {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::{BodyExt, Full};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn streaming_content_types() {
+        assert!(is_streaming_response("text/event-stream; charset=utf-8"));
+        assert!(is_streaming_response("application/x-ndjson"));
+        assert!(!is_streaming_response("application/json"));
+        assert!(!is_streaming_response("image/png"));
+    }
+
+    #[test]
+    fn reassembles_openai_sse() {
+        let stream = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"lo there\"}}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        assert_eq!(reassemble_completion(stream.as_bytes()), "Hello there");
+    }
+
+    #[test]
+    fn reassembles_anthropic_and_responses_api_events() {
+        let anthropic = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"Once \"}}\n\n",
+            "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"upon\"}}\n\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"}}\n\n",
+        );
+        assert_eq!(reassemble_completion(anthropic.as_bytes()), "Once upon");
+
+        let responses = "data: {\"type\":\"response.output_text.delta\",\"delta\":\"Hi\"}\n\n";
+        assert_eq!(reassemble_completion(responses.as_bytes()), "Hi");
+    }
+
+    #[test]
+    fn reassembles_ndjson_and_gemini() {
+        let ollama = concat!(
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"a\"},\"done\":false}\n",
+            "{\"message\":{\"role\":\"assistant\",\"content\":\"b\"},\"done\":false}\n",
+            "{\"done\":true}\n",
+            "{\"message\":{\"content\":\"cut of",
+        );
+        assert_eq!(reassemble_completion(ollama.as_bytes()), "ab");
+
+        let gemini = "data: {\"candidates\":[{\"content\":{\"parts\":[{\"text\":\"x\"},{\"text\":\"y\"}]}}]}\n";
+        assert_eq!(reassemble_completion(gemini.as_bytes()), "xy");
+    }
+
+    #[tokio::test]
+    async fn scanning_body_forwards_and_reports_once() {
+        let seen: Arc<Mutex<Vec<Vec<u8>>>> = Arc::default();
+        let seen_clone = seen.clone();
+        let inner = Body::from(Full::new(Bytes::from_static(b"data: {}\n\n")));
+
+        let body = ScanningBody::new(inner, move |collected| {
+            seen_clone.lock().unwrap().push(collected);
+        });
+        let forwarded = body.collect().await.unwrap().to_bytes();
+
+        assert_eq!(&forwarded[..], b"data: {}\n\n");
+        assert_eq!(*seen.lock().unwrap(), vec![b"data: {}\n\n".to_vec()]);
+    }
+
+    #[test]
+    fn dropped_body_still_reports() {
+        let seen = Arc::new(Mutex::new(None));
+        let seen_clone = seen.clone();
+
+        drop(ScanningBody::new(Body::empty(), move |collected| {
+            *seen_clone.lock().unwrap() = Some(collected);
+        }));
+
+        assert_eq!(*seen.lock().unwrap(), Some(Vec::new()));
+    }
+}
@@ -48,6 +48,8 @@ pub struct SetupResult {
     pub message: String,
     /// Whether admin/root privileges are required.
     pub needs_admin: bool,
+    /// Changes undone after a later step failed (e.g., "system proxy disabled").
+    pub rolled_back: Vec<String>,
 }
 
 impl SetupResult {
@@ -56,6 +58,7 @@ impl SetupResult {
             success: true,
             message: message.into(),
             needs_admin: false,
+            rolled_back: Vec::new(),
         }
     }
 
@@ -64,6 +67,7 @@ impl SetupResult {
             success: false,
             message: message.into(),
             needs_admin: false,
+            rolled_back: Vec::new(),
         }
     }
 
@@ -73,8 +77,31 @@ impl SetupResult {
             success: false,
             message: message.into(),
             needs_admin: true,
+            rolled_back: Vec::new(),
         }
     }
+
+    /// Returns true if the failure left nothing half-configured because
+    /// earlier changes were undone.
+    pub fn was_rolled_back(&self) -> bool {
+        !self.rolled_back.is_empty()
+    }
+
+    /// Undoes the system proxy after this step failed, recording the outcome.
+    fn roll_back_proxy(mut self, ops: &impl SetupOps) -> Self {
+        let undo = ops.disable_proxy();
+        if undo.success {
+            self.message
+                .push_str("; rolled back: system proxy disabled");
+            self.rolled_back.push("system proxy disabled".to_string());
+        } else {
+            self.message.push_str(&format!(
+                "; rollback failed, system proxy may still be set: {}",
+                undo.message
+            ));
+        }
+        self
+    }
 }
 
 // ============================================================================
@@ -238,21 +265,68 @@ pub fn is_proxy_enabled(host: &str, port: u16) -> bool {
 // Full Setup/Teardown
 // ============================================================================
 
+/// System changes made by setup, abstracted so rollback can be tested.
+trait SetupOps {
+    fn install_ca(&self, cert_path: &Path) -> SetupResult;
+    fn enable_proxy(&self, host: &str, port: u16) -> SetupResult;
+    fn disable_proxy(&self) -> SetupResult;
+    fn is_proxy_enabled(&self, host: &str, port: u16) -> bool;
+}
+
+/// The real system trust store and proxy settings.
+struct SystemOps;
+
+impl SetupOps for SystemOps {
+    fn install_ca(&self, cert_path: &Path) -> SetupResult {
+        install_ca_certificate(cert_path)
+    }
+
+    fn enable_proxy(&self, host: &str, port: u16) -> SetupResult {
+        enable_system_proxy(host, port)
+    }
+
+    fn disable_proxy(&self) -> SetupResult {
+        disable_system_proxy()
+    }
+
+    fn is_proxy_enabled(&self, host: &str, port: u16) -> bool {
+        is_proxy_enabled(host, port)
+    }
+}
+
 /// Performs full proxy setup (CA + system proxy).
+///
+/// Interception without a trusted CA breaks every HTTPS site, so setup
+/// never leaves the system proxy pointing at Aegis when a step fails: if
+/// the CA can't be installed, a proxy left over from an earlier run is
+/// disabled, and if enabling the proxy fails, any partial proxy settings
+/// are cleared. The failing step's result lists what was rolled back. An
+/// installed CA is kept, since it is harmless without the proxy.
 pub fn setup_proxy(config: &ProxySetup) -> Vec<SetupResult> {
-    let mut results = Vec::new();
+    setup_proxy_with(&SystemOps, config)
+}
 
+fn setup_proxy_with(ops: &impl SetupOps, config: &ProxySetup) -> Vec<SetupResult> {
     // Step 1: Install CA certificate
-    let ca_result = install_ca_certificate(&config.ca_cert_path);
-    results.push(ca_result.clone());
-
-    // Step 2: Configure system proxy (only if CA succeeded)
-    if ca_result.success {
-        let proxy_result = enable_system_proxy(&config.host, config.port);
-        results.push(proxy_result);
+    let ca_result = ops.install_ca(&config.ca_cert_path);
+    if !ca_result.success {
+        let ca_result = if ops.is_proxy_enabled(&config.host, config.port) {
+            ca_result.roll_back_proxy(ops)
+        } else {
+            ca_result
+        };
+        return vec![ca_result];
     }
 
-    results
+    // Step 2: Configure system proxy
+    let proxy_result = ops.enable_proxy(&config.host, config.port);
+    let proxy_result = if proxy_result.success {
+        proxy_result
+    } else {
+        proxy_result.roll_back_proxy(ops)
+    };
+
+    vec![ca_result, proxy_result]
 }
 
 /// Removes proxy setup (disables proxy + optionally removes CA).
@@ -1041,4 +1115,99 @@ mod tests {
         assert!(!result.success);
         assert!(result.needs_admin);
     }
+
+    /// Records proxy state in memory instead of touching the system.
+    struct MockOps {
+        ca_fails: bool,
+        proxy_fails: bool,
+        proxy_enabled: std::cell::Cell<bool>,
+    }
+
+    impl MockOps {
+        fn new(ca_fails: bool, proxy_fails: bool, proxy_enabled: bool) -> Self {
+            Self {
+                ca_fails,
+                proxy_fails,
+                proxy_enabled: std::cell::Cell::new(proxy_enabled),
+            }
+        }
+    }
+
+    impl SetupOps for MockOps {
+        fn install_ca(&self, _cert_path: &Path) -> SetupResult {
+            if self.ca_fails {
+                SetupResult::needs_admin("Failed to install CA: permission denied")
+            } else {
+                SetupResult::success("CA installed")
+            }
+        }
+
+        fn enable_proxy(&self, _host: &str, _port: u16) -> SetupResult {
+            // A failing enable still leaves partial settings behind
+            self.proxy_enabled.set(true);
+            if self.proxy_fails {
+                SetupResult::failure("Failed to set HTTPS proxy")
+            } else {
+                SetupResult::success("Proxy enabled")
+            }
+        }
+
+        fn disable_proxy(&self) -> SetupResult {
+            self.proxy_enabled.set(false);
+            SetupResult::success("Proxy disabled")
+        }
+
+        fn is_proxy_enabled(&self, _host: &str, _port: u16) -> bool {
+            self.proxy_enabled.get()
+        }
+    }
+
+    fn config() -> ProxySetup {
+        ProxySetup::new("127.0.0.1", 8766, "/tmp/ca.crt")
+    }
+
+    #[test]
+    fn test_setup_rolls_back_proxy_when_ca_install_fails() {
+        // Proxy left enabled by an earlier run
+        let ops = MockOps::new(true, false, true);
+
+        let results = setup_proxy_with(&ops, &config());
+
+        assert_eq!(results.len(), 1);
+        assert!(!results[0].success);
+        assert!(results[0].needs_admin);
+        assert_eq!(results[0].rolled_back, vec!["system proxy disabled"]);
+        assert!(results[0]
+            .message
+            .ends_with("; rolled back: system proxy disabled"));
+        assert!(!ops.is_proxy_enabled("127.0.0.1", 8766));
+    }
+
+    #[test]
+    fn test_setup_rolls_back_partial_proxy_settings() {
+        let ops = MockOps::new(false, true, false);
+
+        let results = setup_proxy_with(&ops, &config());
+
+        assert_eq!(results.len(), 2);
+        assert!(results[0].success);
+        assert!(!results[1].success);
+        assert!(results[1].was_rolled_back());
+        assert!(!ops.is_proxy_enabled("127.0.0.1", 8766));
+    }
+
+    #[test]
+    fn test_setup_success_rolls_nothing_back() {
+        let ops = MockOps::new(false, false, false);
+
+        let results = setup_proxy_with(&ops, &config());
+
+        assert!(results.iter().all(|r| r.success && !r.was_rolled_back()));
+        assert!(ops.is_proxy_enabled("127.0.0.1", 8766));
+
+        // Nothing to undo when the CA fails before the proxy was ever set
+        let ops = MockOps::new(true, false, false);
+        let results = setup_proxy_with(&ops, &config());
+        assert!(!results[0].was_rolled_back());
+    }
 }
@@ -1,7 +1,8 @@
 //! Smart Content Parsing (F026).
 //!
 //! Robust prompt extraction from diverse LLM payload formats with an extensible
-//! parser registry. Supports JSON, form data, multipart, NDJSON, SSE, and raw text.
+//! parser registry. Supports JSON, form data, multipart, NDJSON, gRPC,
+//! gRPC-Web, SSE, and raw text.
 //!
 //! ## Features
 //!
@@ -12,12 +13,17 @@
 //! - Zero-copy parsing where possible
 //! - Confidence scoring for extraction quality
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
+use base64::Engine;
+use parking_lot::Mutex;
+
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::domains::service_name;
+use crate::extractor::{is_thread_message_path, model_name};
 
 // =============================================================================
 // Core Types
@@ -29,6 +35,25 @@ pub const DEFAULT_MAX_PAYLOAD_SIZE: usize = 1024 * 1024;
 /// Minimum string length to extract in fallback mode.
 pub const MIN_EXTRACTED_STRING_LENGTH: usize = 10;
 
+/// Confidence assigned to prompts found inside a framework envelope.
+///
+/// Envelope detection is heuristic, so this sits between a direct format
+/// match (0.95) and whole-body fallback extraction (0.5).
+pub const ENVELOPE_CONFIDENCE: f32 = 0.7;
+
+/// Confidence assigned to prompts found in gRPC-Web payloads.
+///
+/// Without a schema any long enough string field looks like a prompt, so
+/// this sits below gRPC frame extraction (0.5).
+pub const GRPC_WEB_CONFIDENCE: f32 = 0.4;
+
+/// Keys under which frameworks (Vercel AI SDK, tRPC, proxy routes) nest the
+/// provider payload.
+const ENVELOPE_KEYS: &[&str] = &["body", "data", "payload", "json", "request", "input"];
+
+/// Maximum envelope nesting depth to unwrap.
+const MAX_ENVELOPE_DEPTH: usize = 3;
+
 /// Context for parsing a request payload.
 #[derive(Debug, Clone)]
 pub struct ParseContext {
@@ -46,6 +71,8 @@ pub struct ParseContext {
     pub scan_full_history: bool,
     /// Maximum payload size to process.
     pub max_payload_size: usize,
+    /// Whether [`GrpcWebParser`] extracts prompts from gRPC-Web payloads.
+    pub grpc_web: bool,
 }
 
 impl ParseContext {
@@ -59,6 +86,7 @@ impl ParseContext {
             method: "POST".to_string(),
             scan_full_history: true,
             max_payload_size: DEFAULT_MAX_PAYLOAD_SIZE,
+            grpc_web: false,
         }
     }
 
@@ -92,6 +120,12 @@ impl ParseContext {
         self
     }
 
+    /// Sets whether gRPC-Web payloads are decoded by [`GrpcWebParser`].
+    pub fn with_grpc_web(mut self, enabled: bool) -> Self {
+        self.grpc_web = enabled;
+        self
+    }
+
     /// Returns the MIME type without parameters (e.g., "application/json" from "application/json; charset=utf-8").
     pub fn mime_type(&self) -> Option<&str> {
         self.content_type
@@ -120,6 +154,9 @@ pub struct ExtractedPrompt {
     pub role: Option<String>,
     /// Position in conversation (0 = oldest).
     pub position: usize,
+    /// The model the request asked for, if the payload named one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 impl ExtractedPrompt {
@@ -130,6 +167,7 @@ impl ExtractedPrompt {
             is_current,
             role: None,
             position: 0,
+            model: None,
         }
     }
 
@@ -145,6 +183,12 @@ impl ExtractedPrompt {
         self
     }
 
+    /// Sets the requested model.
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
+    }
+
     /// Checks if this is a user message.
     pub fn is_user_message(&self) -> bool {
         self.role.as_deref() == Some("user")
@@ -170,6 +214,8 @@ pub enum ParseWarning {
     BinarySkipped,
     /// Unknown format, using fallback extraction.
     FallbackUsed,
+    /// Prompt was found inside a framework envelope (e.g., Vercel AI SDK).
+    EnvelopeUnwrapped { key: String },
 }
 
 /// Result of parsing a payload.
@@ -263,6 +309,69 @@ impl ParseResult {
         }
         parts.join("\n")
     }
+
+    /// Builds classification text that favors the current prompt without
+    /// growing the input.
+    ///
+    /// Current prompts are kept whole (with none marked, the last prompt
+    /// counts as current). History is cut to `history_weight` of its length,
+    /// clamped to 0.0–1.0, dropping the oldest text first so the context
+    /// nearest the current prompt survives. The result never exceeds
+    /// [`DEFAULT_MAX_PAYLOAD_SIZE`] bytes; current prompts longer than that
+    /// are cut at the end.
+    pub fn classification_text(&self, history_weight: f32) -> String {
+        self.classification_text_within(history_weight, DEFAULT_MAX_PAYLOAD_SIZE)
+    }
+
+    fn classification_text_within(&self, history_weight: f32, max_len: usize) -> String {
+        let (mut current, mut history): (Vec<&str>, Vec<&str>) = (Vec::new(), Vec::new());
+        for prompt in &self.prompts {
+            if prompt.is_current {
+                current.push(&prompt.text);
+            } else {
+                history.push(&prompt.text);
+            }
+        }
+        if current.is_empty() {
+            current.extend(history.pop());
+        }
+
+        let current = current.join("\n");
+        let current = truncate_to_char_boundary(&current, max_len);
+
+        // History gets its weighted share of whatever room is left
+        let history = history.join("\n");
+        let weight = if history_weight.is_nan() {
+            0.0
+        } else {
+            history_weight.clamp(0.0, 1.0)
+        };
+        let room = max_len.saturating_sub(current.len() + 1);
+        let budget = ((history.len() as f64 * weight as f64) as usize).min(room);
+        let mut start = history.len() - budget;
+        while !history.is_char_boundary(start) {
+            start += 1;
+        }
+        let recent_history = &history[start..];
+
+        if recent_history.is_empty() {
+            current.to_string()
+        } else {
+            format!("{}\n{}", recent_history, current)
+        }
+    }
+}
+
+/// Returns the longest prefix of `text` that fits in `max_len` bytes.
+fn truncate_to_char_boundary(text: &str, max_len: usize) -> &str {
+    if text.len() <= max_len {
+        return text;
+    }
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
 }
 
 // =============================================================================
@@ -388,6 +497,9 @@ impl JsonParser {
     fn extract_openai(&self, json: &Value, context: &ParseContext) -> Vec<ExtractedPrompt> {
         let messages = match json.get("messages").and_then(|m| m.as_array()) {
             Some(m) => m,
+            None if is_thread_message_path(&context.path) => {
+                return self.extract_thread_message(json).into_iter().collect()
+            }
             None => return Vec::new(),
         };
 
@@ -407,8 +519,11 @@ impl JsonParser {
                     return None;
                 }
 
-                // Extract content
-                let text = self.extract_content(msg.get("content")?)?;
+                // Extract content, falling back to Vercel AI SDK UI message parts
+                let text = msg
+                    .get("content")
+                    .and_then(|c| self.extract_content(c))
+                    .or_else(|| self.extract_content(msg.get("parts")?))?;
                 let is_current = if context.scan_full_history {
                     idx == total - 1 || self.is_last_user_message(messages, idx)
                 } else {
@@ -424,6 +539,20 @@ impl JsonParser {
             .collect()
     }
 
+    /// Extracts the single message posted to an Assistants API thread,
+    /// which is always the current prompt.
+    fn extract_thread_message(&self, json: &Value) -> Option<ExtractedPrompt> {
+        if json.get("role")?.as_str()? != "user" {
+            return None;
+        }
+        let text = self.extract_content(json.get("content")?)?;
+        Some(
+            ExtractedPrompt::new(text, true)
+                .with_role("user")
+                .with_position(0),
+        )
+    }
+
     /// Checks if this is the last user message in the array.
     fn is_last_user_message(&self, messages: &[Value], current_idx: usize) -> bool {
         for msg in messages.iter().skip(current_idx + 1) {
@@ -548,6 +677,53 @@ impl JsonParser {
             .collect()
     }
 
+    /// Finds a provider payload nested inside a framework envelope.
+    ///
+    /// Frameworks such as the Vercel AI SDK wrap the real request under keys
+    /// like `body` or `data`, sometimes as a stringified JSON document. Returns
+    /// the inner payload and the envelope path that led to it.
+    fn unwrap_envelope(json: &Value, depth: usize) -> Option<(Value, String)> {
+        if depth >= MAX_ENVELOPE_DEPTH {
+            return None;
+        }
+
+        let obj = json.as_object()?;
+        for key in ENVELOPE_KEYS {
+            let inner = match obj.get(*key) {
+                Some(Value::String(s)) => match serde_json::from_str::<Value>(s) {
+                    Ok(v) => v,
+                    Err(_) => continue,
+                },
+                Some(v @ Value::Object(_)) => v.clone(),
+                _ => continue,
+            };
+
+            if Self::is_provider_shape(&inner) {
+                return Some((inner, key.to_string()));
+            }
+            if let Some((nested, path)) = Self::unwrap_envelope(&inner, depth + 1) {
+                return Some((nested, format!("{}.{}", key, path)));
+            }
+        }
+
+        None
+    }
+
+    /// Checks if a JSON value has a known provider request shape.
+    fn is_provider_shape(json: &Value) -> bool {
+        json.get("messages").is_some_and(|m| m.is_array())
+            || json.get("contents").is_some_and(|c| c.is_array())
+    }
+
+    /// Extracts prompts from an unwrapped envelope payload based on its shape.
+    fn extract_unwrapped(&self, json: &Value, context: &ParseContext) -> Vec<ExtractedPrompt> {
+        if json.get("contents").is_some() {
+            self.extract_google(json, context)
+        } else {
+            self.extract_openai(json, context)
+        }
+    }
+
     /// Extracts prompts from generic JSON format.
     fn extract_generic(&self, json: &Value, _context: &ParseContext) -> Vec<ExtractedPrompt> {
         // Try common field names
@@ -642,6 +818,15 @@ impl PayloadParser for JsonParser {
             }
         };
 
+        // Every prompt is tagged with the model the request asked for
+        let model = model_name(&json);
+        let with_model = |prompts: Vec<ExtractedPrompt>, model: Option<String>| {
+            prompts
+                .into_iter()
+                .map(|p| p.with_model(model.clone()))
+                .collect::<Vec<_>>()
+        };
+
         // Extract based on host
         let host = &context.host;
         let prompts = if host.contains("openai.com") || host.contains("chatgpt.com") {
@@ -655,18 +840,30 @@ impl PayloadParser for JsonParser {
         };
 
         if !prompts.is_empty() {
-            result.prompts = prompts;
+            result.prompts = with_model(prompts, model);
             result.confidence = 0.95;
-        } else {
-            // Fallback: extract all text
-            let all_text = extract_all_text_from_json(&json);
-            if !all_text.is_empty() {
-                result.prompts = vec![ExtractedPrompt::new(all_text, true)];
-                result.confidence = 0.5;
-                result.add_warning(ParseWarning::FallbackUsed);
+            return result;
+        }
+
+        // Framework envelope: unwrap and retry with the inner payload's shape
+        if let Some((inner, key)) = Self::unwrap_envelope(&json, 0) {
+            let prompts = self.extract_unwrapped(&inner, context);
+            if !prompts.is_empty() {
+                result.prompts = with_model(prompts, model_name(&inner).or(model));
+                result.confidence = ENVELOPE_CONFIDENCE;
+                result.add_warning(ParseWarning::EnvelopeUnwrapped { key });
+                return result;
             }
         }
 
+        // Fallback: extract all text
+        let all_text = extract_all_text_from_json(&json);
+        if !all_text.is_empty() {
+            result.prompts = vec![ExtractedPrompt::new(all_text, true).with_model(model)];
+            result.confidence = 0.5;
+            result.add_warning(ParseWarning::FallbackUsed);
+        }
+
         result
     }
 
@@ -1070,6 +1267,197 @@ impl PayloadParser for SseParser {
     }
 }
 
+/// gRPC and gRPC-Web parser (e.g., Vertex AI streaming).
+///
+/// Walks the length-prefixed message framing (1-byte flags, 4-byte
+/// big-endian length) and pulls UTF-8 text runs out of each protobuf
+/// message without a schema. Compressed frames can't be read and are
+/// skipped; gRPC-Web trailer frames are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct GrpcParser;
+
+/// gRPC frame flag for a compressed message.
+const GRPC_COMPRESSED_FLAG: u8 = 0x01;
+
+/// gRPC-Web frame flag for a trailers frame.
+const GRPC_WEB_TRAILERS_FLAG: u8 = 0x80;
+
+impl PayloadParser for GrpcParser {
+    fn name(&self) -> &str {
+        "grpc"
+    }
+
+    fn can_parse(&self, content_type: &str, _host: &str) -> bool {
+        content_type
+            .to_lowercase()
+            .trim_start()
+            .starts_with("application/grpc")
+    }
+
+    fn parse(&self, body: &[u8], context: &ParseContext) -> ParseResult {
+        let service = service_name(&context.host);
+        let mut result = ParseResult::empty(self.name(), service);
+
+        let texts = grpc_frame_texts(body, &mut result);
+        result.prompts = grpc_prompts(texts);
+        if !result.prompts.is_empty() {
+            result.confidence = 0.5;
+        }
+
+        result
+    }
+
+    fn priority(&self) -> i32 {
+        88
+    }
+}
+
+/// gRPC-Web parser for browser-facing gRPC endpoints.
+///
+/// Decodes both the binary (`application/grpc-web`, `+proto`) and the base64
+/// text (`application/grpc-web-text`) encodings, then walks the frames like
+/// [`GrpcParser`], tagging prompts with [`GRPC_WEB_CONFIDENCE`]. Only runs
+/// when the context enables it ([`ParseContext::with_grpc_web`]). When it is
+/// disabled, or the payload can't be decoded, it extracts nothing and the
+/// registry falls through to the next parser.
+#[derive(Debug, Clone, Default)]
+pub struct GrpcWebParser;
+
+impl PayloadParser for GrpcWebParser {
+    fn name(&self) -> &str {
+        "grpc-web"
+    }
+
+    fn can_parse(&self, content_type: &str, _host: &str) -> bool {
+        content_type
+            .to_lowercase()
+            .trim_start()
+            .starts_with("application/grpc-web")
+    }
+
+    fn parse(&self, body: &[u8], context: &ParseContext) -> ParseResult {
+        let service = service_name(&context.host);
+        let mut result = ParseResult::empty(self.name(), service);
+        if !context.grpc_web {
+            return result;
+        }
+
+        let is_text = context
+            .mime_type()
+            .is_some_and(|mime| mime.to_lowercase().starts_with("application/grpc-web-text"));
+        let decoded;
+        let frames = if is_text {
+            match decode_grpc_web_text(body) {
+                Some(bytes) => {
+                    decoded = bytes;
+                    &decoded[..]
+                }
+                None => {
+                    result.add_warning(ParseWarning::MalformedContent {
+                        details: "Invalid base64 in gRPC-Web text payload".to_string(),
+                    });
+                    return result;
+                }
+            }
+        } else {
+            body
+        };
+
+        let texts = grpc_frame_texts(frames, &mut result);
+        result.prompts = grpc_prompts(texts);
+        if !result.prompts.is_empty() {
+            result.confidence = GRPC_WEB_CONFIDENCE;
+        }
+
+        result
+    }
+
+    fn priority(&self) -> i32 {
+        89
+    }
+}
+
+/// Walks length-prefixed gRPC frames, returning the text runs of each
+/// uncompressed message and noting malformed or skipped frames on `result`.
+fn grpc_frame_texts(body: &[u8], result: &mut ParseResult) -> Vec<String> {
+    let mut texts = Vec::new();
+    let mut rest = body;
+    while !rest.is_empty() {
+        if rest.len() < 5 {
+            result.add_warning(ParseWarning::MalformedContent {
+                details: "Incomplete gRPC frame header".to_string(),
+            });
+            break;
+        }
+
+        let flags = rest[0];
+        let length = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        let available = rest.len() - 5;
+        if length > available {
+            result.add_warning(ParseWarning::MalformedContent {
+                details: format!(
+                    "gRPC frame declares {} bytes but only {} present",
+                    length, available
+                ),
+            });
+        }
+        let end = 5 + length.min(available);
+        let message = &rest[5..end];
+        rest = &rest[end..];
+
+        if flags & GRPC_WEB_TRAILERS_FLAG != 0 {
+            continue;
+        }
+        if flags & GRPC_COMPRESSED_FLAG != 0 {
+            if !result.warnings.contains(&ParseWarning::BinarySkipped) {
+                result.add_warning(ParseWarning::BinarySkipped);
+            }
+            continue;
+        }
+        texts.extend(extract_text_runs(message));
+    }
+    texts
+}
+
+/// Turns text runs into prompts, the last one being current.
+fn grpc_prompts(texts: Vec<String>) -> Vec<ExtractedPrompt> {
+    let total = texts.len();
+    texts
+        .into_iter()
+        .enumerate()
+        .map(|(idx, text)| ExtractedPrompt::new(text, idx + 1 == total).with_position(idx))
+        .collect()
+}
+
+/// Decodes a `grpc-web-text` body.
+///
+/// Clients may base64-encode each chunk separately, so padding can appear
+/// mid-body; each padded chunk is decoded on its own.
+fn decode_grpc_web_text(body: &[u8]) -> Option<Vec<u8>> {
+    let text: Vec<u8> = body
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+    let mut chunks: Vec<Vec<u8>> = Vec::new();
+    for piece in text.split_inclusive(|&b| b == b'=') {
+        match chunks.last_mut() {
+            Some(last) if piece == b"=" => last.push(b'='),
+            _ => chunks.push(piece.to_vec()),
+        }
+    }
+
+    let mut decoded = Vec::new();
+    for chunk in chunks {
+        decoded.extend(
+            base64::engine::general_purpose::STANDARD
+                .decode(chunk)
+                .ok()?,
+        );
+    }
+    Some(decoded)
+}
+
 /// Fallback parser that extracts all text content.
 #[derive(Debug, Clone, Default)]
 pub struct FallbackParser;
@@ -1145,6 +1533,37 @@ impl PayloadParser for FallbackParser {
 // Helper Functions
 // =============================================================================
 
+/// Returns runs of printable UTF-8 text in binary data (e.g., protobuf
+/// string fields), trimmed, that are at least
+/// [`MIN_EXTRACTED_STRING_LENGTH`] bytes long.
+fn extract_text_runs(data: &[u8]) -> Vec<String> {
+    let mut runs = Vec::new();
+    let mut current = String::new();
+    let mut flush = |current: &mut String| {
+        let run = current.trim();
+        if run.len() >= MIN_EXTRACTED_STRING_LENGTH {
+            runs.push(run.to_string());
+        }
+        current.clear();
+    };
+
+    for chunk in data.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if c.is_control() && !matches!(c, '\t' | '\n' | '\r') {
+                flush(&mut current);
+            } else {
+                current.push(c);
+            }
+        }
+        if !chunk.invalid().is_empty() {
+            flush(&mut current);
+        }
+    }
+    flush(&mut current);
+
+    runs
+}
+
 /// Checks if content appears to be binary.
 fn is_binary_content(body: &[u8]) -> bool {
     if body.is_empty() {
@@ -1177,10 +1596,8 @@ fn extract_all_text_from_json(value: &Value) -> String {
 /// Recursively collects all string values.
 fn collect_text_recursive(value: &Value, texts: &mut Vec<String>) {
     match value {
-        Value::String(s) => {
-            if s.len() >= MIN_EXTRACTED_STRING_LENGTH && !looks_like_id(s) {
-                texts.push(s.clone());
-            }
+        Value::String(s) if s.len() >= MIN_EXTRACTED_STRING_LENGTH && !looks_like_id(s) => {
+            texts.push(s.clone());
         }
         Value::Array(arr) => {
             for item in arr {
@@ -1228,16 +1645,47 @@ fn is_metadata_key(key: &str) -> bool {
 // Parser Registry
 // =============================================================================
 
+/// Lowercases a host pattern and strips a leading `*.` or `.` and trailing `.`.
+fn normalize_host_pattern(pattern: &str) -> String {
+    let pattern = pattern.trim().to_lowercase();
+    let pattern = pattern.strip_prefix("*.").unwrap_or(&pattern);
+    pattern.trim_matches('.').to_string()
+}
+
+/// Returns true if `host` is `pattern` or a subdomain of it.
+///
+/// `pattern` must already be normalized; any port on `host` is ignored.
+fn host_matches(host: &str, pattern: &str) -> bool {
+    let host = host.rsplit_once(':').map_or(host, |(name, port)| {
+        if port.bytes().all(|b| b.is_ascii_digit()) {
+            name
+        } else {
+            host
+        }
+    });
+    let host = host.trim_end_matches('.').to_lowercase();
+    host == pattern
+        || host
+            .strip_suffix(pattern)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
 /// Registry of payload parsers with priority ordering.
+///
+/// Parsers registered with [`ParserRegistry::register_for_host`] are only
+/// consulted for matching hosts, and are tried before the global parsers.
 #[derive(Clone)]
 pub struct ParserRegistry {
     parsers: Vec<Arc<dyn PayloadParser>>,
+    /// Host-scoped overrides as (normalized pattern, parser), most specific first.
+    host_parsers: Vec<(String, Arc<dyn PayloadParser>)>,
 }
 
 impl std::fmt::Debug for ParserRegistry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ParserRegistry")
             .field("parsers", &self.parsers.len())
+            .field("host_parsers", &self.host_parsers.len())
             .finish()
     }
 }
@@ -1253,6 +1701,7 @@ impl ParserRegistry {
     pub fn new() -> Self {
         Self {
             parsers: Vec::new(),
+            host_parsers: Vec::new(),
         }
     }
 
@@ -1261,6 +1710,8 @@ impl ParserRegistry {
         let mut registry = Self::new();
         registry.register(Arc::new(JsonParser));
         registry.register(Arc::new(NdjsonParser));
+        registry.register(Arc::new(GrpcParser));
+        registry.register(Arc::new(GrpcWebParser));
         registry.register(Arc::new(SseParser));
         registry.register(Arc::new(FormParser));
         registry.register(Arc::new(MultipartParser));
@@ -1277,11 +1728,67 @@ impl ParserRegistry {
             .sort_by_key(|p| std::cmp::Reverse(p.priority()));
     }
 
+    /// Registers a parser consulted only for hosts matching `host_pattern`.
+    ///
+    /// The pattern is a domain suffix matched on label boundaries, ignoring
+    /// case, a leading `.` or `*.`, and the request's port: `perplexity.ai`
+    /// matches `perplexity.ai` and `www.perplexity.ai`, but not
+    /// `notperplexity.ai`. Host parsers are tried before the global ones,
+    /// longer patterns first and then by priority; the parser's own
+    /// [`PayloadParser::can_parse`] must still accept the request, and if it
+    /// extracts no prompts the remaining parsers are tried as usual.
+    pub fn register_for_host(&mut self, host_pattern: &str, parser: Arc<dyn PayloadParser>) {
+        let pattern = normalize_host_pattern(host_pattern);
+        if pattern.is_empty() {
+            tracing::warn!(
+                "ignoring host parser '{}' with empty pattern",
+                parser.name()
+            );
+            return;
+        }
+        self.host_parsers.push((pattern, parser));
+        self.host_parsers.sort_by_key(|(pattern, p)| {
+            (
+                std::cmp::Reverse(pattern.len()),
+                std::cmp::Reverse(p.priority()),
+            )
+        });
+    }
+
+    /// Removes every parser named `name`, global or host-scoped, keeping the
+    /// rest in priority order.
+    ///
+    /// Returns true if any parser was removed.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.parsers.len() + self.host_parsers.len();
+        self.parsers.retain(|p| p.name() != name);
+        self.host_parsers.retain(|(_, p)| p.name() != name);
+        self.parsers.len() + self.host_parsers.len() != before
+    }
+
+    /// Returns the global parser named `name`, if one is registered.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn PayloadParser>> {
+        self.parsers.iter().find(|p| p.name() == name).cloned()
+    }
+
+    /// Returns the names of the registered parsers, in the order they are tried.
+    pub fn parser_names(&self) -> Vec<&str> {
+        self.parsers.iter().map(|p| p.name()).collect()
+    }
+
     /// Parses a payload using the first matching parser.
+    ///
+    /// Host-scoped parsers matching the request's host are tried first.
     pub fn parse(&self, body: &[u8], context: &ParseContext) -> ParseResult {
         let content_type = context.mime_type().unwrap_or("application/octet-stream");
 
-        for parser in &self.parsers {
+        let host_parsers = self
+            .host_parsers
+            .iter()
+            .filter(|(pattern, _)| host_matches(&context.host, pattern))
+            .map(|(_, parser)| parser);
+
+        for parser in host_parsers.chain(&self.parsers) {
             if parser.can_parse(content_type, &context.host) {
                 let result = parser.parse(body, context);
                 if result.has_prompts() {
@@ -1326,11 +1833,16 @@ impl ParserRegistry {
 // =============================================================================
 
 /// Accumulator for streaming content (SSE, chunked).
+///
+/// Chunks can end partway through a multi-byte UTF-8 character; the
+/// continuation bytes arrive with the next chunk.
 #[derive(Debug, Clone)]
 pub struct StreamAccumulator {
     buffer: Vec<u8>,
     max_size: usize,
     content_type: Option<String>,
+    truncated: bool,
+    dropped: usize,
 }
 
 impl Default for StreamAccumulator {
@@ -1346,6 +1858,8 @@ impl StreamAccumulator {
             buffer: Vec::new(),
             max_size: DEFAULT_MAX_PAYLOAD_SIZE,
             content_type: None,
+            truncated: false,
+            dropped: 0,
         }
     }
 
@@ -1355,6 +1869,8 @@ impl StreamAccumulator {
             buffer: Vec::new(),
             max_size,
             content_type: None,
+            truncated: false,
+            dropped: 0,
         }
     }
 
@@ -1364,16 +1880,32 @@ impl StreamAccumulator {
     }
 
     /// Appends a chunk to the buffer.
+    ///
+    /// Returns true if the whole chunk was kept, and false if the buffer
+    /// overflowed: the chunk was cut at `max_size` (never splitting a UTF-8
+    /// character) or dropped because the buffer was already full. After an
+    /// overflow every later chunk is dropped so the content stays
+    /// contiguous; [`is_truncated`](Self::is_truncated) and
+    /// [`dropped_bytes`](Self::dropped_bytes) report it.
     pub fn append(&mut self, chunk: &[u8]) -> bool {
-        if self.buffer.len() + chunk.len() > self.max_size {
-            // Truncate
-            let remaining = self.max_size.saturating_sub(self.buffer.len());
-            self.buffer.extend_from_slice(&chunk[..remaining]);
-            false
-        } else {
+        if self.truncated {
+            self.dropped += chunk.len();
+            return false;
+        }
+
+        let remaining = self.max_size.saturating_sub(self.buffer.len());
+        if chunk.len() <= remaining {
             self.buffer.extend_from_slice(chunk);
-            true
+            return true;
         }
+
+        // `remaining < chunk.len()`, so the slice is in bounds
+        self.buffer.extend_from_slice(&chunk[..remaining]);
+        let partial = incomplete_utf8_tail(&self.buffer);
+        self.buffer.truncate(self.buffer.len() - partial);
+        self.dropped += chunk.len() - remaining + partial;
+        self.truncated = true;
+        false
     }
 
     /// Returns the accumulated content.
@@ -1391,13 +1923,27 @@ impl StreamAccumulator {
         self.buffer.is_empty()
     }
 
+    /// Returns true if content was dropped because the buffer filled up.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns how many appended bytes were dropped by overflow.
+    pub fn dropped_bytes(&self) -> usize {
+        self.dropped
+    }
+
     /// Checks if the stream looks complete (for SSE/NDJSON).
+    ///
+    /// A trailing partial UTF-8 character (still waiting on its next chunk)
+    /// is ignored.
     pub fn looks_complete(&self) -> bool {
-        if self.buffer.is_empty() {
+        let complete = &self.buffer[..self.buffer.len() - incomplete_utf8_tail(&self.buffer)];
+        if complete.is_empty() {
             return false;
         }
 
-        let text = match std::str::from_utf8(&self.buffer) {
+        let text = match std::str::from_utf8(complete) {
             Ok(t) => t,
             Err(_) => return false,
         };
@@ -1418,20 +1964,132 @@ impl StreamAccumulator {
         false
     }
 
+    /// Removes and returns the fully received records, keeping a partial
+    /// trailing record buffered for the next chunk.
+    ///
+    /// With a `text/event-stream` content type, records are SSE events
+    /// ended by a blank line (`\n\n` or `\r\n\r\n`); otherwise they are
+    /// NDJSON lines ended by `\n`. Delimiters and blank records are dropped.
+    /// Truncation is unaffected: once the buffer has filled, later chunks
+    /// are still dropped.
+    pub fn take_complete_records(&mut self) -> Vec<String> {
+        let is_sse = self
+            .content_type
+            .as_deref()
+            .is_some_and(|ct| ct.to_lowercase().contains("text/event-stream"));
+
+        let mut records = Vec::new();
+        let mut start = 0;
+        while let Some((end, delimiter_len)) = find_record_end(&self.buffer[start..], is_sse) {
+            let record = String::from_utf8_lossy(&self.buffer[start..start + end]);
+            let record = record.trim_end_matches('\r');
+            if !record.trim().is_empty() {
+                records.push(record.to_string());
+            }
+            start += end + delimiter_len;
+        }
+
+        self.buffer.drain(..start);
+        records
+    }
+
     /// Clears the buffer.
     pub fn clear(&mut self) {
         self.buffer.clear();
+        self.truncated = false;
+        self.dropped = 0;
     }
 }
 
+/// Finds the end of the first record in `bytes`.
+///
+/// Returns the record length and the length of the delimiter after it.
+fn find_record_end(bytes: &[u8], is_sse: bool) -> Option<(usize, usize)> {
+    let mut from = 0;
+    while let Some(offset) = bytes[from..].iter().position(|&b| b == b'\n') {
+        let newline = from + offset;
+        if !is_sse {
+            return Some((newline, 1));
+        }
+        match &bytes[newline + 1..] {
+            [b'\n', ..] => return Some((newline, 2)),
+            [b'\r', b'\n', ..] => return Some((newline, 3)),
+            _ => from = newline + 1,
+        }
+    }
+    None
+}
+
+/// Returns how many trailing bytes of `bytes` are the start of a UTF-8
+/// character whose remaining bytes haven't arrived.
+fn incomplete_utf8_tail(bytes: &[u8]) -> usize {
+    // An incomplete character has at most 3 of its up to 4 bytes
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        if byte & 0xC0 == 0x80 {
+            continue; // continuation byte
+        }
+        let needed = match byte {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => return 0,
+        };
+        return if back < needed { back } else { 0 };
+    }
+    0
+}
+
 // =============================================================================
 // Smart Parser (Main Entry Point)
 // =============================================================================
 
+/// Extraction counters accumulated by a [`SmartParser`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseStats {
+    /// Payloads handled by each parser, keyed by [`ParseResult::parser_name`]
+    /// (`"none"` when no parser extracted anything).
+    pub by_parser: HashMap<String, u64>,
+    /// Total payload bytes parsed.
+    pub bytes_parsed: u64,
+    /// Payloads that fell back to raw text extraction
+    /// ([`ParseWarning::FallbackUsed`]).
+    pub fallback_count: u64,
+}
+
+impl ParseStats {
+    /// Returns the number of payloads parsed.
+    pub fn total(&self) -> u64 {
+        self.by_parser.values().sum()
+    }
+
+    /// Returns the fraction of payloads (0.0-1.0) handled by `parser_name`.
+    pub fn share(&self, parser_name: &str) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.by_parser.get(parser_name).copied().unwrap_or(0) as f64 / total as f64,
+        }
+    }
+
+    fn record(&mut self, result: &ParseResult, bytes: usize) {
+        *self
+            .by_parser
+            .entry(result.parser_name.clone())
+            .or_default() += 1;
+        self.bytes_parsed += bytes as u64;
+        if result.warnings.contains(&ParseWarning::FallbackUsed) {
+            self.fallback_count += 1;
+        }
+    }
+}
+
 /// Smart content parser that uses the registry.
+///
+/// Clones share the same [`ParseStats`].
 #[derive(Debug, Clone)]
 pub struct SmartParser {
     registry: ParserRegistry,
+    stats: Arc<Mutex<ParseStats>>,
 }
 
 impl Default for SmartParser {
@@ -1443,14 +2101,25 @@ impl Default for SmartParser {
 impl SmartParser {
     /// Creates a new smart parser with default parsers.
     pub fn new() -> Self {
-        Self {
-            registry: ParserRegistry::with_defaults(),
-        }
+        Self::with_registry(ParserRegistry::with_defaults())
     }
 
     /// Creates a smart parser with a custom registry.
     pub fn with_registry(registry: ParserRegistry) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            stats: Arc::default(),
+        }
+    }
+
+    /// Returns a snapshot of the extraction counters.
+    pub fn stats(&self) -> ParseStats {
+        self.stats.lock().clone()
+    }
+
+    /// Clears the extraction counters.
+    pub fn reset_stats(&self) {
+        *self.stats.lock() = ParseStats::default();
     }
 
     /// Parses a payload and extracts prompts.
@@ -1467,7 +2136,9 @@ impl SmartParser {
             context.clone()
         };
 
-        self.registry.parse(body, &context)
+        let result = self.registry.parse(body, &context);
+        self.stats.lock().record(&result, body.len());
+        result
     }
 
     /// Extracts prompts using simplified API (backwards compatible).
@@ -1481,11 +2152,15 @@ impl SmartParser {
         let result = self.parse(body, &context);
 
         if result.has_prompts() {
-            Some(crate::extractor::PromptInfo::new(
-                result.combined_text(),
-                result.service.clone(),
-                path,
-            ))
+            let model = result.prompts.iter().find_map(|p| p.model.clone());
+            Some(
+                crate::extractor::PromptInfo::new(
+                    result.combined_text(),
+                    result.service.clone(),
+                    path,
+                )
+                .with_model(model),
+            )
         } else {
             None
         }
@@ -1500,6 +2175,53 @@ impl SmartParser {
 mod tests {
     use super::*;
 
+    // ==================== ParseStats Tests ====================
+
+    #[test]
+    fn smart_parser_counts_parsers_bytes_and_fallbacks() {
+        let parser = SmartParser::new();
+        let json = br#"{"messages": [{"role": "user", "content": "Hello there"}]}"#;
+        let sse = b"data: {\"prompt\": \"Tell me a story\"}\n\n";
+        let text = b"just some plain words typed in";
+        let json_ctx =
+            ParseContext::new("api.openai.com", "/v1/chat").with_content_type("application/json");
+        let sse_ctx =
+            ParseContext::new("example.com", "/stream").with_content_type("text/event-stream");
+        let text_ctx = ParseContext::new("example.com", "/").with_content_type("image/x-unknown");
+
+        parser.parse(json, &json_ctx);
+        parser.parse(json, &json_ctx);
+        parser.parse(sse, &sse_ctx);
+        parser.parse(text, &text_ctx);
+
+        let stats = parser.stats();
+        assert_eq!(stats.total(), 4);
+        assert_eq!(stats.by_parser["json"], 2);
+        assert_eq!(stats.by_parser["sse"], 1);
+        assert_eq!(stats.by_parser["fallback"], 1);
+        assert_eq!(stats.fallback_count, 1);
+        assert_eq!(
+            stats.bytes_parsed,
+            (json.len() * 2 + sse.len() + text.len()) as u64
+        );
+        assert_eq!(stats.share("json"), 0.5);
+        assert_eq!(stats.share("grpc"), 0.0);
+    }
+
+    #[test]
+    fn smart_parser_stats_are_shared_and_resettable() {
+        let parser = SmartParser::new();
+        let clone = parser.clone();
+        let ctx = ParseContext::new("api.openai.com", "/v1/chat");
+
+        clone.parse(br#"{"prompt": "Hello from the clone"}"#, &ctx);
+        assert_eq!(parser.stats().total(), 1);
+
+        parser.reset_stats();
+        assert_eq!(clone.stats(), ParseStats::default());
+        assert_eq!(ParseStats::default().share("json"), 0.0);
+    }
+
     // ==================== ParseContext Tests ====================
 
     #[test]
@@ -1570,6 +2292,67 @@ mod tests {
         assert_eq!(result.combined_text(), "First\nSecond");
     }
 
+    #[test]
+    fn classification_text_keeps_recent_history_within_weight() {
+        let prompts = vec![
+            ExtractedPrompt::new("oldest message here", false),
+            ExtractedPrompt::new("recent context", false),
+            ExtractedPrompt::new("current question", true),
+        ];
+        let result = ParseResult::with_prompts(prompts, 0.9, "json", "ChatGPT");
+
+        // History is 34 bytes; 0.3 keeps the newest 10
+        assert_eq!(
+            result.classification_text(0.3),
+            "nt context\ncurrent question"
+        );
+        assert_eq!(result.classification_text(0.0), "current question");
+        assert_eq!(result.classification_text(1.0), result.combined_text());
+        assert_eq!(result.classification_text(f32::NAN), "current question");
+    }
+
+    #[test]
+    fn classification_text_uses_last_prompt_without_current() {
+        let prompts = vec![
+            ExtractedPrompt::new("earlier", false),
+            ExtractedPrompt::new("latest", false),
+        ];
+        let result = ParseResult::with_prompts(prompts, 0.5, "fallback", "Unknown");
+        assert_eq!(result.classification_text(0.0), "latest");
+        assert_eq!(result.classification_text(1.0), "earlier\nlatest");
+        assert_eq!(
+            ParseResult::empty("json", "ChatGPT").classification_text(0.3),
+            ""
+        );
+    }
+
+    #[test]
+    fn classification_text_stays_within_budget() {
+        let prompts = vec![
+            ExtractedPrompt::new("ééééé", false),
+            ExtractedPrompt::new("current", true),
+        ];
+        let result = ParseResult::with_prompts(prompts, 0.9, "json", "ChatGPT");
+
+        // 12 bytes leaves 4 for history after the newline: two whole 'é's
+        let text = result.classification_text_within(1.0, 12);
+        assert_eq!(text, "éé\ncurrent");
+
+        // A current prompt over the limit is cut and history dropped
+        assert_eq!(result.classification_text_within(1.0, 4), "curr");
+
+        let huge = "x".repeat(DEFAULT_MAX_PAYLOAD_SIZE);
+        let prompts = vec![
+            ExtractedPrompt::new(huge.clone(), false),
+            ExtractedPrompt::new(huge, true),
+        ];
+        let result = ParseResult::with_prompts(prompts, 0.9, "json", "ChatGPT");
+        assert_eq!(
+            result.classification_text(0.3).len(),
+            DEFAULT_MAX_PAYLOAD_SIZE
+        );
+    }
+
     // ==================== JsonParser Tests ====================
 
     #[test]
@@ -1593,6 +2376,42 @@ mod tests {
         assert!(result.prompts[0].is_current);
     }
 
+    #[test]
+    fn json_parser_tags_requested_model() {
+        let parser = JsonParser;
+        let ctx = ParseContext::new("api.anthropic.com", "/v1/messages")
+            .with_content_type("application/json");
+
+        let body =
+            r#"{"model": "claude-3-5-sonnet", "messages": [{"role": "user", "content": "Hi"}]}"#;
+        let result = parser.parse(body.as_bytes(), &ctx);
+        assert_eq!(
+            result.prompts[0].model.as_deref(),
+            Some("claude-3-5-sonnet")
+        );
+
+        let body = r#"{"messages": [{"role": "user", "content": "Hi"}]}"#;
+        let result = parser.parse(body.as_bytes(), &ctx);
+        assert!(result.has_prompts());
+        assert_eq!(result.prompts[0].model, None);
+    }
+
+    #[test]
+    fn json_parser_openai_thread_message() {
+        let parser = JsonParser;
+        let body = r#"{"role": "user", "content": "How do I pick a lock?"}"#;
+        let ctx = ParseContext::new("api.openai.com", "/v1/threads/thread_abc123/messages")
+            .with_content_type("application/json");
+        let result = parser.parse(body.as_bytes(), &ctx);
+
+        assert_eq!(result.confidence, 0.95);
+        assert_eq!(
+            result.current_prompt().map(|p| p.text.as_str()),
+            Some("How do I pick a lock?")
+        );
+        assert!(result.warnings.is_empty());
+    }
+
     #[test]
     fn json_parser_openai_multimodal() {
         let parser = JsonParser;
@@ -1687,6 +2506,47 @@ mod tests {
         assert!(result.prompts[1].is_current); // Last user message
     }
 
+    #[test]
+    fn json_parser_vercel_ai_envelope() {
+        let parser = JsonParser;
+        let body = r#"{
+            "id": "chat-abc123",
+            "trigger": "submit-message",
+            "body": {
+                "messages": [
+                    {"role": "assistant", "parts": [{"type": "text", "text": "How can I help?"}]},
+                    {"role": "user", "parts": [{"type": "text", "text": "Tell me a story"}]}
+                ]
+            }
+        }"#;
+        let ctx = ParseContext::new("my-app.vercel.app", "/api/chat")
+            .with_content_type("application/json");
+        let result = parser.parse(body.as_bytes(), &ctx);
+
+        assert_eq!(result.prompts.len(), 1);
+        assert_eq!(result.prompts[0].text, "Tell me a story");
+        assert!(result.prompts[0].is_current);
+        assert_eq!(result.confidence, ENVELOPE_CONFIDENCE);
+        assert!(result.warnings.contains(&ParseWarning::EnvelopeUnwrapped {
+            key: "body".to_string()
+        }));
+    }
+
+    #[test]
+    fn json_parser_stringified_envelope() {
+        let parser = JsonParser;
+        let inner = r#"{"messages":[{"role":"user","content":"Wrapped question"}]}"#;
+        let body = serde_json::json!({ "data": { "payload": inner } }).to_string();
+        let ctx = ParseContext::new("api.openai.com", "/v1/chat/completions")
+            .with_content_type("application/json");
+        let result = parser.parse(body.as_bytes(), &ctx);
+
+        assert_eq!(result.prompts[0].text, "Wrapped question");
+        assert!(result.warnings.contains(&ParseWarning::EnvelopeUnwrapped {
+            key: "data.payload".to_string()
+        }));
+    }
+
     // ==================== FormParser Tests ====================
 
     #[test]
@@ -1791,6 +2651,158 @@ mod tests {
         assert!(result.prompts[1].is_current);
     }
 
+    // ==================== GrpcParser Tests ====================
+
+    /// Frames `message` with the gRPC length prefix.
+    fn grpc_frame(flags: u8, message: &[u8]) -> Vec<u8> {
+        let mut frame = vec![flags];
+        frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        frame.extend_from_slice(message);
+        frame
+    }
+
+    /// Encodes `text` as protobuf field 1 (length-delimited).
+    fn proto_string(text: &str) -> Vec<u8> {
+        let mut message = vec![0x0a, text.len() as u8];
+        message.extend_from_slice(text.as_bytes());
+        message
+    }
+
+    #[test]
+    fn grpc_parser_can_parse() {
+        let parser = GrpcParser;
+        assert!(parser.can_parse("application/grpc", "example.com"));
+        assert!(parser.can_parse("application/grpc+proto", "example.com"));
+        assert!(parser.can_parse("application/grpc-web+proto", "example.com"));
+        assert!(!parser.can_parse("application/json", "example.com"));
+    }
+
+    #[test]
+    fn grpc_parser_extracts_text_from_frames() {
+        let parser = GrpcParser;
+        let mut body = grpc_frame(0, &proto_string("Explain photosynthesis"));
+        let mut second = proto_string("short");
+        second.extend(proto_string("What about the Calvin cycle?"));
+        body.extend(grpc_frame(0, &second));
+        // gRPC-Web trailers are not prompts
+        body.extend(grpc_frame(0x80, b"grpc-status:0\r\ngrpc-message:ok"));
+        let ctx = ParseContext::new("us-central1-aiplatform.googleapis.com", "/stream")
+            .with_content_type("application/grpc-web+proto");
+
+        let result = parser.parse(&body, &ctx);
+
+        let texts: Vec<&str> = result.prompts.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            ["Explain photosynthesis", "What about the Calvin cycle?"]
+        );
+        assert!(!result.prompts[0].is_current);
+        assert!(result.prompts[1].is_current);
+        assert_eq!(result.confidence, 0.5);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn grpc_parser_skips_compressed_frames() {
+        let parser = GrpcParser;
+        let mut body = grpc_frame(1, b"\x1f\x8b\x08compressed bytes here");
+        body.extend(grpc_frame(0, &proto_string("An uncompressed prompt")));
+        let ctx = ParseContext::new("example.com", "/").with_content_type("application/grpc");
+
+        let result = parser.parse(&body, &ctx);
+
+        assert_eq!(result.prompts.len(), 1);
+        assert_eq!(result.prompts[0].text, "An uncompressed prompt");
+        assert_eq!(result.warnings, vec![ParseWarning::BinarySkipped]);
+    }
+
+    #[test]
+    fn grpc_parser_handles_truncated_frames() {
+        let parser = GrpcParser;
+        let mut body = grpc_frame(0, &proto_string("A prompt cut off mid-stream"));
+        body.truncate(body.len() - 7);
+        let ctx = ParseContext::new("example.com", "/").with_content_type("application/grpc");
+
+        let result = parser.parse(&body, &ctx);
+
+        assert_eq!(result.prompts[0].text, "A prompt cut off mid");
+        assert!(matches!(
+            result.warnings[0],
+            ParseWarning::MalformedContent { .. }
+        ));
+    }
+
+    #[test]
+    fn grpc_parser_registered_between_ndjson_and_sse() {
+        let registry = ParserRegistry::with_defaults();
+        let names = registry.parser_names();
+        let position = |name| names.iter().position(|n| *n == name).unwrap();
+        assert!(position("ndjson") < position("grpc"));
+        assert!(position("grpc") < position("sse"));
+    }
+
+    // ==================== GrpcWebParser Tests ====================
+
+    #[test]
+    fn grpc_web_parser_extracts_framed_prompt() {
+        let mut body = grpc_frame(0, &proto_string("Write a poem about the ocean"));
+        body.extend(grpc_frame(0x80, b"grpc-status:0\r\n"));
+        let ctx = ParseContext::new("example.com", "/chat.v1.ChatService/Send")
+            .with_content_type("application/grpc-web+proto")
+            .with_grpc_web(true);
+
+        let result = ParserRegistry::with_defaults().parse(&body, &ctx);
+
+        assert_eq!(result.parser_name, "grpc-web");
+        assert_eq!(result.prompts.len(), 1);
+        assert_eq!(result.prompts[0].text, "Write a poem about the ocean");
+        assert!(result.prompts[0].is_current);
+        assert_eq!(result.confidence, GRPC_WEB_CONFIDENCE);
+    }
+
+    #[test]
+    fn grpc_web_parser_decodes_text_encoding() {
+        use base64::engine::general_purpose::STANDARD;
+
+        // Each frame base64-encoded separately, as streaming clients do
+        let first = STANDARD.encode(grpc_frame(0, &proto_string("Explain black holes")));
+        let second = STANDARD.encode(grpc_frame(0, &proto_string("and what about wormholes")));
+        let body = format!("{}\n{}", first, second);
+        let ctx = ParseContext::new("example.com", "/")
+            .with_content_type("application/grpc-web-text")
+            .with_grpc_web(true);
+
+        let result = GrpcWebParser.parse(body.as_bytes(), &ctx);
+
+        let texts: Vec<&str> = result.prompts.iter().map(|p| p.text.as_str()).collect();
+        assert_eq!(texts, ["Explain black holes", "and what about wormholes"]);
+    }
+
+    #[test]
+    fn grpc_web_parser_falls_through_when_disabled_or_undecodable() {
+        let framed = grpc_frame(0, &proto_string("Write a poem about the ocean"));
+        let disabled =
+            ParseContext::new("example.com", "/").with_content_type("application/grpc-web+proto");
+        assert!(!GrpcWebParser.parse(&framed, &disabled).has_prompts());
+        // The binary framing is still read by the gRPC parser
+        assert_eq!(
+            ParserRegistry::with_defaults()
+                .parse(&framed, &disabled)
+                .parser_name,
+            "grpc"
+        );
+
+        let invalid = ParseContext::new("example.com", "/")
+            .with_content_type("application/grpc-web-text")
+            .with_grpc_web(true);
+        let result = GrpcWebParser.parse(b"not*valid*base64", &invalid);
+        assert!(!result.has_prompts());
+        assert!(matches!(
+            result.warnings[0],
+            ParseWarning::MalformedContent { .. }
+        ));
+    }
+
     // ==================== SseParser Tests ====================
 
     #[test]
@@ -1858,6 +2870,134 @@ mod tests {
         assert!(json_idx.unwrap() < fallback_idx.unwrap());
     }
 
+    /// Stand-in for a gateway-specific JSON parser.
+    struct GatewayJsonParser;
+
+    impl PayloadParser for GatewayJsonParser {
+        fn name(&self) -> &str {
+            "gateway-json"
+        }
+
+        fn can_parse(&self, content_type: &str, _host: &str) -> bool {
+            content_type.contains("json")
+        }
+
+        fn parse(&self, body: &[u8], context: &ParseContext) -> ParseResult {
+            ParseResult::with_prompts(
+                vec![ExtractedPrompt::new(String::from_utf8_lossy(body), true)],
+                1.0,
+                self.name(),
+                service_name(&context.host),
+            )
+        }
+
+        fn priority(&self) -> i32 {
+            100
+        }
+    }
+
+    #[test]
+    fn parser_registry_unregister_preserves_order() {
+        let mut registry = ParserRegistry::with_defaults();
+        let mut expected = registry.parser_names();
+        expected.retain(|name| *name != "json");
+        let expected: Vec<String> = expected.into_iter().map(String::from).collect();
+
+        assert!(registry.unregister("json"));
+        assert_eq!(registry.parser_names(), expected);
+        assert!(!registry.unregister("json"));
+        assert!(!registry.unregister("no-such-parser"));
+    }
+
+    #[test]
+    fn parser_registry_swap_builtin_parser() {
+        let mut registry = ParserRegistry::with_defaults();
+        registry.unregister("json");
+        registry.register(Arc::new(GatewayJsonParser));
+
+        assert_eq!(registry.parser_names()[0], "gateway-json");
+        let ctx =
+            ParseContext::new("gateway.internal", "/v1/chat").with_content_type("application/json");
+        let result = registry.parse(br#"{"input": "hi"}"#, &ctx);
+        assert_eq!(result.parser_name, "gateway-json");
+    }
+
+    /// Host override returning nothing, to exercise fall-through.
+    struct EmptyParser;
+
+    impl PayloadParser for EmptyParser {
+        fn name(&self) -> &str {
+            "empty"
+        }
+
+        fn can_parse(&self, _content_type: &str, _host: &str) -> bool {
+            true
+        }
+
+        fn parse(&self, _body: &[u8], context: &ParseContext) -> ParseResult {
+            ParseResult::empty(self.name(), service_name(&context.host))
+        }
+    }
+
+    #[test]
+    fn host_parser_takes_precedence_on_matching_hosts() {
+        let mut registry = ParserRegistry::with_defaults();
+        registry.register_for_host("Perplexity.AI", Arc::new(GatewayJsonParser));
+        let body = br#"{"messages": [{"role": "user", "content": "hi"}]}"#;
+
+        for host in [
+            "perplexity.ai",
+            "www.perplexity.ai",
+            "www.perplexity.ai:443",
+        ] {
+            let ctx = ParseContext::new(host, "/api").with_content_type("application/json");
+            assert_eq!(
+                registry.parse(body, &ctx).parser_name,
+                "gateway-json",
+                "{host}"
+            );
+        }
+
+        for host in [
+            "notperplexity.ai",
+            "perplexity.ai.evil.com",
+            "api.openai.com",
+        ] {
+            let ctx = ParseContext::new(host, "/api").with_content_type("application/json");
+            assert_eq!(registry.parse(body, &ctx).parser_name, "json", "{host}");
+        }
+    }
+
+    #[test]
+    fn host_parser_respects_can_parse_and_falls_back() {
+        let mut registry = ParserRegistry::with_defaults();
+        registry.register_for_host("example.com", Arc::new(GatewayJsonParser));
+        let ctx = ParseContext::new("example.com", "/").with_content_type("text/plain");
+        assert_ne!(registry.parse(b"hello", &ctx).parser_name, "gateway-json");
+
+        let mut registry = ParserRegistry::with_defaults();
+        registry.register_for_host("example.com", Arc::new(EmptyParser));
+        let ctx = ParseContext::new("example.com", "/").with_content_type("application/json");
+        let result = registry.parse(br#"{"prompt": "hello"}"#, &ctx);
+        assert_eq!(result.parser_name, "json");
+    }
+
+    #[test]
+    fn more_specific_host_pattern_wins() {
+        let mut registry = ParserRegistry::new();
+        registry.register_for_host("*.example.com", Arc::new(GatewayJsonParser));
+        registry.register_for_host("chat.example.com", Arc::new(JsonParser));
+        let body = br#"{"prompt": "hello"}"#;
+
+        let ctx = ParseContext::new("chat.example.com", "/").with_content_type("application/json");
+        assert_eq!(registry.parse(body, &ctx).parser_name, "json");
+        let ctx = ParseContext::new("api.example.com", "/").with_content_type("application/json");
+        assert_eq!(registry.parse(body, &ctx).parser_name, "gateway-json");
+
+        assert!(registry.unregister("gateway-json"));
+        assert_eq!(registry.parse(body, &ctx).parser_name, "none");
+    }
+
     #[test]
     fn parser_registry_parse() {
         let registry = ParserRegistry::with_defaults();
@@ -1937,6 +3077,151 @@ mod tests {
         assert!(acc.looks_complete());
     }
 
+    #[test]
+    fn stream_accumulator_emoji_split_across_chunks() {
+        let event = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"hi 🎉\"}}]}\n\n",
+            "data: [DONE]\n\n",
+            ": 🎉\n",
+        )
+        .as_bytes();
+        // Second chunk starts halfway through the emoji in the keep-alive comment
+        let split = event.len() - 3;
+        let mut acc = StreamAccumulator::new();
+
+        assert!(acc.append(&event[..split]));
+        assert!(acc.looks_complete());
+        assert!(std::str::from_utf8(acc.content()).is_err());
+
+        assert!(acc.append(&event[split..]));
+        assert_eq!(
+            std::str::from_utf8(acc.content()).unwrap().as_bytes(),
+            event
+        );
+        assert_eq!(
+            crate::response_scanner::reassemble_completion(acc.content()),
+            "hi 🎉"
+        );
+    }
+
+    #[test]
+    fn stream_accumulator_truncation_keeps_whole_characters() {
+        let body = r#"{"text": "🎉🎉"}"#.as_bytes();
+        // The cap falls inside the second emoji
+        let max = body.iter().rposition(|&b| b == 0xF0).unwrap() + 2;
+        let mut acc = StreamAccumulator::with_max_size(max);
+
+        assert!(acc.append(&body[..5]));
+        assert!(!acc.append(&body[5..]));
+        assert!(acc.is_truncated());
+        assert_eq!(
+            std::str::from_utf8(acc.content()).unwrap(),
+            r#"{"text": "🎉"#
+        );
+
+        // Nothing more is taken once full, even if it would fit
+        assert!(!acc.append(b"}"));
+        assert_eq!(acc.len(), max - 2);
+
+        acc.clear();
+        assert!(!acc.is_truncated());
+        assert!(acc.append(&body[..5]));
+    }
+
+    #[test]
+    fn stream_accumulator_exact_fit_is_not_an_overflow() {
+        let mut acc = StreamAccumulator::with_max_size(10);
+        assert!(acc.append(b"12345"));
+        assert!(acc.append(b"67890"));
+        assert!(!acc.is_truncated());
+        assert_eq!(acc.dropped_bytes(), 0);
+        assert!(acc.append(b""));
+
+        // The next byte overflows the full buffer
+        assert!(!acc.append(b"x"));
+        assert!(acc.is_truncated());
+        assert_eq!(acc.content(), b"1234567890");
+        assert_eq!(acc.dropped_bytes(), 1);
+    }
+
+    #[test]
+    fn stream_accumulator_one_over_keeps_what_fits() {
+        let mut acc = StreamAccumulator::with_max_size(10);
+        assert!(acc.append(b"12345"));
+        assert!(!acc.append(b"678901"));
+        assert_eq!(acc.content(), b"1234567890");
+        assert_eq!(acc.dropped_bytes(), 1);
+
+        // Already full: dropped whole, even when empty or tiny
+        assert!(!acc.append(b"abc"));
+        assert!(!acc.append(b""));
+        assert_eq!(acc.len(), 10);
+        assert_eq!(acc.dropped_bytes(), 4);
+
+        acc.clear();
+        assert_eq!(acc.dropped_bytes(), 0);
+    }
+
+    #[test]
+    fn stream_accumulator_zero_capacity_drops_everything() {
+        let mut acc = StreamAccumulator::with_max_size(0);
+        assert!(acc.append(b""));
+        assert!(!acc.append("é".as_bytes()));
+        assert!(acc.is_empty());
+        assert_eq!(acc.dropped_bytes(), 2);
+    }
+
+    #[test]
+    fn stream_accumulator_takes_complete_sse_events() {
+        let mut acc = StreamAccumulator::new();
+        acc.set_content_type("text/event-stream; charset=utf-8");
+
+        acc.append(b"event: delta\ndata: {\"a\":1}\n\ndata: {\"b\"");
+        assert_eq!(
+            acc.take_complete_records(),
+            vec!["event: delta\ndata: {\"a\":1}"]
+        );
+        assert_eq!(acc.content(), b"data: {\"b\"");
+
+        // A single newline doesn't end an event
+        acc.append(b":2}\n");
+        assert!(acc.take_complete_records().is_empty());
+
+        acc.append(b"\r\ndata: [DONE]\r\n\r\n");
+        assert_eq!(
+            acc.take_complete_records(),
+            vec!["data: {\"b\":2}", "data: [DONE]"]
+        );
+        assert!(acc.is_empty());
+    }
+
+    #[test]
+    fn stream_accumulator_takes_complete_ndjson_lines() {
+        let mut acc = StreamAccumulator::new();
+        acc.set_content_type("application/x-ndjson");
+
+        acc.append(b"{\"a\":1}\r\n\n{\"b\":2}\n{\"c\"");
+        assert_eq!(acc.take_complete_records(), vec!["{\"a\":1}", "{\"b\":2}"]);
+        assert_eq!(acc.content(), b"{\"c\"");
+
+        assert!(acc.take_complete_records().is_empty());
+        acc.append(b":3}\n");
+        assert_eq!(acc.take_complete_records(), vec!["{\"c\":3}"]);
+        assert!(acc.is_empty());
+    }
+
+    #[test]
+    fn incomplete_utf8_tail_lengths() {
+        let emoji = "🎉".as_bytes();
+        assert_eq!(incomplete_utf8_tail(b""), 0);
+        assert_eq!(incomplete_utf8_tail(b"abc"), 0);
+        assert_eq!(incomplete_utf8_tail(emoji), 0);
+        assert_eq!(incomplete_utf8_tail(&emoji[..1]), 1);
+        assert_eq!(incomplete_utf8_tail(&emoji[..3]), 3);
+        assert_eq!(incomplete_utf8_tail("aé".as_bytes()), 0);
+        assert_eq!(incomplete_utf8_tail(&"aé".as_bytes()[..2]), 1);
+    }
+
     // ==================== SmartParser Tests ====================
 
     #[test]
@@ -0,0 +1,155 @@
+//! Conservative filtering while the ML model is still loading.
+//!
+//! Until the Prompt Guard model reports loaded, only the keyword tier runs and
+//! subtle prompts can slip under the normal thresholds. A [`StartupGuard`]
+//! blocks Tier 1 matches in sensitive categories during that window, then
+//! steps aside once the model is ready.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use aegis_core::classifier::{Category, ClassificationResult};
+use aegis_core::content_rules::{ContentAction, ContentRuleResult};
+use aegis_core::rule_engine::RuleEngineResult;
+
+/// Categories treated conservatively until the model is ready.
+pub const DEFAULT_SENSITIVE_CATEGORIES: &[Category] = &[
+    Category::Jailbreak,
+    Category::Violence,
+    Category::SelfHarm,
+    Category::Adult,
+];
+
+/// Minimum confidence at which a sensitive match blocks before the model is ready.
+pub const DEFAULT_STARTUP_BLOCK_THRESHOLD: f32 = 0.5;
+
+/// Rule ID reported for blocks made by the startup guard.
+pub const STARTUP_RULE_ID: &str = "startup_conservative";
+
+/// Applies a conservative block policy until the ML model is ready.
+///
+/// Clones share readiness, so a clone can be kept to call
+/// [`mark_ready`](Self::mark_ready) from outside the proxy.
+#[derive(Debug, Clone)]
+pub struct StartupGuard {
+    ready: Arc<AtomicBool>,
+    sensitive_categories: Vec<Category>,
+    block_threshold: f32,
+}
+
+impl StartupGuard {
+    /// Creates a guard with the default sensitive categories and threshold.
+    pub fn new() -> Self {
+        Self {
+            ready: Arc::new(AtomicBool::new(false)),
+            sensitive_categories: DEFAULT_SENSITIVE_CATEGORIES.to_vec(),
+            block_threshold: DEFAULT_STARTUP_BLOCK_THRESHOLD,
+        }
+    }
+
+    /// Sets the categories treated conservatively.
+    pub fn with_sensitive_categories(mut self, categories: Vec<Category>) -> Self {
+        self.sensitive_categories = categories;
+        self
+    }
+
+    /// Sets the confidence threshold for conservative blocks.
+    pub fn with_block_threshold(mut self, threshold: f32) -> Self {
+        self.block_threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Returns the sensitive categories.
+    pub fn sensitive_categories(&self) -> &[Category] {
+        &self.sensitive_categories
+    }
+
+    /// Returns the conservative block threshold.
+    pub fn block_threshold(&self) -> f32 {
+        self.block_threshold
+    }
+
+    /// Returns true once the model has reported ready.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    /// Marks the model as ready, relaxing to the normal policy.
+    pub fn mark_ready(&self) {
+        if !self.ready.swap(true, Ordering::AcqRel) {
+            tracing::info!("ML model ready, leaving conservative startup policy");
+        }
+    }
+
+    /// Returns a block result if a sensitive match should be blocked.
+    ///
+    /// Always returns `None` once the guard is ready.
+    pub fn evaluate(&self, classification: &ClassificationResult) -> Option<RuleEngineResult> {
+        if self.is_ready() {
+            return None;
+        }
+
+        let m = classification
+            .matches
+            .iter()
+            .filter(|m| {
+                self.sensitive_categories.contains(&m.category)
+                    && m.confidence >= self.block_threshold
+            })
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))?;
+
+        Some(RuleEngineResult::from_content_result(ContentRuleResult {
+            rule_id: STARTUP_RULE_ID.to_string(),
+            rule_name: "Startup protection".to_string(),
+            category: m.category,
+            confidence: m.confidence,
+            action: ContentAction::Block,
+        }))
+    }
+}
+
+impl Default for StartupGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_core::classifier::CategoryMatch;
+
+    fn classification(category: Category, confidence: f32) -> ClassificationResult {
+        ClassificationResult::with_matches(vec![CategoryMatch::new(category, confidence, None)], 0)
+    }
+
+    #[test]
+    fn blocks_sensitive_matches_until_ready() {
+        let guard = StartupGuard::new();
+        let result = guard
+            .evaluate(&classification(Category::Jailbreak, 0.6))
+            .expect("should block before ready");
+        assert!(result.should_block());
+        assert_eq!(result.source.rule_id(), Some(STARTUP_RULE_ID));
+
+        guard.clone().mark_ready();
+        assert!(guard.is_ready());
+        assert!(guard
+            .evaluate(&classification(Category::Jailbreak, 0.6))
+            .is_none());
+    }
+
+    #[test]
+    fn ignores_other_categories_and_weak_matches() {
+        let guard = StartupGuard::new().with_sensitive_categories(vec![Category::Violence]);
+        assert!(guard
+            .evaluate(&classification(Category::Profanity, 0.9))
+            .is_none());
+        assert!(guard
+            .evaluate(&classification(Category::Violence, 0.3))
+            .is_none());
+        assert!(guard
+            .evaluate(&classification(Category::Violence, 0.5))
+            .is_some());
+    }
+}
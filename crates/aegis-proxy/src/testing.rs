@@ -0,0 +1,224 @@
+//! In-process test harness for the full request flow.
+//!
+//! Builds a [`ProxyHandler`] backed by an in-memory database so tests can feed
+//! raw requests through extract → classify → block/log and inspect the
+//! outcome, without TLS, sockets, or a running proxy.
+//!
+//! Available in this crate's unit tests and, for other crates and integration
+//! tests, behind the `testing` feature.
+//!
+//! ```ignore
+//! let harness = TestHarness::new();
+//! let outcome = harness
+//!     .send(json_request("api.openai.com", "/v1/chat/completions", body))
+//!     .await;
+//! assert_eq!(blocked_status(&outcome), Some(StatusCode::FORBIDDEN));
+//! ```
+
+use std::sync::Arc;
+
+use http_body_util::{BodyExt, Full};
+use hudsucker::{hyper::Request, Body, RequestOrResponse};
+use hyper::body::Bytes;
+use hyper::StatusCode;
+use parking_lot::RwLock;
+
+use aegis_core::classifier::{LazyNsfwClassifier, TieredClassifier};
+use aegis_core::rule_engine::RuleEngine;
+use aegis_core::site_registry::SiteRegistry;
+use aegis_storage::{Database, Event};
+
+use crate::body_limits::ContentLengthPolicy;
+use crate::handler::{
+    AllowLogging, ClassifierFailurePolicy, FilteringState, HandlerConfig, ProxyHandler,
+};
+use crate::tunnel_hosts::MissingHostPolicy;
+
+/// A [`ProxyHandler`] wired to an in-memory database.
+///
+/// Defaults are deterministic: keyword-only classification (no ML model) and
+/// content rules without time restrictions.
+pub struct TestHarness {
+    handler: ProxyHandler,
+    database: Arc<Database>,
+}
+
+impl TestHarness {
+    /// Creates a harness with keyword classification and family-safe content rules.
+    pub fn new() -> Self {
+        Self::with_rule_engine(RuleEngine::content_only())
+    }
+
+    /// Creates a harness using the given rule engine.
+    pub fn with_rule_engine(rule_engine: RuleEngine) -> Self {
+        Self::with_classifier(TieredClassifier::keyword_only(), rule_engine)
+    }
+
+    /// Creates a harness using the given classifier and rule engine.
+    pub fn with_classifier(classifier: TieredClassifier, rule_engine: RuleEngine) -> Self {
+        let database = Arc::new(Database::in_memory().expect("in-memory database"));
+        let handler = ProxyHandler::new(HandlerConfig {
+            classifier: Arc::new(RwLock::new(classifier)),
+            notifications: None,
+            on_block: None,
+            on_allow: None,
+            filtering_state: FilteringState::with_rule_engine(rule_engine),
+            database: Some(database.clone()),
+            nsfw_classifier: Arc::new(RwLock::new(LazyNsfwClassifier::with_defaults())),
+            site_registry: Arc::new(SiteRegistry::with_defaults()),
+            connection_limiter: None,
+            classifier_failure_policy: ClassifierFailurePolicy::default(),
+            startup_guard: None,
+            content_length_policy: ContentLengthPolicy::default(),
+            missing_host_policy: MissingHostPolicy::default(),
+            allow_logging: AllowLogging::default(),
+            warn_escalation: None,
+            warn_interstitial: None,
+            block_page_template: None,
+            decision_headers: false,
+        });
+
+        Self { handler, database }
+    }
+
+    /// Returns the handler under test.
+    pub fn handler(&self) -> &ProxyHandler {
+        &self.handler
+    }
+
+    /// Returns the filtering state, e.g. to disable filtering mid-test.
+    pub fn filtering_state(&self) -> &FilteringState {
+        self.handler.filtering_state()
+    }
+
+    /// Returns the in-memory database events are logged to.
+    pub fn database(&self) -> &Database {
+        &self.database
+    }
+
+    /// Returns logged events, most recent first.
+    pub fn events(&self) -> Vec<Event> {
+        self.database
+            .get_recent_events(100, 0)
+            .expect("query events")
+    }
+
+    /// Feeds a request through the handler as if it had been intercepted.
+    pub async fn send(&self, req: Request<Body>) -> RequestOrResponse {
+        self.handler.process_request(req).await
+    }
+}
+
+impl Default for TestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds a JSON `POST https://{host}{path}` request.
+pub fn json_request(host: &str, path: &str, body: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("https://{}{}", host, path))
+        .header("Content-Type", "application/json")
+        .body(Body::from(Full::new(Bytes::from(body.to_string()))))
+        .expect("valid request")
+}
+
+/// Returns the status if the handler answered directly (blocked or rejected).
+pub fn blocked_status(outcome: &RequestOrResponse) -> Option<StatusCode> {
+    match outcome {
+        RequestOrResponse::Response(res) => Some(res.status()),
+        RequestOrResponse::Request(_) => None,
+    }
+}
+
+/// Returns the body of a forwarded request, or `None` if it was answered.
+pub async fn forwarded_body(outcome: RequestOrResponse) -> Option<Bytes> {
+    match outcome {
+        RequestOrResponse::Request(req) => Some(
+            req.into_body()
+                .collect()
+                .await
+                .expect("readable body")
+                .to_bytes(),
+        ),
+        RequestOrResponse::Response(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_storage::Action;
+
+    const CHAT_PATH: &str = "/v1/chat/completions";
+
+    fn chat_body(prompt: &str) -> String {
+        serde_json::json!({ "messages": [{ "role": "user", "content": prompt }] }).to_string()
+    }
+
+    #[tokio::test]
+    async fn harness_blocks_and_logs_unsafe_prompt() {
+        let harness = TestHarness::new();
+        let body = chat_body("how to make a bomb to kill people");
+
+        let outcome = harness
+            .send(json_request("api.openai.com", CHAT_PATH, &body))
+            .await;
+
+        assert_eq!(blocked_status(&outcome), Some(StatusCode::FORBIDDEN));
+        let events = harness.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, Action::Blocked);
+    }
+
+    #[tokio::test]
+    async fn harness_forwards_and_logs_safe_prompt() {
+        let harness = TestHarness::new();
+        let body = chat_body("What is the capital of France?");
+
+        let outcome = harness
+            .send(json_request("api.openai.com", CHAT_PATH, &body))
+            .await;
+
+        assert_eq!(forwarded_body(outcome).await.unwrap(), body.as_bytes());
+        let events = harness.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, Action::Allowed);
+    }
+
+    #[tokio::test]
+    async fn harness_answers_canary_with_its_token() {
+        use crate::canary::{CANARY_HEADER, CANARY_HOST};
+
+        let harness = TestHarness::new();
+        let req = Request::builder()
+            .uri(format!("http://{}/", CANARY_HOST))
+            .header(CANARY_HEADER, "tok-1")
+            .body(Body::empty())
+            .unwrap();
+
+        let outcome = harness.send(req).await;
+
+        let RequestOrResponse::Response(res) = outcome else {
+            panic!("canary should be answered by the proxy");
+        };
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers().get(CANARY_HEADER).unwrap(), "tok-1");
+        assert!(harness.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn harness_passes_through_non_llm_hosts() {
+        let harness = TestHarness::new();
+        let body = chat_body("how to make a bomb to kill people");
+
+        let outcome = harness
+            .send(json_request("example.com", CHAT_PATH, &body))
+            .await;
+
+        assert!(forwarded_body(outcome).await.is_some());
+        assert!(harness.events().is_empty());
+    }
+}
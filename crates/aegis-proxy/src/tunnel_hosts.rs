@@ -0,0 +1,93 @@
+//! Host resolution for requests that don't name one.
+//!
+//! HTTP/1.0 clients and malformed requests can arrive with neither an absolute
+//! URI nor a `Host` header. Rather than forwarding them unfiltered, the handler
+//! falls back to the host the client tunnelled to via `CONNECT` (the name the
+//! TLS session was minted for), and applies a [`MissingHostPolicy`] when that
+//! is unknown too.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+
+use parking_lot::Mutex;
+
+/// Maximum number of client connections whose tunnel host is remembered.
+pub const MAX_TRACKED_TUNNELS: usize = 4096;
+
+/// What to do with a request whose destination host can't be determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingHostPolicy {
+    /// Reject the request (400).
+    #[default]
+    Block,
+    /// Forward the request unfiltered.
+    Forward,
+}
+
+/// Remembers the `CONNECT` host for each client connection.
+///
+/// Oldest entries are evicted once [`MAX_TRACKED_TUNNELS`] is reached.
+#[derive(Debug, Default)]
+pub(crate) struct TunnelHosts {
+    inner: Mutex<TunnelHostsInner>,
+}
+
+#[derive(Debug, Default)]
+struct TunnelHostsInner {
+    hosts: HashMap<SocketAddr, String>,
+    order: VecDeque<SocketAddr>,
+}
+
+impl TunnelHosts {
+    /// Records the host `client` tunnelled to.
+    pub(crate) fn record(&self, client: SocketAddr, host: &str) {
+        let mut inner = self.inner.lock();
+        if inner.hosts.insert(client, host.to_string()).is_none() {
+            inner.order.push_back(client);
+        }
+        while inner.order.len() > MAX_TRACKED_TUNNELS {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.hosts.remove(&oldest);
+            }
+        }
+    }
+
+    /// Returns the host `client` tunnelled to, if known.
+    pub(crate) fn get(&self, client: SocketAddr) -> Option<String> {
+        self.inner.lock().hosts.get(&client).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn records_and_replaces_hosts() {
+        let tunnels = TunnelHosts::default();
+        assert_eq!(tunnels.get(addr(1)), None);
+
+        tunnels.record(addr(1), "chatgpt.com");
+        assert_eq!(tunnels.get(addr(1)).as_deref(), Some("chatgpt.com"));
+
+        tunnels.record(addr(1), "claude.ai");
+        assert_eq!(tunnels.get(addr(1)).as_deref(), Some("claude.ai"));
+        assert_eq!(tunnels.inner.lock().order.len(), 1);
+    }
+
+    #[test]
+    fn evicts_oldest_beyond_capacity() {
+        let tunnels = TunnelHosts::default();
+        for port in 0..=MAX_TRACKED_TUNNELS as u16 {
+            tunnels.record(addr(port), "chatgpt.com");
+        }
+
+        assert_eq!(tunnels.get(addr(0)), None);
+        assert!(tunnels.get(addr(MAX_TRACKED_TUNNELS as u16)).is_some());
+        assert_eq!(tunnels.inner.lock().hosts.len(), MAX_TRACKED_TUNNELS);
+    }
+}
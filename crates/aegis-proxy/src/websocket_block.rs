@@ -0,0 +1,140 @@
+//! Telling WebSocket clients their prompt was blocked.
+//!
+//! Dropping a blocked WebSocket message leaves chat UIs such as ChatGPT's
+//! spinning forever, waiting for a reply that never comes. Instead, the proxy
+//! sends the client an error message and then closes the socket with
+//! [`WEBSOCKET_BLOCK_CLOSE_CODE`], so the page shows a failure.
+//!
+//! The error message has the shape of OpenAI's WebSocket error events (as in
+//! the Realtime API), which ChatGPT's client surfaces as a failed reply:
+//!
+//! ```json
+//! {
+//!   "type": "error",
+//!   "error": {
+//!     "type": "aegis_blocked",
+//!     "code": "content_blocked",
+//!     "message": "Blocked by Aegis: <reason>"
+//!   }
+//! }
+//! ```
+//!
+//! Each direction of a WebSocket is forwarded separately, so the side reading
+//! prompts from the client can't write back to it. [`WebSocketClients`] keeps
+//! a channel per connection that the server-to-client side also forwards.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use hudsucker::hyper::Uri;
+use hudsucker::tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use hudsucker::tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use hudsucker::tokio_tungstenite::tungstenite::Message;
+use parking_lot::Mutex;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Close code sent to the client after a blocked prompt (private-use range).
+pub const WEBSOCKET_BLOCK_CLOSE_CODE: u16 = 4403;
+
+/// Error `type` of the injected error message.
+pub const WEBSOCKET_BLOCK_ERROR_TYPE: &str = "aegis_blocked";
+
+/// Returns the messages sent to the client when its prompt is blocked: an
+/// error message, then a close frame.
+pub fn websocket_block_messages(reason: &str) -> Vec<Message> {
+    let error = serde_json::json!({
+        "type": "error",
+        "error": {
+            "type": WEBSOCKET_BLOCK_ERROR_TYPE,
+            "code": "content_blocked",
+            "message": format!("Blocked by Aegis: {}", reason),
+        }
+    });
+    vec![
+        Message::Text(error.to_string().into()),
+        Message::Close(Some(CloseFrame {
+            code: CloseCode::from(WEBSOCKET_BLOCK_CLOSE_CODE),
+            reason: "Blocked by Aegis".into(),
+        })),
+    ]
+}
+
+/// Client connection and the URI it opened a WebSocket to.
+type ConnectionKey = (SocketAddr, String);
+
+/// Channels for sending messages to WebSocket clients, by connection.
+#[derive(Debug, Default)]
+pub(crate) struct WebSocketClients {
+    senders: Mutex<HashMap<ConnectionKey, UnboundedSender<Message>>>,
+}
+
+impl WebSocketClients {
+    /// Opens a channel to `client`'s WebSocket to `uri`, replacing any
+    /// previous one.
+    pub(crate) fn register(&self, client: SocketAddr, uri: &Uri) -> UnboundedReceiver<Message> {
+        let (sender, receiver) = unbounded_channel();
+        self.senders
+            .lock()
+            .insert((client, uri.to_string()), sender);
+        receiver
+    }
+
+    /// Closes the channel to `client`'s WebSocket to `uri`.
+    pub(crate) fn unregister(&self, client: SocketAddr, uri: &Uri) {
+        self.senders.lock().remove(&(client, uri.to_string()));
+    }
+
+    /// Queues `messages` for `client`'s WebSocket to `uri`.
+    ///
+    /// Returns false if the connection has no open channel.
+    pub(crate) fn send(&self, client: SocketAddr, uri: &Uri, messages: Vec<Message>) -> bool {
+        let senders = self.senders.lock();
+        let Some(sender) = senders.get(&(client, uri.to_string())) else {
+            return false;
+        };
+        messages
+            .into_iter()
+            .all(|message| sender.send(message).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_messages_are_error_then_close() {
+        let messages = websocket_block_messages("Violence");
+        let Message::Text(text) = &messages[0] else {
+            panic!("expected an error message first");
+        };
+        let error: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(error["type"], "error");
+        assert_eq!(error["error"]["type"], WEBSOCKET_BLOCK_ERROR_TYPE);
+        assert_eq!(error["error"]["message"], "Blocked by Aegis: Violence");
+
+        let Message::Close(Some(frame)) = &messages[1] else {
+            panic!("expected a close frame last");
+        };
+        assert_eq!(u16::from(frame.code), WEBSOCKET_BLOCK_CLOSE_CODE);
+    }
+
+    #[test]
+    fn messages_reach_only_registered_connections() {
+        let clients = WebSocketClients::default();
+        let client = SocketAddr::from(([127, 0, 0, 1], 50000));
+        let uri: Uri = "wss://chatgpt.com/backend-api/ws".parse().unwrap();
+        let other: Uri = "wss://claude.ai/ws".parse().unwrap();
+
+        assert!(!clients.send(client, &uri, websocket_block_messages("Hate")));
+
+        let mut receiver = clients.register(client, &uri);
+        assert!(!clients.send(client, &other, websocket_block_messages("Hate")));
+        assert!(clients.send(client, &uri, websocket_block_messages("Hate")));
+        assert!(receiver.try_recv().unwrap().is_text());
+        assert!(receiver.try_recv().unwrap().is_close());
+
+        clients.unregister(client, &uri);
+        assert!(!clients.send(client, &uri, websocket_block_messages("Hate")));
+    }
+}
@@ -0,0 +1,233 @@
+//! Data-driven parser regression tests (F026).
+//!
+//! Each fixture in `tests/fixtures/` is a pair of files:
+//!
+//! - `<name>.request` - a captured (anonymized) request: a `METHOD PATH` line,
+//!   `Header: value` lines, a blank line, then the raw body.
+//! - `<name>.expected.json` - the expected [`SmartParser::parse`] result.
+//!
+//! Expected results support these fields:
+//!
+//! ```json
+//! {
+//!   "parser": "json",
+//!   "service": "ChatGPT",
+//!   "min_confidence": 0.9,
+//!   "prompts": [{ "text": "...", "is_current": true, "role": "user" }],
+//!   "warnings": [{ "EnvelopeUnwrapped": { "key": "body" } }]
+//! }
+//! ```
+//!
+//! Only `prompts` is required. `role` is checked only when given, and
+//! `warnings` (when given) must match the serialized warnings exactly.
+//!
+//! To add coverage for a new provider format, drop a new pair into the
+//! fixtures directory; no code changes are needed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use aegis_proxy::{ParseContext, ParseResult, SmartParser};
+use serde::Deserialize;
+
+/// A captured request loaded from a `.request` file.
+struct FixtureRequest {
+    method: String,
+    path: String,
+    host: String,
+    content_type: Option<String>,
+    body: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExpectedPrompt {
+    text: String,
+    is_current: bool,
+    #[serde(default)]
+    role: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Expected {
+    #[serde(default)]
+    parser: Option<String>,
+    #[serde(default)]
+    service: Option<String>,
+    #[serde(default)]
+    min_confidence: Option<f32>,
+    prompts: Vec<ExpectedPrompt>,
+    #[serde(default)]
+    warnings: Option<serde_json::Value>,
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+/// Parses a `.request` file into its request line, headers, and body.
+fn load_request(path: &Path) -> FixtureRequest {
+    let raw = fs::read(path).unwrap_or_else(|e| panic!("read {}: {}", path.display(), e));
+
+    let split = raw
+        .windows(2)
+        .position(|w| w == b"\n\n")
+        .unwrap_or_else(|| panic!("{}: missing blank line before body", path.display()));
+    let head = std::str::from_utf8(&raw[..split])
+        .unwrap_or_else(|_| panic!("{}: headers must be UTF-8", path.display()));
+
+    // Files conventionally end with a newline that is not part of the body
+    let mut body = raw[split + 2..].to_vec();
+    if body.ends_with(b"\n") {
+        body.pop();
+    }
+
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or_default();
+    let (method, req_path) = request_line
+        .split_once(' ')
+        .unwrap_or_else(|| panic!("{}: bad request line {:?}", path.display(), request_line));
+
+    let mut host = None;
+    let mut content_type = None;
+    for line in lines {
+        let (name, value) = line
+            .split_once(':')
+            .unwrap_or_else(|| panic!("{}: bad header {:?}", path.display(), line));
+        match name.trim().to_ascii_lowercase().as_str() {
+            "host" => host = Some(value.trim().to_string()),
+            "content-type" => content_type = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    FixtureRequest {
+        method: method.to_string(),
+        path: req_path.trim().to_string(),
+        host: host.unwrap_or_else(|| panic!("{}: missing Host header", path.display())),
+        content_type,
+        body,
+    }
+}
+
+/// Returns the names of all fixtures that have both files present.
+fn fixture_names() -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(fixtures_dir())
+        .expect("fixtures directory should exist")
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_str()?.strip_suffix(".request")?;
+            Some(name.to_string())
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+fn parse_fixture(request: &FixtureRequest) -> ParseResult {
+    let mut context =
+        ParseContext::new(&request.host, &request.path).with_method(request.method.as_str());
+    if let Some(ct) = &request.content_type {
+        context = context.with_content_type(ct.as_str());
+    }
+    SmartParser::new().parse(&request.body, &context)
+}
+
+/// Compares a parse result against expectations, returning mismatches.
+fn check(result: &ParseResult, expected: &Expected) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if let Some(parser) = &expected.parser {
+        if &result.parser_name != parser {
+            errors.push(format!(
+                "parser: expected {:?}, got {:?}",
+                parser, result.parser_name
+            ));
+        }
+    }
+    if let Some(service) = &expected.service {
+        if &result.service != service {
+            errors.push(format!(
+                "service: expected {:?}, got {:?}",
+                service, result.service
+            ));
+        }
+    }
+    if let Some(min) = expected.min_confidence {
+        if result.confidence < min {
+            errors.push(format!(
+                "confidence: expected >= {}, got {}",
+                min, result.confidence
+            ));
+        }
+    }
+
+    if result.prompts.len() != expected.prompts.len() {
+        errors.push(format!(
+            "prompts: expected {}, got {}: {:?}",
+            expected.prompts.len(),
+            result.prompts.len(),
+            result.prompts.iter().map(|p| &p.text).collect::<Vec<_>>()
+        ));
+    }
+    for (i, (got, want)) in result.prompts.iter().zip(&expected.prompts).enumerate() {
+        if got.text != want.text {
+            errors.push(format!(
+                "prompts[{}].text: expected {:?}, got {:?}",
+                i, want.text, got.text
+            ));
+        }
+        if got.is_current != want.is_current {
+            errors.push(format!(
+                "prompts[{}].is_current: expected {}, got {}",
+                i, want.is_current, got.is_current
+            ));
+        }
+        if want.role.is_some() && got.role != want.role {
+            errors.push(format!(
+                "prompts[{}].role: expected {:?}, got {:?}",
+                i, want.role, got.role
+            ));
+        }
+    }
+
+    if let Some(warnings) = &expected.warnings {
+        let got = serde_json::to_value(&result.warnings).unwrap();
+        if &got != warnings {
+            errors.push(format!("warnings: expected {}, got {}", warnings, got));
+        }
+    }
+
+    errors
+}
+
+#[test]
+fn parser_fixtures_match_expected() {
+    let names = fixture_names();
+    assert!(names.len() >= 3, "expected at least three parser fixtures");
+
+    let mut failures = Vec::new();
+    for name in &names {
+        let dir = fixtures_dir();
+        let request = load_request(&dir.join(format!("{}.request", name)));
+        let expected_path = dir.join(format!("{}.expected.json", name));
+        let expected: Expected = match fs::read_to_string(&expected_path) {
+            Ok(json) => serde_json::from_str(&json)
+                .unwrap_or_else(|e| panic!("{}: {}", expected_path.display(), e)),
+            Err(_) => {
+                failures.push(format!("{}: missing {}.expected.json", name, name));
+                continue;
+            }
+        };
+
+        let result = parse_fixture(&request);
+        for error in check(&result, &expected) {
+            failures.push(format!("{}: {}", name, error));
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "parser fixture mismatches:\n{}",
+        failures.join("\n")
+    );
+}
@@ -1,10 +1,69 @@
 //! API error types.
+//!
+//! Every error is returned as `{"error": {"code": "...", "message": "..."}}`.
+//! `code` is a stable [`ErrorCode`] clients can branch on; `message` is
+//! human-readable and may change.
 
-use axum::http::StatusCode;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use axum::http::{header, StatusCode};
 use axum::response::{IntoResponse, Response};
 use serde::Serialize;
 use thiserror::Error;
 
+use aegis_core::auth::AuthError;
+use aegis_storage::StorageError;
+
+/// Machine-readable error codes, serialized in `SCREAMING_SNAKE_CASE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// No valid session was provided.
+    AuthRequired,
+    /// The password was wrong.
+    InvalidCredentials,
+    /// The session token is unknown or has expired.
+    SessionExpired,
+    /// The requested resource doesn't exist.
+    NotFound,
+    /// The request was malformed or invalid.
+    BadRequest,
+    /// The request body couldn't be parsed.
+    InvalidBody,
+    /// The prompt can't be checked (e.g., it is empty).
+    BadPrompt,
+    /// Too many requests; retry later.
+    RateLimited,
+    /// A versioned update didn't say which version it was based on.
+    VersionRequired,
+    /// A versioned update was based on stale data; re-read and retry.
+    VersionConflict,
+    /// Something failed on the server.
+    Internal,
+    /// The database failed.
+    StorageError,
+}
+
+impl ErrorCode {
+    /// Returns the code as sent on the wire.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::AuthRequired => "AUTH_REQUIRED",
+            ErrorCode::InvalidCredentials => "INVALID_CREDENTIALS",
+            ErrorCode::SessionExpired => "SESSION_EXPIRED",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::BadRequest => "BAD_REQUEST",
+            ErrorCode::InvalidBody => "INVALID_BODY",
+            ErrorCode::BadPrompt => "BAD_PROMPT",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::VersionRequired => "VERSION_REQUIRED",
+            ErrorCode::VersionConflict => "VERSION_CONFLICT",
+            ErrorCode::Internal => "INTERNAL",
+            ErrorCode::StorageError => "STORAGE_ERROR",
+        }
+    }
+}
+
 /// API errors.
 #[derive(Debug, Error)]
 pub enum ApiError {
@@ -28,47 +87,261 @@ pub enum ApiError {
     #[error("bad request: {0}")]
     BadRequest(String),
 
+    /// The request body was missing, not JSON, or the wrong shape.
+    #[error("invalid request body: {message}")]
+    InvalidBody {
+        /// Status chosen by the JSON extractor (400, 415, or 422).
+        status: StatusCode,
+        /// What was wrong with the body.
+        message: String,
+    },
+
+    /// The prompt can't be checked.
+    #[error("bad prompt: {0}")]
+    BadPrompt(String),
+
+    /// Too many requests.
+    #[error("rate limited: retry in {retry_after_secs}s")]
+    RateLimited {
+        /// Seconds until the client may retry, sent as `Retry-After`.
+        retry_after_secs: u64,
+    },
+
+    /// A versioned update omitted `expected_version`.
+    #[error("expected_version is required; read it from GET first")]
+    VersionRequired,
+
     /// Internal server error.
     #[error("internal error: {0}")]
     Internal(String),
 
     /// Storage error.
     #[error("storage error: {0}")]
-    Storage(#[from] aegis_storage::StorageError),
+    Storage(#[from] StorageError),
 
     /// Auth error.
     #[error("auth error: {0}")]
-    Auth(#[from] aegis_core::auth::AuthError),
+    Auth(#[from] AuthError),
+}
+
+impl ApiError {
+    /// Returns the stable code for this error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::Unauthorized => ErrorCode::AuthRequired,
+            ApiError::InvalidCredentials => ErrorCode::InvalidCredentials,
+            ApiError::SessionExpired => ErrorCode::SessionExpired,
+            ApiError::NotFound(_) => ErrorCode::NotFound,
+            ApiError::BadRequest(_) => ErrorCode::BadRequest,
+            ApiError::InvalidBody { .. } => ErrorCode::InvalidBody,
+            ApiError::BadPrompt(_) => ErrorCode::BadPrompt,
+            ApiError::RateLimited { .. } => ErrorCode::RateLimited,
+            ApiError::VersionRequired => ErrorCode::VersionRequired,
+            ApiError::Internal(_) => ErrorCode::Internal,
+            ApiError::Storage(StorageError::VersionConflict { .. }) => ErrorCode::VersionConflict,
+            ApiError::Storage(_) => ErrorCode::StorageError,
+            ApiError::Auth(e) => match e {
+                AuthError::AuthRequired => ErrorCode::AuthRequired,
+                AuthError::SessionInvalid => ErrorCode::SessionExpired,
+                AuthError::PasswordTooShort | AuthError::PasswordEmpty | AuthError::NotSetup => {
+                    ErrorCode::BadRequest
+                }
+                AuthError::HashingFailed(_) | AuthError::VerificationFailed(_) => {
+                    ErrorCode::Internal
+                }
+            },
+        }
+    }
+
+    /// Returns the HTTP status for this error.
+    pub fn status(&self) -> StatusCode {
+        match (self, self.code()) {
+            (ApiError::InvalidBody { status, .. }, _) => *status,
+            (_, ErrorCode::AuthRequired)
+            | (_, ErrorCode::InvalidCredentials)
+            | (_, ErrorCode::SessionExpired) => StatusCode::UNAUTHORIZED,
+            (_, ErrorCode::NotFound) => StatusCode::NOT_FOUND,
+            (_, ErrorCode::BadRequest) | (_, ErrorCode::InvalidBody) => StatusCode::BAD_REQUEST,
+            (_, ErrorCode::BadPrompt) => StatusCode::UNPROCESSABLE_ENTITY,
+            (_, ErrorCode::RateLimited) => StatusCode::TOO_MANY_REQUESTS,
+            (_, ErrorCode::VersionRequired) => StatusCode::PRECONDITION_REQUIRED,
+            (_, ErrorCode::VersionConflict) => StatusCode::CONFLICT,
+            (_, ErrorCode::Internal) | (_, ErrorCode::StorageError) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::InvalidBody {
+            status: rejection.status(),
+            message: rejection.body_text(),
+        }
+    }
 }
 
 /// Error response body.
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
-    pub error: String,
-    pub code: String,
+    /// The error.
+    pub error: ErrorBody,
+}
+
+/// Code and message of an [`ErrorResponse`].
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    /// Stable machine-readable code.
+    pub code: ErrorCode,
+    /// Human-readable description.
+    pub message: String,
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let (status, code) = match &self {
-            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized"),
-            ApiError::InvalidCredentials => (StatusCode::UNAUTHORIZED, "invalid_credentials"),
-            ApiError::SessionExpired => (StatusCode::UNAUTHORIZED, "session_expired"),
-            ApiError::NotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
-            ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, "bad_request"),
-            ApiError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
-            ApiError::Storage(_) => (StatusCode::INTERNAL_SERVER_ERROR, "storage_error"),
-            ApiError::Auth(_) => (StatusCode::INTERNAL_SERVER_ERROR, "auth_error"),
+        let status = self.status();
+        let retry_after = match &self {
+            ApiError::RateLimited { retry_after_secs } => Some(retry_after_secs.to_string()),
+            _ => None,
         };
 
         let body = ErrorResponse {
-            error: self.to_string(),
-            code: code.to_string(),
+            error: ErrorBody {
+                code: self.code(),
+                message: self.to_string(),
+            },
         };
 
-        (status, axum::Json(body)).into_response()
+        let mut response = (status, axum::Json(body)).into_response();
+        if let Some(value) = retry_after.and_then(|v| v.parse().ok()) {
+            response.headers_mut().insert(header::RETRY_AFTER, value);
+        }
+        response
+    }
+}
+
+/// JSON body extractor whose rejections use the [`ErrorResponse`] shape.
+///
+/// Drop-in for [`axum::Json`] in handler arguments.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    axum::Json<T>: FromRequest<S, Rejection = JsonRejection>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> std::result::Result<Self, Self::Rejection> {
+        let axum::Json(value) = axum::Json::<T>::from_request(req, state).await?;
+        Ok(ApiJson(value))
     }
 }
 
 /// Result type for API operations.
 pub type Result<T> = std::result::Result<T, ApiError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_json(error: ApiError) -> (StatusCode, Option<String>, serde_json::Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .map(|v| v.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, retry_after, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn errors_map_to_codes_and_statuses() {
+        let cases = [
+            (
+                ApiError::Unauthorized,
+                StatusCode::UNAUTHORIZED,
+                "AUTH_REQUIRED",
+            ),
+            (
+                ApiError::InvalidCredentials,
+                StatusCode::UNAUTHORIZED,
+                "INVALID_CREDENTIALS",
+            ),
+            (
+                ApiError::SessionExpired,
+                StatusCode::UNAUTHORIZED,
+                "SESSION_EXPIRED",
+            ),
+            (
+                ApiError::NotFound("profile 7".to_string()),
+                StatusCode::NOT_FOUND,
+                "NOT_FOUND",
+            ),
+            (
+                ApiError::BadRequest("invalid action".to_string()),
+                StatusCode::BAD_REQUEST,
+                "BAD_REQUEST",
+            ),
+            (
+                ApiError::BadPrompt("prompt is empty".to_string()),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "BAD_PROMPT",
+            ),
+            (
+                ApiError::VersionRequired,
+                StatusCode::PRECONDITION_REQUIRED,
+                "VERSION_REQUIRED",
+            ),
+            (
+                ApiError::Storage(StorageError::VersionConflict {
+                    expected: 1,
+                    current: 2,
+                }),
+                StatusCode::CONFLICT,
+                "VERSION_CONFLICT",
+            ),
+            (
+                ApiError::Internal("boom".to_string()),
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "INTERNAL",
+            ),
+            (
+                ApiError::Auth(AuthError::AuthRequired),
+                StatusCode::UNAUTHORIZED,
+                "AUTH_REQUIRED",
+            ),
+            (
+                ApiError::Auth(AuthError::PasswordTooShort),
+                StatusCode::BAD_REQUEST,
+                "BAD_REQUEST",
+            ),
+        ];
+
+        for (error, status, code) in cases {
+            assert_eq!(error.code().as_str(), code);
+            let message = error.to_string();
+            let (actual_status, _, json) = body_json(error).await;
+            assert_eq!(actual_status, status, "{}", code);
+            assert_eq!(json["error"]["code"], code);
+            assert_eq!(json["error"]["message"], message);
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limited_sets_retry_after() {
+        let (status, retry_after, json) = body_json(ApiError::RateLimited {
+            retry_after_secs: 30,
+        })
+        .await;
+
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(retry_after.as_deref(), Some("30"));
+        assert_eq!(json["error"]["code"], "RATE_LIMITED");
+    }
+}
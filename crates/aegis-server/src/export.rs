@@ -0,0 +1,206 @@
+//! Streaming exports of event logs and flagged events.
+//!
+//! Rows are read a page at a time and written to the response as they are
+//! fetched, so an export never holds the whole range in memory or keeps the
+//! database locked while a slow client reads.
+
+use std::borrow::Cow;
+
+use axum::body::Body;
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+
+use crate::models::{ExportFormat, FlaggedEntry, LogEntry};
+
+/// Rows fetched from the database per page of an export.
+pub const EXPORT_PAGE_SIZE: i64 = 500;
+
+/// A row that can be written to an export.
+pub trait ExportRow: Serialize {
+    /// Column names, in the order [`ExportRow::csv_fields`] returns them.
+    const CSV_HEADER: &'static [&'static str];
+
+    /// Row ID, used as the cursor for the next page.
+    fn id(&self) -> i64;
+
+    /// Field values for a CSV record, unescaped.
+    fn csv_fields(&self) -> Vec<Cow<'_, str>>;
+}
+
+impl ExportRow for LogEntry {
+    const CSV_HEADER: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "action",
+        "category",
+        "confidence",
+        "source",
+        "model",
+        "preview",
+    ];
+
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn csv_fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.id.to_string().into(),
+            self.created_at.to_rfc3339().into(),
+            self.action.as_str().into(),
+            self.category.map(|c| c.name()).unwrap_or_default().into(),
+            self.confidence
+                .map(|c| format!("{:.2}", c))
+                .unwrap_or_default()
+                .into(),
+            self.source.as_deref().unwrap_or_default().into(),
+            self.model.as_deref().unwrap_or_default().into(),
+            self.preview.as_str().into(),
+        ]
+    }
+}
+
+impl ExportRow for FlaggedEntry {
+    const CSV_HEADER: &'static [&'static str] = &[
+        "id",
+        "created_at",
+        "profile_id",
+        "profile_name",
+        "flag_type",
+        "confidence",
+        "source",
+        "acknowledged",
+        "occurrences",
+        "matched_phrases",
+        "content_snippet",
+    ];
+
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn csv_fields(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            self.id.to_string().into(),
+            self.created_at.to_rfc3339().into(),
+            self.profile_id.to_string().into(),
+            self.profile_name.as_deref().unwrap_or_default().into(),
+            self.flag_type.as_str().into(),
+            format!("{:.2}", self.confidence).into(),
+            self.source.as_deref().unwrap_or_default().into(),
+            self.acknowledged.to_string().into(),
+            self.occurrences.to_string().into(),
+            self.matched_phrases.join("; ").into(),
+            self.content_snippet.as_str().into(),
+        ]
+    }
+}
+
+/// Quotes a CSV field if it contains a delimiter, quote, or line break
+/// (RFC 4180), doubling any quotes inside it.
+pub fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Formats one CSV record, including the trailing CRLF.
+fn csv_record<'a>(fields: impl IntoIterator<Item = Cow<'a, str>>) -> String {
+    let mut record = fields
+        .into_iter()
+        .map(|field| csv_field(&field).into_owned())
+        .collect::<Vec<_>>()
+        .join(",");
+    record.push_str("\r\n");
+    record
+}
+
+/// Encodes a page of rows in `format`.
+fn encode_page<T: ExportRow>(format: ExportFormat, rows: &[T]) -> String {
+    let mut chunk = String::new();
+    for row in rows {
+        match format {
+            ExportFormat::Csv => chunk.push_str(&csv_record(row.csv_fields())),
+            ExportFormat::Json => {
+                // Serializing these plain structs can't fail
+                if let Ok(line) = serde_json::to_string(row) {
+                    chunk.push_str(&line);
+                    chunk.push('\n');
+                }
+            }
+        }
+    }
+    chunk
+}
+
+/// Builds a streaming attachment response named `name` (without extension).
+///
+/// `fetch` is called with the ID of the last row written (0 at first) and
+/// returns the next page of at most [`EXPORT_PAGE_SIZE`] rows. A storage
+/// error after the first page ends the body early.
+pub fn export_response<T, F>(format: ExportFormat, name: &str, fetch: F) -> Response
+where
+    T: ExportRow + Send + 'static,
+    F: FnMut(i64) -> aegis_storage::Result<Vec<T>> + Send + 'static,
+{
+    let header = match format {
+        ExportFormat::Csv => csv_record(T::CSV_HEADER.iter().map(|&h| Cow::Borrowed(h))),
+        ExportFormat::Json => String::new(),
+    };
+
+    let pages = stream::unfold((fetch, Some(0)), move |(mut fetch, cursor)| async move {
+        let cursor = cursor?;
+        match fetch(cursor) {
+            Ok(rows) if rows.is_empty() => None,
+            Ok(rows) => {
+                // A short page is the last one
+                let next = if (rows.len() as i64) < EXPORT_PAGE_SIZE {
+                    None
+                } else {
+                    rows.last().map(ExportRow::id)
+                };
+                Some((Ok(encode_page(format, &rows)), (fetch, next)))
+            }
+            Err(e) => {
+                tracing::warn!("Export stopped early: {}", e);
+                Some((Err(e), (fetch, None)))
+            }
+        }
+    });
+    let body = stream::once(async move { Ok(header) })
+        .chain(pages)
+        .filter(|chunk| std::future::ready(!matches!(chunk, Ok(c) if c.is_empty())));
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}.{}\"", name, format.extension()),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("plain text"), "plain text");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(
+            csv_record(["1".into(), "x,y".into(), "".into()]),
+            "1,\"x,y\",\r\n"
+        );
+    }
+}
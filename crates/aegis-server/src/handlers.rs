@@ -1,53 +1,166 @@
 //! API route handlers.
 
 use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
+use axum::response::Response;
 use axum::Json;
-use tracing::{debug, info};
+use chrono::{Duration, Utc};
+use futures::stream::{self, Stream};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
 
 use aegis_core::auth::SessionToken;
-use aegis_core::classifier::SentimentFlag;
-use aegis_storage::{models::Action, NewRule, PauseDuration};
+use aegis_core::classifier::{
+    CategoryMatch, ClassificationTier, NsfwThresholdPreset, PromptGuardError, SentimentFlag,
+};
+use aegis_core::extension_token::EXTENSION_TOKEN_HEADER;
+use aegis_core::keyword_import::{KeywordFormat, KeywordImport};
+use aegis_core::model_downloader::DownloadProgress;
+use aegis_core::profile::{get_current_os_user, normalize_os_username};
+use aegis_core::rule_validation::RuleDraft;
+use aegis_core::site_registry::SiteSource;
+use aegis_proxy::{CanaryStatus, ParserRegistry};
+use aegis_storage::NsfwThresholdPreset as StoredNsfwThresholdPreset;
+use aegis_storage::{
+    models::Action, Event, FlaggedEvent, LiveUpdate, NewProfile, NewRule, NewSite, PauseDuration,
+    ProfileImageFilteringConfig, Site,
+};
 
-use crate::error::{ApiError, Result};
+use crate::error::{ApiError, ApiJson, Result};
+use crate::export::{export_response, EXPORT_PAGE_SIZE};
+use crate::model_download::DownloadStart;
 use crate::models::{
     AcknowledgeAllRequest, AcknowledgeRequest, AcknowledgeResponse, AuthVerifyRequest,
-    AuthVerifyResponse, CategoryCountsResponse, CategoryMatchResponse, CheckRequest, CheckResponse,
-    DeleteFlaggedRequest, FlaggedEntry, FlaggedQuery, FlaggedResponse, FlaggedStatsResponse,
-    FlaggedTypeCounts, LogEntry, LogsQuery, LogsResponse, PauseProtectionRequest,
-    ProtectionResponse, ProtectionStatusResponse, ReloadRulesRequest, ReloadRulesResponse,
-    ResumeProtectionRequest, RuleEntry, RulesResponse, StatsResponse, UpdateRulesRequest,
-    UpdateRulesResponse,
+    AuthVerifyResponse, BlockAllRequest, CanaryCheckResponse, CategoryCountsResponse,
+    CategoryMatchResponse, CheckBatchRequest, CheckBatchResponse, CheckRequest, CheckResponse,
+    ClassifierStatsResponse, ClearBlockAllRequest, CreateSiteRequest, DeleteFlaggedRequest,
+    ExplanationResponse, ExportQuery, FlaggedEntry, FlaggedQuery, FlaggedResponse,
+    FlaggedStatsResponse, FlaggedTypeCounts, HealthResponse, ImageFilteringResponse,
+    ImportKeywordsRequest, ImportKeywordsResponse, LogEntry, LogsQuery, LogsResponse,
+    ModelCountEntry, ModelDownloadQuery, ModelStatusResponse, PauseProtectionRequest,
+    ProtectionResponse, ProtectionStatusResponse, ProxyStatusResponse, ReloadRulesRequest,
+    ReloadRulesResponse, ResumeProtectionRequest, RuleEntry, RulesResponse, SitesResponse,
+    StatsDelta, StatsResponse, TestRulesRequest, TestRulesResponse, TopCategoryEntry,
+    TopServiceEntry, UpdateImageFilteringRequest, UpdateRulesRequest, UpdateRulesResponse,
+    ValidateRulesResponse, MAX_BATCH_PROMPTS, MAX_MATCHED_TERM_CHARS, TOP_STATS_DAYS,
+    TOP_STATS_LIMIT,
 };
 use crate::state::AppState;
 
+/// Rejects the request unless it carries the extension token, when one is
+/// configured.
+fn require_extension_token(state: &AppState, headers: &HeaderMap) -> Result<()> {
+    let Some(ref token) = state.extension_token else {
+        return Ok(());
+    };
+    let presented = headers
+        .get(EXTENSION_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if presented.is_some_and(|presented| token.verify(presented)) {
+        Ok(())
+    } else {
+        debug!("Rejected request without a valid extension token");
+        Err(ApiError::Unauthorized)
+    }
+}
+
 /// POST /api/check - Classify a prompt and return action.
+///
+/// Requires the extension token header when the server has one.
 pub async fn check_prompt(
     State(state): State<AppState>,
-    Json(req): Json<CheckRequest>,
+    headers: HeaderMap,
+    req: CheckRequest,
 ) -> Result<Json<CheckResponse>> {
+    require_extension_token(&state, &headers)?;
+
     debug!(
         prompt_len = req.prompt.len(),
         os_username = ?req.os_username,
+        context_len = req.context.len(),
         "Checking prompt"
     );
 
+    if req.prompt.trim().is_empty() {
+        return Err(ApiError::BadPrompt("prompt is empty".to_string()));
+    }
+
+    Ok(Json(check_one(
+        &state,
+        &req.prompt,
+        req.os_username.as_deref(),
+        &req.context,
+    )))
+}
+
+/// POST /api/check/batch - Classify several prompts in one request.
+///
+/// Each prompt goes through the same path as `/api/check`; results come back
+/// in request order. Requires the extension token header when the server
+/// has one.
+pub async fn check_prompt_batch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ApiJson(req): ApiJson<CheckBatchRequest>,
+) -> Result<Json<CheckBatchResponse>> {
+    require_extension_token(&state, &headers)?;
+
+    debug!(
+        prompts = req.prompts.len(),
+        os_username = ?req.os_username,
+        "Checking prompt batch"
+    );
+
+    if req.prompts.len() > MAX_BATCH_PROMPTS {
+        return Err(ApiError::BadRequest(format!(
+            "batch has {} prompts; at most {} are allowed",
+            req.prompts.len(),
+            MAX_BATCH_PROMPTS
+        )));
+    }
+    if let Some(index) = req.prompts.iter().position(|p| p.trim().is_empty()) {
+        return Err(ApiError::BadPrompt(format!("prompt {} is empty", index)));
+    }
+
+    let started = std::time::Instant::now();
+    let results = req
+        .prompts
+        .iter()
+        .map(|prompt| check_one(&state, prompt, req.os_username.as_deref(), &[]))
+        .collect();
+
+    Ok(Json(CheckBatchResponse {
+        results,
+        total_latency_ms: started.elapsed().as_millis() as u64,
+    }))
+}
+
+/// Classifies one non-empty prompt, applies the rules, and logs the event.
+fn check_one(
+    state: &AppState,
+    prompt: &str,
+    os_username: Option<&str>,
+    context: &[String],
+) -> CheckResponse {
     // Check if protection is enabled (F032 - read from database)
     let is_filtering_enabled = state.state_manager.is_filtering_enabled().unwrap_or(true); // Default to enabled on error
 
     if !is_filtering_enabled {
         debug!("Protection is paused/disabled, allowing prompt");
-        return Ok(Json(CheckResponse {
+        return CheckResponse {
             action: aegis_core::rule_engine::RuleAction::Allow,
             reason: "protection_paused".to_string(),
             categories: vec![],
+            explanations: vec![],
             latency_ms: 0,
-        }));
+        };
     }
 
     // Classify the prompt
     let classification = {
         let mut classifier = state.classifier.write().unwrap();
-        classifier.classify(&req.prompt)
+        classifier.classify_with_context(prompt, context)
     };
 
     // Get the rule engine (use profile-specific rules if os_username provided)
@@ -56,20 +169,20 @@ pub async fn check_prompt(
         let rules = state.rules.read().unwrap();
 
         // If os_username provided and matches a profile, use that profile's rules
-        if let Some(ref username) = req.os_username {
+        if let Some(username) = os_username {
             if let Some(profile) = profiles.get_by_os_username(username) {
                 // Create a temporary rule engine with profile's rules
                 let profile_engine = aegis_core::rule_engine::RuleEngine {
                     time_rules: profile.time_rules.clone(),
                     content_rules: profile.content_rules.clone(),
                 };
-                profile_engine.evaluate_now(&classification)
+                profile_engine.evaluate_prompt_now(prompt, &classification)
             } else {
                 // No profile found - use default rules
-                rules.evaluate_now(&classification)
+                rules.evaluate_prompt_now(prompt, &classification)
             }
         } else {
-            rules.evaluate_now(&classification)
+            rules.evaluate_prompt_now(prompt, &classification)
         }
     };
 
@@ -78,12 +191,12 @@ pub async fn check_prompt(
     let confidence = classification.highest_confidence().map(|m| m.confidence);
     let action = match rule_result.action {
         aegis_core::rule_engine::RuleAction::Allow => Action::Allowed,
-        aegis_core::rule_engine::RuleAction::Warn => Action::Flagged,
+        aegis_core::rule_engine::RuleAction::Warn => Action::Warned,
         aegis_core::rule_engine::RuleAction::Block => Action::Blocked,
     };
 
     let _ = state.db.log_event(
-        &req.prompt,
+        prompt,
         category,
         confidence,
         action,
@@ -92,28 +205,26 @@ pub async fn check_prompt(
 
     // Run sentiment analysis and flag emotional content
     // Get profile ID - use provided os_username or auto-detect current user
-    let effective_username = req.os_username.clone().or_else(|| {
-        std::env::var("USERNAME")
-            .or_else(|_| std::env::var("USER"))
-            .ok()
+    let effective_username = os_username
+        .map(normalize_os_username)
+        .unwrap_or_else(get_current_os_user);
+
+    let profile_id = state.db.get_all_profiles().ok().and_then(|profiles| {
+        profiles
+            .iter()
+            .find(|p| {
+                p.os_username
+                    .as_deref()
+                    .is_some_and(|u| normalize_os_username(u) == effective_username)
+            })
+            .map(|p| p.id)
     });
 
-    let profile_id = if let Some(ref username) = effective_username {
-        state.db.get_all_profiles().ok().and_then(|profiles| {
-            profiles
-                .iter()
-                .find(|p| p.os_username.as_ref() == Some(username))
-                .map(|p| p.id)
-        })
-    } else {
-        None
-    };
-
     // Run sentiment analysis if we have a profile
     if let Some(pid) = profile_id {
         let sentiment_result = {
             let analyzer = state.sentiment_analyzer.write().unwrap();
-            analyzer.analyze(&req.prompt)
+            analyzer.analyze(prompt)
         };
 
         // Store flagged events
@@ -129,7 +240,7 @@ pub async fn check_prompt(
                 pid,
                 flag_type,
                 flag.confidence,
-                &req.prompt,
+                prompt,
                 Some("browser-extension".to_string()),
                 flag.matched_phrases.clone(),
             ) {
@@ -161,6 +272,17 @@ pub async fn check_prompt(
         })
         .collect();
 
+    let explanations = classification
+        .matches
+        .iter()
+        .map(|m| ExplanationResponse {
+            category: m.category,
+            tier: m.tier,
+            confidence: m.confidence,
+            matched_term: matched_term(m, prompt),
+        })
+        .collect();
+
     let latency_ms = classification.duration_us / 1000;
 
     info!(
@@ -170,23 +292,76 @@ pub async fn check_prompt(
         "Prompt check complete"
     );
 
-    Ok(Json(CheckResponse {
+    CheckResponse {
         action: rule_result.action,
         reason,
         categories,
+        explanations,
         latency_ms,
-    }))
+    }
+}
+
+/// Returns the keyword text behind `m`, shortened so it can't echo `prompt`.
+///
+/// ML matches have no term. A term covering the whole prompt is dropped, and
+/// longer terms (e.g., a broad regex match) are cut to
+/// [`MAX_MATCHED_TERM_CHARS`].
+fn matched_term(m: &CategoryMatch, prompt: &str) -> Option<String> {
+    if m.tier != ClassificationTier::Keyword {
+        return None;
+    }
+    let term = m.matched_pattern.as_deref()?.trim();
+    if term.is_empty() || term.len() >= prompt.trim().len() {
+        return None;
+    }
+    if term.chars().count() <= MAX_MATCHED_TERM_CHARS {
+        return Some(term.to_string());
+    }
+    let mut shortened: String = term.chars().take(MAX_MATCHED_TERM_CHARS - 1).collect();
+    shortened.push('…');
+    Some(shortened)
 }
 
 /// GET /api/stats - Get aggregated statistics.
 pub async fn get_stats(State(state): State<AppState>) -> Result<Json<StatsResponse>> {
     let stats = state.db.get_total_stats()?;
+    let since = Utc::now() - Duration::days(TOP_STATS_DAYS);
+    let top_categories = state
+        .db
+        .top_categories(Action::Blocked, since, TOP_STATS_LIMIT)?
+        .into_iter()
+        .map(|c| TopCategoryEntry {
+            category: c.category,
+            count: c.count,
+        })
+        .collect();
+    let top_services = state
+        .db
+        .top_sources(since, TOP_STATS_LIMIT)?
+        .into_iter()
+        .map(|s| TopServiceEntry {
+            service: s.source,
+            count: s.count,
+        })
+        .collect();
+    let by_model = state
+        .db
+        .count_events_by_model()?
+        .into_iter()
+        .map(|m| ModelCountEntry {
+            model: m.model,
+            count: m.count,
+        })
+        .collect();
 
     Ok(Json(StatsResponse {
         total_prompts: stats.total_prompts,
         blocked_count: stats.blocked_count,
-        warned_count: stats.flagged_count,
+        warned_count: stats.warned_count,
         allowed_count: stats.allowed_count,
+        flagged_count: stats.flagged_count,
+        would_block_count: stats.would_block_count,
+        redacted_count: stats.redacted_count,
         category_counts: CategoryCountsResponse {
             violence: stats.category_counts.violence,
             self_harm: stats.category_counts.self_harm,
@@ -195,6 +370,9 @@ pub async fn get_stats(State(state): State<AppState>) -> Result<Json<StatsRespon
             hate: stats.category_counts.hate,
             illegal: stats.category_counts.illegal,
         },
+        top_categories,
+        top_services,
+        by_model,
     }))
 }
 
@@ -203,36 +381,115 @@ pub async fn get_logs(
     State(state): State<AppState>,
     Query(query): Query<LogsQuery>,
 ) -> Result<Json<LogsResponse>> {
-    let events = if let Some(ref action_str) = query.action {
-        let action = parse_action(action_str)?;
-        state
+    let action = query.action.as_deref().map(parse_action).transpose()?;
+    let events = match (action, query.before) {
+        (Some(action), Some(before)) => {
+            state
+                .db
+                .get_events_by_action_before(action, before, query.limit)?
+        }
+        (None, Some(before)) => state.db.get_events_before(before, query.limit)?,
+        (Some(action), None) => state
             .db
-            .get_events_by_action(action, query.limit, query.offset)?
-    } else {
-        state.db.get_recent_events(query.limit, query.offset)?
+            .get_events_by_action(action, query.limit, query.offset)?,
+        (None, None) => state.db.get_recent_events(query.limit, query.offset)?,
     };
 
     let total = state.db.count_events()?;
+    // A short page is the last one
+    let next_cursor = if events.len() as i64 >= query.limit && query.limit > 0 {
+        events.iter().map(|e| e.id).min()
+    } else {
+        None
+    };
 
-    let logs: Vec<LogEntry> = events
-        .into_iter()
-        .map(|e| LogEntry {
-            id: e.id,
-            preview: e.preview,
-            category: e.category,
-            confidence: e.confidence,
-            action: format!("{:?}", e.action).to_lowercase(),
-            source: e.source,
-            created_at: e.created_at,
-        })
-        .collect();
+    let logs: Vec<LogEntry> = events.into_iter().map(log_entry).collect();
+
+    Ok(Json(LogsResponse {
+        logs,
+        total,
+        next_cursor,
+    }))
+}
+
+fn log_entry(e: Event) -> LogEntry {
+    LogEntry {
+        id: e.id,
+        preview: e.preview,
+        category: e.category,
+        confidence: e.confidence,
+        action: e.action.as_str().to_string(),
+        source: e.source,
+        model: e.model,
+        created_at: e.created_at,
+    }
+}
+
+/// GET /api/logs/export - Download event logs created in a time range.
+///
+/// Streams CSV (default) or newline-delimited JSON (`format=json`) as an
+/// attachment, oldest first.
+pub async fn export_logs(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response> {
+    check_export_range(&query)?;
+    let (from, to) = (query.from, query.to);
+    Ok(export_response(query.format, "aegis-logs", move |after| {
+        let events = state
+            .db
+            .get_events_in_range_after(from, to, after, EXPORT_PAGE_SIZE)?;
+        Ok(events.into_iter().map(log_entry).collect())
+    }))
+}
+
+fn check_export_range(query: &ExportQuery) -> Result<()> {
+    match (query.from, query.to) {
+        (Some(from), Some(to)) if from > to => Err(ApiError::BadRequest(
+            "`from` must not be after `to`".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// GET /api/events/stream - Live event log entries and stat deltas.
+///
+/// Sends an `event: log` frame (a [`LogEntry`]) for each logged event and an
+/// `event: stats` frame (a [`StatsDelta`]) for each counted prompt, from the
+/// server and the proxy alike. A client that falls behind skips the updates
+/// it missed rather than holding up logging.
+pub async fn events_stream(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = std::result::Result<SseEvent, axum::Error>>> {
+    let updates = stream::unfold(state.db.subscribe(), |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(update) => return Some((live_update_frame(update), rx)),
+                Err(RecvError::Lagged(skipped)) => {
+                    debug!("Event stream client lagged; skipped {} updates", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(updates).keep_alive(KeepAlive::default())
+}
 
-    Ok(Json(LogsResponse { logs, total }))
+fn live_update_frame(update: LiveUpdate) -> std::result::Result<SseEvent, axum::Error> {
+    match update {
+        LiveUpdate::Event(event) => SseEvent::default().event("log").json_data(log_entry(event)),
+        LiveUpdate::Stats { action, category } => {
+            SseEvent::default().event("stats").json_data(StatsDelta {
+                action: action.as_str().to_string(),
+                category,
+            })
+        }
+    }
 }
 
 /// GET /api/rules - Get all rules.
 pub async fn get_rules(State(state): State<AppState>) -> Result<Json<RulesResponse>> {
-    let rules = state.db.get_all_rules()?;
+    let (rules, version) = state.db.get_all_rules_versioned()?;
 
     let rule_entries: Vec<RuleEntry> = rules
         .into_iter()
@@ -249,13 +506,17 @@ pub async fn get_rules(State(state): State<AppState>) -> Result<Json<RulesRespon
 
     Ok(Json(RulesResponse {
         rules: rule_entries,
+        version,
     }))
 }
 
 /// PUT /api/rules - Update rules (requires auth).
+///
+/// The update applies only if the rules are still at `expected_version`;
+/// otherwise it fails with 409 so a concurrent save isn't overwritten.
 pub async fn update_rules(
     State(state): State<AppState>,
-    Json(req): Json<UpdateRulesRequest>,
+    ApiJson(req): ApiJson<UpdateRulesRequest>,
 ) -> Result<Json<UpdateRulesResponse>> {
     // Validate session token
     let token = SessionToken::from_string(req.session_token);
@@ -263,33 +524,210 @@ pub async fn update_rules(
         return Err(ApiError::SessionExpired);
     }
 
-    // Update rules
-    let mut updated_count = 0;
-    for rule_update in req.rules {
-        let new_rule = NewRule {
-            name: rule_update.name,
-            enabled: rule_update.enabled,
-            config: rule_update.config,
-            priority: rule_update.priority,
-        };
+    let expected_version = req.expected_version.ok_or(ApiError::VersionRequired)?;
 
-        if state.db.update_rule(rule_update.id, new_rule).is_ok() {
-            updated_count += 1;
-        }
-    }
+    let updates = req
+        .rules
+        .into_iter()
+        .map(|rule_update| {
+            let new_rule = NewRule {
+                name: rule_update.name,
+                enabled: rule_update.enabled,
+                config: rule_update.config,
+                priority: rule_update.priority,
+            };
+            (rule_update.id, new_rule)
+        })
+        .collect();
+
+    let (updated_count, version) = state
+        .db
+        .update_rules_if_version(expected_version, updates)?;
 
-    info!(updated_count, "Rules updated");
+    info!(updated_count, version, "Rules updated");
 
     Ok(Json(UpdateRulesResponse {
         success: true,
         updated_count,
+        version,
     }))
 }
 
+/// POST /api/rules/validate - Check a rule draft without saving it.
+///
+/// Always succeeds for a well-formed body; problems are reported as issues.
+pub async fn validate_rules(
+    ApiJson(draft): ApiJson<RuleDraft>,
+) -> Result<Json<ValidateRulesResponse>> {
+    let issues = draft.validate();
+    let valid = !issues.iter().any(|issue| issue.is_error());
+
+    debug!(valid, issues = issues.len(), "Validated rule draft");
+
+    Ok(Json(ValidateRulesResponse { valid, issues }))
+}
+
+/// POST /api/rules/test - Evaluate a rule draft against a sample prompt.
+///
+/// Builds a throwaway rule engine from the draft and classifies the prompt
+/// with the live classifier; nothing is saved, logged, or applied to
+/// filtering. A draft with errors is rejected with 400.
+pub async fn test_rules(
+    State(state): State<AppState>,
+    ApiJson(req): ApiJson<TestRulesRequest>,
+) -> Result<Json<TestRulesResponse>> {
+    if req.prompt.trim().is_empty() {
+        return Err(ApiError::BadPrompt("prompt is empty".to_string()));
+    }
+
+    let engine = req.rules.to_engine().map_err(|errors| {
+        let messages: Vec<String> = errors
+            .iter()
+            .map(|issue| format!("{}: {}", issue.field, issue.message))
+            .collect();
+        ApiError::BadRequest(format!("invalid rules: {}", messages.join("; ")))
+    })?;
+    let warnings = req.rules.validate();
+
+    let classification = {
+        let mut classifier = state.classifier.write().unwrap();
+        classifier.classify(&req.prompt)
+    };
+    let result = engine.evaluate_prompt_now(&req.prompt, &classification);
+
+    debug!(action = ?result.action, "Tested rule draft");
+
+    Ok(Json(TestRulesResponse {
+        action: result.action,
+        rule_id: result.source.rule_id().map(str::to_string),
+        rule_name: result.source.rule_name().map(str::to_string),
+        categories: classification
+            .matches
+            .iter()
+            .map(|m| CategoryMatchResponse {
+                category: m.category,
+                confidence: m.confidence,
+                tier: m.tier,
+            })
+            .collect(),
+        warnings,
+    }))
+}
+
+/// POST /api/keywords/import - Import a CSV/JSON keyword blocklist.
+///
+/// Terms are merged into the stored parent overrides and, when the proxy's
+/// rule manager is shared, applied to live filtering.
+pub async fn import_keywords(
+    State(state): State<AppState>,
+    ApiJson(req): ApiJson<ImportKeywordsRequest>,
+) -> Result<Json<ImportKeywordsResponse>> {
+    let token = SessionToken::from_string(req.session_token);
+    if !state.auth.validate_session(&token) {
+        return Err(ApiError::SessionExpired);
+    }
+
+    let format = req
+        .format
+        .unwrap_or_else(|| KeywordFormat::detect(&req.content));
+    let import = KeywordImport::parse(&req.content, format)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    if import.is_empty() {
+        return Err(ApiError::BadRequest(
+            "keyword list has no terms".to_string(),
+        ));
+    }
+
+    let (summary, overrides) = state.db.import_keywords(&import)?;
+    if let Some(ref community_rules) = state.community_rules {
+        community_rules.write().unwrap().set_overrides(overrides);
+    }
+
+    info!(
+        blocked = summary.blocked,
+        allowed = summary.allowed,
+        "Keywords imported"
+    );
+
+    Ok(Json(ImportKeywordsResponse {
+        success: true,
+        blocked: summary.blocked,
+        allowed: summary.allowed,
+        unchanged: summary.unchanged,
+        duplicates: summary.duplicates,
+    }))
+}
+
+/// GET /api/sites - List stored sites.
+pub async fn get_sites(State(state): State<AppState>) -> Result<Json<SitesResponse>> {
+    Ok(Json(SitesResponse {
+        sites: state.db.get_all_sites()?,
+    }))
+}
+
+/// POST /api/sites - Add a custom site (requires auth).
+///
+/// The site's parser, service name, and endpoints tell the proxy how to
+/// extract prompts from it. When the proxy's site registry is shared, the
+/// site is inspected without a restart.
+pub async fn create_site(
+    State(state): State<AppState>,
+    ApiJson(req): ApiJson<CreateSiteRequest>,
+) -> Result<(StatusCode, Json<Site>)> {
+    let token = SessionToken::from_string(req.session_token);
+    if !state.auth.validate_session(&token) {
+        return Err(ApiError::SessionExpired);
+    }
+
+    let pattern = req.pattern.trim().to_lowercase();
+    if pattern.is_empty() || req.name.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "site pattern and name are required".to_string(),
+        ));
+    }
+    if let Some(ref parser_id) = req.parser_id {
+        if ParserRegistry::with_defaults().get(parser_id).is_none() {
+            return Err(ApiError::BadRequest(format!(
+                "unknown parser: {}",
+                parser_id
+            )));
+        }
+    }
+    if state.db.get_site_by_pattern(&pattern)?.is_some() {
+        return Err(ApiError::BadRequest(format!(
+            "site {} already exists",
+            pattern
+        )));
+    }
+
+    let id = state.db.create_site(NewSite {
+        pattern,
+        name: req.name.trim().to_string(),
+        category: req.category.as_str().to_string(),
+        parser_id: req.parser_id,
+        enabled: true,
+        source: SiteSource::Custom.as_str().to_string(),
+        priority: req.priority,
+        service_name: req.service_name,
+        endpoints: req.endpoints,
+    })?;
+    let site = state
+        .db
+        .get_site(id)?
+        .ok_or_else(|| ApiError::Internal(format!("site {} missing after insert", id)))?;
+    if let Some(ref registry) = state.site_registry {
+        registry.add_custom(site.to_site_entry());
+    }
+
+    info!(pattern = %site.pattern, parser = ?site.parser_id, "Custom site added");
+
+    Ok((StatusCode::CREATED, Json(site)))
+}
+
 /// POST /api/auth/verify - Verify password and get session token.
 pub async fn verify_auth(
     State(state): State<AppState>,
-    Json(req): Json<AuthVerifyRequest>,
+    ApiJson(req): ApiJson<AuthVerifyRequest>,
 ) -> Result<Json<AuthVerifyResponse>> {
     // Check if auth is set up
     if !state.db.is_auth_setup()? {
@@ -298,6 +736,17 @@ pub async fn verify_auth(
         ));
     }
 
+    if let Some(remaining) = state
+        .login_limiter
+        .lock()
+        .unwrap()
+        .locked_for(std::time::Instant::now())
+    {
+        return Err(ApiError::RateLimited {
+            retry_after_secs: remaining.as_secs_f64().ceil() as u64,
+        });
+    }
+
     // Get stored hash
     let hash = state.db.get_password_hash()?;
 
@@ -308,12 +757,20 @@ pub async fn verify_auth(
         .map_err(|_| ApiError::InvalidCredentials)?;
 
     if !is_valid {
+        state
+            .login_limiter
+            .lock()
+            .unwrap()
+            .record_failure(std::time::Instant::now());
+        warn!("Failed password attempt");
         return Ok(Json(AuthVerifyResponse {
             success: false,
             session_token: None,
         }));
     }
 
+    state.login_limiter.lock().unwrap().record_success();
+
     // Create session
     let token = state.auth.create_session();
 
@@ -333,7 +790,10 @@ fn parse_action(s: &str) -> Result<Action> {
     match s.to_lowercase().as_str() {
         "allowed" => Ok(Action::Allowed),
         "blocked" => Ok(Action::Blocked),
-        "flagged" | "warned" => Ok(Action::Flagged),
+        "flagged" => Ok(Action::Flagged),
+        "warned" => Ok(Action::Warned),
+        "would_block" => Ok(Action::WouldBlock),
+        "redacted" => Ok(Action::Redacted),
         _ => Err(ApiError::BadRequest(format!("invalid action: {}", s))),
     }
 }
@@ -365,19 +825,7 @@ pub async fn get_flagged(
             }
             true
         })
-        .map(|e| FlaggedEntry {
-            id: e.id,
-            profile_id: e.profile_id,
-            profile_name: e.profile_name,
-            flag_type: e.flag_type,
-            confidence: e.confidence,
-            content_snippet: e.content_snippet,
-            source: e.source,
-            matched_phrases: e.matched_phrases,
-            acknowledged: e.acknowledged,
-            acknowledged_at: e.acknowledged_at,
-            created_at: e.created_at,
-        })
+        .map(flagged_entry)
         .collect();
 
     let stats = state.db.get_flagged_event_stats()?;
@@ -389,6 +837,46 @@ pub async fn get_flagged(
     }))
 }
 
+fn flagged_entry(e: FlaggedEvent) -> FlaggedEntry {
+    FlaggedEntry {
+        id: e.id,
+        profile_id: e.profile_id,
+        profile_name: e.profile_name,
+        flag_type: e.flag_type,
+        confidence: e.confidence,
+        content_snippet: e.content_snippet,
+        source: e.source,
+        matched_phrases: e.matched_phrases,
+        acknowledged: e.acknowledged,
+        acknowledged_at: e.acknowledged_at,
+        created_at: e.created_at,
+        occurrences: e.occurrences,
+    }
+}
+
+/// GET /api/flagged/export - Download flagged events created in a time range.
+///
+/// Streams CSV (default) or newline-delimited JSON (`format=json`) as an
+/// attachment, oldest first.
+pub async fn export_flagged(
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response> {
+    check_export_range(&query)?;
+    let (from, to) = (query.from, query.to);
+    Ok(export_response(
+        query.format,
+        "aegis-flagged",
+        move |after| {
+            let events =
+                state
+                    .db
+                    .get_flagged_events_in_range_after(from, to, after, EXPORT_PAGE_SIZE)?;
+            Ok(events.into_iter().map(flagged_entry).collect())
+        },
+    ))
+}
+
 /// GET /api/flagged/stats - Get flagged event statistics.
 pub async fn get_flagged_stats(
     State(state): State<AppState>,
@@ -411,7 +899,7 @@ pub async fn get_flagged_stats(
 pub async fn acknowledge_flagged(
     State(state): State<AppState>,
     Path(id): Path<i64>,
-    Json(req): Json<AcknowledgeRequest>,
+    ApiJson(req): ApiJson<AcknowledgeRequest>,
 ) -> Result<Json<AcknowledgeResponse>> {
     // Validate session token
     let token = SessionToken::from_string(req.session_token);
@@ -432,7 +920,7 @@ pub async fn acknowledge_flagged(
 /// POST /api/flagged/acknowledge-all - Acknowledge multiple flagged events.
 pub async fn acknowledge_all_flagged(
     State(state): State<AppState>,
-    Json(req): Json<AcknowledgeAllRequest>,
+    ApiJson(req): ApiJson<AcknowledgeAllRequest>,
 ) -> Result<Json<AcknowledgeResponse>> {
     // Validate session token
     let token = SessionToken::from_string(req.session_token);
@@ -470,7 +958,7 @@ pub async fn acknowledge_all_flagged(
 pub async fn delete_flagged(
     State(state): State<AppState>,
     Path(id): Path<i64>,
-    Json(req): Json<DeleteFlaggedRequest>,
+    ApiJson(req): ApiJson<DeleteFlaggedRequest>,
 ) -> Result<Json<AcknowledgeResponse>> {
     // Validate session token
     let token = SessionToken::from_string(req.session_token);
@@ -497,7 +985,7 @@ pub async fn delete_flagged(
 /// rules in the UI to apply changes to the running proxy.
 pub async fn reload_rules(
     State(state): State<AppState>,
-    Json(req): Json<ReloadRulesRequest>,
+    ApiJson(req): ApiJson<ReloadRulesRequest>,
 ) -> Result<Json<ReloadRulesResponse>> {
     info!(profile_id = req.profile_id, "Reloading rules from database");
 
@@ -507,18 +995,21 @@ pub async fn reload_rules(
         .get_profile(req.profile_id)?
         .ok_or_else(|| ApiError::BadRequest(format!("Profile {} not found", req.profile_id)))?;
 
+    // Refuse to load malformed rules rather than silently running with defaults
+    if let Some(issue) = profile.rule_issues().into_iter().next() {
+        return Err(ApiError::BadRequest(issue.to_string()));
+    }
+
     // If profile is disabled, use empty rules (no blocking)
     let (time_rules, content_rules) = if profile.enabled {
-        // Parse time rules from JSON
-        let time_rules: aegis_core::time_rules::TimeRuleSet =
-            serde_json::from_value(profile.time_rules.clone()).unwrap_or_default();
+        let time_rules = profile.parse_time_rules().unwrap_or_default();
 
-        // Parse content rules from JSON - use family_safe_defaults if parsing fails or empty
-        let content_rules: aegis_core::content_rules::ContentRuleSet =
-            serde_json::from_value(profile.content_rules.clone())
-                .ok()
-                .filter(|r: &aegis_core::content_rules::ContentRuleSet| !r.rules.is_empty())
-                .unwrap_or_else(aegis_core::content_rules::ContentRuleSet::family_safe_defaults);
+        // Use family_safe_defaults if no content rules are configured
+        let content_rules = profile
+            .parse_content_rules()
+            .ok()
+            .filter(|r| !r.rules.is_empty())
+            .unwrap_or_else(aegis_core::content_rules::ContentRuleSet::family_safe_defaults);
 
         (time_rules, content_rules)
     } else {
@@ -559,35 +1050,8 @@ pub async fn reload_rules(
         filtering_state.update_rules(time_rules.clone(), content_rules.clone());
         filtering_state.set_profile_with_id(Some(profile.name.clone()), Some(profile.id));
 
-        // Also update sentiment analysis config
-        if profile.sentiment_config.enabled {
-            use aegis_core::classifier::{SentimentConfig, SentimentFlag};
-            use std::collections::HashSet;
-
-            let mut enabled_flags = HashSet::new();
-            if profile.sentiment_config.detect_distress {
-                enabled_flags.insert(SentimentFlag::Distress);
-            }
-            if profile.sentiment_config.detect_crisis {
-                enabled_flags.insert(SentimentFlag::CrisisIndicator);
-            }
-            if profile.sentiment_config.detect_bullying {
-                enabled_flags.insert(SentimentFlag::Bullying);
-            }
-            if profile.sentiment_config.detect_negative {
-                enabled_flags.insert(SentimentFlag::NegativeSentiment);
-            }
-
-            let sentiment_config = SentimentConfig {
-                enabled: true,
-                threshold: profile.sentiment_config.sensitivity,
-                enabled_flags,
-                notify_on_flag: true,
-            };
-            filtering_state.enable_sentiment_analysis(sentiment_config);
-        } else {
-            filtering_state.disable_sentiment_analysis();
-        }
+        // Also apply the profile's text, sentiment, and image toggles
+        filtering_state.apply_profile_filters(&profile);
 
         info!(
             profile_name = %profile.name,
@@ -627,8 +1091,15 @@ pub async fn get_protection_status(
         .get_protection_state()
         .map_err(|e| ApiError::Internal(format!("Failed to get protection state: {}", e)))?;
 
+    let blocking_all = state
+        .filtering_state
+        .as_ref()
+        .is_some_and(|fs| fs.is_blocking_all());
+
     let enabled = protection_state.is_active();
-    let status = if protection_state.is_disabled() {
+    let status = if blocking_all {
+        "blocked".to_string()
+    } else if protection_state.is_disabled() {
         "disabled".to_string()
     } else if protection_state.is_paused() {
         "paused".to_string()
@@ -645,7 +1116,7 @@ pub async fn get_protection_status(
 /// Note: Auth is handled by the dashboard UI (user must be logged in to see the pause button).
 pub async fn pause_protection(
     State(state): State<AppState>,
-    Json(req): Json<PauseProtectionRequest>,
+    ApiJson(req): ApiJson<PauseProtectionRequest>,
 ) -> Result<Json<ProtectionResponse>> {
     // Note: We skip session validation because the dashboard runs in a separate process
     // with its own AuthManager. The user has already authenticated in the dashboard.
@@ -699,7 +1170,7 @@ pub async fn pause_protection(
 /// Persists to database (F032) so all processes see the change.
 pub async fn resume_protection(
     State(state): State<AppState>,
-    Json(_req): Json<ResumeProtectionRequest>,
+    ApiJson(_req): ApiJson<ResumeProtectionRequest>,
 ) -> Result<Json<ProtectionResponse>> {
     // Note: Resume does not require auth (security design - resuming is always allowed)
 
@@ -722,3 +1193,384 @@ pub async fn resume_protection(
         message: "Protection resumed".to_string(),
     }))
 }
+
+/// POST /api/protection/block-all - Block all AI access immediately.
+///
+/// Applies to the in-process proxy only and overrides rules and pause state.
+pub async fn block_all(
+    State(state): State<AppState>,
+    ApiJson(_req): ApiJson<BlockAllRequest>,
+) -> Result<Json<ProtectionResponse>> {
+    // Note: Blocking does not require auth (security design - tightening is always allowed)
+    live_filtering_state(&state)?.block_all();
+
+    info!("All AI access blocked");
+
+    Ok(Json(ProtectionResponse {
+        success: true,
+        status: "blocked".to_string(),
+        message: "All AI access blocked".to_string(),
+    }))
+}
+
+/// POST /api/protection/block-all/clear - Lift the block-all override (requires auth).
+pub async fn clear_block_all(
+    State(state): State<AppState>,
+    ApiJson(req): ApiJson<ClearBlockAllRequest>,
+) -> Result<Json<ProtectionResponse>> {
+    let token = SessionToken::from_string(req.session_token);
+    if !state.auth.validate_session(&token) {
+        return Err(ApiError::SessionExpired);
+    }
+
+    live_filtering_state(&state)?.clear_block_all();
+
+    info!("Block-all override cleared");
+
+    Ok(Json(ProtectionResponse {
+        success: true,
+        status: "active".to_string(),
+        message: "AI access restored".to_string(),
+    }))
+}
+
+// ===== Health Handler =====
+
+/// How long the health check waits for the proxy to accept a connection.
+const PROXY_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// GET /api/health - Report whether each subsystem is working.
+///
+/// Responds 503 if the database isn't writable, or if the proxy runs in this
+/// process and isn't accepting connections. Keyword-only mode without the ML
+/// model is reported but isn't a failure.
+pub async fn get_health(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    let db_error = state.db.check_writable().err().map(|e| e.to_string());
+
+    let (ml_model_loaded, ml_model_error, proxy_listening, rules_loaded) =
+        match &state.filtering_state {
+            Some(filtering_state) => {
+                let stats = filtering_state.live_stats();
+                let proxy_listening = match stats.proxy_addr() {
+                    Some(addr) => probe_proxy(addr).await,
+                    None => false,
+                };
+                (
+                    stats.snapshot().model_loaded,
+                    stats.model_error(),
+                    proxy_listening,
+                    has_rules(&filtering_state.rule_engine().read()),
+                )
+            }
+            None => {
+                let classifier = state.classifier.read().unwrap();
+                let ml_model_error = match classifier.ml_load_error() {
+                    Some(PromptGuardError::Incompatible(reason)) => Some(reason.clone()),
+                    _ => None,
+                };
+                (
+                    classifier.has_ml(),
+                    ml_model_error,
+                    false,
+                    has_rules(&state.rules.read().unwrap()),
+                )
+            }
+        };
+
+    let healthy = db_error.is_none() && (proxy_listening || state.filtering_state.is_none());
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(HealthResponse {
+            status: if healthy { "ok" } else { "degraded" }.to_string(),
+            db_ok: db_error.is_none(),
+            db_error,
+            ml_model_loaded,
+            ml_model_error,
+            proxy_listening,
+            rules_loaded,
+            uptime_seconds: state.started.elapsed().as_secs(),
+        }),
+    )
+}
+
+/// Returns true if the proxy at `addr` accepts a connection.
+async fn probe_proxy(addr: std::net::SocketAddr) -> bool {
+    matches!(
+        tokio::time::timeout(PROXY_PROBE_TIMEOUT, tokio::net::TcpStream::connect(addr)).await,
+        Ok(Ok(_))
+    )
+}
+
+/// Returns true if `engine` has any time or content rules, or category defaults.
+fn has_rules(engine: &aegis_core::rule_engine::RuleEngine) -> bool {
+    !engine.time_rules.rules.is_empty()
+        || !engine.content_rules.rules.is_empty()
+        || !engine.content_rules.default_actions.is_empty()
+}
+
+// ===== Model Download Handlers =====
+
+/// POST /api/model/download - Start downloading the ML model files.
+///
+/// Returns 202 once a download starts. If one is already running, no second
+/// download starts and its progress is returned with 200.
+pub async fn download_model(
+    State(state): State<AppState>,
+    Query(query): Query<ModelDownloadQuery>,
+) -> Result<(StatusCode, Json<ModelStatusResponse>)> {
+    let status = match state.model_downloads.start(query.force) {
+        DownloadStart::Started => {
+            info!(force = query.force, "Model download started");
+            StatusCode::ACCEPTED
+        }
+        DownloadStart::AlreadyRunning => StatusCode::OK,
+        DownloadStart::Unavailable => {
+            return Err(ApiError::Internal(
+                "no data directory to download models into".to_string(),
+            ))
+        }
+    };
+
+    Ok((status, Json(model_status(&state))))
+}
+
+/// GET /api/model/status - Get whether models are installed and download progress.
+pub async fn get_model_status(State(state): State<AppState>) -> Json<ModelStatusResponse> {
+    Json(model_status(&state))
+}
+
+/// Builds the response from the current download status.
+fn model_status(state: &AppState) -> ModelStatusResponse {
+    let status = state.model_downloads.status();
+    let percent =
+        DownloadProgress::new("", status.downloaded_bytes, status.total_bytes).percentage();
+
+    ModelStatusResponse {
+        installed: state.model_downloads.is_installed(),
+        state: status.state,
+        step: status.step,
+        downloaded_bytes: status.downloaded_bytes,
+        total_bytes: status.total_bytes,
+        percent,
+        error: status.error,
+    }
+}
+
+// ===== Proxy Status Handlers =====
+
+/// GET /api/classifier/stats - Get how often each classifier tier has run.
+///
+/// Reports the running proxy's classifier when one is attached, otherwise
+/// the server's own (used by `/api/check`).
+pub async fn get_classifier_stats(State(state): State<AppState>) -> Json<ClassifierStatsResponse> {
+    let stats = match &state.filtering_state {
+        Some(filtering_state) => filtering_state.live_stats().classifier_stats(),
+        None => state.classifier.read().unwrap().stats(),
+    };
+    Json(stats.into())
+}
+
+/// GET /api/proxy/status - Get live counters from the running proxy.
+pub async fn get_proxy_status(State(state): State<AppState>) -> Result<Json<ProxyStatusResponse>> {
+    let filtering_state = live_filtering_state(&state)?;
+    let stats = filtering_state.live_stats().snapshot();
+
+    Ok(Json(ProxyStatusResponse {
+        requests_processed: stats.requests_processed,
+        requests_per_second: stats.requests_per_second,
+        blocks_today: stats.blocks_today,
+        model_loaded: stats.model_loaded,
+        model_error: filtering_state.live_stats().model_error(),
+        filtering_enabled: filtering_state.is_enabled(),
+        active_profile: filtering_state.profile_name(),
+        uptime_secs: stats.uptime.as_secs(),
+        canary: filtering_state
+            .live_stats()
+            .canary()
+            .map(|result| CanaryCheckResponse {
+                intercepted: result.status.is_intercepted(),
+                reason: match result.status {
+                    CanaryStatus::Intercepted => None,
+                    CanaryStatus::Bypassed { reason } => Some(reason),
+                },
+                checked_at: result.checked_at,
+            }),
+    }))
+}
+
+// ===== Image Filtering Handlers =====
+
+/// Returns the proxy's filtering state, or an error if none is attached.
+fn live_filtering_state(state: &AppState) -> Result<&aegis_proxy::FilteringState> {
+    state
+        .filtering_state
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("proxy filtering state not available".to_string()))
+}
+
+/// Builds the response from the live filtering state.
+fn image_filtering_response(
+    filtering_state: &aegis_proxy::FilteringState,
+) -> ImageFilteringResponse {
+    let preset = filtering_state.nsfw_threshold();
+    let name = match preset {
+        NsfwThresholdPreset::Child => "child",
+        NsfwThresholdPreset::Teen => "teen",
+        NsfwThresholdPreset::Adult => "adult",
+        NsfwThresholdPreset::Custom(_) => "custom",
+    };
+
+    ImageFilteringResponse {
+        enabled: filtering_state.is_image_filtering_enabled(),
+        preset: name.to_string(),
+        threshold: preset.threshold(),
+        profile_id: filtering_state.profile_id(),
+    }
+}
+
+/// Parses a preset name, using `threshold` for "custom".
+fn parse_nsfw_preset(name: &str, threshold: Option<f32>) -> Result<NsfwThresholdPreset> {
+    match name {
+        "child" => Ok(NsfwThresholdPreset::Child),
+        "teen" => Ok(NsfwThresholdPreset::Teen),
+        "adult" => Ok(NsfwThresholdPreset::Adult),
+        "custom" => match threshold {
+            Some(t) if (0.0..=1.0).contains(&t) => Ok(NsfwThresholdPreset::Custom(t)),
+            Some(t) => Err(ApiError::BadRequest(format!(
+                "Threshold must be between 0.0 and 1.0, got {}",
+                t
+            ))),
+            None => Err(ApiError::BadRequest(
+                "Custom preset requires a threshold".to_string(),
+            )),
+        },
+        _ => Err(ApiError::BadRequest(format!("Invalid preset: {}", name))),
+    }
+}
+
+/// Maps the classifier preset to its stored profile equivalent.
+fn to_stored_preset(preset: NsfwThresholdPreset) -> StoredNsfwThresholdPreset {
+    match preset {
+        NsfwThresholdPreset::Child => StoredNsfwThresholdPreset::Child,
+        NsfwThresholdPreset::Teen => StoredNsfwThresholdPreset::Teen,
+        NsfwThresholdPreset::Adult => StoredNsfwThresholdPreset::Adult,
+        NsfwThresholdPreset::Custom(t) => StoredNsfwThresholdPreset::Custom(t),
+    }
+}
+
+/// GET /api/image-filtering - Get the live image filtering settings.
+pub async fn get_image_filtering(
+    State(state): State<AppState>,
+) -> Result<Json<ImageFilteringResponse>> {
+    let filtering_state = live_filtering_state(&state)?;
+    Ok(Json(image_filtering_response(filtering_state)))
+}
+
+/// PUT /api/image-filtering - Update image filtering settings (requires auth).
+///
+/// Applies to the running proxy immediately and persists to the active profile.
+pub async fn update_image_filtering(
+    State(state): State<AppState>,
+    ApiJson(req): ApiJson<UpdateImageFilteringRequest>,
+) -> Result<Json<ImageFilteringResponse>> {
+    let token = SessionToken::from_string(req.session_token);
+    if !state.auth.validate_session(&token) {
+        return Err(ApiError::SessionExpired);
+    }
+
+    let filtering_state = live_filtering_state(&state)?;
+
+    // Validate before touching anything
+    let preset = match req.preset.as_deref() {
+        Some(name) => Some(parse_nsfw_preset(name, req.threshold)?),
+        None => None,
+    };
+
+    if let Some(enabled) = req.enabled {
+        if enabled {
+            filtering_state.enable_image_filtering();
+        } else {
+            filtering_state.disable_image_filtering();
+        }
+    }
+    if let Some(preset) = preset {
+        filtering_state.set_nsfw_threshold(preset);
+    }
+
+    // Persist to the active profile so the setting survives restarts
+    if let Some(profile_id) = filtering_state.profile_id() {
+        if let Some(profile) = state.db.get_profile(profile_id)? {
+            let image_filtering_config = ProfileImageFilteringConfig {
+                enabled: filtering_state.is_image_filtering_enabled(),
+                nsfw_threshold: to_stored_preset(filtering_state.nsfw_threshold()),
+            };
+            state.db.update_profile(
+                profile_id,
+                NewProfile {
+                    name: profile.name,
+                    os_username: profile.os_username,
+                    time_rules: profile.time_rules,
+                    content_rules: profile.content_rules,
+                    enabled: profile.enabled,
+                    sentiment_config: profile.sentiment_config,
+                    image_filtering_config,
+                    proxy_mode: profile.proxy_mode,
+                    text_filtering_enabled: profile.text_filtering_enabled,
+                },
+            )?;
+        }
+    }
+
+    let response = image_filtering_response(filtering_state);
+    info!(
+        enabled = response.enabled,
+        preset = %response.preset,
+        threshold = response.threshold,
+        "Image filtering updated"
+    );
+
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aegis_core::classifier::Category;
+
+    #[test]
+    fn matched_term_is_shortened_and_never_the_whole_prompt() {
+        let keyword = |term: &str| {
+            CategoryMatch::with_tier(
+                Category::Violence,
+                0.9,
+                Some(term.to_string()),
+                ClassificationTier::Keyword,
+            )
+        };
+
+        assert_eq!(
+            matched_term(&keyword("kill"), "how to kill time").as_deref(),
+            Some("kill")
+        );
+        assert_eq!(matched_term(&keyword(" kill "), "kill"), None);
+
+        let long = "x".repeat(100);
+        let term = matched_term(&keyword(&long), &format!("{} tail", long)).unwrap();
+        assert_eq!(term.chars().count(), MAX_MATCHED_TERM_CHARS);
+        assert!(term.ends_with('…'));
+
+        let ml = CategoryMatch::with_tier(
+            Category::Jailbreak,
+            0.9,
+            Some("injection".to_string()),
+            ClassificationTier::Ml,
+        );
+        assert_eq!(matched_term(&ml, "some injection attempt"), None);
+    }
+}
@@ -0,0 +1,181 @@
+//! On-demand ML model downloads for the dashboard.
+//!
+//! [`ModelDownloads`] runs at most one download at a time in the background
+//! and keeps its latest progress, so `POST /api/model/download` can return
+//! immediately and `GET /api/model/status` can be polled for progress.
+
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+use serde::Serialize;
+
+use aegis_core::model_downloader::{
+    DownloadError, DownloadProgress, ModelDownloader, ProgressCallback,
+};
+
+/// Something that can fetch the ML model files.
+///
+/// Implemented by [`ModelDownloader`]; tests substitute their own.
+pub trait ModelFetcher: Send + Sync {
+    /// Returns true if every model file is installed.
+    fn is_installed(&self) -> bool;
+
+    /// Downloads missing files, or replaces the model if `force` is set,
+    /// reporting progress through `progress`.
+    fn fetch(
+        &self,
+        force: bool,
+        progress: ProgressCallback,
+    ) -> BoxFuture<'_, Result<(), DownloadError>>;
+}
+
+impl ModelFetcher for ModelDownloader {
+    fn is_installed(&self) -> bool {
+        self.is_ml_ready()
+    }
+
+    fn fetch(
+        &self,
+        force: bool,
+        progress: ProgressCallback,
+    ) -> BoxFuture<'_, Result<(), DownloadError>> {
+        Box::pin(async move {
+            // The runtime library may be loaded, so only the model is replaced
+            self.ensure_onnx_runtime(Some(progress.clone())).await?;
+            if force {
+                self.redownload_nsfw_model(Some(progress)).await?;
+            } else {
+                self.ensure_nsfw_model(Some(progress)).await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Where a model download is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadState {
+    /// No download has run.
+    #[default]
+    Idle,
+    /// A download is running.
+    Downloading,
+    /// The last download finished.
+    Complete,
+    /// The last download failed.
+    Failed,
+}
+
+/// Latest progress of the current or last download.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelDownloadStatus {
+    /// Where the download is.
+    pub state: DownloadState,
+    /// What the download is doing, e.g. "Downloading NSFW model...".
+    pub step: Option<String>,
+    /// Bytes of the current file received so far.
+    pub downloaded_bytes: u64,
+    /// Size of the current file, if known.
+    pub total_bytes: Option<u64>,
+    /// Why the last download failed.
+    pub error: Option<String>,
+}
+
+/// Outcome of [`ModelDownloads::start`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadStart {
+    /// A new download was started.
+    Started,
+    /// A download was already running; no second one was started.
+    AlreadyRunning,
+    /// There is nowhere to download to (no data directory).
+    Unavailable,
+}
+
+/// Single-flight model downloads with shared progress.
+///
+/// Clones share the same status, so every handler sees the same download.
+#[derive(Clone)]
+pub struct ModelDownloads {
+    fetcher: Option<Arc<dyn ModelFetcher>>,
+    status: Arc<Mutex<ModelDownloadStatus>>,
+}
+
+impl ModelDownloads {
+    /// Downloads with `fetcher`.
+    pub fn new(fetcher: Arc<dyn ModelFetcher>) -> Self {
+        Self {
+            fetcher: Some(fetcher),
+            status: Arc::default(),
+        }
+    }
+
+    /// Downloads into the default data directory, if there is one.
+    pub fn with_default_downloader() -> Self {
+        match ModelDownloader::new() {
+            Some(downloader) => Self::new(Arc::new(downloader)),
+            None => Self {
+                fetcher: None,
+                status: Arc::default(),
+            },
+        }
+    }
+
+    /// Returns true if every model file is installed.
+    pub fn is_installed(&self) -> bool {
+        self.fetcher.as_ref().is_some_and(|f| f.is_installed())
+    }
+
+    /// Returns the progress of the current or last download.
+    pub fn status(&self) -> ModelDownloadStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Starts a background download unless one is already running.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn start(&self, force: bool) -> DownloadStart {
+        let Some(fetcher) = self.fetcher.clone() else {
+            return DownloadStart::Unavailable;
+        };
+
+        {
+            let mut status = self.status.lock().unwrap();
+            if status.state == DownloadState::Downloading {
+                return DownloadStart::AlreadyRunning;
+            }
+            *status = ModelDownloadStatus {
+                state: DownloadState::Downloading,
+                ..Default::default()
+            };
+        }
+
+        let progress_status = self.status.clone();
+        let progress: ProgressCallback = Arc::new(move |progress: DownloadProgress| {
+            let mut status = progress_status.lock().unwrap();
+            status.step = Some(progress.step);
+            status.downloaded_bytes = progress.downloaded;
+            status.total_bytes = progress.total;
+        });
+
+        let status = self.status.clone();
+        tokio::spawn(async move {
+            let result = fetcher.fetch(force, progress).await;
+            let mut status = status.lock().unwrap();
+            match result {
+                Ok(()) => {
+                    tracing::info!("Model download complete");
+                    status.state = DownloadState::Complete;
+                }
+                Err(e) => {
+                    tracing::warn!("Model download failed: {}", e);
+                    status.state = DownloadState::Failed;
+                    status.error = Some(e.to_string());
+                }
+            }
+        });
+
+        DownloadStart::Started
+    }
+}
@@ -1,17 +1,140 @@
 //! API request and response models.
 
-use aegis_core::classifier::{Category, ClassificationTier};
+use aegis_core::classifier::{Category, ClassificationTier, TierStats};
+use aegis_core::keyword_import::KeywordFormat;
 use aegis_core::rule_engine::RuleAction;
+use aegis_core::rule_validation::{RuleDraft, RuleIssue};
+use aegis_core::site_registry::SiteCategory;
+use aegis_storage::Site;
+use axum::body::Bytes;
+use axum::extract::{FromRequest, Request};
+use axum::http::{header, StatusCode};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::error::ApiError;
+use crate::model_download::DownloadState;
+
 /// Request body for POST /api/check.
+///
+/// Accepted as JSON (`{"prompt": "...", "os_username": "...", "context": [...]}`)
+/// or, with a `text/*` content type, as the raw prompt text.
 #[derive(Debug, Deserialize)]
 pub struct CheckRequest {
     /// The prompt text to classify.
     pub prompt: String,
     /// Optional OS username for profile lookup.
     pub os_username: Option<String>,
+    /// Earlier conversation messages, oldest first. Each is a string or an
+    /// object with a `content` string (e.g., `{"role": "user", "content": "..."}`).
+    #[serde(default)]
+    pub context: Vec<String>,
+}
+
+impl CheckRequest {
+    /// Parses a request body, returning a 400 that names the problem.
+    ///
+    /// Without a content type, a body starting with `{` is read as JSON and
+    /// anything else as raw text.
+    pub fn parse(content_type: Option<&str>, body: &[u8]) -> Result<Self, ApiError> {
+        let invalid = |message: &str| ApiError::InvalidBody {
+            status: StatusCode::BAD_REQUEST,
+            message: message.to_string(),
+        };
+
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Err(invalid(
+                "request body is empty; send {\"prompt\": \"...\"} or the prompt as text/plain",
+            ));
+        }
+
+        let mime = content_type
+            .and_then(|value| value.split(';').next())
+            .map(|mime| mime.trim().to_ascii_lowercase());
+        let is_json = match mime.as_deref() {
+            Some(mime) if mime == "application/json" || mime.ends_with("+json") => true,
+            Some(mime) if mime.starts_with("text/") => false,
+            Some(mime) => {
+                return Err(ApiError::InvalidBody {
+                    status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    message: format!(
+                        "unsupported content type '{}'; use application/json or text/plain",
+                        mime
+                    ),
+                })
+            }
+            None => body.trim_ascii_start().starts_with(b"{"),
+        };
+
+        if !is_json {
+            let prompt = std::str::from_utf8(body)
+                .map_err(|_| invalid("request body must be UTF-8 text"))?;
+            return Ok(Self {
+                prompt: prompt.to_string(),
+                os_username: None,
+                context: Vec::new(),
+            });
+        }
+
+        let value: serde_json::Value = serde_json::from_slice(body)
+            .map_err(|e| invalid(&format!("request body is not valid JSON: {}", e)))?;
+        let object = value
+            .as_object()
+            .ok_or_else(|| invalid("request body must be a JSON object"))?;
+        let prompt = object
+            .get("prompt")
+            .and_then(|prompt| prompt.as_str())
+            .ok_or_else(|| invalid("prompt is required and must be a string"))?;
+        let os_username = match object.get("os_username") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(serde_json::Value::String(name)) => Some(name.clone()),
+            Some(_) => return Err(invalid("os_username must be a string")),
+        };
+        let context = match object.get("context") {
+            None | Some(serde_json::Value::Null) => Vec::new(),
+            Some(serde_json::Value::Array(messages)) => messages
+                .iter()
+                .map(|message| match message {
+                    serde_json::Value::String(text) => Some(text.clone()),
+                    serde_json::Value::Object(message) => message
+                        .get("content")
+                        .and_then(|content| content.as_str())
+                        .map(str::to_string),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| {
+                    invalid("context messages must be strings or objects with a content string")
+                })?,
+            Some(_) => return Err(invalid("context must be an array of messages")),
+        };
+
+        Ok(Self {
+            prompt: prompt.to_string(),
+            os_username,
+            context,
+        })
+    }
+}
+
+impl<S: Send + Sync> FromRequest<S> for CheckRequest {
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body =
+            Bytes::from_request(req, state)
+                .await
+                .map_err(|rejection| ApiError::InvalidBody {
+                    status: rejection.status(),
+                    message: rejection.body_text(),
+                })?;
+        Self::parse(content_type.as_deref(), &body)
+    }
 }
 
 /// Category match in the response.
@@ -22,6 +145,22 @@ pub struct CategoryMatchResponse {
     pub tier: ClassificationTier,
 }
 
+/// Longest matched term echoed in an explanation, in characters.
+pub const MAX_MATCHED_TERM_CHARS: usize = 40;
+
+/// Why a category matched, for parents reviewing a decision.
+#[derive(Debug, Serialize)]
+pub struct ExplanationResponse {
+    pub category: Category,
+    pub tier: ClassificationTier,
+    pub confidence: f32,
+    /// Keyword or rule text that matched, at most [`MAX_MATCHED_TERM_CHARS`]
+    /// characters. Omitted for ML matches and when the match would echo the
+    /// whole prompt.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_term: Option<String>,
+}
+
 /// Response body for POST /api/check.
 #[derive(Debug, Serialize)]
 pub struct CheckResponse {
@@ -31,10 +170,33 @@ pub struct CheckResponse {
     pub reason: String,
     /// Matched categories.
     pub categories: Vec<CategoryMatchResponse>,
+    /// What produced each match, in the same order as `categories`.
+    pub explanations: Vec<ExplanationResponse>,
     /// Classification latency in milliseconds.
     pub latency_ms: u64,
 }
 
+/// Most prompts accepted by one POST /api/check/batch request.
+pub const MAX_BATCH_PROMPTS: usize = 50;
+
+/// Request body for POST /api/check/batch.
+#[derive(Debug, Deserialize)]
+pub struct CheckBatchRequest {
+    /// Prompts to classify, at most [`MAX_BATCH_PROMPTS`].
+    pub prompts: Vec<String>,
+    /// Optional OS username for profile lookup, applied to every prompt.
+    pub os_username: Option<String>,
+}
+
+/// Response body for POST /api/check/batch.
+#[derive(Debug, Serialize)]
+pub struct CheckBatchResponse {
+    /// One result per prompt, in request order.
+    pub results: Vec<CheckResponse>,
+    /// Wall-clock time for the whole batch in milliseconds.
+    pub total_latency_ms: u64,
+}
+
 /// Request body for POST /api/auth/verify.
 #[derive(Debug, Deserialize)]
 pub struct AuthVerifyRequest {
@@ -58,11 +220,16 @@ pub struct LogsQuery {
     /// Maximum number of logs to return (default: 50).
     #[serde(default = "default_limit")]
     pub limit: i64,
-    /// Offset for pagination (default: 0).
+    /// Offset for pagination (default: 0). Ignored when `before` is set.
     #[serde(default)]
     pub offset: i64,
     /// Filter by action (optional).
     pub action: Option<String>,
+    /// Cursor: only return events with an ID below this one (optional).
+    ///
+    /// Pass the previous page's `next_cursor` to page without duplicates
+    /// or skips when new events arrive in between.
+    pub before: Option<i64>,
 }
 
 fn default_limit() -> i64 {
@@ -78,6 +245,8 @@ pub struct LogEntry {
     pub confidence: Option<f32>,
     pub action: String,
     pub source: Option<String>,
+    /// Model the request asked for, if the payload named one.
+    pub model: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -86,6 +255,58 @@ pub struct LogEntry {
 pub struct LogsResponse {
     pub logs: Vec<LogEntry>,
     pub total: i64,
+    /// Cursor for the next page (`?before=`), or null on the last page.
+    pub next_cursor: Option<i64>,
+}
+
+/// File format of an export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Comma-separated values with a header row.
+    #[default]
+    Csv,
+    /// Newline-delimited JSON, one object per line.
+    #[serde(alias = "ndjson")]
+    Json,
+}
+
+impl ExportFormat {
+    /// Content type of the response body.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/x-ndjson",
+        }
+    }
+
+    /// File extension of the attachment.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "ndjson",
+        }
+    }
+}
+
+/// Query parameters for GET /api/logs/export and GET /api/flagged/export.
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// Output format (default: csv).
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Only export rows created at or after this time (RFC 3339, optional).
+    pub from: Option<DateTime<Utc>>,
+    /// Only export rows created at or before this time (RFC 3339, optional).
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Data of an `event: stats` frame on GET /api/events/stream: one prompt
+/// counted under `action` (and `category`, if any).
+#[derive(Debug, Serialize)]
+pub struct StatsDelta {
+    pub action: String,
+    pub category: Option<Category>,
 }
 
 /// Response body for GET /api/stats.
@@ -95,7 +316,43 @@ pub struct StatsResponse {
     pub blocked_count: i64,
     pub warned_count: i64,
     pub allowed_count: i64,
+    pub flagged_count: i64,
+    pub would_block_count: i64,
+    pub redacted_count: i64,
     pub category_counts: CategoryCountsResponse,
+    /// Most blocked categories over the last [`TOP_STATS_DAYS`] days.
+    pub top_categories: Vec<TopCategoryEntry>,
+    /// Most used services over the last [`TOP_STATS_DAYS`] days.
+    pub top_services: Vec<TopServiceEntry>,
+    /// Logged prompts per requested model, most used first.
+    pub by_model: Vec<ModelCountEntry>,
+}
+
+/// Number of days covered by the top lists in the stats response.
+pub const TOP_STATS_DAYS: i64 = 7;
+
+/// Number of entries in each top list in the stats response.
+pub const TOP_STATS_LIMIT: i64 = 3;
+
+/// Blocked category with its count.
+#[derive(Debug, Serialize)]
+pub struct TopCategoryEntry {
+    pub category: Category,
+    pub count: i64,
+}
+
+/// Service (event source) with its count.
+#[derive(Debug, Serialize)]
+pub struct TopServiceEntry {
+    pub service: String,
+    pub count: i64,
+}
+
+/// Requested model with its count.
+#[derive(Debug, Serialize)]
+pub struct ModelCountEntry {
+    pub model: String,
+    pub count: i64,
 }
 
 /// Category counts in stats response.
@@ -125,6 +382,8 @@ pub struct RuleEntry {
 #[derive(Debug, Serialize)]
 pub struct RulesResponse {
     pub rules: Vec<RuleEntry>,
+    /// Version of the rule set; send it back as `expected_version` on update.
+    pub version: i64,
 }
 
 /// Request body for PUT /api/rules.
@@ -134,6 +393,9 @@ pub struct UpdateRulesRequest {
     pub session_token: String,
     /// Rules to update.
     pub rules: Vec<RuleUpdate>,
+    /// The `version` from GET /api/rules this update is based on (required).
+    #[serde(default)]
+    pub expected_version: Option<i64>,
 }
 
 /// A single rule update.
@@ -146,11 +408,104 @@ pub struct RuleUpdate {
     pub priority: i32,
 }
 
+/// Response body for POST /api/rules/validate.
+#[derive(Debug, Serialize)]
+pub struct ValidateRulesResponse {
+    /// True if the draft has no errors (warnings are allowed).
+    pub valid: bool,
+    /// Errors and warnings, in draft order.
+    pub issues: Vec<RuleIssue>,
+}
+
+/// Request body for POST /api/rules/test.
+#[derive(Debug, Deserialize)]
+pub struct TestRulesRequest {
+    /// Rules to try, in the same shape as POST /api/rules/validate.
+    pub rules: RuleDraft,
+    /// Sample prompt to evaluate them against.
+    pub prompt: String,
+}
+
+/// Response body for POST /api/rules/test.
+#[derive(Debug, Serialize)]
+pub struct TestRulesResponse {
+    /// Action the rules would take on the prompt right now.
+    pub action: RuleAction,
+    /// ID of the rule that decided the action, if any.
+    pub rule_id: Option<String>,
+    /// Name of the rule that decided the action, if any.
+    pub rule_name: Option<String>,
+    /// Categories the prompt was classified into.
+    pub categories: Vec<CategoryMatchResponse>,
+    /// Warnings about the rules (they were still applied).
+    pub warnings: Vec<RuleIssue>,
+}
+
+/// Request body for POST /api/keywords/import.
+#[derive(Debug, Deserialize)]
+pub struct ImportKeywordsRequest {
+    /// Session token for authentication.
+    pub session_token: String,
+    /// The CSV or JSON keyword list.
+    pub content: String,
+    /// Format of `content`; detected from the content if omitted.
+    #[serde(default)]
+    pub format: Option<KeywordFormat>,
+}
+
+/// Response body for POST /api/keywords/import.
+#[derive(Debug, Serialize)]
+pub struct ImportKeywordsResponse {
+    pub success: bool,
+    /// Terms added to the blacklist.
+    pub blocked: usize,
+    /// Terms added to the whitelist.
+    pub allowed: usize,
+    /// Terms that were already configured the same way.
+    pub unchanged: usize,
+    /// Rows skipped as duplicates within the list.
+    pub duplicates: usize,
+}
+
+/// Request body for POST /api/sites.
+#[derive(Debug, Deserialize)]
+pub struct CreateSiteRequest {
+    /// Session token for authentication.
+    pub session_token: String,
+    /// Domain pattern, exact (`chat.example.com`) or wildcard (`*.example.com`).
+    pub pattern: String,
+    /// Display name.
+    pub name: String,
+    /// Site category; defaults to consumer.
+    #[serde(default)]
+    pub category: SiteCategory,
+    /// Parser that extracts prompts (e.g., `json`, `form`, `grpc-web`).
+    #[serde(default)]
+    pub parser_id: Option<String>,
+    /// Service name used in events and block pages; defaults to `name`.
+    #[serde(default)]
+    pub service_name: Option<String>,
+    /// Prompt endpoint path patterns; empty inspects every path.
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    /// Priority among wildcard patterns.
+    #[serde(default)]
+    pub priority: i32,
+}
+
+/// Response body for GET /api/sites.
+#[derive(Debug, Serialize)]
+pub struct SitesResponse {
+    pub sites: Vec<Site>,
+}
+
 /// Response body for PUT /api/rules.
 #[derive(Debug, Serialize)]
 pub struct UpdateRulesResponse {
     pub success: bool,
     pub updated_count: usize,
+    /// Version of the rule set after the update.
+    pub version: i64,
 }
 
 // ===== Flagged Events API =====
@@ -185,6 +540,7 @@ pub struct FlaggedEntry {
     pub acknowledged: bool,
     pub acknowledged_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
+    pub occurrences: i64,
 }
 
 /// Response body for GET /api/flagged.
@@ -286,6 +642,139 @@ pub struct ResumeProtectionRequest {
     pub session_token: Option<String>,
 }
 
+/// Response body for GET /api/proxy/status.
+#[derive(Debug, Serialize)]
+pub struct ProxyStatusResponse {
+    /// Requests processed since the proxy started.
+    pub requests_processed: u64,
+    /// Recent requests per second.
+    pub requests_per_second: f64,
+    /// Requests blocked since local midnight.
+    pub blocks_today: u64,
+    /// Whether the ML classifier is loaded.
+    pub model_loaded: bool,
+    /// Why the ML model was rejected as incompatible, if it was.
+    pub model_error: Option<String>,
+    /// Whether filtering is enabled.
+    pub filtering_enabled: bool,
+    /// Active profile name, if any.
+    pub active_profile: Option<String>,
+    /// Seconds since the proxy started.
+    pub uptime_secs: u64,
+    /// Latest interception canary check, if one has run.
+    pub canary: Option<CanaryCheckResponse>,
+}
+
+/// Response body for GET /api/classifier/stats.
+#[derive(Debug, Serialize)]
+pub struct ClassifierStatsResponse {
+    /// Prompts classified, including cache hits.
+    pub classifications: u64,
+    /// Prompts answered from the classification cache.
+    pub cache_hits: u64,
+    /// Prompts decided by the keyword tier without the ML tier.
+    pub keyword_only: u64,
+    /// Prompts the ML tier ran on.
+    pub ml_invoked: u64,
+    /// Prompts where a confident keyword match skipped the ML tier.
+    pub short_circuited: u64,
+    /// ML tier runs that failed.
+    pub ml_failures: u64,
+    /// Average keyword tier time in microseconds, if it has run.
+    pub avg_keyword_us: Option<f64>,
+    /// Average ML tier time in microseconds, if it has run.
+    pub avg_ml_us: Option<f64>,
+}
+
+impl From<TierStats> for ClassifierStatsResponse {
+    fn from(stats: TierStats) -> Self {
+        Self {
+            classifications: stats.classifications,
+            cache_hits: stats.cache_hits,
+            keyword_only: stats.keyword_only,
+            ml_invoked: stats.ml_invoked,
+            short_circuited: stats.short_circuited,
+            ml_failures: stats.ml_failures,
+            avg_keyword_us: stats.avg_keyword_duration_us(),
+            avg_ml_us: stats.avg_ml_duration_us(),
+        }
+    }
+}
+
+/// Query parameters for POST /api/model/download.
+#[derive(Debug, Default, Deserialize)]
+pub struct ModelDownloadQuery {
+    /// Replace the model even if it is already installed (e.g., corrupt).
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Response body for GET /api/model/status and POST /api/model/download.
+#[derive(Debug, Serialize)]
+pub struct ModelStatusResponse {
+    /// Whether every model file is installed.
+    pub installed: bool,
+    /// Where the current or last download is.
+    pub state: DownloadState,
+    /// What the download is doing.
+    pub step: Option<String>,
+    /// Bytes of the current file received so far.
+    pub downloaded_bytes: u64,
+    /// Size of the current file, if known.
+    pub total_bytes: Option<u64>,
+    /// Progress through the current file (0-100), if its size is known.
+    pub percent: Option<u8>,
+    /// Why the last download failed.
+    pub error: Option<String>,
+}
+
+/// Response body for GET /api/health.
+#[derive(Debug, Serialize)]
+pub struct HealthResponse {
+    /// "ok", or "degraded" if a subsystem that must work is failing.
+    pub status: String,
+    /// Whether the database accepts writes.
+    pub db_ok: bool,
+    /// Why the database check failed, if it did.
+    pub db_error: Option<String>,
+    /// Whether the ML classifier loaded (false means keyword-only mode).
+    pub ml_model_loaded: bool,
+    /// Why the ML model was rejected as incompatible, if it was.
+    pub ml_model_error: Option<String>,
+    /// Whether the filtering proxy is accepting connections.
+    pub proxy_listening: bool,
+    /// Whether the rule engine has any rules.
+    pub rules_loaded: bool,
+    /// Seconds since the API server started.
+    pub uptime_seconds: u64,
+}
+
+/// Result of the latest interception canary check.
+#[derive(Debug, Serialize)]
+pub struct CanaryCheckResponse {
+    /// Whether the canary passed through the proxy.
+    pub intercepted: bool,
+    /// Why the canary was not intercepted.
+    pub reason: Option<String>,
+    /// When the check ran.
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Request body for POST /api/protection/block-all.
+#[derive(Debug, Deserialize)]
+pub struct BlockAllRequest {
+    /// Session token for authentication (optional - blocking is always allowed).
+    #[serde(default)]
+    pub session_token: Option<String>,
+}
+
+/// Request body for POST /api/protection/block-all/clear.
+#[derive(Debug, Deserialize)]
+pub struct ClearBlockAllRequest {
+    /// Session token for authentication.
+    pub session_token: String,
+}
+
 /// Response body for protection control operations.
 #[derive(Debug, Serialize)]
 pub struct ProtectionResponse {
@@ -304,3 +793,34 @@ pub struct ProtectionStatusResponse {
     /// Current status: "active", "paused", or "disabled".
     pub status: String,
 }
+
+// ===== Image Filtering API =====
+
+/// Response body for GET/PUT /api/image-filtering.
+#[derive(Debug, Serialize)]
+pub struct ImageFilteringResponse {
+    /// Whether image filtering is enabled.
+    pub enabled: bool,
+    /// NSFW threshold preset: "child", "teen", "adult", or "custom".
+    pub preset: String,
+    /// Effective NSFW threshold (0.0 to 1.0).
+    pub threshold: f32,
+    /// Active profile the settings belong to, if any.
+    pub profile_id: Option<i64>,
+}
+
+/// Request body for PUT /api/image-filtering.
+#[derive(Debug, Deserialize)]
+pub struct UpdateImageFilteringRequest {
+    /// Session token for authentication.
+    pub session_token: String,
+    /// Enable or disable image filtering (unchanged if omitted).
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Preset: "child", "teen", "adult", or "custom" (unchanged if omitted).
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Threshold for the "custom" preset (0.0 to 1.0).
+    #[serde(default)]
+    pub threshold: Option<f32>,
+}
@@ -1,14 +1,21 @@
 //! Application state for the API server.
 
-use std::sync::{Arc, RwLock};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 use aegis_core::auth::AuthManager;
 use aegis_core::classifier::{SentimentAnalyzer, SentimentConfig, TieredClassifier};
+use aegis_core::community_rules::CommunityRuleManager;
+use aegis_core::extension_token::ExtensionToken;
 use aegis_core::profile::ProfileManager;
 use aegis_core::rule_engine::RuleEngine;
+use aegis_core::site_registry::SiteRegistry;
 use aegis_proxy::FilteringState;
 use aegis_storage::{Database, StateManager};
 
+use crate::model_download::{ModelDownloads, ModelFetcher};
+
 /// Shared application state.
 #[derive(Clone)]
 pub struct AppState {
@@ -27,9 +34,24 @@ pub struct AppState {
     /// Optional filtering state shared with the proxy.
     /// When set, rule reloads will also update the proxy's rules.
     pub filtering_state: Option<FilteringState>,
+    /// Optional community rule manager shared with the proxy's classifier.
+    /// When set, keyword imports take effect without a restart.
+    pub community_rules: Option<Arc<RwLock<CommunityRuleManager>>>,
+    /// Optional site registry shared with the proxy.
+    /// When set, sites added through the API are inspected without a restart.
+    pub site_registry: Option<Arc<SiteRegistry>>,
+    /// Token the browser extension must send on extension endpoints.
+    /// When unset, those endpoints are open to any local client.
+    pub extension_token: Option<ExtensionToken>,
     /// Centralized state manager for cross-process state (F032).
     /// Protection state is now persisted to database for dashboard/proxy sync.
     pub state_manager: StateManager,
+    /// Failed password attempts on `/api/auth/verify`.
+    pub login_limiter: Arc<Mutex<LoginLimiter>>,
+    /// When the state was created, for the uptime reported by `/api/health`.
+    pub started: Instant,
+    /// On-demand ML model downloads started from the dashboard.
+    pub model_downloads: ModelDownloads,
 }
 
 impl AppState {
@@ -47,7 +69,13 @@ impl AppState {
                 SentimentConfig::default(),
             ))),
             filtering_state: None,
+            community_rules: None,
+            site_registry: None,
+            extension_token: None,
             state_manager,
+            login_limiter: Arc::default(),
+            started: Instant::now(),
+            model_downloads: ModelDownloads::with_default_downloader(),
         }
     }
 
@@ -74,7 +102,13 @@ impl AppState {
                 SentimentConfig::default(),
             ))),
             filtering_state: None,
+            community_rules: None,
+            site_registry: None,
+            extension_token: None,
             state_manager,
+            login_limiter: Arc::default(),
+            started: Instant::now(),
+            model_downloads: ModelDownloads::with_default_downloader(),
         }
     }
 
@@ -94,7 +128,13 @@ impl AppState {
                 SentimentConfig::default(),
             ))),
             filtering_state: Some(filtering_state),
+            community_rules: None,
+            site_registry: None,
+            extension_token: None,
             state_manager,
+            login_limiter: Arc::default(),
+            started: Instant::now(),
+            model_downloads: ModelDownloads::with_default_downloader(),
         }
     }
 
@@ -118,7 +158,164 @@ impl AppState {
                 SentimentConfig::default(),
             ))),
             filtering_state: None,
+            community_rules: None,
+            site_registry: None,
+            extension_token: None,
             state_manager,
+            login_limiter: Arc::default(),
+            started: Instant::now(),
+            model_downloads: ModelDownloads::with_default_downloader(),
         }
     }
+
+    /// Shares the proxy classifier's community rule manager, so keyword
+    /// imports are applied to live filtering.
+    pub fn with_community_rules(mut self, rules: Arc<RwLock<CommunityRuleManager>>) -> Self {
+        self.community_rules = Some(rules);
+        self
+    }
+
+    /// Loads the custom keyword list stored in the database into the
+    /// classifier, so `/api/check` matches the same terms as the proxy.
+    ///
+    /// An invalid list is logged and ignored, leaving the built-in keywords.
+    pub fn with_stored_custom_keywords(self) -> Self {
+        match self.db.custom_keywords() {
+            Ok(Some(keywords)) => {
+                match self
+                    .classifier
+                    .write()
+                    .unwrap()
+                    .load_custom_keywords(&keywords)
+                {
+                    Ok(count) => tracing::info!("Loaded {} custom keyword(s) for the API", count),
+                    Err(e) => tracing::error!("Ignoring invalid custom keyword list: {}", e),
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to load custom keywords: {}", e),
+        }
+        self
+    }
+
+    /// Shares the proxy's site registry, so added sites are applied to live
+    /// filtering.
+    pub fn with_site_registry(mut self, registry: Arc<SiteRegistry>) -> Self {
+        self.site_registry = Some(registry);
+        self
+    }
+
+    /// Downloads ML models with `fetcher` instead of the default downloader.
+    pub fn with_model_fetcher(mut self, fetcher: Arc<dyn ModelFetcher>) -> Self {
+        self.model_downloads = ModelDownloads::new(fetcher);
+        self
+    }
+
+    /// Requires `token` on extension endpoints such as `/api/check`.
+    pub fn with_extension_token(mut self, token: ExtensionToken) -> Self {
+        self.extension_token = Some(token);
+        self
+    }
+}
+
+/// Failed attempts within [`LOGIN_FAILURE_WINDOW`] that trigger a lockout.
+pub const MAX_LOGIN_FAILURES: usize = 5;
+
+/// Window over which failed attempts are counted.
+pub const LOGIN_FAILURE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Length of the first lockout; each further lockout doubles it.
+pub const BASE_LOGIN_LOCKOUT: Duration = Duration::from_secs(30);
+
+/// Longest lockout.
+pub const MAX_LOGIN_LOCKOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Sliding-window limiter for password attempts.
+///
+/// There is a single parent password, so attempts are counted globally
+/// rather than per client. After [`MAX_LOGIN_FAILURES`] failures within
+/// [`LOGIN_FAILURE_WINDOW`], verification is refused for a lockout that
+/// doubles each time it is hit again. A successful verify resets it.
+#[derive(Debug, Default)]
+pub struct LoginLimiter {
+    failures: VecDeque<Instant>,
+    lockouts: u32,
+    locked_until: Option<Instant>,
+}
+
+impl LoginLimiter {
+    /// Returns the remaining lockout if attempts are currently refused.
+    pub fn locked_for(&self, now: Instant) -> Option<Duration> {
+        self.locked_until
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    /// Records a failed attempt, starting a lockout once the limit is hit.
+    pub fn record_failure(&mut self, now: Instant) {
+        while self
+            .failures
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= LOGIN_FAILURE_WINDOW)
+        {
+            self.failures.pop_front();
+        }
+        self.failures.push_back(now);
+
+        if self.failures.len() >= MAX_LOGIN_FAILURES {
+            let lockout = BASE_LOGIN_LOCKOUT
+                .saturating_mul(2u32.saturating_pow(self.lockouts))
+                .min(MAX_LOGIN_LOCKOUT);
+            self.locked_until = Some(now + lockout);
+            self.lockouts = self.lockouts.saturating_add(1);
+            self.failures.clear();
+        }
+    }
+
+    /// Clears failures and lockout history after a successful attempt.
+    pub fn record_success(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fail_until_locked(limiter: &mut LoginLimiter, now: Instant) {
+        for _ in 0..MAX_LOGIN_FAILURES {
+            assert!(limiter.locked_for(now).is_none());
+            limiter.record_failure(now);
+        }
+    }
+
+    #[test]
+    fn lockouts_back_off_exponentially() {
+        let mut limiter = LoginLimiter::default();
+        let start = Instant::now();
+
+        fail_until_locked(&mut limiter, start);
+        assert_eq!(limiter.locked_for(start), Some(BASE_LOGIN_LOCKOUT));
+
+        let later = start + BASE_LOGIN_LOCKOUT;
+        fail_until_locked(&mut limiter, later);
+        assert_eq!(limiter.locked_for(later), Some(BASE_LOGIN_LOCKOUT * 2));
+
+        limiter.record_success();
+        assert!(limiter.locked_for(later).is_none());
+        fail_until_locked(&mut limiter, later);
+        assert_eq!(limiter.locked_for(later), Some(BASE_LOGIN_LOCKOUT));
+    }
+
+    #[test]
+    fn old_failures_leave_the_window() {
+        let mut limiter = LoginLimiter::default();
+        let start = Instant::now();
+        for _ in 0..MAX_LOGIN_FAILURES - 1 {
+            limiter.record_failure(start);
+        }
+
+        limiter.record_failure(start + LOGIN_FAILURE_WINDOW);
+        assert!(limiter.locked_for(start + LOGIN_FAILURE_WINDOW).is_none());
+    }
 }
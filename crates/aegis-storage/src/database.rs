@@ -3,27 +3,41 @@
 use std::path::PathBuf;
 
 use aegis_core::classifier::Category;
-use chrono::NaiveDate;
-use directories::ProjectDirs;
-use tracing::info;
+use aegis_core::community_rules::ParentOverrides;
+use aegis_core::data_dir::project_data_dir;
+use aegis_core::keyword_import::{ImportSummary, KeywordImport};
+use chrono::{DateTime, NaiveDate, Utc};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
 
 use crate::error::{Result, StorageError};
 use crate::models::{
-    Action, Auth, Config, DailyStats, DisabledBundledSite, Event, FlaggedEvent, FlaggedEventFilter,
-    FlaggedEventStats, NewEvent, NewFlaggedEvent, NewProfile, NewRule, NewSite, Profile, Rule,
-    Site,
+    Action, Auth, CategoryCount, Config, DailyStats, DisabledBundledSite, Event,
+    FlaggedCapturePolicy, FlaggedEvent, FlaggedEventFilter, FlaggedEventStats, LiveUpdate,
+    ModelCount, NewEvent, NewFlaggedEvent, NewProfile, NewRule, NewSite, PreviewPolicy, Profile,
+    ProfileRuleIssue, Rule, Site, SourceCount, CUSTOM_KEYWORDS_CONFIG_KEY,
+    DEFAULT_FLAGGED_DAILY_CAP, FLAGGED_CAPTURE_POLICY_CONFIG_KEY, FLAGGED_DAILY_CAP_CONFIG_KEY,
+    PARENT_OVERRIDES_CONFIG_KEY, PREVIEW_POLICY_CONFIG_KEY,
 };
 use crate::pool::ConnectionPool;
 use crate::repository::{
-    create_preview, create_snippet, hash_prompt, AuthRepo, ConfigRepo, DisabledBundledRepo,
+    create_safe_preview, create_snippet, hash_prompt, AuthRepo, ConfigRepo, DisabledBundledRepo,
     EventsRepo, FlaggedEventsRepo, ProfileRepo, RulesRepo, SiteRepo, StatsRepo,
 };
 
+/// Live updates buffered per subscriber before it starts lagging.
+pub const LIVE_UPDATE_CAPACITY: usize = 256;
+
 /// High-level database interface for Aegis.
+///
+/// Clones share the connection pool and the live update channel, so a
+/// subscriber sees events logged through any clone.
 #[derive(Clone)]
 pub struct Database {
     /// Connection pool (pub(crate) for repository access).
     pub(crate) pool: ConnectionPool,
+    /// Publishes [`LiveUpdate`]s to subscribers.
+    updates: broadcast::Sender<LiveUpdate>,
 }
 
 impl Database {
@@ -39,7 +53,7 @@ impl Database {
         info!("Opening database at: {:?}", path);
         let pool = ConnectionPool::new(&path)?;
 
-        Ok(Self { pool })
+        Ok(Self::from_pool(pool))
     }
 
     /// Create a new database at a specific path.
@@ -54,26 +68,57 @@ impl Database {
         info!("Opening database at: {:?}", path);
         let pool = ConnectionPool::new(&path)?;
 
-        Ok(Self { pool })
+        Ok(Self::from_pool(pool))
     }
 
     /// Create an in-memory database (for testing).
     pub fn in_memory() -> Result<Self> {
         let pool = ConnectionPool::in_memory()?;
-        Ok(Self { pool })
+        Ok(Self::from_pool(pool))
+    }
+
+    fn from_pool(pool: ConnectionPool) -> Self {
+        let (updates, _) = broadcast::channel(LIVE_UPDATE_CAPACITY);
+        Self { pool, updates }
+    }
+
+    /// Subscribe to events and stats as they are recorded.
+    ///
+    /// Publishing never waits on subscribers: one that falls more than
+    /// [`LIVE_UPDATE_CAPACITY`] updates behind misses the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Returns true if anyone is listening for live updates.
+    fn has_subscribers(&self) -> bool {
+        self.updates.receiver_count() > 0
+    }
+
+    /// Checks that the database accepts writes.
+    ///
+    /// Takes and releases the write lock without changing anything, so a
+    /// read-only, locked, or missing database file shows up as an error.
+    pub fn check_writable(&self) -> Result<()> {
+        let conn = self.pool.get()?;
+        conn.execute_batch("BEGIN IMMEDIATE; ROLLBACK;")?;
+        Ok(())
     }
 
     /// Get the default database path.
     pub fn default_db_path() -> Result<PathBuf> {
-        let proj_dirs = ProjectDirs::from("com", "aegis", "aegis")
+        let data_dir = project_data_dir("com", "aegis", "aegis")
             .ok_or_else(|| StorageError::Config("Could not determine app data directory".into()))?;
 
-        Ok(proj_dirs.data_dir().join("aegis.db"))
+        Ok(data_dir.join("aegis.db"))
     }
 
     // === Events ===
 
     /// Log a new event from a prompt.
+    ///
+    /// The preview follows the stored [`PreviewPolicy`], so prompts in
+    /// sensitive categories are stored as a label rather than text.
     pub fn log_event(
         &self,
         prompt: &str,
@@ -82,22 +127,97 @@ impl Database {
         action: Action,
         source: Option<String>,
     ) -> Result<i64> {
-        let conn = self.pool.get()?;
+        self.log_event_with_model(prompt, category, confidence, action, source, None)
+    }
 
-        let event = NewEvent {
+    /// Log a new event from a prompt, recording the model it requested.
+    pub fn log_event_with_model(
+        &self,
+        prompt: &str,
+        category: Option<Category>,
+        confidence: Option<f32>,
+        action: Action,
+        source: Option<String>,
+        model: Option<String>,
+    ) -> Result<i64> {
+        let policy = match category {
+            Some(_) => self.preview_policy()?,
+            None => PreviewPolicy::show_all(),
+        };
+        self.insert_event(NewEvent {
             prompt_hash: hash_prompt(prompt),
-            preview: create_preview(prompt),
+            preview: create_safe_preview(prompt, category, &policy),
             category,
             confidence,
             action,
             source,
-        };
+            model,
+        })
+    }
+
+    /// Get the policy for which categories get labeled previews.
+    pub fn preview_policy(&self) -> Result<PreviewPolicy> {
+        self.get_config_or_default(PREVIEW_POLICY_CONFIG_KEY, PreviewPolicy::default())
+    }
+
+    /// Set the policy for which categories get labeled previews.
+    ///
+    /// Applies to events logged afterwards; existing previews are unchanged.
+    pub fn set_preview_policy(&self, policy: &PreviewPolicy) -> Result<()> {
+        self.set_config(PREVIEW_POLICY_CONFIG_KEY, &serde_json::to_value(policy)?)
+    }
+
+    /// Log an event keeping only the prompt hash, with an empty preview.
+    pub fn log_event_without_preview(
+        &self,
+        prompt: &str,
+        category: Option<Category>,
+        confidence: Option<f32>,
+        action: Action,
+        source: Option<String>,
+    ) -> Result<i64> {
+        self.log_event_without_preview_with_model(
+            prompt, category, confidence, action, source, None,
+        )
+    }
+
+    /// Log an event without a preview, recording the model it requested.
+    pub fn log_event_without_preview_with_model(
+        &self,
+        prompt: &str,
+        category: Option<Category>,
+        confidence: Option<f32>,
+        action: Action,
+        source: Option<String>,
+        model: Option<String>,
+    ) -> Result<i64> {
+        self.insert_event(NewEvent {
+            prompt_hash: hash_prompt(prompt),
+            preview: String::new(),
+            category,
+            confidence,
+            action,
+            source,
+            model,
+        })
+    }
+
+    fn insert_event(&self, event: NewEvent) -> Result<i64> {
+        let conn = self.pool.get()?;
+        let (action, category) = (event.action, event.category);
 
         let id = EventsRepo::insert(&conn, event)?;
 
         // Update daily stats
         StatsRepo::increment(&conn, action, category)?;
 
+        if self.has_subscribers() {
+            if let Some(event) = EventsRepo::get_by_id(&conn, id)? {
+                let _ = self.updates.send(LiveUpdate::Event(event));
+            }
+            let _ = self.updates.send(LiveUpdate::Stats { action, category });
+        }
+
         Ok(id)
     }
 
@@ -124,12 +244,65 @@ impl Database {
         EventsRepo::get_by_action(&conn, action, limit, offset)
     }
 
+    /// Get events with an ID below `id`, newest first.
+    pub fn get_events_before(&self, id: i64, limit: i64) -> Result<Vec<Event>> {
+        let conn = self.pool.get()?;
+        EventsRepo::get_events_before(&conn, id, limit)
+    }
+
+    /// Get events with `action` and an ID below `id`, newest first.
+    pub fn get_events_by_action_before(
+        &self,
+        action: Action,
+        id: i64,
+        limit: i64,
+    ) -> Result<Vec<Event>> {
+        let conn = self.pool.get()?;
+        EventsRepo::get_events_by_action_before(&conn, action, id, limit)
+    }
+
+    /// Get events with an ID above `id` created between `from` and `to`
+    /// (inclusive), oldest first.
+    pub fn get_events_in_range_after(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        id: i64,
+        limit: i64,
+    ) -> Result<Vec<Event>> {
+        let conn = self.pool.get()?;
+        EventsRepo::get_events_in_range_after(&conn, from, to, id, limit)
+    }
+
     /// Count total events.
     pub fn count_events(&self) -> Result<i64> {
         let conn = self.pool.get()?;
         EventsRepo::count(&conn)
     }
 
+    /// Get the most frequent categories among events with `action` since `since`.
+    pub fn top_categories(
+        &self,
+        action: Action,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<CategoryCount>> {
+        let conn = self.pool.get()?;
+        EventsRepo::top_categories(&conn, action, since, limit)
+    }
+
+    /// Get the most frequent event sources since `since`.
+    pub fn top_sources(&self, since: DateTime<Utc>, limit: i64) -> Result<Vec<SourceCount>> {
+        let conn = self.pool.get()?;
+        EventsRepo::top_sources(&conn, since, limit)
+    }
+
+    /// Get the number of events per requested model, most used first.
+    pub fn count_events_by_model(&self) -> Result<Vec<ModelCount>> {
+        let conn = self.pool.get()?;
+        EventsRepo::count_by_model(&conn)
+    }
+
     // === Rules ===
 
     /// Create a new rule.
@@ -156,6 +329,18 @@ impl Database {
         RulesRepo::get_all(&conn)
     }
 
+    /// Get all rules and the version they were read at.
+    pub fn get_all_rules_versioned(&self) -> Result<(Vec<Rule>, i64)> {
+        let conn = self.pool.get()?;
+        RulesRepo::get_all_versioned(&conn)
+    }
+
+    /// Get the current rules version.
+    pub fn rules_version(&self) -> Result<i64> {
+        let conn = self.pool.get()?;
+        RulesRepo::version(&conn)
+    }
+
     /// Get all enabled rules.
     pub fn get_enabled_rules(&self) -> Result<Vec<Rule>> {
         let conn = self.pool.get()?;
@@ -168,6 +353,20 @@ impl Database {
         RulesRepo::update(&conn, id, rule)
     }
 
+    /// Update rules only if they are still at `expected_version`.
+    ///
+    /// Returns the number updated and the new version, or
+    /// [`StorageError::VersionConflict`](crate::StorageError::VersionConflict)
+    /// if another write happened first.
+    pub fn update_rules_if_version(
+        &self,
+        expected_version: i64,
+        updates: Vec<(i64, NewRule)>,
+    ) -> Result<(usize, i64)> {
+        let conn = self.pool.get()?;
+        RulesRepo::update_if_version(&conn, expected_version, updates)
+    }
+
     /// Enable or disable a rule.
     pub fn set_rule_enabled(&self, id: i64, enabled: bool) -> Result<()> {
         let conn = self.pool.get()?;
@@ -182,6 +381,16 @@ impl Database {
 
     // === Stats ===
 
+    /// Count an action in today's stats without storing an event.
+    pub fn increment_stats(&self, action: Action, category: Option<Category>) -> Result<()> {
+        let conn = self.pool.get()?;
+        StatsRepo::increment(&conn, action, category)?;
+        if self.has_subscribers() {
+            let _ = self.updates.send(LiveUpdate::Stats { action, category });
+        }
+        Ok(())
+    }
+
     /// Get stats for a specific date.
     pub fn get_stats(&self, date: NaiveDate) -> Result<Option<DailyStats>> {
         let conn = self.pool.get()?;
@@ -214,6 +423,12 @@ impl Database {
         ConfigRepo::set(&conn, key, value)
     }
 
+    /// Get all configuration values, ordered by key.
+    pub fn get_all_config(&self) -> Result<Vec<Config>> {
+        let conn = self.pool.get()?;
+        ConfigRepo::get_all(&conn)
+    }
+
     /// Get a typed configuration value with default.
     pub fn get_config_or_default<T: serde::de::DeserializeOwned>(
         &self,
@@ -224,6 +439,45 @@ impl Database {
         ConfigRepo::get_or_default(&conn, key, default)
     }
 
+    /// Get the stored custom keyword list, if one has been saved.
+    pub fn custom_keywords(&self) -> Result<Option<serde_json::Value>> {
+        Ok(self
+            .get_config(CUSTOM_KEYWORDS_CONFIG_KEY)?
+            .map(|config| config.value))
+    }
+
+    /// Store a custom keyword list, loaded by the classifier at startup.
+    pub fn set_custom_keywords(&self, keywords: &serde_json::Value) -> Result<()> {
+        self.set_config(CUSTOM_KEYWORDS_CONFIG_KEY, keywords)
+    }
+
+    /// Get the parent's whitelist/blacklist overrides (empty if unset).
+    pub fn parent_overrides(&self) -> Result<ParentOverrides> {
+        self.get_config_or_default(PARENT_OVERRIDES_CONFIG_KEY, ParentOverrides::new())
+    }
+
+    /// Set the parent's whitelist/blacklist overrides.
+    pub fn set_parent_overrides(&self, overrides: &ParentOverrides) -> Result<()> {
+        self.set_config(
+            PARENT_OVERRIDES_CONFIG_KEY,
+            &serde_json::to_value(overrides)?,
+        )
+    }
+
+    /// Merge an imported keyword list into the stored parent overrides.
+    ///
+    /// Returns what changed and the updated overrides, so callers can apply
+    /// them to a running classifier.
+    pub fn import_keywords(
+        &self,
+        import: &KeywordImport,
+    ) -> Result<(ImportSummary, ParentOverrides)> {
+        let mut overrides = self.parent_overrides()?;
+        let summary = import.apply(&mut overrides);
+        self.set_parent_overrides(&overrides)?;
+        Ok((summary, overrides))
+    }
+
     // === Auth ===
 
     /// Check if authentication is set up.
@@ -288,6 +542,24 @@ impl Database {
         ProfileRepo::get_enabled(&conn)
     }
 
+    /// Check every profile's stored rules, returning those that fail to parse.
+    ///
+    /// Run at startup so a corrupt profile is reported instead of silently
+    /// running with default rules.
+    pub fn preflight_profiles(&self) -> Result<Vec<ProfileRuleIssue>> {
+        let issues: Vec<_> = self
+            .get_all_profiles()?
+            .iter()
+            .flat_map(Profile::rule_issues)
+            .collect();
+
+        for issue in &issues {
+            warn!("Profile preflight: {}", issue);
+        }
+
+        Ok(issues)
+    }
+
     /// Update a profile.
     pub fn update_profile(&self, id: i64, profile: NewProfile) -> Result<()> {
         let conn = self.pool.get()?;
@@ -433,6 +705,13 @@ impl Database {
     // === Flagged Events (Sentiment Analysis) ===
 
     /// Log a flagged event from sentiment analysis.
+    ///
+    /// Flag types suppressed by the stored [`FlaggedCapturePolicy`] are
+    /// recorded with an empty snippet and no matched phrases.
+    ///
+    /// Once the profile has reached its [daily cap](Self::flagged_daily_cap),
+    /// a flag of a type already seen today is counted on the latest event of
+    /// that type, whose ID is returned, instead of adding a row.
     pub fn log_flagged_event(
         &self,
         profile_id: i64,
@@ -442,13 +721,35 @@ impl Database {
         source: Option<String>,
         matched_phrases: Vec<String>,
     ) -> Result<i64> {
+        let (content_snippet, matched_phrases) =
+            if self.flagged_capture_policy()?.suppresses(flag_type) {
+                (String::new(), Vec::new())
+            } else {
+                (create_snippet(content), matched_phrases)
+            };
+        let daily_cap = self.flagged_daily_cap()?;
         let conn = self.pool.get()?;
 
+        if let Some(cap) = daily_cap {
+            let today = Utc::now()
+                .date_naive()
+                .and_hms_opt(0, 0, 0)
+                .expect("midnight is a valid time")
+                .and_utc();
+            if FlaggedEventsRepo::count_for_profile_since(&conn, profile_id, today)? >= cap as i64 {
+                if let Some(id) = FlaggedEventsRepo::add_occurrence_to_latest(
+                    &conn, profile_id, flag_type, confidence, today,
+                )? {
+                    return Ok(id);
+                }
+            }
+        }
+
         let event = NewFlaggedEvent {
             profile_id,
             flag_type: flag_type.to_string(),
             confidence,
-            content_snippet: create_snippet(content),
+            content_snippet,
             source,
             matched_phrases,
         };
@@ -456,6 +757,39 @@ impl Database {
         FlaggedEventsRepo::insert(&conn, event)
     }
 
+    /// Get the number of flagged events stored per profile per day before
+    /// repeats are counted instead (`None` for no cap).
+    pub fn flagged_daily_cap(&self) -> Result<Option<u32>> {
+        self.get_config_or_default(
+            FLAGGED_DAILY_CAP_CONFIG_KEY,
+            Some(DEFAULT_FLAGGED_DAILY_CAP),
+        )
+    }
+
+    /// Set the number of flagged events stored per profile per day
+    /// (`None` for no cap).
+    pub fn set_flagged_daily_cap(&self, cap: Option<u32>) -> Result<()> {
+        self.set_config(FLAGGED_DAILY_CAP_CONFIG_KEY, &serde_json::to_value(cap)?)
+    }
+
+    /// Get the policy for which flagged events keep their text.
+    pub fn flagged_capture_policy(&self) -> Result<FlaggedCapturePolicy> {
+        self.get_config_or_default(
+            FLAGGED_CAPTURE_POLICY_CONFIG_KEY,
+            FlaggedCapturePolicy::default(),
+        )
+    }
+
+    /// Set the policy for which flagged events keep their text.
+    ///
+    /// Applies to events flagged afterwards; stored events are unchanged.
+    pub fn set_flagged_capture_policy(&self, policy: &FlaggedCapturePolicy) -> Result<()> {
+        self.set_config(
+            FLAGGED_CAPTURE_POLICY_CONFIG_KEY,
+            &serde_json::to_value(policy)?,
+        )
+    }
+
     /// Get a flagged event by ID.
     pub fn get_flagged_event(&self, id: i64) -> Result<Option<FlaggedEvent>> {
         let conn = self.pool.get()?;
@@ -468,6 +802,19 @@ impl Database {
         FlaggedEventsRepo::get_filtered(&conn, filter)
     }
 
+    /// Get flagged events with an ID above `id` created between `from` and
+    /// `to` (inclusive), oldest first.
+    pub fn get_flagged_events_in_range_after(
+        &self,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        id: i64,
+        limit: i64,
+    ) -> Result<Vec<FlaggedEvent>> {
+        let conn = self.pool.get()?;
+        FlaggedEventsRepo::get_in_range_after(&conn, from, to, id, limit)
+    }
+
     /// Get recent flagged events.
     pub fn get_recent_flagged_events(&self, limit: i64, offset: i64) -> Result<Vec<FlaggedEvent>> {
         let conn = self.pool.get()?;
@@ -528,6 +875,25 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn default_db_path_uses_data_dir_override() {
+        let dir = tempfile::tempdir().unwrap();
+
+        std::env::set_var(aegis_core::data_dir::DATA_DIR_ENV, dir.path());
+        let path = Database::default_db_path();
+        std::env::remove_var(aegis_core::data_dir::DATA_DIR_ENV);
+
+        assert_eq!(path.unwrap(), dir.path().join("aegis.db"));
+    }
+
+    #[test]
+    fn check_writable_leaves_database_unchanged() {
+        let db = Database::in_memory().unwrap();
+        db.check_writable().unwrap();
+        db.check_writable().unwrap();
+        assert_eq!(db.count_events().unwrap(), 0);
+    }
+
     #[test]
     fn test_log_and_get_event() {
         let db = Database::in_memory().unwrap();
@@ -547,6 +913,186 @@ mod tests {
         assert_eq!(event.action, Action::Blocked);
     }
 
+    #[test]
+    fn test_subscribers_see_logged_events() {
+        let db = Database::in_memory().unwrap();
+        let mut updates = db.clone().subscribe();
+
+        let id = db
+            .log_event("hello", None, None, Action::Allowed, None)
+            .unwrap();
+        db.increment_stats(Action::Allowed, None).unwrap();
+
+        match updates.try_recv().unwrap() {
+            LiveUpdate::Event(event) => assert_eq!(event.id, id),
+            other => panic!("expected event, got {:?}", other),
+        }
+        for _ in 0..2 {
+            assert!(matches!(
+                updates.try_recv().unwrap(),
+                LiveUpdate::Stats {
+                    action: Action::Allowed,
+                    category: None
+                }
+            ));
+        }
+        assert!(updates.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_sensitive_event_preview_is_labeled() {
+        let db = Database::in_memory().unwrap();
+
+        let self_harm = db
+            .log_event(
+                "I want to hurt myself tonight",
+                Some(Category::SelfHarm),
+                Some(0.9),
+                Action::Blocked,
+                None,
+            )
+            .unwrap();
+        let benign = db
+            .log_event(
+                "What is the capital of France?",
+                None,
+                None,
+                Action::Allowed,
+                None,
+            )
+            .unwrap();
+
+        assert_eq!(
+            db.get_event(self_harm).unwrap().unwrap().preview,
+            "[Self-Harm content, 29 chars]"
+        );
+        assert_eq!(
+            db.get_event(benign).unwrap().unwrap().preview,
+            "What is the capital of France?"
+        );
+
+        // Turning labeling off applies to new events
+        db.set_preview_policy(&PreviewPolicy::show_all()).unwrap();
+        assert_eq!(db.preview_policy().unwrap(), PreviewPolicy::show_all());
+        let shown = db
+            .log_event(
+                "I want to hurt myself tonight",
+                Some(Category::SelfHarm),
+                Some(0.9),
+                Action::Blocked,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            db.get_event(shown).unwrap().unwrap().preview,
+            "I want to hurt myself tonight"
+        );
+    }
+
+    #[test]
+    fn test_crisis_flags_are_stored_without_text() {
+        let db = Database::in_memory().unwrap();
+        let profile_id = db
+            .create_profile(profile_with_rules(
+                serde_json::json!({}),
+                serde_json::json!({}),
+            ))
+            .unwrap();
+        let log = |flag_type: &str| {
+            let id = db
+                .log_flagged_event(
+                    profile_id,
+                    flag_type,
+                    0.9,
+                    "nobody would miss me if I was gone",
+                    Some("chatgpt".to_string()),
+                    vec!["nobody would miss me".to_string()],
+                )
+                .unwrap();
+            db.get_flagged_event(id).unwrap().unwrap()
+        };
+
+        assert_eq!(
+            db.flagged_capture_policy().unwrap(),
+            FlaggedCapturePolicy::default()
+        );
+        let crisis = log("crisis_indicator");
+        assert_eq!(crisis.flag_type, "crisis_indicator");
+        assert_eq!(crisis.confidence, 0.9);
+        assert_eq!(crisis.content_snippet, "");
+        assert!(crisis.matched_phrases.is_empty());
+
+        // Lower-severity flags keep their snippet
+        let distress = log("distress");
+        assert_eq!(
+            distress.content_snippet,
+            "nobody would miss me if I was gone"
+        );
+        assert_eq!(distress.matched_phrases.len(), 1);
+
+        // Full capture is an explicit opt-in
+        db.set_flagged_capture_policy(&FlaggedCapturePolicy::full_capture())
+            .unwrap();
+        let captured = log("crisis_indicator");
+        assert_eq!(
+            captured.content_snippet,
+            "nobody would miss me if I was gone"
+        );
+        assert_eq!(captured.matched_phrases.len(), 1);
+    }
+
+    #[test]
+    fn test_flags_beyond_daily_cap_are_counted() {
+        let db = Database::in_memory().unwrap();
+        let profile_id = db
+            .create_profile(profile_with_rules(
+                serde_json::json!({}),
+                serde_json::json!({}),
+            ))
+            .unwrap();
+        assert_eq!(
+            db.flagged_daily_cap().unwrap(),
+            Some(DEFAULT_FLAGGED_DAILY_CAP)
+        );
+        db.set_flagged_daily_cap(Some(2)).unwrap();
+
+        let log = |flag_type: &str, confidence: f32| {
+            db.log_flagged_event(
+                profile_id,
+                flag_type,
+                confidence,
+                "I feel so alone",
+                None,
+                vec!["i feel so alone".to_string()],
+            )
+            .unwrap()
+        };
+
+        let first = log("distress", 0.7);
+        let second = log("distress", 0.7);
+        assert_ne!(first, second);
+
+        // Beyond the cap, distress flags update the latest distress event
+        assert_eq!(log("distress", 0.9), second);
+        assert_eq!(log("distress", 0.8), second);
+        assert_eq!(db.count_flagged_events().unwrap(), 2);
+        let latest = db.get_flagged_event(second).unwrap().unwrap();
+        assert_eq!(latest.occurrences, 3);
+        assert_eq!(latest.confidence, 0.9);
+        assert_eq!(db.get_flagged_event(first).unwrap().unwrap().occurrences, 1);
+
+        // A type not yet seen today still gets its own event
+        let bullying = log("bullying", 0.8);
+        assert_eq!(db.count_flagged_events().unwrap(), 3);
+        assert_eq!(log("bullying", 0.8), bullying);
+
+        // Without a cap every flag is stored
+        db.set_flagged_daily_cap(None).unwrap();
+        assert_eq!(db.flagged_daily_cap().unwrap(), None);
+        log("distress", 0.7);
+        assert_eq!(db.count_flagged_events().unwrap(), 4);
+    }
+
     #[test]
     fn test_rules_crud() {
         let db = Database::in_memory().unwrap();
@@ -616,6 +1162,66 @@ mod tests {
         assert_eq!(stats.allowed_count, 1);
     }
 
+    #[test]
+    fn test_each_action_persisted_and_counted() {
+        let db = Database::in_memory().unwrap();
+
+        for action in Action::ALL {
+            let id = db
+                .log_event(action.as_str(), None, None, action, None)
+                .unwrap();
+            assert_eq!(db.get_event(id).unwrap().unwrap().action, action);
+            assert_eq!(db.get_events_by_action(action, 10, 0).unwrap().len(), 1);
+        }
+
+        let stats = db.get_total_stats().unwrap();
+        assert_eq!(stats.total_prompts, Action::ALL.len() as i64);
+        for action in Action::ALL {
+            assert_eq!(stats.action_count(action), 1, "{:?}", action);
+        }
+    }
+
+    #[test]
+    fn test_event_without_preview_and_stats_only() {
+        let db = Database::in_memory().unwrap();
+
+        let id = db
+            .log_event_without_preview("secret prompt", None, None, Action::Allowed, None)
+            .unwrap();
+        let event = db.get_event(id).unwrap().unwrap();
+        assert!(event.preview.is_empty());
+        assert!(!event.prompt_hash.is_empty());
+
+        db.increment_stats(Action::Allowed, None).unwrap();
+        assert_eq!(db.count_events().unwrap(), 1);
+        assert_eq!(db.get_total_stats().unwrap().allowed_count, 2);
+    }
+
+    #[test]
+    fn test_log_event_with_model() {
+        let db = Database::in_memory().unwrap();
+
+        let id = db
+            .log_event_with_model(
+                "hello",
+                None,
+                None,
+                Action::Allowed,
+                None,
+                Some("gpt-4o".to_string()),
+            )
+            .unwrap();
+        assert_eq!(
+            db.get_event(id).unwrap().unwrap().model.as_deref(),
+            Some("gpt-4o")
+        );
+
+        let id = db
+            .log_event("hello", None, None, Action::Allowed, None)
+            .unwrap();
+        assert_eq!(db.get_event(id).unwrap().unwrap().model, None);
+    }
+
     #[test]
     fn test_config() {
         let db = Database::in_memory().unwrap();
@@ -623,6 +1229,46 @@ mod tests {
         db.set_config("test_key", &json!({"nested": true})).unwrap();
         let config = db.get_config("test_key").unwrap().unwrap();
         assert_eq!(config.value["nested"], true);
+
+        let all = db.get_all_config().unwrap();
+        assert!(all.iter().any(|c| c.key == "test_key"));
+    }
+
+    #[test]
+    fn test_parent_overrides_round_trip() {
+        let db = Database::in_memory().unwrap();
+        assert!(db.parent_overrides().unwrap().blacklist.is_empty());
+
+        let mut overrides = ParentOverrides::new();
+        overrides.add_blacklist("gore", Category::Violence);
+        db.set_parent_overrides(&overrides).unwrap();
+
+        assert_eq!(
+            db.parent_overrides()
+                .unwrap()
+                .get_blacklist_category("gore"),
+            Some(Category::Violence)
+        );
+    }
+
+    #[test]
+    fn test_import_keywords_merges_with_stored_overrides() {
+        let db = Database::in_memory().unwrap();
+        let mut overrides = ParentOverrides::new();
+        overrides.add_whitelist("scrub");
+        db.set_parent_overrides(&overrides).unwrap();
+
+        let import = KeywordImport::from_csv("term,category\ngore,violence\n").unwrap();
+        let (summary, overrides) = db.import_keywords(&import).unwrap();
+
+        assert_eq!(summary.blocked, 1);
+        assert!(overrides.is_whitelisted("scrub"));
+        assert_eq!(
+            db.parent_overrides()
+                .unwrap()
+                .get_blacklist_category("gore"),
+            Some(Category::Violence)
+        );
     }
 
     #[test]
@@ -661,4 +1307,54 @@ mod tests {
         let state = db.get_protection_state().unwrap();
         assert!(state.is_active());
     }
+
+    fn profile_with_rules(
+        time_rules: serde_json::Value,
+        content_rules: serde_json::Value,
+    ) -> NewProfile {
+        NewProfile {
+            name: "Child".to_string(),
+            os_username: None,
+            time_rules,
+            content_rules,
+            enabled: true,
+            sentiment_config: Default::default(),
+            image_filtering_config: Default::default(),
+            proxy_mode: Default::default(),
+            text_filtering_enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_preflight_reports_malformed_rules() {
+        let db = Database::in_memory().unwrap();
+        db.create_profile(profile_with_rules(json!({"rules": []}), json!({})))
+            .unwrap();
+        let bad = db
+            .create_profile(profile_with_rules(
+                json!({"rules": [{"name": "missing fields"}]}),
+                json!({"rules": "not a list"}),
+            ))
+            .unwrap();
+
+        let issues = db.preflight_profiles().unwrap();
+
+        assert_eq!(issues.len(), 2);
+        assert!(issues.iter().all(|i| i.profile_id == bad));
+        assert_eq!(issues[0].field, "time_rules");
+        assert_eq!(issues[1].field, "content_rules");
+    }
+
+    #[test]
+    fn test_unset_rules_parse_as_empty() {
+        let db = Database::in_memory().unwrap();
+        let id = db
+            .create_profile(profile_with_rules(json!({}), serde_json::Value::Null))
+            .unwrap();
+        let profile = db.get_profile(id).unwrap().unwrap();
+
+        assert!(profile.parse_time_rules().unwrap().rules.is_empty());
+        assert!(profile.parse_content_rules().unwrap().rules.is_empty());
+        assert!(db.preflight_profiles().unwrap().is_empty());
+    }
 }
@@ -28,6 +28,15 @@ pub enum StorageError {
     /// Migration error.
     #[error("Migration error: {0}")]
     Migration(String),
+
+    /// A versioned write was based on stale data.
+    #[error("Version conflict: expected version {expected}, current is {current}")]
+    VersionConflict {
+        /// The version the caller last read.
+        expected: i64,
+        /// The version currently stored.
+        current: i64,
+    },
 }
 
 /// Result type for storage operations.
@@ -40,15 +40,18 @@ pub mod state_manager;
 pub use database::Database;
 pub use error::{Result, StorageError};
 pub use models::{
-    Action, Auth, CategoryCounts, Config, DailyStats, DisabledBundledSite, Event, FlaggedEvent,
-    FlaggedEventFilter, FlaggedEventStats, FlaggedTypeCounts, NewEvent, NewFlaggedEvent,
-    NewProfile, NewRule, NewSite, NsfwThresholdPreset, Profile, ProfileImageFilteringConfig,
-    ProfileSentimentConfig, Rule, Site,
+    Action, Auth, CategoryCount, CategoryCounts, Config, DailyStats, DisabledBundledSite, Event,
+    FlaggedCapturePolicy, FlaggedEvent, FlaggedEventFilter, FlaggedEventStats, FlaggedTypeCounts,
+    LiveUpdate, ModelCount, NewEvent, NewFlaggedEvent, NewProfile, NewRule, NewSite,
+    NsfwThresholdPreset, PreviewPolicy, Profile, ProfileImageFilteringConfig, ProfileRuleIssue,
+    ProfileSentimentConfig, Rule, Site, SourceCount, CUSTOM_KEYWORDS_CONFIG_KEY,
+    DEFAULT_FLAGGED_DAILY_CAP, FLAGGED_CAPTURE_POLICY_CONFIG_KEY, FLAGGED_DAILY_CAP_CONFIG_KEY,
+    PARENT_OVERRIDES_CONFIG_KEY, PREVIEW_POLICY_CONFIG_KEY,
 };
 pub use pool::ConnectionPool;
 pub use repository::{
-    create_preview, create_snippet, hash_prompt, FlaggedEventsRepo, ProtectionState, SessionRecord,
-    StateChange,
+    create_preview, create_safe_preview, create_snippet, hash_prompt, FlaggedEventsRepo,
+    ProtectionState, SessionRecord, StateChange,
 };
 pub use state_manager::{PauseDuration, StateError, StateManager};
 
@@ -1,6 +1,10 @@
 //! Data models for storage.
 
 use aegis_core::classifier::Category;
+use aegis_core::content_rules::ContentRuleSet;
+use aegis_core::profile::ProxyMode;
+use aegis_core::site_registry::{SiteCategory, SiteEntry, SiteSource};
+use aegis_core::time_rules::TimeRuleSet;
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -14,15 +18,34 @@ pub enum Action {
     Blocked,
     /// Prompt was flagged for review.
     Flagged,
+    /// Prompt was forwarded with a warning shown to the user.
+    Warned,
+    /// Prompt would have been blocked, but was allowed (monitor mode).
+    WouldBlock,
+    /// Prompt was forwarded with sensitive content redacted.
+    Redacted,
 }
 
 impl Action {
+    /// Every action, in display order.
+    pub const ALL: [Action; 6] = [
+        Action::Allowed,
+        Action::Blocked,
+        Action::Flagged,
+        Action::Warned,
+        Action::WouldBlock,
+        Action::Redacted,
+    ];
+
     /// Convert to database string representation.
     pub fn as_str(&self) -> &'static str {
         match self {
             Action::Allowed => "allowed",
             Action::Blocked => "blocked",
             Action::Flagged => "flagged",
+            Action::Warned => "warned",
+            Action::WouldBlock => "would_block",
+            Action::Redacted => "redacted",
         }
     }
 
@@ -32,6 +55,9 @@ impl Action {
             "allowed" => Some(Action::Allowed),
             "blocked" => Some(Action::Blocked),
             "flagged" => Some(Action::Flagged),
+            "warned" => Some(Action::Warned),
+            "would_block" => Some(Action::WouldBlock),
+            "redacted" => Some(Action::Redacted),
             _ => None,
         }
     }
@@ -56,6 +82,144 @@ pub struct Event {
     pub source: Option<String>,
     /// Timestamp.
     pub created_at: DateTime<Utc>,
+    /// Model the request asked for, if the payload named one.
+    pub model: Option<String>,
+}
+
+/// A change published to [`Database::subscribe`](crate::Database::subscribe)
+/// listeners as it is recorded.
+#[derive(Debug, Clone)]
+pub enum LiveUpdate {
+    /// An event was logged.
+    Event(Event),
+    /// Daily stats were incremented by one prompt.
+    Stats {
+        /// Action counted.
+        action: Action,
+        /// Category counted, if any.
+        category: Option<Category>,
+    },
+}
+
+/// Number of events in a category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryCount {
+    /// The category.
+    pub category: Category,
+    /// Number of events.
+    pub count: i64,
+}
+
+/// Number of events from a source (service).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceCount {
+    /// The source application/site.
+    pub source: String,
+    /// Number of events.
+    pub count: i64,
+}
+
+/// Number of events that asked for a model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelCount {
+    /// The requested model name.
+    pub model: String,
+    /// Number of events.
+    pub count: i64,
+}
+
+/// Config key under which the parent's whitelist/blacklist
+/// ([`ParentOverrides`](aegis_core::community_rules::ParentOverrides)) is stored.
+pub const PARENT_OVERRIDES_CONFIG_KEY: &str = "parent_overrides";
+
+/// Config key under which the custom keyword list (see
+/// [`KeywordClassifier::load_from_json`](aegis_core::classifier::KeywordClassifier::load_from_json))
+/// is stored.
+pub const CUSTOM_KEYWORDS_CONFIG_KEY: &str = "custom_keywords";
+
+/// Config key under which the [`PreviewPolicy`] is stored.
+pub const PREVIEW_POLICY_CONFIG_KEY: &str = "preview_policy";
+
+/// Which event categories get a labeled preview instead of the prompt text.
+///
+/// A truncated prompt can still be distressing to read, so events in
+/// sensitive categories store e.g. `[Self-Harm content, 42 chars]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreviewPolicy {
+    /// Categories whose previews are replaced with a label.
+    pub labeled_categories: Vec<Category>,
+}
+
+impl Default for PreviewPolicy {
+    fn default() -> Self {
+        Self {
+            labeled_categories: vec![Category::SelfHarm],
+        }
+    }
+}
+
+impl PreviewPolicy {
+    /// A policy that always shows the prompt text.
+    pub fn show_all() -> Self {
+        Self {
+            labeled_categories: Vec::new(),
+        }
+    }
+
+    /// Returns true if previews for `category` are labeled.
+    pub fn labels(&self, category: Category) -> bool {
+        self.labeled_categories.contains(&category)
+    }
+}
+
+/// Config key under which the [`FlaggedCapturePolicy`] is stored.
+pub const FLAGGED_CAPTURE_POLICY_CONFIG_KEY: &str = "flagged_capture_policy";
+
+/// Config key under which the daily flagged event cap is stored.
+pub const FLAGGED_DAILY_CAP_CONFIG_KEY: &str = "flagged_daily_cap";
+
+/// Flagged events stored per profile per day before repeats are counted
+/// on the latest event of the same type instead.
+pub const DEFAULT_FLAGGED_DAILY_CAP: u32 = 50;
+
+/// Which flagged events keep the text that triggered them.
+///
+/// A rare phrase from a crisis message can identify the child who wrote it,
+/// so by default high-severity flags are stored with only their type and
+/// confidence: no snippet and no matched phrases. Parents can opt into
+/// full capture.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlaggedCapturePolicy {
+    /// Flag types (e.g., `crisis_indicator`) stored without their text.
+    pub suppressed_flag_types: Vec<String>,
+    /// Keep the text for every flag type, overriding `suppressed_flag_types`.
+    pub full_capture: bool,
+}
+
+impl Default for FlaggedCapturePolicy {
+    fn default() -> Self {
+        Self {
+            suppressed_flag_types: vec!["crisis_indicator".to_string()],
+            full_capture: false,
+        }
+    }
+}
+
+impl FlaggedCapturePolicy {
+    /// A policy that keeps the text of every flagged event.
+    pub fn full_capture() -> Self {
+        Self {
+            full_capture: true,
+            ..Self::default()
+        }
+    }
+
+    /// Returns true if events of `flag_type` are stored without their text.
+    pub fn suppresses(&self, flag_type: &str) -> bool {
+        !self.full_capture && self.suppressed_flag_types.iter().any(|t| t == flag_type)
+    }
 }
 
 /// Parameters for creating a new event.
@@ -73,6 +237,8 @@ pub struct NewEvent {
     pub action: Action,
     /// Source application/site.
     pub source: Option<String>,
+    /// Model the request asked for, if the payload named one.
+    pub model: Option<String>,
 }
 
 /// Daily aggregated statistics.
@@ -88,10 +254,45 @@ pub struct DailyStats {
     pub allowed_count: i64,
     /// Number of prompts flagged.
     pub flagged_count: i64,
+    /// Number of prompts forwarded with a warning.
+    pub warned_count: i64,
+    /// Number of prompts that would have been blocked (monitor mode).
+    pub would_block_count: i64,
+    /// Number of prompts forwarded with redactions.
+    pub redacted_count: i64,
     /// Breakdown by category (JSON).
     pub category_counts: CategoryCounts,
 }
 
+impl DailyStats {
+    /// Creates zeroed stats for a date.
+    pub fn empty(date: NaiveDate) -> Self {
+        Self {
+            date,
+            total_prompts: 0,
+            blocked_count: 0,
+            allowed_count: 0,
+            flagged_count: 0,
+            warned_count: 0,
+            would_block_count: 0,
+            redacted_count: 0,
+            category_counts: CategoryCounts::default(),
+        }
+    }
+
+    /// Returns the count for an action.
+    pub fn action_count(&self, action: Action) -> i64 {
+        match action {
+            Action::Allowed => self.allowed_count,
+            Action::Blocked => self.blocked_count,
+            Action::Flagged => self.flagged_count,
+            Action::Warned => self.warned_count,
+            Action::WouldBlock => self.would_block_count,
+            Action::Redacted => self.redacted_count,
+        }
+    }
+}
+
 /// Category breakdown for daily stats.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CategoryCounts {
@@ -287,6 +488,86 @@ pub struct Profile {
     pub created_at: DateTime<Utc>,
     /// Updated timestamp.
     pub updated_at: DateTime<Utc>,
+    /// Whether filtering applies to this profile.
+    #[serde(default)]
+    pub proxy_mode: ProxyMode,
+    /// Whether prompts are classified against content rules.
+    #[serde(default = "default_true")]
+    pub text_filtering_enabled: bool,
+}
+
+impl Profile {
+    /// Parses the stored time rules.
+    ///
+    /// An unset value (`null` or `{}`) is an empty rule set; anything else
+    /// must deserialize.
+    pub fn parse_time_rules(&self) -> std::result::Result<TimeRuleSet, serde_json::Error> {
+        parse_rule_json(&self.time_rules)
+    }
+
+    /// Parses the stored content rules.
+    ///
+    /// An unset value (`null` or `{}`) is an empty rule set; anything else
+    /// must deserialize.
+    pub fn parse_content_rules(&self) -> std::result::Result<ContentRuleSet, serde_json::Error> {
+        parse_rule_json(&self.content_rules)
+    }
+
+    /// Returns an issue for each rule field that fails to parse.
+    pub fn rule_issues(&self) -> Vec<ProfileRuleIssue> {
+        let mut issues = Vec::new();
+        if let Err(e) = self.parse_time_rules() {
+            issues.push(ProfileRuleIssue::new(self, "time_rules", e));
+        }
+        if let Err(e) = self.parse_content_rules() {
+            issues.push(ProfileRuleIssue::new(self, "content_rules", e));
+        }
+        issues
+    }
+}
+
+fn parse_rule_json<T: Default + serde::de::DeserializeOwned>(
+    value: &serde_json::Value,
+) -> std::result::Result<T, serde_json::Error> {
+    match value {
+        serde_json::Value::Null => Ok(T::default()),
+        serde_json::Value::Object(map) if map.is_empty() => Ok(T::default()),
+        _ => serde_json::from_value(value.clone()),
+    }
+}
+
+/// A profile whose stored rule JSON could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileRuleIssue {
+    /// Profile ID.
+    pub profile_id: i64,
+    /// Profile display name.
+    pub profile_name: String,
+    /// The offending field (`time_rules` or `content_rules`).
+    pub field: String,
+    /// Parse error.
+    pub error: String,
+}
+
+impl ProfileRuleIssue {
+    fn new(profile: &Profile, field: &str, error: serde_json::Error) -> Self {
+        Self {
+            profile_id: profile.id,
+            profile_name: profile.name.clone(),
+            field: field.to_string(),
+            error: error.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ProfileRuleIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "profile '{}' (id={}) has invalid {}: {}",
+            self.profile_name, self.profile_id, self.field, self.error
+        )
+    }
 }
 
 /// Parameters for creating a new profile.
@@ -306,6 +587,10 @@ pub struct NewProfile {
     pub sentiment_config: ProfileSentimentConfig,
     /// Image filtering configuration (F033).
     pub image_filtering_config: ProfileImageFilteringConfig,
+    /// Whether filtering applies to this profile.
+    pub proxy_mode: ProxyMode,
+    /// Whether prompts are classified against content rules.
+    pub text_filtering_enabled: bool,
 }
 
 /// A site entry stored in the database.
@@ -327,12 +612,33 @@ pub struct Site {
     pub source: String,
     /// Priority for pattern matching.
     pub priority: i32,
+    /// Service name used in events and block pages (defaults to `name`).
+    pub service_name: Option<String>,
+    /// Prompt-bearing endpoint path patterns (empty = every path).
+    pub endpoints: Vec<String>,
     /// Created timestamp.
     pub created_at: DateTime<Utc>,
     /// Updated timestamp.
     pub updated_at: DateTime<Utc>,
 }
 
+impl Site {
+    /// Converts the stored site into a registry entry.
+    ///
+    /// Unknown categories and sources fall back to consumer and custom.
+    pub fn to_site_entry(&self) -> SiteEntry {
+        let mut entry = SiteEntry::new(&self.pattern, &self.name)
+            .with_category(SiteCategory::parse(&self.category).unwrap_or(SiteCategory::Consumer))
+            .with_source(SiteSource::parse(&self.source).unwrap_or(SiteSource::Custom))
+            .with_priority(self.priority);
+        entry.parser_id = self.parser_id.clone();
+        entry.service_name = self.service_name.clone();
+        entry.endpoints = self.endpoints.clone();
+        entry.enabled = self.enabled;
+        entry
+    }
+}
+
 /// Parameters for creating a new site.
 #[derive(Debug, Clone)]
 pub struct NewSite {
@@ -350,6 +656,10 @@ pub struct NewSite {
     pub source: String,
     /// Priority for pattern matching.
     pub priority: i32,
+    /// Service name used in events and block pages (defaults to `name`).
+    pub service_name: Option<String>,
+    /// Prompt-bearing endpoint path patterns (empty = every path).
+    pub endpoints: Vec<String>,
 }
 
 /// A disabled bundled site pattern.
@@ -386,6 +696,14 @@ pub struct FlaggedEvent {
     pub acknowledged_at: Option<DateTime<Utc>>,
     /// Timestamp.
     pub created_at: DateTime<Utc>,
+    /// Number of flags this event stands for; above 1 when repeats past the
+    /// daily cap were counted here instead of stored separately.
+    #[serde(default = "default_occurrences")]
+    pub occurrences: i64,
+}
+
+fn default_occurrences() -> i64 {
+    1
 }
 
 /// Parameters for creating a new flagged event.
@@ -5,7 +5,9 @@ use rusqlite::{params, Connection};
 use sha2::{Digest, Sha256};
 
 use crate::error::Result;
-use crate::models::{Action, Event, NewEvent};
+use crate::models::{
+    Action, CategoryCount, Event, ModelCount, NewEvent, PreviewPolicy, SourceCount,
+};
 
 /// Maximum preview length in characters.
 const PREVIEW_MAX_LEN: usize = 50;
@@ -17,8 +19,8 @@ impl EventsRepo {
     /// Insert a new event.
     pub fn insert(conn: &Connection, event: NewEvent) -> Result<i64> {
         conn.execute(
-            "INSERT INTO events (prompt_hash, preview, category, confidence, action, source)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO events (prompt_hash, preview, category, confidence, action, source, model)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 event.prompt_hash,
                 event.preview,
@@ -26,6 +28,7 @@ impl EventsRepo {
                 event.confidence,
                 event.action.as_str(),
                 event.source,
+                event.model,
             ],
         )?;
 
@@ -35,7 +38,7 @@ impl EventsRepo {
     /// Get an event by ID.
     pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<Event>> {
         let mut stmt = conn.prepare(
-            "SELECT id, prompt_hash, preview, category, confidence, action, source, created_at
+            "SELECT id, prompt_hash, preview, category, confidence, action, source, created_at, model
              FROM events WHERE id = ?1",
         )?;
 
@@ -56,6 +59,7 @@ impl EventsRepo {
                         .unwrap_or(Action::Allowed),
                     source: row.get(6)?,
                     created_at: parse_datetime(&row.get::<_, String>(7)?),
+                    model: row.get(8)?,
                 })
             })
             .ok();
@@ -66,7 +70,7 @@ impl EventsRepo {
     /// Get recent events with pagination.
     pub fn get_recent(conn: &Connection, limit: i64, offset: i64) -> Result<Vec<Event>> {
         let mut stmt = conn.prepare(
-            "SELECT id, prompt_hash, preview, category, confidence, action, source, created_at
+            "SELECT id, prompt_hash, preview, category, confidence, action, source, created_at, model
              FROM events ORDER BY created_at DESC LIMIT ?1 OFFSET ?2",
         )?;
 
@@ -87,6 +91,7 @@ impl EventsRepo {
                         .unwrap_or(Action::Allowed),
                     source: row.get(6)?,
                     created_at: parse_datetime(&row.get::<_, String>(7)?),
+                    model: row.get(8)?,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -103,7 +108,7 @@ impl EventsRepo {
         offset: i64,
     ) -> Result<Vec<Event>> {
         let mut stmt = conn.prepare(
-            "SELECT id, prompt_hash, preview, category, confidence, action, source, created_at
+            "SELECT id, prompt_hash, preview, category, confidence, action, source, created_at, model
              FROM events WHERE action = ?1 ORDER BY created_at DESC LIMIT ?2 OFFSET ?3",
         )?;
 
@@ -124,6 +129,7 @@ impl EventsRepo {
                         .unwrap_or(Action::Allowed),
                     source: row.get(6)?,
                     created_at: parse_datetime(&row.get::<_, String>(7)?),
+                    model: row.get(8)?,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -132,6 +138,79 @@ impl EventsRepo {
         Ok(events)
     }
 
+    /// Get up to `limit` events with an ID below `id`, newest first.
+    ///
+    /// IDs only grow, so paging with the last ID of each page as the next
+    /// cursor neither repeats nor skips events inserted between pages.
+    pub fn get_events_before(conn: &Connection, id: i64, limit: i64) -> Result<Vec<Event>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, prompt_hash, preview, category, confidence, action, source, created_at, model
+             FROM events WHERE id < ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+
+        let events = stmt
+            .query_map([id, limit], event_from_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Get up to `limit` events with `action` and an ID below `id`, newest first.
+    pub fn get_events_by_action_before(
+        conn: &Connection,
+        action: Action,
+        id: i64,
+        limit: i64,
+    ) -> Result<Vec<Event>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, prompt_hash, preview, category, confidence, action, source, created_at, model
+             FROM events WHERE action = ?1 AND id < ?2 ORDER BY id DESC LIMIT ?3",
+        )?;
+
+        let events = stmt
+            .query_map(params![action.as_str(), id, limit], event_from_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Get up to `limit` events with an ID above `id`, created between `from`
+    /// and `to` (both inclusive, either optional), oldest first.
+    ///
+    /// Exports page through a range with the last ID of each page as the
+    /// next cursor, so no single query holds the connection for long.
+    pub fn get_events_in_range_after(
+        conn: &Connection,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        id: i64,
+        limit: i64,
+    ) -> Result<Vec<Event>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, prompt_hash, preview, category, confidence, action, source, created_at, model
+             FROM events
+             WHERE id > ?1 AND (?2 IS NULL OR created_at >= ?2) AND (?3 IS NULL OR created_at <= ?3)
+             ORDER BY id ASC LIMIT ?4",
+        )?;
+
+        let events = stmt
+            .query_map(
+                params![
+                    id,
+                    from.map(format_datetime),
+                    to.map(format_datetime),
+                    limit
+                ],
+                event_from_row,
+            )?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
     /// Count total events.
     pub fn count(conn: &Connection) -> Result<i64> {
         let count: i64 = conn.query_row("SELECT COUNT(*) FROM events", [], |row| row.get(0))?;
@@ -148,6 +227,89 @@ impl EventsRepo {
         Ok(count)
     }
 
+    /// Most frequent categories among events with `action` since `since`.
+    ///
+    /// Ordered by count (descending), then category name.
+    pub fn top_categories(
+        conn: &Connection,
+        action: Action,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<CategoryCount>> {
+        let mut stmt = conn.prepare(
+            "SELECT category, COUNT(*) AS n FROM events
+             WHERE action = ?1 AND category IS NOT NULL AND created_at >= ?2
+             GROUP BY category ORDER BY n DESC, category ASC LIMIT ?3",
+        )?;
+
+        let counts = stmt
+            .query_map(
+                params![action.as_str(), format_datetime(since), limit],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+            )?
+            .filter_map(|r| r.ok())
+            .filter_map(|(category, count)| {
+                Some(CategoryCount {
+                    category: parse_category(&category)?,
+                    count,
+                })
+            })
+            .collect();
+
+        Ok(counts)
+    }
+
+    /// Most frequent sources among all events since `since`.
+    ///
+    /// Ordered by count (descending), then source name.
+    pub fn top_sources(
+        conn: &Connection,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<SourceCount>> {
+        let mut stmt = conn.prepare(
+            "SELECT source, COUNT(*) AS n FROM events
+             WHERE source IS NOT NULL AND created_at >= ?1
+             GROUP BY source ORDER BY n DESC, source ASC LIMIT ?2",
+        )?;
+
+        let counts = stmt
+            .query_map(params![format_datetime(since), limit], |row| {
+                Ok(SourceCount {
+                    source: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(counts)
+    }
+
+    /// Number of events per requested model, most used first.
+    ///
+    /// Events without a model (e.g. payloads that don't name one) are left
+    /// out. Ties are ordered by model name.
+    pub fn count_by_model(conn: &Connection) -> Result<Vec<ModelCount>> {
+        let mut stmt = conn.prepare(
+            "SELECT model, COUNT(*) AS n FROM events
+             WHERE model IS NOT NULL
+             GROUP BY model ORDER BY n DESC, model ASC",
+        )?;
+
+        let counts = stmt
+            .query_map([], |row| {
+                Ok(ModelCount {
+                    model: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(counts)
+    }
+
     /// Delete events older than a given date.
     pub fn delete_older_than(conn: &Connection, before: DateTime<Utc>) -> Result<i64> {
         let deleted = conn.execute(
@@ -181,7 +343,48 @@ pub fn create_preview(prompt: &str) -> String {
     }
 }
 
+/// Create a preview that respects `policy`.
+///
+/// Prompts in a labeled category are replaced with the category name and
+/// length; everything else gets the regular [`create_preview`].
+pub fn create_safe_preview(
+    prompt: &str,
+    category: Option<aegis_core::classifier::Category>,
+    policy: &PreviewPolicy,
+) -> String {
+    match category {
+        Some(category) if policy.labels(category) => format!(
+            "[{} content, {} chars]",
+            category.name(),
+            prompt.chars().count()
+        ),
+        _ => create_preview(prompt),
+    }
+}
+
 /// Parse a category from string.
+/// Maps a row selected as `id, prompt_hash, preview, category, confidence,
+/// action, source, created_at, model`.
+fn event_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Event> {
+    Ok(Event {
+        id: row.get(0)?,
+        prompt_hash: row.get(1)?,
+        preview: row.get(2)?,
+        category: row
+            .get::<_, Option<String>>(3)?
+            .and_then(|s| parse_category(&s)),
+        confidence: row.get(4)?,
+        action: row
+            .get::<_, String>(5)
+            .ok()
+            .and_then(|s| Action::parse(&s))
+            .unwrap_or(Action::Allowed),
+        source: row.get(6)?,
+        created_at: parse_datetime(&row.get::<_, String>(7)?),
+        model: row.get(8)?,
+    })
+}
+
 fn parse_category(s: &str) -> Option<aegis_core::classifier::Category> {
     use aegis_core::classifier::Category;
     match s {
@@ -191,10 +394,16 @@ fn parse_category(s: &str) -> Option<aegis_core::classifier::Category> {
         "jailbreak" => Some(Category::Jailbreak),
         "hate" => Some(Category::Hate),
         "illegal" => Some(Category::Illegal),
+        "profanity" => Some(Category::Profanity),
         _ => None,
     }
 }
 
+/// Format a datetime the way SQLite's `datetime('now')` stores `created_at`.
+fn format_datetime(dt: DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
 /// Parse a datetime from SQLite format.
 fn parse_datetime(s: &str) -> DateTime<Utc> {
     DateTime::parse_from_rfc3339(s)
@@ -242,6 +451,7 @@ mod tests {
             confidence: Some(0.95),
             action: Action::Blocked,
             source: Some("test".to_string()),
+            model: None,
         };
 
         let id = EventsRepo::insert(&conn, event).unwrap();
@@ -264,6 +474,7 @@ mod tests {
                 confidence: None,
                 action: Action::Allowed,
                 source: None,
+                model: None,
             };
             EventsRepo::insert(&conn, event).unwrap();
         }
@@ -272,6 +483,108 @@ mod tests {
         assert_eq!(events.len(), 3);
     }
 
+    #[test]
+    fn test_get_events_before_pages_by_id() {
+        let conn = setup_db();
+
+        let insert = |i: usize, action: Action| {
+            let event = NewEvent {
+                prompt_hash: hash_prompt(&format!("prompt {}", i)),
+                preview: create_preview(&format!("prompt {}", i)),
+                category: None,
+                confidence: None,
+                action,
+                source: None,
+                model: None,
+            };
+            EventsRepo::insert(&conn, event).unwrap()
+        };
+        let ids: Vec<i64> = (0..5)
+            .map(|i| {
+                let action = if i % 2 == 0 {
+                    Action::Blocked
+                } else {
+                    Action::Allowed
+                };
+                insert(i, action)
+            })
+            .collect();
+
+        let page = EventsRepo::get_events_before(&conn, i64::MAX, 2).unwrap();
+        let first: Vec<i64> = page.iter().map(|e| e.id).collect();
+        assert_eq!(first, [ids[4], ids[3]]);
+
+        // Events arriving between pages don't shift the next page
+        insert(5, Action::Allowed);
+        let page = EventsRepo::get_events_before(&conn, ids[3], 2).unwrap();
+        let second: Vec<i64> = page.iter().map(|e| e.id).collect();
+        assert_eq!(second, [ids[2], ids[1]]);
+
+        let page = EventsRepo::get_events_before(&conn, ids[0], 2).unwrap();
+        assert!(page.is_empty());
+
+        let blocked =
+            EventsRepo::get_events_by_action_before(&conn, Action::Blocked, ids[4], 10).unwrap();
+        let blocked: Vec<i64> = blocked.iter().map(|e| e.id).collect();
+        assert_eq!(blocked, [ids[2], ids[0]]);
+    }
+
+    #[test]
+    fn test_get_events_in_range_after() {
+        let conn = setup_db();
+
+        let mut ids = Vec::new();
+        for (i, created_at) in [
+            "2026-03-01 08:00:00",
+            "2026-03-02 12:00:00",
+            "2026-03-02 18:30:00",
+            "2026-03-04 09:00:00",
+        ]
+        .iter()
+        .enumerate()
+        {
+            let event = NewEvent {
+                prompt_hash: hash_prompt(&format!("prompt {}", i)),
+                preview: create_preview(&format!("prompt {}", i)),
+                category: None,
+                confidence: None,
+                action: Action::Allowed,
+                source: None,
+                model: None,
+            };
+            let id = EventsRepo::insert(&conn, event).unwrap();
+            conn.execute(
+                "UPDATE events SET created_at = ?1 WHERE id = ?2",
+                params![created_at, id],
+            )
+            .unwrap();
+            ids.push(id);
+        }
+
+        let at = |s: &str| {
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                .unwrap()
+                .and_utc()
+        };
+        let from = Some(at("2026-03-02 00:00:00"));
+        let to = Some(at("2026-03-02 18:30:00"));
+
+        let page = EventsRepo::get_events_in_range_after(&conn, from, to, 0, 1).unwrap();
+        assert_eq!(page.iter().map(|e| e.id).collect::<Vec<_>>(), [ids[1]]);
+        let page = EventsRepo::get_events_in_range_after(&conn, from, to, ids[1], 1).unwrap();
+        assert_eq!(page.iter().map(|e| e.id).collect::<Vec<_>>(), [ids[2]]);
+        let page = EventsRepo::get_events_in_range_after(&conn, from, to, ids[2], 1).unwrap();
+        assert!(page.is_empty());
+
+        let all = EventsRepo::get_events_in_range_after(&conn, None, None, 0, 10).unwrap();
+        assert_eq!(all.iter().map(|e| e.id).collect::<Vec<_>>(), ids);
+        let after = EventsRepo::get_events_in_range_after(&conn, to, None, 0, 10).unwrap();
+        assert_eq!(
+            after.iter().map(|e| e.id).collect::<Vec<_>>(),
+            [ids[2], ids[3]]
+        );
+    }
+
     #[test]
     fn test_count_events() {
         let conn = setup_db();
@@ -284,6 +597,7 @@ mod tests {
                 confidence: None,
                 action: Action::Blocked,
                 source: None,
+                model: None,
             };
             EventsRepo::insert(&conn, event).unwrap();
         }
@@ -299,6 +613,138 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_create_safe_preview() {
+        use aegis_core::classifier::Category;
+
+        let policy = PreviewPolicy::default();
+        assert_eq!(
+            create_safe_preview("I want to hurt myself", Some(Category::SelfHarm), &policy),
+            "[Self-Harm content, 21 chars]"
+        );
+        assert_eq!(
+            create_safe_preview("how to build a bomb", Some(Category::Violence), &policy),
+            "how to build a bomb"
+        );
+        assert_eq!(create_safe_preview("hello", None, &policy), "hello");
+
+        let policy = PreviewPolicy {
+            labeled_categories: vec![Category::Violence],
+        };
+        assert_eq!(
+            create_safe_preview("how to build a bomb", Some(Category::Violence), &policy),
+            "[Violence content, 19 chars]"
+        );
+        assert_eq!(
+            create_safe_preview(
+                "I want to hurt myself",
+                Some(Category::SelfHarm),
+                &PreviewPolicy::show_all()
+            ),
+            "I want to hurt myself"
+        );
+    }
+
+    #[test]
+    fn test_top_categories_and_sources() {
+        use aegis_core::classifier::Category;
+
+        let conn = setup_db();
+        let log = |category, action, source: &str| {
+            let event = NewEvent {
+                prompt_hash: hash_prompt("prompt"),
+                preview: create_preview("prompt"),
+                category: Some(category),
+                confidence: Some(0.9),
+                action,
+                source: Some(source.to_string()),
+                model: None,
+            };
+            EventsRepo::insert(&conn, event).unwrap();
+        };
+
+        for _ in 0..3 {
+            log(Category::Violence, Action::Blocked, "chatgpt");
+        }
+        for _ in 0..2 {
+            log(Category::Profanity, Action::Blocked, "claude");
+        }
+        log(Category::Jailbreak, Action::Blocked, "gemini");
+        log(Category::Adult, Action::Blocked, "claude");
+        // Not blocked, so counted for services but not categories
+        for _ in 0..4 {
+            log(Category::Hate, Action::Flagged, "claude");
+        }
+
+        let since = Utc::now() - chrono::Duration::days(7);
+        let categories = EventsRepo::top_categories(&conn, Action::Blocked, since, 3).unwrap();
+        assert_eq!(
+            categories,
+            vec![
+                CategoryCount {
+                    category: Category::Violence,
+                    count: 3
+                },
+                CategoryCount {
+                    category: Category::Profanity,
+                    count: 2
+                },
+                // Ties are broken by name
+                CategoryCount {
+                    category: Category::Adult,
+                    count: 1
+                },
+            ]
+        );
+
+        let sources = EventsRepo::top_sources(&conn, since, 3).unwrap();
+        let sources: Vec<_> = sources
+            .iter()
+            .map(|s| (s.source.as_str(), s.count))
+            .collect();
+        assert_eq!(sources, vec![("claude", 7), ("chatgpt", 3), ("gemini", 1)]);
+
+        let future = Utc::now() + chrono::Duration::hours(1);
+        assert!(
+            EventsRepo::top_categories(&conn, Action::Blocked, future, 3)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(EventsRepo::top_sources(&conn, future, 3)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_count_by_model() {
+        let conn = setup_db();
+        for model in [Some("gpt-4o"), Some("claude-3"), Some("gpt-4o"), None] {
+            let event = NewEvent {
+                prompt_hash: hash_prompt("prompt"),
+                preview: create_preview("prompt"),
+                category: None,
+                confidence: None,
+                action: Action::Allowed,
+                source: Some("chatgpt".to_string()),
+                model: model.map(str::to_string),
+            };
+            EventsRepo::insert(&conn, event).unwrap();
+        }
+
+        let counts: Vec<_> = EventsRepo::count_by_model(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.model, c.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![("gpt-4o".to_string(), 2), ("claude-3".to_string(), 1)]
+        );
+
+        let recent = EventsRepo::get_recent(&conn, 10, 0).unwrap();
+        assert_eq!(recent.iter().filter(|e| e.model.is_some()).count(), 3);
+    }
+
     #[test]
     fn test_hash_prompt() {
         let hash1 = hash_prompt("hello");
@@ -36,12 +36,58 @@ impl FlaggedEventsRepo {
         Ok(conn.last_insert_rowid())
     }
 
+    /// Count the flagged events stored for a profile since `since`.
+    pub fn count_for_profile_since(
+        conn: &Connection,
+        profile_id: i64,
+        since: DateTime<Utc>,
+    ) -> Result<i64> {
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM flagged_events WHERE profile_id = ?1 AND created_at >= ?2",
+            params![profile_id, format_datetime(since)],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Count another occurrence on the profile's latest event of `flag_type`
+    /// created since `since`, keeping the higher confidence.
+    ///
+    /// Returns the event's ID, or `None` if there is no such event.
+    pub fn add_occurrence_to_latest(
+        conn: &Connection,
+        profile_id: i64,
+        flag_type: &str,
+        confidence: f32,
+        since: DateTime<Utc>,
+    ) -> Result<Option<i64>> {
+        let id: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM flagged_events
+                 WHERE profile_id = ?1 AND flag_type = ?2 AND created_at >= ?3
+                 ORDER BY id DESC LIMIT 1",
+                params![profile_id, flag_type, format_datetime(since)],
+                |row| row.get(0),
+            )
+            .ok();
+
+        if let Some(id) = id {
+            conn.execute(
+                "UPDATE flagged_events
+                 SET occurrences = occurrences + 1, confidence = MAX(confidence, ?1)
+                 WHERE id = ?2",
+                params![confidence, id],
+            )?;
+        }
+        Ok(id)
+    }
+
     /// Get a flagged event by ID.
     pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<FlaggedEvent>> {
         let mut stmt = conn.prepare(
             "SELECT f.id, f.profile_id, p.name as profile_name, f.flag_type, f.confidence,
                     f.content_snippet, f.source, f.matched_phrases, f.acknowledged,
-                    f.acknowledged_at, f.created_at
+                    f.acknowledged_at, f.created_at, f.occurrences
              FROM flagged_events f
              LEFT JOIN profiles p ON f.profile_id = p.id
              WHERE f.id = ?1",
@@ -61,6 +107,7 @@ impl FlaggedEventsRepo {
                     acknowledged: row.get::<_, i32>(8)? != 0,
                     acknowledged_at: row.get::<_, Option<String>>(9)?.map(|s| parse_datetime(&s)),
                     created_at: parse_datetime(&row.get::<_, String>(10)?),
+                    occurrences: row.get(11)?,
                 })
             })
             .ok();
@@ -76,7 +123,7 @@ impl FlaggedEventsRepo {
         let mut sql = String::from(
             "SELECT f.id, f.profile_id, p.name as profile_name, f.flag_type, f.confidence,
                     f.content_snippet, f.source, f.matched_phrases, f.acknowledged,
-                    f.acknowledged_at, f.created_at
+                    f.acknowledged_at, f.created_at, f.occurrences
              FROM flagged_events f
              LEFT JOIN profiles p ON f.profile_id = p.id
              WHERE 1=1",
@@ -130,6 +177,7 @@ impl FlaggedEventsRepo {
                     acknowledged: row.get::<_, i32>(8)? != 0,
                     acknowledged_at: row.get::<_, Option<String>>(9)?.map(|s| parse_datetime(&s)),
                     created_at: parse_datetime(&row.get::<_, String>(10)?),
+                    occurrences: row.get(11)?,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -138,6 +186,59 @@ impl FlaggedEventsRepo {
         Ok(events)
     }
 
+    /// Get up to `limit` flagged events with an ID above `id`, created
+    /// between `from` and `to` (both inclusive, either optional), oldest first.
+    pub fn get_in_range_after(
+        conn: &Connection,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        id: i64,
+        limit: i64,
+    ) -> Result<Vec<FlaggedEvent>> {
+        let mut stmt = conn.prepare(
+            "SELECT f.id, f.profile_id, p.name as profile_name, f.flag_type, f.confidence,
+                    f.content_snippet, f.source, f.matched_phrases, f.acknowledged,
+                    f.acknowledged_at, f.created_at, f.occurrences
+             FROM flagged_events f
+             LEFT JOIN profiles p ON f.profile_id = p.id
+             WHERE f.id > ?1 AND (?2 IS NULL OR f.created_at >= ?2)
+                   AND (?3 IS NULL OR f.created_at <= ?3)
+             ORDER BY f.id ASC LIMIT ?4",
+        )?;
+
+        let events = stmt
+            .query_map(
+                params![
+                    id,
+                    from.map(format_datetime),
+                    to.map(format_datetime),
+                    limit
+                ],
+                |row| {
+                    Ok(FlaggedEvent {
+                        id: row.get(0)?,
+                        profile_id: row.get(1)?,
+                        profile_name: row.get(2)?,
+                        flag_type: row.get(3)?,
+                        confidence: row.get(4)?,
+                        content_snippet: row.get(5)?,
+                        source: row.get(6)?,
+                        matched_phrases: parse_json_array(&row.get::<_, String>(7)?),
+                        acknowledged: row.get::<_, i32>(8)? != 0,
+                        acknowledged_at: row
+                            .get::<_, Option<String>>(9)?
+                            .map(|s| parse_datetime(&s)),
+                        created_at: parse_datetime(&row.get::<_, String>(10)?),
+                        occurrences: row.get(11)?,
+                    })
+                },
+            )?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
     /// Get recent flagged events with pagination.
     pub fn get_recent(conn: &Connection, limit: i64, offset: i64) -> Result<Vec<FlaggedEvent>> {
         Self::get_filtered(
@@ -288,6 +389,11 @@ fn parse_json_array(s: &str) -> Vec<String> {
     serde_json::from_str(s).unwrap_or_default()
 }
 
+/// Format a datetime the way SQLite's `datetime('now')` stores `created_at`.
+fn format_datetime(dt: DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
 /// Parse a datetime from SQLite format.
 fn parse_datetime(s: &str) -> DateTime<Utc> {
     DateTime::parse_from_rfc3339(s)
@@ -426,6 +532,42 @@ mod tests {
         assert_eq!(stats.by_type.negative_sentiment, 0);
     }
 
+    #[test]
+    fn test_add_occurrence_to_latest() {
+        let conn = setup_db();
+        let since = Utc::now() - chrono::Duration::hours(1);
+
+        assert_eq!(
+            FlaggedEventsRepo::add_occurrence_to_latest(&conn, 1, "distress", 0.9, since).unwrap(),
+            None
+        );
+
+        for flag_type in ["distress", "distress", "bullying"] {
+            let event = NewFlaggedEvent {
+                profile_id: 1,
+                flag_type: flag_type.to_string(),
+                confidence: 0.7,
+                content_snippet: "Test".to_string(),
+                source: None,
+                matched_phrases: vec![],
+            };
+            FlaggedEventsRepo::insert(&conn, event).unwrap();
+        }
+        assert_eq!(
+            FlaggedEventsRepo::count_for_profile_since(&conn, 1, since).unwrap(),
+            3
+        );
+
+        let id = FlaggedEventsRepo::add_occurrence_to_latest(&conn, 1, "distress", 0.9, since)
+            .unwrap()
+            .unwrap();
+        assert_eq!(id, 2);
+        let event = FlaggedEventsRepo::get_by_id(&conn, id).unwrap().unwrap();
+        assert_eq!(event.occurrences, 2);
+        assert!((event.confidence - 0.9).abs() < 0.001);
+        assert_eq!(FlaggedEventsRepo::count(&conn).unwrap(), 3);
+    }
+
     #[test]
     fn test_create_snippet() {
         assert_eq!(create_snippet("short"), "short");
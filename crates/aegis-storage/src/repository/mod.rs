@@ -12,7 +12,7 @@ pub mod stats;
 
 pub use auth::AuthRepo;
 pub use config::ConfigRepo;
-pub use events::{create_preview, hash_prompt, EventsRepo};
+pub use events::{create_preview, create_safe_preview, hash_prompt, EventsRepo};
 pub use flagged::{create_snippet, FlaggedEventsRepo};
 pub use profiles::ProfileRepo;
 pub use rules::RulesRepo;
@@ -1,5 +1,6 @@
 //! Profile repository.
 
+use aegis_core::profile::ProxyMode;
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection};
 
@@ -20,8 +21,8 @@ impl ProfileRepo {
         let image_filtering_config_json = serde_json::to_string(&profile.image_filtering_config)?;
 
         conn.execute(
-            "INSERT INTO profiles (name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT INTO profiles (name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config, proxy_mode, text_filtering_enabled)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
                 profile.name,
                 profile.os_username,
@@ -29,7 +30,9 @@ impl ProfileRepo {
                 content_rules_json,
                 profile.enabled as i32,
                 sentiment_config_json,
-                image_filtering_config_json
+                image_filtering_config_json,
+                profile.proxy_mode.as_str(),
+                profile.text_filtering_enabled as i32
             ],
         )?;
 
@@ -39,7 +42,7 @@ impl ProfileRepo {
     /// Get a profile by ID.
     pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<Profile>> {
         let mut stmt = conn.prepare(
-            "SELECT id, name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config, created_at, updated_at
+            "SELECT id, name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config, created_at, updated_at, proxy_mode, text_filtering_enabled
              FROM profiles WHERE id = ?1",
         )?;
 
@@ -64,6 +67,8 @@ impl ProfileRepo {
                         .unwrap_or_default(),
                     created_at: parse_datetime(&row.get::<_, String>(8)?),
                     updated_at: parse_datetime(&row.get::<_, String>(9)?),
+                    proxy_mode: parse_proxy_mode(&row.get::<_, String>(10)?),
+                    text_filtering_enabled: row.get::<_, i32>(11)? != 0,
                 })
             })
             .ok();
@@ -74,7 +79,7 @@ impl ProfileRepo {
     /// Get a profile by OS username.
     pub fn get_by_os_username(conn: &Connection, os_username: &str) -> Result<Option<Profile>> {
         let mut stmt = conn.prepare(
-            "SELECT id, name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config, created_at, updated_at
+            "SELECT id, name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config, created_at, updated_at, proxy_mode, text_filtering_enabled
              FROM profiles WHERE os_username = ?1 AND enabled = 1",
         )?;
 
@@ -99,6 +104,8 @@ impl ProfileRepo {
                         .unwrap_or_default(),
                     created_at: parse_datetime(&row.get::<_, String>(8)?),
                     updated_at: parse_datetime(&row.get::<_, String>(9)?),
+                    proxy_mode: parse_proxy_mode(&row.get::<_, String>(10)?),
+                    text_filtering_enabled: row.get::<_, i32>(11)? != 0,
                 })
             })
             .ok();
@@ -109,7 +116,7 @@ impl ProfileRepo {
     /// Get all profiles.
     pub fn get_all(conn: &Connection) -> Result<Vec<Profile>> {
         let mut stmt = conn.prepare(
-            "SELECT id, name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config, created_at, updated_at
+            "SELECT id, name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config, created_at, updated_at, proxy_mode, text_filtering_enabled
              FROM profiles ORDER BY name ASC",
         )?;
 
@@ -134,6 +141,8 @@ impl ProfileRepo {
                         .unwrap_or_default(),
                     created_at: parse_datetime(&row.get::<_, String>(8)?),
                     updated_at: parse_datetime(&row.get::<_, String>(9)?),
+                    proxy_mode: parse_proxy_mode(&row.get::<_, String>(10)?),
+                    text_filtering_enabled: row.get::<_, i32>(11)? != 0,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -145,7 +154,7 @@ impl ProfileRepo {
     /// Get all enabled profiles.
     pub fn get_enabled(conn: &Connection) -> Result<Vec<Profile>> {
         let mut stmt = conn.prepare(
-            "SELECT id, name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config, created_at, updated_at
+            "SELECT id, name, os_username, time_rules, content_rules, enabled, sentiment_config, image_filtering_config, created_at, updated_at, proxy_mode, text_filtering_enabled
              FROM profiles WHERE enabled = 1 ORDER BY name ASC",
         )?;
 
@@ -170,6 +179,8 @@ impl ProfileRepo {
                         .unwrap_or_default(),
                     created_at: parse_datetime(&row.get::<_, String>(8)?),
                     updated_at: parse_datetime(&row.get::<_, String>(9)?),
+                    proxy_mode: parse_proxy_mode(&row.get::<_, String>(10)?),
+                    text_filtering_enabled: row.get::<_, i32>(11)? != 0,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -187,7 +198,8 @@ impl ProfileRepo {
 
         let updated = conn.execute(
             "UPDATE profiles SET name = ?1, os_username = ?2, time_rules = ?3, content_rules = ?4,
-             enabled = ?5, sentiment_config = ?6, image_filtering_config = ?7, updated_at = datetime('now') WHERE id = ?8",
+             enabled = ?5, sentiment_config = ?6, image_filtering_config = ?7, proxy_mode = ?8,
+             text_filtering_enabled = ?9, updated_at = datetime('now') WHERE id = ?10",
             params![
                 profile.name,
                 profile.os_username,
@@ -196,6 +208,8 @@ impl ProfileRepo {
                 profile.enabled as i32,
                 sentiment_config_json,
                 image_filtering_config_json,
+                profile.proxy_mode.as_str(),
+                profile.text_filtering_enabled as i32,
                 id
             ],
         )?;
@@ -239,6 +253,15 @@ impl ProfileRepo {
     }
 }
 
+/// Parse a stored proxy mode, defaulting to filtering for unknown values.
+fn parse_proxy_mode(s: &str) -> ProxyMode {
+    match s {
+        "disabled" => ProxyMode::Disabled,
+        "passthrough" => ProxyMode::Passthrough,
+        _ => ProxyMode::Enabled,
+    }
+}
+
 /// Parse a datetime from SQLite format.
 fn parse_datetime(s: &str) -> DateTime<Utc> {
     DateTime::parse_from_rfc3339(s)
@@ -273,6 +296,8 @@ mod tests {
             enabled: true,
             sentiment_config: ProfileSentimentConfig::default(),
             image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: ProxyMode::Enabled,
+            text_filtering_enabled: true,
         };
 
         let id = ProfileRepo::insert(&conn, profile).unwrap();
@@ -295,6 +320,8 @@ mod tests {
             enabled: true,
             sentiment_config: ProfileSentimentConfig::default(),
             image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: ProxyMode::Enabled,
+            text_filtering_enabled: true,
         };
 
         ProfileRepo::insert(&conn, profile).unwrap();
@@ -317,6 +344,8 @@ mod tests {
             enabled: false,
             sentiment_config: ProfileSentimentConfig::default(),
             image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: ProxyMode::Enabled,
+            text_filtering_enabled: true,
         };
 
         ProfileRepo::insert(&conn, profile).unwrap();
@@ -340,6 +369,8 @@ mod tests {
                 enabled: true,
                 sentiment_config: ProfileSentimentConfig::default(),
                 image_filtering_config: ProfileImageFilteringConfig::default(),
+                proxy_mode: ProxyMode::Enabled,
+                text_filtering_enabled: true,
             },
         )
         .unwrap();
@@ -354,6 +385,8 @@ mod tests {
                 enabled: true,
                 sentiment_config: ProfileSentimentConfig::default(),
                 image_filtering_config: ProfileImageFilteringConfig::default(),
+                proxy_mode: ProxyMode::Enabled,
+                text_filtering_enabled: true,
             },
         )
         .unwrap();
@@ -376,6 +409,8 @@ mod tests {
                 enabled: true,
                 sentiment_config: ProfileSentimentConfig::default(),
                 image_filtering_config: ProfileImageFilteringConfig::default(),
+                proxy_mode: ProxyMode::Enabled,
+                text_filtering_enabled: true,
             },
         )
         .unwrap();
@@ -390,6 +425,8 @@ mod tests {
                 enabled: false,
                 sentiment_config: ProfileSentimentConfig::default(),
                 image_filtering_config: ProfileImageFilteringConfig::default(),
+                proxy_mode: ProxyMode::Enabled,
+                text_filtering_enabled: true,
             },
         )
         .unwrap();
@@ -411,6 +448,8 @@ mod tests {
             enabled: true,
             sentiment_config: ProfileSentimentConfig::default(),
             image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: ProxyMode::Enabled,
+            text_filtering_enabled: true,
         };
 
         let id = ProfileRepo::insert(&conn, profile).unwrap();
@@ -426,6 +465,8 @@ mod tests {
                 enabled: false,
                 sentiment_config: ProfileSentimentConfig::default(),
                 image_filtering_config: ProfileImageFilteringConfig::default(),
+                proxy_mode: ProxyMode::Enabled,
+                text_filtering_enabled: true,
             },
         )
         .unwrap();
@@ -448,6 +489,8 @@ mod tests {
             enabled: true,
             sentiment_config: ProfileSentimentConfig::default(),
             image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: ProxyMode::Enabled,
+            text_filtering_enabled: true,
         };
 
         let id = ProfileRepo::insert(&conn, profile).unwrap();
@@ -469,6 +512,8 @@ mod tests {
             enabled: true,
             sentiment_config: ProfileSentimentConfig::default(),
             image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: ProxyMode::Enabled,
+            text_filtering_enabled: true,
         };
 
         let id = ProfileRepo::insert(&conn, profile).unwrap();
@@ -493,6 +538,8 @@ mod tests {
                 enabled: true,
                 sentiment_config: ProfileSentimentConfig::default(),
                 image_filtering_config: ProfileImageFilteringConfig::default(),
+                proxy_mode: ProxyMode::Enabled,
+                text_filtering_enabled: true,
             },
         )
         .unwrap();
@@ -512,6 +559,8 @@ mod tests {
             enabled: true,
             sentiment_config: ProfileSentimentConfig::default(),
             image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode: ProxyMode::Enabled,
+            text_filtering_enabled: true,
         };
 
         let id = ProfileRepo::insert(&conn, profile).unwrap();
@@ -520,4 +569,43 @@ mod tests {
         assert_eq!(retrieved.name, "Manual Only");
         assert!(retrieved.os_username.is_none());
     }
+
+    #[test]
+    fn test_proxy_mode_follows_explicit_field() {
+        let conn = setup_db();
+        let new_profile = |name: &str, proxy_mode| NewProfile {
+            name: name.to_string(),
+            os_username: None,
+            time_rules: json!({}),
+            content_rules: json!({}),
+            enabled: true,
+            sentiment_config: ProfileSentimentConfig::default(),
+            image_filtering_config: ProfileImageFilteringConfig::default(),
+            proxy_mode,
+            text_filtering_enabled: true,
+        };
+
+        let parent_named =
+            ProfileRepo::insert(&conn, new_profile("Parent", ProxyMode::Enabled)).unwrap();
+        let child_named =
+            ProfileRepo::insert(&conn, new_profile("Harper", ProxyMode::Disabled)).unwrap();
+
+        let parent_named = ProfileRepo::get_by_id(&conn, parent_named)
+            .unwrap()
+            .unwrap();
+        let child_named = ProfileRepo::get_by_id(&conn, child_named).unwrap().unwrap();
+        assert_eq!(parent_named.proxy_mode, ProxyMode::Enabled);
+        assert_eq!(child_named.proxy_mode, ProxyMode::Disabled);
+
+        ProfileRepo::update(
+            &conn,
+            child_named.id,
+            new_profile("Harper", ProxyMode::Passthrough),
+        )
+        .unwrap();
+        let updated = ProfileRepo::get_by_id(&conn, child_named.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.proxy_mode, ProxyMode::Passthrough);
+    }
 }
@@ -1,7 +1,7 @@
 //! Rules repository.
 
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, Transaction, TransactionBehavior};
 
 use crate::error::{Result, StorageError};
 use crate::models::{NewRule, Rule};
@@ -173,6 +173,59 @@ impl RulesRepo {
         Ok(())
     }
 
+    /// Get the current rules version. Every write to the rules table bumps it.
+    pub fn version(conn: &Connection) -> Result<i64> {
+        let version: i64 = conn.query_row(
+            "SELECT version FROM rules_version WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(version)
+    }
+
+    /// Get all rules together with the version they were read at.
+    pub fn get_all_versioned(conn: &Connection) -> Result<(Vec<Rule>, i64)> {
+        let tx = conn.unchecked_transaction()?;
+        let version = Self::version(&tx)?;
+        let rules = Self::get_all(&tx)?;
+        tx.commit()?;
+        Ok((rules, version))
+    }
+
+    /// Apply `updates` only if the rules are still at `expected_version`.
+    ///
+    /// Holds the write lock from the version check to the commit, so
+    /// concurrent writers can't interleave. Updates to missing IDs are
+    /// skipped. Returns the number of rules updated and the new version.
+    pub fn update_if_version(
+        conn: &Connection,
+        expected_version: i64,
+        updates: Vec<(i64, NewRule)>,
+    ) -> Result<(usize, i64)> {
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+
+        let current = Self::version(&tx)?;
+        if current != expected_version {
+            return Err(StorageError::VersionConflict {
+                expected: expected_version,
+                current,
+            });
+        }
+
+        let mut updated = 0;
+        for (id, rule) in updates {
+            match Self::update(&tx, id, rule) {
+                Ok(()) => updated += 1,
+                Err(StorageError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let version = Self::version(&tx)?;
+        tx.commit()?;
+        Ok((updated, version))
+    }
+
     /// Count total rules.
     pub fn count(conn: &Connection) -> Result<i64> {
         let count: i64 = conn.query_row("SELECT COUNT(*) FROM rules", [], |row| row.get(0))?;
@@ -353,4 +406,51 @@ mod tests {
         assert_eq!(rules[0].name, "high");
         assert_eq!(rules[1].name, "low");
     }
+
+    fn rule_named(name: &str) -> NewRule {
+        NewRule {
+            name: name.to_string(),
+            enabled: true,
+            config: json!({}),
+            priority: 0,
+        }
+    }
+
+    #[test]
+    fn test_writes_bump_version() {
+        let conn = setup_db();
+        let initial = RulesRepo::version(&conn).unwrap();
+
+        let id = RulesRepo::insert(&conn, rule_named("a")).unwrap();
+        let after_insert = RulesRepo::version(&conn).unwrap();
+        assert!(after_insert > initial);
+
+        RulesRepo::set_enabled(&conn, id, false).unwrap();
+        assert!(RulesRepo::version(&conn).unwrap() > after_insert);
+    }
+
+    #[test]
+    fn test_update_if_version() {
+        let conn = setup_db();
+        let id = RulesRepo::insert(&conn, rule_named("a")).unwrap();
+        let (_, version) = RulesRepo::get_all_versioned(&conn).unwrap();
+
+        let (updated, new_version) = RulesRepo::update_if_version(
+            &conn,
+            version,
+            vec![(id, rule_named("b")), (9999, rule_named("missing"))],
+        )
+        .unwrap();
+        assert_eq!(updated, 1);
+        assert_eq!(new_version, RulesRepo::version(&conn).unwrap());
+        assert_ne!(new_version, version);
+
+        let stale = RulesRepo::update_if_version(&conn, version, vec![(id, rule_named("c"))]);
+        assert!(matches!(
+            stale,
+            Err(StorageError::VersionConflict { expected, current })
+                if expected == version && current == new_version
+        ));
+        assert_eq!(RulesRepo::get_by_id(&conn, id).unwrap().unwrap().name, "b");
+    }
 }
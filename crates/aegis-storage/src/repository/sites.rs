@@ -13,8 +13,9 @@ impl SiteRepo {
     /// Insert a new site.
     pub fn insert(conn: &Connection, site: NewSite) -> Result<i64> {
         conn.execute(
-            "INSERT INTO sites (pattern, name, category, parser_id, enabled, source, priority)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            "INSERT INTO sites (pattern, name, category, parser_id, enabled, source, priority,
+                                service_name, endpoints)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             params![
                 site.pattern,
                 site.name,
@@ -22,7 +23,9 @@ impl SiteRepo {
                 site.parser_id,
                 site.enabled as i32,
                 site.source,
-                site.priority
+                site.priority,
+                site.service_name,
+                endpoints_json(&site.endpoints)
             ],
         )?;
 
@@ -32,7 +35,8 @@ impl SiteRepo {
     /// Get a site by ID.
     pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<Site>> {
         let mut stmt = conn.prepare(
-            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at
+            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at,
+                    service_name, endpoints
              FROM sites WHERE id = ?1",
         )?;
 
@@ -49,6 +53,8 @@ impl SiteRepo {
                     priority: row.get(7)?,
                     created_at: parse_datetime(&row.get::<_, String>(8)?),
                     updated_at: parse_datetime(&row.get::<_, String>(9)?),
+                    service_name: row.get(10)?,
+                    endpoints: parse_endpoints(&row.get::<_, String>(11)?),
                 })
             })
             .ok();
@@ -59,7 +65,8 @@ impl SiteRepo {
     /// Get a site by pattern.
     pub fn get_by_pattern(conn: &Connection, pattern: &str) -> Result<Option<Site>> {
         let mut stmt = conn.prepare(
-            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at
+            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at,
+                    service_name, endpoints
              FROM sites WHERE pattern = ?1",
         )?;
 
@@ -76,6 +83,8 @@ impl SiteRepo {
                     priority: row.get(7)?,
                     created_at: parse_datetime(&row.get::<_, String>(8)?),
                     updated_at: parse_datetime(&row.get::<_, String>(9)?),
+                    service_name: row.get(10)?,
+                    endpoints: parse_endpoints(&row.get::<_, String>(11)?),
                 })
             })
             .ok();
@@ -86,7 +95,8 @@ impl SiteRepo {
     /// Get all sites.
     pub fn get_all(conn: &Connection) -> Result<Vec<Site>> {
         let mut stmt = conn.prepare(
-            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at
+            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at,
+                    service_name, endpoints
              FROM sites ORDER BY priority DESC, name ASC",
         )?;
 
@@ -103,6 +113,8 @@ impl SiteRepo {
                     priority: row.get(7)?,
                     created_at: parse_datetime(&row.get::<_, String>(8)?),
                     updated_at: parse_datetime(&row.get::<_, String>(9)?),
+                    service_name: row.get(10)?,
+                    endpoints: parse_endpoints(&row.get::<_, String>(11)?),
                 })
             })?
             .filter_map(|r| r.ok())
@@ -114,7 +126,8 @@ impl SiteRepo {
     /// Get all enabled sites.
     pub fn get_enabled(conn: &Connection) -> Result<Vec<Site>> {
         let mut stmt = conn.prepare(
-            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at
+            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at,
+                    service_name, endpoints
              FROM sites WHERE enabled = 1 ORDER BY priority DESC, name ASC",
         )?;
 
@@ -131,6 +144,8 @@ impl SiteRepo {
                     priority: row.get(7)?,
                     created_at: parse_datetime(&row.get::<_, String>(8)?),
                     updated_at: parse_datetime(&row.get::<_, String>(9)?),
+                    service_name: row.get(10)?,
+                    endpoints: parse_endpoints(&row.get::<_, String>(11)?),
                 })
             })?
             .filter_map(|r| r.ok())
@@ -142,7 +157,8 @@ impl SiteRepo {
     /// Get sites by source.
     pub fn get_by_source(conn: &Connection, source: &str) -> Result<Vec<Site>> {
         let mut stmt = conn.prepare(
-            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at
+            "SELECT id, pattern, name, category, parser_id, enabled, source, priority, created_at, updated_at,
+                    service_name, endpoints
              FROM sites WHERE source = ?1 ORDER BY priority DESC, name ASC",
         )?;
 
@@ -159,6 +175,8 @@ impl SiteRepo {
                     priority: row.get(7)?,
                     created_at: parse_datetime(&row.get::<_, String>(8)?),
                     updated_at: parse_datetime(&row.get::<_, String>(9)?),
+                    service_name: row.get(10)?,
+                    endpoints: parse_endpoints(&row.get::<_, String>(11)?),
                 })
             })?
             .filter_map(|r| r.ok())
@@ -171,7 +189,8 @@ impl SiteRepo {
     pub fn update(conn: &Connection, id: i64, site: NewSite) -> Result<()> {
         let updated = conn.execute(
             "UPDATE sites SET pattern = ?1, name = ?2, category = ?3, parser_id = ?4,
-             enabled = ?5, source = ?6, priority = ?7, updated_at = datetime('now') WHERE id = ?8",
+             enabled = ?5, source = ?6, priority = ?7, service_name = ?8, endpoints = ?9,
+             updated_at = datetime('now') WHERE id = ?10",
             params![
                 site.pattern,
                 site.name,
@@ -180,6 +199,8 @@ impl SiteRepo {
                 site.enabled as i32,
                 site.source,
                 site.priority,
+                site.service_name,
+                endpoints_json(&site.endpoints),
                 id
             ],
         )?;
@@ -361,6 +382,16 @@ fn parse_datetime(s: &str) -> DateTime<Utc> {
         .unwrap_or_else(|_| Utc::now())
 }
 
+/// Parse the stored JSON array of endpoint patterns.
+fn parse_endpoints(s: &str) -> Vec<String> {
+    serde_json::from_str(s).unwrap_or_default()
+}
+
+/// Serialize endpoint patterns for storage.
+fn endpoints_json(endpoints: &[String]) -> String {
+    serde_json::to_string(endpoints).unwrap_or_else(|_| "[]".to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -386,6 +417,8 @@ mod tests {
             enabled: true,
             source: "custom".to_string(),
             priority: 100,
+            service_name: None,
+            endpoints: Vec::new(),
         };
 
         let id = SiteRepo::insert(&conn, site).unwrap();
@@ -400,6 +433,34 @@ mod tests {
         assert_eq!(retrieved.priority, 100);
     }
 
+    #[test]
+    fn test_site_service_name_and_endpoints() {
+        let conn = setup_db();
+
+        let site = NewSite {
+            pattern: "chat.niche-llm.test".to_string(),
+            name: "Niche LLM".to_string(),
+            category: "consumer".to_string(),
+            parser_id: Some("form".to_string()),
+            enabled: true,
+            source: "custom".to_string(),
+            priority: 100,
+            service_name: Some("NicheChat".to_string()),
+            endpoints: vec!["/api/ask".to_string(), "/api/chat/*".to_string()],
+        };
+
+        let id = SiteRepo::insert(&conn, site).unwrap();
+        let retrieved = SiteRepo::get_by_id(&conn, id).unwrap().unwrap();
+        assert_eq!(retrieved.service_name.as_deref(), Some("NicheChat"));
+        assert_eq!(retrieved.endpoints, ["/api/ask", "/api/chat/*"]);
+
+        let entry = retrieved.to_site_entry();
+        assert_eq!(entry.parser_id.as_deref(), Some("form"));
+        assert_eq!(entry.service_name.as_deref(), Some("NicheChat"));
+        assert!(entry.is_prompt_endpoint("/api/ask"));
+        assert!(!entry.is_prompt_endpoint("/api/settings"));
+    }
+
     #[test]
     fn test_get_by_pattern() {
         let conn = setup_db();
@@ -412,6 +473,8 @@ mod tests {
             enabled: true,
             source: "custom".to_string(),
             priority: 50,
+            service_name: None,
+            endpoints: Vec::new(),
         };
 
         SiteRepo::insert(&conn, site).unwrap();
@@ -436,6 +499,8 @@ mod tests {
                 enabled: true,
                 source: "custom".to_string(),
                 priority: 10,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -450,6 +515,8 @@ mod tests {
                 enabled: true,
                 source: "custom".to_string(),
                 priority: 20,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -474,6 +541,8 @@ mod tests {
                 enabled: true,
                 source: "custom".to_string(),
                 priority: 0,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -488,6 +557,8 @@ mod tests {
                 enabled: false,
                 source: "custom".to_string(),
                 priority: 0,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -511,6 +582,8 @@ mod tests {
                 enabled: true,
                 source: "custom".to_string(),
                 priority: 0,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -525,6 +598,8 @@ mod tests {
                 enabled: true,
                 source: "remote".to_string(),
                 priority: 0,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -550,6 +625,8 @@ mod tests {
             enabled: true,
             source: "custom".to_string(),
             priority: 0,
+            service_name: None,
+            endpoints: Vec::new(),
         };
 
         let id = SiteRepo::insert(&conn, site).unwrap();
@@ -565,6 +642,8 @@ mod tests {
                 enabled: false,
                 source: "custom".to_string(),
                 priority: 50,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -589,6 +668,8 @@ mod tests {
             enabled: true,
             source: "custom".to_string(),
             priority: 0,
+            service_name: None,
+            endpoints: Vec::new(),
         };
 
         let id = SiteRepo::insert(&conn, site).unwrap();
@@ -613,6 +694,8 @@ mod tests {
             enabled: true,
             source: "custom".to_string(),
             priority: 0,
+            service_name: None,
+            endpoints: Vec::new(),
         };
 
         SiteRepo::insert(&conn, site).unwrap();
@@ -636,6 +719,8 @@ mod tests {
             enabled: true,
             source: "custom".to_string(),
             priority: 0,
+            service_name: None,
+            endpoints: Vec::new(),
         };
 
         let id = SiteRepo::insert(&conn, site).unwrap();
@@ -657,6 +742,8 @@ mod tests {
             enabled: true,
             source: "custom".to_string(),
             priority: 0,
+            service_name: None,
+            endpoints: Vec::new(),
         };
 
         SiteRepo::insert(&conn, site).unwrap();
@@ -685,6 +772,8 @@ mod tests {
                 enabled: true,
                 source: "custom".to_string(),
                 priority: 0,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -704,6 +793,8 @@ mod tests {
             enabled: true,
             source: "custom".to_string(),
             priority: 0,
+            service_name: None,
+            endpoints: Vec::new(),
         };
 
         SiteRepo::upsert(&conn, site).unwrap();
@@ -728,6 +819,8 @@ mod tests {
                 enabled: true,
                 source: "custom".to_string(),
                 priority: 0,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -743,6 +836,8 @@ mod tests {
                 enabled: false,
                 source: "custom".to_string(),
                 priority: 100,
+                service_name: None,
+                endpoints: Vec::new(),
             },
         )
         .unwrap();
@@ -1,10 +1,45 @@
 //! Daily statistics repository.
 
 use chrono::{NaiveDate, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, Row};
 
 use crate::error::Result;
-use crate::models::{Action, CategoryCounts, DailyStats};
+use crate::models::{Action, DailyStats};
+
+/// Columns selected for a stats row, in [`stats_from_row`] order.
+const STATS_COLUMNS: &str = "date, total_prompts, blocked_count, allowed_count, flagged_count, \
+     warned_count, would_block_count, redacted_count, category_counts";
+
+/// Daily stats column counting an action.
+fn action_column(action: Action) -> &'static str {
+    match action {
+        Action::Allowed => "allowed_count",
+        Action::Blocked => "blocked_count",
+        Action::Flagged => "flagged_count",
+        Action::Warned => "warned_count",
+        Action::WouldBlock => "would_block_count",
+        Action::Redacted => "redacted_count",
+    }
+}
+
+/// Maps a row selected with [`STATS_COLUMNS`].
+fn stats_from_row(row: &Row<'_>) -> rusqlite::Result<DailyStats> {
+    let date_str: String = row.get(0)?;
+    let category_counts_str: String = row.get(8)?;
+
+    Ok(DailyStats {
+        date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
+            .unwrap_or_else(|_| Utc::now().date_naive()),
+        total_prompts: row.get(1)?,
+        blocked_count: row.get(2)?,
+        allowed_count: row.get(3)?,
+        flagged_count: row.get(4)?,
+        warned_count: row.get(5)?,
+        would_block_count: row.get(6)?,
+        redacted_count: row.get(7)?,
+        category_counts: serde_json::from_str(&category_counts_str).unwrap_or_default(),
+    })
+}
 
 /// Repository for daily statistics operations.
 pub struct StatsRepo;
@@ -19,48 +54,25 @@ impl StatsRepo {
             return Ok(stats);
         }
 
-        // Create new stats for the date
+        // Create new stats for the date (counts default to 0)
         conn.execute(
-            "INSERT INTO daily_stats (date, total_prompts, blocked_count, allowed_count, flagged_count, category_counts)
-             VALUES (?1, 0, 0, 0, 0, '{}')",
+            "INSERT INTO daily_stats (date, category_counts) VALUES (?1, '{}')",
             [&date_str],
         )?;
 
-        Ok(DailyStats {
-            date,
-            total_prompts: 0,
-            blocked_count: 0,
-            allowed_count: 0,
-            flagged_count: 0,
-            category_counts: CategoryCounts::default(),
-        })
+        Ok(DailyStats::empty(date))
     }
 
     /// Get stats for a specific date.
     pub fn get_by_date(conn: &Connection, date: NaiveDate) -> Result<Option<DailyStats>> {
         let date_str = date.format("%Y-%m-%d").to_string();
 
-        let mut stmt = conn.prepare(
-            "SELECT date, total_prompts, blocked_count, allowed_count, flagged_count, category_counts
-             FROM daily_stats WHERE date = ?1",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM daily_stats WHERE date = ?1",
+            STATS_COLUMNS
+        ))?;
 
-        let stats = stmt
-            .query_row([&date_str], |row| {
-                let date_str: String = row.get(0)?;
-                let category_counts_str: String = row.get(5)?;
-
-                Ok(DailyStats {
-                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                        .unwrap_or_else(|_| Utc::now().date_naive()),
-                    total_prompts: row.get(1)?,
-                    blocked_count: row.get(2)?,
-                    allowed_count: row.get(3)?,
-                    flagged_count: row.get(4)?,
-                    category_counts: serde_json::from_str(&category_counts_str).unwrap_or_default(),
-                })
-            })
-            .ok();
+        let stats = stmt.query_row([&date_str], stats_from_row).ok();
 
         Ok(stats)
     }
@@ -74,26 +86,13 @@ impl StatsRepo {
         let start_str = start.format("%Y-%m-%d").to_string();
         let end_str = end.format("%Y-%m-%d").to_string();
 
-        let mut stmt = conn.prepare(
-            "SELECT date, total_prompts, blocked_count, allowed_count, flagged_count, category_counts
-             FROM daily_stats WHERE date >= ?1 AND date <= ?2 ORDER BY date ASC",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM daily_stats WHERE date >= ?1 AND date <= ?2 ORDER BY date ASC",
+            STATS_COLUMNS
+        ))?;
 
         let stats = stmt
-            .query_map([&start_str, &end_str], |row| {
-                let date_str: String = row.get(0)?;
-                let category_counts_str: String = row.get(5)?;
-
-                Ok(DailyStats {
-                    date: NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-                        .unwrap_or_else(|_| Utc::now().date_naive()),
-                    total_prompts: row.get(1)?,
-                    blocked_count: row.get(2)?,
-                    allowed_count: row.get(3)?,
-                    flagged_count: row.get(4)?,
-                    category_counts: serde_json::from_str(&category_counts_str).unwrap_or_default(),
-                })
-            })?
+            .query_map([&start_str, &end_str], stats_from_row)?
             .filter_map(|r| r.ok())
             .collect();
 
@@ -112,14 +111,8 @@ impl StatsRepo {
         // Ensure the row exists
         Self::get_or_create(conn, today)?;
 
-        // Determine which action column to increment
-        let action_column = match action {
-            Action::Allowed => "allowed_count",
-            Action::Blocked => "blocked_count",
-            Action::Flagged => "flagged_count",
-        };
-
         // Increment total and action count
+        let action_column = action_column(action);
         conn.execute(
             &format!(
                 "UPDATE daily_stats SET total_prompts = total_prompts + 1, {} = {} + 1 WHERE date = ?1",
@@ -130,14 +123,8 @@ impl StatsRepo {
 
         // If there's a category, update the category counts
         if let Some(cat) = category {
-            let mut stats = Self::get_by_date(conn, today)?.unwrap_or_else(|| DailyStats {
-                date: today,
-                total_prompts: 0,
-                blocked_count: 0,
-                allowed_count: 0,
-                flagged_count: 0,
-                category_counts: CategoryCounts::default(),
-            });
+            let mut stats =
+                Self::get_by_date(conn, today)?.unwrap_or_else(|| DailyStats::empty(today));
 
             stats.category_counts.increment(cat);
 
@@ -158,18 +145,24 @@ impl StatsRepo {
                 COALESCE(SUM(total_prompts), 0),
                 COALESCE(SUM(blocked_count), 0),
                 COALESCE(SUM(allowed_count), 0),
-                COALESCE(SUM(flagged_count), 0)
+                COALESCE(SUM(flagged_count), 0),
+                COALESCE(SUM(warned_count), 0),
+                COALESCE(SUM(would_block_count), 0),
+                COALESCE(SUM(redacted_count), 0)
              FROM daily_stats",
         )?;
 
         let totals = stmt.query_row([], |row| {
             Ok(DailyStats {
-                date: Utc::now().date_naive(),
                 total_prompts: row.get(0)?,
                 blocked_count: row.get(1)?,
                 allowed_count: row.get(2)?,
                 flagged_count: row.get(3)?,
-                category_counts: CategoryCounts::default(), // Would need separate query for this
+                warned_count: row.get(4)?,
+                would_block_count: row.get(5)?,
+                redacted_count: row.get(6)?,
+                // Would need separate query for category counts
+                ..DailyStats::empty(Utc::now().date_naive())
             })
         })?;
 
@@ -254,4 +247,27 @@ mod tests {
         assert_eq!(totals.blocked_count, 2);
         assert_eq!(totals.allowed_count, 1);
     }
+
+    #[test]
+    fn test_each_action_counted_separately() {
+        let conn = setup_db();
+        let today = Utc::now().date_naive();
+
+        // Increment each action a distinct number of times
+        for (i, action) in Action::ALL.into_iter().enumerate() {
+            for _ in 0..=i {
+                StatsRepo::increment(&conn, action, None).unwrap();
+            }
+        }
+
+        let stats = StatsRepo::get_by_date(&conn, today).unwrap().unwrap();
+        let totals = StatsRepo::get_totals(&conn).unwrap();
+        for (i, action) in Action::ALL.into_iter().enumerate() {
+            assert_eq!(stats.action_count(action), i as i64 + 1, "{:?}", action);
+            assert_eq!(totals.action_count(action), i as i64 + 1, "{:?}", action);
+        }
+        assert_eq!(stats.total_prompts, 21);
+        assert_eq!(stats.warned_count, 4);
+        assert_eq!(stats.flagged_count, 3);
+    }
 }
@@ -6,7 +6,7 @@ use tracing::{debug, info};
 use crate::error::Result;
 
 /// Current schema version.
-pub const SCHEMA_VERSION: i32 = 7;
+pub const SCHEMA_VERSION: i32 = 14;
 
 /// Run all pending migrations.
 pub fn run_migrations(conn: &Connection) -> Result<()> {
@@ -46,6 +46,34 @@ pub fn run_migrations(conn: &Connection) -> Result<()> {
             migrate_v7(conn)?;
         }
 
+        if current_version < 8 {
+            migrate_v8(conn)?;
+        }
+
+        if current_version < 9 {
+            migrate_v9(conn)?;
+        }
+
+        if current_version < 10 {
+            migrate_v10(conn)?;
+        }
+
+        if current_version < 11 {
+            migrate_v11(conn)?;
+        }
+
+        if current_version < 12 {
+            migrate_v12(conn)?;
+        }
+
+        if current_version < 13 {
+            migrate_v13(conn)?;
+        }
+
+        if current_version < 14 {
+            migrate_v14(conn)?;
+        }
+
         set_schema_version(conn, SCHEMA_VERSION)?;
         info!("Database migration complete");
     } else {
@@ -398,6 +426,147 @@ fn migrate_v7(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+/// Migration to version 8: Explicit proxy mode per profile.
+fn migrate_v8(conn: &Connection) -> Result<()> {
+    debug!("Applying migration v8: Explicit proxy mode per profile");
+
+    if !column_exists(conn, "profiles", "proxy_mode") {
+        conn.execute(
+            "ALTER TABLE profiles ADD COLUMN proxy_mode TEXT NOT NULL DEFAULT 'enabled'",
+            [],
+        )?;
+
+        // Proxy mode used to be inferred from the name; keep existing
+        // "parent" profiles unfiltered
+        conn.execute(
+            "UPDATE profiles SET proxy_mode = 'disabled' WHERE LOWER(name) LIKE '%parent%'",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Migration to version 9: Independent text filtering toggle per profile.
+fn migrate_v9(conn: &Connection) -> Result<()> {
+    debug!("Applying migration v9: Text filtering toggle per profile");
+
+    if !column_exists(conn, "profiles", "text_filtering_enabled") {
+        conn.execute(
+            "ALTER TABLE profiles ADD COLUMN text_filtering_enabled INTEGER NOT NULL DEFAULT 1",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Migration to version 10: Separate counts for warned, would-block, and redacted prompts.
+fn migrate_v10(conn: &Connection) -> Result<()> {
+    debug!("Applying migration v10: Warned, would-block, and redacted stats");
+
+    // Warn decisions were stored as flagged until now; move them over once,
+    // when the warned column is first added
+    let move_warns = !column_exists(conn, "daily_stats", "warned_count");
+
+    for column in ["warned_count", "would_block_count", "redacted_count"] {
+        if !column_exists(conn, "daily_stats", column) {
+            conn.execute(
+                &format!(
+                    "ALTER TABLE daily_stats ADD COLUMN {} INTEGER NOT NULL DEFAULT 0",
+                    column
+                ),
+                [],
+            )?;
+        }
+    }
+
+    if move_warns {
+        conn.execute_batch(
+            "UPDATE events SET action = 'warned' WHERE action = 'flagged';
+             UPDATE daily_stats SET warned_count = flagged_count, flagged_count = 0;",
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Migration to version 11: Rules version for optimistic concurrency.
+///
+/// Triggers bump the version on every write to `rules`, so writers that
+/// bypass the versioned API still invalidate stale readers.
+fn migrate_v11(conn: &Connection) -> Result<()> {
+    debug!("Applying migration v11: Rules version");
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS rules_version (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            version INTEGER NOT NULL
+        );
+        INSERT OR IGNORE INTO rules_version (id, version) VALUES (1, 1);
+
+        CREATE TRIGGER IF NOT EXISTS rules_version_insert AFTER INSERT ON rules
+        BEGIN
+            UPDATE rules_version SET version = version + 1 WHERE id = 1;
+        END;
+        CREATE TRIGGER IF NOT EXISTS rules_version_update AFTER UPDATE ON rules
+        BEGIN
+            UPDATE rules_version SET version = version + 1 WHERE id = 1;
+        END;
+        CREATE TRIGGER IF NOT EXISTS rules_version_delete AFTER DELETE ON rules
+        BEGIN
+            UPDATE rules_version SET version = version + 1 WHERE id = 1;
+        END;",
+    )?;
+
+    Ok(())
+}
+
+/// Migration to version 12: Occurrence counts for flagged events.
+///
+/// Once a profile reaches its daily flag cap, repeats are counted on the
+/// latest event of the same type instead of adding rows.
+fn migrate_v12(conn: &Connection) -> Result<()> {
+    debug!("Applying migration v12: Flagged event occurrences");
+
+    if !column_exists(conn, "flagged_events", "occurrences") {
+        conn.execute(
+            "ALTER TABLE flagged_events ADD COLUMN occurrences INTEGER NOT NULL DEFAULT 1",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Migration to version 13: Requested model on events.
+fn migrate_v13(conn: &Connection) -> Result<()> {
+    debug!("Applying migration v13: Requested model on events");
+
+    if !column_exists(conn, "events", "model") {
+        conn.execute("ALTER TABLE events ADD COLUMN model TEXT", [])?;
+    }
+
+    Ok(())
+}
+
+/// Migration to version 14: Service names and prompt endpoints for sites.
+fn migrate_v14(conn: &Connection) -> Result<()> {
+    debug!("Applying migration v14: Site service names and endpoints");
+
+    if !column_exists(conn, "sites", "service_name") {
+        conn.execute("ALTER TABLE sites ADD COLUMN service_name TEXT", [])?;
+    }
+    if !column_exists(conn, "sites", "endpoints") {
+        conn.execute(
+            "ALTER TABLE sites ADD COLUMN endpoints TEXT NOT NULL DEFAULT '[]'",
+            [],
+        )?;
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -477,4 +646,78 @@ mod tests {
             .unwrap();
         assert_eq!(count, 1);
     }
+
+    #[test]
+    fn test_v10_migration_moves_flagged_warns_to_warned() {
+        let conn = Connection::open_in_memory().unwrap();
+        get_schema_version(&conn).unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        migrate_v8(&conn).unwrap();
+        migrate_v9(&conn).unwrap();
+        set_schema_version(&conn, 9).unwrap();
+
+        conn.execute_batch(
+            "INSERT INTO events (prompt_hash, preview, action) VALUES ('h1', 'p1', 'flagged'), ('h2', 'p2', 'blocked');
+             INSERT INTO daily_stats (date, total_prompts, blocked_count, flagged_count) VALUES ('2026-01-01', 5, 1, 3);",
+        )
+        .unwrap();
+
+        run_migrations(&conn).unwrap();
+
+        let flagged_events: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM events WHERE action = 'flagged'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(flagged_events, 0);
+        let counts: (i64, i64, i64) = conn
+            .query_row(
+                "SELECT blocked_count, flagged_count, warned_count FROM daily_stats",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(counts, (1, 0, 3));
+    }
+
+    #[test]
+    fn test_v8_migration_keeps_parent_profiles_unfiltered() {
+        let conn = Connection::open_in_memory().unwrap();
+        get_schema_version(&conn).unwrap();
+        migrate_v1(&conn).unwrap();
+        migrate_v2(&conn).unwrap();
+        migrate_v3(&conn).unwrap();
+        migrate_v4(&conn).unwrap();
+        migrate_v5(&conn).unwrap();
+        migrate_v6(&conn).unwrap();
+        migrate_v7(&conn).unwrap();
+        set_schema_version(&conn, 7).unwrap();
+
+        conn.execute(
+            "INSERT INTO profiles (name, time_rules, content_rules) VALUES ('Parent', '{}', '{}'), ('Harper', '{}', '{}')",
+            [],
+        )
+        .unwrap();
+
+        run_migrations(&conn).unwrap();
+
+        let mode = |name: &str| -> String {
+            conn.query_row(
+                "SELECT proxy_mode FROM profiles WHERE name = ?1",
+                [name],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(mode("Parent"), "disabled");
+        assert_eq!(mode("Harper"), "enabled");
+    }
 }
@@ -1,10 +1,13 @@
 //! Tray menu types and building.
 
 use crate::status::TrayStatus;
-use muda::{accelerator::Accelerator, Menu, MenuId, MenuItem, PredefinedMenuItem};
+use muda::{accelerator::Accelerator, Menu, MenuId, MenuItem, PredefinedMenuItem, Submenu};
+
+/// Menu ID prefix for profile switch items.
+const SWITCH_PROFILE_PREFIX: &str = "switch_profile:";
 
 /// Menu action triggered by user interaction.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MenuAction {
     /// Open the parent dashboard.
     Dashboard,
@@ -21,43 +24,58 @@ pub enum MenuAction {
     /// Resume protection.
     Resume,
 
+    /// Block all AI access immediately (emergency override).
+    BlockAll,
+
+    /// Manually switch the active profile (by profile name).
+    SwitchProfile(String),
+
     /// Quit the application.
     Quit,
 }
 
 impl MenuAction {
     /// Returns the menu ID string for this action.
-    pub fn id(&self) -> &'static str {
+    pub fn id(&self) -> String {
         match self {
-            Self::Dashboard => "dashboard",
-            Self::Settings => "settings",
-            Self::Logs => "logs",
-            Self::Pause => "pause",
-            Self::Resume => "resume",
-            Self::Quit => "quit",
+            Self::Dashboard => "dashboard".to_string(),
+            Self::Settings => "settings".to_string(),
+            Self::Logs => "logs".to_string(),
+            Self::Pause => "pause".to_string(),
+            Self::Resume => "resume".to_string(),
+            Self::BlockAll => "block_all".to_string(),
+            Self::SwitchProfile(name) => format!("{}{}", SWITCH_PROFILE_PREFIX, name),
+            Self::Quit => "quit".to_string(),
         }
     }
 
     /// Returns the menu item label for this action.
-    pub fn label(&self) -> &'static str {
+    pub fn label(&self) -> String {
         match self {
-            Self::Dashboard => "Dashboard",
-            Self::Settings => "Settings",
-            Self::Logs => "Activity Logs",
-            Self::Pause => "Pause Protection",
-            Self::Resume => "Resume Protection",
-            Self::Quit => "Quit Aegis",
+            Self::Dashboard => "Dashboard".to_string(),
+            Self::Settings => "Settings".to_string(),
+            Self::Logs => "Activity Logs".to_string(),
+            Self::Pause => "Pause Protection".to_string(),
+            Self::Resume => "Resume Protection".to_string(),
+            Self::BlockAll => "Block All AI Now".to_string(),
+            Self::SwitchProfile(name) => name.clone(),
+            Self::Quit => "Quit Aegis".to_string(),
         }
     }
 
     /// Creates a MenuAction from an ID string.
     pub fn from_id(id: &str) -> Option<Self> {
+        if let Some(name) = id.strip_prefix(SWITCH_PROFILE_PREFIX) {
+            return (!name.is_empty()).then(|| Self::SwitchProfile(name.to_string()));
+        }
+
         match id {
             "dashboard" => Some(Self::Dashboard),
             "settings" => Some(Self::Settings),
             "logs" => Some(Self::Logs),
             "pause" => Some(Self::Pause),
             "resume" => Some(Self::Resume),
+            "block_all" => Some(Self::BlockAll),
             "quit" => Some(Self::Quit),
             _ => None,
         }
@@ -75,6 +93,13 @@ pub struct TrayMenu {
 impl TrayMenu {
     /// Creates a new tray menu with the given status.
     pub fn new(status: TrayStatus) -> crate::Result<Self> {
+        Self::with_profiles(status, &[])
+    }
+
+    /// Creates a new tray menu with a "Switch Profile" submenu listing `profiles`.
+    ///
+    /// The submenu is omitted when `profiles` is empty.
+    pub fn with_profiles(status: TrayStatus, profiles: &[String]) -> crate::Result<Self> {
         let menu = Menu::new();
 
         // Status header (disabled, just for display)
@@ -123,6 +148,13 @@ impl TrayMenu {
             None::<Accelerator>,
         );
 
+        let block_all_item = MenuItem::with_id(
+            MenuId::new(MenuAction::BlockAll.id()),
+            MenuAction::BlockAll.label(),
+            true,
+            None::<Accelerator>,
+        );
+
         let quit_item = MenuItem::with_id(
             MenuId::new(MenuAction::Quit.id()),
             MenuAction::Quit.label(),
@@ -141,12 +173,31 @@ impl TrayMenu {
             .map_err(|e| crate::TrayError::MenuCreation(e.to_string()))?;
         menu.append(&logs_item)
             .map_err(|e| crate::TrayError::MenuCreation(e.to_string()))?;
+        if !profiles.is_empty() {
+            let profiles_menu = Submenu::new("Switch Profile", true);
+            for name in profiles {
+                let action = MenuAction::SwitchProfile(name.clone());
+                let item = MenuItem::with_id(
+                    MenuId::new(action.id()),
+                    action.label(),
+                    true,
+                    None::<Accelerator>,
+                );
+                profiles_menu
+                    .append(&item)
+                    .map_err(|e| crate::TrayError::MenuCreation(e.to_string()))?;
+            }
+            menu.append(&profiles_menu)
+                .map_err(|e| crate::TrayError::MenuCreation(e.to_string()))?;
+        }
         menu.append(&PredefinedMenuItem::separator())
             .map_err(|e| crate::TrayError::MenuCreation(e.to_string()))?;
         menu.append(&pause_item)
             .map_err(|e| crate::TrayError::MenuCreation(e.to_string()))?;
         menu.append(&resume_item)
             .map_err(|e| crate::TrayError::MenuCreation(e.to_string()))?;
+        menu.append(&block_all_item)
+            .map_err(|e| crate::TrayError::MenuCreation(e.to_string()))?;
         menu.append(&PredefinedMenuItem::separator())
             .map_err(|e| crate::TrayError::MenuCreation(e.to_string()))?;
         menu.append(&quit_item)
@@ -192,12 +243,13 @@ mod tests {
             MenuAction::Logs,
             MenuAction::Pause,
             MenuAction::Resume,
+            MenuAction::BlockAll,
             MenuAction::Quit,
         ];
 
         for action in actions {
             let id = action.id();
-            let parsed = MenuAction::from_id(id);
+            let parsed = MenuAction::from_id(&id);
             assert_eq!(parsed, Some(action));
         }
     }
@@ -210,6 +262,7 @@ mod tests {
             MenuAction::Logs,
             MenuAction::Pause,
             MenuAction::Resume,
+            MenuAction::BlockAll,
             MenuAction::Quit,
         ];
 
@@ -217,4 +270,19 @@ mod tests {
             assert!(!action.label().is_empty());
         }
     }
+
+    #[test]
+    fn switch_profile_action_parsing() {
+        let action = MenuAction::SwitchProfile("Alice".to_string());
+        assert_eq!(action.id(), "switch_profile:Alice");
+        assert_eq!(action.label(), "Alice");
+        assert_eq!(MenuAction::from_id(&action.id()), Some(action));
+
+        // Names may contain the separator
+        assert_eq!(
+            MenuAction::from_id("switch_profile:Homework: Math"),
+            Some(MenuAction::SwitchProfile("Homework: Math".to_string()))
+        );
+        assert_eq!(MenuAction::from_id("switch_profile:"), None);
+    }
 }
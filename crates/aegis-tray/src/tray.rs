@@ -30,6 +30,9 @@ pub struct TrayConfig {
 
     /// Initial protection status.
     pub initial_status: TrayStatus,
+
+    /// Profile names offered in the "Switch Profile" submenu.
+    pub profiles: Vec<String>,
 }
 
 impl Default for TrayConfig {
@@ -37,6 +40,7 @@ impl Default for TrayConfig {
         Self {
             app_name: "Aegis",
             initial_status: TrayStatus::Protected,
+            profiles: Vec::new(),
         }
     }
 }
@@ -58,6 +62,12 @@ impl TrayConfig {
         self.initial_status = status;
         self
     }
+
+    /// Sets the profiles offered for manual switching.
+    pub fn with_profiles(mut self, profiles: Vec<String>) -> Self {
+        self.profiles = profiles;
+        self
+    }
 }
 
 /// System tray manager.
@@ -80,8 +90,7 @@ pub struct SystemTray {
     /// Whether the tray is running.
     running: Arc<AtomicBool>,
 
-    /// Configuration (for future use).
-    #[allow(dead_code)]
+    /// Tray configuration.
     config: TrayConfig,
 }
 
@@ -112,7 +121,7 @@ impl SystemTray {
     /// This must be called from the main thread on some platforms.
     pub fn init(&mut self) -> crate::Result<()> {
         // Create the menu
-        let menu = TrayMenu::new(self.status)?;
+        let menu = TrayMenu::with_profiles(self.status, &self.config.profiles)?;
 
         // Create the icon
         let icon = TrayIcon::for_status(self.status)?;
@@ -145,7 +154,7 @@ impl SystemTray {
         if let Ok(event) = MenuEvent::receiver().try_recv() {
             let id_str = event.id.0.as_str();
             if let Some(action) = MenuAction::from_id(id_str) {
-                events.push(TrayEvent::MenuAction(action));
+                events.push(TrayEvent::MenuAction(action.clone()));
                 let _ = self.event_tx.send(TrayEvent::MenuAction(action));
             }
         }
@@ -240,6 +249,13 @@ mod tests {
         assert_eq!(config.initial_status, TrayStatus::Error);
     }
 
+    #[test]
+    fn config_with_profiles() {
+        let config = TrayConfig::new().with_profiles(vec!["Alice".to_string()]);
+        assert_eq!(config.profiles, vec!["Alice".to_string()]);
+        assert!(TrayConfig::default().profiles.is_empty());
+    }
+
     #[test]
     fn tray_event_equality() {
         assert_eq!(
@@ -47,7 +47,7 @@ pub fn HeroCard() -> Element {
                         div { class: "card-hero-stats",
                             MiniStat { label: "Checked", value: stats.total_prompts, color: "white" }
                             MiniStat { label: "Blocked", value: stats.blocked_count, color: "coral" }
-                            MiniStat { label: "Flagged", value: stats.flagged_count, color: "orange" }
+                            MiniStat { label: "Warned", value: stats.warned_count, color: "orange" }
                             MiniStat { label: "Allowed", value: stats.allowed_count, color: "green" }
                         }
                     }
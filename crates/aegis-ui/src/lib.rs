@@ -25,9 +25,9 @@
 
 use std::sync::Mutex;
 
+use aegis_core::data_dir::project_data_dir;
 use aegis_proxy::FilteringState;
 use dioxus::prelude::*;
-use directories::ProjectDirs;
 
 mod components;
 pub mod error;
@@ -137,7 +137,7 @@ fn load_icon() -> Option<dioxus::desktop::tao::window::Icon> {
 /// This fails in Program Files due to UAC permissions.
 /// We redirect to AppData instead.
 fn get_webview_data_dir() -> Option<std::path::PathBuf> {
-    ProjectDirs::from("", "aegis", "Aegis").map(|dirs| dirs.data_dir().join("webview2"))
+    project_data_dir("", "aegis", "Aegis").map(|dir| dir.join("webview2"))
 }
 
 /// Main application component.
@@ -6,7 +6,9 @@ use std::time::{Duration, Instant};
 use aegis_core::auth::{AuthManager, SessionToken, SESSION_TIMEOUT};
 use aegis_core::classifier::Category;
 use aegis_core::community_rules::ParentOverrides;
+use aegis_core::model_downloader::{self, MlStatus};
 use aegis_core::protection::{PauseDuration, ProtectionManager};
+use aegis_proxy::setup::{self, ProxySetup, SetupResult};
 use aegis_proxy::FilteringState;
 use aegis_storage::{
     DailyStats, Database, Event, FlaggedEvent, FlaggedEventStats,
@@ -116,6 +118,85 @@ pub struct LogFilter {
     pub search: String,
 }
 
+/// Live status of each setup step, checked against the system rather than
+/// remembered from the setup wizard.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetupStatus {
+    /// The CA certificate is trusted by the system.
+    pub ca_installed: bool,
+    /// The system proxy points at Aegis.
+    pub proxy_enabled: bool,
+    /// The image model and runtime are installed.
+    pub model_ready: bool,
+    /// Failure messages from the last setup run.
+    pub last_errors: Vec<String>,
+    /// Whether the checks have run yet.
+    pub checked: bool,
+}
+
+impl SetupStatus {
+    /// Returns true if every step is in place.
+    pub fn is_complete(&self) -> bool {
+        self.ca_installed && self.proxy_enabled && self.model_ready
+    }
+
+    /// Returns display names of the steps that aren't in place.
+    pub fn missing_steps(&self) -> Vec<&'static str> {
+        [
+            (self.ca_installed, "CA certificate"),
+            (self.proxy_enabled, "System proxy"),
+            (self.model_ready, "Image model"),
+        ]
+        .into_iter()
+        .filter(|(done, _)| !done)
+        .map(|(_, name)| name)
+        .collect()
+    }
+}
+
+/// Checks and re-runs setup steps, abstracted so status handling can be tested.
+pub trait SetupProbe {
+    /// Returns true if the CA certificate is trusted.
+    fn ca_installed(&self) -> bool;
+    /// Returns true if the system proxy points at Aegis.
+    fn proxy_enabled(&self) -> bool;
+    /// Returns the image model status.
+    fn ml_status(&self) -> MlStatus;
+    /// Runs CA and proxy setup.
+    fn run_setup(&self) -> Vec<SetupResult>;
+}
+
+/// Checks the real trust store, proxy settings, and model files.
+#[derive(Debug, Clone)]
+pub struct SystemSetupProbe {
+    config: ProxySetup,
+}
+
+impl SystemSetupProbe {
+    /// Creates a probe for the given proxy setup.
+    pub fn new(config: ProxySetup) -> Self {
+        Self { config }
+    }
+}
+
+impl SetupProbe for SystemSetupProbe {
+    fn ca_installed(&self) -> bool {
+        self.config.ca_cert_path.exists() && setup::is_ca_installed(&self.config.ca_cert_path)
+    }
+
+    fn proxy_enabled(&self) -> bool {
+        setup::is_proxy_enabled(&self.config.host, self.config.port)
+    }
+
+    fn ml_status(&self) -> MlStatus {
+        model_downloader::get_ml_status()
+    }
+
+    fn run_setup(&self) -> Vec<SetupResult> {
+        setup::setup_proxy(&self.config)
+    }
+}
+
 /// Application state for the dashboard.
 /// This state is shared via Dioxus context and must be Clone.
 #[derive(Clone)]
@@ -202,6 +283,9 @@ pub struct AppState {
 
     /// Currently detected active profile ID (based on OS username).
     pub active_profile_id: Option<i64>,
+
+    /// Live setup status for the Settings view.
+    pub setup_status: SetupStatus,
 }
 
 impl AppState {
@@ -276,6 +360,7 @@ impl AppState {
             filtering_state,
             parent_overrides: ParentOverrides::new(),
             active_profile_id: None,
+            setup_status: SetupStatus::default(),
         }
     }
 
@@ -312,19 +397,15 @@ impl AppState {
 
     /// Loads parent overrides from the database.
     pub fn load_parent_overrides(&mut self) -> Result<()> {
-        if let Ok(Some(config)) = self.db.get_config("parent_overrides") {
-            if let Ok(overrides) = serde_json::from_value(config.value) {
-                self.parent_overrides = overrides;
-            }
+        if let Ok(overrides) = self.db.parent_overrides() {
+            self.parent_overrides = overrides;
         }
         Ok(())
     }
 
     /// Saves parent overrides to the database.
     pub fn save_parent_overrides(&self) -> Result<()> {
-        let json = serde_json::to_value(&self.parent_overrides)
-            .map_err(|e| UiError::InvalidInput(e.to_string()))?;
-        self.db.set_config("parent_overrides", &json)?;
+        self.db.set_parent_overrides(&self.parent_overrides)?;
         Ok(())
     }
 
@@ -803,6 +884,41 @@ impl AppState {
         Ok(())
     }
 
+    // ==================== Setup Status ====================
+
+    /// Re-checks each setup step against the system.
+    pub fn refresh_setup_status(&mut self, probe: &impl SetupProbe) {
+        self.setup_status.ca_installed = probe.ca_installed();
+        self.setup_status.proxy_enabled = probe.proxy_enabled();
+        self.setup_status.model_ready = probe.ml_status().is_ready();
+        self.setup_status.checked = true;
+    }
+
+    /// Runs CA and proxy setup again, then re-checks the status.
+    ///
+    /// Step results can report success for changes the system didn't keep,
+    /// so the status comes from the checks afterwards, not the results.
+    pub fn rerun_setup(&mut self, probe: &impl SetupProbe) {
+        let results = probe.run_setup();
+        self.setup_status.last_errors = results
+            .iter()
+            .filter(|r| !r.success)
+            .map(|r| r.message.clone())
+            .collect();
+        self.refresh_setup_status(probe);
+
+        if let Some(error) = self.setup_status.last_errors.first() {
+            self.set_error(format!("Setup failed: {}", error));
+        } else if self.setup_status.ca_installed && self.setup_status.proxy_enabled {
+            self.set_success("Setup completed");
+        } else {
+            self.set_error(format!(
+                "Setup reported success but is incomplete: {}",
+                self.setup_status.missing_steps().join(", ")
+            ));
+        }
+    }
+
     // ==================== Message Methods ====================
 
     /// Clears message after display.
@@ -881,6 +997,116 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// Setup probe with fixed answers; `run_setup` applies `fixes_proxy`.
+    struct MockProbe {
+        ca_installed: bool,
+        proxy_enabled: std::cell::Cell<bool>,
+        ml_status: MlStatus,
+        results: Vec<SetupResult>,
+        fixes_proxy: bool,
+    }
+
+    impl MockProbe {
+        fn new(ca_installed: bool, proxy_enabled: bool, ml_status: MlStatus) -> Self {
+            Self {
+                ca_installed,
+                proxy_enabled: std::cell::Cell::new(proxy_enabled),
+                ml_status,
+                results: Vec::new(),
+                fixes_proxy: false,
+            }
+        }
+    }
+
+    impl SetupProbe for MockProbe {
+        fn ca_installed(&self) -> bool {
+            self.ca_installed
+        }
+
+        fn proxy_enabled(&self) -> bool {
+            self.proxy_enabled.get()
+        }
+
+        fn ml_status(&self) -> MlStatus {
+            self.ml_status.clone()
+        }
+
+        fn run_setup(&self) -> Vec<SetupResult> {
+            if self.fixes_proxy {
+                self.proxy_enabled.set(true);
+            }
+            self.results.clone()
+        }
+    }
+
+    fn result(success: bool, message: &str) -> SetupResult {
+        SetupResult {
+            success,
+            message: message.to_string(),
+            needs_admin: false,
+            rolled_back: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_setup_status_populated_from_checks() {
+        let mut state = AppState::in_memory().unwrap();
+        assert!(!state.setup_status.checked);
+
+        state.refresh_setup_status(&MockProbe::new(true, false, MlStatus::MissingModel));
+
+        assert!(state.setup_status.checked);
+        assert!(state.setup_status.ca_installed);
+        assert!(!state.setup_status.proxy_enabled);
+        assert!(!state.setup_status.model_ready);
+        assert!(!state.setup_status.is_complete());
+        assert_eq!(
+            state.setup_status.missing_steps(),
+            vec!["System proxy", "Image model"]
+        );
+
+        state.refresh_setup_status(&MockProbe::new(true, true, MlStatus::Ready));
+        assert!(state.setup_status.is_complete());
+        assert!(state.setup_status.missing_steps().is_empty());
+    }
+
+    #[test]
+    fn test_rerun_setup_records_failures() {
+        let mut state = AppState::in_memory().unwrap();
+        let mut probe = MockProbe::new(false, false, MlStatus::Ready);
+        probe.results = vec![result(false, "CA install cancelled")];
+
+        state.rerun_setup(&probe);
+
+        assert_eq!(state.setup_status.last_errors, vec!["CA install cancelled"]);
+        assert!(!state.setup_status.ca_installed);
+        assert_eq!(
+            state.error_message.as_deref(),
+            Some("Setup failed: CA install cancelled")
+        );
+    }
+
+    #[test]
+    fn test_rerun_setup_uses_checks_not_results() {
+        let mut state = AppState::in_memory().unwrap();
+
+        // Both steps report success but the proxy setting didn't stick
+        let mut probe = MockProbe::new(true, false, MlStatus::Ready);
+        probe.results = vec![result(true, "CA installed"), result(true, "Proxy enabled")];
+        state.rerun_setup(&probe);
+        assert!(state.setup_status.last_errors.is_empty());
+        assert!(!state.setup_status.proxy_enabled);
+        assert_eq!(
+            state.error_message.as_deref(),
+            Some("Setup reported success but is incomplete: System proxy")
+        );
+
+        probe.fixes_proxy = true;
+        state.rerun_setup(&probe);
+        assert!(state.setup_status.proxy_enabled);
+        assert_eq!(state.success_message.as_deref(), Some("Setup completed"));
+    }
+
     #[test]
     fn test_log_filter_default() {
         let filter = LogFilter::default();
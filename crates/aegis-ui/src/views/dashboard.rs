@@ -60,7 +60,7 @@ pub fn DashboardView() -> Element {
                 }
                 StatCard {
                     label: "Warnings",
-                    value: today_stats.as_ref().map(|s| s.flagged_count).unwrap_or(0),
+                    value: today_stats.as_ref().map(|s| s.warned_count).unwrap_or(0),
                     color: "orange",
                     icon: "alert-triangle"
                 }
@@ -117,6 +117,9 @@ fn ActivityItem(
         aegis_storage::Action::Allowed => ("✓", "allowed"),
         aegis_storage::Action::Blocked => ("✗", "blocked"),
         aegis_storage::Action::Flagged => ("!", "flagged"),
+        aegis_storage::Action::Warned => ("!", "warned"),
+        aegis_storage::Action::WouldBlock => ("?", "would-block"),
+        aegis_storage::Action::Redacted => ("~", "redacted"),
     };
 
     let preview_text = if preview.len() > 60 {
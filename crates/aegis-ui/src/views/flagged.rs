@@ -120,7 +120,11 @@ fn FlaggedItem(
 
             // Content
             div { class: "w-full",
-                p { style: "word-break: break-word;", "{content_snippet}" }
+                if content_snippet.is_empty() {
+                    p { class: "text-muted", "Text not stored for privacy" }
+                } else {
+                    p { style: "word-break: break-word;", "{content_snippet}" }
+                }
                 p { class: "text-sm text-muted mt-sm", "Confidence: {confidence:.0}%" }
             }
 
@@ -165,6 +165,9 @@ pub fn LogsView() -> Element {
                                 "allowed" => Some(aegis_storage::Action::Allowed),
                                 "blocked" => Some(aegis_storage::Action::Blocked),
                                 "flagged" => Some(aegis_storage::Action::Flagged),
+                                "warned" => Some(aegis_storage::Action::Warned),
+                                "would_block" => Some(aegis_storage::Action::WouldBlock),
+                                "redacted" => Some(aegis_storage::Action::Redacted),
                                 _ => None,
                             };
                             action_filter.set(action);
@@ -176,6 +179,9 @@ pub fn LogsView() -> Element {
                         option { value: "allowed", "Allowed" }
                         option { value: "blocked", "Blocked" }
                         option { value: "flagged", "Flagged" }
+                        option { value: "warned", "Warned" }
+                        option { value: "would_block", "Would Block" }
+                        option { value: "redacted", "Redacted" }
                     }
 
                     // Clear filters button
@@ -276,6 +282,9 @@ pub fn LogsView() -> Element {
                                         aegis_storage::Action::Allowed => ("tag-success", "Allowed"),
                                         aegis_storage::Action::Blocked => ("tag-error", "Blocked"),
                                         aegis_storage::Action::Flagged => ("tag-warning", "Flagged"),
+                                        aegis_storage::Action::Warned => ("tag-warning", "Warned"),
+                                        aegis_storage::Action::WouldBlock => ("tag-info", "Would Block"),
+                                        aegis_storage::Action::Redacted => ("tag-info", "Redacted"),
                                     };
                                     let source_str = event.source.clone().unwrap_or_else(|| "-".to_string());
 
@@ -2,6 +2,7 @@
 
 use dioxus::prelude::*;
 
+use aegis_core::profile::ProxyMode;
 use aegis_storage::{NsfwThresholdPreset, ProfileImageFilteringConfig, ProfileSentimentConfig};
 
 use crate::state::{AppState, View};
@@ -537,26 +538,39 @@ fn save_profile(
         Some(os_username().trim().to_string())
     };
 
-    // When editing, preserve existing rules; when creating, use empty rules
-    let (time_rules, content_rules) = if let Some(id) = profile_id() {
-        state
-            .read()
-            .profiles
-            .iter()
-            .find(|p| p.id == id)
-            .map(|p| (p.time_rules.clone(), p.content_rules.clone()))
-            .unwrap_or_else(|| {
-                (
-                    serde_json::json!({"rules": []}),
-                    serde_json::json!({"rules": []}),
-                )
-            })
-    } else {
-        (
-            serde_json::json!({"rules": []}),
-            serde_json::json!({"rules": []}),
-        )
-    };
+    // When editing, preserve existing rules and filter modes; when creating,
+    // use empty rules with filtering enabled
+    let (time_rules, content_rules, proxy_mode, text_filtering_enabled) =
+        if let Some(id) = profile_id() {
+            state
+                .read()
+                .profiles
+                .iter()
+                .find(|p| p.id == id)
+                .map(|p| {
+                    (
+                        p.time_rules.clone(),
+                        p.content_rules.clone(),
+                        p.proxy_mode,
+                        p.text_filtering_enabled,
+                    )
+                })
+                .unwrap_or_else(|| {
+                    (
+                        serde_json::json!({"rules": []}),
+                        serde_json::json!({"rules": []}),
+                        ProxyMode::Enabled,
+                        true,
+                    )
+                })
+        } else {
+            (
+                serde_json::json!({"rules": []}),
+                serde_json::json!({"rules": []}),
+                ProxyMode::Enabled,
+                true,
+            )
+        };
 
     let new_profile = aegis_storage::NewProfile {
         name: name_str,
@@ -566,6 +580,8 @@ fn save_profile(
         enabled: enabled(),
         sentiment_config: sentiment_config(),
         image_filtering_config: image_filtering_config(),
+        proxy_mode,
+        text_filtering_enabled,
     };
 
     let result = if let Some(id) = profile_id() {
@@ -637,7 +637,8 @@ fn ContentRulesTab() -> Element {
                             let mut content_rules = content_rules.clone();
                             let state = state.clone();
                             move |_| {
-                                content_rules.set(create_family_safe_preset());
+                                let preset = with_allowlist_of(create_family_safe_preset(), &content_rules.read());
+                                content_rules.set(preset);
                                 save_content_rules(&state, &content_rules);
                             }
                         },
@@ -649,7 +650,8 @@ fn ContentRulesTab() -> Element {
                             let mut content_rules = content_rules.clone();
                             let state = state.clone();
                             move |_| {
-                                content_rules.set(create_permissive_preset());
+                                let preset = with_allowlist_of(create_permissive_preset(), &content_rules.read());
+                                content_rules.set(preset);
                                 save_content_rules(&state, &content_rules);
                             }
                         },
@@ -1489,6 +1491,8 @@ fn save_time_rules(state: &Signal<AppState>, time_rules: &Signal<TimeRuleSet>) {
         enabled: profile.enabled,
         sentiment_config: profile.sentiment_config,
         image_filtering_config: profile.image_filtering_config,
+        proxy_mode: profile.proxy_mode,
+        text_filtering_enabled: profile.text_filtering_enabled,
     };
 
     if let Err(e) = state_ref.db.update_profile(profile_id, updated_profile) {
@@ -1534,9 +1538,9 @@ fn reload_rules_from_api(profile_id: i64) {
 fn parse_content_rules(json: &serde_json::Value) -> ContentRuleSet {
     // Try to parse as ContentRuleSet directly
     if let Ok(rule_set) = serde_json::from_value::<ContentRuleSet>(json.clone()) {
-        // If successfully parsed but empty, return defaults
+        // If successfully parsed but empty, return defaults (keeping the allowlist)
         if rule_set.rules.is_empty() {
-            return create_full_defaults();
+            return with_allowlist_of(create_full_defaults(), &rule_set);
         }
         return rule_set;
     }
@@ -1545,6 +1549,14 @@ fn parse_content_rules(json: &serde_json::Value) -> ContentRuleSet {
     create_full_defaults()
 }
 
+/// Returns `preset` with the allowlist and category defaults carried over
+/// from `current`.
+fn with_allowlist_of(mut preset: ContentRuleSet, current: &ContentRuleSet) -> ContentRuleSet {
+    preset.allowlist = current.allowlist.clone();
+    preset.default_actions = current.default_actions.clone();
+    preset
+}
+
 /// Creates a full default rule set with all 7 categories (same as family safe).
 fn create_full_defaults() -> ContentRuleSet {
     create_family_safe_preset()
@@ -1604,6 +1616,7 @@ fn create_family_safe_preset() -> ContentRuleSet {
                 0.8,
             ),
         ],
+        ..Default::default()
     }
 }
 
@@ -1661,6 +1674,7 @@ fn create_permissive_preset() -> ContentRuleSet {
                 0.0,
             ),
         ],
+        ..Default::default()
     }
 }
 
@@ -1742,6 +1756,8 @@ fn save_content_rules(state: &Signal<AppState>, content_rules: &Signal<ContentRu
         enabled: profile.enabled,
         sentiment_config: profile.sentiment_config,
         image_filtering_config: profile.image_filtering_config,
+        proxy_mode: profile.proxy_mode,
+        text_filtering_enabled: profile.text_filtering_enabled,
     };
 
     if let Err(e) = state_ref.db.update_profile(profile_id, updated_profile) {
@@ -5,15 +5,17 @@ use std::env;
 use auto_launch::{AutoLaunch, AutoLaunchBuilder};
 use dioxus::prelude::*;
 
-use aegis_core::extension_install::get_extension_path;
+use aegis_core::extension_install::{
+    get_extension_path, repair_extension, verify_extension, ExtensionStatus,
+};
 use aegis_core::model_downloader::{self, MlStatus, ModelDownloader};
 use aegis_proxy::setup::{
     disable_system_proxy, enable_system_proxy, install_ca_certificate, is_ca_installed,
-    is_proxy_enabled, uninstall_ca_certificate,
+    is_proxy_enabled, uninstall_ca_certificate, ProxySetup,
 };
 use aegis_proxy::{CaManager, DEFAULT_PROXY_PORT};
 
-use crate::state::AppState;
+use crate::state::{AppState, SystemSetupProbe};
 
 /// App name for autostart.
 const APP_NAME: &str = "Aegis";
@@ -94,16 +96,30 @@ pub fn SettingsView() -> Element {
         .filter(|p| p.exists());
     let ca_path_display = ca_path.as_ref().map(|p| p.display().to_string());
 
+    // Live setup checks; a CA that hasn't been generated reads as not installed
+    let setup_probe = CaManager::with_default_dir().ok().map(|m| {
+        SystemSetupProbe::new(ProxySetup::new(
+            PROXY_HOST,
+            DEFAULT_PROXY_PORT,
+            m.cert_path(),
+        ))
+    });
+    let mut setup_running = use_signal(|| false);
+
     // Deferred status checks - these are slow system calls
     let mut ca_installed_status = use_signal(|| false);
     let mut proxy_enabled_status = use_signal(|| false);
+    let mut extension_statuses = use_signal(Vec::new);
+    let mut extension_repairing = use_signal(|| false);
 
-    // Clone ca_path for use in the effect closure
+    // Clone ca_path and the setup probe for use in the effect closure
     let ca_path_for_effect = ca_path.clone();
+    let setup_probe_for_effect = setup_probe.clone();
 
     // Load slow system checks asynchronously on mount
     use_effect(move || {
         let ca_path_clone = ca_path_for_effect.clone();
+        let setup_probe_clone = setup_probe_for_effect.clone();
         spawn(async move {
             // Run slow checks in background
             let autostart_enabled = is_autostart_enabled();
@@ -112,6 +128,10 @@ pub fn SettingsView() -> Element {
                 .map(|p| is_ca_installed(p))
                 .unwrap_or(false);
             let proxy_enabled = is_proxy_enabled(PROXY_HOST, DEFAULT_PROXY_PORT);
+            extension_statuses.set(verify_extension());
+            if let Some(ref probe) = setup_probe_clone {
+                state.write().refresh_setup_status(probe);
+            }
 
             // Update signals
             autostart.set(autostart_enabled);
@@ -123,6 +143,17 @@ pub fn SettingsView() -> Element {
 
     let ca_installed = ca_installed_status();
     let proxy_enabled = proxy_enabled_status();
+    let setup_status = state.read().setup_status.clone();
+    let detected_browsers: Vec<_> = extension_statuses()
+        .into_iter()
+        .filter(|s| s.status != ExtensionStatus::BrowserNotFound)
+        .collect();
+    let extension_needs_repair = detected_browsers.iter().any(|s| s.needs_repair());
+    let setup_steps = [
+        ("CA certificate", setup_status.ca_installed),
+        ("System proxy", setup_status.proxy_enabled),
+        ("Image model", setup_status.model_ready),
+    ];
 
     // Show loading indicator while checking system status
     if loading() {
@@ -143,6 +174,48 @@ pub fn SettingsView() -> Element {
         div {
             h1 { class: "text-lg font-bold mb-lg", "Settings" }
 
+            // Setup status section
+            div { class: "card mb-lg",
+                h2 { class: "font-bold mb-md", "Setup Status" }
+                p { class: "text-sm text-muted mb-md", "Checked against the system, not the setup wizard's results." }
+
+                for (label, done) in setup_steps {
+                    div { class: "flex justify-between items-center mb-sm",
+                        span { "{label}" }
+                        if !setup_status.checked {
+                            span { class: "tag", "Unknown" }
+                        } else if done {
+                            span { class: "tag tag-success", "OK" }
+                        } else {
+                            span { class: "tag tag-warning", "Not set up" }
+                        }
+                    }
+                }
+
+                for error in setup_status.last_errors.iter() {
+                    p { class: "text-sm text-danger mb-sm", "{error}" }
+                }
+
+                if let Some(ref probe) = setup_probe {
+                    button {
+                        class: "btn btn-secondary btn-sm mt-sm",
+                        disabled: setup_running(),
+                        onclick: {
+                            let probe = probe.clone();
+                            move |_| {
+                                setup_running.set(true);
+                                state.write().rerun_setup(&probe);
+                                let status = state.read().setup_status.clone();
+                                ca_installed_status.set(status.ca_installed);
+                                proxy_enabled_status.set(status.proxy_enabled);
+                                setup_running.set(false);
+                            }
+                        },
+                        if setup_running() { "Running setup..." } else { "Re-run Setup" }
+                    }
+                }
+            }
+
             // General section
             div { class: "card mb-lg",
                 h2 { class: "font-bold mb-md", "General" }
@@ -647,6 +720,46 @@ pub fn SettingsView() -> Element {
                     "The Aegis browser extension monitors AI chat interactions. Install it manually using Developer Mode."
                 }
 
+                // Registration status per detected browser
+                if !detected_browsers.is_empty() {
+                    div { class: "mb-md",
+                        for browser_status in detected_browsers.iter() {
+                            div { class: "flex justify-between items-center mb-sm",
+                                span { "{browser_status.browser.name()}" }
+                                if browser_status.needs_repair() {
+                                    span { class: "tag tag-warning", "Missing" }
+                                } else {
+                                    span { class: "tag tag-success", "Registered" }
+                                }
+                            }
+                        }
+
+                        if extension_needs_repair {
+                            button {
+                                class: "btn btn-primary btn-sm mt-sm",
+                                disabled: extension_repairing() || ext_path.is_none(),
+                                onclick: {
+                                    let ext_path_clone = ext_path.clone();
+                                    move |_| {
+                                        if let Some(ref path) = ext_path_clone {
+                                            extension_repairing.set(true);
+                                            let result = repair_extension(path);
+                                            extension_statuses.set(verify_extension());
+                                            extension_repairing.set(false);
+                                            if result.success {
+                                                state.write().set_success(&result.message);
+                                            } else {
+                                                state.write().set_error(&result.message);
+                                            }
+                                        }
+                                    }
+                                },
+                                if extension_repairing() { "Repairing..." } else { "Fix Extension" }
+                            }
+                        }
+                    }
+                }
+
                 if let Some(ref path_str) = ext_path_display {
                     // Installation steps (always shown)
                     div { class: "card mb-md", style: "background-color: var(--aegis-slate-900);",
@@ -900,6 +900,8 @@ fn create_profile(
         enabled: true,
         sentiment_config: aegis_storage::ProfileSentimentConfig::default(),
         image_filtering_config: aegis_storage::ProfileImageFilteringConfig::default(),
+        proxy_mode: aegis_core::profile::ProxyMode::Enabled,
+        text_filtering_enabled: true,
     };
 
     match state.read().db.create_profile(new_profile) {
@@ -3,8 +3,8 @@
 use std::collections::VecDeque;
 use std::path::PathBuf;
 
+use aegis_core::data_dir::project_data_dir;
 use dioxus::prelude::*;
-use directories::ProjectDirs;
 
 /// Log level for display.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -103,8 +103,7 @@ impl LogEntry {
 
 /// Find the most recent log file.
 fn find_log_file() -> Option<PathBuf> {
-    let dirs = ProjectDirs::from("", "aegis", "Aegis")?;
-    let log_dir = dirs.data_dir().join("logs");
+    let log_dir = project_data_dir("", "aegis", "Aegis")?.join("logs");
 
     if !log_dir.exists() {
         return None;
@@ -208,9 +207,8 @@ pub fn SystemLogsView() -> Element {
                     button {
                         class: "btn btn-secondary btn-sm",
                         onclick: move |_| {
-                            if let Some(dirs) = ProjectDirs::from("", "aegis", "Aegis") {
-                                let log_dir = dirs.data_dir().join("logs");
-                                let _ = open::that(&log_dir);
+                            if let Some(dir) = project_data_dir("", "aegis", "Aegis") {
+                                let _ = open::that(dir.join("logs"));
                             }
                         },
                         "Open Log Folder"
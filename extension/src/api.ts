@@ -5,16 +5,32 @@
 const API_BASE = 'http://127.0.0.1:48765';
 const API_TIMEOUT = 5000;
 
+/** Header the local API checks for the shared extension token. */
+const TOKEN_HEADER = 'X-Aegis-Extension-Token';
+/** Discovery file the Aegis app writes into the extension directory at install. */
+const DISCOVERY_FILE = 'aegis-connection.json';
+/** Storage key the token is cached under for content scripts. */
+const TOKEN_STORAGE_KEY = 'extensionToken';
+
 export interface CategoryMatch {
   category: string;
   confidence: number;
   tier: string;
 }
 
+/** Why a category matched; `matched_term` is set for keyword matches only. */
+export interface Explanation {
+  category: string;
+  tier: string;
+  confidence: number;
+  matched_term?: string;
+}
+
 export interface CheckResponse {
   action: 'allow' | 'warn' | 'block';
   reason: string;
   categories: CategoryMatch[];
+  explanations: Explanation[];
   latency_ms: number;
 }
 
@@ -30,6 +46,38 @@ export interface StatusResponse {
   error?: string;
 }
 
+/**
+ * Read the token from the discovery file and cache it in extension storage.
+ *
+ * Only extension pages (the background worker) can read the file without
+ * exposing it to web pages, so content scripts get it from storage.
+ */
+export async function loadExtensionToken(): Promise<string | null> {
+  try {
+    const response = await fetch(chrome.runtime.getURL(DISCOVERY_FILE));
+    if (!response.ok) {
+      return null;
+    }
+    const discovery: { token?: string } = await response.json();
+    if (!discovery.token) {
+      return null;
+    }
+    await chrome.storage.local.set({ [TOKEN_STORAGE_KEY]: discovery.token });
+    return discovery.token;
+  } catch {
+    // Not installed by the Aegis app (e.g., loaded unpacked for development)
+    return null;
+  }
+}
+
+/**
+ * Get the cached extension token, if the background worker has loaded one.
+ */
+async function getExtensionToken(): Promise<string | null> {
+  const stored = await chrome.storage.local.get(TOKEN_STORAGE_KEY);
+  return stored[TOKEN_STORAGE_KEY] ?? null;
+}
+
 /**
  * Check a prompt against the Aegis safety filter.
  */
@@ -43,11 +91,17 @@ export async function checkPrompt(prompt: string, osUsername?: string): Promise<
       body.os_username = osUsername;
     }
 
+    const headers: Record<string, string> = {
+      'Content-Type': 'application/json',
+    };
+    const token = await getExtensionToken();
+    if (token) {
+      headers[TOKEN_HEADER] = token;
+    }
+
     const response = await fetch(`${API_BASE}/api/check`, {
       method: 'POST',
-      headers: {
-        'Content-Type': 'application/json',
-      },
+      headers,
       body: JSON.stringify(body),
       signal: controller.signal,
     });
@@ -3,7 +3,7 @@
  * Manages connection to local Aegis service with automatic reconnection.
  */
 
-import { checkServiceStatus, type ServiceStatus } from './api.js';
+import { checkServiceStatus, loadExtensionToken, type ServiceStatus } from './api.js';
 
 interface State {
   status: ServiceStatus;
@@ -193,6 +193,11 @@ chrome.tabs.onRemoved.addListener((tabId) => {
  * Initialize the background worker.
  */
 async function initialize(): Promise<void> {
+  // Pick up the API token the Aegis app installed alongside the extension
+  if (!(await loadExtensionToken())) {
+    console.warn('[Aegis] No extension token found; the Aegis app will reject prompt checks');
+  }
+
   // Initial status check
   await checkStatus();
 }